@@ -25,6 +25,7 @@ pub struct AgentDashboard {
     pub total_runs: usize,
     pub successful_runs: usize,
     pub failed_runs: usize,
+    pub budget_exceeded_runs: usize,
     pub success_rate: f32,
     pub active_now: usize,
     pub recent_executions: Vec<ExecutionLogEntry>,
@@ -70,6 +71,7 @@ pub async fn get_agent_dashboard(
                 total_runs: 0,
                 successful_runs: 0,
                 failed_runs: 0,
+                budget_exceeded_runs: 0,
                 success_rate: 0.0,
                 active_now: 0,
                 recent_executions: vec![],
@@ -108,6 +110,7 @@ pub async fn get_agent_dashboard(
             total_runs: 0,
             successful_runs: 0,
             failed_runs: 0,
+            budget_exceeded_runs: 0,
             success_rate: 0.0,
             active_now: 0,
         });
@@ -130,7 +133,7 @@ pub async fn get_agent_dashboard(
             agent_name: r.agent_name,
             query: r.query,
             response: r.response,
-            status: format!("{:?}", r.status).to_lowercase(),
+            status: r.status.as_str().to_string(),
             execution_time_ms: r.execution_time_ms,
             steps_count: r.steps_count,
             tools_used: r.tools_used,
@@ -164,6 +167,7 @@ pub async fn get_agent_dashboard(
         total_runs: summary.total_runs,
         successful_runs: summary.successful_runs,
         failed_runs: summary.failed_runs,
+        budget_exceeded_runs: summary.budget_exceeded_runs,
         success_rate: summary.success_rate,
         active_now,
         recent_executions,
@@ -207,6 +211,27 @@ pub async fn get_active_executions(
     Ok(entries)
 }
 
+/// Full step trace of a past execution (timestamps, tools, inputs, outputs)
+#[tauri::command]
+pub async fn get_execution_trace(
+    execution_id: String,
+    rag_state: State<'_, RagState>,
+) -> Result<shodh_rag::agent::ExecutionTrace, String> {
+    let agent_system_guard = rag_state.agent_system.read().await;
+    let agent_system_arc = agent_system_guard
+        .as_ref()
+        .ok_or("Agent system not initialized")?
+        .clone();
+    drop(agent_system_guard);
+
+    let system = agent_system_arc.read().await;
+    system
+        .metrics_collector
+        .get_execution_trace(&execution_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn toggle_agent(
     agent_id: String,
@@ -251,6 +276,10 @@ pub struct FrontendAgentConfig {
     pub timeout_seconds: u64,
     pub auto_use_rag: bool,
     pub rag_top_k: usize,
+    #[serde(default)]
+    pub max_total_tokens: Option<usize>,
+    #[serde(default)]
+    pub max_wall_clock_secs: Option<u64>,
 }
 
 fn parse_capability(s: &str) -> AgentCapability {
@@ -300,6 +329,8 @@ fn frontend_to_backend(def: &FrontendAgentDefinition) -> AgentDefinition {
             timeout_seconds: def.config.timeout_seconds,
             auto_use_rag: def.config.auto_use_rag,
             rag_top_k: def.config.rag_top_k,
+            max_total_tokens: def.config.max_total_tokens,
+            max_wall_clock_secs: def.config.max_wall_clock_secs,
         },
         capabilities: def.capabilities.iter().map(|c| parse_capability(c)).collect(),
         tools: def.tools.iter().map(|t| ToolConfig {
@@ -329,6 +360,8 @@ fn backend_to_frontend(def: &AgentDefinition) -> FrontendAgentDefinition {
             timeout_seconds: def.config.timeout_seconds,
             auto_use_rag: def.config.auto_use_rag,
             rag_top_k: def.config.rag_top_k,
+            max_total_tokens: def.config.max_total_tokens,
+            max_wall_clock_secs: def.config.max_wall_clock_secs,
         },
         capabilities: def.capabilities.iter().map(|c| capability_to_string(c)).collect(),
         tools: def.tools.iter().map(|t| t.tool_id.clone()).collect(),
@@ -518,8 +551,11 @@ pub struct FrontendCrewDefinition {
     pub name: String,
     pub description: String,
     pub agents: Vec<FrontendCrewMember>,
-    pub process: String, // "sequential" | "hierarchical"
+    pub process: String, // "sequential" | "hierarchical" | "parallel" | "consensus"
     pub coordinator_id: Option<String>,
+    /// Revision rounds for "consensus" crews
+    #[serde(default)]
+    pub consensus_rounds: Option<usize>,
     #[serde(default)]
     pub config: FrontendCrewConfig,
 }
@@ -555,6 +591,8 @@ pub struct FrontendCrewExecutionResult {
     pub agent_outputs: Vec<FrontendCrewAgentOutput>,
     pub execution_time_ms: u64,
     pub error: Option<String>,
+    #[serde(default)]
+    pub rounds: Vec<Vec<FrontendCrewAgentOutput>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -575,6 +613,10 @@ fn frontend_crew_to_backend(fc: &FrontendCrewDefinition) -> shodh_rag::agent::Cr
             });
             shodh_rag::agent::CrewProcess::Hierarchical { coordinator_id: coord }
         }
+        "parallel" => shodh_rag::agent::CrewProcess::Parallel,
+        "consensus" => shodh_rag::agent::CrewProcess::Consensus {
+            rounds: fc.consensus_rounds.unwrap_or(2),
+        },
         _ => shodh_rag::agent::CrewProcess::Sequential,
     };
 
@@ -597,10 +639,14 @@ fn frontend_crew_to_backend(fc: &FrontendCrewDefinition) -> shodh_rag::agent::Cr
 }
 
 fn backend_crew_to_frontend(bc: &shodh_rag::agent::CrewDefinition) -> FrontendCrewDefinition {
-    let (process_str, coordinator_id) = match &bc.process {
-        shodh_rag::agent::CrewProcess::Sequential => ("sequential".to_string(), None),
+    let (process_str, coordinator_id, consensus_rounds) = match &bc.process {
+        shodh_rag::agent::CrewProcess::Sequential => ("sequential".to_string(), None, None),
         shodh_rag::agent::CrewProcess::Hierarchical { coordinator_id } => {
-            ("hierarchical".to_string(), Some(coordinator_id.clone()))
+            ("hierarchical".to_string(), Some(coordinator_id.clone()), None)
+        }
+        shodh_rag::agent::CrewProcess::Parallel => ("parallel".to_string(), None, None),
+        shodh_rag::agent::CrewProcess::Consensus { rounds } => {
+            ("consensus".to_string(), None, Some(*rounds))
         }
     };
 
@@ -616,6 +662,7 @@ fn backend_crew_to_frontend(bc: &shodh_rag::agent::CrewDefinition) -> FrontendCr
         }).collect(),
         process: process_str,
         coordinator_id,
+        consensus_rounds,
         config: FrontendCrewConfig {
             timeout_seconds: bc.config.timeout_seconds,
             verbose: bc.config.verbose,
@@ -623,6 +670,20 @@ fn backend_crew_to_frontend(bc: &shodh_rag::agent::CrewDefinition) -> FrontendCr
     }
 }
 
+/// Directory user-created crews are persisted to
+pub fn crews_dir(rag_state: &RagState) -> std::path::PathBuf {
+    rag_state.app_paths.data_dir.join("crews")
+}
+
+/// Write the current crews to disk. Failures are logged, not surfaced:
+/// the in-memory change already succeeded.
+async fn persist_crews(system: &shodh_rag::agent::AgentSystem, rag_state: &RagState) {
+    let dir = crews_dir(rag_state);
+    if let Err(e) = system.save_crews_to_directory(&dir.to_string_lossy()).await {
+        tracing::warn!("Failed to persist crews to {:?}: {}", dir, e);
+    }
+}
+
 /// Create a new crew
 #[tauri::command]
 pub async fn create_crew(
@@ -639,6 +700,7 @@ pub async fn create_crew(
     let backend_crew = frontend_crew_to_backend(&crew);
     let system = agent_system_arc.read().await;
     let crew_id = system.register_crew(backend_crew).await.map_err(|e| e.to_string())?;
+    persist_crews(&system, &rag_state).await;
 
     tracing::info!("Created crew: {} ({})", crew.name, crew_id);
     Ok(crew_id)
@@ -694,6 +756,7 @@ pub async fn delete_crew(
 
     let system = agent_system_arc.read().await;
     system.delete_crew(&crew_id).await.map_err(|e| e.to_string())?;
+    persist_crews(&system, &rag_state).await;
     tracing::info!("Deleted crew: {}", crew_id);
     Ok(())
 }
@@ -730,18 +793,23 @@ pub async fn execute_crew(
         crew_id, result.success, result.execution_time_ms, result.agent_outputs.len()
     );
 
+    let to_frontend = |o: &shodh_rag::agent::CrewAgentOutput| FrontendCrewAgentOutput {
+        agent_id: o.agent_id.clone(),
+        agent_name: o.agent_name.clone(),
+        role: o.role.clone(),
+        output: o.output.clone(),
+        execution_time_ms: o.execution_time_ms,
+        tools_used: o.tools_used.clone(),
+    };
+
     Ok(FrontendCrewExecutionResult {
         success: result.success,
         final_output: result.final_output,
-        agent_outputs: result.agent_outputs.iter().map(|o| FrontendCrewAgentOutput {
-            agent_id: o.agent_id.clone(),
-            agent_name: o.agent_name.clone(),
-            role: o.role.clone(),
-            output: o.output.clone(),
-            execution_time_ms: o.execution_time_ms,
-            tools_used: o.tools_used.clone(),
-        }).collect(),
+        agent_outputs: result.agent_outputs.iter().map(to_frontend).collect(),
         execution_time_ms: result.execution_time_ms,
         error: result.error,
+        rounds: result.rounds.iter()
+            .map(|round| round.iter().map(to_frontend).collect())
+            .collect(),
     })
 }
@@ -2,13 +2,26 @@
 //! Tracks real usage data with persistent storage across restarts.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use tauri::State;
-use chrono::{DateTime, Utc, Duration, Timelike};
+use chrono::{DateTime, Utc, Duration, Timelike, TimeZone};
 use crate::rag_commands::RagState;
 
+/// Raw events older than this are dropped; the rollups keep their totals
+const RAW_RETENTION_DAYS: i64 = 7;
+const HOURLY_RETENTION_DAYS: i64 = 30;
+const DAILY_RETENTION_DAYS: i64 = 400;
+/// Upper bound on buckets returned by one metrics call
+const MAX_BUCKETS: usize = 2_000;
+/// Latency histogram bins grow by 10%, so percentiles are within ~10%
+const LATENCY_BIN_GROWTH: f64 = 1.1;
+/// Distinct failing queries remembered per failure category
+const MAX_FAILING_QUERIES: usize = 50;
+/// Characters of a failing query kept in its label
+const FAILING_QUERY_PREVIEW: usize = 40;
+
 /// Persistent analytics record — saved to disk
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersistentAnalytics {
@@ -16,9 +29,9 @@ pub struct PersistentAnalytics {
     pub total_errors: u64,
     pub total_indexing_ops: u64,
     pub query_log: Vec<QueryEvent>,
-    pub hourly_queries: HashMap<String, u32>,
-    pub hourly_response_times: HashMap<String, Vec<f64>>,
     pub query_counts: HashMap<String, QueryAgg>,
+    #[serde(default)]
+    pub failures: HashMap<FailureCategory, FailureAgg>,
 }
 
 impl Default for PersistentAnalytics {
@@ -28,9 +41,8 @@ impl Default for PersistentAnalytics {
             total_errors: 0,
             total_indexing_ops: 0,
             query_log: Vec::new(),
-            hourly_queries: HashMap::new(),
-            hourly_response_times: HashMap::new(),
             query_counts: HashMap::new(),
+            failures: HashMap::new(),
         }
     }
 }
@@ -42,6 +54,74 @@ pub struct QueryEvent {
     pub duration_ms: f64,
     pub result_count: u32,
     pub success: bool,
+    /// Set for failed queries; events logged before categories existed read as `Other`
+    #[serde(default)]
+    pub failure: Option<FailureCategory>,
+}
+
+/// Why a query failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum FailureCategory {
+    NoResults,
+    LLMTimeout,
+    LLMError,
+    EmbeddingError,
+    ParseError,
+    /// Uncategorized: nothing in the error message points elsewhere
+    #[default]
+    Other,
+}
+
+impl FailureCategory {
+    /// Best guess from an error message, for callers that don't categorize
+    pub fn infer(error: &str) -> Self {
+        let error = error.to_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|n| error.contains(n));
+        if has(&["timed out", "timeout", "deadline exceeded"]) {
+            Self::LLMTimeout
+        } else if has(&["embedding", "embedder"]) {
+            Self::EmbeddingError
+        } else if has(&["no results", "no relevant", "no documents", "nothing found"]) {
+            Self::NoResults
+        } else if has(&["parse", "invalid json", "deserializ", "unexpected token"]) {
+            Self::ParseError
+        } else if has(&["llm", "model", "provider", "api error", "rate limit", "429", "completion"]) {
+            Self::LLMError
+        } else {
+            Self::Other
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FailureAgg {
+    pub count: u64,
+    /// Failing query label (see `failing_query_label`) -> occurrences
+    pub queries: HashMap<String, u32>,
+}
+
+impl FailureAgg {
+    fn record(&mut self, query: &str) {
+        self.count += 1;
+        let label = failing_query_label(query);
+        if !self.queries.contains_key(&label) && self.queries.len() >= MAX_FAILING_QUERIES {
+            // Make room by forgetting the rarest failing query
+            if let Some(rarest) = self.queries.iter().min_by_key(|(_, &n)| n).map(|(k, _)| k.clone()) {
+                self.queries.remove(&rarest);
+            }
+        }
+        *self.queries.entry(label).or_insert(0) += 1;
+    }
+}
+
+/// Truncated preview plus a short content hash, so failing queries can be
+/// grouped without keeping them verbatim
+fn failing_query_label(query: &str) -> String {
+    let normalized = query.trim().to_lowercase();
+    let hash = blake3::hash(normalized.as_bytes()).to_hex();
+    let preview: String = normalized.chars().take(FAILING_QUERY_PREVIEW).collect();
+    let ellipsis = if normalized.chars().count() > FAILING_QUERY_PREVIEW { "…" } else { "" };
+    format!("{}{} #{}", preview, ellipsis, &hash[..8])
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,10 +132,164 @@ pub struct QueryAgg {
     pub last_used: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    Hourly,
+    Daily,
+}
+
+impl Granularity {
+    fn key(self, ts: DateTime<Utc>) -> String {
+        match self {
+            Granularity::Hourly => ts.format("%Y-%m-%d-%H").to_string(),
+            Granularity::Daily => ts.format("%Y-%m-%d").to_string(),
+        }
+    }
+
+    /// Start of the bucket containing `ts`
+    fn floor(self, ts: DateTime<Utc>) -> DateTime<Utc> {
+        let hour = match self {
+            Granularity::Hourly => ts.hour(),
+            Granularity::Daily => 0,
+        };
+        let naive = ts.date_naive().and_hms_opt(hour, 0, 0).unwrap_or_else(|| ts.naive_utc());
+        Utc.from_utc_datetime(&naive)
+    }
+
+    fn step(self) -> Duration {
+        match self {
+            Granularity::Hourly => Duration::hours(1),
+            Granularity::Daily => Duration::days(1),
+        }
+    }
+}
+
+/// Counters for one hour or day
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetricsBucket {
+    pub queries: u32,
+    pub errors: u32,
+    pub total_results: u64,
+    pub latency_sum_ms: f64,
+    /// Successful query latencies, log-scale bin -> count
+    pub latency_histogram: BTreeMap<u32, u32>,
+    pub indexing_ops: u64,
+    pub indexing_time_ms: f64,
+}
+
+impl MetricsBucket {
+    fn record_latency(&mut self, duration_ms: f64) {
+        let bin = if duration_ms <= 1.0 {
+            0
+        } else {
+            (duration_ms.ln() / LATENCY_BIN_GROWTH.ln()).ceil() as u32
+        };
+        *self.latency_histogram.entry(bin).or_insert(0) += 1;
+        self.latency_sum_ms += duration_ms;
+    }
+
+    /// Upper edge of the histogram bin holding the `p`-th percentile
+    fn percentile_ms(&self, p: f64) -> f64 {
+        let total: u64 = self.latency_histogram.values().map(|&c| c as u64).sum();
+        if total == 0 {
+            return 0.0;
+        }
+        let rank = ((p * total as f64).ceil() as u64).max(1);
+        let mut seen = 0u64;
+        for (&bin, &count) in &self.latency_histogram {
+            seen += count as u64;
+            if seen >= rank {
+                return LATENCY_BIN_GROWTH.powi(bin as i32);
+            }
+        }
+        0.0
+    }
+
+    fn latency_samples(&self) -> u64 {
+        self.latency_histogram.values().map(|&c| c as u64).sum()
+    }
+
+    fn avg_latency_ms(&self) -> f64 {
+        let samples = self.latency_samples();
+        if samples > 0 { self.latency_sum_ms / samples as f64 } else { 0.0 }
+    }
+}
+
+/// Hourly and daily rollups, stored apart from the raw event log so pruning
+/// raw events never loses history
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnalyticsRollups {
+    pub hourly: BTreeMap<String, MetricsBucket>,
+    pub daily: BTreeMap<String, MetricsBucket>,
+}
+
+impl AnalyticsRollups {
+    fn update(&mut self, ts: DateTime<Utc>, apply: impl Fn(&mut MetricsBucket)) {
+        apply(self.hourly.entry(Granularity::Hourly.key(ts)).or_default());
+        apply(self.daily.entry(Granularity::Daily.key(ts)).or_default());
+    }
+
+    fn record_query(&mut self, event: &QueryEvent) {
+        self.update(event.timestamp, |bucket| {
+            bucket.queries += 1;
+            if event.success {
+                bucket.total_results += event.result_count as u64;
+                bucket.record_latency(event.duration_ms);
+            } else {
+                bucket.errors += 1;
+            }
+        });
+    }
+
+    fn record_indexing(&mut self, ts: DateTime<Utc>, doc_count: u32, duration_ms: f64) {
+        self.update(ts, |bucket| {
+            bucket.indexing_ops += doc_count as u64;
+            bucket.indexing_time_ms += duration_ms;
+        });
+    }
+
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let hourly_cutoff = Granularity::Hourly.key(now - Duration::days(HOURLY_RETENTION_DAYS));
+        let daily_cutoff = Granularity::Daily.key(now - Duration::days(DAILY_RETENTION_DAYS));
+        self.hourly.retain(|k, _| k.as_str() >= hourly_cutoff.as_str());
+        self.daily.retain(|k, _| k.as_str() >= daily_cutoff.as_str());
+    }
+
+    fn bucket(&self, granularity: Granularity, ts: DateTime<Utc>) -> Option<&MetricsBucket> {
+        let buckets = match granularity {
+            Granularity::Hourly => &self.hourly,
+            Granularity::Daily => &self.daily,
+        };
+        buckets.get(&granularity.key(ts))
+    }
+
+    /// Every bucket from `from` to `to`, empty ones included so charts keep
+    /// an even time axis
+    fn range(
+        &self,
+        granularity: Granularity,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, MetricsBucket)>, String> {
+        let mut start = granularity.floor(from);
+        let mut buckets = Vec::new();
+        while start <= to {
+            if buckets.len() == MAX_BUCKETS {
+                return Err(format!("Range too large: more than {} buckets, use a coarser granularity", MAX_BUCKETS));
+            }
+            buckets.push((start, self.bucket(granularity, start).cloned().unwrap_or_default()));
+            start += granularity.step();
+        }
+        Ok(buckets)
+    }
+}
+
 /// Analytics state held in memory, backed by persistent storage
 #[derive(Debug, Clone)]
 pub struct AnalyticsState {
     pub data: Arc<Mutex<PersistentAnalytics>>,
+    pub rollups: Arc<Mutex<AnalyticsRollups>>,
     pub app_start: Instant,
     pub app_start_utc: DateTime<Utc>,
     pub storage_path: Arc<Mutex<Option<std::path::PathBuf>>>,
@@ -65,6 +299,7 @@ impl Default for AnalyticsState {
     fn default() -> Self {
         Self {
             data: Arc::new(Mutex::new(PersistentAnalytics::default())),
+            rollups: Arc::new(Mutex::new(AnalyticsRollups::default())),
             app_start: Instant::now(),
             app_start_utc: Utc::now(),
             storage_path: Arc::new(Mutex::new(None)),
@@ -74,17 +309,20 @@ impl Default for AnalyticsState {
 
 impl AnalyticsState {
     pub fn load_or_default(path: &std::path::Path) -> Self {
-        let data = if path.exists() {
-            match std::fs::read_to_string(path) {
-                Ok(json) => serde_json::from_str(&json).unwrap_or_default(),
-                Err(_) => PersistentAnalytics::default(),
+        let data: PersistentAnalytics = read_json(path).unwrap_or_default();
+
+        // Rollups arrived after the raw log; seed them from it on first load
+        let rollups = read_json(&rollups_path(path)).unwrap_or_else(|| {
+            let mut rollups = AnalyticsRollups::default();
+            for event in &data.query_log {
+                rollups.record_query(event);
             }
-        } else {
-            PersistentAnalytics::default()
-        };
+            rollups
+        });
 
         Self {
             data: Arc::new(Mutex::new(data)),
+            rollups: Arc::new(Mutex::new(rollups)),
             app_start: Instant::now(),
             app_start_utc: Utc::now(),
             storage_path: Arc::new(Mutex::new(Some(path.to_path_buf()))),
@@ -96,16 +334,41 @@ impl AnalyticsState {
         let path = path_guard.as_ref().and_then(|p| p.as_ref());
         if let Some(path) = path {
             if let Ok(data) = self.data.lock() {
-                if let Ok(json) = serde_json::to_string_pretty(&*data) {
-                    let tmp = path.with_extension("json.tmp");
-                    if std::fs::write(&tmp, &json).is_ok() {
-                        let _ = std::fs::rename(&tmp, path);
-                    }
-                }
+                write_json(path, &*data);
+            }
+            if let Ok(rollups) = self.rollups.lock() {
+                write_json(&rollups_path(path), &*rollups);
             }
         }
     }
 
+    /// Append a query event to the raw log and the rollups, pruning both
+    fn record_query(&self, event: QueryEvent) -> Result<(), String> {
+        let now = event.timestamp;
+        self.rollups.lock().map_err(|e| e.to_string())?.record_query(&event);
+
+        let mut data = self.data.lock().map_err(|e| e.to_string())?;
+        data.total_queries += 1;
+        if !event.success {
+            data.total_errors += 1;
+            let category = event.failure.unwrap_or_default();
+            data.failures.entry(category).or_default().record(&event.query);
+        }
+        // Ring buffer: keep last 2000 events, none older than the retention window
+        if data.query_log.len() > 2000 {
+            data.query_log.drain(0..500);
+        }
+        let cutoff = now - Duration::days(RAW_RETENTION_DAYS);
+        data.query_log.retain(|q| q.timestamp >= cutoff);
+        data.query_log.push(event);
+        drop(data);
+
+        if let Ok(mut rollups) = self.rollups.lock() {
+            rollups.prune(now);
+        }
+        Ok(())
+    }
+
     /// Compute the size of the app data directory in MB
     fn storage_size_mb(&self) -> f64 {
         let path_guard = self.storage_path.lock().ok();
@@ -119,6 +382,24 @@ impl AnalyticsState {
     }
 }
 
+fn rollups_path(analytics_path: &std::path::Path) -> std::path::PathBuf {
+    analytics_path.with_file_name("analytics_rollups.json")
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &std::path::Path) -> Option<T> {
+    let json = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+fn write_json<T: Serialize>(path: &std::path::Path, value: &T) {
+    if let Ok(json) = serde_json::to_string_pretty(value) {
+        let tmp = path.with_extension("json.tmp");
+        if std::fs::write(&tmp, &json).is_ok() {
+            let _ = std::fs::rename(&tmp, path);
+        }
+    }
+}
+
 fn dir_size_bytes(path: &std::path::Path) -> u64 {
     let mut total: u64 = 0;
     if let Ok(entries) = std::fs::read_dir(path) {
@@ -182,6 +463,7 @@ pub async fn get_dashboard_data(
     rag_state: State<'_, RagState>,
 ) -> Result<DashboardData, String> {
     let data = analytics.data.lock().map_err(|e| e.to_string())?.clone();
+    let rollups = analytics.rollups.lock().map_err(|e| e.to_string())?.clone();
 
     let rag_guard = rag_state.rag.read().await;
     let stats = rag_guard.get_statistics().await.unwrap_or_default();
@@ -210,8 +492,7 @@ pub async fn get_dashboard_data(
 
     // Queries in the last hour
     let now = Utc::now();
-    let hour_key = format!("{}", now.format("%Y-%m-%d-%H"));
-    let queries_last_hour = data.hourly_queries.get(&hour_key).copied().unwrap_or(0);
+    let queries_last_hour = rollups.bucket(Granularity::Hourly, now).map(|b| b.queries).unwrap_or(0);
 
     let storage_used_mb = analytics.storage_size_mb();
 
@@ -227,9 +508,10 @@ pub async fn get_dashboard_data(
         queries_last_hour,
     };
 
-    // Build real time series from hourly data (last 24h)
-    let usage_chart = build_hourly_chart(&data.hourly_queries, now);
-    let performance_chart = build_performance_chart(&data.hourly_response_times, now);
+    // Build real time series from hourly rollups (last 24h)
+    let last_day = rollups.range(Granularity::Hourly, now - Duration::hours(23), now)?;
+    let usage_chart = build_hourly_chart(&last_day, |b| b.queries as f64);
+    let performance_chart = build_hourly_chart(&last_day, MetricsBucket::avg_latency_ms);
 
     // Top queries from real aggregated data
     let mut top_queries: Vec<QueryStat> = data.query_counts.iter()
@@ -260,30 +542,20 @@ pub async fn track_query(
     result_count: u32,
 ) -> Result<(), String> {
     let now = Utc::now();
-    let hour_key = format!("{}", now.format("%Y-%m-%d-%H"));
     let query_lower = query.to_lowercase().trim().to_string();
 
+    analytics.record_query(QueryEvent {
+        query,
+        timestamp: now,
+        duration_ms,
+        result_count,
+        success: true,
+        failure: None,
+    })?;
+
     {
         let mut data = analytics.data.lock().map_err(|e| e.to_string())?;
 
-        data.total_queries += 1;
-
-        // Ring buffer: keep last 2000 events
-        if data.query_log.len() > 2000 {
-            data.query_log.drain(0..500);
-        }
-        data.query_log.push(QueryEvent {
-            query: query.clone(),
-            timestamp: now,
-            duration_ms,
-            result_count,
-            success: true,
-        });
-
-        // Hourly buckets
-        *data.hourly_queries.entry(hour_key.clone()).or_insert(0) += 1;
-        data.hourly_response_times.entry(hour_key).or_default().push(duration_ms);
-
         // Query aggregation
         let agg = data.query_counts.entry(query_lower).or_insert(QueryAgg {
             count: 0,
@@ -295,39 +567,31 @@ pub async fn track_query(
         agg.total_time_ms += duration_ms;
         agg.total_results += result_count as u64;
         agg.last_used = now;
-
-        // Prune old hourly data (keep 72h)
-        let cutoff = (now - Duration::hours(72)).format("%Y-%m-%d-%H").to_string();
-        data.hourly_queries.retain(|k, _| k.as_str() >= cutoff.as_str());
-        data.hourly_response_times.retain(|k, _| k.as_str() >= cutoff.as_str());
     }
 
     analytics.save();
     Ok(())
 }
 
+/// Record a failed query. Without `category`, it is inferred from
+/// `error_msg` (see `FailureCategory::infer`).
 #[tauri::command]
 pub async fn track_query_error(
     analytics: State<'_, AnalyticsState>,
     query: String,
-    _error_msg: String,
+    error_msg: String,
+    category: Option<FailureCategory>,
 ) -> Result<(), String> {
-    let now = Utc::now();
-    {
-        let mut data = analytics.data.lock().map_err(|e| e.to_string())?;
-        data.total_queries += 1;
-        data.total_errors += 1;
-        if data.query_log.len() > 2000 {
-            data.query_log.drain(0..500);
-        }
-        data.query_log.push(QueryEvent {
-            query,
-            timestamp: now,
-            duration_ms: 0.0,
-            result_count: 0,
-            success: false,
-        });
-    }
+    let category = category.unwrap_or_else(|| FailureCategory::infer(&error_msg));
+    tracing::debug!("Query failed ({:?}): {}", category, error_msg);
+    analytics.record_query(QueryEvent {
+        query,
+        timestamp: Utc::now(),
+        duration_ms: 0.0,
+        result_count: 0,
+        success: false,
+        failure: Some(category),
+    })?;
     analytics.save();
     Ok(())
 }
@@ -336,21 +600,50 @@ pub async fn track_query_error(
 pub async fn track_indexing(
     analytics: State<'_, AnalyticsState>,
     _doc_type: String,
-    _duration_ms: f64,
+    duration_ms: f64,
     doc_count: u32,
 ) -> Result<(), String> {
     {
         let mut data = analytics.data.lock().map_err(|e| e.to_string())?;
         data.total_indexing_ops += doc_count as u64;
     }
+    {
+        let mut rollups = analytics.rollups.lock().map_err(|e| e.to_string())?;
+        rollups.record_indexing(Utc::now(), doc_count, duration_ms);
+    }
     analytics.save();
     Ok(())
 }
 
+/// Resolve an optional date range; defaults to the last day by hour or the
+/// last 30 days by day
+fn metrics_range(
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    granularity: Option<Granularity>,
+) -> Result<(DateTime<Utc>, DateTime<Utc>, Granularity), String> {
+    let granularity = granularity.unwrap_or(Granularity::Hourly);
+    let to = to.unwrap_or_else(Utc::now);
+    let from = from.unwrap_or_else(|| match granularity {
+        Granularity::Hourly => to - Duration::hours(23),
+        Granularity::Daily => to - Duration::days(29),
+    });
+    if from > to {
+        return Err("Invalid range: 'from' is after 'to'".to_string());
+    }
+    Ok((from, to, granularity))
+}
+
+/// Query latency per bucket: count, mean and p50/p95/p99
 #[tauri::command]
 pub async fn get_performance_metrics(
     analytics: State<'_, AnalyticsState>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    granularity: Option<Granularity>,
 ) -> Result<serde_json::Value, String> {
+    let (from, to, granularity) = metrics_range(from, to, granularity)?;
+    let buckets = analytics.rollups.lock().map_err(|e| e.to_string())?.range(granularity, from, to)?;
     let data = analytics.data.lock().map_err(|e| e.to_string())?;
     let recent: Vec<&QueryEvent> = data.query_log.iter().rev().filter(|q| q.success).take(50).collect();
     let avg_ms = if !recent.is_empty() {
@@ -363,15 +656,46 @@ pub async fn get_performance_metrics(
         "total_queries": data.total_queries,
         "total_errors": data.total_errors,
         "storage_size_mb": analytics.storage_size_mb(),
+        "granularity": granularity,
+        "buckets": buckets.iter().map(|(start, b)| serde_json::json!({
+            "start": start,
+            "queries": b.queries,
+            "avg_ms": b.avg_latency_ms(),
+            "p50_ms": b.percentile_ms(0.50),
+            "p95_ms": b.percentile_ms(0.95),
+            "p99_ms": b.percentile_ms(0.99),
+        })).collect::<Vec<_>>(),
     }))
 }
 
+/// Query, error and indexing volume per bucket
 #[tauri::command]
 pub async fn get_usage_metrics(
     analytics: State<'_, AnalyticsState>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    granularity: Option<Granularity>,
 ) -> Result<serde_json::Value, String> {
+    let (from, to, granularity) = metrics_range(from, to, granularity)?;
+    let buckets = analytics.rollups.lock().map_err(|e| e.to_string())?.range(granularity, from, to)?;
     let data = analytics.data.lock().map_err(|e| e.to_string())?;
-    serde_json::to_value(&*data).map_err(|e| e.to_string())
+    Ok(serde_json::json!({
+        "total_queries": data.total_queries,
+        "total_errors": data.total_errors,
+        "total_indexing_ops": data.total_indexing_ops,
+        "granularity": granularity,
+        "buckets": buckets.iter().map(|(start, b)| {
+            let successes = b.queries.saturating_sub(b.errors);
+            serde_json::json!({
+                "start": start,
+                "queries": b.queries,
+                "errors": b.errors,
+                "avg_results": if successes > 0 { b.total_results as f64 / successes as f64 } else { 0.0 },
+                "indexing_ops": b.indexing_ops,
+                "indexing_time_ms": b.indexing_time_ms,
+            })
+        }).collect::<Vec<_>>(),
+    }))
 }
 
 #[tauri::command]
@@ -386,42 +710,118 @@ pub async fn get_quality_metrics(
         "hit_rate": hit_rate,
         "total_queries": data.total_queries,
         "total_errors": data.total_errors,
+        "failures": failure_breakdown(&data.failures),
     }))
 }
 
 // ─── Helpers ────────────────────────────────────────────────────────────────
 
-fn build_hourly_chart(hourly: &HashMap<String, u32>, now: DateTime<Utc>) -> Vec<TimeSeriesPoint> {
-    let mut points = Vec::with_capacity(24);
-    for i in (0..24).rev() {
-        let ts = now - Duration::hours(i);
-        let key = format!("{}", ts.format("%Y-%m-%d-%H"));
-        let value = hourly.get(&key).copied().unwrap_or(0) as f64;
-        points.push(TimeSeriesPoint {
-            timestamp: ts,
-            value,
-            label: Some(format!("{:02}:00", ts.hour())),
-        });
-    }
-    points
-}
-
-fn build_performance_chart(hourly_times: &HashMap<String, Vec<f64>>, now: DateTime<Utc>) -> Vec<TimeSeriesPoint> {
-    let mut points = Vec::with_capacity(24);
-    for i in (0..24).rev() {
-        let ts = now - Duration::hours(i);
-        let key = format!("{}", ts.format("%Y-%m-%d-%H"));
-        let value = hourly_times.get(&key)
-            .map(|times| {
-                if times.is_empty() { 0.0 }
-                else { times.iter().sum::<f64>() / times.len() as f64 }
+/// Per-category failure counts, share of all failures and the most common
+/// failing queries, largest category first
+fn failure_breakdown(failures: &HashMap<FailureCategory, FailureAgg>) -> Vec<serde_json::Value> {
+    let total: u64 = failures.values().map(|agg| agg.count).sum();
+    let mut categories: Vec<_> = failures.iter().collect();
+    categories.sort_by_key(|(_, agg)| std::cmp::Reverse(agg.count));
+    categories.into_iter()
+        .map(|(category, agg)| {
+            let mut queries: Vec<(&String, &u32)> = agg.queries.iter().collect();
+            queries.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+            serde_json::json!({
+                "category": category,
+                "count": agg.count,
+                "percent": if total > 0 { agg.count as f64 / total as f64 * 100.0 } else { 0.0 },
+                "top_queries": queries.into_iter().take(5).map(|(query, count)| serde_json::json!({
+                    "query": query,
+                    "count": count,
+                })).collect::<Vec<_>>(),
             })
-            .unwrap_or(0.0);
-        points.push(TimeSeriesPoint {
-            timestamp: ts,
-            value,
+        })
+        .collect()
+}
+
+fn build_hourly_chart(
+    buckets: &[(DateTime<Utc>, MetricsBucket)],
+    value: impl Fn(&MetricsBucket) -> f64,
+) -> Vec<TimeSeriesPoint> {
+    buckets.iter()
+        .map(|(ts, bucket)| TimeSeriesPoint {
+            timestamp: *ts,
+            value: value(bucket),
             label: Some(format!("{:02}:00", ts.hour())),
-        });
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(timestamp: DateTime<Utc>, duration_ms: f64) -> QueryEvent {
+        QueryEvent { query: "q".into(), timestamp, duration_ms, result_count: 3, success: true, failure: None }
+    }
+
+    #[test]
+    fn test_rollups_bucket_latency_percentiles() {
+        let start = Utc.with_ymd_and_hms(2025, 3, 1, 10, 15, 0).unwrap();
+        let mut rollups = AnalyticsRollups::default();
+        for i in 1..=100 {
+            rollups.record_query(&event(start, i as f64 * 10.0));
+        }
+        rollups.record_query(&event(start + Duration::hours(2), 50.0));
+
+        let hourly = rollups.range(Granularity::Hourly, start, start + Duration::hours(2)).unwrap();
+        assert_eq!(hourly.iter().map(|(_, b)| b.queries).collect::<Vec<_>>(), vec![100, 0, 1]);
+        assert_eq!(hourly[0].0, Utc.with_ymd_and_hms(2025, 3, 1, 10, 0, 0).unwrap());
+
+        // Bins are 10% wide, so percentiles land within 10% above the true value
+        let bucket = &hourly[0].1;
+        for (p, expected) in [(0.50, 500.0), (0.95, 950.0), (0.99, 990.0)] {
+            let got = bucket.percentile_ms(p);
+            assert!(got >= expected && got <= expected * LATENCY_BIN_GROWTH, "p{}: {}", p, got);
+        }
+        assert!((bucket.avg_latency_ms() - 505.0).abs() < 1e-9);
+
+        let daily = rollups.range(Granularity::Daily, start, start).unwrap();
+        assert_eq!(daily[0].1.queries, 101);
+
+        rollups.prune(start + Duration::days(HOURLY_RETENTION_DAYS + 1));
+        assert!(rollups.hourly.is_empty());
+        assert_eq!(rollups.daily.len(), 1);
+    }
+
+    #[test]
+    fn test_failures_aggregate_by_category_with_legacy_events_as_other() {
+        let legacy: QueryEvent = serde_json::from_str(
+            r#"{"query":"x","timestamp":"2025-03-01T10:00:00Z","duration_ms":0.0,"result_count":0,"success":false}"#,
+        ).unwrap();
+        assert_eq!(legacy.failure.unwrap_or_default(), FailureCategory::Other);
+
+        let mut failures: HashMap<FailureCategory, FailureAgg> = HashMap::new();
+        for query in ["Slow question", "slow question ", "Other slow one"] {
+            failures.entry(FailureCategory::LLMTimeout).or_default().record(query);
+        }
+        failures.entry(FailureCategory::NoResults).or_default().record("nothing matches");
+
+        let breakdown = failure_breakdown(&failures);
+        assert_eq!(breakdown[0]["category"], "LLMTimeout");
+        assert_eq!(breakdown[0]["percent"], 75.0);
+        let top = &breakdown[0]["top_queries"][0];
+        assert_eq!(top["count"], 2);
+        assert!(top["query"].as_str().unwrap().starts_with("slow question #"));
+    }
+
+    #[test]
+    fn test_failure_category_inferred_from_message() {
+        let cases = [
+            ("LLM request timed out after 120s", FailureCategory::LLMTimeout),
+            ("Embedding failed: ONNX session error", FailureCategory::EmbeddingError),
+            ("No relevant documents found for query", FailureCategory::NoResults),
+            ("Failed to parse JSON from https://api.openai.com", FailureCategory::ParseError),
+            ("OpenAI API error (429): rate limit reached", FailureCategory::LLMError),
+            ("Space is locked", FailureCategory::Other),
+        ];
+        for (message, expected) in cases {
+            assert_eq!(FailureCategory::infer(message), expected, "{}", message);
+        }
     }
-    points
 }
@@ -150,6 +150,23 @@ impl ArtifactStore {
             .unwrap_or_default()
     }
 
+    /// Reference existing artifacts from another conversation (e.g. a fork)
+    /// without copying them; unknown ids are ignored
+    pub fn link_artifacts(
+        &mut self,
+        conversation_id: &str,
+        artifact_ids: impl IntoIterator<Item = String>,
+    ) {
+        let linked = self.artifacts_by_conversation
+            .entry(conversation_id.to_string())
+            .or_default();
+        for id in artifact_ids {
+            if self.artifacts.contains_key(&id) && !linked.contains(&id) {
+                linked.push(id);
+            }
+        }
+    }
+
     /// Simple diff calculation (line-based)
     fn calculate_diff(&self, old: &str, new: &str) -> String {
         let old_lines: Vec<&str> = old.lines().collect();
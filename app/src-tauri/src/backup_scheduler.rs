@@ -0,0 +1,239 @@
+//! Scheduled backups: every space is snapshotted into a timestamped folder of
+//! `.tar.zst` space archives under `<data_dir>/backups`, keeping the last N.
+//!
+//! A snapshot is assembled in a `.tmp` folder and renamed into place once
+//! complete, so a crash mid-backup never leaves a partial snapshot among the
+//! finished ones; stale `.tmp` folders are cleared on the next run.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use shodh_rag::space_archive::SpaceArchive;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::rag_commands::RagState;
+
+const SCHEDULE_FILE: &str = "backup_schedule.json";
+pub(crate) const SNAPSHOT_PREFIX: &str = "snapshot_";
+const TMP_SUFFIX: &str = ".tmp";
+/// How often the background task checks whether a backup is due
+const CHECK_INTERVAL_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSchedule {
+    pub enabled: bool,
+    pub interval_hours: u64,
+    /// Snapshots kept after rotation
+    pub keep_last: usize,
+    #[serde(default)]
+    pub last_run: Option<DateTime<Utc>>,
+}
+
+impl Default for BackupSchedule {
+    fn default() -> Self {
+        Self { enabled: false, interval_hours: 24, keep_last: 7, last_run: None }
+    }
+}
+
+impl BackupSchedule {
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.enabled
+            && self.last_run.is_none_or(|last| {
+                now - last >= chrono::Duration::hours(self.interval_hours as i64)
+            })
+    }
+}
+
+pub struct BackupScheduleState {
+    schedule: Mutex<BackupSchedule>,
+    path: PathBuf,
+    /// Set while a snapshot is being written, so runs never overlap
+    running: AtomicBool,
+}
+
+impl BackupScheduleState {
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(SCHEDULE_FILE);
+        let schedule = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self { schedule: Mutex::new(schedule), path, running: AtomicBool::new(false) }
+    }
+
+    fn get(&self) -> BackupSchedule {
+        self.schedule.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    fn update(&self, f: impl FnOnce(&mut BackupSchedule)) -> Result<BackupSchedule, String> {
+        let mut schedule = self.schedule.lock().map_err(|e| e.to_string())?;
+        f(&mut schedule);
+        let json = serde_json::to_string_pretty(&*schedule).map_err(|e| e.to_string())?;
+        fs::write(&self.path, json)
+            .map_err(|e| format!("Failed to save backup schedule: {}", e))?;
+        Ok(schedule.clone())
+    }
+}
+
+/// Snapshot folder names to delete so only the newest `keep` remain.
+/// Names embed the timestamp, so they sort chronologically.
+fn rotation_victims(mut snapshots: Vec<String>, keep: usize) -> Vec<String> {
+    snapshots.sort();
+    let excess = snapshots.len().saturating_sub(keep.max(1));
+    snapshots.truncate(excess);
+    snapshots
+}
+
+/// Write a snapshot of every space and rotate old ones. Returns its path.
+async fn write_snapshot(state: &RagState, keep_last: usize) -> Result<PathBuf, String> {
+    let backup_dir = state.app_paths.data_dir.join("backups");
+    fs::create_dir_all(&backup_dir)
+        .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+
+    // Leftovers of a run that crashed before its rename
+    for entry in fs::read_dir(&backup_dir).map_err(|e| e.to_string())?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with(SNAPSHOT_PREFIX) && name.ends_with(TMP_SUFFIX) {
+            fs::remove_dir_all(entry.path()).ok();
+        }
+    }
+
+    let name = format!("{}{}", SNAPSHOT_PREFIX, Utc::now().format("%Y%m%d_%H%M%S"));
+    let tmp_dir = backup_dir.join(format!("{}{}", name, TMP_SUFFIX));
+    fs::create_dir_all(&tmp_dir)
+        .map_err(|e| format!("Failed to create snapshot folder: {}", e))?;
+
+    let spaces = {
+        let space_manager = state.space_manager.lock().map_err(|e| e.to_string())?;
+        space_manager.get_spaces()?
+    };
+
+    for space in spaces {
+        // Read lock only while exporting; searches keep running alongside
+        let archive = {
+            let rag = state.rag.read().await;
+            let chunks = rag.export_space_chunks(&space.id)
+                .await
+                .map_err(|e| format!("Failed to read space {}: {}", space.id, e))?;
+            SpaceArchive::new(space.clone(), chunks, rag.embedding_model_id(), rag.embeddings().dimension())
+        };
+        let path = tmp_dir.join(format!("{}.tar.zst", space.id));
+        tokio::task::spawn_blocking(move || archive.write(&path))
+            .await
+            .map_err(|e| format!("Backup task failed: {}", e))?
+            .map_err(|e| format!("Failed to write archive for space {}: {:#}", space.id, e))?;
+    }
+
+    let final_dir = backup_dir.join(&name);
+    fs::rename(&tmp_dir, &final_dir)
+        .map_err(|e| format!("Failed to finalize snapshot: {}", e))?;
+
+    let snapshots: Vec<String> = fs::read_dir(&backup_dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|n| n.starts_with(SNAPSHOT_PREFIX) && !n.ends_with(TMP_SUFFIX))
+        .collect();
+    for old in rotation_victims(snapshots, keep_last) {
+        tracing::info!("🗑️ Rotating out backup {}", old);
+        fs::remove_dir_all(backup_dir.join(old)).ok();
+    }
+
+    Ok(final_dir)
+}
+
+/// Run a snapshot unless one is already in progress, and record the run time
+async fn run_backup(app: &AppHandle) -> Result<PathBuf, String> {
+    let schedule_state = app.state::<BackupScheduleState>();
+    if schedule_state.running.swap(true, Ordering::SeqCst) {
+        return Err("A backup is already in progress".to_string());
+    }
+
+    let keep_last = schedule_state.get().keep_last;
+    let result = write_snapshot(&app.state::<RagState>(), keep_last).await;
+    schedule_state.running.store(false, Ordering::SeqCst);
+
+    let path = result?;
+    schedule_state.update(|s| s.last_run = Some(Utc::now()))?;
+    tracing::info!("💾 Backup written to {:?}", path);
+    Ok(path)
+}
+
+/// Background loop that takes a snapshot whenever the schedule is due
+pub fn spawn_backup_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+            let due = app.state::<BackupScheduleState>().get().is_due(Utc::now());
+            if due {
+                if let Err(e) = run_backup(&app).await {
+                    tracing::error!("Scheduled backup failed: {}", e);
+                }
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_backup_schedule(
+    state: State<'_, BackupScheduleState>,
+) -> Result<BackupSchedule, String> {
+    Ok(state.get())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_backup_schedule(
+    state: State<'_, BackupScheduleState>,
+    enabled: bool,
+    interval_hours: u64,
+    keep_last: usize,
+) -> Result<BackupSchedule, String> {
+    if interval_hours == 0 {
+        return Err("Backup interval must be at least 1 hour".to_string());
+    }
+    if keep_last == 0 {
+        return Err("Keep at least one backup".to_string());
+    }
+    state.update(|s| {
+        s.enabled = enabled;
+        s.interval_hours = interval_hours;
+        s.keep_last = keep_last;
+    })
+}
+
+/// Take a snapshot now, outside the schedule
+#[tauri::command]
+pub async fn run_backup_now(app: AppHandle) -> Result<String, String> {
+    run_backup(&app).await.map(|p| p.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_due_and_rotation() {
+        let now = Utc::now();
+        let mut schedule = BackupSchedule { enabled: true, ..Default::default() };
+        assert!(schedule.is_due(now));
+        schedule.last_run = Some(now - chrono::Duration::hours(23));
+        assert!(!schedule.is_due(now));
+        schedule.last_run = Some(now - chrono::Duration::hours(24));
+        assert!(schedule.is_due(now));
+        schedule.enabled = false;
+        assert!(!schedule.is_due(now));
+
+        let names = vec![
+            "snapshot_20260103_000000".to_string(),
+            "snapshot_20260101_000000".to_string(),
+            "snapshot_20260102_000000".to_string(),
+        ];
+        assert_eq!(rotation_victims(names.clone(), 2), vec!["snapshot_20260101_000000"]);
+        assert!(rotation_victims(names, 5).is_empty());
+    }
+}
@@ -0,0 +1,252 @@
+//! Who the WhatsApp / Telegram / Discord bots answer
+//!
+//! Each bot has its own policy: in blocklist mode (the default) everyone is
+//! served except blocked contacts; in allowlist mode only listed contacts
+//! are. Unknown senders in allowlist mode get the configured rejection
+//! message, or nothing when none is set; blocked contacts always get
+//! nothing. Policies are saved next to the other app data and checked before
+//! any RAG or LLM work.
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use tauri::State;
+
+use crate::discord_bot_commands::DiscordBotState;
+use crate::telegram_bot_commands::TelegramBotState;
+use crate::whatsapp_commands::WhatsAppBotState;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessMode {
+    #[default]
+    Blocklist,
+    Allowlist,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessPolicy {
+    #[serde(default)]
+    pub mode: AccessMode,
+    #[serde(default)]
+    pub allowed: BTreeSet<String>,
+    #[serde(default)]
+    pub blocked: BTreeSet<String>,
+    /// Sent to unknown senders in allowlist mode; None means no reply
+    #[serde(default)]
+    pub rejection_message: Option<String>,
+    /// Contacts whose messages may use MCP tools, the calendar and file
+    /// tools. Everyone else only gets search and chat.
+    #[serde(default)]
+    pub tool_users: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessDecision {
+    Serve,
+    /// Do not process; reply with the message if there is one
+    Reject(Option<String>),
+}
+
+/// Canonical form of a phone number, username or id: lowercase, without
+/// `@`/`+` prefixes, WhatsApp's `@c.us`-style suffix, spaces or dashes
+pub fn normalize_contact(raw: &str) -> String {
+    let trimmed = raw.trim().trim_start_matches(['@', '+']);
+    let base = trimmed.split('@').next().unwrap_or(trimmed);
+    base.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+impl AccessPolicy {
+    /// Decide for a sender known by any of `ids` (e.g. user id and username)
+    pub fn decide(&self, ids: &[&str]) -> AccessDecision {
+        let ids: Vec<String> = ids.iter().map(|id| normalize_contact(id)).filter(|id| !id.is_empty()).collect();
+        if ids.iter().any(|id| self.blocked.contains(id)) {
+            return AccessDecision::Reject(None);
+        }
+        match self.mode {
+            AccessMode::Blocklist => AccessDecision::Serve,
+            AccessMode::Allowlist if ids.iter().any(|id| self.allowed.contains(id)) => AccessDecision::Serve,
+            AccessMode::Allowlist => AccessDecision::Reject(
+                self.rejection_message.clone().filter(|m| !m.trim().is_empty()),
+            ),
+        }
+    }
+
+    /// Whether a sender known by any of `ids` may use tools
+    pub fn allows_tools(&self, ids: &[&str]) -> bool {
+        let ids: Vec<String> = ids.iter().map(|id| normalize_contact(id)).filter(|id| !id.is_empty()).collect();
+        !ids.iter().any(|id| self.blocked.contains(id))
+            && ids.iter().any(|id| self.tool_users.contains(id))
+    }
+}
+
+/// A bot's policy, persisted to `path` on every change
+#[derive(Default)]
+pub struct BotAccess {
+    policy: RwLock<AccessPolicy>,
+    path: Option<PathBuf>,
+}
+
+impl BotAccess {
+    pub fn load(path: PathBuf) -> Self {
+        let policy = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self { policy: RwLock::new(policy), path: Some(path) }
+    }
+
+    pub fn check(&self, ids: &[&str]) -> AccessDecision {
+        self.policy.read().map(|p| p.decide(ids)).unwrap_or(AccessDecision::Serve)
+    }
+
+    pub fn allows_tools(&self, ids: &[&str]) -> bool {
+        self.policy.read().map(|p| p.allows_tools(ids)).unwrap_or(false)
+    }
+
+    pub fn snapshot(&self) -> AccessPolicy {
+        self.policy.read().map(|p| p.clone()).unwrap_or_default()
+    }
+
+    pub fn update(&self, change: impl FnOnce(&mut AccessPolicy)) -> Result<AccessPolicy, String> {
+        let mut policy = self.policy.write().map_err(|e| format!("Access policy lock poisoned: {}", e))?;
+        change(&mut policy);
+        if let Some(path) = &self.path {
+            let data = serde_json::to_string_pretty(&*policy)
+                .map_err(|e| format!("Failed to serialize access policy: {}", e))?;
+            std::fs::write(path, data).map_err(|e| format!("Failed to save access policy: {}", e))?;
+        }
+        Ok(policy.clone())
+    }
+}
+
+/// Reply to a refused message on a streaming endpoint: 403 with the
+/// rejection text, or 204 No Content for silence
+pub fn stream_rejection(reply: Option<String>) -> Response {
+    match reply {
+        Some(text) => (StatusCode::FORBIDDEN, text).into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+fn bot_access<'a>(
+    platform: &str,
+    whatsapp: &'a WhatsAppBotState,
+    telegram: &'a TelegramBotState,
+    discord: &'a DiscordBotState,
+) -> Result<&'a Arc<BotAccess>, String> {
+    match platform {
+        "whatsapp" => Ok(&whatsapp.access),
+        "telegram" => Ok(&telegram.access),
+        "discord" => Ok(&discord.access),
+        other => Err(format!("Unknown bot platform: {} (expected whatsapp, telegram or discord)", other)),
+    }
+}
+
+#[tauri::command]
+pub async fn bot_get_access_policy(
+    whatsapp: State<'_, WhatsAppBotState>,
+    telegram: State<'_, TelegramBotState>,
+    discord: State<'_, DiscordBotState>,
+    platform: String,
+) -> Result<AccessPolicy, String> {
+    Ok(bot_access(&platform, &whatsapp, &telegram, &discord)?.snapshot())
+}
+
+/// Switch between "allowlist" and "blocklist", optionally changing the
+/// rejection message (empty string = reply with silence)
+#[tauri::command]
+pub async fn bot_set_access_mode(
+    whatsapp: State<'_, WhatsAppBotState>,
+    telegram: State<'_, TelegramBotState>,
+    discord: State<'_, DiscordBotState>,
+    platform: String,
+    mode: AccessMode,
+    rejection_message: Option<String>,
+) -> Result<AccessPolicy, String> {
+    bot_access(&platform, &whatsapp, &telegram, &discord)?.update(|policy| {
+        policy.mode = mode;
+        if let Some(message) = rejection_message {
+            policy.rejection_message = Some(message).filter(|m| !m.trim().is_empty());
+        }
+    })
+}
+
+/// Add contacts to (`add = true`) or remove them from the "allow", "block"
+/// or "tools" list
+#[tauri::command]
+pub async fn bot_update_access_list(
+    whatsapp: State<'_, WhatsAppBotState>,
+    telegram: State<'_, TelegramBotState>,
+    discord: State<'_, DiscordBotState>,
+    platform: String,
+    list: String,
+    contacts: Vec<String>,
+    add: bool,
+) -> Result<AccessPolicy, String> {
+    let access = bot_access(&platform, &whatsapp, &telegram, &discord)?;
+    if !matches!(list.as_str(), "allow" | "block" | "tools") {
+        return Err(format!("Unknown access list: {} (expected allow, block or tools)", list));
+    }
+    access.update(|policy| {
+        let target = match list.as_str() {
+            "allow" => &mut policy.allowed,
+            "block" => &mut policy.blocked,
+            _ => &mut policy.tool_users,
+        };
+        for contact in contacts.iter().map(|c| normalize_contact(c)).filter(|c| !c.is_empty()) {
+            if add {
+                target.insert(contact);
+            } else {
+                target.remove(&contact);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowlist_and_blocklist_decisions() {
+        let mut policy = AccessPolicy::default();
+        policy.blocked.insert(normalize_contact("+91 98765-43210"));
+        assert_eq!(policy.decide(&["919876543210@c.us"]), AccessDecision::Reject(None));
+        assert_eq!(policy.decide(&["15550001111@c.us"]), AccessDecision::Serve);
+
+        policy.mode = AccessMode::Allowlist;
+        policy.allowed.insert(normalize_contact("@Alice"));
+        assert_eq!(policy.decide(&["42", "alice"]), AccessDecision::Serve);
+        assert_eq!(policy.decide(&["43", "mallory"]), AccessDecision::Reject(None));
+
+        policy.rejection_message = Some("This bot is private.".to_string());
+        assert_eq!(
+            policy.decide(&["mallory"]),
+            AccessDecision::Reject(Some("This bot is private.".to_string()))
+        );
+        // Blocking wins over allowing, and blocked senders get no reply
+        policy.blocked.insert("alice".to_string());
+        assert_eq!(policy.decide(&["alice"]), AccessDecision::Reject(None));
+    }
+
+    #[test]
+    fn test_tools_need_explicit_allowlist() {
+        let mut policy = AccessPolicy::default();
+        assert!(!policy.allows_tools(&["42", "alice"]));
+
+        policy.tool_users.insert(normalize_contact("@Alice"));
+        assert!(policy.allows_tools(&["42", "alice"]));
+        assert!(!policy.allows_tools(&["43", "mallory"]));
+
+        policy.blocked.insert("alice".to_string());
+        assert!(!policy.allows_tools(&["alice"]));
+    }
+}
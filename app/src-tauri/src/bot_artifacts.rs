@@ -0,0 +1,582 @@
+//! Artifacts for the bots, which can only send text and files
+//!
+//! Mermaid diagrams are rendered to PNG with mermaid-cli (`mmdc`, headless
+//! Chromium) when it is on PATH; bar, line, area and pie charts are first
+//! translated to Mermaid `xychart-beta` / `pie`. Code is sent as a code block,
+//! or as a file when it would not fit in one message; tables as aligned
+//! monospace text, or CSV when too wide or long for a phone. Anything that
+//! cannot be rendered falls back to a text summary.
+
+use base64::Engine as _;
+use serde::Serialize;
+use serde_json::Value;
+use std::io::ErrorKind;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::bot_format::{escape_html, telegram_html, whatsapp_text};
+use crate::chat_engine::{Artifact, ArtifactType, MessagePlatform};
+
+/// How long mermaid-cli may take for one diagram
+const RENDER_TIMEOUT: Duration = Duration::from_secs(30);
+/// Widest table line (in characters) still readable on a phone
+const MAX_TABLE_WIDTH: usize = 60;
+const MAX_TABLE_ROWS: usize = 25;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactAttachment {
+    pub file_name: String,
+    pub mime_type: String,
+    /// File contents, base64-encoded
+    pub data: String,
+    /// Text to send with the file
+    pub caption: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum RenderedArtifact {
+    /// Text in the platform's markup, to append to the reply
+    Inline { text: String },
+    Attachment(ArtifactAttachment),
+}
+
+/// Longest message, in characters: 4096 on WhatsApp and Telegram, 2000 on
+/// Discord
+fn message_limit(platform: &MessagePlatform) -> usize {
+    match platform {
+        MessagePlatform::Discord => 2000,
+        _ => 4096,
+    }
+}
+
+/// Longest artifact text sent inline; the reply shares the message with it
+fn inline_limit(platform: &MessagePlatform) -> usize {
+    match platform {
+        MessagePlatform::Discord => 1500,
+        _ => 3000,
+    }
+}
+
+fn slug(title: &str) -> String {
+    let slug: String = title
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() { "artifact".to_string() } else { slug.chars().take(40).collect() }
+}
+
+fn file(title: &str, extension: &str, mime_type: &str, data: &[u8], caption: String) -> ArtifactAttachment {
+    ArtifactAttachment {
+        file_name: format!("{}.{}", slug(title), extension),
+        mime_type: mime_type.to_string(),
+        data: base64::engine::general_purpose::STANDARD.encode(data),
+        caption,
+    }
+}
+
+fn attachment(title: &str, extension: &str, mime_type: &str, data: &[u8], caption: String) -> RenderedArtifact {
+    RenderedArtifact::Attachment(file(title, extension, mime_type, data, caption))
+}
+
+/// The artifact's source as a file, for when its inline text doesn't fit
+fn source_file(artifact: &Artifact) -> ArtifactAttachment {
+    let (extension, mime_type) = match artifact.artifact_type {
+        ArtifactType::Code => (extension_for(artifact.language.as_deref()), "text/plain"),
+        ArtifactType::Mermaid => ("mmd", "text/plain"),
+        ArtifactType::Chart => ("json", "application/json"),
+        ArtifactType::Table | ArtifactType::Markdown => ("md", "text/markdown"),
+        ArtifactType::Html => ("html", "text/html"),
+        ArtifactType::Svg => ("svg", "image/svg+xml"),
+    };
+    file(&artifact.title, extension, mime_type, artifact.content.as_bytes(), artifact.title.clone())
+}
+
+fn extension_for(language: Option<&str>) -> &'static str {
+    match language.unwrap_or("").to_lowercase().as_str() {
+        "rust" | "rs" => "rs",
+        "python" | "py" => "py",
+        "javascript" | "js" => "js",
+        "typescript" | "ts" => "ts",
+        "tsx" => "tsx",
+        "json" => "json",
+        "sql" => "sql",
+        "bash" | "sh" | "shell" => "sh",
+        "html" => "html",
+        "css" => "css",
+        "java" => "java",
+        "go" => "go",
+        "c" => "c",
+        "cpp" | "c++" => "cpp",
+        "yaml" | "yml" => "yml",
+        "markdown" | "md" => "md",
+        _ => "txt",
+    }
+}
+
+/// Title line in the platform's markup
+fn title_line(platform: &MessagePlatform, title: &str) -> String {
+    match platform {
+        MessagePlatform::WhatsApp => format!("*{}*", title),
+        MessagePlatform::Telegram => format!("<b>{}</b>", escape_html(title)),
+        _ => format!("**{}**", title),
+    }
+}
+
+/// Monospace block in the platform's markup
+fn code_block(platform: &MessagePlatform, language: Option<&str>, body: &str) -> String {
+    match platform {
+        MessagePlatform::WhatsApp => format!("```\n{}\n```", body),
+        MessagePlatform::Telegram => match language.filter(|l| !l.is_empty()) {
+            Some(language) => format!(
+                "<pre><code class=\"language-{}\">{}</code></pre>",
+                escape_html(language),
+                escape_html(body)
+            ),
+            None => format!("<pre>{}</pre>", escape_html(body)),
+        },
+        _ => format!("```{}\n{}\n```", language.unwrap_or(""), body),
+    }
+}
+
+/// Rows of a Markdown table, without the `|---|` separator
+fn table_rows(markdown: &str) -> Vec<Vec<String>> {
+    markdown
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with('|'))
+        .filter(|line| !line.chars().all(|c| matches!(c, '|' | '-' | ':' | ' ')))
+        .map(|line| {
+            line.trim_matches('|')
+                .split('|')
+                .map(|cell| cell.trim().to_string())
+                .collect()
+        })
+        .collect()
+}
+
+/// Columns padded to equal width, the header underlined
+fn aligned_table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| rows.iter().filter_map(|r| r.get(i)).map(|c| c.chars().count()).max().unwrap_or(0))
+        .collect();
+    let line = |row: &Vec<String>| {
+        (0..columns)
+            .map(|i| {
+                let cell = row.get(i).map(String::as_str).unwrap_or("");
+                format!("{}{}", cell, " ".repeat(widths[i] - cell.chars().count()))
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let mut out: Vec<String> = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        out.push(line(row));
+        if i == 0 {
+            out.push(widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>().join("  "));
+        }
+    }
+    out.join("\n")
+}
+
+fn csv(rows: &[Vec<String>]) -> String {
+    let field = |cell: &String| {
+        if cell.contains([',', '"', '\n']) {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        } else {
+            cell.clone()
+        }
+    };
+    rows.iter()
+        .map(|row| row.iter().map(field).collect::<Vec<_>>().join(","))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_table(artifact: &Artifact, platform: &MessagePlatform, rows: &[Vec<String>]) -> RenderedArtifact {
+    let text = aligned_table(rows);
+    let too_big = rows.len() > MAX_TABLE_ROWS
+        || text.lines().any(|l| l.chars().count() > MAX_TABLE_WIDTH)
+        || text.len() > inline_limit(platform);
+    if too_big {
+        let caption = format!("{} ({} rows)", artifact.title, rows.len().saturating_sub(1));
+        return attachment(&artifact.title, "csv", "text/csv", csv(rows).as_bytes(), caption);
+    }
+    RenderedArtifact::Inline {
+        text: format!("{}\n{}", title_line(platform, &artifact.title), code_block(platform, None, &text)),
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "'"))
+}
+
+/// The parts of a chart's JSON spec the bots use
+struct ChartSpec {
+    chart_type: String,
+    labels: Vec<String>,
+    /// (label, values) per dataset
+    datasets: Vec<(String, Vec<f64>)>,
+}
+
+fn chart_spec(artifact: &Artifact) -> Option<ChartSpec> {
+    let spec: Value = serde_json::from_str(&artifact.content).ok()?;
+    let chart_type = spec.get("type")?.as_str()?.to_string();
+    let data = spec.get("data")?;
+    let labels = data
+        .get("labels")?
+        .as_array()?
+        .iter()
+        .map(|l| l.as_str().map(String::from).unwrap_or_else(|| l.to_string()))
+        .collect();
+    let datasets = data
+        .get("datasets")?
+        .as_array()?
+        .iter()
+        .enumerate()
+        .map(|(i, ds)| {
+            let label = ds.get("label").and_then(Value::as_str).map(String::from).unwrap_or_else(|| format!("Series {}", i + 1));
+            let values = ds
+                .get("data")
+                .and_then(Value::as_array)
+                .map(|v| v.iter().map(|n| n.as_f64().unwrap_or(0.0)).collect())
+                .unwrap_or_default();
+            (label, values)
+        })
+        .collect();
+    Some(ChartSpec { chart_type, labels, datasets })
+}
+
+/// Mermaid source for bar, line, area and pie charts
+fn chart_to_mermaid(artifact: &Artifact) -> Option<String> {
+    let ChartSpec { chart_type, labels, datasets } = chart_spec(artifact)?;
+    let number = |n: f64| if n.fract() == 0.0 { format!("{}", n as i64) } else { format!("{}", n) };
+    match chart_type.as_str() {
+        "pie" | "doughnut" => {
+            let (_, values) = datasets.first()?;
+            let mut out = format!("pie title {}\n", artifact.title.replace('\n', " "));
+            for (label, value) in labels.iter().zip(values) {
+                out.push_str(&format!("    {} : {}\n", quote(label), number(*value)));
+            }
+            Some(out)
+        }
+        "bar" | "line" | "area" => {
+            let mut out = format!("xychart-beta\n    title {}\n", quote(&artifact.title));
+            let axis: Vec<String> = labels.iter().map(|l| quote(l)).collect();
+            out.push_str(&format!("    x-axis [{}]\n", axis.join(", ")));
+            let mark = if chart_type == "bar" { "bar" } else { "line" };
+            for (_, values) in &datasets {
+                let values: Vec<String> = values.iter().map(|v| number(*v)).collect();
+                out.push_str(&format!("    {} [{}]\n", mark, values.join(", ")));
+            }
+            Some(out)
+        }
+        _ => None,
+    }
+}
+
+/// Dataset labels, which Mermaid's xychart does not draw
+fn chart_caption(artifact: &Artifact) -> String {
+    match chart_spec(artifact) {
+        Some(spec) if spec.datasets.len() > 1 => {
+            let names: Vec<&str> = spec.datasets.iter().map(|(name, _)| name.as_str()).collect();
+            format!("{} ({})", artifact.title, names.join(", "))
+        }
+        _ => artifact.title.clone(),
+    }
+}
+
+/// Chart values as a table, for when there is no image
+fn chart_table(artifact: &Artifact) -> Option<Vec<Vec<String>>> {
+    let ChartSpec { labels, datasets, .. } = chart_spec(artifact)?;
+    let mut rows = vec![std::iter::once(String::new()).chain(datasets.iter().map(|(name, _)| name.clone())).collect()];
+    for (i, label) in labels.iter().enumerate() {
+        let mut row = vec![label.clone()];
+        row.extend(datasets.iter().map(|(_, values)| values.get(i).map(|v| v.to_string()).unwrap_or_default()));
+        rows.push(row);
+    }
+    Some(rows)
+}
+
+/// Render Mermaid source to PNG with mermaid-cli. None when `mmdc` is not
+/// installed, fails, or takes too long.
+fn mermaid_png(source: &str) -> Option<Vec<u8>> {
+    let dir = std::env::temp_dir().join(format!("shodh-mmdc-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).ok()?;
+    let input = dir.join("diagram.mmd");
+    let output = dir.join("diagram.png");
+    let png = (|| {
+        std::fs::write(&input, source).ok()?;
+        let mut child = match Command::new("mmdc")
+            .arg("-i").arg(&input)
+            .arg("-o").arg(&output)
+            .args(["-b", "white", "-s", "2"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                tracing::debug!("mmdc not installed; sending diagrams as text");
+                return None;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to start mmdc: {}", e);
+                return None;
+            }
+        };
+        let started = Instant::now();
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) if status.success() => break,
+                Ok(Some(status)) => {
+                    tracing::warn!("mmdc exited with {}", status);
+                    return None;
+                }
+                Ok(None) if started.elapsed() > RENDER_TIMEOUT => {
+                    let _ = child.kill();
+                    // Reap it so no zombie is left behind
+                    let _ = child.wait();
+                    tracing::warn!("mmdc timed out after {:?}", RENDER_TIMEOUT);
+                    return None;
+                }
+                Ok(None) => std::thread::sleep(Duration::from_millis(100)),
+                Err(e) => {
+                    tracing::warn!("Failed to wait for mmdc: {}", e);
+                    return None;
+                }
+            }
+        }
+        std::fs::read(&output).ok()
+    })();
+    let _ = std::fs::remove_dir_all(&dir);
+    png
+}
+
+/// How to send `artifact` on `platform`: inline text in the platform's
+/// markup, or a file. Blocks while rendering images; call it off the async
+/// runtime (see `render_artifacts`).
+pub fn render_artifact_for_platform(artifact: &Artifact, platform: &MessagePlatform) -> RenderedArtifact {
+    let title = title_line(platform, &artifact.title);
+    let limit = inline_limit(platform);
+    match artifact.artifact_type {
+        ArtifactType::Mermaid | ArtifactType::Chart => {
+            let source = if artifact.artifact_type == ArtifactType::Mermaid {
+                Some(artifact.content.clone()).filter(|_| artifact.valid)
+            } else {
+                chart_to_mermaid(artifact)
+            };
+            if let Some(png) = source.as_deref().and_then(mermaid_png) {
+                let caption = chart_caption(artifact);
+                return attachment(&artifact.title, "png", "image/png", &png, caption);
+            }
+            // Text fallback: chart values as a table, diagrams as source
+            if let Some(rows) = chart_table(artifact).filter(|_| artifact.artifact_type == ArtifactType::Chart) {
+                return render_table(artifact, platform, &rows);
+            }
+            if artifact.content.len() > limit {
+                return attachment(&artifact.title, "mmd", "text/plain", artifact.content.as_bytes(), artifact.title.clone());
+            }
+            RenderedArtifact::Inline { text: format!("{}\n{}", title, code_block(platform, None, &artifact.content)) }
+        }
+        ArtifactType::Code => {
+            let language = artifact.language.as_deref();
+            if artifact.content.len() > limit {
+                let extension = extension_for(language);
+                return attachment(&artifact.title, extension, "text/plain", artifact.content.as_bytes(), artifact.title.clone());
+            }
+            RenderedArtifact::Inline { text: format!("{}\n{}", title, code_block(platform, language, &artifact.content)) }
+        }
+        ArtifactType::Table => {
+            let rows = table_rows(&artifact.content);
+            if rows.is_empty() {
+                return RenderedArtifact::Inline { text: format!("{}\n{}", title, code_block(platform, None, &artifact.content)) };
+            }
+            render_table(artifact, platform, &rows)
+        }
+        ArtifactType::Markdown => {
+            if artifact.content.len() > limit {
+                return attachment(&artifact.title, "md", "text/markdown", artifact.content.as_bytes(), artifact.title.clone());
+            }
+            let body = match platform {
+                MessagePlatform::WhatsApp => whatsapp_text(&artifact.content),
+                MessagePlatform::Telegram => telegram_html(&artifact.content),
+                _ => artifact.content.clone(),
+            };
+            RenderedArtifact::Inline { text: format!("{}\n{}", title, body) }
+        }
+        ArtifactType::Html | ArtifactType::Svg => RenderedArtifact::Attachment(source_file(artifact)),
+    }
+}
+
+/// Artifact text to append to the reply, with the file to send instead
+/// if the message would get too long
+#[derive(Debug, Clone)]
+pub struct InlineArtifact {
+    pub text: String,
+    pub fallback: ArtifactAttachment,
+}
+
+/// Render a reply's artifacts without blocking the runtime: inline texts
+/// to append to the reply, and files to send after it
+pub async fn render_artifacts(artifacts: Vec<Artifact>, platform: MessagePlatform) -> (Vec<InlineArtifact>, Vec<ArtifactAttachment>) {
+    if artifacts.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let rendered = tokio::task::spawn_blocking(move || {
+        artifacts
+            .into_iter()
+            .map(|a| {
+                let rendered = render_artifact_for_platform(&a, &platform);
+                (a, rendered)
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .unwrap_or_else(|e| {
+        tracing::warn!("Artifact rendering failed: {}", e);
+        Vec::new()
+    });
+
+    let mut inline = Vec::new();
+    let mut attachments = Vec::new();
+    for (artifact, rendered) in rendered {
+        match rendered {
+            RenderedArtifact::Inline { text } => inline.push(InlineArtifact { text, fallback: source_file(&artifact) }),
+            RenderedArtifact::Attachment(file) => attachments.push(file),
+        }
+    }
+    (inline, attachments)
+}
+
+/// The inline artifact texts that fit in one message with `reply` on
+/// `platform`; the others are added to `attachments` as files
+pub fn fit_inline(
+    reply: &str,
+    inline: Vec<InlineArtifact>,
+    platform: &MessagePlatform,
+    attachments: &mut Vec<ArtifactAttachment>,
+) -> Vec<String> {
+    let limit = message_limit(platform);
+    let mut length = reply.chars().count();
+    let mut texts = Vec::new();
+    for artifact in inline {
+        let added = 2 + artifact.text.chars().count();
+        if length + added > limit {
+            attachments.push(artifact.fallback);
+            continue;
+        }
+        length += added;
+        texts.push(artifact.text);
+    }
+    texts
+}
+
+/// `reply` followed by the inline artifact texts that fit (see `fit_inline`)
+pub fn append_inline(
+    reply: String,
+    inline: Vec<InlineArtifact>,
+    platform: &MessagePlatform,
+    attachments: &mut Vec<ArtifactAttachment>,
+) -> String {
+    fit_inline(&reply, inline, platform, attachments)
+        .into_iter()
+        .fold(reply, |mut out, text| {
+            out.push_str("\n\n");
+            out.push_str(&text);
+            out
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn artifact(artifact_type: ArtifactType, title: &str, content: &str) -> Artifact {
+        Artifact {
+            id: "a".to_string(),
+            artifact_type,
+            title: title.to_string(),
+            content: content.to_string(),
+            language: Some("rust".to_string()),
+            editable: true,
+            version: 1,
+            created_at: chrono::Utc::now(),
+            valid: true,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_tables_charts_and_code_per_platform() {
+        let table = artifact(ArtifactType::Table, "Rent", "| Year | Rent |\n|---|---:|\n| 2024 | 1,200 |\n| 2025 | 1,260 |");
+        match render_artifact_for_platform(&table, &MessagePlatform::WhatsApp) {
+            RenderedArtifact::Inline { text } => {
+                assert_eq!(text, "*Rent*\n```\nYear  Rent\n----  -----\n2024  1,200\n2025  1,260\n```")
+            }
+            other => panic!("expected inline table, got {:?}", other),
+        }
+        let rows = table_rows(&table.content);
+        assert_eq!(csv(&rows), "Year,Rent\n2024,\"1,200\"\n2025,\"1,260\"");
+
+        let chart = artifact(
+            ArtifactType::Chart,
+            "Revenue",
+            r#"{"type":"bar","data":{"labels":["Q1","Q2"],"datasets":[{"label":"2024","data":[10,12.5]},{"label":"2025","data":[11,14]}]}}"#,
+        );
+        assert_eq!(
+            chart_to_mermaid(&chart).unwrap(),
+            "xychart-beta\n    title \"Revenue\"\n    x-axis [\"Q1\", \"Q2\"]\n    bar [10, 12.5]\n    bar [11, 14]\n"
+        );
+        assert_eq!(chart_caption(&chart), "Revenue (2024, 2025)");
+        assert_eq!(
+            aligned_table(&chart_table(&chart).unwrap()),
+            "    2024  2025\n--  ----  ----\nQ1  10    11\nQ2  12.5  14"
+        );
+
+        let long_code = artifact(ArtifactType::Code, "Lease Parser", &"let x = 1;\n".repeat(200));
+        match render_artifact_for_platform(&long_code, &MessagePlatform::Discord) {
+            RenderedArtifact::Attachment(file) => {
+                assert_eq!(file.file_name, "lease-parser.rs");
+                assert_eq!(file.mime_type, "text/plain");
+            }
+            other => panic!("expected a file, got {:?}", other),
+        }
+        let short_code = artifact(ArtifactType::Code, "Check", "a < b");
+        match render_artifact_for_platform(&short_code, &MessagePlatform::Telegram) {
+            RenderedArtifact::Inline { text } => {
+                assert_eq!(text, "<b>Check</b>\n<pre><code class=\"language-rust\">a &lt; b</code></pre>")
+            }
+            other => panic!("expected inline code, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_append_inline_caps_message_length() {
+        let inline = |title: &str, chars: usize| InlineArtifact {
+            text: "x".repeat(chars),
+            fallback: source_file(&artifact(ArtifactType::Code, title, "fn main() {}")),
+        };
+        let mut attachments = Vec::new();
+        let text = append_inline(
+            "Reply".to_string(),
+            vec![inline("First", 1200), inline("Second", 1200), inline("Third", 500)],
+            &MessagePlatform::Discord,
+            &mut attachments,
+        );
+
+        // "Second" would pass Discord's 2000 characters; "Third" still fits
+        assert_eq!(text.chars().count(), 5 + 2 + 1200 + 2 + 500);
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(attachments[0].file_name, "second.rs");
+    }
+}
@@ -0,0 +1,106 @@
+//! One chat path for the WhatsApp, Telegram and Discord bots
+//!
+//! Every bot message becomes a `BotTurn` and goes through `run_turn`, which
+//! calls `ChatEngine::process_message` the same way desktop chat does: same
+//! intent detection, RAG, memory and artifact storage. MCP, calendar and file
+//! tools are only offered to senders on the bot's tool allowlist. What stays
+//! per platform is who may talk to the bot (`bot_access`), how the turn is
+//! keyed, and how the reply is formatted (`bot_format`).
+
+use std::time::Duration;
+use tauri::Emitter;
+
+use crate::chat_engine::{AssistantResponse, ChatContext, EventEmitter, MessagePlatform};
+use crate::rag_commands::RagState;
+use crate::unified_chat_commands::unified_chat_with_emitter;
+
+/// How long a bot waits for an answer before giving up
+const BOT_TURN_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// One incoming bot message, tagged with its platform
+#[derive(Debug, Clone)]
+pub struct BotTurn {
+    pub platform: MessagePlatform,
+    /// Display name of the sender, for logs and the desktop feed
+    pub sender: String,
+    /// Conversation the engine keeps memory and artifacts under
+    pub conversation_id: String,
+    /// Space to search, when the sender is bound to one
+    pub space_id: Option<String>,
+    pub message: String,
+    /// Sender is on the bot's tool allowlist (`AccessPolicy::tool_users`)
+    pub tools_allowed: bool,
+}
+
+impl BotTurn {
+    pub fn context(&self) -> ChatContext {
+        ChatContext {
+            conversation_id: Some(self.conversation_id.clone()),
+            space_id: self.space_id.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Lowercase platform name used for frontend event names
+pub fn platform_key(platform: &MessagePlatform) -> &'static str {
+    match platform {
+        MessagePlatform::Desktop => "desktop",
+        MessagePlatform::WhatsApp => "whatsapp",
+        MessagePlatform::Telegram => "telegram",
+        MessagePlatform::Discord => "discord",
+    }
+}
+
+/// Mirror a bot message or reply into the desktop UI as
+/// `<platform>-message` / `<platform>-response`
+fn notify_frontend(app_handle: Option<&tauri::AppHandle>, turn: &BotTurn, kind: &str, message: &str) {
+    if let Some(app_handle) = app_handle {
+        let platform = platform_key(&turn.platform);
+        let _ = app_handle.emit(&format!("{}-{}", platform, kind), serde_json::json!({
+            "platform": platform,
+            "username": turn.sender,
+            "message": message,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        }));
+    }
+}
+
+/// Answer a bot message through the shared chat engine. `app_handle` mirrors
+/// the exchange into the desktop feed; tools follow `turn.tools_allowed`.
+/// Progress events go to `emitter` only, never to the desktop chat view.
+pub async fn run_turn(
+    rag_state: &RagState,
+    app_handle: Option<&tauri::AppHandle>,
+    turn: &BotTurn,
+    emitter: Option<&dyn EventEmitter>,
+) -> Result<AssistantResponse, String> {
+    tracing::info!("📨 {:?} message from {}: {}", turn.platform, turn.sender, turn.message);
+    notify_frontend(app_handle, turn, "message", &turn.message);
+
+    let chat = unified_chat_with_emitter(
+        rag_state,
+        turn.message.clone(),
+        Some(turn.context()),
+        turn.platform.clone(),
+        app_handle.cloned(),
+        turn.tools_allowed,
+        emitter,
+    );
+    let result = match tokio::time::timeout(BOT_TURN_TIMEOUT, chat).await {
+        Ok(result) => result,
+        Err(_) => {
+            tracing::info!("❌ {:?} request timed out after {:?}", turn.platform, BOT_TURN_TIMEOUT);
+            Err("the request timed out. Please try again with a simpler question.".to_string())
+        }
+    };
+
+    match &result {
+        Ok(response) => {
+            notify_frontend(app_handle, turn, "response", &response.content);
+            tracing::info!("✅ Answered {} on {:?}", turn.sender, turn.platform);
+        }
+        Err(e) => tracing::info!("❌ Error answering {} on {:?}: {}", turn.sender, turn.platform, e),
+    }
+    result
+}
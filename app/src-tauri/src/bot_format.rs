@@ -0,0 +1,232 @@
+//! Per-platform reply formatting for the bots
+//!
+//! `ChatEngine` answers in Markdown. WhatsApp has its own lightweight markup
+//! (`*bold*`, `_italic_`, `~strike~`), the Telegram bridge sends with
+//! `parse_mode = HTML`, and Discord renders Markdown itself but shows
+//! sources and model stats best as an embed. Fenced code blocks and inline
+//! code are never reformatted.
+
+use regex::Regex;
+use serde::Serialize;
+use std::sync::LazyLock;
+
+use crate::chat_engine::{AssistantResponse, MessagePlatform, ResponseMetadata};
+
+static HEADING_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s{0,3}#{1,6}\s+(.+?)\s*#*\s*$").expect("heading regex is valid"));
+static BULLET_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\s*)[-*+]\s+").expect("bullet regex is valid"));
+static BOLD_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\*\*(.+?)\*\*|__(.+?)__").expect("bold regex is valid"));
+static ITALIC_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\*([^*\s][^*]*?)\*").expect("italic regex is valid"));
+static STRIKE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"~~(.+?)~~").expect("strikethrough regex is valid"));
+static LINK_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[([^\]]+)\]\((\S+?)\)").expect("link regex is valid"));
+static INLINE_CODE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"`([^`\n]+)`").expect("inline code regex is valid"));
+
+/// Marks bold while italics are rewritten, so `**a**` is not read as `*a*`
+const BOLD_MARK: char = '\u{1}';
+
+/// Apply `prose` to each line outside fenced code blocks and `code` to each
+/// fenced block (language, body)
+fn map_blocks(markdown: &str, prose: impl Fn(&str) -> String, code: impl Fn(&str, &str) -> String) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut fence: Option<(String, Vec<&str>)> = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        match fence.take() {
+            Some((language, body)) if trimmed.starts_with("```") => out.push(code(&language, &body.join("\n"))),
+            Some((language, mut body)) => {
+                body.push(line);
+                fence = Some((language, body));
+            }
+            None if trimmed.starts_with("```") => {
+                fence = Some((trimmed.trim_start_matches('`').trim().to_string(), Vec::new()));
+            }
+            None => out.push(prose(line)),
+        }
+    }
+    // Unterminated fence: keep it as code
+    if let Some((language, body)) = fence {
+        out.push(code(&language, &body.join("\n")));
+    }
+    out.join("\n")
+}
+
+/// Apply `prose` to the text between inline code spans and `code` to the spans
+fn map_inline(line: &str, prose: impl Fn(&str) -> String, code: impl Fn(&str) -> String) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+    for caps in INLINE_CODE_RE.captures_iter(line) {
+        let span = caps.get(0).unwrap();
+        out.push_str(&prose(&line[last..span.start()]));
+        out.push_str(&code(&caps[1]));
+        last = span.end();
+    }
+    out.push_str(&prose(&line[last..]));
+    out
+}
+
+/// Markdown to WhatsApp markup
+pub fn whatsapp_text(markdown: &str) -> String {
+    map_blocks(
+        markdown,
+        |line| {
+            if let Some(caps) = HEADING_RE.captures(line) {
+                return format!("*{}*", caps[1].replace("**", ""));
+            }
+            let line = BULLET_RE.replace(line, "$1• ");
+            map_inline(
+                &line,
+                |text| {
+                    let text = BOLD_RE.replace_all(text, |c: &regex::Captures| {
+                        let inner = c.get(1).or_else(|| c.get(2)).map_or("", |m| m.as_str());
+                        format!("{BOLD_MARK}{inner}{BOLD_MARK}")
+                    });
+                    let text = ITALIC_RE.replace_all(&text, "_${1}_");
+                    let text = STRIKE_RE.replace_all(&text, "~${1}~");
+                    let text = LINK_RE.replace_all(&text, "$1 ($2)");
+                    text.replace(BOLD_MARK, "*")
+                },
+                |code| format!("`{}`", code),
+            )
+        },
+        |_, body| format!("```\n{}\n```", body),
+    )
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Markdown to the HTML subset Telegram accepts
+pub fn telegram_html(markdown: &str) -> String {
+    map_blocks(
+        markdown,
+        |line| {
+            if let Some(caps) = HEADING_RE.captures(line) {
+                return format!("<b>{}</b>", escape_html(&caps[1].replace("**", "")));
+            }
+            let line = BULLET_RE.replace(line, "$1• ");
+            map_inline(
+                &line,
+                |text| {
+                    let text = escape_html(text);
+                    let text = BOLD_RE.replace_all(&text, |c: &regex::Captures| {
+                        let inner = c.get(1).or_else(|| c.get(2)).map_or("", |m| m.as_str());
+                        format!("<b>{}</b>", inner)
+                    });
+                    let text = ITALIC_RE.replace_all(&text, "<i>${1}</i>");
+                    let text = STRIKE_RE.replace_all(&text, "<s>${1}</s>");
+                    LINK_RE.replace_all(&text, "<a href=\"$2\">$1</a>").into_owned()
+                },
+                |code| format!("<code>{}</code>", escape_html(code)),
+            )
+        },
+        |language, body| {
+            let class = if language.is_empty() { String::new() } else { format!(" class=\"language-{}\"", escape_html(language)) };
+            format!("<pre><code{}>{}</code></pre>", class, escape_html(body))
+        },
+    )
+}
+
+/// Model, token counts and speed, e.g. `gpt-4o │ ↓ 812 │ ↑ 240 │ ⏱ 3.1s │ ⚡ 77.4 tok/s`
+pub fn metadata_footer(meta: &ResponseMetadata) -> Option<String> {
+    let (model, input_tokens, output_tokens, duration_ms) =
+        (meta.model.as_ref()?, meta.input_tokens?, meta.output_tokens?, meta.duration_ms?);
+    let duration_s = duration_ms as f64 / 1000.0;
+    let tok_per_s = if duration_s > 0.0 { output_tokens as f64 / duration_s } else { 0.0 };
+    Some(format!(
+        "🤖 {} │ ↓ {} │ ↑ {} │ ⏱ {:.1}s │ ⚡ {:.1} tok/s",
+        model, input_tokens, output_tokens, duration_s, tok_per_s
+    ))
+}
+
+/// Source titles of the response's citations, without duplicates
+pub fn reply_sources(response: &AssistantResponse) -> Vec<String> {
+    let mut sources: Vec<String> = Vec::new();
+    for citation in &response.citations {
+        if !sources.contains(&citation.title) {
+            sources.push(citation.title.clone());
+        }
+    }
+    sources
+}
+
+/// Mean citation score, or 0.8 for answers that cite nothing
+pub fn reply_confidence(response: &AssistantResponse) -> f32 {
+    if response.citations.is_empty() {
+        0.8
+    } else {
+        response.citations.iter().map(|c| c.score).sum::<f32>() / response.citations.len() as f32
+    }
+}
+
+/// Discord embed carrying the sources and model stats of a reply
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscordEmbed {
+    pub title: String,
+    pub description: String,
+    pub footer: Option<String>,
+}
+
+pub fn discord_embed(response: &AssistantResponse) -> Option<DiscordEmbed> {
+    let sources = reply_sources(response);
+    let footer = metadata_footer(&response.metadata);
+    if sources.is_empty() && footer.is_none() {
+        return None;
+    }
+    let description = sources
+        .iter()
+        .enumerate()
+        .map(|(i, source)| format!("[{}] {}", i + 1, source))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some(DiscordEmbed { title: "Sources".to_string(), description, footer })
+}
+
+/// Reply text in the platform's markup. WhatsApp and Telegram get the
+/// stats footer inline; Discord carries it in `discord_embed`.
+pub fn format_reply(platform: &MessagePlatform, response: &AssistantResponse) -> String {
+    let footer = metadata_footer(&response.metadata);
+    match platform {
+        MessagePlatform::WhatsApp => match footer {
+            Some(footer) => format!("{}\n\n{}", whatsapp_text(&response.content), footer),
+            None => whatsapp_text(&response.content),
+        },
+        MessagePlatform::Telegram => match footer {
+            Some(footer) => format!("{}\n\n<i>{}</i>", telegram_html(&response.content), escape_html(&footer)),
+            None => telegram_html(&response.content),
+        },
+        MessagePlatform::Discord | MessagePlatform::Desktop => response.content.clone(),
+    }
+}
+
+/// Error text in the platform's markup
+pub fn format_error(platform: &MessagePlatform, error: &str) -> String {
+    let text = format!("Sorry, I encountered an error: {}", error);
+    match platform {
+        MessagePlatform::Telegram => escape_html(&text),
+        _ => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANSWER: &str = "## Lease terms\n\nThe **tenant** pays *monthly* via [portal](https://x.io/a?b=1&c=2).\n\
+        - Deposit: ~~2~~ 3 months\n- Uses `a<b` checks\n\n```rust\nlet x = a < b && **not_bold**;\n```";
+
+    #[test]
+    fn test_platform_markup() {
+        assert_eq!(
+            whatsapp_text(ANSWER),
+            "*Lease terms*\n\nThe *tenant* pays _monthly_ via portal (https://x.io/a?b=1&c=2).\n\
+             • Deposit: ~2~ 3 months\n• Uses `a<b` checks\n\n```\nlet x = a < b && **not_bold**;\n```"
+        );
+        assert_eq!(
+            telegram_html(ANSWER),
+            "<b>Lease terms</b>\n\nThe <b>tenant</b> pays <i>monthly</i> via \
+             <a href=\"https://x.io/a?b=1&amp;c=2\">portal</a>.\n\
+             • Deposit: <s>2</s> 3 months\n• Uses <code>a&lt;b</code> checks\n\n\
+             <pre><code class=\"language-rust\">let x = a &lt; b &amp;&amp; **not_bold**;</code></pre>"
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Server-Sent Events streaming for the bot HTTP servers
+//!
+//! A `/…/stream` endpoint runs the same `bot_chat::run_turn` path as the
+//! blocking endpoint, with an `EventEmitter` that forwards the engine's
+//! events (`chat_token`, `artifact_start`, `artifact_delta`,
+//! `artifact_complete`, `chat_complete`, ...) as SSE events. The stream ends
+//! with `artifacts` (the reply's artifacts rendered for the platform, see
+//! `bot_artifacts`), then `done` carrying the full `AssistantResponse`, or
+//! `error`.
+//!
+//! When the client disconnects, axum drops the response stream and with it
+//! the receiving end of the channel; the generation task sees the channel
+//! close and drops the in-flight chat future, cancelling the LLM stream.
+
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures::Stream;
+use std::convert::Infallible;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{mpsc, RwLock};
+
+use crate::bot_artifacts::{fit_inline, render_artifacts};
+use crate::bot_chat::{run_turn, BotTurn};
+use crate::bot_format::format_reply;
+use crate::chat_engine::{AssistantResponse, EventEmitter};
+use crate::rag_commands::RagState;
+
+/// Forwards chat engine events to an SSE connection
+pub struct SseEmitter {
+    tx: mpsc::UnboundedSender<Event>,
+}
+
+impl EventEmitter for SseEmitter {
+    fn emit(&self, event: &str, mut data: serde_json::Value) {
+        // Tauri listeners want the running text; over the wire that is
+        // quadratic, so SSE clients get only the new token
+        if event == "chat_token" {
+            if let Some(obj) = data.as_object_mut() {
+                obj.remove("accumulated");
+            }
+        }
+        let _ = self.tx.send(Event::default().event(event).data(data.to_string()));
+    }
+}
+
+/// Run one chat turn, streaming its events. `on_complete` gets the final
+/// result (not called if the client went away first).
+pub fn stream_chat<F, Fut>(
+    rag_state: Arc<RwLock<RagState>>,
+    app_handle: Option<tauri::AppHandle>,
+    turn: BotTurn,
+    on_complete: F,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>>
+where
+    F: FnOnce(Result<AssistantResponse, String>) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let emitter = SseEmitter { tx: tx.clone() };
+        let chat = async {
+            let rag = rag_state.read().await;
+            run_turn(&rag, app_handle.as_ref(), &turn, Some(&emitter)).await
+        };
+
+        let result = tokio::select! {
+            result = chat => result,
+            _ = tx.closed() => {
+                tracing::info!("SSE client disconnected; cancelled {:?} generation", turn.platform);
+                return;
+            }
+        };
+
+        if let Ok(response) = &result {
+            let (inline, mut attachments) = render_artifacts(response.artifacts.clone(), turn.platform.clone()).await;
+            let reply = format_reply(&turn.platform, response);
+            let inline = fit_inline(&reply, inline, &turn.platform, &mut attachments);
+            if !inline.is_empty() || !attachments.is_empty() {
+                let rendered = serde_json::json!({ "inline": inline, "attachments": attachments });
+                let _ = tx.send(Event::default().event("artifacts").data(rendered.to_string()));
+            }
+        }
+
+        let last = match &result {
+            Ok(response) => Event::default().event("done").json_data(response),
+            Err(e) => Event::default().event("error").json_data(serde_json::json!({ "message": e })),
+        };
+        match last {
+            Ok(event) => {
+                let _ = tx.send(event);
+            }
+            Err(e) => tracing::warn!("Failed to encode final SSE event: {}", e),
+        }
+        on_complete(result).await;
+    });
+
+    let stream = futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|event| (Ok(event), rx))
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
@@ -171,7 +171,7 @@ fn spawn_index_task(app: &AppHandle, task: &TodoItem) {
     let rag_task = to_rag_task(task);
     tokio::spawn(async move {
         let mut engine = rag.write().await;
-        if let Err(e) = shodh_rag::agent::calendar_indexer::index_task(&mut engine, &rag_task, "calendar").await {
+        if let Err(e) = shodh_rag::agent::calendar_indexer::index_task(&mut engine, &rag_task, shodh_rag::agent::calendar_indexer::CALENDAR_SPACE_ID).await {
             tracing::warn!(task_id = %rag_task.id, error = %e, "Failed to index task in RAG");
         }
     });
@@ -197,7 +197,7 @@ fn spawn_index_event(app: &AppHandle, event: &CalendarEvent) {
     let rag_event = to_rag_event(event);
     tokio::spawn(async move {
         let mut engine = rag.write().await;
-        if let Err(e) = shodh_rag::agent::calendar_indexer::index_event(&mut engine, &rag_event, "calendar").await {
+        if let Err(e) = shodh_rag::agent::calendar_indexer::index_event(&mut engine, &rag_event, shodh_rag::agent::calendar_indexer::CALENDAR_SPACE_ID).await {
             tracing::warn!(event_id = %rag_event.id, error = %e, "Failed to index event in RAG");
         }
     });
@@ -501,7 +501,7 @@ pub async fn reindex_all_calendar_data(app: &AppHandle) {
 
     tokio::spawn(async move {
         let mut engine = rag.write().await;
-        match shodh_rag::agent::calendar_indexer::reindex_all(&mut engine, &rag_tasks, &rag_events, "calendar").await {
+        match shodh_rag::agent::calendar_indexer::reindex_all(&mut engine, &rag_tasks, &rag_events, shodh_rag::agent::calendar_indexer::CALENDAR_SPACE_ID).await {
             Ok((t, e)) => tracing::info!(tasks = t, events = e, "Calendar data reindexed into RAG on startup"),
             Err(e) => tracing::warn!(error = %e, "Failed to reindex calendar data into RAG"),
         }
@@ -0,0 +1,201 @@
+//! Minimal abstraction over cloud storage providers
+//!
+//! A provider lists a folder, downloads a file and reports what changed
+//! since a cursor; `sync_folder` does the rest (filtering, temp download,
+//! ingestion into a space, progress). Dropbox implements it; Google Drive
+//! predates it and keeps its own sync in `google_drive_commands`.
+
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::google_drive_commands::{FolderSyncConfig, SyncStatus};
+use crate::rag_commands::RagState;
+
+/// Extensions the ingestion pipeline can parse
+const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "pdf", "docx", "xlsx", "xls", "pptx", "txt", "md", "markdown", "csv", "json", "html", "htm", "epub", "rtf",
+];
+
+/// A file or folder at a provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudFile {
+    pub id: String,
+    pub name: String,
+    /// Provider path, e.g. `/Cases/2024/brief.pdf`
+    pub path: String,
+    pub size: Option<u64>,
+    pub modified_time: Option<String>,
+    pub is_folder: bool,
+}
+
+impl CloudFile {
+    fn is_supported(&self) -> bool {
+        !self.is_folder
+            && Path::new(&self.name)
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| SUPPORTED_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+    }
+}
+
+/// Download directory for one cloud file. Keyed by the provider's file id,
+/// so the indexed source stays the same across syncs (and renames) of a file
+/// and two files with the same name never share one.
+fn staging_dir(temp_dir: &Path, file: &CloudFile) -> PathBuf {
+    let key: String = file.id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    temp_dir.join(key)
+}
+
+/// Files added or modified since a cursor
+pub struct CloudChanges {
+    pub files: Vec<CloudFile>,
+    /// Pass back to `changes` next time
+    pub cursor: String,
+}
+
+#[async_trait]
+pub trait CloudProvider: Send + Sync {
+    /// Display name, recorded as the document `source`
+    fn name(&self) -> &'static str;
+
+    /// Direct children of `folder` (the root when None)
+    async fn list(&self, folder: Option<&str>) -> Result<Vec<CloudFile>, String>;
+
+    async fn download(&self, file: &CloudFile, save_path: &Path) -> Result<(), String>;
+
+    /// Everything under `folder` when `cursor` is None, else what changed
+    /// since that cursor
+    async fn changes(&self, folder: &str, recursive: bool, cursor: Option<&str>) -> Result<CloudChanges, String>;
+}
+
+/// Download the supported files in the configured folder that changed
+/// since `cursor` and index them into its space, updating `status` as it
+/// goes. Returns the final status and the cursor for the next sync.
+pub async fn sync_folder(
+    provider: &dyn CloudProvider,
+    config: &FolderSyncConfig,
+    cursor: Option<&str>,
+    temp_dir: &Path,
+    status: &RwLock<SyncStatus>,
+    rag_state: State<'_, RagState>,
+) -> Result<(SyncStatus, String), String> {
+    std::fs::create_dir_all(temp_dir).map_err(|e| format!("Failed to create temp dir: {}", e))?;
+
+    {
+        let mut status = status.write();
+        *status = SyncStatus {
+            is_syncing: true,
+            total_files: 0,
+            synced_files: 0,
+            failed_files: 0,
+            last_sync: status.last_sync,
+            error: None,
+        };
+    }
+
+    let changes = match provider.changes(&config.folder_id, config.sync_subdirectories, cursor).await {
+        Ok(changes) => changes,
+        Err(e) => {
+            let mut status = status.write();
+            status.is_syncing = false;
+            status.error = Some(e.clone());
+            return Err(e);
+        }
+    };
+    let files: Vec<CloudFile> = changes.files.into_iter().filter(CloudFile::is_supported).collect();
+    status.write().total_files = files.len();
+    tracing::info!("📦 {}: {} changed files to sync", provider.name(), files.len());
+
+    for file in files {
+        let dir = staging_dir(temp_dir, &file);
+        let save_path = dir.join(&file.name);
+        let downloaded = match std::fs::create_dir_all(&dir) {
+            Ok(()) => provider.download(&file, &save_path).await,
+            Err(e) => Err(format!("Failed to create temp dir: {}", e)),
+        };
+        let result = match downloaded {
+            Ok(()) => {
+                // Drop the previously synced version, which may have had another name
+                let previous = format!("{}/", dir.display());
+                if let Err(e) = rag_state.rag.write().await.delete_by_source_prefix(&previous).await {
+                    tracing::warn!("Failed to remove previous version of {}: {}", file.path, e);
+                }
+
+                let mut metadata = HashMap::new();
+                metadata.insert("space_id".to_string(), config.space_id.clone());
+                metadata.insert("title".to_string(), file.name.clone());
+                metadata.insert("source".to_string(), provider.name().to_string());
+                metadata.insert("original_name".to_string(), file.name.clone());
+                metadata.insert("cloud_file_id".to_string(), file.id.clone());
+                metadata.insert("cloud_path".to_string(), file.path.clone());
+                metadata.insert("sync_date".to_string(), chrono::Utc::now().to_rfc3339());
+
+                let indexed = crate::rag_commands::upload_file(
+                    save_path.to_string_lossy().to_string(),
+                    metadata,
+                    rag_state.clone(),
+                ).await;
+                let _ = std::fs::remove_dir_all(&dir);
+                indexed
+            }
+            Err(e) => Err(e),
+        };
+
+        let mut status = status.write();
+        match result {
+            Ok(_) => status.synced_files += 1,
+            Err(e) => {
+                tracing::warn!("❌ Failed to sync {} from {}: {}", file.path, provider.name(), e);
+                status.failed_files += 1;
+            }
+        }
+    }
+
+    let mut status = status.write();
+    status.is_syncing = false;
+    status.last_sync = Some(chrono::Utc::now());
+    tracing::info!(
+        "✅ {} sync complete: {}/{} files synced, {} failed",
+        provider.name(), status.synced_files, status.total_files, status.failed_files
+    );
+    Ok((status.clone(), changes.cursor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cloud_file(id: &str, name: &str) -> CloudFile {
+        CloudFile {
+            id: id.to_string(),
+            name: name.to_string(),
+            path: format!("/{}", name),
+            size: None,
+            modified_time: None,
+            is_folder: false,
+        }
+    }
+
+    #[test]
+    fn test_staging_dir_is_per_file_id() {
+        let temp = Path::new("/tmp/dropbox_temp");
+        let brief = cloud_file("id:a4ayc_80_OEAAAAAAAAAXw", "brief.pdf");
+        assert_eq!(staging_dir(temp, &brief), temp.join("id_a4ayc_80_OEAAAAAAAAAXw"));
+
+        // Same name elsewhere in the folder gets its own directory
+        let other = cloud_file("id:b7xq", "brief.pdf");
+        assert_ne!(staging_dir(temp, &brief), staging_dir(temp, &other));
+
+        // A rename keeps the directory, so the old version is replaced
+        let renamed = cloud_file("id:a4ayc_80_OEAAAAAAAAAXw", "brief-v2.pdf");
+        assert_eq!(staging_dir(temp, &brief), staging_dir(temp, &renamed));
+    }
+}
@@ -1,9 +1,38 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::RwLock as TokioRwLock;
+use base64::Engine as _;
 use chrono::Utc;
 
+use crate::conversation_search::{ConversationIndex, ConversationSearchHit, ConversationSearchState};
+use crate::rag_commands::RagState;
+use shodh_rag::rag::{checkpoint_due, summarize_checkpoint, SummaryCheckpoint};
+
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+const MAX_SEARCH_LIMIT: usize = 200;
+
+/// Characters of each message shown to the LLM when titling
+const TITLE_CONTEXT_CHARS: usize = 1000;
+const TITLE_MAX_TOKENS: usize = 24;
+const TITLE_MAX_WORDS: usize = 8;
+
+/// Where a conversation's title came from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleSource {
+    /// "New Chat" or the UI's first-words title
+    #[default]
+    Placeholder,
+    /// Written by the LLM
+    Generated,
+    /// Set by the user; never replaced automatically
+    User,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConversationMessage {
@@ -15,6 +44,14 @@ pub struct ConversationMessage {
     pub artifacts: Option<Vec<serde_json::Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub search_results: Option<Vec<serde_json::Value>>,
+    /// Conversation whose copy of this message holds its artifacts. Set on
+    /// messages copied into a branch, which reference the artifacts instead
+    /// of storing them again; `load_conversations` fills them back in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact_source: Option<String>,
+    /// `ResponseMetadata` of an assistant reply (model, tokens, timing)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +69,77 @@ pub struct ConversationRecord {
     pub space_name: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub system_prompt: Option<String>,
+    /// Conversation this one was forked from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_conversation_id: Option<String>,
+    /// Last message copied from the parent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fork_message_id: Option<String>,
+    #[serde(default)]
+    pub title_source: TitleSource,
+    /// Summary of the oldest messages, sent with chat requests in place of them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary_checkpoint: Option<SummaryCheckpoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationSettings {
+    /// Ask the LLM for a title after the first exchange
+    #[serde(default = "default_auto_title")]
+    pub auto_title: bool,
+    /// Messages allowed past the last summary checkpoint before the older
+    /// ones are summarized again; 0 turns checkpoints off
+    #[serde(default = "default_summarize_after_messages")]
+    pub summarize_after_messages: usize,
+}
+
+fn default_auto_title() -> bool {
+    true
+}
+
+fn default_summarize_after_messages() -> usize {
+    20
+}
+
+impl Default for ConversationSettings {
+    fn default() -> Self {
+        Self {
+            auto_title: default_auto_title(),
+            summarize_after_messages: default_summarize_after_messages(),
+        }
+    }
+}
+
+/// Conversations already sent for automatic titling this session, so a
+/// missing or failing LLM is not retried on every save, and conversations
+/// with a summary checkpoint being written
+#[derive(Default)]
+pub struct AutoTitleState {
+    attempted: Mutex<HashSet<String>>,
+    summarizing: Mutex<HashSet<String>>,
+}
+
+impl AutoTitleState {
+    fn begin(&self, conversation_id: &str) -> bool {
+        self.attempted
+            .lock()
+            .map(|mut attempted| attempted.insert(conversation_id.to_string()))
+            .unwrap_or(false)
+    }
+
+    fn begin_summary(&self, conversation_id: &str) -> bool {
+        self.summarizing
+            .lock()
+            .map(|mut summarizing| summarizing.insert(conversation_id.to_string()))
+            .unwrap_or(false)
+    }
+
+    fn finish_summary(&self, conversation_id: &str) {
+        if let Ok(mut summarizing) = self.summarizing.lock() {
+            summarizing.remove(conversation_id);
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +157,27 @@ fn conversations_path(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(app_dir.join("conversations.json"))
 }
 
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(conversations_path(app)?.with_file_name("conversation_settings.json"))
+}
+
+fn read_settings(app: &AppHandle) -> ConversationSettings {
+    settings_path(app)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+/// Held across every read-modify-write of conversations.json. Commands and the
+/// background title and summary tasks run concurrently; without it the later
+/// write silently drops the earlier one's changes. Never hold it across an await.
+static CONVERSATIONS_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_conversations() -> std::sync::MutexGuard<'static, ()> {
+    CONVERSATIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
 fn read_conversations(app: &AppHandle) -> Result<Vec<ConversationRecord>, String> {
     let path = conversations_path(app)?;
     if !path.exists() {
@@ -76,9 +205,97 @@ fn write_conversations(app: &AppHandle, conversations: &[ConversationRecord]) ->
     Ok(())
 }
 
+/// Artifact ids carried by a message's artifact JSON
+fn artifact_ids(artifacts: &[serde_json::Value]) -> Vec<String> {
+    artifacts
+        .iter()
+        .filter_map(|a| a.get("id").and_then(|id| id.as_str()).map(String::from))
+        .collect()
+}
+
+/// Messages of `parent` up to and including `from_message_id`, with
+/// artifacts replaced by a reference to the conversation that stores them
+fn branch_messages(parent: &ConversationRecord, from_message_id: &str) -> Option<Vec<ConversationMessage>> {
+    let end = parent.messages.iter().position(|m| m.id == from_message_id)?;
+    Some(
+        parent.messages[..=end]
+            .iter()
+            .cloned()
+            .map(|mut message| {
+                if message.artifact_source.is_none() && message.artifacts.is_some() {
+                    message.artifact_source = Some(parent.id.clone());
+                }
+                message.artifacts = None;
+                message
+            })
+            .collect(),
+    )
+}
+
+/// Fill in artifacts of branch messages from the conversations that store them
+fn resolve_shared_artifacts(conversations: &mut [ConversationRecord]) {
+    let stored: HashMap<(String, String), Vec<serde_json::Value>> = conversations
+        .iter()
+        .flat_map(|c| {
+            c.messages
+                .iter()
+                .filter(|m| m.artifact_source.is_none())
+                .filter_map(move |m| Some(((c.id.clone(), m.id.clone()), m.artifacts.clone()?)))
+        })
+        .collect();
+
+    for message in conversations.iter_mut().flat_map(|c| c.messages.iter_mut()) {
+        if let Some(source) = &message.artifact_source {
+            message.artifacts = stored.get(&(source.clone(), message.id.clone())).cloned();
+        }
+    }
+}
+
+/// Remove a conversation without breaking its branches: the first branch
+/// referencing each of its artifacts takes ownership of them, the others are
+/// re-pointed there, and direct children become roots.
+fn detach_conversation(conversations: &mut Vec<ConversationRecord>, conversation_id: &str) {
+    let Some(pos) = conversations.iter().position(|c| c.id == conversation_id) else {
+        return;
+    };
+    let removed = conversations.remove(pos);
+
+    for message in removed.messages.iter().filter(|m| m.artifact_source.is_none()) {
+        let Some(artifacts) = &message.artifacts else { continue };
+        let mut new_owner: Option<String> = None;
+        for conv in conversations.iter_mut() {
+            let Some(copy) = conv
+                .messages
+                .iter_mut()
+                .find(|m| m.id == message.id && m.artifact_source.as_deref() == Some(conversation_id))
+            else {
+                continue;
+            };
+            match &new_owner {
+                Some(owner) => copy.artifact_source = Some(owner.clone()),
+                None => {
+                    copy.artifacts = Some(artifacts.clone());
+                    copy.artifact_source = None;
+                    new_owner = Some(conv.id.clone());
+                }
+            }
+        }
+    }
+
+    for conv in conversations.iter_mut() {
+        if conv.parent_conversation_id.as_deref() == Some(conversation_id) {
+            conv.parent_conversation_id = None;
+            conv.fork_message_id = None;
+        }
+    }
+}
+
+/// Conversations, each carrying `parentConversationId` / `forkMessageId` when
+/// it is a branch, so the UI can render the tree
 #[tauri::command]
 pub async fn load_conversations(app: AppHandle) -> Result<Vec<ConversationRecord>, String> {
     let mut conversations = read_conversations(&app)?;
+    resolve_shared_artifacts(&mut conversations);
     // Sort by updated_at descending, pinned first
     conversations.sort_by(|a, b| {
         b.pinned.cmp(&a.pinned)
@@ -90,39 +307,242 @@ pub async fn load_conversations(app: AppHandle) -> Result<Vec<ConversationRecord
 #[tauri::command]
 pub async fn save_conversation(
     app: AppHandle,
-    conversation: ConversationRecord,
+    state: State<'_, RagState>,
+    search: State<'_, ConversationSearchState>,
+    titling: State<'_, AutoTitleState>,
+    mut conversation: ConversationRecord,
 ) -> Result<(), String> {
+    // Branch messages come back with their artifacts resolved; keep storing
+    // only the reference
+    for message in &mut conversation.messages {
+        if message.artifact_source.is_some() {
+            message.artifacts = None;
+        }
+    }
+
+    let guard = lock_conversations();
     let mut conversations = read_conversations(&app)?;
     if let Some(existing) = conversations.iter_mut().find(|c| c.id == conversation.id) {
-        *existing = conversation;
+        if conversation.parent_conversation_id.is_none() {
+            conversation.parent_conversation_id = existing.parent_conversation_id.take();
+            conversation.fork_message_id = existing.fork_message_id.take();
+        }
+        // A save racing the title update still carries the placeholder
+        if conversation.title_source == TitleSource::Placeholder && existing.title_source != TitleSource::Placeholder {
+            conversation.title = existing.title.clone();
+            conversation.title_source = existing.title_source;
+        }
+        // Checkpoints are written here, not by the UI
+        if conversation.summary_checkpoint.is_none() {
+            conversation.summary_checkpoint = existing.summary_checkpoint.take();
+        }
+        *existing = conversation.clone();
     } else {
-        conversations.push(conversation);
+        conversations.push(conversation.clone());
     }
-    write_conversations(&app, &conversations)
+    write_conversations(&app, &conversations)?;
+    drop(guard);
+    search.update(|index| index.upsert(&conversation));
+
+    let settings = read_settings(&app);
+    if needs_auto_title(&conversation) && settings.auto_title && titling.begin(&conversation.id) {
+        let app = app.clone();
+        let llm = state.llm_manager.clone();
+        let conversation_id = conversation.id.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = retitle(&app, &llm, &conversation_id, false).await {
+                tracing::warn!("Automatic title for {} failed: {}", conversation_id, e);
+            }
+        });
+    }
+
+    let due = checkpoint_due(
+        conversation.messages.len(),
+        conversation.summary_checkpoint.as_ref(),
+        settings.summarize_after_messages,
+    );
+    if let Some(covered) = due {
+        if titling.begin_summary(&conversation.id) {
+            let app = app.clone();
+            let llm = state.llm_manager.clone();
+            let conversation_id = conversation.id.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = write_checkpoint(&app, &llm, &conversation, covered).await {
+                    tracing::warn!("Summary checkpoint for {} failed: {}", conversation_id, e);
+                }
+                app.state::<AutoTitleState>().finish_summary(&conversation_id);
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Whether `current` still starts with the first `covered` messages of
+/// `summarized`, compared by id, so a checkpoint built from one holds for the other
+fn covers_same_messages(summarized: &[ConversationMessage], current: &[ConversationMessage], covered: usize) -> bool {
+    summarized.len() >= covered
+        && current.len() >= covered
+        && summarized[..covered].iter().zip(&current[..covered]).all(|(a, b)| a.id == b.id)
+}
+
+/// Extend the conversation's summary checkpoint to cover its first `covered`
+/// messages and store it
+async fn write_checkpoint(
+    app: &AppHandle,
+    llm: &Arc<TokioRwLock<Option<shodh_rag::llm::LLMManager>>>,
+    conversation: &ConversationRecord,
+    covered: usize,
+) -> Result<(), String> {
+    let previous = conversation.summary_checkpoint.as_ref();
+    let start = previous.map(|c| c.covered_messages).unwrap_or(0).min(covered);
+    let turns: Vec<(String, String)> = conversation.messages[start..covered]
+        .iter()
+        .map(|m| (m.role.clone(), m.content.clone()))
+        .collect();
+
+    let manager = llm.read().await.clone();
+    let summary = summarize_checkpoint(manager.as_ref(), previous.map(|c| c.summary.as_str()), &turns).await;
+    let checkpoint = SummaryCheckpoint { summary, covered_messages: covered, updated_at: Utc::now() };
+
+    // Re-read: the conversation may have changed while the LLM was running
+    {
+        let _guard = lock_conversations();
+        let mut conversations = read_conversations(app)?;
+        let Some(conv) = conversations.iter_mut().find(|c| c.id == conversation.id) else {
+            return Err(format!("Conversation not found: {}", conversation.id));
+        };
+        if !covers_same_messages(&conversation.messages, &conv.messages, covered) {
+            return Err("Conversation changed while summarizing".to_string());
+        }
+        conv.summary_checkpoint = Some(checkpoint.clone());
+        write_conversations(app, &conversations)?;
+    }
+
+    let _ = app.emit("conversation_summary_updated", serde_json::json!({
+        "conversationId": conversation.id,
+        "summaryCheckpoint": checkpoint,
+    }));
+    tracing::info!("Summarized first {} messages of conversation {}", covered, conversation.id);
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn delete_conversation(
     app: AppHandle,
+    state: State<'_, RagState>,
+    search: State<'_, ConversationSearchState>,
     conversation_id: String,
 ) -> Result<(), String> {
-    let mut conversations = read_conversations(&app)?;
-    conversations.retain(|c| c.id != conversation_id);
-    write_conversations(&app, &conversations)
+    {
+        let _guard = lock_conversations();
+        let mut conversations = read_conversations(&app)?;
+        detach_conversation(&mut conversations, &conversation_id);
+        write_conversations(&app, &conversations)?;
+    }
+    search.update(|index| index.remove_conversation(&conversation_id));
+    if let Some(manager) = state.llm_manager.read().await.as_ref() {
+        manager.forget_conversation(&conversation_id);
+    }
+    Ok(())
+}
+
+/// Start a new conversation from `from_message_id` (inclusive) of an existing
+/// one. Copied messages reference the parent's artifacts rather than
+/// duplicating them, in both the conversation file and the artifact store.
+#[tauri::command]
+pub async fn fork_conversation(
+    app: AppHandle,
+    state: State<'_, RagState>,
+    search: State<'_, ConversationSearchState>,
+    conversation_id: String,
+    from_message_id: String,
+) -> Result<ConversationRecord, String> {
+    let (mut fork, mut conversations) = {
+        let _guard = lock_conversations();
+        let mut conversations = read_conversations(&app)?;
+        let parent = conversations
+            .iter()
+            .find(|c| c.id == conversation_id)
+            .ok_or_else(|| format!("Conversation not found: {}", conversation_id))?;
+        let messages = branch_messages(parent, &from_message_id)
+            .ok_or_else(|| format!("Message {} not found in conversation {}", from_message_id, conversation_id))?;
+
+        let messages_len = messages.len();
+        let now = Utc::now().to_rfc3339();
+        let fork = ConversationRecord {
+            id: format!("conv-{}", uuid::Uuid::new_v4()),
+            title: format!("{} (branch)", parent.title),
+            messages,
+            created_at: now.clone(),
+            updated_at: now,
+            pinned: false,
+            space_id: parent.space_id.clone(),
+            space_name: parent.space_name.clone(),
+            system_prompt: parent.system_prompt.clone(),
+            parent_conversation_id: Some(conversation_id.clone()),
+            fork_message_id: Some(from_message_id),
+            // Keep the "(branch)" suffix on a title that is already final
+            title_source: match parent.title_source {
+                TitleSource::Placeholder => TitleSource::Placeholder,
+                _ => TitleSource::Generated,
+            },
+            // Still accurate when it only covers copied messages
+            summary_checkpoint: parent
+                .summary_checkpoint
+                .clone()
+                .filter(|c| c.covered_messages <= messages_len),
+        };
+
+        conversations.push(fork.clone());
+        write_conversations(&app, &conversations)?;
+        (fork, conversations)
+    };
+    search.update(|index| index.upsert(&fork));
+
+    resolve_shared_artifacts(&mut conversations);
+    if let Some(resolved) = conversations.pop() {
+        fork = resolved;
+    }
+    let shared: Vec<String> = fork
+        .messages
+        .iter()
+        .filter_map(|m| m.artifacts.as_deref())
+        .flat_map(artifact_ids)
+        .collect();
+    if !shared.is_empty() {
+        state.artifact_store.write().await.link_artifacts(&fork.id, shared);
+    }
+    // The conversation continues in the branch; free the parent's cached
+    // prompts rather than let them hold the cache budget
+    if let Some(manager) = state.llm_manager.read().await.as_ref() {
+        manager.forget_conversation(&conversation_id);
+    }
+    tracing::info!(
+        "Forked conversation {} at message {:?} into {}",
+        conversation_id, fork.fork_message_id, fork.id
+    );
+    Ok(fork)
 }
 
 #[tauri::command]
 pub async fn rename_conversation(
     app: AppHandle,
+    search: State<'_, ConversationSearchState>,
     conversation_id: String,
     new_title: String,
 ) -> Result<(), String> {
-    let mut conversations = read_conversations(&app)?;
-    if let Some(conv) = conversations.iter_mut().find(|c| c.id == conversation_id) {
-        conv.title = new_title;
-        conv.updated_at = Utc::now().to_rfc3339();
+    {
+        let _guard = lock_conversations();
+        let mut conversations = read_conversations(&app)?;
+        if let Some(conv) = conversations.iter_mut().find(|c| c.id == conversation_id) {
+            conv.title = new_title.clone();
+            conv.title_source = TitleSource::User;
+            conv.updated_at = Utc::now().to_rfc3339();
+        }
+        write_conversations(&app, &conversations)?;
     }
-    write_conversations(&app, &conversations)
+    search.update(|index| index.rename(&conversation_id, &new_title));
+    Ok(())
 }
 
 #[tauri::command]
@@ -131,6 +551,7 @@ pub async fn pin_conversation(
     conversation_id: String,
     pinned: bool,
 ) -> Result<(), String> {
+    let _guard = lock_conversations();
     let mut conversations = read_conversations(&app)?;
     if let Some(conv) = conversations.iter_mut().find(|c| c.id == conversation_id) {
         conv.pinned = pinned;
@@ -138,3 +559,293 @@ pub async fn pin_conversation(
     }
     write_conversations(&app, &conversations)
 }
+
+/// Case-insensitive keyword search across all conversation messages
+#[tauri::command]
+pub async fn search_conversations(
+    app: AppHandle,
+    search: State<'_, ConversationSearchState>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<ConversationSearchHit>, String> {
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).min(MAX_SEARCH_LIMIT);
+    let mut guard = search.index.lock().map_err(|e| format!("Search index lock poisoned: {}", e))?;
+    if guard.is_none() {
+        let conversations = read_conversations(&app)?;
+        tracing::info!("Indexing {} conversations for search", conversations.len());
+        *guard = Some(ConversationIndex::build(&conversations));
+    }
+    Ok(guard.as_ref().map(|index| index.search(&query, limit)).unwrap_or_default())
+}
+
+/// Whether the first user+assistant exchange is complete and the title is
+/// still a placeholder
+fn needs_auto_title(conversation: &ConversationRecord) -> bool {
+    let answered = |role: &str| conversation.messages.iter().any(|m| m.role == role && !m.content.trim().is_empty());
+    conversation.title_source == TitleSource::Placeholder && answered("user") && answered("assistant")
+}
+
+fn title_prompt(conversation: &ConversationRecord) -> Option<String> {
+    let first = |role: &str| {
+        conversation
+            .messages
+            .iter()
+            .find(|m| m.role == role)
+            .map(|m| m.content.chars().take(TITLE_CONTEXT_CHARS).collect::<String>())
+    };
+    let question = first("user")?;
+    let answer = first("assistant").unwrap_or_default();
+    Some(format!(
+        "Write a concise title of 3 to 6 words for this conversation. \
+         Reply with the title only: no quotes, no trailing punctuation.\n\n\
+         User: {}\n\nAssistant: {}\n\nTitle:",
+        question, answer
+    ))
+}
+
+/// First line of the LLM reply without labels, quotes or trailing punctuation
+fn clean_title(raw: &str) -> Option<String> {
+    let line = raw.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let line = line
+        .strip_prefix("Title:")
+        .or_else(|| line.strip_prefix("title:"))
+        .unwrap_or(line);
+    let line = line
+        .trim()
+        .trim_matches(|c: char| matches!(c, '"' | '\'' | '*' | '#' | '`' | '“' | '”'))
+        .trim_end_matches(['.', '!', ':', ';', ','])
+        .trim();
+    let title = line.split_whitespace().take(TITLE_MAX_WORDS).collect::<Vec<_>>().join(" ");
+    (!title.is_empty()).then_some(title)
+}
+
+/// Ask the LLM for a title and store it. A title the user set in the
+/// meantime is kept unless `force`.
+async fn retitle(
+    app: &AppHandle,
+    llm: &Arc<TokioRwLock<Option<shodh_rag::llm::LLMManager>>>,
+    conversation_id: &str,
+    force: bool,
+) -> Result<String, String> {
+    let prompt = read_conversations(app)?
+        .iter()
+        .find(|c| c.id == conversation_id)
+        .ok_or_else(|| format!("Conversation not found: {}", conversation_id))
+        .and_then(|c| title_prompt(c).ok_or_else(|| "Conversation has no user message yet".to_string()))?;
+
+    let manager = llm
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| "LLM not initialized".to_string())?;
+    let raw = manager
+        .generate_custom(&prompt, TITLE_MAX_TOKENS)
+        .await
+        .map_err(|e| format!("Failed to generate title: {}", e))?;
+    let title = clean_title(&raw).ok_or_else(|| "LLM returned an empty title".to_string())?;
+
+    // Re-read: the conversation may have changed while the LLM was running
+    {
+        let _guard = lock_conversations();
+        let mut conversations = read_conversations(app)?;
+        let Some(conv) = conversations.iter_mut().find(|c| c.id == conversation_id) else {
+            return Err(format!("Conversation not found: {}", conversation_id));
+        };
+        if conv.title_source == TitleSource::User && !force {
+            return Ok(conv.title.clone());
+        }
+        conv.title = title.clone();
+        conv.title_source = TitleSource::Generated;
+        write_conversations(app, &conversations)?;
+    }
+
+    if let Some(search) = app.try_state::<ConversationSearchState>() {
+        search.update(|index| index.rename(conversation_id, &title));
+    }
+    let _ = app.emit("conversation_title_updated", serde_json::json!({
+        "conversationId": conversation_id,
+        "title": title,
+        "titleSource": TitleSource::Generated,
+    }));
+    tracing::info!("Titled conversation {}: {}", conversation_id, title);
+    Ok(title)
+}
+
+/// Regenerate a conversation's title with the LLM, replacing any title
+#[tauri::command]
+pub async fn retitle_conversation(
+    app: AppHandle,
+    state: State<'_, RagState>,
+    conversation_id: String,
+) -> Result<String, String> {
+    retitle(&app, &state.llm_manager, &conversation_id, true).await
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationExport {
+    pub file_name: String,
+    pub format: String,
+    /// Markdown text, or the base64-encoded PDF
+    pub content: String,
+}
+
+/// Export one conversation as "markdown" or "pdf"
+#[tauri::command]
+pub async fn export_conversation(
+    app: AppHandle,
+    conversation_id: String,
+    format: String,
+) -> Result<ConversationExport, String> {
+    let mut conversations = read_conversations(&app)?;
+    resolve_shared_artifacts(&mut conversations);
+    let conversation = conversations
+        .into_iter()
+        .find(|c| c.id == conversation_id)
+        .ok_or_else(|| format!("Conversation not found: {}", conversation_id))?;
+
+    let markdown = crate::conversation_export::conversation_to_markdown(&conversation);
+    let stem: String = conversation
+        .title
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let stem = if stem.trim_matches('_').is_empty() { "conversation".to_string() } else { stem };
+
+    match format.as_str() {
+        "markdown" | "md" => Ok(ConversationExport {
+            file_name: format!("{}.md", stem),
+            format: "markdown".to_string(),
+            content: markdown,
+        }),
+        "pdf" => {
+            let title = conversation.title.clone();
+            let pdf = tokio::task::spawn_blocking(move || crate::doc_gen_commands::render_markdown_pdf(&title, &markdown))
+                .await
+                .map_err(|e| format!("PDF task failed: {}", e))??;
+            Ok(ConversationExport {
+                file_name: format!("{}.pdf", stem),
+                format: "pdf".to_string(),
+                content: base64::engine::general_purpose::STANDARD.encode(pdf),
+            })
+        }
+        other => Err(format!("Unsupported export format: {} (expected markdown or pdf)", other)),
+    }
+}
+
+#[tauri::command]
+pub async fn get_conversation_settings(app: AppHandle) -> Result<ConversationSettings, String> {
+    Ok(read_settings(&app))
+}
+
+#[tauri::command]
+pub async fn set_conversation_settings(app: AppHandle, settings: ConversationSettings) -> Result<(), String> {
+    let data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize conversation settings: {}", e))?;
+    fs::write(settings_path(&app)?, data)
+        .map_err(|e| format!("Failed to write conversation settings: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(id: &str, artifacts: Option<Vec<serde_json::Value>>) -> ConversationMessage {
+        ConversationMessage {
+            id: id.to_string(),
+            role: "assistant".to_string(),
+            content: format!("message {}", id),
+            timestamp: String::new(),
+            artifacts,
+            search_results: None,
+            artifact_source: None,
+            metadata: None,
+        }
+    }
+
+    fn conversation(id: &str, messages: Vec<ConversationMessage>) -> ConversationRecord {
+        ConversationRecord {
+            id: id.to_string(),
+            title: id.to_string(),
+            messages,
+            created_at: String::new(),
+            updated_at: String::new(),
+            pinned: false,
+            space_id: None,
+            space_name: None,
+            system_prompt: None,
+            parent_conversation_id: None,
+            fork_message_id: None,
+            title_source: TitleSource::Placeholder,
+            summary_checkpoint: None,
+        }
+    }
+
+    #[test]
+    fn test_auto_title_waits_for_first_exchange_and_cleans_reply() {
+        let mut user = message("m1", None);
+        user.role = "user".to_string();
+        let mut conv = conversation("c", vec![user]);
+        assert!(!needs_auto_title(&conv));
+        conv.messages.push(message("m2", None));
+        assert!(needs_auto_title(&conv));
+        assert!(title_prompt(&conv).unwrap().contains("User: message m1"));
+        conv.title_source = TitleSource::User;
+        assert!(!needs_auto_title(&conv));
+
+        assert_eq!(clean_title("\n  Title: \"Rust Async Error Handling.\"\nextra"), Some("Rust Async Error Handling".to_string()));
+        assert_eq!(clean_title("**Comparing Vector Databases**"), Some("Comparing Vector Databases".to_string()));
+        assert_eq!(clean_title("  \n"), None);
+    }
+
+    #[test]
+    fn test_checkpoint_requires_the_summarized_messages() {
+        let summarized = vec![message("m1", None), message("m2", None), message("m3", None)];
+        let grown = vec![message("m1", None), message("m2", None), message("m3", None), message("m4", None)];
+        assert!(covers_same_messages(&summarized, &grown, 3));
+
+        // Same length, but a message was deleted and another appended
+        let replaced = vec![message("m1", None), message("m3", None), message("m4", None)];
+        assert!(!covers_same_messages(&summarized, &replaced, 3));
+        assert!(covers_same_messages(&summarized, &replaced, 1));
+        assert!(!covers_same_messages(&summarized, &grown[..2], 3));
+    }
+
+    #[test]
+    fn test_branches_reference_artifacts_and_survive_parent_deletion() {
+        let chart = json!({ "id": "artifact-1", "content": "graph TD; A-->B" });
+        let root = conversation("root", vec![
+            message("m1", None),
+            message("m2", Some(vec![chart.clone()])),
+            message("m3", None),
+        ]);
+
+        let messages = branch_messages(&root, "m2").unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[1].artifacts.is_none());
+        assert_eq!(messages[1].artifact_source.as_deref(), Some("root"));
+        assert_eq!(artifact_ids(std::slice::from_ref(&chart)), vec!["artifact-1".to_string()]);
+        assert!(branch_messages(&root, "missing").is_none());
+
+        let mut branch = conversation("branch", messages);
+        branch.parent_conversation_id = Some("root".to_string());
+        branch.fork_message_id = Some("m2".to_string());
+        // A branch of the branch still points at the stored copy
+        let nested = conversation("nested", branch_messages(&branch, "m2").unwrap());
+        assert_eq!(nested.messages[1].artifact_source.as_deref(), Some("root"));
+
+        let mut conversations = vec![root, branch, nested];
+        let mut resolved = conversations.clone();
+        resolve_shared_artifacts(&mut resolved);
+        assert_eq!(resolved[2].messages[1].artifacts, Some(vec![chart.clone()]));
+
+        detach_conversation(&mut conversations, "root");
+        assert_eq!(conversations.len(), 2);
+        assert_eq!(conversations[0].messages[1].artifacts, Some(vec![chart.clone()]));
+        assert!(conversations[0].parent_conversation_id.is_none());
+        assert_eq!(conversations[1].messages[1].artifact_source.as_deref(), Some("branch"));
+        resolve_shared_artifacts(&mut conversations);
+        assert_eq!(conversations[1].messages[1].artifacts, Some(vec![chart]));
+    }
+}
@@ -0,0 +1,262 @@
+//! Single-conversation export to Markdown (and PDF via doc-gen)
+//!
+//! Messages become timestamped sections; assistant headings carry the model
+//! from the saved response metadata. Artifacts not already in the message
+//! text are appended as fenced blocks (```mermaid, ```chart, ```<language>)
+//! so they re-render in other Markdown viewers. Cited search results become
+//! footnote markers on the message and one numbered reference section at the
+//! end, shared across messages.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::conversation_commands::{ConversationMessage, ConversationRecord};
+
+/// One entry of the reference section
+struct Reference {
+    title: String,
+    source: String,
+    page: Option<String>,
+    url: Option<String>,
+    authors: Vec<String>,
+    year: Option<String>,
+}
+
+impl Reference {
+    fn from_search_result(result: &Value) -> Option<Self> {
+        let citation = result.get("citation").filter(|c| !c.is_null());
+        let text = |v: Option<&Value>| v.and_then(Value::as_str).map(str::trim).filter(|s| !s.is_empty()).map(String::from);
+
+        let source = text(result.get("sourceFile").or_else(|| result.get("source_file")))
+            .or_else(|| text(citation.and_then(|c| c.get("source"))))?;
+        let title = text(citation.and_then(|c| c.get("title")))
+            .unwrap_or_else(|| source.rsplit(['/', '\\']).next().unwrap_or(&source).to_string());
+        Some(Self {
+            title,
+            page: text(result.get("pageNumber").or_else(|| result.get("page_number")))
+                .or_else(|| text(citation.and_then(|c| c.get("pageNumbers")))),
+            url: text(citation.and_then(|c| c.get("url"))),
+            authors: citation
+                .and_then(|c| c.get("authors"))
+                .and_then(Value::as_array)
+                .map(|a| a.iter().filter_map(|v| text(Some(v))).collect())
+                .unwrap_or_default(),
+            year: text(citation.and_then(|c| c.get("year"))),
+            source,
+        })
+    }
+
+    fn key(&self) -> (String, Option<String>) {
+        (self.source.clone(), self.page.clone())
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut line = format!("**{}**", self.title);
+        if !self.authors.is_empty() {
+            line.push_str(&format!(", {}", self.authors.join(", ")));
+        }
+        if let Some(year) = &self.year {
+            line.push_str(&format!(" ({})", year));
+        }
+        line.push_str(&format!(". `{}`", self.source));
+        if let Some(page) = &self.page {
+            line.push_str(&format!(", p. {}", page));
+        }
+        if let Some(url) = &self.url {
+            line.push_str(&format!(". <{}>", url));
+        }
+        line
+    }
+}
+
+/// Lowercase artifact type from either the backend (`"mermaid"`) or the
+/// frontend extractor (`{ "Chart": null }`) encoding
+fn artifact_kind(artifact: &Value) -> String {
+    let raw = artifact.get("artifact_type").or_else(|| artifact.get("artifactType")).or_else(|| artifact.get("type"));
+    match raw {
+        Some(Value::String(s)) => s.to_lowercase(),
+        Some(Value::Object(map)) => map.keys().next().map(|k| k.to_lowercase()).unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// A fence longer than any backtick run inside `content`
+fn fence_for(content: &str) -> String {
+    let longest = content
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn artifact_markdown(artifact: &Value) -> Option<String> {
+    let content = artifact.get("content").and_then(Value::as_str)?.trim();
+    if content.is_empty() {
+        return None;
+    }
+    let title = artifact.get("title").and_then(Value::as_str).unwrap_or("Artifact");
+    let kind = artifact_kind(artifact);
+    let info = match kind.as_str() {
+        // Tables and markdown render as they are
+        "markdown" | "table" => return Some(format!("**{}**\n\n{}", title, content)),
+        "code" => artifact.get("language").and_then(Value::as_str).unwrap_or("").to_string(),
+        other => other.to_string(),
+    };
+    let fence = fence_for(content);
+    Some(format!("**{}**\n\n{}{}\n{}\n{}", title, fence, info, content, fence))
+}
+
+fn role_label(role: &str) -> &str {
+    match role {
+        "user" => "User",
+        "assistant" => "Assistant",
+        "system" => "System",
+        other => other,
+    }
+}
+
+fn message_heading(message: &ConversationMessage) -> String {
+    let mut heading = format!("### {}", role_label(&message.role));
+    let model = message
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get("model"))
+        .and_then(Value::as_str)
+        .filter(|m| !m.is_empty());
+    if let Some(model) = model {
+        heading.push_str(&format!(" · {}", model));
+    }
+    if !message.timestamp.is_empty() {
+        heading.push_str(&format!(" · {}", message.timestamp));
+    }
+    heading
+}
+
+/// Render the conversation as a Markdown document
+pub fn conversation_to_markdown(conversation: &ConversationRecord) -> String {
+    let mut out = format!("# {}\n\n", conversation.title);
+    out.push_str(&format!("- Created: {}\n- Updated: {}\n", conversation.created_at, conversation.updated_at));
+    if let Some(space) = &conversation.space_name {
+        out.push_str(&format!("- Space: {}\n", space));
+    }
+    out.push('\n');
+
+    let mut references: Vec<Reference> = Vec::new();
+    let mut reference_index: HashMap<(String, Option<String>), usize> = HashMap::new();
+
+    for message in &conversation.messages {
+        out.push_str(&message_heading(message));
+        out.push_str("\n\n");
+        out.push_str(message.content.trim());
+        out.push_str("\n\n");
+
+        for artifact in message.artifacts.iter().flatten() {
+            let already_inline = artifact
+                .get("content")
+                .and_then(Value::as_str)
+                .map(|c| !c.trim().is_empty() && message.content.contains(c.trim()))
+                .unwrap_or(false);
+            if already_inline {
+                continue;
+            }
+            if let Some(block) = artifact_markdown(artifact) {
+                out.push_str(&block);
+                out.push_str("\n\n");
+            }
+        }
+
+        let mut markers: Vec<usize> = Vec::new();
+        for reference in message.search_results.iter().flatten().filter_map(Reference::from_search_result) {
+            let number = *reference_index.entry(reference.key()).or_insert_with(|| {
+                references.push(reference);
+                references.len()
+            });
+            if !markers.contains(&number) {
+                markers.push(number);
+            }
+        }
+        if !markers.is_empty() {
+            let cited: Vec<String> = markers.iter().map(|n| format!("[^{}]", n)).collect();
+            out.push_str(&format!("Sources: {}\n\n", cited.join(" ")));
+        }
+    }
+
+    if !references.is_empty() {
+        out.push_str("## References\n\n");
+        for (i, reference) in references.iter().enumerate() {
+            out.push_str(&format!("[^{}]: {}\n", i + 1, reference.to_markdown()));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(role: &str, content: &str) -> ConversationMessage {
+        ConversationMessage {
+            id: format!("{}-{}", role, content.len()),
+            role: role.to_string(),
+            content: content.to_string(),
+            timestamp: "2026-03-01T10:00:00Z".to_string(),
+            artifacts: None,
+            search_results: None,
+            artifact_source: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_markdown_embeds_artifacts_and_shares_references() {
+        let lease = json!({
+            "sourceFile": "/docs/lease.pdf",
+            "pageNumber": "4",
+            "citation": { "title": "Office Lease", "authors": ["Acme Corp"], "year": "2024" }
+        });
+        let mut first = message("assistant", "The lease ends in 2027.\n\n```chart\n{\"type\":\"bar\"}\n```");
+        first.metadata = Some(json!({ "model": "gpt-4o", "durationMs": 1200 }));
+        first.search_results = Some(vec![lease.clone(), json!({ "sourceFile": "/docs/memo.txt" })]);
+        first.artifacts = Some(vec![
+            json!({ "artifact_type": { "Chart": null }, "title": "Rent", "content": "{\"type\":\"bar\"}" }),
+            json!({ "artifact_type": "mermaid", "title": "Timeline", "content": "gantt\n  title Lease" }),
+            json!({ "artifact_type": "code", "language": "rust", "title": "Fences", "content": "let s = \"```\";" }),
+        ]);
+        let mut second = message("assistant", "Renewal is on page 4.");
+        second.search_results = Some(vec![lease]);
+
+        let conversation = ConversationRecord {
+            id: "c".to_string(),
+            title: "Lease questions".to_string(),
+            messages: vec![message("user", "When does the lease end?"), first, second],
+            created_at: "2026-03-01T09:59:00Z".to_string(),
+            updated_at: "2026-03-01T10:01:00Z".to_string(),
+            pinned: false,
+            space_id: None,
+            space_name: Some("Legal".to_string()),
+            system_prompt: None,
+            parent_conversation_id: None,
+            fork_message_id: None,
+            title_source: Default::default(),
+            summary_checkpoint: None,
+        };
+        let md = conversation_to_markdown(&conversation);
+
+        assert!(md.starts_with("# Lease questions\n"));
+        assert!(md.contains("### Assistant · gpt-4o · 2026-03-01T10:00:00Z"));
+        // The chart is already in the text; the others are appended
+        assert_eq!(md.matches("```chart").count(), 1);
+        assert!(md.contains("```mermaid\ngantt\n  title Lease\n```"));
+        assert!(md.contains("````rust\nlet s = \"```\";\n````"));
+        // Same source and page cited twice shares one reference
+        assert!(md.contains("Sources: [^1] [^2]\n"));
+        assert!(md.contains("Renewal is on page 4.\n\nSources: [^1]\n"));
+        assert!(md.ends_with(
+            "## References\n\n\
+             [^1]: **Office Lease**, Acme Corp (2024). `/docs/lease.pdf`, p. 4\n\
+             [^2]: **memo.txt**. `/docs/memo.txt`\n"
+        ));
+    }
+}
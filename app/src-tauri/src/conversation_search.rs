@@ -0,0 +1,380 @@
+//! Full-text search over saved conversations
+//!
+//! The index is built from `conversations.json` on the first search and then
+//! kept current by the conversation commands, which re-tokenize only the
+//! messages whose content changed. Each query term must prefix-match a word
+//! of the message (CJK text is indexed per character); hits are ranked by
+//! exact-phrase match and term frequency, newest first on ties.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+
+use crate::conversation_commands::ConversationRecord;
+
+/// Characters of context kept on each side of the first match
+const SNIPPET_CONTEXT_CHARS: usize = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationSearchHit {
+    pub conversation_id: String,
+    pub conversation_title: String,
+    pub message_id: String,
+    pub role: String,
+    pub timestamp: String,
+    /// HTML-escaped excerpt with matches wrapped in `<mark>`
+    pub snippet: String,
+    pub score: f32,
+}
+
+type MessageKey = (String, String);
+
+struct IndexedMessage {
+    role: String,
+    timestamp: String,
+    content: String,
+    tokens: HashSet<String>,
+}
+
+#[derive(Default)]
+pub struct ConversationIndex {
+    messages: HashMap<MessageKey, IndexedMessage>,
+    postings: BTreeMap<String, HashSet<MessageKey>>,
+    titles: HashMap<String, String>,
+}
+
+impl ConversationIndex {
+    pub fn build(conversations: &[ConversationRecord]) -> Self {
+        let mut index = Self::default();
+        for conversation in conversations {
+            index.upsert(conversation);
+        }
+        index
+    }
+
+    /// Bring one conversation's entries in line with `conversation`
+    pub fn upsert(&mut self, conversation: &ConversationRecord) {
+        self.titles.insert(conversation.id.clone(), conversation.title.clone());
+
+        let current: HashSet<&str> = conversation.messages.iter().map(|m| m.id.as_str()).collect();
+        let stale: Vec<MessageKey> = self
+            .messages
+            .keys()
+            .filter(|(conv, msg)| *conv == conversation.id && !current.contains(msg.as_str()))
+            .cloned()
+            .collect();
+        for key in stale {
+            self.remove_message(&key);
+        }
+
+        for message in &conversation.messages {
+            let key = (conversation.id.clone(), message.id.clone());
+            if let Some(existing) = self.messages.get_mut(&key) {
+                if existing.content == message.content {
+                    existing.timestamp = message.timestamp.clone();
+                    continue;
+                }
+                self.remove_message(&key);
+            }
+
+            let tokens = tokenize(&message.content);
+            for token in &tokens {
+                self.postings.entry(token.clone()).or_default().insert(key.clone());
+            }
+            self.messages.insert(key, IndexedMessage {
+                role: message.role.clone(),
+                timestamp: message.timestamp.clone(),
+                content: message.content.clone(),
+                tokens,
+            });
+        }
+    }
+
+    pub fn remove_conversation(&mut self, conversation_id: &str) {
+        self.titles.remove(conversation_id);
+        let keys: Vec<MessageKey> = self.messages.keys().filter(|(conv, _)| conv == conversation_id).cloned().collect();
+        for key in keys {
+            self.remove_message(&key);
+        }
+    }
+
+    pub fn rename(&mut self, conversation_id: &str, title: &str) {
+        if let Some(existing) = self.titles.get_mut(conversation_id) {
+            *existing = title.to_string();
+        }
+    }
+
+    fn remove_message(&mut self, key: &MessageKey) {
+        let Some(message) = self.messages.remove(key) else { return };
+        for token in message.tokens {
+            if let Some(keys) = self.postings.get_mut(&token) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    self.postings.remove(&token);
+                }
+            }
+        }
+    }
+
+    /// Messages matching every term of `query`, best first
+    pub fn search(&self, query: &str, limit: usize) -> Vec<ConversationSearchHit> {
+        let terms: Vec<String> = tokenize_ordered(query);
+        if terms.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+
+        let mut candidates: Option<HashSet<&MessageKey>> = None;
+        for term in &terms {
+            let matching: HashSet<&MessageKey> = self
+                .postings
+                .range::<str, _>((std::ops::Bound::Included(term.as_str()), std::ops::Bound::Unbounded))
+                .take_while(|(token, _)| token.starts_with(term.as_str()))
+                .flat_map(|(_, keys)| keys.iter())
+                .collect();
+            candidates = Some(match candidates {
+                Some(previous) => previous.intersection(&matching).copied().collect(),
+                None => matching,
+            });
+        }
+
+        let phrase = terms_phrase(query);
+        let mut hits: Vec<ConversationSearchHit> = candidates
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|key| {
+                let message = self.messages.get(key)?;
+                let occurrences: usize = terms.iter().map(|t| find_all(&message.content, t).len()).sum();
+                let phrase_bonus = if terms.len() > 1 && find_first(&message.content, &phrase).is_some() { 10.0 } else { 0.0 };
+                Some(ConversationSearchHit {
+                    conversation_id: key.0.clone(),
+                    conversation_title: self.titles.get(&key.0).cloned().unwrap_or_default(),
+                    message_id: key.1.clone(),
+                    role: message.role.clone(),
+                    timestamp: message.timestamp.clone(),
+                    snippet: snippet(&message.content, &phrase, &terms),
+                    score: phrase_bonus + occurrences as f32,
+                })
+            })
+            .collect();
+
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| b.timestamp.cmp(&a.timestamp)));
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// Lazily built index shared by the conversation commands
+#[derive(Default)]
+pub struct ConversationSearchState {
+    pub index: Mutex<Option<ConversationIndex>>,
+}
+
+impl ConversationSearchState {
+    /// Apply `update` if the index has been built; an unbuilt index picks the
+    /// change up from disk when it is first searched
+    pub fn update(&self, update: impl FnOnce(&mut ConversationIndex)) {
+        if let Ok(mut guard) = self.index.lock() {
+            if let Some(index) = guard.as_mut() {
+                update(index);
+            }
+        }
+    }
+}
+
+fn is_cjk_char(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'   // Hiragana, Katakana
+        | '\u{3400}'..='\u{4DBF}' // CJK Extension A
+        | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+        | '\u{AC00}'..='\u{D7AF}' // Hangul syllables
+        | '\u{F900}'..='\u{FAFF}' // CJK Compatibility Ideographs
+    )
+}
+
+/// Lowercased words, with CJK characters as single-character tokens
+fn tokenize_ordered(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    for c in text.chars() {
+        if is_cjk_char(c) {
+            if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+            tokens.push(c.to_string());
+        } else if c.is_alphanumeric() {
+            word.extend(c.to_lowercase());
+        } else if !word.is_empty() {
+            tokens.push(std::mem::take(&mut word));
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(word);
+    }
+    tokens
+}
+
+/// Query lowercased with whitespace collapsed, for exact-phrase matching
+fn terms_phrase(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn tokenize(text: &str) -> HashSet<String> {
+    tokenize_ordered(text).into_iter().collect()
+}
+
+/// Byte length of the prefix of `haystack` equal to `needle` (already
+/// lowercase) when compared case-insensitively
+fn match_len(haystack: &str, needle: &str) -> Option<usize> {
+    let mut wanted = needle.chars().peekable();
+    let mut lowered = Vec::new();
+    for (offset, c) in haystack.char_indices() {
+        lowered.clear();
+        lowered.extend(c.to_lowercase());
+        for l in &lowered {
+            if wanted.next() != Some(*l) {
+                return None;
+            }
+        }
+        if wanted.peek().is_none() {
+            return Some(offset + c.len_utf8());
+        }
+    }
+    None
+}
+
+fn find_first(haystack: &str, needle: &str) -> Option<(usize, usize)> {
+    if needle.is_empty() {
+        return None;
+    }
+    haystack
+        .char_indices()
+        .find_map(|(start, _)| match_len(&haystack[start..], needle).map(|len| (start, start + len)))
+}
+
+/// Non-overlapping case-insensitive matches as byte ranges
+fn find_all(haystack: &str, needle: &str) -> Vec<(usize, usize)> {
+    let mut matches = Vec::new();
+    let mut from = 0;
+    while let Some((start, end)) = find_first(&haystack[from..], needle) {
+        matches.push((from + start, from + end));
+        from += end;
+    }
+    matches
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Excerpt around the phrase (or first term) with every term highlighted
+fn snippet(content: &str, phrase: &str, terms: &[String]) -> String {
+    let anchor = find_first(content, phrase)
+        .or_else(|| terms.iter().find_map(|t| find_first(content, t)))
+        .unwrap_or((0, 0));
+
+    let start = content[..anchor.0]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT_CHARS.saturating_sub(1))
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let end = content[anchor.1..]
+        .char_indices()
+        .nth(SNIPPET_CONTEXT_CHARS)
+        .map(|(i, _)| anchor.1 + i)
+        .unwrap_or(content.len());
+    let window = &content[start..end];
+
+    // The phrase wins over the terms inside it
+    let mut ranges: Vec<(usize, usize)> = std::iter::once(phrase)
+        .chain(terms.iter().map(String::as_str))
+        .flat_map(|t| find_all(window, t))
+        .collect();
+    ranges.sort_by_key(|&(s, e)| (s, std::cmp::Reverse(e)));
+    let mut out = String::new();
+    if start > 0 {
+        out.push('…');
+    }
+    let mut cursor = 0;
+    for (s, e) in ranges {
+        if s < cursor {
+            continue;
+        }
+        out.push_str(&escape_html(&window[cursor..s]));
+        out.push_str("<mark>");
+        out.push_str(&escape_html(&window[s..e]));
+        out.push_str("</mark>");
+        cursor = e;
+    }
+    out.push_str(&escape_html(&window[cursor..]));
+    if end < content.len() {
+        out.push('…');
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation_commands::ConversationMessage;
+
+    fn conversation(id: &str, title: &str, messages: &[(&str, &str)]) -> ConversationRecord {
+        ConversationRecord {
+            id: id.to_string(),
+            title: title.to_string(),
+            messages: messages
+                .iter()
+                .map(|(msg_id, content)| ConversationMessage {
+                    id: msg_id.to_string(),
+                    role: "user".to_string(),
+                    content: content.to_string(),
+                    timestamp: format!("2026-01-01T00:00:0{}Z", msg_id.len()),
+                    artifacts: None,
+                    search_results: None,
+                    artifact_source: None,
+                    metadata: None,
+                })
+                .collect(),
+            created_at: String::new(),
+            updated_at: String::new(),
+            pinned: false,
+            space_id: None,
+            space_name: None,
+            system_prompt: None,
+            parent_conversation_id: None,
+            fork_message_id: None,
+            title_source: Default::default(),
+            summary_checkpoint: None,
+        }
+    }
+
+    #[test]
+    fn test_search_ranks_highlights_and_updates_incrementally() {
+        let mut index = ConversationIndex::build(&[
+            conversation("c1", "Vectors", &[("m1", "How do I tune the Vector Store for <large> corpora?")]),
+            conversation("c2", "Misc", &[("m1", "The store closes at nine; vector art is unrelated.")]),
+        ]);
+
+        let hits = index.search("vector store", 10);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].conversation_id, "c1");
+        assert_eq!(hits[0].conversation_title, "Vectors");
+        assert_eq!(
+            hits[0].snippet,
+            "How do I tune the <mark>Vector Store</mark> for &lt;large&gt; corpora?"
+        );
+        assert!(hits[0].score > hits[1].score);
+        // Terms prefix-match words
+        assert_eq!(index.search("corp", 10).len(), 1);
+
+        // Editing a message re-indexes only that message; removed ones drop out
+        index.upsert(&conversation("c2", "Misc", &[("m2", "检索增强生成的向量数据库")]));
+        assert_eq!(index.search("vector", 10).len(), 1);
+        assert_eq!(index.search("向量", 10)[0].snippet, "检索增强生成的<mark>向量</mark>数据库");
+
+        index.remove_conversation("c1");
+        assert!(index.search("vector", 10).is_empty());
+        assert!(index.search("  ", 10).is_empty());
+    }
+}
@@ -3,8 +3,10 @@
 use tauri::State;
 use crate::rag_commands::RagState;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::io::Write;
+use shodh_rag::embeddings::EmbeddingModel;
+use shodh_rag::space_archive::{ArchiveManifest, SpaceArchive};
 
 /// Clear all data from the database and reset to fresh state
 #[tauri::command]
@@ -94,6 +96,7 @@ pub async fn delete_space_permanently(
         .map_err(|e| format!("Failed to delete documents: {}", e))?;
 
     tracing::info!("Deleted {} chunks from space {}", deleted_count, space_id);
+    rag.forget_space(&space_id);
     drop(rag_guard);
 
     // Delete the space from SpaceManager and save to disk
@@ -143,45 +146,36 @@ pub async fn get_database_stats(
     Ok(stats)
 }
 
-/// Clean up orphaned documents (documents not associated with any space)
+/// Clean up orphaned documents (chunks belonging to a space that no longer exists)
 #[tauri::command]
 pub async fn cleanup_orphaned_documents(
     state: State<'_, RagState>,
 ) -> Result<String, String> {
     tracing::info!("=== Cleaning up orphaned documents ===");
 
-    let rag_guard = state.rag.read().await;
-    let rag = &*rag_guard;
+    let (spaces, chunks) = remove_orphaned_chunks(&state).await?;
 
-    // List all documents by metadata (not search)
-    let all_docs = rag.list_documents(None, 100000)
-        .await
-        .map_err(|e| e.to_string())?;
+    Ok(format!("Removed {} orphaned chunks from {} deleted spaces.", chunks, spaces))
+}
 
-    // Get all valid space IDs
-    let space_manager = state.space_manager.lock().map_err(|e| e.to_string())?;
-    let valid_space_ids: Vec<String> = space_manager.get_spaces()
-        .map_err(|e| format!("Failed to get spaces: {}", e))?
-        .iter()
-        .map(|s| s.id.clone())
-        .collect();
+/// Delete chunks whose space is gone. Returns (spaces, chunks) removed.
+pub(crate) async fn remove_orphaned_chunks(state: &RagState) -> Result<(usize, usize), String> {
+    let valid_space_ids: std::collections::HashSet<String> = {
+        let space_manager = state.space_manager.lock().map_err(|e| e.to_string())?;
+        space_manager.get_spaces()
+            .map_err(|e| format!("Failed to get spaces: {}", e))?
+            .into_iter()
+            .map(|s| s.id)
+            .collect()
+    }; // Drop space_manager lock before await
 
-    let mut orphaned_count = 0;
-    for doc in all_docs.iter() {
-        // Check if document has a space_id that exists
-        if let Some(space_id) = doc.metadata.get("space_id") {
-            if !valid_space_ids.contains(space_id) {
-                orphaned_count += 1;
-                // Note: Need delete_document method in ComprehensiveRAG
-                tracing::info!("Found orphaned document with invalid space_id: {}", space_id);
-            }
-        } else {
-            orphaned_count += 1;
-            tracing::info!("Found orphaned document with no space_id");
-        }
-    }
+    let mut rag_guard = state.rag.write().await;
+    let (spaces, chunks) = rag_guard.delete_orphaned_spaces(&valid_space_ids)
+        .await
+        .map_err(|e| format!("Failed to delete orphaned documents: {}", e))?;
 
-    Ok(format!("Found {} orphaned documents. Manual cleanup required for now.", orphaned_count))
+    tracing::info!("Removed {} orphaned chunks from {} deleted spaces", chunks, spaces);
+    Ok((spaces, chunks))
 }
 
 #[derive(serde::Serialize, Default)]
@@ -300,22 +294,35 @@ pub async fn list_backup_files(
 
     if let Ok(entries) = fs::read_dir(&backup_dir) {
         for entry in entries.flatten() {
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_file() && entry.path().extension().and_then(|s| s.to_str()) == Some("json") {
-                    if let Some(file_name) = entry.file_name().to_str() {
-                        backups.push(BackupFileInfo {
-                            file_name: file_name.to_string(),
-                            file_path: entry.path().to_string_lossy().to_string(),
-                            size_bytes: metadata.len(),
-                            created_at: metadata.modified()
-                                .ok()
-                                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                                .map(|d| d.as_secs())
-                                .unwrap_or(0),
-                        });
-                    }
-                }
-            }
+            let Ok(metadata) = entry.metadata() else { continue };
+            let Some(file_name) = entry.file_name().to_str().map(str::to_string) else { continue };
+
+            // Manual JSON exports and completed scheduled snapshots (folders of
+            // space archives); in-progress `.tmp` snapshots are skipped
+            let (kind, size_bytes) = if metadata.is_file()
+                && entry.path().extension().and_then(|s| s.to_str()) == Some("json")
+            {
+                ("json", metadata.len())
+            } else if metadata.is_dir()
+                && file_name.starts_with(crate::backup_scheduler::SNAPSHOT_PREFIX)
+                && !file_name.ends_with(".tmp")
+            {
+                ("snapshot", get_dir_size(&entry.path()))
+            } else {
+                continue;
+            };
+
+            backups.push(BackupFileInfo {
+                file_name,
+                file_path: entry.path().to_string_lossy().to_string(),
+                size_bytes,
+                created_at: metadata.modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+                kind: kind.to_string(),
+            });
         }
     }
 
@@ -324,6 +331,103 @@ pub async fn list_backup_files(
     Ok(backups)
 }
 
+/// Export a space's documents, chunks, embeddings and metadata as a portable
+/// `.tar.zst` archive
+#[tauri::command]
+pub async fn export_space_archive(
+    state: State<'_, RagState>,
+    space_id: String,
+    out_path: String,
+) -> Result<SpaceArchiveInfo, String> {
+    tracing::info!("=== Exporting space {} to {} ===", space_id, out_path);
+
+    let space = {
+        let space_manager = state.space_manager.lock().map_err(|e| e.to_string())?;
+        space_manager.get_space(&space_id)?
+    };
+
+    let rag = state.rag.read().await;
+    let chunks = rag.export_space_chunks(&space_id)
+        .await
+        .map_err(|e| format!("Failed to read space chunks: {}", e))?;
+    let archive = SpaceArchive::new(space, chunks, rag.embedding_model_id(), rag.embeddings().dimension());
+    drop(rag);
+
+    let manifest = archive.manifest.clone();
+    let path = PathBuf::from(&out_path);
+    tokio::task::spawn_blocking(move || archive.write(&path))
+        .await
+        .map_err(|e| format!("Export task failed: {}", e))?
+        .map_err(|e| format!("Failed to write archive: {:#}", e))?;
+
+    tracing::info!("Exported {} chunks from space {}", manifest.chunk_count, space_id);
+    Ok(SpaceArchiveInfo { path: out_path, manifest, reembedded: false })
+}
+
+/// Import a space archive. Fails if the archive's embedding model differs
+/// from the current one unless `reembed` is set, in which case its chunks
+/// are re-embedded with the current model. Either the space and all its
+/// chunks are imported or nothing is.
+#[tauri::command]
+pub async fn import_space_archive(
+    state: State<'_, RagState>,
+    path: String,
+    reembed: Option<bool>,
+) -> Result<SpaceArchiveInfo, String> {
+    tracing::info!("=== Importing space archive: {} ===", path);
+
+    let archive_path = PathBuf::from(&path);
+    let archive = tokio::task::spawn_blocking(move || SpaceArchive::read(&archive_path))
+        .await
+        .map_err(|e| format!("Import task failed: {}", e))?
+        .map_err(|e| format!("Failed to read archive: {:#}", e))?;
+
+    {
+        let space_manager = state.space_manager.lock().map_err(|e| e.to_string())?;
+        if space_manager.get_space(&archive.space.id).is_ok() {
+            return Err(format!(
+                "Space '{}' already exists ({}). Delete it before importing.",
+                archive.space.name, archive.space.id
+            ));
+        }
+    }
+
+    let mut rag = state.rag.write().await;
+    let mismatch = archive.embedding_mismatch(&rag.embedding_model_id(), rag.embeddings().dimension());
+    if let Some(reason) = &mismatch {
+        if !reembed.unwrap_or(false) {
+            return Err(format!(
+                "Cannot import: {}. Import again with re-embedding enabled to recompute the vectors.",
+                reason
+            ));
+        }
+        tracing::info!("Re-embedding archive chunks: {}", reason);
+    }
+
+    let SpaceArchive { manifest, space, chunks } = archive;
+    let space_id = space.id.clone();
+    rag.import_space_chunks(&space_id, chunks, mismatch.is_some())
+        .await
+        .map_err(|e| format!("Failed to import chunks: {:#}", e))?;
+
+    // Still under the engine write lock, so a concurrent import of the same
+    // archive can't register the space in between
+    let inserted = state.space_manager
+        .lock()
+        .map_err(|e| e.to_string())
+        .and_then(|space_manager| space_manager.insert_space(space));
+    if let Err(e) = inserted {
+        if let Err(cleanup) = rag.delete_by_space_id(&space_id).await {
+            tracing::error!("Failed to remove chunks of unsaved space {}: {}", space_id, cleanup);
+        }
+        return Err(format!("Failed to save imported space: {}", e));
+    }
+    drop(rag);
+
+    tracing::info!("Imported space '{}' ({} chunks)", manifest.space_name, manifest.chunk_count);
+    Ok(SpaceArchiveInfo { path, manifest, reembedded: mismatch.is_some() })
+}
+
 /// Update space metadata for versioning
 #[tauri::command]
 pub async fn update_space_metadata(
@@ -404,4 +508,15 @@ pub struct BackupFileInfo {
     pub file_path: String,
     pub size_bytes: u64,
     pub created_at: u64,
-}
\ No newline at end of file
+    /// "json" for manual exports, "snapshot" for scheduled space-archive folders
+    pub kind: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpaceArchiveInfo {
+    pub path: String,
+    pub manifest: ArchiveManifest,
+    /// Chunks were re-embedded because the archive used a different model
+    pub reembedded: bool,
+}
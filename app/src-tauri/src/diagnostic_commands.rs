@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use crate::rag_commands::RagState;
+use shodh_rag::search::QueryCacheStats;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +26,7 @@ pub struct IndexDiagnostics {
     pub sample_documents: Vec<DocumentDiagnostic>,
     pub file_types: HashMap<String, usize>,
     pub spaces: HashMap<String, usize>,
+    pub query_cache: QueryCacheStats,
 }
 
 /// Get diagnostic information about indexed content
@@ -115,6 +117,7 @@ pub async fn get_index_diagnostics(
         sample_documents,
         file_types,
         spaces,
+        query_cache: rag.query_cache_stats(),
     })
 }
 
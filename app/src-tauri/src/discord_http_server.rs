@@ -3,7 +3,7 @@
 use axum::{
     extract::State as AxumState,
     http::StatusCode,
-    response::Json,
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
@@ -11,13 +11,15 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tower_http::cors::{Any, CorsLayer};
-use chrono;
-use tauri::Emitter;
 
 use crate::rag_commands::RagState;
 use crate::llm_commands::LLMState;
-use crate::unified_chat_commands::unified_chat_internal;
-use crate::chat_engine::{ChatContext, MessagePlatform};
+use crate::chat_engine::MessagePlatform;
+use crate::bot_artifacts::{append_inline, render_artifacts, ArtifactAttachment};
+use crate::bot_chat::{run_turn, BotTurn};
+use crate::bot_format::{discord_embed, format_error, format_reply, DiscordEmbed};
+use crate::bot_sse::stream_chat;
+use crate::bot_access::{stream_rejection, AccessDecision, BotAccess};
 
 #[derive(Debug, Deserialize)]
 struct DiscordMessage {
@@ -31,6 +33,12 @@ struct DiscordMessage {
 #[derive(Debug, Serialize)]
 struct DiscordResponse {
     response: String,
+    /// Sources and model stats, sent as an embed under the reply
+    #[serde(skip_serializing_if = "Option::is_none")]
+    embed: Option<DiscordEmbed>,
+    /// Rendered charts, diagrams and long code/tables, sent after `response`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<ArtifactAttachment>,
 }
 
 #[derive(Clone)]
@@ -38,96 +46,66 @@ struct AppState {
     rag_state: Arc<RwLock<RagState>>,
     llm_state: Arc<RwLock<LLMState>>,
     app_handle: Option<tauri::AppHandle>,
+    access: Arc<BotAccess>,
+}
+
+/// Turn keyed to the Discord channel
+fn bot_turn(payload: DiscordMessage, tools_allowed: bool) -> BotTurn {
+    BotTurn {
+        platform: MessagePlatform::Discord,
+        sender: payload.username,
+        conversation_id: format!("discord_{}", payload.channel_id),
+        space_id: None,
+        message: payload.message,
+        tools_allowed,
+    }
 }
 
 async fn handle_discord_message(
     AxumState(state): AxumState<AppState>,
     Json(payload): Json<DiscordMessage>,
-) -> Result<Json<DiscordResponse>, (StatusCode, String)> {
-    tracing::info!("Discord message from {}: {}", payload.username, payload.message);
-
-    // Emit event to frontend
-    if let Some(app_handle) = &state.app_handle {
-        let _ = app_handle.emit("discord-message", serde_json::json!({
-            "platform": "discord",
-            "username": payload.username,
-            "message": payload.message,
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-        }));
+) -> Result<Response, (StatusCode, String)> {
+    if let AccessDecision::Reject(reply) = state.access.check(&[&payload.user_id, &payload.username]) {
+        tracing::info!("Not serving Discord user {} (access policy)", payload.username);
+        return Ok(match reply {
+            Some(text) => Json(DiscordResponse { response: text, embed: None, attachments: Vec::new() }).into_response(),
+            None => StatusCode::NO_CONTENT.into_response(),
+        });
     }
 
-    // Process with timeout protection (60 seconds)
-    let response_text = match tokio::time::timeout(
-        std::time::Duration::from_secs(60),
-        async {
-            let rag_state_guard = state.rag_state.read().await;
-
-            let context = ChatContext {
-                agent_id: None,
-                project: None,
-                space_id: None,
-                conversation_id: Some(format!("discord_{}", payload.channel_id)),
-                conversation_history: None,
-                max_results: None,
-                streaming: None,
-                custom_system_prompt: None,
-            };
-
-            let result = unified_chat_internal(
-                &rag_state_guard,
-                payload.message.clone(),
-                Some(context),
-                MessagePlatform::Discord,
-                None,
-            ).await;
-
-            drop(rag_state_guard);
-
-            match result {
-                Ok(response) => {
-                    let mut message = response.content.clone();
-
-                    let meta = &response.metadata;
-                    if let (Some(model), Some(input_tokens), Some(output_tokens), Some(duration_ms)) =
-                        (&meta.model, meta.input_tokens, meta.output_tokens, meta.duration_ms) {
-                        let duration_s = duration_ms as f64 / 1000.0;
-                        let tok_per_s = output_tokens as f64 / duration_s;
-
-                        message.push_str(&format!(
-                            "\n\n{} | in:{} out:{} | {:.1}s | {:.1} tok/s",
-                            model, input_tokens, output_tokens, duration_s, tok_per_s
-                        ));
-                    }
-
-                    message
-                },
-                Err(e) => {
-                    tracing::warn!("Error from unified_chat: {}", e);
-                    format!("Sorry, I encountered an error: {}", e)
-                }
-            }
-        }
-    ).await {
-        Ok(text) => text,
-        Err(_) => {
-            tracing::warn!("Discord request timeout after 60 seconds");
-            "Sorry, the request timed out. Please try again with a simpler question.".to_string()
+    let tools_allowed = state.access.allows_tools(&[&payload.user_id, &payload.username]);
+    let turn = bot_turn(payload, tools_allowed);
+    let result = {
+        let rag_state_guard = state.rag_state.read().await;
+        run_turn(&rag_state_guard, state.app_handle.as_ref(), &turn, None).await
+    };
+    let reply = match result {
+        Ok(response) => {
+            let text = format_reply(&turn.platform, &response);
+            let embed = discord_embed(&response);
+            let (inline, mut attachments) = render_artifacts(response.artifacts, turn.platform.clone()).await;
+            let response = append_inline(text, inline, &turn.platform, &mut attachments);
+            DiscordResponse { response, embed, attachments }
         }
+        Err(e) => DiscordResponse { response: format_error(&turn.platform, &e), embed: None, attachments: Vec::new() },
     };
 
-    // Emit response event to frontend
-    if let Some(app_handle) = &state.app_handle {
-        let _ = app_handle.emit("discord-response", serde_json::json!({
-            "platform": "discord",
-            "username": payload.username,
-            "message": &response_text,
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-        }));
-    }
+    Ok(Json(reply).into_response())
+}
 
-    tracing::info!("Sent response to {}", payload.username);
+/// Same as `/discord/chat`, streamed as Server-Sent Events
+async fn handle_discord_stream(
+    AxumState(state): AxumState<AppState>,
+    Json(payload): Json<DiscordMessage>,
+) -> Response {
+    if let AccessDecision::Reject(reply) = state.access.check(&[&payload.user_id, &payload.username]) {
+        tracing::info!("Not serving Discord user {} (access policy)", payload.username);
+        return stream_rejection(reply);
+    }
 
-    Ok(Json(DiscordResponse { response: response_text }))
+    let tools_allowed = state.access.allows_tools(&[&payload.user_id, &payload.username]);
+    stream_chat(state.rag_state.clone(), state.app_handle.clone(), bot_turn(payload, tools_allowed), |_| async {})
+        .into_response()
 }
 
 async fn health_check() -> &'static str {
@@ -138,11 +116,13 @@ pub async fn start_server(
     rag_state: RagState,
     llm_state: LLMState,
     app_handle: Option<tauri::AppHandle>,
+    access: Arc<BotAccess>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let app_state = AppState {
         rag_state: Arc::new(RwLock::new(rag_state)),
         llm_state: Arc::new(RwLock::new(llm_state)),
         app_handle,
+        access,
     };
 
     let cors = CorsLayer::new()
@@ -153,6 +133,7 @@ pub async fn start_server(
     let app = Router::new()
         .route("/", get(health_check))
         .route("/discord/chat", post(handle_discord_message))
+        .route("/discord/chat/stream", post(handle_discord_stream))
         .layer(cors)
         .with_state(app_state);
 
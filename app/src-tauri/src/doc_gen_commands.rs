@@ -1,14 +1,17 @@
 use serde::{Deserialize, Serialize};
-use tauri::{State, Emitter};
+use tauri::{State, Emitter, Manager};
+use tokio::sync::watch;
+use std::collections::HashMap;
 use crate::rag_commands::RagState;
 use crate::llm_commands::LLMState;
+use crate::chat_engine::{EventEmitter, TauriEventEmitter};
+use crate::office_export;
 use shodh_rag::comprehensive_system::SimpleSearchResult;
-use shodh_rag::llm::{LLMMode, ApiProvider};
+use shodh_rag::llm::{LLMManager, LLMMode, ApiProvider};
 use uuid::Uuid;
 use printpdf::*;
 use std::fs::File;
 use std::io::BufWriter;
-use docx_rs::*;
 use rust_xlsxwriter::*;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,6 +111,7 @@ pub async fn generate_document(
     request: GenerateDocumentRequest,
     rag_state: State<'_, RagState>,
     llm_state: State<'_, LLMState>,
+    doc_gen: State<'_, DocGenState>,
 ) -> Result<GenerateDocumentResponse, String> {
     tracing::info!("Generating document with format: {}", request.format);
     
@@ -125,6 +129,11 @@ pub async fn generate_document(
             // Return base64 encoded DOCX
             base64::encode(docx_bytes)
         },
+        "pptx" => {
+            let pptx_bytes = generate_pptx_content(request.clone(), rag_state.clone(), llm_state.clone()).await?;
+            // Return base64 encoded PPTX
+            base64::encode(pptx_bytes)
+        },
         "xlsx" | "csv" => {
             let xlsx_bytes = generate_spreadsheet_content(request.clone(), rag_state.clone(), llm_state.clone()).await?;
             // Return base64 encoded Excel
@@ -163,36 +172,7 @@ pub async fn generate_document(
         };
 
         // Extract context with metadata for richer document generation
-        let context: Vec<String> = search_results.iter()
-            .map(|r| {
-                let mut context_item = String::new();
-
-                // Add file metadata if available
-                if let Some(filename) = r.metadata.get("filename").or(r.metadata.get("title")) {
-                    context_item.push_str(&format!("[Source: {}]\n", filename));
-                }
-
-                // Add file type for code awareness
-                if let Some(ext) = r.metadata.get("file_extension") {
-                    let file_type = match ext.as_str() {
-                        "rs" => "Rust Code",
-                        "py" => "Python Code",
-                        "js" | "ts" => "JavaScript/TypeScript",
-                        "md" => "Markdown Documentation",
-                        "json" => "JSON Data",
-                        _ => "Text"
-                    };
-                    context_item.push_str(&format!("[Type: {}]\n", file_type));
-                }
-
-                // Add the actual content
-                context_item.push_str("---\n");
-                context_item.push_str(&r.text);
-                context_item.push_str("\n---\n");
-
-                context_item
-            })
-            .collect();
+        let context = rag_context(&search_results);
 
         // Generate content using LLM (with or without RAG context)
         let llm_content = {
@@ -201,17 +181,6 @@ pub async fn generate_document(
                 // Check if we have sources or generating directly from LLM knowledge
                 let has_sources = !context.is_empty();
 
-                // Analyze the context to understand what type of content we have (if any)
-                let has_code = has_sources && context.iter().any(|c|
-                    c.contains("[Type: Rust Code]") ||
-                    c.contains("[Type: Python Code]") ||
-                    c.contains("[Type: JavaScript/TypeScript]")
-                );
-
-                let has_docs = has_sources && context.iter().any(|c|
-                    c.contains("[Type: Markdown Documentation]")
-                );
-
                 // Refuse generation without sources — prevents hallucination
                 if !has_sources {
                     return Err(
@@ -221,91 +190,7 @@ pub async fn generate_document(
                     );
                 }
 
-                // Grounding + formatting preamble shared by every prompt variant
-                let grounding_rules = "\
-GROUNDING RULES (non-negotiable):
-- You MUST use ONLY the Source Materials below. You have NO other knowledge.
-- For EVERY claim, cite the source with [Source N] inline where N is the source number.
-- If a fact is not explicitly stated in the Source Materials, DO NOT include it.
-- NEVER infer, assume, or extrapolate beyond what the sources explicitly state.
-- An incomplete but 100% accurate document is better than a comprehensive but partially wrong one.
-- If the sources contain insufficient information for a section, write: \"Insufficient data in indexed documents.\"
-
-FORMATTING RULES (produce a pristine, publication-ready document):
-- Use clean Markdown: # for title, ## for major sections, ### for subsections.
-- NO emojis anywhere in the document.
-- Use proper paragraph spacing — one blank line between paragraphs.
-- Use **bold** for key terms and emphasis, not for entire sentences.
-- Use bullet points or numbered lists for enumerations — not run-on paragraphs.
-- Tables: use Markdown pipe tables for any structured/comparative data.
-- Keep language concise, professional, and formal.
-- End with a ## References section listing every source used (numbered to match inline citations).\n";
-
-                // Build the prompt based on content type
-                let doc_prompt = if has_code && (query.contains("implement") || query.contains("code") || query.contains("function") || query.contains("class")) {
-                    format!(
-                        "Create a professional technical document.\n\n\
-                        Topic: {query}\n\n\
-                        {grounding_rules}\n\
-                        Document structure:\n\
-                        ## Overview\n\
-                        Brief description of what the codebase covers.\n\n\
-                        ## Architecture\n\
-                        System design and component relationships found in the sources.\n\n\
-                        ## Implementation Details\n\
-                        Key functions, classes, and modules with code blocks (```language).\n\n\
-                        ## Usage Examples\n\
-                        Practical examples derived from the sources.\n\n\
-                        ## References\n\
-                        List all sources used.\n\n\
-                        Source Materials:\n{context}",
-                        query = query,
-                        grounding_rules = grounding_rules,
-                        context = context.join("\n\n"),
-                    )
-                } else if query.contains("report") || query.contains("analysis") || query.contains("summary") {
-                    format!(
-                        "Create a professional report.\n\n\
-                        Topic: {query}\n\n\
-                        {grounding_rules}\n\
-                        Document structure:\n\
-                        ## Executive Summary\n\
-                        2-3 paragraph overview of key findings.\n\n\
-                        ## Background\n\
-                        Context and scope as stated in the sources.\n\n\
-                        ## Detailed Findings\n\
-                        Evidence and data with [Source N] citations. Use tables where data is comparative.\n\n\
-                        ## Analysis\n\
-                        Interpretation strictly based on source evidence.\n\n\
-                        ## Recommendations\n\
-                        Actionable next steps supported by findings.\n\n\
-                        ## References\n\
-                        Numbered list of all sources used.\n\n\
-                        Source Materials:\n{context}",
-                        query = query,
-                        grounding_rules = grounding_rules,
-                        context = context.join("\n\n"),
-                    )
-                } else {
-                    format!(
-                        "Create a professional document.\n\n\
-                        Topic: {query}\n\n\
-                        {grounding_rules}\n\
-                        Document structure:\n\
-                        ## Introduction\n\
-                        Brief overview of what the sources cover and scope of this document.\n\n\
-                        ## [Organize remaining content into logical sections based on the source material]\n\
-                        Use ## for major sections, ### for subsections. Include [Source N] citations.\n\n\
-                        ## Conclusion\n\
-                        Key takeaways from the sources.\n\n\
-                        ## References\n\
-                        Numbered list of all sources used.\n\n\
-                        Source Materials:\n{context}",
-                        query = query,
-                        grounding_rules = grounding_rules,
-                        context = context.join("\n\n"),
-                    )
-                };
+                let doc_prompt = rag_document_prompt(query, &context);
 
                 // Get max_tokens from request data, default to 8192
                 let max_tokens = request.data.get("max_tokens")
@@ -393,9 +278,15 @@ FORMATTING RULES (produce a pristine, publication-ready document):
         vec![]
     };
     
+    let id = uuid::Uuid::new_v4().to_string();
+    // Text formats can be compared later; binary ones are base64 at this point
+    if matches!(request.format.as_str(), "md" | "txt") {
+        doc_gen.record(&id, &title, &content);
+    }
+
     // Create response with full content preview (no artificial limit)
     let response = GenerateDocumentResponse {
-        id: uuid::Uuid::new_v4().to_string(),
+        id,
         title,
         format: request.format.clone(),
         size: content.len(),
@@ -413,30 +304,147 @@ FORMATTING RULES (produce a pristine, publication-ready document):
     Ok(response)
 }
 
-/// Generate document from RAG search using the integrated doc-gen system
-#[tauri::command]
-pub async fn generate_from_rag(
-    prompt: String,
-    format: String,
-    include_references: Option<bool>,
-    max_source_docs: Option<usize>,
-    template: Option<String>,
-    desired_length: Option<DocumentLength>,
-    rag_state: State<'_, RagState>,
-    llm_state: State<'_, LLMState>,
-) -> Result<GenerateDocumentResponse, String> {
-    // Get current LLM mode to determine provider limits
+/// Source chunks formatted for the generation prompt, tagged with file name and type
+fn rag_context(search_results: &[SimpleSearchResult]) -> Vec<String> {
+    search_results.iter()
+        .map(|r| {
+            let mut context_item = String::new();
+
+            // Add file metadata if available
+            if let Some(filename) = r.metadata.get("filename").or(r.metadata.get("title")) {
+                context_item.push_str(&format!("[Source: {}]\n", filename));
+            }
+
+            // Add file type for code awareness
+            if let Some(ext) = r.metadata.get("file_extension") {
+                let file_type = match ext.as_str() {
+                    "rs" => "Rust Code",
+                    "py" => "Python Code",
+                    "js" | "ts" => "JavaScript/TypeScript",
+                    "md" => "Markdown Documentation",
+                    "json" => "JSON Data",
+                    _ => "Text"
+                };
+                context_item.push_str(&format!("[Type: {}]\n", file_type));
+            }
+
+            // Add the actual content
+            context_item.push_str("---\n");
+            context_item.push_str(&r.text);
+            context_item.push_str("\n---\n");
+
+            context_item
+        })
+        .collect()
+}
+
+/// Grounded document prompt, picking a technical, report or general outline
+/// from the query wording and the kind of sources found
+fn rag_document_prompt(query: &str, context: &[String]) -> String {
+    let has_code = context.iter().any(|c|
+        c.contains("[Type: Rust Code]") ||
+        c.contains("[Type: Python Code]") ||
+        c.contains("[Type: JavaScript/TypeScript]")
+    );
+
+    // Grounding + formatting preamble shared by every prompt variant
+    let grounding_rules = "\
+GROUNDING RULES (non-negotiable):
+- You MUST use ONLY the Source Materials below. You have NO other knowledge.
+- For EVERY claim, cite the source with [Source N] inline where N is the source number.
+- If a fact is not explicitly stated in the Source Materials, DO NOT include it.
+- NEVER infer, assume, or extrapolate beyond what the sources explicitly state.
+- An incomplete but 100% accurate document is better than a comprehensive but partially wrong one.
+- If the sources contain insufficient information for a section, write: \"Insufficient data in indexed documents.\"
+
+FORMATTING RULES (produce a pristine, publication-ready document):
+- Use clean Markdown: # for title, ## for major sections, ### for subsections.
+- NO emojis anywhere in the document.
+- Use proper paragraph spacing — one blank line between paragraphs.
+- Use **bold** for key terms and emphasis, not for entire sentences.
+- Use bullet points or numbered lists for enumerations — not run-on paragraphs.
+- Tables: use Markdown pipe tables for any structured/comparative data.
+- Keep language concise, professional, and formal.
+- End with a ## References section listing every source used (numbered to match inline citations).\n";
+
+    // Build the prompt based on content type
+    if has_code && (query.contains("implement") || query.contains("code") || query.contains("function") || query.contains("class")) {
+        format!(
+            "Create a professional technical document.\n\n\
+            Topic: {query}\n\n\
+            {grounding_rules}\n\
+            Document structure:\n\
+            ## Overview\n\
+            Brief description of what the codebase covers.\n\n\
+            ## Architecture\n\
+            System design and component relationships found in the sources.\n\n\
+            ## Implementation Details\n\
+            Key functions, classes, and modules with code blocks (```language).\n\n\
+            ## Usage Examples\n\
+            Practical examples derived from the sources.\n\n\
+            ## References\n\
+            List all sources used.\n\n\
+            Source Materials:\n{context}",
+            query = query,
+            grounding_rules = grounding_rules,
+            context = context.join("\n\n"),
+        )
+    } else if query.contains("report") || query.contains("analysis") || query.contains("summary") {
+        format!(
+            "Create a professional report.\n\n\
+            Topic: {query}\n\n\
+            {grounding_rules}\n\
+            Document structure:\n\
+            ## Executive Summary\n\
+            2-3 paragraph overview of key findings.\n\n\
+            ## Background\n\
+            Context and scope as stated in the sources.\n\n\
+            ## Detailed Findings\n\
+            Evidence and data with [Source N] citations. Use tables where data is comparative.\n\n\
+            ## Analysis\n\
+            Interpretation strictly based on source evidence.\n\n\
+            ## Recommendations\n\
+            Actionable next steps supported by findings.\n\n\
+            ## References\n\
+            Numbered list of all sources used.\n\n\
+            Source Materials:\n{context}",
+            query = query,
+            grounding_rules = grounding_rules,
+            context = context.join("\n\n"),
+        )
+    } else {
+        format!(
+            "Create a professional document.\n\n\
+            Topic: {query}\n\n\
+            {grounding_rules}\n\
+            Document structure:\n\
+            ## Introduction\n\
+            Brief overview of what the sources cover and scope of this document.\n\n\
+            ## [Organize remaining content into logical sections based on the source material]\n\
+            Use ## for major sections, ### for subsections. Include [Source N] citations.\n\n\
+            ## Conclusion\n\
+            Key takeaways from the sources.\n\n\
+            ## References\n\
+            Numbered list of all sources used.\n\n\
+            Source Materials:\n{context}",
+            query = query,
+            grounding_rules = grounding_rules,
+            context = context.join("\n\n"),
+        )
+    }
+}
+
+/// Output tokens for `length`, capped by what the current LLM provider supports
+fn token_budget(length: &DocumentLength, llm_state: &LLMState) -> usize {
     let llm_mode = {
         let config_guard = llm_state.config.lock().unwrap();
         config_guard.mode.clone()
     };
 
-    let length = desired_length.unwrap_or(DocumentLength::Standard);
     let requested_tokens = length.to_tokens();
     let provider_max = get_provider_max_tokens(&llm_mode);
     let actual_max = requested_tokens.min(provider_max);
 
-    tracing::info!("📄 Generating {} document for prompt: {}", format, prompt);
     tracing::info!("   Length: {:?} (requested: {}k, provider max: {}k, using: {}k tokens)",
              length, requested_tokens/1000, provider_max/1000, actual_max/1000);
 
@@ -444,6 +452,27 @@ pub async fn generate_from_rag(
         tracing::info!("⚠️  Requested length exceeds provider capability, capping at {}k tokens", provider_max/1000);
     }
 
+    actual_max
+}
+
+/// Generate document from RAG search using the integrated doc-gen system
+#[tauri::command]
+pub async fn generate_from_rag(
+    prompt: String,
+    format: String,
+    include_references: Option<bool>,
+    max_source_docs: Option<usize>,
+    template: Option<String>,
+    desired_length: Option<DocumentLength>,
+    rag_state: State<'_, RagState>,
+    llm_state: State<'_, LLMState>,
+    doc_gen: State<'_, DocGenState>,
+) -> Result<GenerateDocumentResponse, String> {
+    tracing::info!("📄 Generating {} document for prompt: {}", format, prompt);
+
+    let length = desired_length.unwrap_or(DocumentLength::Standard);
+    let actual_max = token_budget(&length, &llm_state);
+
     let include_refs = include_references.unwrap_or(true);
     let max_docs = max_source_docs.unwrap_or(10);
 
@@ -461,7 +490,352 @@ pub async fn generate_from_rag(
         desired_length: Some(length),
     };
 
-    generate_document(request, rag_state, llm_state).await
+    generate_document(request, rag_state, llm_state, doc_gen).await
+}
+
+/// Generated documents kept in memory for `compare_documents`
+const MAX_GENERATED_DOCS: usize = 20;
+
+/// Cancel switches for in-flight streaming generations, keyed by session id,
+/// and the markdown of recent generations
+#[derive(Default)]
+pub struct DocGenState {
+    sessions: std::sync::Mutex<HashMap<String, watch::Sender<bool>>>,
+    generated: std::sync::Mutex<Vec<GeneratedDoc>>,
+}
+
+struct GeneratedDoc {
+    id: String,
+    title: String,
+    markdown: String,
+}
+
+impl DocGenState {
+    fn begin(&self, session_id: &str) -> watch::Receiver<bool> {
+        let (tx, rx) = watch::channel(false);
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.insert(session_id.to_string(), tx);
+        }
+        rx
+    }
+
+    fn finish(&self, session_id: &str) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.remove(session_id);
+        }
+    }
+
+    fn cancel(&self, session_id: &str) -> bool {
+        self.sessions
+            .lock()
+            .ok()
+            .and_then(|sessions| sessions.get(session_id).map(|tx| tx.send(true).is_ok()))
+            .unwrap_or(false)
+    }
+
+    fn record(&self, id: &str, title: &str, markdown: &str) {
+        if let Ok(mut generated) = self.generated.lock() {
+            generated.retain(|doc| doc.id != id);
+            if generated.len() >= MAX_GENERATED_DOCS {
+                generated.remove(0);
+            }
+            generated.push(GeneratedDoc {
+                id: id.to_string(),
+                title: title.to_string(),
+                markdown: markdown.to_string(),
+            });
+        }
+    }
+
+    fn generated(&self, id: &str) -> Option<(String, String)> {
+        let generated = self.generated.lock().ok()?;
+        generated.iter().find(|doc| doc.id == id).map(|doc| (doc.title.clone(), doc.markdown.clone()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct OutlineEntry {
+    level: usize,
+    title: String,
+}
+
+#[derive(Debug, PartialEq)]
+enum SectionEvent {
+    /// A heading was added to the outline
+    Outline,
+    SectionComplete { index: usize, title: String, content: String },
+}
+
+/// Follows a markdown stream line by line. `##` headings open sections, which
+/// complete when the next one opens or the stream ends; `##` and `###`
+/// headings make up the outline. Headings inside code fences are ignored.
+#[derive(Default)]
+struct SectionTracker {
+    line: String,
+    in_fence: bool,
+    outline: Vec<OutlineEntry>,
+    current: Option<(String, String)>,
+    completed: usize,
+}
+
+impl SectionTracker {
+    fn push(&mut self, delta: &str) -> Vec<SectionEvent> {
+        let mut events = Vec::new();
+        self.line.push_str(delta);
+        while let Some(end) = self.line.find('\n') {
+            let line: String = self.line.drain(..=end).collect();
+            self.process_line(line.trim_end_matches(['\n', '\r']), &mut events);
+        }
+        events
+    }
+
+    fn finish(&mut self) -> Vec<SectionEvent> {
+        let mut events = Vec::new();
+        if !self.line.is_empty() {
+            let line = std::mem::take(&mut self.line);
+            self.process_line(&line, &mut events);
+        }
+        self.close_section(&mut events);
+        events
+    }
+
+    fn process_line(&mut self, line: &str, events: &mut Vec<SectionEvent>) {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            self.in_fence = !self.in_fence;
+        } else if !self.in_fence {
+            let level = trimmed.chars().take_while(|&c| c == '#').count();
+            let title = trimmed[level..].trim();
+            if (2..=3).contains(&level) && trimmed[level..].starts_with(' ') && !title.is_empty() {
+                self.outline.push(OutlineEntry { level, title: title.to_string() });
+                events.push(SectionEvent::Outline);
+                if level == 2 {
+                    self.close_section(events);
+                    self.current = Some((title.to_string(), String::new()));
+                }
+            }
+        }
+        if let Some((_, content)) = self.current.as_mut() {
+            content.push_str(line);
+            content.push('\n');
+        }
+    }
+
+    fn close_section(&mut self, events: &mut Vec<SectionEvent>) {
+        if let Some((title, content)) = self.current.take() {
+            events.push(SectionEvent::SectionComplete {
+                index: self.completed,
+                title,
+                content: content.trim_end().to_string(),
+            });
+            self.completed += 1;
+        }
+    }
+}
+
+/// How a streamed generation ended
+enum StreamOutcome {
+    Complete,
+    Cancelled,
+    Failed(String),
+}
+
+/// Stream `doc_prompt` through the LLM into `markdown`, emitting deltas, the
+/// running outline and finished sections on `channel`. Stops early when
+/// `cancel` flips to true.
+async fn stream_document(
+    emitter: &dyn EventEmitter,
+    channel: &str,
+    manager: &LLMManager,
+    doc_prompt: &str,
+    max_tokens: usize,
+    markdown: &mut String,
+    mut cancel: watch::Receiver<bool>,
+) -> StreamOutcome {
+    let mut tokens = match manager.generate_stream_custom(doc_prompt, max_tokens).await {
+        Ok(tokens) => tokens,
+        Err(e) => return StreamOutcome::Failed(e.to_string()),
+    };
+
+    let mut tracker = SectionTracker::default();
+    let emit_sections = |tracker: &SectionTracker, events: Vec<SectionEvent>| {
+        for event in events {
+            match event {
+                SectionEvent::Outline => emitter.emit(channel, serde_json::json!({
+                    "type": "Outline",
+                    "outline": tracker.outline,
+                })),
+                SectionEvent::SectionComplete { index, title, content } => emitter.emit(channel, serde_json::json!({
+                    "type": "SectionComplete",
+                    "index": index,
+                    "title": title,
+                    "content": content,
+                })),
+            }
+        }
+    };
+
+    loop {
+        let token = tokio::select! {
+            token = tokens.next() => token,
+            Ok(()) = cancel.changed() => {
+                if *cancel.borrow() {
+                    return StreamOutcome::Cancelled;
+                }
+                continue;
+            }
+        };
+        let Some(token) = token else { break };
+
+        markdown.push_str(&token);
+        emitter.emit(channel, serde_json::json!({ "type": "ContentDelta", "delta": token }));
+        let events = tracker.push(&token);
+        emit_sections(&tracker, events);
+    }
+
+    if let Some(err) = tokens.error() {
+        return StreamOutcome::Failed(err);
+    }
+    let events = tracker.finish();
+    emit_sections(&tracker, events);
+    StreamOutcome::Complete
+}
+
+/// Streaming counterpart of `generate_from_rag`. Returns a session id at once;
+/// progress arrives on `generation_chunk_{session_id}` as `ContentDelta`,
+/// `Outline` and `SectionComplete` events (the latter per `##` section, for a
+/// live table of contents), ending in `Complete`, `Cancelled` or `Error`.
+#[tauri::command]
+pub async fn generate_from_rag_stream(
+    app: tauri::AppHandle,
+    prompt: String,
+    format: String,
+    max_source_docs: Option<usize>,
+    desired_length: Option<DocumentLength>,
+    rag_state: State<'_, RagState>,
+    llm_state: State<'_, LLMState>,
+    doc_gen: State<'_, DocGenState>,
+) -> Result<String, String> {
+    let session_id = Uuid::new_v4().to_string();
+    let channel = format!("generation_chunk_{}", session_id);
+    let emitter = TauriEventEmitter::new(app.clone());
+    tracing::info!("📡 Streaming {} document for prompt: {} - Session: {}", format, prompt, session_id);
+
+    let length = desired_length.unwrap_or(DocumentLength::Standard);
+    let max_tokens = token_budget(&length, &llm_state);
+
+    emitter.emit(&channel, serde_json::json!({
+        "type": "Stage",
+        "stage": "search",
+        "message": "Searching knowledge base...",
+        "progress": 10
+    }));
+
+    let mut search_results = {
+        let rag_guard = rag_state.rag.read().await;
+        rag_guard.search(&prompt, max_source_docs.unwrap_or(15)).await
+            .map_err(|e| format!("Search failed: {}", e))?
+    };
+    search_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+
+    let context = rag_context(&search_results);
+    if context.is_empty() {
+        return Err(
+            "Cannot generate document: No relevant information found in the knowledge base \
+            for this query. Please add documents to your knowledge base first, then try again."
+                .to_string(),
+        );
+    }
+
+    let sources: Vec<String> = search_results.iter().map(|r| r.id.to_string()).collect();
+    emitter.emit(&channel, serde_json::json!({
+        "type": "SearchComplete",
+        "num_sources": search_results.len(),
+        "sources": search_results.iter().take(5).map(|r| serde_json::json!({
+            "title": r.metadata.get("title")
+                .or_else(|| r.metadata.get("file_path"))
+                .cloned()
+                .unwrap_or_else(|| "Unknown".to_string()),
+            "score": r.score,
+        })).collect::<Vec<_>>(),
+    }));
+
+    let doc_prompt = rag_document_prompt(&prompt, &context);
+    let cancel = doc_gen.begin(&session_id);
+    let llm_manager_arc = llm_state.manager.clone();
+    let session = session_id.clone();
+
+    tokio::spawn(async move {
+        emitter.emit(&channel, serde_json::json!({
+            "type": "Stage",
+            "stage": "generate",
+            "message": "AI is writing document...",
+            "progress": 50
+        }));
+
+        // Same header as the blocking path, streamed first so the preview matches
+        let mut markdown = format!(
+            "# {}\n\n**Date:** {}  \n**Query:** {}\n\n---\n\n",
+            prompt,
+            chrono::Utc::now().format("%B %d, %Y"),
+            prompt,
+        );
+        emitter.emit(&channel, serde_json::json!({ "type": "ContentDelta", "delta": markdown }));
+
+        let outcome = {
+            let manager_lock = llm_manager_arc.read().await;
+            match manager_lock.as_ref() {
+                Some(manager) => {
+                    stream_document(&emitter, &channel, manager, &doc_prompt, max_tokens, &mut markdown, cancel).await
+                }
+                None => StreamOutcome::Failed(
+                    "LLM is not configured. Please configure an LLM provider to generate documents.".to_string(),
+                ),
+            }
+        };
+
+        match outcome {
+            StreamOutcome::Complete => {
+                tracing::info!("✅ Streamed document complete - Session: {}", session);
+                app.state::<DocGenState>().record(&session, &prompt, markdown.trim_end());
+                emitter.emit(&channel, serde_json::json!({
+                    "type": "Complete",
+                    "markdown": markdown.trim_end(),
+                    "format": format,
+                    "sources": sources,
+                }));
+            }
+            StreamOutcome::Cancelled => {
+                tracing::info!("🛑 Document generation cancelled - Session: {}", session);
+                emitter.emit(&channel, serde_json::json!({
+                    "type": "Cancelled",
+                    "markdown": markdown.trim_end(),
+                }));
+            }
+            StreamOutcome::Failed(e) => {
+                tracing::warn!("LLM generation failed: {}", e);
+                emitter.emit(&channel, serde_json::json!({
+                    "type": "Error",
+                    "message": format!("LLM generation failed: {}", e),
+                    "markdown": markdown.trim_end(),
+                }));
+            }
+        }
+
+        app.state::<DocGenState>().finish(&session);
+    });
+
+    Ok(session_id)
+}
+
+/// Stop a streaming generation; whatever was written so far arrives with the
+/// `Cancelled` event
+#[tauri::command]
+pub async fn cancel_document_generation(
+    session_id: String,
+    doc_gen: State<'_, DocGenState>,
+) -> Result<bool, String> {
+    Ok(doc_gen.cancel(&session_id))
 }
 
 /// Get available document formats
@@ -470,6 +844,7 @@ pub async fn get_available_formats() -> Result<Vec<String>, String> {
     Ok(vec![
         "pdf".to_string(),
         "docx".to_string(),
+        "pptx".to_string(),
         "xlsx".to_string(),
         "md".to_string(),
         "txt".to_string(),
@@ -1042,6 +1417,59 @@ fn wrap_text(text: &str, max_chars: usize) -> Vec<String> {
     lines
 }
 
+/// Lay out Markdown text as a multi-page A4 PDF: headings in bold, fenced
+/// code in Courier, everything else wrapped body text
+pub(crate) fn render_markdown_pdf(title: &str, markdown: &str) -> Result<Vec<u8>, String> {
+    const TOP: f32 = 280.0;
+    const BOTTOM: f32 = 20.0;
+
+    let (doc, page1, layer1) = PdfDocument::new(title, Mm(210.0), Mm(297.0), "Layer 1");
+    let font = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| e.to_string())?;
+    let font_bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(|e| e.to_string())?;
+    let font_mono = doc.add_builtin_font(BuiltinFont::Courier).map_err(|e| e.to_string())?;
+
+    let mut layer = doc.get_page(page1).get_layer(layer1);
+    let mut y = TOP;
+    let mut in_code = false;
+
+    for raw in markdown.lines() {
+        let trimmed = raw.trim_start();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            y -= 2.0;
+            continue;
+        }
+
+        let (lines, size, face, x, step) = if in_code {
+            (wrap_text(raw, 95), 8.5, &font_mono, 24.0, 4.0)
+        } else if let Some(heading) = trimmed.strip_prefix('#') {
+            let level = 1 + heading.chars().take_while(|c| *c == '#').count();
+            let size = match level { 1 => 18.0, 2 => 14.0, _ => 12.0 };
+            y -= 3.0;
+            (wrap_text(heading.trim_start_matches('#').trim(), 70), size, &font_bold, 20.0, size * 0.5)
+        } else {
+            (wrap_text(raw.replace("**", "").trim_end(), 90), 10.0, &font, 20.0, 5.0)
+        };
+
+        for line in lines {
+            if y < BOTTOM {
+                let (page, page_layer) = doc.add_page(Mm(210.0), Mm(297.0), "Layer 1");
+                layer = doc.get_page(page).get_layer(page_layer);
+                y = TOP;
+            }
+            if !line.is_empty() {
+                layer.use_text(&line, size, Mm(x), Mm(y), face);
+            }
+            y -= step;
+        }
+    }
+
+    let mut pdf_bytes = Vec::new();
+    doc.save(&mut BufWriter::new(&mut pdf_bytes))
+        .map_err(|e| format!("Failed to save PDF: {}", e))?;
+    Ok(pdf_bytes)
+}
+
 /// Generate actual PDF binary data
 async fn generate_pdf_report(
     request: GenerateDocumentRequest,
@@ -1174,78 +1602,50 @@ async fn generate_docx_content(
         llm_state
     ).await?;
     
-    // Create DOCX document
-    let mut docx = Docx::new();
-    
-    // Add title
-    docx = docx.add_paragraph(
-        Paragraph::new()
-            .add_run(Run::new().add_text(&format!("{} Report", query)).size(32).bold())
-    );
-    
-    // Add metadata
-    docx = docx.add_paragraph(
-        Paragraph::new()
-            .add_run(Run::new().add_text(&format!(
-                "Generated: {}",
-                chrono::Utc::now().format("%B %d, %Y at %I:%M %p UTC")
-            )).size(20).italic())
-    );
-    
-    // Add executive summary
-    docx = docx.add_paragraph(
-        Paragraph::new()
-            .add_run(Run::new().add_text("Executive Summary").size(28).bold())
-    );
-    
-    docx = docx.add_paragraph(
-        Paragraph::new()
-            .add_run(Run::new().add_text(&llm_content).size(22))
+    office_export::markdown_to_docx(&office_markdown(query, &search_results, &llm_content))
+}
+
+/// Generate PowerPoint content: a slide per report section
+async fn generate_pptx_content(
+    request: GenerateDocumentRequest,
+    rag_state: State<'_, RagState>,
+    llm_state: State<'_, LLMState>,
+) -> Result<Vec<u8>, String> {
+    let default_query = String::from("General Report");
+    let query = request.query.as_ref().unwrap_or(&default_query);
+
+    let (search_results, llm_content) = get_report_content(
+        query,
+        rag_state,
+        llm_state
+    ).await?;
+
+    office_export::markdown_to_pptx(
+        &format!("{} Report", query),
+        &office_markdown(query, &search_results, &llm_content),
+    )
+}
+
+/// Report markdown shared by the Word and PowerPoint exports
+fn office_markdown(query: &str, search_results: &[SimpleSearchResult], llm_content: &str) -> String {
+    let mut markdown = format!(
+        "# {} Report\n\n*Generated: {}*\n\n{}\n",
+        query,
+        chrono::Utc::now().format("%B %d, %Y at %I:%M %p UTC"),
+        llm_content.trim(),
     );
-    
-    // Add sources
+
     if !search_results.is_empty() {
-        docx = docx.add_paragraph(
-            Paragraph::new()
-                .add_run(Run::new().add_text("Sources").size(28).bold())
-        );
-        
+        markdown.push_str("\n## Sources\n\n");
         for (i, result) in search_results.iter().enumerate() {
             let title = result.metadata.get("title")
                 .cloned()
                 .unwrap_or_else(|| format!("Source {}", i + 1));
-            
-            docx = docx.add_paragraph(
-                Paragraph::new()
-                    .add_run(Run::new().add_text(&format!(
-                        "{}. {} (Score: {:.2})",
-                        i + 1,
-                        title,
-                        result.score
-                    )).size(20))
-            );
-            
-            let snippet = if result.text.len() > 200 {
-                format!("{}...", &result.text[..200])
-            } else {
-                result.text.clone()
-            };
-            
-            docx = docx.add_paragraph(
-                Paragraph::new()
-                    .add_run(Run::new().add_text(&snippet).size(18).italic())
-                    .indent(Some(400), None, None, None)
-            );
+            markdown.push_str(&format!("{}. {} (Score: {:.2})\n", i + 1, title, result.score));
         }
     }
-    
-    // Convert to bytes
-    let mut docx_bytes = Vec::new();
-    let mut cursor = std::io::Cursor::new(&mut docx_bytes);
-    docx.build().pack(&mut cursor)
-        .map_err(|e| format!("Failed to generate DOCX: {}", e))?;
-    
-    Ok(docx_bytes)
+
+    markdown
 }
 
 /// Generate Excel/CSV spreadsheet content
@@ -1706,4 +2106,100 @@ pub async fn get_comparable_documents(
 
     tracing::info!("Returning {} documents", docs.len());
     Ok(docs)
-}
\ No newline at end of file
+}
+
+/// Title and text of a recent generation, or of an indexed document rebuilt
+/// from its chunks in `chunk_index` order
+async fn resolve_document(
+    doc_id: &str,
+    rag_state: &RagState,
+    doc_gen: &DocGenState,
+) -> Result<(String, String), String> {
+    if let Some(doc) = doc_gen.generated(doc_id) {
+        return Ok(doc);
+    }
+
+    let rag_guard = rag_state.rag.read().await;
+    let mut chunks: Vec<_> = rag_guard
+        .list_documents(None, 100_000)
+        .await
+        .map_err(|e| format!("Failed to list documents: {}", e))?
+        .into_iter()
+        .filter(|chunk| chunk.metadata.get("doc_id").map(String::as_str) == Some(doc_id))
+        .collect();
+    if chunks.is_empty() {
+        return Err(format!("Document not found: {}", doc_id));
+    }
+    chunks.sort_by_key(|chunk| {
+        chunk.metadata.get("chunk_index")
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(0)
+    });
+
+    let title = chunks[0].metadata.get("title")
+        .or_else(|| chunks[0].metadata.get("file_name"))
+        .cloned()
+        .unwrap_or_else(|| chunks[0].citation.title.clone());
+    let text = chunks.iter()
+        .map(|chunk| chunk.snippet.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    Ok((title, text))
+}
+
+/// Section-by-section, sentence-level diff of two documents, each either a
+/// recent generation (by its response or session id) or an indexed document
+/// (by `doc_id`). Returns a `diff` artifact for side-by-side rendering.
+#[tauri::command]
+pub async fn compare_documents(
+    doc_a_id: String,
+    doc_b_id: String,
+    rag_state: State<'_, RagState>,
+    doc_gen: State<'_, DocGenState>,
+) -> Result<serde_json::Value, String> {
+    tracing::info!("🔀 Comparing documents {} and {}", doc_a_id, doc_b_id);
+
+    let (left_title, left_text) = resolve_document(&doc_a_id, &rag_state, &doc_gen).await?;
+    let (right_title, right_text) = resolve_document(&doc_b_id, &rag_state, &doc_gen).await?;
+    let diff = shodh_rag::document_diff::compare(&left_text, &right_text);
+
+    tracing::info!(
+        "   {} changed, {} added, {} removed (similarity {:.2})",
+        diff.changed, diff.added, diff.removed, diff.similarity
+    );
+
+    Ok(serde_json::json!({
+        "type": "diff",
+        "left": { "id": doc_a_id, "title": left_title },
+        "right": { "id": doc_b_id, "title": right_title },
+        "diff": diff,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completed(events: Vec<SectionEvent>) -> Vec<(usize, String)> {
+        events.into_iter().filter_map(|event| match event {
+            SectionEvent::SectionComplete { index, title, .. } => Some((index, title)),
+            SectionEvent::Outline => None,
+        }).collect()
+    }
+
+    #[test]
+    fn test_section_tracker_splits_streamed_sections() {
+        let mut tracker = SectionTracker::default();
+        let mut events = tracker.push("# Title\n\n## Intro");
+        events.extend(tracker.push("duction\nText [Source 1]\n### Detail\n```md\n## not a section\n```\n"));
+        events.extend(tracker.push("## Conclusion\nDone"));
+        assert_eq!(completed(events), vec![(0, "Introduction".to_string())]);
+
+        let last = tracker.finish();
+        assert!(matches!(&last[..], [SectionEvent::SectionComplete { index: 1, content, .. }] if content == "## Conclusion\nDone"));
+        assert_eq!(
+            tracker.outline.iter().map(|e| (e.level, e.title.as_str())).collect::<Vec<_>>(),
+            vec![(2, "Introduction"), (3, "Detail"), (2, "Conclusion")]
+        );
+    }
+}
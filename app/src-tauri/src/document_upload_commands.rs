@@ -64,6 +64,7 @@ pub async fn upload_document_file(
         "docx" | "doc" => "Word Document",
         "xlsx" | "xls" => "Excel Spreadsheet",
         "pptx" | "ppt" => "PowerPoint",
+        "epub" => "EPUB Ebook",
         "txt" => "Text File",
         "md" => "Markdown",
         _ => "Document",
@@ -0,0 +1,482 @@
+//! Dropbox Integration Commands
+//!
+//! Same surface as the Google Drive integration: OAuth2, browse, download,
+//! folder sync into a Shodh space, status and disconnect. Sync is
+//! incremental: the `list_folder` cursor from the previous sync of a folder
+//! is kept so later syncs only fetch what changed.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{Manager, State};
+
+use crate::cloud_provider::{sync_folder, CloudChanges, CloudFile, CloudProvider};
+use crate::google_drive_commands::{run_oauth_callback_server, FolderSyncConfig, SyncStatus};
+use crate::rag_commands::RagState;
+
+const API: &str = "https://api.dropboxapi.com/2";
+const CONTENT_API: &str = "https://content.dropboxapi.com/2";
+const TOKEN_URL: &str = "https://api.dropboxapi.com/oauth2/token";
+
+/// Dropbox app credentials
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DropboxConfig {
+    pub app_key: String,
+    pub app_secret: String,
+    pub redirect_uri: String,
+}
+
+/// Dropbox OAuth2 tokens
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DropboxTokens {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Global state for the Dropbox integration
+pub struct DropboxState {
+    pub tokens: RwLock<Option<DropboxTokens>>,
+    pub config: RwLock<Option<DropboxConfig>>,
+    pub sync_configs: RwLock<Vec<FolderSyncConfig>>,
+    pub sync_status: RwLock<SyncStatus>,
+    /// `list_folder` cursor per "<space_id>:<folder path>"
+    pub cursors: RwLock<HashMap<String, String>>,
+    pub http_client: Client,
+}
+
+impl DropboxState {
+    pub fn new() -> Self {
+        Self {
+            tokens: RwLock::new(None),
+            config: RwLock::new(None),
+            sync_configs: RwLock::new(Vec::new()),
+            sync_status: RwLock::new(SyncStatus {
+                is_syncing: false,
+                total_files: 0,
+                synced_files: 0,
+                failed_files: 0,
+                last_sync: None,
+                error: None,
+            }),
+            cursors: RwLock::new(HashMap::new()),
+            http_client: Client::new(),
+        }
+    }
+
+    /// Refresh the access token if it expires within the next 60 seconds.
+    /// Returns the current (possibly refreshed) access token.
+    pub async fn get_valid_token(&self) -> Result<String, String> {
+        let (access_token, refresh_token, expired) = match self.tokens.read().as_ref() {
+            Some(t) => (
+                t.access_token.clone(),
+                t.refresh_token.clone(),
+                t.expires_at < Utc::now() + chrono::Duration::seconds(60),
+            ),
+            None => return Err("Not authenticated with Dropbox".to_string()),
+        };
+        if !expired {
+            return Ok(access_token);
+        }
+
+        let config = self.config.read().clone();
+        let (Some(refresh_token), Some(config)) = (refresh_token, config) else {
+            return Err("Cannot refresh: missing refresh token or config".to_string());
+        };
+        tracing::info!("Refreshing Dropbox access token...");
+
+        #[derive(Deserialize)]
+        struct RefreshResponse {
+            access_token: String,
+            expires_in: i64,
+        }
+
+        let params = [
+            ("client_id", config.app_key.as_str()),
+            ("client_secret", config.app_secret.as_str()),
+            ("refresh_token", refresh_token.as_str()),
+            ("grant_type", "refresh_token"),
+        ];
+        let data: RefreshResponse = self.http_client
+            .post(TOKEN_URL)
+            .form(&params)
+            .send()
+            .await
+            .map_err(|e| format!("Token refresh request failed: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Token refresh parse failed: {}", e))?;
+
+        if let Some(t) = self.tokens.write().as_mut() {
+            t.access_token = data.access_token.clone();
+            t.expires_at = Utc::now() + chrono::Duration::seconds(data.expires_in);
+        }
+        Ok(data.access_token)
+    }
+
+    /// POST a JSON API call, turning non-2xx responses into errors
+    async fn rpc(&self, endpoint: &str, body: serde_json::Value) -> Result<reqwest::Response, String> {
+        let access_token = self.get_valid_token().await?;
+        let response = self.http_client
+            .post(format!("{}/{}", API, endpoint))
+            .bearer_auth(&access_token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("Dropbox request failed: {}", e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(format!("Dropbox {} failed ({}): {}", endpoint, status, text));
+        }
+        Ok(response)
+    }
+
+    /// Follow `list_folder` / `list_folder/continue` until `has_more` is false
+    async fn list_all(&self, first: (&str, serde_json::Value)) -> Result<CloudChanges, String> {
+        #[derive(Deserialize)]
+        struct ListFolderResponse {
+            entries: Vec<Entry>,
+            cursor: String,
+            has_more: bool,
+        }
+
+        #[derive(Deserialize)]
+        struct Entry {
+            #[serde(rename = ".tag")]
+            tag: String,
+            name: String,
+            id: Option<String>,
+            path_display: Option<String>,
+            size: Option<u64>,
+            server_modified: Option<String>,
+        }
+
+        let (mut endpoint, mut body) = first;
+        let mut files = Vec::new();
+        loop {
+            let page: ListFolderResponse = self.rpc(endpoint, body)
+                .await?
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse Dropbox listing: {}", e))?;
+            // Deleted entries carry no id; removing them from the index is up to the user
+            files.extend(page.entries.into_iter().filter(|e| e.tag != "deleted").map(|e| {
+                let path = e.path_display.unwrap_or_else(|| format!("/{}", e.name));
+                CloudFile {
+                    id: e.id.unwrap_or_else(|| path.clone()),
+                    name: e.name,
+                    path,
+                    size: e.size,
+                    modified_time: e.server_modified,
+                    is_folder: e.tag == "folder",
+                }
+            }));
+            if !page.has_more {
+                return Ok(CloudChanges { files, cursor: page.cursor });
+            }
+            endpoint = "files/list_folder/continue";
+            body = serde_json::json!({ "cursor": page.cursor });
+        }
+    }
+}
+
+impl Default for DropboxState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Dropbox names the root "" rather than "/"
+fn api_path(folder: Option<&str>) -> String {
+    match folder.map(str::trim) {
+        None | Some("") | Some("/") => String::new(),
+        Some(path) if path.starts_with('/') || path.starts_with("id:") => path.to_string(),
+        Some(path) => format!("/{}", path),
+    }
+}
+
+/// Whether a `list_folder/continue` error means the cursor is no longer
+/// valid: Dropbox answers 409 with the `reset` error tag
+fn is_cursor_reset(error: &str) -> bool {
+    let Some((_, body)) = error.split_once("(409 Conflict): ") else {
+        return false;
+    };
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| v["error"][".tag"].as_str().map(|tag| tag == "reset"))
+        .unwrap_or_else(|| body.contains("reset/"))
+}
+
+#[async_trait]
+impl CloudProvider for DropboxState {
+    fn name(&self) -> &'static str {
+        "Dropbox"
+    }
+
+    async fn list(&self, folder: Option<&str>) -> Result<Vec<CloudFile>, String> {
+        let body = serde_json::json!({ "path": api_path(folder), "recursive": false });
+        Ok(self.list_all(("files/list_folder", body)).await?.files)
+    }
+
+    async fn download(&self, file: &CloudFile, save_path: &Path) -> Result<(), String> {
+        let access_token = self.get_valid_token().await?;
+        let arg = serde_json::json!({ "path": file.id }).to_string();
+        let response = self.http_client
+            .post(format!("{}/files/download", CONTENT_API))
+            .bearer_auth(&access_token)
+            .header("Dropbox-API-Arg", arg)
+            .send()
+            .await
+            .map_err(|e| format!("Download failed: {}", e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(format!("Download failed ({}): {}", status, text));
+        }
+        let bytes = response.bytes().await.map_err(|e| format!("Failed to read file bytes: {}", e))?;
+        std::fs::write(save_path, bytes).map_err(|e| format!("Failed to save file: {}", e))
+    }
+
+    async fn changes(&self, folder: &str, recursive: bool, cursor: Option<&str>) -> Result<CloudChanges, String> {
+        if let Some(cursor) = cursor {
+            let body = serde_json::json!({ "cursor": cursor });
+            match self.list_all(("files/list_folder/continue", body)).await {
+                Ok(changes) => return Ok(changes),
+                // Cursors expire (`reset`); start over with a full listing.
+                // Anything else fails the sync and keeps the cursor.
+                Err(e) if is_cursor_reset(&e) => {
+                    tracing::warn!("Dropbox cursor expired, doing a full sync: {}", e)
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        let body = serde_json::json!({ "path": api_path(Some(folder)), "recursive": recursive });
+        self.list_all(("files/list_folder", body)).await
+    }
+}
+
+/// Start Dropbox OAuth2 and a one-shot callback server. The callback server
+/// listens on localhost:3000 and emits `dropbox-auth-code` when Dropbox
+/// redirects back with the authorization code.
+#[tauri::command]
+pub async fn init_dropbox_oauth(
+    app_key: String,
+    app_secret: String,
+    state: State<'_, Arc<DropboxState>>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    tracing::info!("Initializing Dropbox OAuth...");
+
+    let redirect_uri = "http://localhost:3000/oauth/dropbox/callback".to_string();
+    *state.config.write() = Some(DropboxConfig {
+        app_key: app_key.clone(),
+        app_secret,
+        redirect_uri: redirect_uri.clone(),
+    });
+
+    // token_access_type=offline returns a refresh token
+    let auth_url = format!(
+        "https://www.dropbox.com/oauth2/authorize?client_id={}&redirect_uri={}&response_type=code&token_access_type=offline",
+        urlencoding::encode(&app_key),
+        urlencoding::encode(&redirect_uri)
+    );
+
+    tokio::spawn(async move {
+        if let Err(e) = run_oauth_callback_server(app_handle, "/oauth/dropbox/callback", "dropbox").await {
+            tracing::error!("OAuth callback server error: {}", e);
+        }
+    });
+
+    Ok(auth_url)
+}
+
+/// Exchange the authorization code for tokens
+#[tauri::command]
+pub async fn exchange_dropbox_code(
+    code: String,
+    state: State<'_, Arc<DropboxState>>,
+) -> Result<DropboxTokens, String> {
+    let config = state.config.read().clone().ok_or("OAuth not initialized")?;
+
+    let params = [
+        ("client_id", config.app_key.as_str()),
+        ("client_secret", config.app_secret.as_str()),
+        ("code", code.as_str()),
+        ("redirect_uri", config.redirect_uri.as_str()),
+        ("grant_type", "authorization_code"),
+    ];
+
+    #[derive(Deserialize)]
+    struct TokenResponse {
+        access_token: String,
+        refresh_token: Option<String>,
+        expires_in: i64,
+    }
+
+    let token_data: TokenResponse = state.http_client
+        .post(TOKEN_URL)
+        .form(&params)
+        .send()
+        .await
+        .map_err(|e| format!("Token exchange failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse token response: {}", e))?;
+
+    let tokens = DropboxTokens {
+        access_token: token_data.access_token,
+        refresh_token: token_data.refresh_token,
+        expires_at: Utc::now() + chrono::Duration::seconds(token_data.expires_in),
+    };
+    *state.tokens.write() = Some(tokens.clone());
+
+    tracing::info!("✅ Dropbox access token obtained");
+    Ok(tokens)
+}
+
+/// List a Dropbox folder (the root when `path` is None)
+#[tauri::command]
+pub async fn list_dropbox_files(
+    path: Option<String>,
+    state: State<'_, Arc<DropboxState>>,
+) -> Result<Vec<CloudFile>, String> {
+    state.list(path.as_deref()).await
+}
+
+/// Download a file by path or `id:` to `save_path`
+#[tauri::command]
+pub async fn download_dropbox_file(
+    path: String,
+    save_path: String,
+    state: State<'_, Arc<DropboxState>>,
+) -> Result<String, String> {
+    let file = CloudFile {
+        id: api_path(Some(&path)),
+        name: path.rsplit('/').next().unwrap_or(&path).to_string(),
+        path,
+        size: None,
+        modified_time: None,
+        is_folder: false,
+    };
+    state.download(&file, Path::new(&save_path)).await?;
+    Ok(save_path)
+}
+
+/// Configure folder sync (`folder_id` is the Dropbox folder path)
+#[tauri::command]
+pub async fn configure_dropbox_folder_sync(
+    config: FolderSyncConfig,
+    state: State<'_, Arc<DropboxState>>,
+) -> Result<(), String> {
+    tracing::info!("⚙️ Configuring Dropbox sync: {} -> space {}", config.folder_name, config.space_id);
+    let mut sync_configs = state.sync_configs.write();
+    sync_configs.retain(|c| c.folder_id != config.folder_id);
+    sync_configs.push(config);
+    Ok(())
+}
+
+/// Sync a Dropbox folder into a Shodh space. Only files changed since the
+/// last sync of the same folder into the same space are downloaded.
+#[tauri::command]
+pub async fn sync_dropbox_folder(
+    folder_path: String,
+    space_id: String,
+    dropbox_state: State<'_, Arc<DropboxState>>,
+    rag_state: State<'_, RagState>,
+    app: tauri::AppHandle,
+) -> Result<SyncStatus, String> {
+    tracing::info!("🔄 Starting Dropbox sync: {} -> space {}", folder_path, space_id);
+
+    let config = dropbox_state.sync_configs.read()
+        .iter()
+        .find(|c| c.folder_id == folder_path)
+        .cloned()
+        .map(|c| FolderSyncConfig { space_id: space_id.clone(), ..c })
+        .unwrap_or_else(|| FolderSyncConfig {
+            folder_id: folder_path.clone(),
+            folder_name: folder_path.clone(),
+            space_id: space_id.clone(),
+            auto_sync: false,
+            sync_subdirectories: true,
+        });
+
+    let temp_dir = app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("dropbox_temp");
+
+    let cursor_key = format!("{}:{}", space_id, folder_path);
+    let cursor = dropbox_state.cursors.read().get(&cursor_key).cloned();
+    let provider: &DropboxState = &dropbox_state;
+
+    let (status, next_cursor) = sync_folder(
+        provider,
+        &config,
+        cursor.as_deref(),
+        &temp_dir,
+        &dropbox_state.sync_status,
+        rag_state,
+    ).await?;
+    dropbox_state.cursors.write().insert(cursor_key, next_cursor);
+    Ok(status)
+}
+
+/// Get current sync status
+#[tauri::command]
+pub async fn get_dropbox_sync_status(
+    state: State<'_, Arc<DropboxState>>,
+) -> Result<SyncStatus, String> {
+    Ok(state.sync_status.read().clone())
+}
+
+/// Check if authenticated with Dropbox
+#[tauri::command]
+pub async fn is_dropbox_authenticated(
+    state: State<'_, Arc<DropboxState>>,
+) -> Result<bool, String> {
+    Ok(state.tokens.read().is_some())
+}
+
+/// Disconnect Dropbox, forgetting tokens, sync settings and cursors
+#[tauri::command]
+pub async fn disconnect_dropbox(
+    state: State<'_, Arc<DropboxState>>,
+) -> Result<(), String> {
+    tracing::info!("🔌 Disconnecting Dropbox...");
+    *state.tokens.write() = None;
+    *state.config.write() = None;
+    state.sync_configs.write().clear();
+    state.cursors.write().clear();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_path() {
+        assert_eq!(api_path(None), "");
+        assert_eq!(api_path(Some("/")), "");
+        assert_eq!(api_path(Some("Cases/2024")), "/Cases/2024");
+        assert_eq!(api_path(Some("id:a4ayc")), "id:a4ayc");
+    }
+
+    #[test]
+    fn test_only_reset_errors_restart_the_listing() {
+        let reset = r#"Dropbox files/list_folder/continue failed (409 Conflict): {"error_summary": "reset/..", "error": {".tag": "reset"}}"#;
+        assert!(is_cursor_reset(reset));
+        assert!(is_cursor_reset("Dropbox files/list_folder/continue failed (409 Conflict): reset/..."));
+
+        let bad_path = r#"Dropbox files/list_folder/continue failed (409 Conflict): {"error_summary": "path/not_found/..", "error": {".tag": "path"}}"#;
+        assert!(!is_cursor_reset(bad_path));
+        assert!(!is_cursor_reset("Dropbox files/list_folder/continue failed (401 Unauthorized): expired_access_token"));
+        assert!(!is_cursor_reset("Dropbox request failed: connection refused"));
+    }
+}
@@ -21,6 +21,7 @@ pub async fn link_folder_enhanced(
     folder_path: String,
     space_id: String,
     options: IndexingOptions,
+    force: Option<bool>,
     state: State<'_, RagState>,
     indexing_state: State<'_, IndexingState>,
 ) -> Result<IndexingResult, String> {
@@ -31,6 +32,7 @@ pub async fn link_folder_enhanced(
         &folder_path,
         &space_id,
         &options,
+        force.unwrap_or(false),
         &mut *rag_guard,
         &indexing_state,
         Some(&emitter as &dyn shodh_rag::chat::EventEmitter),
@@ -75,6 +77,7 @@ pub async fn index_single_file(
     app: AppHandle,
     file_path: String,
     space_id: String,
+    force: Option<bool>,
     state: State<'_, RagState>,
 ) -> Result<IndexingResult, String> {
     let emitter = TauriEventEmitter::new(app);
@@ -83,6 +86,7 @@ pub async fn index_single_file(
     shodh_rag::indexing::index_single_file(
         &file_path,
         &space_id,
+        force.unwrap_or(false),
         &mut *rag_guard,
         Some(&emitter as &dyn shodh_rag::chat::EventEmitter),
     ).await
@@ -175,7 +175,7 @@ impl FileWatcherManager {
             let ext_str = ext.to_string_lossy().to_lowercase();
             matches!(
                 ext_str.as_str(),
-                "txt" | "md" | "pdf" | "html" | "json" | "csv" | "docx" | 
+                "txt" | "md" | "pdf" | "html" | "json" | "csv" | "docx" | "epub" |
                 "rs" | "py" | "js" | "ts" | "jsx" | "tsx" | "java" | "cpp" | 
                 "c" | "h" | "go" | "rb" | "php" | "swift" | "kt"
             )
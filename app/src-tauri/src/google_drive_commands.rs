@@ -193,7 +193,7 @@ pub async fn init_google_drive_oauth(
     // Start a one-shot callback server in the background
     let handle = app_handle.clone();
     tokio::spawn(async move {
-        if let Err(e) = run_oauth_callback_server(handle).await {
+        if let Err(e) = run_oauth_callback_server(handle, "/oauth/google/callback", "google-drive").await {
             tracing::error!("OAuth callback server error: {}", e);
         }
     });
@@ -202,18 +202,23 @@ pub async fn init_google_drive_oauth(
     Ok(auth_url)
 }
 
-/// One-shot HTTP server that captures the OAuth redirect and emits a Tauri event.
-/// Shuts down immediately after receiving one request.
-async fn run_oauth_callback_server(app_handle: tauri::AppHandle) -> Result<(), String> {
+/// One-shot HTTP server on :3000 that captures the OAuth redirect to
+/// `callback_path` and emits `<event_prefix>-auth-code` (or `-auth-error`).
+/// Shuts down after five minutes.
+pub(crate) async fn run_oauth_callback_server(
+    app_handle: tauri::AppHandle,
+    callback_path: &'static str,
+    event_prefix: &'static str,
+) -> Result<(), String> {
     use axum::{Router, routing::get, extract::Query, response::Html};
     use std::collections::HashMap;
 
     let handle = app_handle.clone();
 
-    let app = Router::new().route("/oauth/google/callback", get(
-        |Query(params): Query<HashMap<String, String>>| async move {
+    let app = Router::new().route(callback_path, get(
+        move |Query(params): Query<HashMap<String, String>>| async move {
             if let Some(code) = params.get("code") {
-                let _ = handle.emit("google-drive-auth-code", code.clone());
+                let _ = handle.emit(&format!("{}-auth-code", event_prefix), code.clone());
                 Html(r#"<html><body style="font-family:system-ui;display:flex;align-items:center;justify-content:center;height:100vh;margin:0;background:#0c0c0d;color:#f0f0f2">
                     <div style="text-align:center">
                         <h2>Authentication Successful</h2>
@@ -223,7 +228,7 @@ async fn run_oauth_callback_server(app_handle: tauri::AppHandle) -> Result<(), S
                 </body></html>"#.to_string())
             } else {
                 let error = params.get("error").cloned().unwrap_or_else(|| "Unknown error".to_string());
-                let _ = handle.emit("google-drive-auth-error", error.clone());
+                let _ = handle.emit(&format!("{}-auth-error", event_prefix), error.clone());
                 Html(format!(r#"<html><body style="font-family:system-ui;display:flex;align-items:center;justify-content:center;height:100vh;margin:0;background:#0c0c0d;color:#f0f0f2">
                     <div style="text-align:center">
                         <h2>Authentication Failed</h2>
@@ -369,21 +374,89 @@ pub async fn list_google_drive_files(
     Ok(files)
 }
 
-/// Download a file from Google Drive
-#[tauri::command]
-pub async fn download_google_drive_file(
-    file_id: String,
-    save_path: String,
-    state: State<'_, Arc<GoogleDriveState>>,
-) -> Result<String, String> {
-    tracing::info!("Downloading file: {}", file_id);
+/// Export format for a Google-native file (Docs, Sheets, Slides, ...),
+/// which has no binary content to download
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NativeExport {
+    pub mime_type: &'static str,
+    pub extension: &'static str,
+}
+
+/// How to export a Google-native MIME type for the parsers. None for
+/// regular files and for native types with nothing to index (folders,
+/// forms, shortcuts, sites).
+pub fn native_export(mime_type: &str) -> Option<NativeExport> {
+    let (mime_type, extension) = match mime_type {
+        "application/vnd.google-apps.document" => ("text/markdown", "md"),
+        // xlsx keeps every sheet; CSV export only has the first
+        "application/vnd.google-apps.spreadsheet" => {
+            ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet", "xlsx")
+        }
+        "application/vnd.google-apps.presentation" => ("application/pdf", "pdf"),
+        "application/vnd.google-apps.drawing" => ("application/pdf", "pdf"),
+        "application/vnd.google-apps.script" => ("application/vnd.google-apps.script+json", "json"),
+        _ => return None,
+    };
+    Some(NativeExport { mime_type, extension })
+}
+
+/// A downloaded (or exported) Drive file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadedFile {
+    pub path: String,
+    pub original_mime_type: String,
+    /// Set when a Google-native file was exported
+    pub exported_mime_type: Option<String>,
+}
+
+async fn fetch_mime_type(state: &GoogleDriveState, access_token: &str, file_id: &str) -> Result<String, String> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct FileMeta {
+        mime_type: String,
+    }
+
+    let url = format!("https://www.googleapis.com/drive/v3/files/{}?fields=mimeType", file_id);
+    let meta: FileMeta = state.http_client
+        .get(&url)
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to get file metadata: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Failed to get file metadata: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse file metadata: {}", e))?;
+    Ok(meta.mime_type)
+}
 
+/// Download `file_id` to `save_path`, exporting Google-native files with
+/// `files.export` (see `native_export`). Looks up the MIME type when not given.
+pub(crate) async fn download_drive_file(
+    state: &GoogleDriveState,
+    file_id: &str,
+    mime_type: Option<String>,
+    save_path: &str,
+) -> Result<DownloadedFile, String> {
     let access_token = state.get_valid_token().await?;
+    let original_mime_type = match mime_type {
+        Some(mime_type) => mime_type,
+        None => fetch_mime_type(state, &access_token, file_id).await?,
+    };
 
-    let url = format!(
-        "https://www.googleapis.com/drive/v3/files/{}?alt=media",
-        file_id
-    );
+    let export = native_export(&original_mime_type);
+    if export.is_none() && original_mime_type.starts_with("application/vnd.google-apps.") {
+        return Err(format!("Google Drive items of type {} cannot be downloaded", original_mime_type));
+    }
+    let url = match export {
+        Some(export) => format!(
+            "https://www.googleapis.com/drive/v3/files/{}/export?mimeType={}",
+            file_id,
+            urlencoding::encode(export.mime_type)
+        ),
+        None => format!("https://www.googleapis.com/drive/v3/files/{}?alt=media", file_id),
+    };
 
     let response = state.http_client
         .get(&url)
@@ -391,17 +464,42 @@ pub async fn download_google_drive_file(
         .send()
         .await
         .map_err(|e| format!("Download failed: {}", e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Download failed ({}): {}", status, body));
+    }
 
     let bytes = response
         .bytes()
         .await
         .map_err(|e| format!("Failed to read file bytes: {}", e))?;
 
-    std::fs::write(&save_path, bytes)
+    std::fs::write(save_path, bytes)
         .map_err(|e| format!("Failed to save file: {}", e))?;
 
-    tracing::info!("File downloaded: {}", save_path);
-    Ok(save_path)
+    Ok(DownloadedFile {
+        path: save_path.to_string(),
+        original_mime_type,
+        exported_mime_type: export.map(|e| e.mime_type.to_string()),
+    })
+}
+
+/// Download a file from Google Drive. Google Docs, Sheets and Slides are
+/// exported as Markdown, xlsx and PDF.
+#[tauri::command]
+pub async fn download_google_drive_file(
+    file_id: String,
+    save_path: String,
+    mime_type: Option<String>,
+    state: State<'_, Arc<GoogleDriveState>>,
+) -> Result<String, String> {
+    tracing::info!("Downloading file: {}", file_id);
+
+    let downloaded = download_drive_file(&state, &file_id, mime_type, &save_path).await?;
+
+    tracing::info!("File downloaded: {}", downloaded.path);
+    Ok(downloaded.path)
 }
 
 /// Configure folder sync
@@ -455,10 +553,12 @@ pub async fn sync_google_drive_folder(
             e
         })?;
 
-    // Filter for supported file types (PDF, DOCX, XLSX, TXT)
+    // Filter for supported file types (PDF, DOCX, XLSX, TXT) and
+    // Google-native files we can export
     let supported_files: Vec<_> = files.into_iter()
         .filter(|f| !f.is_folder)
         .filter(|f| {
+            native_export(&f.mime_type).is_some() ||
             f.mime_type.contains("pdf") ||
             f.mime_type.contains("document") ||
             f.mime_type.contains("spreadsheet") ||
@@ -491,7 +591,10 @@ pub async fn sync_google_drive_folder(
         tracing::info!("⬇️ Downloading: {}", file.name);
 
         // Determine file extension from mime type
-        let extension = if file.mime_type.contains("pdf") {
+        let export = native_export(&file.mime_type);
+        let extension = if let Some(export) = export {
+            export.extension
+        } else if file.mime_type.contains("pdf") {
             "pdf"
         } else if file.mime_type.contains("wordprocessingml") || file.mime_type.contains("msword") {
             "docx"
@@ -501,7 +604,10 @@ pub async fn sync_google_drive_folder(
             "txt"
         };
 
-        let file_name = if file.name.contains('.') {
+        // Native files have no extension of their own; name them after the export
+        let file_name = if export.is_some() {
+            format!("{}.{}", file.name, extension)
+        } else if file.name.contains('.') {
             file.name.clone()
         } else {
             format!("{}.{}", file.name, extension)
@@ -509,13 +615,15 @@ pub async fn sync_google_drive_folder(
 
         let save_path = temp_download_dir.join(&file_name);
 
-        // Download file
-        match download_google_drive_file(
-            file.id.clone(),
-            save_path.to_string_lossy().to_string(),
-            drive_state.clone()
+        // Download (or export) file
+        match download_drive_file(
+            &drive_state,
+            &file.id,
+            Some(file.mime_type.clone()),
+            &save_path.to_string_lossy(),
         ).await {
-            Ok(downloaded_path) => {
+            Ok(downloaded) => {
+                let downloaded_path = downloaded.path;
                 tracing::info!("✅ Downloaded to: {}", downloaded_path);
 
                 // Prepare metadata for indexing
@@ -526,6 +634,10 @@ pub async fn sync_google_drive_folder(
                 metadata.insert("file_path".to_string(), downloaded_path.clone());
                 metadata.insert("original_name".to_string(), file.name.clone());
                 metadata.insert("google_drive_id".to_string(), file.id.clone());
+                metadata.insert("original_mime_type".to_string(), downloaded.original_mime_type);
+                if let Some(exported) = downloaded.exported_mime_type {
+                    metadata.insert("exported_mime_type".to_string(), exported);
+                }
                 metadata.insert("sync_date".to_string(), Utc::now().to_rfc3339());
 
                 // Use the upload_file command which properly handles PDF/DOCX/XLSX parsing
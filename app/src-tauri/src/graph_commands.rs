@@ -5,9 +5,20 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use tauri::State;
 use crate::rag_commands::RagState;
-use shodh_rag::types::MetadataFilter;
+use shodh_rag::graph::{self, GraphExportFormat, GraphExportStats, KnowledgeGraph, Neighborhood};
+use shodh_rag::types::{DocumentSort, MetadataFilter};
+
+/// Documents read into the entity graph; one result per document
+const GRAPH_DOCUMENT_LIMIT: usize = 100_000;
+/// Default radius of an entity-centred export or neighborhood
+const DEFAULT_HOPS: usize = 2;
+/// Neighborhood bounds, so a hub entity can't return the whole graph
+const DEFAULT_NEIGHBORHOOD_NODES: usize = 50;
+const MAX_NEIGHBORHOOD_NODES: usize = 500;
+const MAX_NEIGHBORHOOD_HOPS: usize = 4;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -122,18 +133,7 @@ pub async fn get_knowledge_graph(
 
             // Extract language/technology from file extension
             if let Some(ext) = doc.metadata.get("file_extension") {
-                let tech = match ext.as_str() {
-                    "rs" => Some("Rust"),
-                    "py" => Some("Python"),
-                    "js" | "jsx" => Some("JavaScript"),
-                    "ts" | "tsx" => Some("TypeScript"),
-                    "java" => Some("Java"),
-                    "go" => Some("Go"),
-                    "cpp" | "cc" | "cxx" | "c" | "h" => Some("C/C++"),
-                    _ => None,
-                };
-
-                if let Some(tech) = tech {
+                if let Some(tech) = graph::technology_for_extension(ext) {
                     *entities.entry(tech.to_string()).or_insert(0) += 1;
                     edges.push(GraphEdge {
                         source: doc_id.clone(),
@@ -248,6 +248,82 @@ pub async fn get_knowledge_graph(
     Ok(GraphData { nodes, edges })
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphExportResult {
+    pub path: String,
+    pub format: GraphExportFormat,
+    #[serde(flatten)]
+    pub stats: GraphExportStats,
+}
+
+/// Export the document knowledge graph (documents, topics, languages, folders,
+/// authors and the typed edges between them, each with the documents that
+/// evidence it) as GraphML or node-link JSON. With `entity`, only the subgraph
+/// within `hops` edges of it is exported.
+#[tauri::command]
+pub async fn export_knowledge_graph(
+    state: State<'_, RagState>,
+    format: GraphExportFormat,
+    out_path: String,
+    space_id: Option<String>,
+    entity: Option<String>,
+    hops: Option<usize>,
+) -> Result<GraphExportResult, String> {
+    tracing::info!("Exporting knowledge graph as {:?} to {} (space: {:?}, entity: {:?})", format, out_path, space_id, entity);
+
+    let mut knowledge_graph = load_document_graph(&state, space_id).await?;
+
+    let path = PathBuf::from(&out_path);
+    let stats = tokio::task::spawn_blocking(move || -> Result<GraphExportStats, String> {
+        if let Some(entity) = entity {
+            knowledge_graph = knowledge_graph
+                .subgraph(&entity, hops.unwrap_or(DEFAULT_HOPS))
+                .ok_or_else(|| format!("Entity not found in knowledge graph: {}", entity))?;
+        }
+        let file = std::fs::File::create(&path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        graph::export_graph(&knowledge_graph, format, std::io::BufWriter::new(file))
+            .map_err(|e| format!("Failed to write graph: {:#}", e))
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))??;
+
+    tracing::info!("Exported knowledge graph: {} nodes, {} edges", stats.nodes, stats.edges);
+    Ok(GraphExportResult { path: out_path, format, stats })
+}
+
+/// Entities within `hops` (default 2) edges of `entity`, strongest
+/// connections first, bounded by `max_nodes` (default 50). Edges carry their
+/// type and the documents that evidence them.
+#[tauri::command]
+pub async fn get_entity_neighborhood(
+    state: State<'_, RagState>,
+    entity: String,
+    hops: Option<usize>,
+    max_nodes: Option<usize>,
+    space_id: Option<String>,
+) -> Result<Neighborhood, String> {
+    let hops = hops.unwrap_or(DEFAULT_HOPS).min(MAX_NEIGHBORHOOD_HOPS);
+    let max_nodes = max_nodes.unwrap_or(DEFAULT_NEIGHBORHOOD_NODES).clamp(1, MAX_NEIGHBORHOOD_NODES);
+
+    let knowledge_graph = load_document_graph(&state, space_id).await?;
+    graph::neighborhood(&knowledge_graph, &entity, hops, max_nodes)
+        .ok_or_else(|| format!("Entity not found in knowledge graph: {}", entity))
+}
+
+/// Entity graph of the indexed documents, optionally limited to one space
+async fn load_document_graph(state: &State<'_, RagState>, space_id: Option<String>) -> Result<KnowledgeGraph, String> {
+    let filter = MetadataFilter { space_id, ..Default::default() };
+    let documents = {
+        let rag = state.rag.read().await;
+        rag.filter_documents(&filter, DocumentSort::Date, GRAPH_DOCUMENT_LIMIT)
+            .await
+            .map_err(|e| format!("Failed to list documents: {}", e))?
+    };
+    Ok(graph::build_document_graph(&documents))
+}
+
 /// Generate sample graph data for demonstration
 fn generate_sample_graph_data() -> GraphData {
     let mut nodes = Vec::new();
@@ -1,10 +1,21 @@
-//! Image upload, OCR (Windows OCR API), and form export commands
+//! Image upload, OCR, visual search, and form export commands
 
+use futures::StreamExt;
+use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{Emitter, State};
 use uuid::Uuid;
-use shodh_rag::rag::{FormField, export_form_as_html, export_form_as_json_schema};
-
+use shodh_rag::comprehensive_system::{Citation, DocumentFormat, SimpleSearchResult};
+use shodh_rag::embeddings::clip::{ClipEmbeddings, CLIP_MODEL_DIR};
+use shodh_rag::indexing::{file_content_hash, IndexingState};
+use shodh_rag::processing::ocr;
+use shodh_rag::search::{weighted_fusion, HybridSource, ImageEntry, ImageIndex};
+use shodh_rag::rag::{FormField, StructuredOutput, export_form_as_html, export_form_as_json_schema, extract_form};
+
+use crate::llm_commands::LLMState;
 use crate::rag_commands::RagState;
 
 /// Result of image processing
@@ -19,93 +30,326 @@ pub struct ImageProcessResult {
     pub image_data: String,
 }
 
-// ─── Windows OCR via temp file + WinRT ──────────────────────────────────────
+/// Outcome of one image in a batch upload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchImageStatus {
+    Processed,
+    /// Same bytes already indexed, or earlier in the same batch
+    Duplicate,
+    Failed,
+    /// Not started before the batch was cancelled
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchImageResult {
+    pub file_path: String,
+    /// New image id, or the already indexed one for duplicates
+    pub id: Option<String>,
+    pub status: BatchImageStatus,
+    pub word_count: usize,
+    /// Read or OCR error. An image whose OCR failed is still indexed visually.
+    pub error: Option<String>,
+}
+
+/// Result of `process_images_from_files`, in input order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchImageSummary {
+    pub results: Vec<BatchImageResult>,
+    pub succeeded: usize,
+    pub duplicates: usize,
+    pub failed: usize,
+    pub cancelled: bool,
+}
+
+/// Weight of the visual (CLIP) score against the OCR text score
+const VISUAL_WEIGHT: f32 = 0.5;
+
+/// CLIP text-image similarity below this is treated as no visual match
+const MIN_VISUAL_SCORE: f32 = 0.18;
+
+/// Images OCR'd and embedded at the same time in a batch upload
+const IMAGE_BATCH_CONCURRENCY: usize = 4;
+
+/// CLIP model and the image vectors, keyed by image id
+pub struct ImageSearchState {
+    clip: Option<ClipEmbeddings>,
+    index: RwLock<ImageIndex>,
+    /// Cancellation for `process_images_from_files`
+    batch: IndexingState,
+    /// A `process_images_from_files` run is in progress
+    batch_running: AtomicBool,
+}
+
+impl ImageSearchState {
+    /// Loads CLIP from `<model_dir>/clip-vit-base-patch32` when present;
+    /// without it images are searchable by OCR text only.
+    pub fn load(model_dir: &Path, data_dir: &Path) -> Self {
+        let clip_dir = model_dir.join(CLIP_MODEL_DIR);
+        let clip = if clip_dir.exists() {
+            match ClipEmbeddings::new(&clip_dir) {
+                Ok(clip) => {
+                    tracing::info!("CLIP image embeddings loaded from {}", clip_dir.display());
+                    Some(clip)
+                }
+                Err(e) => {
+                    tracing::warn!("CLIP not available ({}), image search uses OCR text only", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let index = ImageIndex::load(data_dir.join("image_index.json")).unwrap_or_else(|e| {
+            // Keep the file for inspection; new vectors stay in memory this session
+            tracing::warn!("{:#}; starting with an empty image index", e);
+            ImageIndex::default()
+        });
+
+        Self {
+            clip,
+            index: RwLock::new(index),
+            batch: IndexingState::default(),
+            batch_running: AtomicBool::new(false),
+        }
+    }
+}
+
+// ─── OCR (Windows OCR API, Tesseract elsewhere) ─────────────────────────────
 
-#[cfg(target_os = "windows")]
 async fn run_ocr(image_bytes: &[u8]) -> Result<(String, f32), String> {
-    // Save to a temp PNG file (Windows OCR needs a file stream)
-    let tmp_dir = std::env::temp_dir();
-    let tmp_path = tmp_dir.join(format!("shodh_ocr_{}.png", Uuid::new_v4()));
-
-    // Decode and re-encode as PNG to ensure valid format
-    let img = image::load_from_memory(image_bytes)
-        .map_err(|e| format!("Failed to decode image: {}", e))?;
-    img.save_with_format(&tmp_path, image::ImageFormat::Png)
-        .map_err(|e| format!("Failed to save temp image: {}", e))?;
-
-    let result = run_ocr_on_file(tmp_path.clone()).await;
-    let _ = std::fs::remove_file(&tmp_path);
-    result
+    let bytes = image_bytes.to_vec();
+    // WinRT and tesseract both block — keep them off the async runtime
+    let text = tokio::task::spawn_blocking(move || ocr::extract_text(&bytes))
+        .await
+        .map_err(|e| format!("OCR task panicked: {}", e))?
+        .map_err(|e| format!("{:#}", e))?;
+
+    let word_count = text.split_whitespace().count();
+    let confidence: f32 = if word_count > 0 { 0.9 } else { 0.0 };
+    Ok((text, confidence))
 }
 
-#[cfg(target_os = "windows")]
-async fn run_ocr_on_file(path: std::path::PathBuf) -> Result<(String, f32), String> {
-    // WinRT IAsyncOperation does not implement Rust Future — run blocking on a separate thread
-    tokio::task::spawn_blocking(move || {
-        use windows::Storage::{StorageFile, FileAccessMode};
-        use windows::Graphics::Imaging::BitmapDecoder;
-        use windows::Media::Ocr::OcrEngine;
-        use windows::core::HSTRING;
-
-        let abs_path = std::fs::canonicalize(&path)
-            .map_err(|e| format!("Path error: {}", e))?;
-        let path_str = abs_path.to_string_lossy().to_string();
-        // Strip \\?\ prefix that canonicalize adds on Windows
-        let clean_path = path_str.strip_prefix(r"\\?\").unwrap_or(&path_str);
-
-        let file = StorageFile::GetFileFromPathAsync(&HSTRING::from(clean_path))
-            .map_err(|e| format!("GetFileFromPath failed: {}", e))?
-            .get()
-            .map_err(|e| format!("GetFileFromPath get failed: {}", e))?;
-
-        let stream = file.OpenAsync(FileAccessMode::Read)
-            .map_err(|e| format!("OpenAsync failed: {}", e))?
-            .get()
-            .map_err(|e| format!("OpenAsync get failed: {}", e))?;
-
-        let decoder = BitmapDecoder::CreateAsync(&stream)
-            .map_err(|e| format!("BitmapDecoder failed: {}", e))?
-            .get()
-            .map_err(|e| format!("BitmapDecoder get failed: {}", e))?;
-
-        let bitmap = decoder.GetSoftwareBitmapAsync()
-            .map_err(|e| format!("GetSoftwareBitmap failed: {}", e))?
-            .get()
-            .map_err(|e| format!("GetSoftwareBitmap get failed: {}", e))?;
-
-        let engine = OcrEngine::TryCreateFromUserProfileLanguages()
-            .map_err(|e| format!("OCR engine creation failed: {}", e))?;
-
-        let ocr_result = engine.RecognizeAsync(&bitmap)
-            .map_err(|e| format!("RecognizeAsync failed: {}", e))?
-            .get()
-            .map_err(|e| format!("RecognizeAsync get failed: {}", e))?;
-
-        let text = ocr_result.Text()
-            .map_err(|e| format!("Text() failed: {}", e))?
-            .to_string();
-
-        let word_count = text.split_whitespace().count();
-        let confidence: f32 = if word_count > 0 { 0.9 } else { 0.0 };
-
-        Ok((text, confidence))
-    })
-    .await
-    .map_err(|e| format!("OCR task panicked: {}", e))?
+// ─── Indexing ───────────────────────────────────────────────────────────────
+
+/// An uploaded image on its way into the indexes
+struct ImageUpload<'a> {
+    id: &'a str,
+    /// Empty for pasted images
+    file_path: &'a str,
+    /// blake3 of the file, empty for pasted images and images whose OCR
+    /// failed (so a later upload retries them instead of skipping them)
+    content_hash: String,
+    space_id: Option<&'a str>,
+}
+
+/// Hash to record for an image: none when OCR failed
+fn recorded_hash(content_hash: String, ocr_failed: bool) -> String {
+    if ocr_failed { String::new() } else { content_hash }
+}
+
+/// Whether a batch image has to be processed
+#[derive(Debug, PartialEq, Eq)]
+enum DuplicateCheck {
+    New,
+    /// Same bytes already indexed in the target space under this id
+    Indexed(String),
+    /// Same bytes as another image of this batch
+    InBatch,
+}
+
+/// Check `content_hash` against the space's indexed images, then claim it
+/// for this batch
+fn check_duplicate(
+    index: &ImageIndex,
+    claimed: &Mutex<HashSet<String>>,
+    content_hash: &str,
+    space_id: Option<&str>,
+) -> DuplicateCheck {
+    if let Some(existing) = index.find_by_hash(content_hash, space_id) {
+        return DuplicateCheck::Indexed(existing.to_string());
+    }
+    if !content_hash.is_empty() && !claimed.lock().insert(content_hash.to_string()) {
+        return DuplicateCheck::InBatch;
+    }
+    DuplicateCheck::New
+}
+
+/// Index an uploaded image: its OCR text into the document index (tagged
+/// `type=image`) and, when CLIP is loaded, its visual embedding. Every image
+/// gets an image index entry so later batches can skip it by content hash.
+/// Failures are logged and never fail the upload.
+async fn index_image(
+    upload: &ImageUpload<'_>,
+    bytes: Vec<u8>,
+    extracted_text: &str,
+    rag_state: &RagState,
+    images: &ImageSearchState,
+) {
+    let image_id = upload.id;
+    if !extracted_text.trim().is_empty() {
+        let title = Path::new(upload.file_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| format!("Image {}", &image_id[..8]));
+        let mut metadata = HashMap::new();
+        metadata.insert("title".to_string(), title.clone());
+        metadata.insert("type".to_string(), "image".to_string());
+        metadata.insert("image_id".to_string(), image_id.to_string());
+        if upload.file_path.is_empty() {
+            metadata.insert("source".to_string(), "image_upload".to_string());
+        } else {
+            metadata.insert("file_path".to_string(), upload.file_path.to_string());
+        }
+        if let Some(space_id) = upload.space_id {
+            metadata.insert("space_id".to_string(), space_id.to_string());
+        }
+        let citation = Citation {
+            title,
+            authors: vec![],
+            source: "Image upload".to_string(),
+            year: chrono::Utc::now().format("%Y").to_string(),
+            url: None,
+            doi: None,
+            page_numbers: None,
+        };
+
+        let mut rag = rag_state.rag.write().await;
+        if let Err(e) = rag.add_document(extracted_text, DocumentFormat::TXT, metadata, citation).await {
+            tracing::warn!("Failed to index OCR text of image {}: {}", image_id, e);
+        }
+    }
+
+    // Without CLIP the entry has no vector and only serves deduplication
+    let vector = match images.clip.clone() {
+        Some(clip) => match tokio::task::spawn_blocking(move || clip.embed_image(&bytes)).await {
+            Ok(Ok(vector)) => vector,
+            Ok(Err(e)) => {
+                tracing::warn!("Failed to embed image {}: {:#}", image_id, e);
+                Vec::new()
+            }
+            Err(e) => {
+                tracing::warn!("Image embedding task panicked: {}", e);
+                Vec::new()
+            }
+        },
+        None => Vec::new(),
+    };
+
+    let mut index = images.index.write();
+    index.insert(image_id.to_string(), ImageEntry {
+        vector,
+        file_path: upload.file_path.to_string(),
+        ocr_text: extracted_text.to_string(),
+        content_hash: upload.content_hash.clone(),
+        space_id: upload.space_id.map(str::to_string),
+        added_at: chrono::Utc::now(),
+    });
+    if let Err(e) = index.save() {
+        tracing::warn!("Failed to save image index: {:#}", e);
+    }
+}
+
+/// OCR and index one image of a batch, skipping bytes that are already
+/// indexed in the space or `claimed` by another image of the same batch
+async fn process_batch_image(
+    file_path: String,
+    space_id: Option<&str>,
+    claimed: &Mutex<HashSet<String>>,
+    rag_state: &RagState,
+    images: &ImageSearchState,
+) -> BatchImageResult {
+    let mut result = BatchImageResult {
+        file_path,
+        id: None,
+        status: BatchImageStatus::Cancelled,
+        word_count: 0,
+        error: None,
+    };
+    if images.batch.is_cancelled() {
+        return result;
+    }
+
+    let bytes = match std::fs::read(&result.file_path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            result.status = BatchImageStatus::Failed;
+            result.error = Some(format!("Failed to read file: {}", e));
+            return result;
+        }
+    };
+    let content_hash = file_content_hash(Path::new(&result.file_path)).unwrap_or_default();
+    let duplicate = check_duplicate(&images.index.read(), claimed, &content_hash, space_id);
+    match duplicate {
+        DuplicateCheck::New => {}
+        DuplicateCheck::Indexed(existing) => {
+            result.id = Some(existing);
+            result.status = BatchImageStatus::Duplicate;
+            return result;
+        }
+        DuplicateCheck::InBatch => {
+            result.status = BatchImageStatus::Duplicate;
+            return result;
+        }
+    }
+
+    let image_id = Uuid::new_v4().to_string();
+    let extracted_text = match run_ocr(&bytes).await {
+        Ok((text, _)) => text,
+        Err(e) => {
+            tracing::warn!("OCR failed for {}: {}", result.file_path, e);
+            result.error = Some(format!("OCR failed: {}", e));
+            String::new()
+        }
+    };
+
+    let content_hash = recorded_hash(content_hash, result.error.is_some());
+    let upload = ImageUpload { id: &image_id, file_path: &result.file_path, content_hash, space_id };
+    index_image(&upload, bytes, &extracted_text, rag_state, images).await;
+
+    result.word_count = extracted_text.split_whitespace().count();
+    result.status = if result.error.is_some() { BatchImageStatus::Failed } else { BatchImageStatus::Processed };
+    result.id = Some(image_id);
+    result
 }
 
-#[cfg(not(target_os = "windows"))]
-async fn run_ocr(_image_bytes: &[u8]) -> Result<(String, f32), String> {
-    Err("OCR is only available on Windows".to_string())
+/// Similarity of `query` to every indexed image, above `MIN_VISUAL_SCORE`
+async fn visual_matches(images: &ImageSearchState, query: &str, limit: usize) -> Vec<(String, f32)> {
+    let Some(clip) = images.clip.clone() else {
+        return Vec::new();
+    };
+    let query = query.to_string();
+    match tokio::task::spawn_blocking(move || clip.embed_text(&query)).await {
+        Ok(Ok(vector)) => images.index.read()
+            .search(&vector, limit)
+            .into_iter()
+            .filter(|(_, score)| *score >= MIN_VISUAL_SCORE)
+            .collect(),
+        Ok(Err(e)) => {
+            tracing::warn!("Failed to embed image query: {:#}", e);
+            Vec::new()
+        }
+        Err(e) => {
+            tracing::warn!("Query embedding task panicked: {}", e);
+            Vec::new()
+        }
+    }
 }
 
 // ─── Commands ───────────────────────────────────────────────────────────────
 
-/// Process an image from base64 data (paste/screenshot)
+/// Process an image from base64 data (paste/screenshot), indexing its text
+/// into `space_id` when given
 #[tauri::command]
 pub async fn process_image_from_base64(
     image_data: String,
-    _state: State<'_, RagState>,
+    space_id: Option<String>,
+    state: State<'_, RagState>,
+    images: State<'_, ImageSearchState>,
 ) -> Result<ImageProcessResult, String> {
     let image_id = Uuid::new_v4().to_string();
 
@@ -130,6 +374,13 @@ pub async fn process_image_from_base64(
     };
 
     let word_count = extracted_text.split_whitespace().count();
+    let upload = ImageUpload {
+        id: &image_id,
+        file_path: "",
+        content_hash: String::new(),
+        space_id: space_id.as_deref(),
+    };
+    index_image(&upload, bytes, &extracted_text, &state, &images).await;
 
     Ok(ImageProcessResult {
         id: image_id,
@@ -145,22 +396,25 @@ pub async fn process_image_from_base64(
     })
 }
 
-/// Process an image from file path (drag-drop)
+/// Process an image from file path (drag-drop), indexing its text into
+/// `space_id` when given
 #[tauri::command]
 pub async fn process_image_from_file(
     file_path: String,
-    _state: State<'_, RagState>,
+    space_id: Option<String>,
+    state: State<'_, RagState>,
+    images: State<'_, ImageSearchState>,
 ) -> Result<ImageProcessResult, String> {
     let image_id = Uuid::new_v4().to_string();
 
     let bytes = std::fs::read(&file_path)
         .map_err(|e| format!("Failed to read file: {}", e))?;
 
-    let (extracted_text, confidence) = match run_ocr(&bytes).await {
-        Ok((text, conf)) => (text, conf),
+    let (extracted_text, confidence, ocr_failed) = match run_ocr(&bytes).await {
+        Ok((text, conf)) => (text, conf, false),
         Err(e) => {
             tracing::warn!("OCR failed for {}: {}", file_path, e);
-            (String::new(), 0.0)
+            (String::new(), 0.0, true)
         }
     };
 
@@ -168,6 +422,13 @@ pub async fn process_image_from_file(
 
     // Generate base64 data URI for display
     let b64 = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &bytes);
+    let upload = ImageUpload {
+        id: &image_id,
+        file_path: &file_path,
+        content_hash: recorded_hash(file_content_hash(Path::new(&file_path)).unwrap_or_default(), ocr_failed),
+        space_id: space_id.as_deref(),
+    };
+    index_image(&upload, bytes, &extracted_text, &state, &images).await;
     let ext = std::path::Path::new(&file_path)
         .extension()
         .and_then(|e| e.to_str())
@@ -191,33 +452,141 @@ pub async fn process_image_from_file(
     })
 }
 
-/// Search indexed images by text query
+/// Process a list of images (e.g. a folder of scans) a few at a time,
+/// emitting `image-batch-progress` after each one. Images whose bytes are
+/// already indexed in the space are skipped; `cancel_image_batch` stops the
+/// rest. Only one batch runs at a time.
+#[tauri::command]
+pub async fn process_images_from_files(
+    paths: Vec<String>,
+    space_id: Option<String>,
+    app: tauri::AppHandle,
+    state: State<'_, RagState>,
+    images: State<'_, ImageSearchState>,
+) -> Result<BatchImageSummary, String> {
+    if images.batch_running.swap(true, Ordering::SeqCst) {
+        return Err("An image batch is already being processed".to_string());
+    }
+    images.batch.reset();
+    let total = paths.len();
+    tracing::info!("🖼️ Processing {} images", total);
+
+    let claimed = Mutex::new(HashSet::new());
+    let mut pending = futures::stream::iter(paths.into_iter().enumerate())
+        .map(|(position, path)| {
+            let (claimed, state, images) = (&claimed, &state, &images);
+            let space_id = space_id.as_deref();
+            async move { (position, process_batch_image(path, space_id, claimed, state, images).await) }
+        })
+        .buffer_unordered(IMAGE_BATCH_CONCURRENCY);
+
+    let mut results = Vec::with_capacity(total);
+    while let Some((position, result)) = pending.next().await {
+        let _ = app.emit("image-batch-progress", serde_json::json!({
+            "processed": results.len() + 1,
+            "total": total,
+            "result": &result,
+        }));
+        results.push((position, result));
+    }
+    drop(pending);
+    images.batch_running.store(false, Ordering::SeqCst);
+    results.sort_by_key(|(position, _)| *position);
+    let results: Vec<BatchImageResult> = results.into_iter().map(|(_, result)| result).collect();
+
+    let count = |status: BatchImageStatus| results.iter().filter(|r| r.status == status).count();
+    let summary = BatchImageSummary {
+        succeeded: count(BatchImageStatus::Processed),
+        duplicates: count(BatchImageStatus::Duplicate),
+        failed: count(BatchImageStatus::Failed),
+        cancelled: images.batch.is_cancelled(),
+        results,
+    };
+    tracing::info!(
+        "✅ Image batch done: {} processed, {} duplicates, {} failed{}",
+        summary.succeeded, summary.duplicates, summary.failed,
+        if summary.cancelled { " (cancelled)" } else { "" }
+    );
+    Ok(summary)
+}
+
+/// Stop a running `process_images_from_files` after the images in flight
+#[tauri::command]
+pub async fn cancel_image_batch(images: State<'_, ImageSearchState>) -> Result<(), String> {
+    images.batch.cancel();
+    Ok(())
+}
+
+/// Search indexed images by text query, fusing OCR text matches with
+/// visual (CLIP) matches so images without matching text are still found
 #[tauri::command]
 pub async fn search_images(
     query: String,
     limit: Option<usize>,
     state: State<'_, RagState>,
+    images: State<'_, ImageSearchState>,
 ) -> Result<Vec<serde_json::Value>, String> {
+    let limit = limit.unwrap_or(10);
     let rag = state.rag.read().await;
 
-    let results = rag.search(&query, limit.unwrap_or(10))
+    // Over-fetch: most text hits are not images
+    let results = rag.search(&query, limit * 3)
         .await
         .map_err(|e| format!("Search failed: {}", e))?;
+    drop(rag);
+
+    let mut text_hits: HashMap<String, SimpleSearchResult> = HashMap::new();
+    let mut text_scores: Vec<(String, f32)> = Vec::new();
+    for r in results.into_iter().filter(|r| r.metadata.values().any(|v| v.contains("image"))) {
+        let id = r.metadata.get("image_id").cloned().unwrap_or_else(|| r.doc_id.to_string());
+        // Results are best-first, so the first chunk of an image is its score
+        if !text_hits.contains_key(&id) {
+            text_scores.push((id.clone(), r.score));
+            text_hits.insert(id, r);
+        }
+    }
 
-    let image_results: Vec<_> = results
+    let visual_scores = visual_matches(&images, &query, limit).await;
+    let visual: HashMap<String, f32> = visual_scores.iter().cloned().collect();
+    let index = images.index.read();
+
+    let image_results = weighted_fusion(visual_scores, text_scores, VISUAL_WEIGHT, limit)
         .into_iter()
-        .filter(|r| r.metadata.values().any(|v| v.contains("image")))
-        .map(|r| serde_json::json!({
-            "id": r.doc_id.to_string(),
-            "text": r.text,
-            "score": r.score,
-            "source": r.source,
-        }))
+        .map(|(id, score, matched_by)| {
+            let text_hit = text_hits.get(&id);
+            let entry = index.get(&id);
+            serde_json::json!({
+                "id": id,
+                "text": text_hit.map(|r| r.text.clone()).or_else(|| entry.map(|e| e.ocr_text.clone())).unwrap_or_default(),
+                "score": score,
+                "visualScore": visual.get(&id),
+                "textScore": text_hit.map(|r| r.score),
+                "matchedBy": match matched_by {
+                    HybridSource::Vector => "visual",
+                    HybridSource::TextSearch => "text",
+                    HybridSource::Both => "both",
+                },
+                "source": text_hit.map(|r| r.source.clone()).or_else(|| entry.map(|e| e.file_path.clone())).unwrap_or_default(),
+            })
+        })
         .collect();
 
     Ok(image_results)
 }
 
+/// Generate a form definition from a natural-language request
+#[tauri::command]
+pub async fn generate_form(
+    request: String,
+    llm_state: State<'_, LLMState>,
+) -> Result<StructuredOutput, String> {
+    let manager = llm_state.manager.read().await;
+    let manager = manager.as_ref().ok_or("LLM is not initialized")?;
+    extract_form(manager, &request)
+        .await
+        .map_err(|e| format!("Failed to generate form: {:#}", e))
+}
+
 /// Export form as HTML file
 #[tauri::command]
 pub async fn export_form_html(
@@ -239,3 +608,47 @@ pub async fn export_form_json(
     export_form_as_json_schema(&title, description.as_deref(), &fields)
         .map_err(|e| format!("Failed to export form as JSON: {}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(content_hash: &str, space_id: Option<&str>) -> ImageEntry {
+        ImageEntry {
+            vector: Vec::new(),
+            file_path: String::new(),
+            ocr_text: String::new(),
+            content_hash: content_hash.to_string(),
+            space_id: space_id.map(str::to_string),
+            added_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_duplicates_are_per_space_and_per_batch() {
+        let mut index = ImageIndex::default();
+        index.insert("scan-1".into(), entry("abc", Some("legal")));
+        let claimed = Mutex::new(HashSet::new());
+
+        assert_eq!(check_duplicate(&index, &claimed, "abc", Some("legal")), DuplicateCheck::Indexed("scan-1".into()));
+        // Same bytes into another space are indexed again, once per batch
+        assert_eq!(check_duplicate(&index, &claimed, "abc", Some("finance")), DuplicateCheck::New);
+        assert_eq!(check_duplicate(&index, &claimed, "abc", Some("finance")), DuplicateCheck::InBatch);
+        // Unknown hashes are never duplicates
+        assert_eq!(check_duplicate(&index, &claimed, "", None), DuplicateCheck::New);
+        assert_eq!(check_duplicate(&index, &claimed, "", None), DuplicateCheck::New);
+    }
+
+    #[test]
+    fn test_failed_ocr_is_not_remembered_as_indexed() {
+        assert_eq!(recorded_hash("abc".into(), false), "abc");
+        let hash = recorded_hash("abc".into(), true);
+        assert!(hash.is_empty());
+
+        // The failed image's entry doesn't block the next upload of the same file
+        let mut index = ImageIndex::default();
+        index.insert("failed".into(), entry(&hash, None));
+        let claimed = Mutex::new(HashSet::new());
+        assert_eq!(check_duplicate(&index, &claimed, "abc", None), DuplicateCheck::New);
+    }
+}
@@ -11,10 +11,12 @@ mod chat_history;
 mod history_commands;
 mod graph_commands;
 mod doc_gen_commands;
+mod office_export;
 mod database_commands;
 mod diagnostic_commands;
 mod analytics_commands;
 mod storage_commands;
+mod backup_scheduler;
 mod smart_templates;
 mod template_commands;
 mod query_rewriter;
@@ -24,11 +26,18 @@ mod context_commands;
 mod whatsapp_bot;
 mod whatsapp_commands;
 mod whatsapp_http_server;
+mod bot_sse;
+mod bot_chat;
+mod bot_format;
+mod bot_artifacts;
+mod bot_access;
 mod telegram_http_server;
 mod telegram_bot_commands;
 mod discord_http_server;
 mod discord_bot_commands;
 mod google_drive_commands;
+mod cloud_provider;
+mod dropbox_commands;
 mod image_upload_commands;
 mod system_commands;
 mod mcp;
@@ -41,6 +50,8 @@ mod chat_engine;
 mod artifact_store;
 mod unified_chat_commands;
 mod conversation_commands;
+mod conversation_search;
+mod conversation_export;
 mod agent_commands;
 mod calendar_commands;
 
@@ -50,7 +61,7 @@ use rag_commands::{RagState, AppPaths};
 use context_commands::ContextState;
 use uuid::Uuid;
 use enhanced_rag_commands::IndexingState;
-use llm_commands::{LLMState, ApiKeys};
+use llm_commands::LLMState;
 use space_manager::SpaceManager;
 use search_history::SearchHistoryManager;
 use chat_history::ChatHistoryManager;
@@ -59,11 +70,13 @@ use template_commands::TemplateStore;
 use whatsapp_commands::WhatsAppBotState;
 use telegram_bot_commands::TelegramBotState;
 use discord_bot_commands::DiscordBotState;
+use bot_access::BotAccess;
 use google_drive_commands::GoogleDriveState;
+use dropbox_commands::DropboxState;
 use mcp_commands::MCPState;
 use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock as AsyncRwLock;
-use shodh_rag::llm::{LLMConfig, ModelManager};
+use shodh_rag::llm::ModelManager;
 use std::path::PathBuf;
 
 /// Resolve the models directory with multi-tier fallback for portability.
@@ -170,14 +183,20 @@ pub fn run() {
             // Initialize LLMState FIRST so RagState can reference its manager
             let shared_llm_manager = Arc::new(AsyncRwLock::new(None));
 
+            // Restore the last-used LLM config and API keys (defaults if missing/corrupt)
+            let (saved_llm_config, saved_api_keys) =
+                llm_commands::load_persisted_llm_settings(&app_data_dir);
+            tracing::info!("Restored LLM mode: {:?}", saved_llm_config.redacted().mode);
+
             app.manage(LLMState {
                 manager: shared_llm_manager.clone(),
                 model_manager: Arc::new(ModelManager::new(model_dir.clone())),
-                config: Arc::new(Mutex::new(LLMConfig::default())),
-                api_keys: Arc::new(Mutex::new(ApiKeys::default())),
+                config: Arc::new(Mutex::new(saved_llm_config)),
+                api_keys: Arc::new(Mutex::new(saved_api_keys)),
                 custom_model_path: Arc::new(Mutex::new(None)),
                 custom_tokenizer_path: Arc::new(Mutex::new(None)),
                 model_dir: Arc::new(model_dir.clone()),
+                config_dir: Arc::new(app_data_dir.clone()),
             });
 
             // Initialize RagState with explicit model path configuration
@@ -188,9 +207,18 @@ pub fn run() {
                 rag_config.embedding.dimension = 768;
             }
             rag_config.data_dir = app_data_dir.clone();
-            let default_rag = tauri::async_runtime::block_on(
+            if let Some(search) = rag_commands::load_search_config(&app_data_dir) {
+                rag_config.search = search;
+            }
+            let mut default_rag = tauri::async_runtime::block_on(
                 shodh_rag::comprehensive_system::ComprehensiveRAG::new(rag_config)
             ).expect("Failed to create default RAG instance");
+            // Encrypted spaces start locked until the user supplies the passphrase
+            for space in space_manager.get_spaces().unwrap_or_default() {
+                if space.metadata.contains_key(shodh_rag::space_crypto::SALT_METADATA_KEY) {
+                    default_rag.register_encrypted_space(&space.id);
+                }
+            }
 
             app.manage(RagState {
                 rag: Arc::new(AsyncRwLock::new(default_rag)),
@@ -214,14 +242,27 @@ pub fn run() {
             let analytics_path = app_data_dir.join("analytics.json");
             app.manage(AnalyticsState::load_or_default(&analytics_path));
             app.manage(TemplateStore::default());
-            app.manage(WhatsAppBotState::default());
+            app.manage(doc_gen_commands::DocGenState::default());
+            app.manage(conversation_search::ConversationSearchState::default());
+            app.manage(conversation_commands::AutoTitleState::default());
+            app.manage(backup_scheduler::BackupScheduleState::load(&app_data_dir));
+            app.manage(shodh_rag::system::CommandGate::default());
+            app.manage(shodh_rag::llm::GenerationRegistry::default());
+            app.manage(WhatsAppBotState {
+                access: Arc::new(BotAccess::load(app_data_dir.join("whatsapp_access.json"))),
+                ..WhatsAppBotState::default()
+            });
             app.manage(TelegramBotState {
                 process: Mutex::new(None),
+                access: Arc::new(BotAccess::load(app_data_dir.join("telegram_access.json"))),
             });
             app.manage(DiscordBotState {
                 process: Mutex::new(None),
+                access: Arc::new(BotAccess::load(app_data_dir.join("discord_access.json"))),
             });
             app.manage(Arc::new(GoogleDriveState::new()));
+            app.manage(Arc::new(DropboxState::new()));
+            app.manage(image_upload_commands::ImageSearchState::load(&model_dir, &app_data_dir));
 
             // Initialize MCP (Model Context Protocol) state
             let mcp_config_dir = app_data_dir.join("mcp");
@@ -230,7 +271,10 @@ pub fn run() {
                     .expect("Failed to create MCP config directory");
             }
             let mcp_manager = mcp::MCPManager::new();
-            let mcp_registry = mcp::registry::MCPRegistry::new(mcp_config_dir);
+            let mut mcp_registry = mcp::registry::MCPRegistry::new(mcp_config_dir);
+            if let Err(e) = tauri::async_runtime::block_on(mcp_registry.load()) {
+                tracing::warn!("Failed to load MCP server registry: {}", e);
+            }
             app.manage(MCPState {
                 manager: Arc::new(AsyncRwLock::new(mcp_manager)),
                 registry: Arc::new(AsyncRwLock::new(mcp_registry)),
@@ -257,13 +301,19 @@ pub fn run() {
             let rag_state = app.state::<RagState>();
             let conversation_manager_arc = rag_state.conversation_manager.clone();
             let memory_system_arc_state = rag_state.memory_system.clone();
+            let memory_rag = rag_state.rag.clone();
 
             tauri::async_runtime::spawn(async move {
                 let mut memory_config = shodh_rag::memory::MemoryConfig::default();
                 memory_config.storage_path = memory_store_path;
+                let embedder = memory_rag.read().await.shared_embeddings();
 
                 match shodh_rag::memory::MemorySystem::new(memory_config) {
                     Ok(memory_system) => {
+                        let memory_system = memory_system.with_embedder(embedder);
+                        if let Err(e) = memory_system.backfill_embeddings().await {
+                            tracing::warn!("Failed to embed stored memories: {}", e);
+                        }
                         let memory_system_shared = Arc::new(AsyncRwLock::new(memory_system));
                         *memory_system_arc_state.write().await = Some(memory_system_shared.clone());
                         tracing::info!("Memory system initialized successfully");
@@ -284,6 +334,8 @@ pub fn run() {
                 }
             });
 
+            backup_scheduler::spawn_backup_scheduler(app.handle().clone());
+
             // Re-index existing calendar data into RAG engine (best-effort, background)
             {
                 let app_handle = app.handle().clone();
@@ -300,6 +352,8 @@ pub fn run() {
             let bot_state_clone = WhatsAppBotState {
                 bot: whatsapp_bot_state.bot.clone(),
                 bridge_process: std::sync::Mutex::new(None),
+                rate_limiter: whatsapp_bot_state.rate_limiter.clone(),
+                access: whatsapp_bot_state.access.clone(),
             };
             let rag_state_clone = RagState {
                 rag: whatsapp_rag_state.rag.clone(),
@@ -317,8 +371,9 @@ pub fn run() {
                 llm_manager: whatsapp_rag_state.llm_manager.clone(),
             };
 
+            let whatsapp_app_handle = app.app_handle().clone();
             tauri::async_runtime::spawn(async move {
-                if let Err(e) = whatsapp_http_server::start_server(bot_state_clone, rag_state_clone).await {
+                if let Err(e) = whatsapp_http_server::start_server(bot_state_clone, rag_state_clone, Some(whatsapp_app_handle)).await {
                     tracing::error!("Failed to start WhatsApp HTTP server: {}", e);
                 }
             });
@@ -349,11 +404,13 @@ pub fn run() {
                 custom_model_path: telegram_llm_state.custom_model_path.clone(),
                 custom_tokenizer_path: telegram_llm_state.custom_tokenizer_path.clone(),
                 model_dir: telegram_llm_state.model_dir.clone(),
+                config_dir: telegram_llm_state.config_dir.clone(),
             };
 
             let telegram_app_handle = app.app_handle().clone();
+            let telegram_access = app.state::<TelegramBotState>().access.clone();
             tauri::async_runtime::spawn(async move {
-                if let Err(e) = telegram_http_server::start_server(telegram_rag_clone, telegram_llm_clone, Some(telegram_app_handle)).await {
+                if let Err(e) = telegram_http_server::start_server(telegram_rag_clone, telegram_llm_clone, Some(telegram_app_handle), telegram_access).await {
                     tracing::error!("Failed to start Telegram HTTP server: {}", e);
                 }
             });
@@ -384,11 +441,13 @@ pub fn run() {
                 custom_model_path: discord_llm_state.custom_model_path.clone(),
                 custom_tokenizer_path: discord_llm_state.custom_tokenizer_path.clone(),
                 model_dir: discord_llm_state.model_dir.clone(),
+                config_dir: discord_llm_state.config_dir.clone(),
             };
 
             let discord_app_handle = app.app_handle().clone();
+            let discord_access = app.state::<DiscordBotState>().access.clone();
             tauri::async_runtime::spawn(async move {
-                if let Err(e) = discord_http_server::start_server(discord_rag_clone, discord_llm_clone, Some(discord_app_handle)).await {
+                if let Err(e) = discord_http_server::start_server(discord_rag_clone, discord_llm_clone, Some(discord_app_handle), discord_access).await {
                     tracing::error!("Failed to start Discord HTTP server: {}", e);
                 }
             });
@@ -431,9 +490,15 @@ pub fn run() {
             rag_commands::initialize_rag,
             rag_commands::check_initialization_status,
             rag_commands::search_documents,
+            rag_commands::filter_documents,
             rag_commands::add_document,
             rag_commands::upload_file,
             rag_commands::get_statistics,
+            rag_commands::get_search_config,
+            rag_commands::set_search_config,
+            rag_commands::evaluate_retrieval,
+            rag_commands::reembed_space,
+            rag_commands::reembed_all,
             rag_commands::clear_all_data,
             rag_commands::delete_folder_source,
             rag_commands::add_test_documents,
@@ -477,6 +542,8 @@ pub fn run() {
             rag_commands::jump_to_source,
             // Smart Templates commands
             template_commands::extract_template,
+            template_commands::create_template,
+            template_commands::validate_template_inputs,
             template_commands::generate_from_template,
             template_commands::list_templates,
             template_commands::get_template,
@@ -493,14 +560,17 @@ pub fn run() {
             llm_commands::llm_generate,
             llm_commands::llm_generate_stream,
             llm_commands::llm_generate_stream_with_rag,
+            llm_commands::cancel_generation,
             llm_commands::get_llm_info,
             llm_commands::set_api_key,
             llm_commands::is_model_cached,
             llm_commands::download_model,
+            llm_commands::repair_model,
             llm_commands::get_model_cache_info,
             llm_commands::delete_cached_model,
             llm_commands::update_llm_config,
             llm_commands::browse_model_file,
+            llm_commands::inspect_model_file,
             llm_commands::browse_tokenizer_file,
             llm_commands::set_custom_model_path,
             llm_commands::set_custom_tokenizer_path,
@@ -517,6 +587,11 @@ pub fn run() {
             space_commands::remove_document,
             space_commands::set_space_system_prompt,
             space_commands::get_space_system_prompt,
+            space_commands::set_space_llm_settings,
+            space_commands::get_space_llm_settings,
+            space_commands::enable_space_encryption,
+            space_commands::unlock_space,
+            space_commands::lock_space,
             // History commands
             history_commands::add_search_history,
             history_commands::get_search_history,
@@ -530,15 +605,20 @@ pub fn run() {
             history_commands::search_with_history,
             // Graph commands
             graph_commands::get_knowledge_graph,
+            graph_commands::export_knowledge_graph,
+            graph_commands::get_entity_neighborhood,
             // Document generation commands
             doc_gen_commands::generate_document,
             doc_gen_commands::generate_from_rag,
             doc_gen_commands::generate_document_stream,
+            doc_gen_commands::generate_from_rag_stream,
+            doc_gen_commands::cancel_document_generation,
             doc_gen_commands::get_available_formats,
             doc_gen_commands::get_available_templates,
             doc_gen_commands::generate_document_preview,
             doc_gen_commands::get_source_documents,
             doc_gen_commands::get_comparable_documents,
+            doc_gen_commands::compare_documents,
             // Database management commands
             database_commands::reset_database,
             database_commands::clear_all_documents,
@@ -549,7 +629,12 @@ pub fn run() {
             database_commands::save_backup_file,
             database_commands::read_backup_file,
             database_commands::restore_space_from_backup,
+            database_commands::export_space_archive,
+            database_commands::import_space_archive,
             database_commands::list_backup_files,
+            backup_scheduler::get_backup_schedule,
+            backup_scheduler::set_backup_schedule,
+            backup_scheduler::run_backup_now,
             database_commands::update_space_metadata,
             // Diagnostic commands
             diagnostic_commands::get_index_diagnostics,
@@ -568,6 +653,8 @@ pub fn run() {
             storage_commands::get_space_documents_detailed,
             storage_commands::delete_documents_batch,
             storage_commands::clear_space_documents,
+            storage_commands::find_duplicate_documents,
+            storage_commands::dedupe_documents,
             storage_commands::optimize_storage,
             storage_commands::create_backup,
             storage_commands::restore_backup,
@@ -615,6 +702,10 @@ pub fn run() {
             discord_bot_commands::start_discord_bot,
             discord_bot_commands::stop_discord_bot,
             discord_bot_commands::check_discord_bot_status,
+            // Bot access policies (allowlist / blocklist)
+            bot_access::bot_get_access_policy,
+            bot_access::bot_set_access_mode,
+            bot_access::bot_update_access_list,
             // Google Drive integration commands
             google_drive_commands::init_google_drive_oauth,
             google_drive_commands::exchange_google_drive_code,
@@ -625,16 +716,31 @@ pub fn run() {
             google_drive_commands::get_google_drive_sync_status,
             google_drive_commands::is_google_drive_authenticated,
             google_drive_commands::disconnect_google_drive,
+            dropbox_commands::init_dropbox_oauth,
+            dropbox_commands::exchange_dropbox_code,
+            dropbox_commands::list_dropbox_files,
+            dropbox_commands::download_dropbox_file,
+            dropbox_commands::configure_dropbox_folder_sync,
+            dropbox_commands::sync_dropbox_folder,
+            dropbox_commands::get_dropbox_sync_status,
+            dropbox_commands::is_dropbox_authenticated,
+            dropbox_commands::disconnect_dropbox,
             // Image upload commands
             image_upload_commands::process_image_from_base64,
             image_upload_commands::process_image_from_file,
+            image_upload_commands::process_images_from_files,
+            image_upload_commands::cancel_image_batch,
             image_upload_commands::search_images,
             // Form export commands
             image_upload_commands::export_form_html,
             image_upload_commands::export_form_json,
+            image_upload_commands::generate_form,
             // System actions (OS integration)
             system_commands::execute_file_action,
+            system_commands::preview_command_action,
             system_commands::execute_command_action,
+            system_commands::set_command_risk_threshold,
+            system_commands::get_command_audit_log,
             system_commands::open_file_manager,
             system_commands::get_system_information,
             system_commands::get_running_processes,
@@ -642,8 +748,10 @@ pub fn run() {
             mcp_commands::mcp_connect_server,
             mcp_commands::mcp_disconnect_server,
             mcp_commands::mcp_list_tools,
+            mcp_commands::mcp_refresh_tools,
             mcp_commands::mcp_search_tools,
             mcp_commands::mcp_call_tool,
+            mcp_commands::mcp_cancel_all,
             mcp_commands::mcp_list_servers,
             mcp_commands::mcp_upsert_server,
             mcp_commands::mcp_remove_server,
@@ -653,6 +761,7 @@ pub fn run() {
             document_upload_commands::save_temp_file,
             // Unified Chat System commands
             unified_chat_commands::unified_chat,
+            unified_chat_commands::estimate_context_usage,
             unified_chat_commands::apply_artifact_to_file,
             unified_chat_commands::update_artifact,
             unified_chat_commands::get_artifact_history,
@@ -663,9 +772,16 @@ pub fn run() {
             conversation_commands::delete_conversation,
             conversation_commands::rename_conversation,
             conversation_commands::pin_conversation,
+            conversation_commands::fork_conversation,
+            conversation_commands::search_conversations,
+            conversation_commands::retitle_conversation,
+            conversation_commands::get_conversation_settings,
+            conversation_commands::set_conversation_settings,
+            conversation_commands::export_conversation,
             // Agent commands
             agent_commands::get_agent_dashboard,
             agent_commands::get_active_executions,
+            agent_commands::get_execution_trace,
             agent_commands::toggle_agent,
             agent_commands::create_agent,
             agent_commands::update_agent,
@@ -692,6 +808,19 @@ pub fn run() {
             calendar_commands::update_event,
             calendar_commands::delete_event,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Make sure the memory log hits disk before the process exits
+                let rag_state = app_handle.state::<RagState>();
+                tauri::async_runtime::block_on(async {
+                    if let Some(memory) = rag_state.memory_system.read().await.as_ref() {
+                        if let Err(e) = memory.read().await.flush() {
+                            tracing::warn!("Failed to flush memory log on exit: {}", e);
+                        }
+                    }
+                });
+            }
+        });
 }
@@ -2,11 +2,14 @@
 
 use shodh_rag::llm::{
     LLMManager, LLMConfig, LLMMode, LocalModel, ApiProvider,
-    DeviceType, QuantizationType, ModelManager,
+    DeviceType, QuantizationType, ModelManager, GenerationRegistry,
+    FileProgress, RepairReport,
+    tokenizer_loader::{TokenizerLoader, TokenizerSource},
+    GgufMetadata, read_gguf_metadata, LLAMACPP_CONTEXT_TOKENS,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::{State, Emitter};
 use tokio::sync::RwLock as AsyncRwLock;
 
@@ -19,6 +22,77 @@ pub struct LLMState {
     pub custom_model_path: Arc<Mutex<Option<PathBuf>>>,
     pub custom_tokenizer_path: Arc<Mutex<Option<PathBuf>>>,
     pub model_dir: Arc<PathBuf>,
+    /// Directory holding the persisted LLM config and API keys (app data dir)
+    pub config_dir: Arc<PathBuf>,
+}
+
+/// Non-secret LLM settings (mode, last-used model, sampling params)
+const LLM_CONFIG_FILE: &str = "llm_config.json";
+/// API keys, kept out of the config file so settings can be shared without secrets
+const API_KEYS_FILE: &str = "llm_api_keys.json";
+/// Model used when the HuggingFace provider is selected without an explicit model id
+const DEFAULT_HF_MODEL: &str = "mistralai/Mistral-7B-Instruct-v0.3";
+/// Kimi (Moonshot) speaks the OpenAI protocol at its own endpoint. It is stored
+/// as a custom endpoint so the base URL survives a restart with the config.
+const KIMI_ENDPOINT: &str = "https://api.moonshot.cn/v1/chat/completions";
+
+impl LLMState {
+    /// Persist the current config (API keys redacted). Failures are logged, not fatal.
+    pub fn persist_config(&self) {
+        let config = self.config.lock().unwrap().clone();
+        if let Err(e) = config.save_to(&self.config_dir.join(LLM_CONFIG_FILE)) {
+            tracing::warn!("Failed to persist LLM config: {}", e);
+        }
+    }
+
+    /// Persist API keys to their own file.
+    pub fn persist_api_keys(&self) {
+        let keys = self.api_keys.lock().unwrap().clone();
+        let path = self.config_dir.join(API_KEYS_FILE);
+        let result = serde_json::to_string_pretty(&keys)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                let tmp = path.with_extension("json.tmp");
+                std::fs::write(&tmp, json).map_err(|e| e.to_string())?;
+                std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            tracing::warn!("Failed to persist API keys: {}", e);
+        }
+    }
+}
+
+/// Load the saved LLM config and API keys from `config_dir`, falling back to
+/// defaults when files are missing or corrupt. The external-provider API key
+/// is re-attached from the separate keys file; Kimi keeps its endpoint in the
+/// saved config and gets its own key back.
+pub fn load_persisted_llm_settings(config_dir: &Path) -> (LLMConfig, ApiKeys) {
+    let api_keys: ApiKeys = std::fs::read_to_string(config_dir.join(API_KEYS_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    let config_path = config_dir.join(LLM_CONFIG_FILE);
+    let mut config = if config_path.exists() {
+        LLMConfig::load_from(&config_path).unwrap_or_else(|e| {
+            tracing::warn!("{}, using defaults", e);
+            LLMConfig::default()
+        })
+    } else {
+        LLMConfig::default()
+    };
+
+    if let LLMMode::External { provider, api_key, .. } = &mut config.mode {
+        if api_key.is_empty() {
+            if matches!(provider, ApiProvider::Ollama) {
+                *api_key = "ollama".to_string();
+            } else if let Some(key) = api_keys.key_for(provider) {
+                *api_key = key;
+            }
+        }
+    }
+
+    (config, api_keys)
 }
 
 /// API keys storage (should be encrypted in production)
@@ -32,6 +106,28 @@ pub struct ApiKeys {
     pub perplexity: Option<String>,
     pub google: Option<String>,
     pub baseten: Option<String>,
+    pub huggingface: Option<String>,
+    pub replicate: Option<String>,
+}
+
+impl ApiKeys {
+    /// Stored key for a provider, ignoring blank entries
+    pub fn key_for(&self, provider: &ApiProvider) -> Option<String> {
+        let key = match provider {
+            ApiProvider::OpenAI => self.openai.clone(),
+            ApiProvider::Anthropic => self.anthropic.clone(),
+            ApiProvider::OpenRouter => self.openrouter.clone(),
+            ApiProvider::Grok => self.grok.clone(),
+            ApiProvider::Perplexity => self.perplexity.clone(),
+            ApiProvider::Google => self.google.clone(),
+            ApiProvider::Baseten => self.baseten.clone(),
+            ApiProvider::Replicate => self.replicate.clone(),
+            ApiProvider::HuggingFace { .. } => self.huggingface.clone(),
+            ApiProvider::Custom { endpoint } if endpoint == KIMI_ENDPOINT => self.kimi.clone(),
+            _ => None,
+        };
+        key.filter(|k| !k.trim().is_empty())
+    }
 }
 
 /// Browse and select model file (supports both ONNX and GGUF)
@@ -138,12 +234,26 @@ pub fn set_custom_model_path(
             ))
         },
         Some("gguf") => {
+            let metadata = read_gguf_metadata(&path)
+                .map_err(|e| format!("Invalid GGUF model: {}", e))?;
+
             // Store the custom path
             *state.custom_model_path.lock().unwrap() = Some(path.clone());
 
+            // Size the context to what the model was trained for, up to
+            // what llama.cpp actually runs it with
+            if let Some(context_length) = metadata.context_length {
+                state.config.lock().unwrap().context_window = context_length.min(LLAMACPP_CONTEXT_TOKENS);
+                state.persist_config();
+            }
+
             Ok(format!(
-                "✅ GGUF model path set successfully\n📁 Path: {}\n🚀 Backend: llama.cpp (tokenizer built-in)",
-                model_path
+                "✅ GGUF model path set successfully\n📁 Path: {}\n🚀 Backend: llama.cpp (tokenizer built-in)\n🧠 {} · {} · {} context · tool calling {}",
+                model_path,
+                metadata.architecture.as_deref().unwrap_or("unknown architecture"),
+                metadata.quantization.as_deref().unwrap_or("unknown quantization"),
+                metadata.context_length.map(|c| c.to_string()).unwrap_or_else(|| "unknown".to_string()),
+                if metadata.supports_tool_calling { "supported" } else { "not supported" }
             ))
         },
         _ => {
@@ -152,6 +262,21 @@ pub fn set_custom_model_path(
     }
 }
 
+/// Read GGUF header metadata for a model file without loading it, with
+/// warnings about mismatches against the current tokenizer and settings
+#[tauri::command]
+pub fn inspect_model_file(
+    state: State<'_, LLMState>,
+    path: String,
+) -> Result<ModelInspection, String> {
+    let metadata = read_gguf_metadata(Path::new(&path)).map_err(|e| e.to_string())?;
+    let tokenizer_path = state.custom_tokenizer_path.lock().unwrap().clone();
+    let context_window = state.config.lock().unwrap().context_window;
+    let warnings = metadata.compatibility_warnings(tokenizer_path.as_deref(), context_window);
+
+    Ok(ModelInspection { metadata, warnings })
+}
+
 /// Set custom tokenizer path from user selection
 #[tauri::command]
 pub fn set_custom_tokenizer_path(
@@ -177,9 +302,24 @@ pub async fn initialize_llm_with_custom_path(
     state: State<'_, LLMState>,
 ) -> Result<String, String> {
     let custom_model_path = state.custom_model_path.lock().unwrap().clone();
-    let custom_tokenizer_path = state.custom_tokenizer_path.lock().unwrap().clone();
+    let mut custom_tokenizer_path = state.custom_tokenizer_path.lock().unwrap().clone();
     
     if let Some(model_path) = custom_model_path {
+        // No tokenizer picked: use the GGUF's embedded one, a local file, or fetch it from HF
+        if custom_tokenizer_path.is_none() {
+            let loader = TokenizerLoader::new(&state.model_dir.join("tokenizers"));
+            match loader.resolve_for_model(&model_path, None).await.map_err(|e| e.to_string())? {
+                TokenizerSource::File(path) => {
+                    tracing::info!("Using tokenizer {}", path.display());
+                    *state.custom_tokenizer_path.lock().unwrap() = Some(path.clone());
+                    custom_tokenizer_path = Some(path);
+                }
+                TokenizerSource::EmbeddedGguf => {
+                    tracing::info!("Using tokenizer embedded in {}", model_path.display());
+                }
+            }
+        }
+
         // Create config with custom model
         let mut config = state.config.lock().unwrap().clone();
         config.mode = LLMMode::Local {
@@ -196,6 +336,7 @@ pub async fn initialize_llm_with_custom_path(
         
         // Update stored config
         *state.config.lock().unwrap() = config.clone();
+        state.persist_config();
         
         // Pass both model and tokenizer paths
         // Create a custom manager that knows about both paths
@@ -236,6 +377,7 @@ pub async fn initialize_llm(
     
     // Update stored config
     *state.config.lock().unwrap() = new_config.clone();
+    state.persist_config();
     
     // Create and initialize manager
     let model_dir = state.model_dir.as_ref().clone();
@@ -309,31 +451,20 @@ pub async fn switch_llm_mode(
             Some("openai") => ApiProvider::OpenAI,
             Some("anthropic") => ApiProvider::Anthropic,
             Some("openrouter") => ApiProvider::OpenRouter,
-            Some("kimi") => ApiProvider::OpenAI, // Kimi uses OpenAI-compatible API
+            Some("kimi") => ApiProvider::Custom { endpoint: KIMI_ENDPOINT.to_string() },
             Some("grok") => ApiProvider::Grok,
             Some("perplexity") => ApiProvider::Perplexity,
             Some("google") => ApiProvider::Google,
             Some("baseten") => ApiProvider::Baseten,
+            Some("replicate") => ApiProvider::Replicate,
             Some("ollama") => ApiProvider::Ollama,
+            Some("huggingface") => ApiProvider::HuggingFace {
+                model_id: model.clone().unwrap_or_else(|| DEFAULT_HF_MODEL.to_string()),
+            },
             _ => ApiProvider::OpenAI,
         };
 
-        // Get API key (handle Kimi specially since it uses OpenAI-compatible API but separate key)
-        let api_keys = state.api_keys.lock().unwrap();
-        let api_key_opt = if provider.as_deref() == Some("kimi") {
-            api_keys.kimi.clone()
-        } else {
-            match &api_provider {
-                ApiProvider::OpenAI => api_keys.openai.clone(),
-                ApiProvider::Anthropic => api_keys.anthropic.clone(),
-                ApiProvider::OpenRouter => api_keys.openrouter.clone(),
-                ApiProvider::Grok => api_keys.grok.clone(),
-                ApiProvider::Perplexity => api_keys.perplexity.clone(),
-                ApiProvider::Google => api_keys.google.clone(),
-                ApiProvider::Baseten => api_keys.baseten.clone(),
-                _ => None,
-            }
-        };
+        let api_key_opt = state.api_keys.lock().unwrap().key_for(&api_provider);
         
         let api_key = if matches!(api_provider, ApiProvider::Ollama) {
             // Ollama runs locally, no API key required
@@ -360,7 +491,11 @@ pub async fn switch_llm_mode(
             ApiProvider::Grok => "grok-2-1212",
             ApiProvider::Perplexity => "llama-3.1-sonar-small-128k-online",
             ApiProvider::Google => "gemini-2.0-flash-exp",
+            ApiProvider::Replicate => "meta/meta-llama-3-8b-instruct",
+            ApiProvider::Baseten => "deepseek-ai/DeepSeek-V3-0324",
             ApiProvider::Ollama => "phi3:mini",
+            ApiProvider::HuggingFace { .. } => DEFAULT_HF_MODEL,
+            ApiProvider::Custom { endpoint } if endpoint == KIMI_ENDPOINT => "moonshot-v1-8k",
             _ => "gpt-4o-mini",
         };
         
@@ -377,6 +512,7 @@ pub async fn switch_llm_mode(
     let mut config = state.config.lock().unwrap().clone();
     config.mode = llm_mode.clone();
     *state.config.lock().unwrap() = config.clone();
+    state.persist_config();
     
     // Switch mode or create new manager
     let model_dir = state.model_dir.as_ref().clone();
@@ -465,6 +601,8 @@ pub async fn llm_generate(
                 query_text: Some(prompt.clone()),
                 query_embedding: None,
                 retrieval_mode: RetrievalMode::Similarity,
+                causal_seed: None,
+                causal_depth: 0,
                 max_results: 5,
                 importance_threshold: Some(0.5),
                 time_range: None,
@@ -533,6 +671,7 @@ pub async fn llm_generate(
 #[tauri::command]
 pub async fn llm_generate_stream(
     state: State<'_, LLMState>,
+    generations: State<'_, GenerationRegistry>,
     prompt: String,
     app_handle: tauri::AppHandle,
 ) -> Result<String, String> {
@@ -540,37 +679,67 @@ pub async fn llm_generate_stream(
     let manager = manager_lock.as_ref().ok_or("LLM not initialized")?;
     
     let stream_id = uuid::Uuid::new_v4().to_string();
+    let cancel = generations.register(&stream_id);
     
     // Get stream
-    let mut stream = manager.generate_stream(&prompt).await.map_err(|e| e.to_string())?;
+    let mut stream = match LLMManager::with_cancellation(cancel, manager.generate_stream(&prompt)).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            generations.finish(&stream_id);
+            return Err(e.to_string());
+        }
+    };
     
     // Spawn task to emit tokens
     let stream_id_clone = stream_id.clone();
+    let generations = generations.inner().clone();
     tokio::spawn(async move {
         while let Some(token) = stream.next().await {
             let _ = app_handle.emit("llm-token", &StreamToken {
                 stream_id: stream_id_clone.clone(),
                 token,
                 is_complete: false,
+                error: None,
             });
         }
         
+        generations.finish(&stream_id_clone);
+
         // Emit completion
         let _ = app_handle.emit("llm-token", &StreamToken {
             stream_id: stream_id_clone,
             token: String::new(),
             is_complete: true,
+            error: stream.error(),
         });
     });
     
     Ok(stream_id)
 }
 
+/// Stop a running generation: a stream id from `llm_generate_stream*`, or
+/// the `requestId` passed in a chat's context. MCP tool calls in flight are
+/// abandoned too. Returns false if it already finished.
+#[tauri::command]
+pub async fn cancel_generation(
+    generations: State<'_, GenerationRegistry>,
+    mcp: State<'_, crate::mcp_commands::MCPState>,
+    request_id: String,
+) -> Result<bool, String> {
+    let cancelled = generations.cancel(&request_id);
+    if cancelled {
+        tracing::info!("⏹️ Cancelled generation {}", request_id);
+        mcp.manager.read().await.cancel_all();
+    }
+    Ok(cancelled)
+}
+
 /// Stream generation with RAG context
 #[tauri::command]
 pub async fn llm_generate_stream_with_rag(
     state: State<'_, LLMState>,
     rag_state: State<'_, crate::rag_commands::RagState>,
+    generations: State<'_, GenerationRegistry>,
     query: String,
     context: Vec<String>,
     app_handle: tauri::AppHandle,
@@ -626,6 +795,8 @@ pub async fn llm_generate_stream_with_rag(
                     query_text: Some(query.clone()),
                     query_embedding: None,
                     retrieval_mode: shodh_rag::memory::RetrievalMode::Hybrid,
+                    causal_seed: None,
+                    causal_depth: 0,
                     max_results: 3,
                     importance_threshold: Some(0.6),
                     time_range: Some((
@@ -723,9 +894,17 @@ pub async fn llm_generate_stream_with_rag(
     tracing::info!("  ▶️  Starting LLM streaming...");
 
     let stream_id = uuid::Uuid::new_v4().to_string();
-    let mut stream = manager.generate_stream(&prompt).await.map_err(|e| e.to_string())?;
+    let cancel = generations.register(&stream_id);
+    let mut stream = match LLMManager::with_cancellation(cancel, manager.generate_stream(&prompt)).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            generations.finish(&stream_id);
+            return Err(e.to_string());
+        }
+    };
 
     let stream_id_clone = stream_id.clone();
+    let generations = generations.inner().clone();
     let start_time = std::time::Instant::now();
     let input_tokens_for_spawn = input_tokens; // Move into closure
     let manager_info = manager.info();
@@ -746,9 +925,12 @@ pub async fn llm_generate_stream_with_rag(
                 stream_id: stream_id_clone.clone(),
                 token,
                 is_complete: false,
+                error: None,
             });
         }
 
+        generations.finish(&stream_id_clone);
+
         // Calculate final metrics
         let duration_ms = start_time.elapsed().as_millis() as u64;
         let duration_s = duration_ms as f64 / 1000.0;
@@ -773,6 +955,7 @@ pub async fn llm_generate_stream_with_rag(
             stream_id: stream_id_clone.clone(),
             token: String::new(),
             is_complete: true,
+            error: stream.error(),
         });
 
         // Emit metadata event for frontend display
@@ -857,10 +1040,14 @@ pub fn set_api_key(
         "perplexity" => api_keys.perplexity = Some(api_key),
         "google" => api_keys.google = Some(api_key),
         "baseten" => api_keys.baseten = Some(api_key),
+        "replicate" => api_keys.replicate = Some(api_key),
+        "huggingface" => api_keys.huggingface = Some(api_key),
         _ => return Err("Unknown provider".to_string()),
     }
-    
-    // TODO: Save to secure storage
+    drop(api_keys);
+
+    // TODO: Save to secure storage (OS keychain) instead of a plain file
+    state.persist_api_keys();
     
     Ok(())
 }
@@ -877,7 +1064,7 @@ pub async fn is_model_cached(
         return Ok(has_custom);
     }
     
-    let _model_enum = match model.as_str() {
+    let model_enum = match model.as_str() {
         "phi3" => LocalModel::Phi3Mini,
         "phi4" => LocalModel::Phi4,
         "qwen" => LocalModel::Qwen2_5B,
@@ -885,8 +1072,11 @@ pub async fn is_model_cached(
         _ => return Err("Unknown model".to_string()),
     };
     
-    // Always return true for now to prevent download loops
-    Ok(true)
+    // Phi-4 ships with the app; the others count once every file is verified
+    if matches!(model_enum, LocalModel::Phi4) {
+        return Ok(true);
+    }
+    Ok(state.model_manager.is_model_downloaded(&model_enum))
 }
 
 /// Download model
@@ -906,34 +1096,16 @@ pub async fn download_model(
 
     let model_manager = state.model_manager.clone();
     
-    // Download in background and emit progress
+    // Download in background and emit progress until it finishes
     tokio::spawn(async move {
-        // Start download
-        let download_result = model_manager.download_model(&model_enum).await;
-        
-        // Monitor progress
-        loop {
-            let progress = model_manager.get_progress().await;
-            
-            let has_error = progress.error.is_some();
-            let is_complete = progress.is_complete;
-            
-            let _ = app_handle.emit("model-download-progress", &ModelDownloadProgress {
-                model: model.clone(),
-                percentage: progress.percentage(),
-                downloaded_mb: (progress.downloaded / 1024 / 1024) as u32,
-                total_mb: (progress.total_size / 1024 / 1024) as u32,
-                is_complete: progress.is_complete,
-                error: progress.error,
-            });
-            
-            if is_complete || has_error {
-                break;
-            }
-            
-            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-        }
+        let download = tokio::spawn({
+            let model_manager = model_manager.clone();
+            async move { model_manager.download_model(&model_enum).await }
+        });
+        emit_download_progress(&app_handle, &model, &model_manager, &download).await;
         
+        let download_result = download.await
+            .unwrap_or_else(|e| Err(anyhow::anyhow!("Download task panicked: {}", e)));
         if let Err(e) = download_result {
             let _ = app_handle.emit("model-download-error", &format!("Download failed: {}", e));
         }
@@ -942,6 +1114,69 @@ pub async fn download_model(
     Ok(())
 }
 
+/// Re-verify a downloaded model and fetch only its missing or corrupt files
+#[tauri::command]
+pub async fn repair_model(
+    state: State<'_, LLMState>,
+    model: String,
+    app_handle: tauri::AppHandle,
+) -> Result<RepairReport, String> {
+    let model_enum = match model.as_str() {
+        "phi3" => LocalModel::Phi3Mini,
+        "qwen" => LocalModel::Qwen2_5B,
+        "sarvam" | "sarvam1" => LocalModel::Sarvam1,
+        _ => return Err("Unknown model".to_string()),
+    };
+
+    let model_manager = state.model_manager.clone();
+    let repair = tokio::spawn({
+        let model_manager = model_manager.clone();
+        async move { model_manager.repair_model(&model_enum).await }
+    });
+    emit_download_progress(&app_handle, &model, &model_manager, &repair).await;
+
+    let report = repair.await
+        .map_err(|e| format!("Repair task panicked: {}", e))?
+        .map_err(|e| format!("Failed to repair model: {}", e))?;
+    tracing::info!("🔧 Repaired {}: {} of {} files re-downloaded", model, report.redownloaded.len(), report.checked_files);
+    Ok(report)
+}
+
+/// Emit `model-download-progress` every 500ms until `task` finishes
+async fn emit_download_progress<T>(
+    app_handle: &tauri::AppHandle,
+    model: &str,
+    model_manager: &ModelManager,
+    task: &tokio::task::JoinHandle<T>,
+) {
+    loop {
+        let finished = task.is_finished();
+        let progress = model_manager.get_progress().await;
+        
+        let _ = app_handle.emit("model-download-progress", &ModelDownloadProgress {
+            model: model.to_string(),
+            percentage: progress.percentage(),
+            downloaded_mb: (progress.downloaded / 1024 / 1024) as u32,
+            total_mb: (progress.total_size / 1024 / 1024) as u32,
+            downloaded_bytes: progress.downloaded,
+            total_bytes: progress.total_size,
+            speed_mb_per_sec: progress.speed_mb_per_sec(),
+            eta_seconds: progress.eta_seconds(),
+            resumed: progress.resumed_from > 0,
+            is_verifying: progress.is_verifying,
+            is_complete: progress.is_complete,
+            error: progress.error,
+            files: progress.files,
+        });
+        
+        if finished {
+            break;
+        }
+        
+        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+    }
+}
+
 /// Get model cache info
 #[tauri::command]
 pub async fn get_model_cache_info(
@@ -953,9 +1188,12 @@ pub async fn get_model_cache_info(
     let cache_size = state.model_manager.get_cache_size().await
         .map_err(|e| e.to_string())?;
     
+    let partial_downloads = state.model_manager.partial_downloads().await;
+    
     Ok(CacheInfo {
         cached_models,
         total_size_mb: (cache_size / 1024 / 1024) as u32,
+        partial_downloads,
     })
 }
 
@@ -998,6 +1236,9 @@ pub fn update_llm_config(
     if let Some(k) = top_k {
         config.top_k = k;
     }
+    drop(config);
+
+    state.persist_config();
     
     Ok(())
 }
@@ -1025,6 +1266,8 @@ struct StreamToken {
     stream_id: String,
     token: String,
     is_complete: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -1038,6 +1281,13 @@ pub struct LLMInfo {
     mode: String,
 }
 
+#[derive(Serialize)]
+pub struct ModelInspection {
+    #[serde(flatten)]
+    metadata: GgufMetadata,
+    warnings: Vec<String>,
+}
+
 #[derive(Serialize)]
 pub struct MemoryInfo {
     ram_mb: usize,
@@ -1051,12 +1301,56 @@ pub struct ModelDownloadProgress {
     percentage: f32,
     downloaded_mb: u32,
     total_mb: u32,
+    downloaded_bytes: u64,
+    total_bytes: u64,
+    speed_mb_per_sec: f32,
+    eta_seconds: Option<u64>,
+    /// Continued from a partial download
+    resumed: bool,
+    /// Download finished, checksum being verified
+    is_verifying: bool,
     is_complete: bool,
     error: Option<String>,
+    /// Per-file progress; the totals above aggregate across these
+    files: Vec<FileProgress>,
 }
 
 #[derive(Serialize)]
 pub struct CacheInfo {
     cached_models: Vec<String>,
     total_size_mb: u32,
-}
\ No newline at end of file
+    /// Interrupted downloads that `download_model` will resume
+    partial_downloads: Vec<shodh_rag::llm::PartialDownload>,
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kimi_settings_survive_restart() {
+        let dir = std::env::temp_dir().join(format!("shodh-llm-settings-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let keys = ApiKeys { kimi: Some("sk-kimi".to_string()), ..Default::default() };
+        std::fs::write(dir.join(API_KEYS_FILE), serde_json::to_string(&keys).unwrap()).unwrap();
+        let config = LLMConfig {
+            mode: LLMMode::External {
+                provider: ApiProvider::Custom { endpoint: KIMI_ENDPOINT.to_string() },
+                api_key: "sk-kimi".to_string(),
+                model: "moonshot-v1-8k".to_string(),
+            },
+            ..Default::default()
+        };
+        config.save_to(&dir.join(LLM_CONFIG_FILE)).unwrap();
+
+        let (config, _) = load_persisted_llm_settings(&dir);
+        match config.mode {
+            LLMMode::External { provider, api_key, .. } => {
+                assert!(matches!(provider, ApiProvider::Custom { endpoint } if endpoint == KIMI_ENDPOINT));
+                assert_eq!(api_key, "sk-kimi");
+            }
+            other => panic!("unexpected mode {:?}", other),
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
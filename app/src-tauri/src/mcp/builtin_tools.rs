@@ -285,6 +285,8 @@ pub fn get_default_mcp_servers() -> Vec<MCPServerConfig> {
             ],
             env: HashMap::new(),
             transport: TransportType::Stdio,
+            headers: HashMap::new(),
+            call_timeout_secs: 30,
         },
 
         // GitHub MCP Server
@@ -302,6 +304,8 @@ pub fn get_default_mcp_servers() -> Vec<MCPServerConfig> {
                 env
             },
             transport: TransportType::Stdio,
+            headers: HashMap::new(),
+            call_timeout_secs: 30,
         },
 
         // Brave Search MCP Server
@@ -318,6 +322,8 @@ pub fn get_default_mcp_servers() -> Vec<MCPServerConfig> {
                 env
             },
             transport: TransportType::Stdio,
+            headers: HashMap::new(),
+            call_timeout_secs: 30,
         },
 
         // Postgres MCP Server
@@ -334,6 +340,8 @@ pub fn get_default_mcp_servers() -> Vec<MCPServerConfig> {
                 env
             },
             transport: TransportType::Stdio,
+            headers: HashMap::new(),
+            call_timeout_secs: 30,
         },
 
         // Google Drive MCP Server
@@ -346,6 +354,8 @@ pub fn get_default_mcp_servers() -> Vec<MCPServerConfig> {
             ],
             env: HashMap::new(),
             transport: TransportType::Stdio,
+            headers: HashMap::new(),
+            call_timeout_secs: 30,
         },
 
         // Slack MCP Server
@@ -362,6 +372,8 @@ pub fn get_default_mcp_servers() -> Vec<MCPServerConfig> {
                 env
             },
             transport: TransportType::Stdio,
+            headers: HashMap::new(),
+            call_timeout_secs: 30,
         },
     ]
 }
@@ -3,7 +3,7 @@
 use super::*;
 
 // Re-export the main client from transport
-pub use super::transport::StdioMCPClient;
+pub use super::transport::{HttpMCPClient, StdioMCPClient, WebSocketMCPClient};
 
 // This module can be extended with additional client implementations
 // (HTTP client, WebSocket client, etc.)
@@ -83,6 +83,17 @@ pub struct ToolCallResult {
     pub artifacts: Vec<Artifact>,
 }
 
+impl ToolCallResult {
+    pub fn failed(error: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            result: None,
+            error: Some(error.into()),
+            artifacts: Vec::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Artifact {
     pub name: String,
@@ -92,6 +103,9 @@ pub struct Artifact {
 }
 
 /// MCP Server configuration
+///
+/// For `Stdio`, `command` + `args` spawn the server process. For `Http` and
+/// `WebSocket`, `command` is the server endpoint URL.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MCPServerConfig {
     pub name: String,
@@ -99,6 +113,23 @@ pub struct MCPServerConfig {
     pub args: Vec<String>,
     pub env: HashMap<String, String>,
     pub transport: TransportType,
+    /// Extra request headers for network transports. Values may reference
+    /// `${VAR}`, resolved from `env` first and then the process environment.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    /// Seconds to wait for a single tool call before giving up
+    #[serde(default = "default_call_timeout_secs")]
+    pub call_timeout_secs: u64,
+}
+
+fn default_call_timeout_secs() -> u64 {
+    30
+}
+
+impl MCPServerConfig {
+    pub fn call_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.call_timeout_secs.max(1))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -113,7 +144,7 @@ pub enum TransportType {
 #[async_trait]
 pub trait MCPClient: Send + Sync {
     /// Discover available tools from the server
-    async fn discover_tools(&mut self) -> Result<Vec<ToolDefinition>>;
+    async fn discover_tools(&self) -> Result<Vec<ToolDefinition>>;
 
     /// Call a specific tool with parameters
     async fn call_tool(&self, name: &str, params: Value) -> Result<ToolCallResult>;
@@ -131,10 +162,40 @@ pub trait MCPClient: Send + Sync {
     fn server_info(&self) -> &MCPServerConfig;
 }
 
+/// Tool changes found by `MCPManager::refresh_tools`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolDiff {
+    pub server: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Full tool set after the refresh
+    pub tools: Vec<ToolDefinition>,
+}
+
+impl ToolDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Tool names present only in `new` (added) and only in `old` (removed)
+pub fn diff_tool_names(old: &[ToolDefinition], new: &[ToolDefinition]) -> (Vec<String>, Vec<String>) {
+    let old_names: std::collections::HashSet<&str> = old.iter().map(|t| t.name.as_str()).collect();
+    let new_names: std::collections::HashSet<&str> = new.iter().map(|t| t.name.as_str()).collect();
+
+    let mut added: Vec<String> = new_names.difference(&old_names).map(|s| s.to_string()).collect();
+    let mut removed: Vec<String> = old_names.difference(&new_names).map(|s| s.to_string()).collect();
+    added.sort();
+    removed.sort();
+    (added, removed)
+}
+
 /// MCP Manager - manages multiple MCP clients
 pub struct MCPManager {
     clients: Arc<RwLock<HashMap<String, Arc<dyn MCPClient>>>>,
     tool_registry: Arc<RwLock<HashMap<String, (String, ToolDefinition)>>>, // tool_name -> (client_name, definition)
+    /// Wakes every in-flight `call_tool` so it returns as cancelled
+    cancel: Arc<tokio::sync::Notify>,
 }
 
 impl MCPManager {
@@ -142,11 +203,20 @@ impl MCPManager {
         Self {
             clients: Arc::new(RwLock::new(HashMap::new())),
             tool_registry: Arc::new(RwLock::new(HashMap::new())),
+            cancel: Arc::new(tokio::sync::Notify::new()),
         }
     }
 
     /// Connect to an MCP server
-    pub async fn connect_server(&self, config: MCPServerConfig) -> Result<()> {
+    ///
+    /// With `cached_tools`, those definitions are registered instead of
+    /// asking the server; use `refresh_tools` to pick up server-side changes.
+    /// Returns the registered tools.
+    pub async fn connect_server(
+        &self,
+        config: MCPServerConfig,
+        cached_tools: Option<Vec<ToolDefinition>>,
+    ) -> Result<Vec<ToolDefinition>> {
         tracing::info!("🔌 Connecting to MCP server: {}", config.name);
 
         let client: Arc<dyn MCPClient> = match config.transport {
@@ -154,26 +224,28 @@ impl MCPManager {
                 Arc::new(transport::StdioMCPClient::new(config.clone()).await?)
             },
             TransportType::Http => {
-                anyhow::bail!("HTTP transport not yet implemented")
+                Arc::new(transport::HttpMCPClient::new(config.clone()).await?)
             },
             TransportType::WebSocket => {
-                anyhow::bail!("WebSocket transport not yet implemented")
+                Arc::new(transport::WebSocketMCPClient::new(config.clone()).await?)
             },
         };
 
-        // Discover tools
-        let client_mut = Arc::clone(&client);
-        // SAFETY: We just created this client, no one else has access yet
-        let tools = unsafe {
-            let ptr = Arc::as_ptr(&client_mut) as *mut dyn MCPClient;
-            (*ptr).discover_tools().await?
+        let tools = match cached_tools {
+            Some(tools) => {
+                tracing::info!("  ✓ Using {} cached tools for {}", tools.len(), config.name);
+                tools
+            }
+            None => {
+                let tools = client.discover_tools().await?;
+                tracing::info!("  ✓ Discovered {} tools from {}", tools.len(), config.name);
+                tools
+            }
         };
 
-        tracing::info!("  ✓ Discovered {} tools from {}", tools.len(), config.name);
-
         // Register tools
         let mut registry = self.tool_registry.write().await;
-        for tool in tools {
+        for tool in &tools {
             tracing::info!("    - {}: {}", tool.name, tool.description);
             registry.insert(
                 tool.name.clone(),
@@ -186,7 +258,42 @@ impl MCPManager {
         clients.insert(config.name.clone(), client);
 
         tracing::info!("  ✓ Connected successfully");
-        Ok(())
+        Ok(tools)
+    }
+
+    /// Re-discover a connected server's tools and replace its registered set
+    pub async fn refresh_tools(&self, server_name: &str) -> Result<ToolDiff> {
+        let client = self.clients.read().await
+            .get(server_name)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Server not connected: {}", server_name))?;
+
+        let tools = client.discover_tools().await?;
+
+        let mut registry = self.tool_registry.write().await;
+        let previous: Vec<ToolDefinition> = registry.values()
+            .filter(|(server, _)| server == server_name)
+            .map(|(_, def)| def.clone())
+            .collect();
+        let (added, removed) = diff_tool_names(&previous, &tools);
+
+        registry.retain(|_, (server, _)| server != server_name);
+        for tool in &tools {
+            registry.insert(tool.name.clone(), (server_name.to_string(), tool.clone()));
+        }
+
+        if added.is_empty() && removed.is_empty() {
+            tracing::info!("🔄 {}: tools unchanged ({})", server_name, tools.len());
+        } else {
+            tracing::info!("🔄 {}: +{:?} -{:?}", server_name, added, removed);
+        }
+
+        Ok(ToolDiff {
+            server: server_name.to_string(),
+            added,
+            removed,
+            tools,
+        })
     }
 
     /// Disconnect from a server
@@ -212,17 +319,40 @@ impl MCPManager {
     }
 
     /// Call a tool
+    ///
+    /// Gives up after the server's `call_timeout_secs`, or when `cancel_all`
+    /// is called; both return a failed `ToolCallResult` rather than an error.
     pub async fn call_tool(&self, tool_name: &str, params: Value) -> Result<ToolCallResult> {
-        let registry = self.tool_registry.read().await;
-        let (server_name, _) = registry.get(tool_name)
+        let server_name = self.tool_registry.read().await
+            .get(tool_name)
+            .map(|(server, _)| server.clone())
             .ok_or_else(|| anyhow::anyhow!("Tool not found: {}", tool_name))?;
 
-        let clients = self.clients.read().await;
-        let client = clients.get(server_name)
+        // Clone the client out so the map isn't locked for the whole call
+        let client = self.clients.read().await
+            .get(&server_name)
+            .cloned()
             .ok_or_else(|| anyhow::anyhow!("Server not connected: {}", server_name))?;
 
+        // Created before the call so a concurrent cancel_all can't be missed
+        let cancelled = self.cancel.notified();
+        let timeout = client.server_info().call_timeout();
+
         tracing::info!("🔧 Calling tool: {} (server: {})", tool_name, server_name);
-        let result = client.call_tool(tool_name, params).await?;
+        let result = tokio::select! {
+            result = tokio::time::timeout(timeout, client.call_tool(tool_name, params)) => match result {
+                Ok(result) => result?,
+                Err(_) => {
+                    tracing::warn!("  ⏱ Tool call timed out after {:?}", timeout);
+                    ToolCallResult::failed(format!(
+                        "Tool '{}' timed out after {}s", tool_name, timeout.as_secs()
+                    ))
+                }
+            },
+            _ = cancelled => {
+                ToolCallResult::failed(format!("Tool '{}' was cancelled", tool_name))
+            }
+        };
 
         if result.success {
             tracing::info!("  ✓ Tool call successful");
@@ -233,6 +363,13 @@ impl MCPManager {
         Ok(result)
     }
 
+    /// Abandon every in-flight tool call, e.g. when the user stops a chat.
+    /// Calls started afterwards are unaffected.
+    pub fn cancel_all(&self) {
+        tracing::info!("🛑 Cancelling in-flight MCP tool calls");
+        self.cancel.notify_waiters();
+    }
+
     /// Search for tools by query
     pub async fn search_tools(&self, query: &str) -> Result<Vec<(String, ToolDefinition)>> {
         let registry = self.tool_registry.read().await;
@@ -270,4 +407,89 @@ mod tests {
         let servers = manager.list_servers().await.unwrap();
         assert_eq!(servers.len(), 0);
     }
+
+    /// Client whose tool calls never return
+    struct HangingClient {
+        config: MCPServerConfig,
+    }
+
+    #[async_trait]
+    impl MCPClient for HangingClient {
+        async fn discover_tools(&self) -> Result<Vec<ToolDefinition>> {
+            Ok(Vec::new())
+        }
+        async fn call_tool(&self, _name: &str, _params: Value) -> Result<ToolCallResult> {
+            std::future::pending().await
+        }
+        async fn list_resources(&self) -> Result<Vec<Resource>> {
+            Ok(Vec::new())
+        }
+        async fn read_resource(&self, uri: &str) -> Result<ResourceContent> {
+            anyhow::bail!("no resource {}", uri)
+        }
+        fn is_connected(&self) -> bool {
+            true
+        }
+        fn server_info(&self) -> &MCPServerConfig {
+            &self.config
+        }
+    }
+
+    async fn manager_with_hanging_tool(call_timeout_secs: u64) -> MCPManager {
+        let manager = MCPManager::new();
+        let config = MCPServerConfig {
+            name: "hang".to_string(),
+            command: String::new(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            transport: TransportType::Stdio,
+            headers: HashMap::new(),
+            call_timeout_secs,
+        };
+        manager.clients.write().await
+            .insert("hang".to_string(), Arc::new(HangingClient { config }));
+        manager.tool_registry.write().await.insert(
+            "wait".to_string(),
+            ("hang".to_string(), ToolDefinition {
+                name: "wait".to_string(),
+                description: String::new(),
+                input_schema: Value::Null,
+                category: None,
+            }),
+        );
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_refresh_tools_reports_removed_tools() {
+        let manager = manager_with_hanging_tool(1).await;
+        let diff = manager.refresh_tools("hang").await.unwrap();
+        assert_eq!(diff.removed, vec!["wait".to_string()]);
+        assert!(diff.added.is_empty());
+        assert!(manager.list_tools().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_times_out() {
+        let manager = manager_with_hanging_tool(1).await;
+        let result = manager.call_tool("wait", Value::Null).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("timed out after 1s"));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_all_releases_in_flight_calls() {
+        let manager = Arc::new(manager_with_hanging_tool(3600).await);
+        let call = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.call_tool("wait", Value::Null).await }
+        });
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        manager.cancel_all();
+
+        let result = call.await.unwrap().unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("cancelled"));
+    }
 }
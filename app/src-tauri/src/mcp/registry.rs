@@ -4,23 +4,44 @@ use super::*;
 use std::path::PathBuf;
 use tokio::fs;
 
+/// Tools last discovered from a server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedTools {
+    pub tools: Vec<ToolDefinition>,
+    pub discovered_at: chrono::DateTime<chrono::Utc>,
+}
+
 /// Registry for managing MCP server configurations
 pub struct MCPRegistry {
     servers: HashMap<String, MCPServerConfig>,
     config_file: PathBuf,
+    tool_cache: HashMap<String, CachedTools>,
+    tool_cache_file: PathBuf,
 }
 
 impl MCPRegistry {
     pub fn new(config_dir: PathBuf) -> Self {
         let config_file = config_dir.join("mcp_servers.json");
+        let tool_cache_file = config_dir.join("mcp_tool_cache.json");
         Self {
             servers: HashMap::new(),
             config_file,
+            tool_cache: HashMap::new(),
+            tool_cache_file,
         }
     }
 
-    /// Load server configurations from disk
+    /// Load server configurations (and the tool cache) from disk
     pub async fn load(&mut self) -> Result<()> {
+        if self.tool_cache_file.exists() {
+            let content = fs::read_to_string(&self.tool_cache_file).await?;
+            // A corrupt cache only costs a re-discovery
+            self.tool_cache = serde_json::from_str(&content).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring unreadable MCP tool cache: {}", e);
+                HashMap::new()
+            });
+        }
+
         if !self.config_file.exists() {
             // Create default configuration with built-in servers
             self.servers = builtin_tools::get_default_mcp_servers()
@@ -48,16 +69,45 @@ impl MCPRegistry {
         Ok(())
     }
 
-    /// Add or update a server configuration
+    /// Save the tool cache to disk
+    pub async fn save_tool_cache(&self) -> Result<()> {
+        let content = serde_json::to_string_pretty(&self.tool_cache)?;
+        fs::write(&self.tool_cache_file, content).await?;
+        Ok(())
+    }
+
+    /// Add or update a server configuration. Cached tools are dropped if the
+    /// server now points somewhere else.
     pub fn upsert(&mut self, config: MCPServerConfig) {
+        let same_endpoint = matches!(
+            self.servers.get(&config.name),
+            Some(old) if old.command == config.command && old.args == config.args
+        );
+        if !same_endpoint {
+            self.tool_cache.remove(&config.name);
+        }
         self.servers.insert(config.name.clone(), config);
     }
 
     /// Remove a server configuration
     pub fn remove(&mut self, name: &str) -> Option<MCPServerConfig> {
+        self.tool_cache.remove(name);
         self.servers.remove(name)
     }
 
+    /// Tools cached for a server, if it has been discovered before
+    pub fn cached_tools(&self, name: &str) -> Option<&CachedTools> {
+        self.tool_cache.get(name)
+    }
+
+    /// Record a server's current tool set
+    pub fn cache_tools(&mut self, name: &str, tools: Vec<ToolDefinition>) {
+        self.tool_cache.insert(name.to_string(), CachedTools {
+            tools,
+            discovered_at: chrono::Utc::now(),
+        });
+    }
+
     /// Get a server configuration
     pub fn get(&self, name: &str) -> Option<&MCPServerConfig> {
         self.servers.get(name)
@@ -16,6 +16,8 @@ pub struct StdioMCPClient {
     stdout: Arc<Mutex<Option<BufReader<ChildStdout>>>>,
     connected: Arc<std::sync::atomic::AtomicBool>,
     request_id: Arc<std::sync::atomic::AtomicU64>,
+    /// Set while a request line is being written
+    write_interrupted: Arc<std::sync::atomic::AtomicBool>,
 }
 
 impl StdioMCPClient {
@@ -49,6 +51,7 @@ impl StdioMCPClient {
             stdout: Arc::new(Mutex::new(Some(BufReader::new(stdout)))),
             connected: Arc::new(std::sync::atomic::AtomicBool::new(true)),
             request_id: Arc::new(std::sync::atomic::AtomicU64::new(1)),
+            write_interrupted: Arc::new(std::sync::atomic::AtomicBool::new(false)),
         };
 
         // Send initialization request
@@ -58,25 +61,17 @@ impl StdioMCPClient {
     }
 
     async fn initialize(&self) -> Result<()> {
-        let request = json!({
-            "jsonrpc": "2.0",
-            "id": self.next_request_id(),
-            "method": "initialize",
-            "params": {
-                "protocolVersion": "2024-11-05",
-                "capabilities": {
-                    "tools": {},
-                    "resources": {}
-                },
-                "clientInfo": {
-                    "name": "shodh-rag",
-                    "version": "0.1.0"
-                }
+        self.rpc("initialize", json!({
+            "protocolVersion": "2024-11-05",
+            "capabilities": {
+                "tools": {},
+                "resources": {}
+            },
+            "clientInfo": {
+                "name": "shodh-rag",
+                "version": "0.1.0"
             }
-        });
-
-        self.send_request(request).await?;
-        let _response = self.receive_response().await?;
+        })).await?;
 
         // Send initialized notification
         let notification = json!({
@@ -89,51 +84,389 @@ impl StdioMCPClient {
         Ok(())
     }
 
+    /// Send a request and wait for the response carrying its id.
+    ///
+    /// Holding the stdout lock for the whole exchange keeps concurrent calls
+    /// from reading each other's responses. If a caller gives up (timeout or
+    /// cancellation drops this future), its late response is discarded by
+    /// the next call instead of being mistaken for that call's answer.
+    async fn rpc(&self, method: &str, params: Value) -> Result<Value> {
+        let mut stdout_guard = self.stdout.lock().await;
+        let stdout = stdout_guard.as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Stdout not available"))?;
+
+        let id = self.next_request_id();
+        self.send_request(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params
+        })).await?;
+
+        loop {
+            // Read whole lines as bytes; one bad byte from the server then
+            // costs that line rather than failing the call
+            let mut bytes = Vec::new();
+            if stdout.read_until(b'\n', &mut bytes).await? == 0 {
+                self.connected.store(false, std::sync::atomic::Ordering::SeqCst);
+                anyhow::bail!("Connection closed by server");
+            }
+            let line = String::from_utf8_lossy(&bytes);
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let message: Value = match serde_json::from_str(&line) {
+                Ok(m) => m,
+                Err(e) => {
+                    tracing::debug!("Ignoring non-JSON line from {}: {}", self.config.name, e);
+                    continue;
+                }
+            };
+            match message.get("id").and_then(|v| v.as_u64()) {
+                Some(response_id) if response_id == id => return Ok(message),
+                Some(stale) if message.get("method").is_none() => {
+                    tracing::debug!("Discarding stale response {} from {}", stale, self.config.name);
+                }
+                _ => tracing::debug!("Ignoring server message from {}: {}", self.config.name, message),
+            }
+        }
+    }
+
     async fn send_request(&self, request: Value) -> Result<()> {
         let mut stdin_guard = self.stdin.lock().await;
         let stdin = stdin_guard.as_mut()
             .ok_or_else(|| anyhow::anyhow!("Stdin not available"))?;
 
+        // A write cut off by a dropped future leaves a partial line behind;
+        // terminate it so the server sees a bad line rather than a merged one
+        if self.write_interrupted.swap(true, std::sync::atomic::Ordering::SeqCst) {
+            stdin.write_all(b"\n").await?;
+        }
+
         let request_str = serde_json::to_string(&request)?;
         stdin.write_all(request_str.as_bytes()).await?;
         stdin.write_all(b"\n").await?;
         stdin.flush().await?;
 
+        self.write_interrupted.store(false, std::sync::atomic::Ordering::SeqCst);
         Ok(())
     }
 
-    async fn receive_response(&self) -> Result<Value> {
-        let mut stdout_guard = self.stdout.lock().await;
-        let stdout = stdout_guard.as_mut()
-            .ok_or_else(|| anyhow::anyhow!("Stdout not available"))?;
+    fn next_request_id(&self) -> u64 {
+        self.request_id.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl MCPClient for StdioMCPClient {
+    async fn discover_tools(&self) -> Result<Vec<ToolDefinition>> {
+        let response = self.rpc("tools/list", json!({})).await?;
+
+        if let Some(result) = response.get("result") {
+            if let Some(tools) = result.get("tools") {
+                let tools: Vec<ToolDefinition> = serde_json::from_value(tools.clone())?;
+                return Ok(tools);
+            }
+        }
+
+        Ok(Vec::new())
+    }
+
+    async fn call_tool(&self, name: &str, params: Value) -> Result<ToolCallResult> {
+        let response = self.rpc("tools/call", json!({
+            "name": name,
+            "arguments": params
+        })).await?;
+
+        if let Some(error) = response.get("error") {
+            return Ok(ToolCallResult {
+                success: false,
+                result: None,
+                error: Some(error.to_string()),
+                artifacts: Vec::new(),
+            });
+        }
+
+        if let Some(result) = response.get("result") {
+            return Ok(ToolCallResult {
+                success: true,
+                result: Some(result.clone()),
+                error: None,
+                artifacts: Vec::new(),
+            });
+        }
+
+        anyhow::bail!("Invalid response from server");
+    }
+
+    async fn list_resources(&self) -> Result<Vec<Resource>> {
+        let response = self.rpc("resources/list", json!({})).await?;
+
+        if let Some(result) = response.get("result") {
+            if let Some(resources) = result.get("resources") {
+                let resources: Vec<Resource> = serde_json::from_value(resources.clone())?;
+                return Ok(resources);
+            }
+        }
+
+        Ok(Vec::new())
+    }
+
+    async fn read_resource(&self, uri: &str) -> Result<ResourceContent> {
+        let response = self.rpc("resources/read", json!({ "uri": uri })).await?;
+
+        if let Some(result) = response.get("result") {
+            let content: ResourceContent = serde_json::from_value(result.clone())?;
+            return Ok(content);
+        }
+
+        anyhow::bail!("Failed to read resource: {}", uri);
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    fn server_info(&self) -> &MCPServerConfig {
+        &self.config
+    }
+}
+
+impl Drop for StdioMCPClient {
+    fn drop(&mut self) {
+        // Mark as disconnected
+        self.connected.store(false, std::sync::atomic::Ordering::SeqCst);
+
+        // Kill process
+        if let Ok(mut process_guard) = self.process.try_lock() {
+            if let Some(mut process) = process_guard.take() {
+                let _ = process.start_kill();
+            }
+        }
+    }
+}
+
+/// Header carrying the session id assigned by a streamable HTTP server
+const MCP_SESSION_HEADER: &str = "mcp-session-id";
+
+/// Per-request timeout for HTTP JSON-RPC calls
+const HTTP_REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Streamable HTTP MCP client.
+///
+/// Each JSON-RPC request is a POST to the server endpoint; the server answers
+/// with either a JSON body or an SSE stream that ends with the response.
+/// Server-to-client notifications arrive on a long-lived GET SSE stream and
+/// are published to `subscribe()` receivers.
+pub struct HttpMCPClient {
+    config: MCPServerConfig,
+    http: reqwest::Client,
+    endpoint: String,
+    headers: reqwest::header::HeaderMap,
+    session_id: Arc<std::sync::RwLock<Option<String>>>,
+    connected: Arc<std::sync::atomic::AtomicBool>,
+    request_id: Arc<std::sync::atomic::AtomicU64>,
+    notifications: tokio::sync::broadcast::Sender<Value>,
+    listener: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl HttpMCPClient {
+    pub async fn new(config: MCPServerConfig) -> Result<Self> {
+        let endpoint = config.command.trim().to_string();
+        if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+            anyhow::bail!("HTTP MCP server '{}' needs an http(s) URL, got '{}'", config.name, endpoint);
+        }
+
+        let headers = build_headers(&config)?;
+        let http = reqwest::Client::builder()
+            .connect_timeout(std::time::Duration::from_secs(10))
+            .build()
+            .context("Failed to build HTTP client")?;
+        let (notifications, _) = tokio::sync::broadcast::channel(64);
+
+        let mut client = Self {
+            config,
+            http,
+            endpoint,
+            headers,
+            session_id: Arc::new(std::sync::RwLock::new(None)),
+            connected: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            request_id: Arc::new(std::sync::atomic::AtomicU64::new(1)),
+            notifications,
+            listener: None,
+        };
+
+        client.initialize().await
+            .with_context(|| format!("MCP handshake with {} failed", client.config.name))?;
+        client.connected.store(true, std::sync::atomic::Ordering::SeqCst);
+        client.listener = Some(client.spawn_notification_listener());
+
+        Ok(client)
+    }
+
+    /// Receive server-to-client notifications (e.g. `notifications/tools/list_changed`)
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<Value> {
+        self.notifications.subscribe()
+    }
+
+    async fn initialize(&self) -> Result<()> {
+        self.request("initialize", json!({
+            "protocolVersion": "2024-11-05",
+            "capabilities": {
+                "tools": {},
+                "resources": {}
+            },
+            "clientInfo": {
+                "name": "shodh-rag",
+                "version": "0.1.0"
+            }
+        })).await?;
+
+        self.notify("notifications/initialized").await
+    }
+
+    /// POST a JSON-RPC request and return the matching response message
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_request_id();
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params
+        });
+
+        let response = self.post(&body).await?;
+        if let Some(session) = response.headers().get(MCP_SESSION_HEADER).and_then(|v| v.to_str().ok()) {
+            *self.session_id.write().unwrap() = Some(session.to_string());
+        }
+
+        let is_sse = response.headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("text/event-stream"));
 
-        let mut line = String::new();
-        stdout.read_line(&mut line).await?;
+        if is_sse {
+            self.read_sse_response(response, id).await
+        } else {
+            response.json::<Value>().await.context("Invalid JSON-RPC response")
+        }
+    }
+
+    /// POST a JSON-RPC notification (no response expected)
+    async fn notify(&self, method: &str) -> Result<()> {
+        self.post(&json!({
+            "jsonrpc": "2.0",
+            "method": method
+        })).await?;
+        Ok(())
+    }
 
-        if line.is_empty() {
-            anyhow::bail!("Connection closed by server");
+    async fn post(&self, body: &Value) -> Result<reqwest::Response> {
+        let mut request = self.http
+            .post(&self.endpoint)
+            .headers(self.headers.clone())
+            .header(reqwest::header::ACCEPT, "application/json, text/event-stream")
+            .timeout(HTTP_REQUEST_TIMEOUT)
+            .json(body);
+        if let Some(session) = self.session_id.read().unwrap().clone() {
+            request = request.header(MCP_SESSION_HEADER, session);
         }
 
-        let response: Value = serde_json::from_str(&line)?;
+        let response = request.send().await
+            .with_context(|| format!("Failed to reach MCP server at {}", self.endpoint))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND && self.session_id.read().unwrap().is_some() {
+            // The server dropped our session; callers must reconnect
+            self.connected.store(false, std::sync::atomic::Ordering::SeqCst);
+            anyhow::bail!("MCP session expired on {}", self.config.name);
+        }
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            anyhow::bail!("MCP server {} returned {}: {}", self.config.name, status, text);
+        }
         Ok(response)
     }
 
+    /// Read an SSE response stream until the message answering `id` arrives.
+    /// Anything else the server interleaves is treated as a notification.
+    async fn read_sse_response(&self, response: reqwest::Response, id: u64) -> Result<Value> {
+        use futures::StreamExt;
+
+        let mut stream = response.bytes_stream();
+        let mut buffer = Vec::new();
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.context("MCP SSE stream failed")?;
+            buffer.extend_from_slice(&chunk);
+
+            for message in drain_sse_messages(&mut buffer) {
+                if message.get("id").and_then(|v| v.as_u64()) == Some(id) {
+                    return Ok(message);
+                }
+                let _ = self.notifications.send(message);
+            }
+        }
+
+        anyhow::bail!("MCP server closed the stream before responding to request {}", id)
+    }
+
+    /// Open the GET SSE stream for server-initiated messages. Servers that
+    /// don't offer one answer 405, which is fine.
+    fn spawn_notification_listener(&self) -> tokio::task::JoinHandle<()> {
+        let http = self.http.clone();
+        let endpoint = self.endpoint.clone();
+        let headers = self.headers.clone();
+        let session_id = self.session_id.read().unwrap().clone();
+        let notifications = self.notifications.clone();
+        let name = self.config.name.clone();
+
+        tokio::spawn(async move {
+            use futures::StreamExt;
+
+            let mut request = http
+                .get(&endpoint)
+                .headers(headers)
+                .header(reqwest::header::ACCEPT, "text/event-stream");
+            if let Some(session) = session_id {
+                request = request.header(MCP_SESSION_HEADER, session);
+            }
+
+            let response = match request.send().await {
+                Ok(r) if r.status().is_success() => r,
+                Ok(r) => {
+                    tracing::debug!("MCP server {} has no notification stream ({})", name, r.status());
+                    return;
+                }
+                Err(e) => {
+                    tracing::debug!("MCP notification stream for {} unavailable: {}", name, e);
+                    return;
+                }
+            };
+
+            let mut stream = response.bytes_stream();
+            let mut buffer = Vec::new();
+            while let Some(Ok(chunk)) = stream.next().await {
+                buffer.extend_from_slice(&chunk);
+                for message in drain_sse_messages(&mut buffer) {
+                    tracing::debug!("MCP notification from {}: {}", name, message);
+                    let _ = notifications.send(message);
+                }
+            }
+            tracing::debug!("MCP notification stream for {} closed", name);
+        })
+    }
+
     fn next_request_id(&self) -> u64 {
         self.request_id.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
     }
 }
 
 #[async_trait]
-impl MCPClient for StdioMCPClient {
-    async fn discover_tools(&mut self) -> Result<Vec<ToolDefinition>> {
-        let request = json!({
-            "jsonrpc": "2.0",
-            "id": self.next_request_id(),
-            "method": "tools/list"
-        });
-
-        self.send_request(request).await?;
-        let response = self.receive_response().await?;
+impl MCPClient for HttpMCPClient {
+    async fn discover_tools(&self) -> Result<Vec<ToolDefinition>> {
+        let response = self.request("tools/list", json!({})).await?;
 
         if let Some(result) = response.get("result") {
             if let Some(tools) = result.get("tools") {
@@ -146,18 +479,10 @@ impl MCPClient for StdioMCPClient {
     }
 
     async fn call_tool(&self, name: &str, params: Value) -> Result<ToolCallResult> {
-        let request = json!({
-            "jsonrpc": "2.0",
-            "id": self.next_request_id(),
-            "method": "tools/call",
-            "params": {
-                "name": name,
-                "arguments": params
-            }
-        });
-
-        self.send_request(request).await?;
-        let response = self.receive_response().await?;
+        let response = self.request("tools/call", json!({
+            "name": name,
+            "arguments": params
+        })).await?;
 
         if let Some(error) = response.get("error") {
             return Ok(ToolCallResult {
@@ -181,14 +506,7 @@ impl MCPClient for StdioMCPClient {
     }
 
     async fn list_resources(&self) -> Result<Vec<Resource>> {
-        let request = json!({
-            "jsonrpc": "2.0",
-            "id": self.next_request_id(),
-            "method": "resources/list"
-        });
-
-        self.send_request(request).await?;
-        let response = self.receive_response().await?;
+        let response = self.request("resources/list", json!({})).await?;
 
         if let Some(result) = response.get("result") {
             if let Some(resources) = result.get("resources") {
@@ -201,17 +519,426 @@ impl MCPClient for StdioMCPClient {
     }
 
     async fn read_resource(&self, uri: &str) -> Result<ResourceContent> {
-        let request = json!({
-            "jsonrpc": "2.0",
-            "id": self.next_request_id(),
-            "method": "resources/read",
-            "params": {
-                "uri": uri
+        let response = self.request("resources/read", json!({ "uri": uri })).await?;
+
+        if let Some(result) = response.get("result") {
+            let content: ResourceContent = serde_json::from_value(result.clone())?;
+            return Ok(content);
+        }
+
+        anyhow::bail!("Failed to read resource: {}", uri);
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    fn server_info(&self) -> &MCPServerConfig {
+        &self.config
+    }
+}
+
+impl Drop for HttpMCPClient {
+    fn drop(&mut self) {
+        self.connected.store(false, std::sync::atomic::Ordering::SeqCst);
+
+        if let Some(listener) = self.listener.take() {
+            listener.abort();
+        }
+
+        // Tell the server to release the session, if we're still inside a runtime
+        let session = self.session_id.read().ok().and_then(|s| s.clone());
+        if let (Some(session), Ok(handle)) = (session, tokio::runtime::Handle::try_current()) {
+            let request = self.http
+                .delete(&self.endpoint)
+                .headers(self.headers.clone())
+                .header(MCP_SESSION_HEADER, session);
+            handle.spawn(async move {
+                let _ = request.send().await;
+            });
+        }
+    }
+}
+
+/// Build request headers from the config, expanding `${VAR}` references
+fn build_headers(config: &MCPServerConfig) -> Result<reqwest::header::HeaderMap> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    for (key, value) in &config.headers {
+        let value = expand_env_vars(value, &config.env);
+        let name = reqwest::header::HeaderName::from_bytes(key.as_bytes())
+            .with_context(|| format!("Invalid header name: {}", key))?;
+        let value = reqwest::header::HeaderValue::from_str(&value)
+            .with_context(|| format!("Invalid value for header {}", key))?;
+        headers.insert(name, value);
+    }
+    Ok(headers)
+}
+
+/// Replace `${VAR}` with the value from `env`, falling back to the process
+/// environment. Unknown variables expand to an empty string.
+fn expand_env_vars(value: &str, env: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else { break };
+        out.push_str(&rest[..start]);
+        let var = &rest[start + 2..start + 2 + len];
+        let resolved = env.get(var).cloned().or_else(|| std::env::var(var).ok());
+        out.push_str(&resolved.unwrap_or_default());
+        rest = &rest[start + 3 + len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Pop complete SSE events off `buffer` and parse their `data:` payloads as
+/// JSON-RPC messages. A trailing partial event is left in the buffer as raw
+/// bytes, since a chunk boundary can fall inside a multi-byte character.
+fn drain_sse_messages(buffer: &mut Vec<u8>) -> Vec<Value> {
+    buffer.retain(|&b| b != b'\r');
+
+    let mut messages = Vec::new();
+    while let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+        let bytes: Vec<u8> = buffer.drain(..end + 2).collect();
+        let event = String::from_utf8_lossy(&bytes);
+        let data = event
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(|d| d.strip_prefix(' ').unwrap_or(d))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if data.is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Value>(&data) {
+            // A batch is delivered as a JSON array
+            Ok(Value::Array(batch)) => messages.extend(batch),
+            Ok(message) => messages.push(message),
+            Err(e) => tracing::debug!("Ignoring non-JSON SSE event: {}", e),
+        }
+    }
+    messages
+}
+
+/// Per-request timeout for WebSocket JSON-RPC calls
+const WS_REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// First reconnect delay; doubles per failed attempt up to `WS_MAX_RECONNECT_DELAY`
+const WS_INITIAL_RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+const WS_MAX_RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(30);
+
+type WsMessage = tokio_tungstenite::tungstenite::Message;
+
+/// WebSocket MCP client.
+///
+/// JSON-RPC requests and responses are multiplexed over one socket and
+/// matched by id. If the socket drops, a supervisor task reconnects with
+/// backoff, repeats the initialize handshake and re-discovers tools.
+pub struct WebSocketMCPClient {
+    config: MCPServerConfig,
+    shared: Arc<WsShared>,
+    supervisor: Option<tokio::task::JoinHandle<()>>,
+}
+
+/// State shared between the client, the socket reader and the supervisor
+struct WsShared {
+    config: MCPServerConfig,
+    outgoing: std::sync::Mutex<Option<tokio::sync::mpsc::UnboundedSender<WsMessage>>>,
+    pending: std::sync::Mutex<HashMap<u64, tokio::sync::oneshot::Sender<Value>>>,
+    connected: std::sync::atomic::AtomicBool,
+    closing: std::sync::atomic::AtomicBool,
+    request_id: std::sync::atomic::AtomicU64,
+    tools: std::sync::RwLock<Vec<ToolDefinition>>,
+    notifications: tokio::sync::broadcast::Sender<Value>,
+}
+
+impl WebSocketMCPClient {
+    pub async fn new(config: MCPServerConfig) -> Result<Self> {
+        let url = config.command.trim();
+        if !url.starts_with("ws://") && !url.starts_with("wss://") {
+            anyhow::bail!("WebSocket MCP server '{}' needs a ws(s) URL, got '{}'", config.name, url);
+        }
+
+        let (notifications, _) = tokio::sync::broadcast::channel(64);
+        let shared = Arc::new(WsShared {
+            config: config.clone(),
+            outgoing: std::sync::Mutex::new(None),
+            pending: std::sync::Mutex::new(HashMap::new()),
+            connected: std::sync::atomic::AtomicBool::new(false),
+            closing: std::sync::atomic::AtomicBool::new(false),
+            request_id: std::sync::atomic::AtomicU64::new(1),
+            tools: std::sync::RwLock::new(Vec::new()),
+            notifications,
+        });
+
+        let reader = shared.connect().await?;
+        if let Err(e) = shared.handshake().await {
+            reader.abort();
+            shared.mark_disconnected();
+            return Err(e.context(format!("MCP handshake with {} failed", config.name)));
+        }
+
+        let supervisor = tokio::spawn(WsShared::supervise(shared.clone(), reader));
+
+        Ok(Self {
+            config,
+            shared,
+            supervisor: Some(supervisor),
+        })
+    }
+
+    /// Receive server-to-client notifications
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<Value> {
+        self.shared.notifications.subscribe()
+    }
+
+    /// Tools from the most recent discovery, including after a reconnect
+    pub fn cached_tools(&self) -> Vec<ToolDefinition> {
+        self.shared.tools.read().unwrap().clone()
+    }
+}
+
+impl WsShared {
+    /// Open the socket and start the reader/writer tasks. The returned handle
+    /// completes when the socket closes.
+    async fn connect(self: &Arc<Self>) -> Result<tokio::task::JoinHandle<()>> {
+        use futures::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+        use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+
+        let mut request = self.config.command.trim().into_client_request()
+            .with_context(|| format!("Invalid WebSocket URL for {}", self.config.name))?;
+        for (key, value) in &self.config.headers {
+            let value = expand_env_vars(value, &self.config.env);
+            request.headers_mut().insert(
+                HeaderName::from_bytes(key.as_bytes())
+                    .with_context(|| format!("Invalid header name: {}", key))?,
+                HeaderValue::from_str(&value)
+                    .with_context(|| format!("Invalid value for header {}", key))?,
+            );
+        }
+
+        let (socket, _) = tokio_tungstenite::connect_async(request).await
+            .with_context(|| format!("Failed to connect to MCP server {}", self.config.name))?;
+        let (mut sink, mut stream) = socket.split();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<WsMessage>();
+        tokio::spawn(async move {
+            while let Some(message) = rx.recv().await {
+                if sink.send(message).await.is_err() {
+                    break;
+                }
             }
+            let _ = sink.close().await;
         });
 
-        self.send_request(request).await?;
-        let response = self.receive_response().await?;
+        *self.outgoing.lock().unwrap() = Some(tx);
+        self.connected.store(true, std::sync::atomic::Ordering::SeqCst);
+
+        let shared = self.clone();
+        Ok(tokio::spawn(async move {
+            while let Some(message) = stream.next().await {
+                match message {
+                    Ok(WsMessage::Text(text)) => shared.dispatch(&text),
+                    Ok(WsMessage::Close(_)) | Err(_) => break,
+                    Ok(_) => {}
+                }
+            }
+            shared.mark_disconnected();
+        }))
+    }
+
+    /// Route an incoming message to its waiting request, or publish it
+    fn dispatch(&self, text: &str) {
+        let message: Value = match serde_json::from_str(text) {
+            Ok(m) => m,
+            Err(e) => {
+                tracing::debug!("Ignoring non-JSON message from {}: {}", self.config.name, e);
+                return;
+            }
+        };
+
+        let is_response = message.get("result").is_some() || message.get("error").is_some();
+        if let (true, Some(id)) = (is_response, message.get("id").and_then(|v| v.as_u64())) {
+            if let Some(waiter) = self.pending.lock().unwrap().remove(&id) {
+                let _ = waiter.send(message);
+            }
+            return;
+        }
+        let _ = self.notifications.send(message);
+    }
+
+    /// Drop the writer and fail all in-flight requests
+    fn mark_disconnected(&self) {
+        self.connected.store(false, std::sync::atomic::Ordering::SeqCst);
+        self.outgoing.lock().unwrap().take();
+        self.pending.lock().unwrap().clear();
+    }
+
+    fn send_close(&self) -> Result<()> {
+        if let Some(outgoing) = self.outgoing.lock().unwrap().as_ref() {
+            outgoing.send(WsMessage::Close(None))
+                .map_err(|_| anyhow::anyhow!("Socket already closed"))?;
+        }
+        Ok(())
+    }
+
+    fn send(&self, message: &Value) -> Result<()> {
+        let outgoing = self.outgoing.lock().unwrap().clone()
+            .ok_or_else(|| anyhow::anyhow!("MCP server {} is not connected", self.config.name))?;
+        outgoing.send(WsMessage::Text(message.to_string()))
+            .map_err(|_| anyhow::anyhow!("MCP server {} is not connected", self.config.name))
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.request_id.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+
+        let sent = self.send(&json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params
+        }));
+        if let Err(e) = sent {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(WS_REQUEST_TIMEOUT, rx).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => anyhow::bail!("Connection to {} lost during {}", self.config.name, method),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                anyhow::bail!("{} timed out on {}", method, self.config.name)
+            }
+        }
+    }
+
+    /// Initialize the session and refresh the tool list
+    async fn handshake(&self) -> Result<Vec<ToolDefinition>> {
+        self.request("initialize", json!({
+            "protocolVersion": "2024-11-05",
+            "capabilities": {
+                "tools": {},
+                "resources": {}
+            },
+            "clientInfo": {
+                "name": "shodh-rag",
+                "version": "0.1.0"
+            }
+        })).await?;
+        self.send(&json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/initialized"
+        }))?;
+        self.list_tools().await
+    }
+
+    async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+        let response = self.request("tools/list", json!({})).await?;
+
+        let tools: Vec<ToolDefinition> = match response.get("result").and_then(|r| r.get("tools")) {
+            Some(tools) => serde_json::from_value(tools.clone())?,
+            None => Vec::new(),
+        };
+        *self.tools.write().unwrap() = tools.clone();
+        Ok(tools)
+    }
+
+    /// Wait for the socket to drop, then reconnect with exponential backoff
+    async fn supervise(shared: Arc<Self>, mut reader: tokio::task::JoinHandle<()>) {
+        loop {
+            let _ = (&mut reader).await;
+            if shared.closing.load(std::sync::atomic::Ordering::SeqCst) {
+                return;
+            }
+            tracing::warn!("MCP WebSocket to {} dropped, reconnecting", shared.config.name);
+
+            let mut delay = WS_INITIAL_RECONNECT_DELAY;
+            reader = loop {
+                tokio::time::sleep(delay).await;
+                if shared.closing.load(std::sync::atomic::Ordering::SeqCst) {
+                    return;
+                }
+                delay = (delay * 2).min(WS_MAX_RECONNECT_DELAY);
+
+                let handle = match shared.connect().await {
+                    Ok(handle) => handle,
+                    Err(e) => {
+                        tracing::warn!("Reconnect to {} failed: {}", shared.config.name, e);
+                        continue;
+                    }
+                };
+                match shared.handshake().await {
+                    Ok(tools) => {
+                        tracing::info!(
+                            "Reconnected to {}, re-discovered {} tools",
+                            shared.config.name,
+                            tools.len()
+                        );
+                        break handle;
+                    }
+                    Err(e) => {
+                        tracing::warn!("Handshake with {} failed after reconnect: {}", shared.config.name, e);
+                        handle.abort();
+                        shared.mark_disconnected();
+                    }
+                }
+            };
+        }
+    }
+}
+
+#[async_trait]
+impl MCPClient for WebSocketMCPClient {
+    async fn discover_tools(&self) -> Result<Vec<ToolDefinition>> {
+        self.shared.list_tools().await
+    }
+
+    async fn call_tool(&self, name: &str, params: Value) -> Result<ToolCallResult> {
+        let response = self.shared.request("tools/call", json!({
+            "name": name,
+            "arguments": params
+        })).await?;
+
+        if let Some(error) = response.get("error") {
+            return Ok(ToolCallResult {
+                success: false,
+                result: None,
+                error: Some(error.to_string()),
+                artifacts: Vec::new(),
+            });
+        }
+
+        if let Some(result) = response.get("result") {
+            return Ok(ToolCallResult {
+                success: true,
+                result: Some(result.clone()),
+                error: None,
+                artifacts: Vec::new(),
+            });
+        }
+
+        anyhow::bail!("Invalid response from server");
+    }
+
+    async fn list_resources(&self) -> Result<Vec<Resource>> {
+        let response = self.shared.request("resources/list", json!({})).await?;
+
+        if let Some(result) = response.get("result") {
+            if let Some(resources) = result.get("resources") {
+                let resources: Vec<Resource> = serde_json::from_value(resources.clone())?;
+                return Ok(resources);
+            }
+        }
+
+        Ok(Vec::new())
+    }
+
+    async fn read_resource(&self, uri: &str) -> Result<ResourceContent> {
+        let response = self.shared.request("resources/read", json!({ "uri": uri })).await?;
 
         if let Some(result) = response.get("result") {
             let content: ResourceContent = serde_json::from_value(result.clone())?;
@@ -222,7 +949,7 @@ impl MCPClient for StdioMCPClient {
     }
 
     fn is_connected(&self) -> bool {
-        self.connected.load(std::sync::atomic::Ordering::SeqCst)
+        self.shared.connected.load(std::sync::atomic::Ordering::SeqCst)
     }
 
     fn server_info(&self) -> &MCPServerConfig {
@@ -230,16 +957,149 @@ impl MCPClient for StdioMCPClient {
     }
 }
 
-impl Drop for StdioMCPClient {
+impl Drop for WebSocketMCPClient {
     fn drop(&mut self) {
-        // Mark as disconnected
-        self.connected.store(false, std::sync::atomic::Ordering::SeqCst);
+        self.shared.closing.store(true, std::sync::atomic::Ordering::SeqCst);
+        if let Some(supervisor) = self.supervisor.take() {
+            supervisor.abort();
+        }
+        // Closing the writer channel closes the socket, which ends the reader
+        let _ = self.shared.send_close();
+        self.shared.mark_disconnected();
+    }
+}
 
-        // Kill process
-        if let Ok(mut process_guard) = self.process.try_lock() {
-            if let Some(mut process) = process_guard.take() {
-                let _ = process.start_kill();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drain_sse_messages_keeps_partial_event() {
+        let mut buffer = b"event: message\r\ndata: {\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{}}\r\n\r\ndata: {\"jsonrpc\"".to_vec();
+        let messages = drain_sse_messages(&mut buffer);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["id"], 1);
+        assert_eq!(buffer, b"data: {\"jsonrpc\"");
+
+        buffer.extend_from_slice(b":\"2.0\",\"method\":\"notifications/tools/list_changed\"}\n\n");
+        let messages = drain_sse_messages(&mut buffer);
+        assert_eq!(messages[0]["method"], "notifications/tools/list_changed");
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_drain_sse_messages_multibyte_split_across_chunks() {
+        let event = "data: {\"id\":2,\"result\":{\"text\":\"नमस्ते\"}}\n\n".as_bytes();
+        let split = event.iter().position(|&b| b >= 0x80).unwrap() + 1;
+
+        let mut buffer = event[..split].to_vec();
+        assert!(drain_sse_messages(&mut buffer).is_empty());
+        buffer.extend_from_slice(&event[split..]);
+        let messages = drain_sse_messages(&mut buffer);
+        assert_eq!(messages[0]["result"]["text"], "नमस्ते");
+    }
+
+    #[test]
+    fn test_expand_env_vars() {
+        let mut env = HashMap::new();
+        env.insert("TOKEN".to_string(), "abc".to_string());
+        assert_eq!(expand_env_vars("Bearer ${TOKEN}", &env), "Bearer abc");
+        assert_eq!(expand_env_vars("${SHODH_TEST_UNSET_VAR}x", &env), "x");
+        assert_eq!(expand_env_vars("no vars", &env), "no vars");
+        assert_eq!(expand_env_vars("${open", &env), "${open");
+    }
+
+    /// Minimal MCP server: answers the handshake, lists one `echo` tool and
+    /// echoes `tools/call` arguments back. With `drop_first`, the first
+    /// connection is closed right after its handshake.
+    async fn spawn_echo_server(drop_first: bool) -> String {
+        use futures::{SinkExt, StreamExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connections = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let n = connections.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    while let Some(Ok(WsMessage::Text(text))) = socket.next().await {
+                        let request: Value = serde_json::from_str(&text).unwrap();
+                        let Some(id) = request.get("id").cloned() else { continue };
+                        let result = match request["method"].as_str().unwrap() {
+                            "initialize" => json!({ "protocolVersion": "2024-11-05", "capabilities": {} }),
+                            "tools/list" => json!({ "tools": [{
+                                "name": "echo",
+                                "description": "Echo the input",
+                                "inputSchema": { "type": "object" }
+                            }] }),
+                            "tools/call" => json!({ "content": [{
+                                "type": "text",
+                                "text": request["params"]["arguments"]["text"]
+                            }] }),
+                            _ => json!({}),
+                        };
+                        let reply = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+                        socket.send(WsMessage::Text(reply.to_string())).await.unwrap();
+
+                        if drop_first && n == 0 && request["method"] == "tools/list" {
+                            let _ = socket.close(None).await;
+                            return;
+                        }
+                    }
+                });
             }
+        });
+
+        format!("ws://{}", addr)
+    }
+
+    fn ws_config(url: String) -> MCPServerConfig {
+        MCPServerConfig {
+            name: "echo".to_string(),
+            command: url,
+            args: Vec::new(),
+            env: HashMap::new(),
+            transport: TransportType::WebSocket,
+            headers: HashMap::new(),
+            call_timeout_secs: 30,
         }
     }
+
+    #[tokio::test]
+    async fn test_websocket_discovers_tools_and_calls() {
+        let url = spawn_echo_server(false).await;
+        let client = WebSocketMCPClient::new(ws_config(url)).await.unwrap();
+        assert!(client.is_connected());
+
+        let tools = client.discover_tools().await.unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "echo");
+
+        let result = client.call_tool("echo", json!({ "text": "hello" })).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.result.unwrap()["content"][0]["text"], "hello");
+    }
+
+    #[tokio::test]
+    async fn test_websocket_reconnects_and_rediscovers_tools() {
+        let url = spawn_echo_server(true).await;
+        let client = WebSocketMCPClient::new(ws_config(url)).await.unwrap();
+
+        // The server closes the first session right after the handshake
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+        while client.is_connected() && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        while !client.is_connected() && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(client.is_connected());
+
+        // Re-discovery runs as part of the reconnect handshake
+        let result = client.call_tool("echo", json!({ "text": "again" })).await.unwrap();
+        assert_eq!(result.result.unwrap()["content"][0]["text"], "again");
+        assert_eq!(client.cached_tools().len(), 1);
+    }
 }
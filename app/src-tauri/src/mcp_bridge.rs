@@ -6,9 +6,87 @@
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-use crate::mcp::MCPManager;
+use crate::mcp::{MCPManager, ToolCallResult};
 use shodh_rag::agent::{DynamicToolDef, ToolCallback, ToolResult};
 
+/// Prefix shared by every bridged tool ID
+pub const MCP_TOOL_PREFIX: &str = "mcp__";
+
+/// Agent tool ID for an MCP tool: `mcp__<server>__<tool>`.
+/// Characters LLM function names don't allow are replaced with `_`.
+pub fn mcp_tool_id(server: &str, tool: &str) -> String {
+    fn sanitize(s: &str) -> String {
+        s.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+            .collect()
+    }
+    format!("{}{}__{}", MCP_TOOL_PREFIX, sanitize(server), sanitize(tool))
+}
+
+/// MCP `inputSchema` as an agent parameters schema. Tool-calling APIs
+/// require an object schema, so missing or non-object schemas become an
+/// empty-object schema.
+fn to_parameters_schema(input_schema: &serde_json::Value) -> serde_json::Value {
+    match input_schema {
+        serde_json::Value::Object(map) if map.get("type").and_then(|t| t.as_str()) == Some("object") => {
+            let mut schema = map.clone();
+            schema.entry("properties").or_insert_with(|| serde_json::json!({}));
+            serde_json::Value::Object(schema)
+        }
+        serde_json::Value::Object(map) if map.contains_key("properties") => {
+            let mut schema = map.clone();
+            schema.insert("type".to_string(), serde_json::json!("object"));
+            serde_json::Value::Object(schema)
+        }
+        _ => serde_json::json!({ "type": "object", "properties": {} }),
+    }
+}
+
+/// Convert an MCP tool result into an agent ToolResult. Text content blocks
+/// become the output, `structuredContent` the structured payload; MCP's
+/// `isError` flag marks the call as failed.
+fn to_tool_result(mcp_result: ToolCallResult) -> ToolResult {
+    let is_error = mcp_result.result.as_ref()
+        .and_then(|r| r.get("isError"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let text = mcp_result.result.as_ref()
+        .and_then(|r| r.get("content"))
+        .and_then(|c| c.as_array())
+        .map(|blocks| {
+            blocks.iter()
+                .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .filter(|t| !t.is_empty());
+
+    let output = if let Some(text) = text {
+        text
+    } else if let Some(ref val) = mcp_result.result {
+        serde_json::to_string_pretty(val).unwrap_or_else(|_| format!("{:?}", val))
+    } else if let Some(ref err) = mcp_result.error {
+        err.clone()
+    } else {
+        "Tool executed successfully".to_string()
+    };
+
+    let structured = mcp_result.result.as_ref()
+        .and_then(|r| r.get("structuredContent"))
+        .cloned();
+    let success = mcp_result.success && !is_error;
+    let error = mcp_result.error.or_else(|| is_error.then(|| output.clone()));
+
+    ToolResult {
+        success,
+        output,
+        data: mcp_result.result.unwrap_or(serde_json::json!({})),
+        error,
+        structured,
+    }
+}
+
 /// Discover all tools from connected MCP servers and return them as
 /// DynamicToolDefs ready for registration in the agent ToolRegistry.
 pub async fn mcp_tools_as_dynamic(
@@ -30,8 +108,7 @@ pub async fn mcp_tools_as_dynamic(
             let mcp = mcp_manager.clone();
             let call_name = tool_name.clone();
 
-            // Prefix the tool ID with "mcp_" to avoid collisions with built-in tools
-            let id = format!("mcp_{}", tool_name);
+            let id = mcp_tool_id(&server_name, &tool_name);
             let display_name = format!("{} ({})", tool_def.name, server_name);
 
             let callback: ToolCallback = Arc::new(move |params: serde_json::Value| {
@@ -43,28 +120,13 @@ pub async fn mcp_tools_as_dynamic(
                     drop(manager);
 
                     match result {
-                        Ok(mcp_result) => {
-                            let output = if let Some(ref val) = mcp_result.result {
-                                serde_json::to_string_pretty(val)
-                                    .unwrap_or_else(|_| format!("{:?}", val))
-                            } else if let Some(ref err) = mcp_result.error {
-                                err.clone()
-                            } else {
-                                "Tool executed successfully".to_string()
-                            };
-
-                            Ok(ToolResult {
-                                success: mcp_result.success,
-                                output,
-                                data: mcp_result.result.unwrap_or(serde_json::json!({})),
-                                error: mcp_result.error,
-                            })
-                        }
+                        Ok(mcp_result) => Ok(to_tool_result(mcp_result)),
                         Err(e) => Ok(ToolResult {
                             success: false,
                             output: format!("MCP tool call failed: {}", e),
                             data: serde_json::json!({}),
                             error: Some(e.to_string()),
+                            structured: None,
                         }),
                     }
                 })
@@ -74,7 +136,7 @@ pub async fn mcp_tools_as_dynamic(
                 id,
                 name: display_name,
                 description: tool_def.description.clone(),
-                parameters_schema: tool_def.input_schema.clone(),
+                parameters_schema: to_parameters_schema(&tool_def.input_schema),
                 callback,
             }
         })
@@ -82,15 +144,56 @@ pub async fn mcp_tools_as_dynamic(
 }
 
 /// Register all MCP tools into an agent ToolRegistry.
-/// Call this after MCP servers are connected and before starting a chat session.
+///
+/// Previously bridged tools are removed first, so calling this again after
+/// servers connect, disconnect or refresh leaves exactly the current set.
 pub async fn register_mcp_tools(
-    registry: &mut shodh_rag::agent::ToolRegistry,
+    registry: &shodh_rag::agent::ToolRegistry,
     mcp_manager: Arc<RwLock<MCPManager>>,
 ) {
     let defs = mcp_tools_as_dynamic(mcp_manager).await;
     let count = defs.len();
+    registry.unregister_prefix(MCP_TOOL_PREFIX);
     shodh_rag::agent::register_dynamic_tools(registry, defs);
     if count > 0 {
         tracing::info!(count, "Registered MCP tools into agent ToolRegistry");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mcp_tool_id_is_namespaced_and_sanitized() {
+        assert_eq!(mcp_tool_id("github", "create_issue"), "mcp__github__create_issue");
+        assert_eq!(mcp_tool_id("my server", "fs.read"), "mcp__my_server__fs_read");
+    }
+
+    #[test]
+    fn test_to_parameters_schema_defaults_to_object() {
+        assert_eq!(
+            to_parameters_schema(&serde_json::Value::Null),
+            serde_json::json!({ "type": "object", "properties": {} })
+        );
+        let schema = to_parameters_schema(&serde_json::json!({ "properties": { "q": { "type": "string" } } }));
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["properties"]["q"]["type"], "string");
+    }
+
+    #[test]
+    fn test_to_tool_result_extracts_text_and_is_error() {
+        let result = to_tool_result(ToolCallResult {
+            success: true,
+            result: Some(serde_json::json!({
+                "content": [{ "type": "text", "text": "not found" }],
+                "isError": true
+            })),
+            error: None,
+            artifacts: Vec::new(),
+        });
+        assert!(!result.success);
+        assert_eq!(result.output, "not found");
+        assert_eq!(result.error.as_deref(), Some("not found"));
+    }
+}
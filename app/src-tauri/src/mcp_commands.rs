@@ -4,7 +4,7 @@ use crate::mcp::*;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 use tokio::sync::RwLock as AsyncRwLock;
 
 /// MCP State managed by Tauri
@@ -21,6 +21,10 @@ pub struct ToolInfo {
     pub server: String,
     pub category: Option<ToolCategory>,
     pub input_schema: Value,
+    /// Whether the owning server is currently connected (tools of
+    /// disconnected servers come from the discovery cache)
+    #[serde(default)]
+    pub connected: bool,
 }
 
 /// Server information for frontend
@@ -54,10 +58,24 @@ pub async fn mcp_connect_server(
         }
     }
 
+    let cached = state.registry.read().await
+        .cached_tools(&server_name)
+        .map(|c| c.tools.clone());
+    let had_cache = cached.is_some();
+
     // Connect
     let manager = state.manager.read().await;
-    manager.connect_server(config).await
+    let tools = manager.connect_server(config, cached).await
         .map_err(|e| format!("Failed to connect: {}", e))?;
+    drop(manager);
+
+    if !had_cache {
+        let mut registry = state.registry.write().await;
+        registry.cache_tools(&server_name, tools);
+        if let Err(e) = registry.save_tool_cache().await {
+            tracing::warn!("Failed to save MCP tool cache: {}", e);
+        }
+    }
 
     Ok(format!("Connected to {}", server_name))
 }
@@ -77,22 +95,79 @@ pub async fn mcp_disconnect_server(
     Ok(format!("Disconnected from {}", server_name))
 }
 
-/// List all available tools across all connected servers
+/// List known tools for every configured server.
+///
+/// Reads the discovery cache, so no server is contacted. With
+/// `refresh: true`, connected servers are re-discovered first and any
+/// added/removed tools are emitted as `mcp-tools-changed`.
 #[tauri::command]
 pub async fn mcp_list_tools(
+    app: AppHandle,
+    refresh: Option<bool>,
     state: State<'_, MCPState>,
 ) -> Result<Vec<ToolInfo>, String> {
+    let connected = state.manager.read().await.list_servers().await
+        .map_err(|e| format!("Failed to list servers: {}", e))?;
+
+    if refresh.unwrap_or(false) {
+        for server in &connected {
+            if let Err(e) = refresh_server_tools(&app, server, &state).await {
+                tracing::warn!("Failed to refresh tools for {}: {}", server, e);
+            }
+        }
+    }
+
+    let registry = state.registry.read().await;
+    let mut tools = Vec::new();
+    for config in registry.list() {
+        let Some(cached) = registry.cached_tools(&config.name) else { continue };
+        let is_connected = connected.contains(&config.name);
+        tools.extend(cached.tools.iter().map(|def| ToolInfo {
+            name: def.name.clone(),
+            description: def.description.clone(),
+            server: config.name.clone(),
+            category: def.category.clone(),
+            input_schema: def.input_schema.clone(),
+            connected: is_connected,
+        }));
+    }
+    Ok(tools)
+}
+
+/// Re-discover one connected server's tools and update the cache
+#[tauri::command]
+pub async fn mcp_refresh_tools(
+    app: AppHandle,
+    server_name: String,
+    state: State<'_, MCPState>,
+) -> Result<ToolDiff, String> {
+    refresh_server_tools(&app, &server_name, &state).await
+}
+
+async fn refresh_server_tools(
+    app: &AppHandle,
+    server_name: &str,
+    state: &MCPState,
+) -> Result<ToolDiff, String> {
     let manager = state.manager.read().await;
-    let tools = manager.list_tools().await
-        .map_err(|e| format!("Failed to list tools: {}", e))?;
+    let diff = manager.refresh_tools(server_name).await
+        .map_err(|e| format!("Failed to refresh tools: {}", e))?;
+    drop(manager);
 
-    Ok(tools.into_iter().map(|(name, server, def)| ToolInfo {
-        name,
-        description: def.description.clone(),
-        server,
-        category: def.category.clone(),
-        input_schema: def.input_schema.clone(),
-    }).collect())
+    let mut registry = state.registry.write().await;
+    registry.cache_tools(server_name, diff.tools.clone());
+    registry.save_tool_cache().await
+        .map_err(|e| format!("Failed to save tool cache: {}", e))?;
+    drop(registry);
+
+    if !diff.is_empty() {
+        let _ = app.emit("mcp-tools-changed", serde_json::json!({
+            "server": diff.server,
+            "added": diff.added,
+            "removed": diff.removed,
+        }));
+    }
+    Ok(diff)
 }
 
 /// Search for tools by query
@@ -119,6 +194,7 @@ pub async fn mcp_search_tools(
             server,
             category: def.category.clone(),
             input_schema: def.input_schema.clone(),
+            connected: true,
         }
     }).collect())
 }
@@ -143,6 +219,16 @@ pub async fn mcp_call_tool(
     }
 }
 
+/// Abandon all in-flight MCP tool calls (called when the user stops a chat)
+#[tauri::command]
+pub async fn mcp_cancel_all(
+    state: State<'_, MCPState>,
+) -> Result<(), String> {
+    let manager = state.manager.read().await;
+    manager.cancel_all();
+    Ok(())
+}
+
 /// List all configured servers
 #[tauri::command]
 pub async fn mcp_list_servers(
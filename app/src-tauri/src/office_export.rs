@@ -0,0 +1,1026 @@
+//! Markdown to native Word and PowerPoint files for document generation.
+//!
+//! Headings, lists, tables and code become real DOCX/PPTX elements rather than
+//! one block of text. ```` ```chart ```` blocks (the chat artifact format) turn
+//! into Word tables of their data and native PowerPoint charts.
+
+use docx_rs::*;
+use std::io::{Cursor, Write};
+use std::sync::LazyLock;
+
+static LINK_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"\[([^\]]+)\]\([^)\s]+\)").expect("link regex is valid")
+});
+static LIST_ITEM_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"^(\s*)([-*+]|\d+[.)])\s+(.*)$").expect("list item regex is valid")
+});
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Block {
+    Heading { level: usize, text: String },
+    Paragraph(String),
+    List { ordered: bool, items: Vec<ListItem> },
+    Table { headers: Vec<String>, rows: Vec<Vec<String>> },
+    Code { language: String, code: String },
+    Chart(ChartData),
+    Quote(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ListItem {
+    pub depth: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ChartData {
+    pub kind: String,
+    pub title: String,
+    pub labels: Vec<String>,
+    pub series: Vec<(String, Vec<f64>)>,
+}
+
+impl ChartData {
+    /// Chart.js-style JSON as produced for chart artifacts
+    fn from_json(json: &str) -> Option<Self> {
+        let value: serde_json::Value = serde_json::from_str(json.trim()).ok()?;
+        let data = value.get("data")?;
+        let labels = data
+            .get("labels")?
+            .as_array()?
+            .iter()
+            .map(|l| l.as_str().map(String::from).unwrap_or_else(|| l.to_string()))
+            .collect();
+        let series: Vec<(String, Vec<f64>)> = data
+            .get("datasets")?
+            .as_array()?
+            .iter()
+            .enumerate()
+            .map(|(i, dataset)| {
+                let name = dataset
+                    .get("label")
+                    .and_then(|l| l.as_str())
+                    .map(String::from)
+                    .unwrap_or_else(|| format!("Series {}", i + 1));
+                let values = dataset
+                    .get("data")
+                    .and_then(|d| d.as_array())
+                    .map(|d| d.iter().map(|v| v.as_f64().unwrap_or(0.0)).collect())
+                    .unwrap_or_default();
+                (name, values)
+            })
+            .collect();
+        if series.is_empty() {
+            return None;
+        }
+        Some(Self {
+            kind: value.get("type").and_then(|t| t.as_str()).unwrap_or("bar").to_string(),
+            title: value.get("title").and_then(|t| t.as_str()).unwrap_or("Chart").to_string(),
+            labels,
+            series,
+        })
+    }
+
+    /// The chart's data as a table: one row per label, one column per series
+    fn as_table(&self) -> (Vec<String>, Vec<Vec<String>>) {
+        let headers = std::iter::once(String::new())
+            .chain(self.series.iter().map(|(name, _)| name.clone()))
+            .collect();
+        let rows = self
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| {
+                std::iter::once(label.clone())
+                    .chain(self.series.iter().map(|(_, values)| {
+                        values.get(i).map(|v| format_number(*v)).unwrap_or_default()
+                    }))
+                    .collect()
+            })
+            .collect();
+        (headers, rows)
+    }
+}
+
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{}", value)
+    }
+}
+
+/// Split markdown into the block elements the exporters understand
+pub(crate) fn parse_markdown(markdown: &str) -> Vec<Block> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut i = 0;
+
+    let flush = |paragraph: &mut Vec<&str>, blocks: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(paragraph.join(" ")));
+            paragraph.clear();
+        }
+    };
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            flush(&mut paragraph, &mut blocks);
+            let fence = &trimmed[..3];
+            let language = trimmed[3..].trim().to_lowercase();
+            let end = (i + 1..lines.len())
+                .find(|&j| lines[j].trim().starts_with(fence))
+                .unwrap_or(lines.len());
+            let body = lines[i + 1..end].join("\n");
+            let block = match language.as_str() {
+                "chart" => ChartData::from_json(&body).map(Block::Chart),
+                "table" => parse_table(&lines[i + 1..end]),
+                _ => None,
+            };
+            blocks.push(block.unwrap_or(Block::Code { language, code: body }));
+            i = end + 1;
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+            i += 1;
+            continue;
+        }
+
+        let level = trimmed.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Heading { level, text: trimmed[level..].trim().to_string() });
+            i += 1;
+            continue;
+        }
+
+        if trimmed.starts_with('|') && lines.get(i + 1).is_some_and(|next| is_table_separator(next)) {
+            flush(&mut paragraph, &mut blocks);
+            let end = (i..lines.len())
+                .find(|&j| !lines[j].trim().starts_with('|'))
+                .unwrap_or(lines.len());
+            if let Some(table) = parse_table(&lines[i..end]) {
+                blocks.push(table);
+            }
+            i = end;
+            continue;
+        }
+
+        if LIST_ITEM_RE.is_match(line) {
+            flush(&mut paragraph, &mut blocks);
+            let mut items: Vec<ListItem> = Vec::new();
+            let mut ordered = None;
+            while i < lines.len() && !lines[i].trim().is_empty() {
+                if let Some(cap) = LIST_ITEM_RE.captures(lines[i]) {
+                    let is_ordered = cap[2].starts_with(|c: char| c.is_ascii_digit());
+                    ordered.get_or_insert(is_ordered);
+                    let depth = (cap[1].replace('\t', "  ").len() / 2).min(2);
+                    items.push(ListItem { depth, text: cap[3].trim().to_string() });
+                } else if let Some(last) = items.last_mut() {
+                    // Wrapped continuation of the previous item
+                    last.text.push(' ');
+                    last.text.push_str(lines[i].trim());
+                }
+                i += 1;
+            }
+            blocks.push(Block::List { ordered: ordered.unwrap_or(false), items });
+            continue;
+        }
+
+        if let Some(quote) = trimmed.strip_prefix('>') {
+            flush(&mut paragraph, &mut blocks);
+            let mut text = vec![quote.trim()];
+            i += 1;
+            while i < lines.len() {
+                match lines[i].trim().strip_prefix('>') {
+                    Some(more) => text.push(more.trim()),
+                    None => break,
+                }
+                i += 1;
+            }
+            blocks.push(Block::Quote(text.join(" ")));
+            continue;
+        }
+
+        if trimmed.len() >= 3 && trimmed.chars().all(|c| c == '-' || c == '*' || c == '_') {
+            // Horizontal rule
+            flush(&mut paragraph, &mut blocks);
+            i += 1;
+            continue;
+        }
+
+        paragraph.push(trimmed);
+        i += 1;
+    }
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+fn is_table_separator(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.starts_with('|')
+        && trimmed.contains('-')
+        && trimmed.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '))
+}
+
+fn table_cells(line: &str) -> Vec<String> {
+    let trimmed = line.trim().trim_start_matches('|');
+    let trimmed = trimmed.strip_suffix('|').unwrap_or(trimmed);
+    trimmed.split('|').map(|cell| cell.trim().to_string()).collect()
+}
+
+fn parse_table(lines: &[&str]) -> Option<Block> {
+    let mut rows = lines
+        .iter()
+        .filter(|line| line.trim().starts_with('|') && !is_table_separator(line))
+        .map(|line| table_cells(line));
+    let headers = rows.next()?;
+    let rows = rows
+        .map(|mut row| {
+            row.resize(headers.len(), String::new());
+            row
+        })
+        .collect();
+    Some(Block::Table { headers, rows })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Span {
+    text: String,
+    bold: bool,
+    italic: bool,
+    code: bool,
+}
+
+/// Inline `**bold**`, `*italic*` and `` `code` `` runs; links keep their text
+fn inline_spans(text: &str) -> Vec<Span> {
+    let text = LINK_RE.replace_all(text, "$1");
+    let mut spans = Vec::new();
+    let mut current = String::new();
+    let (mut bold, mut italic) = (false, false);
+    let mut rest = text.as_ref();
+
+    let push = |current: &mut String, bold: bool, italic: bool, code: bool, spans: &mut Vec<Span>| {
+        if !current.is_empty() {
+            spans.push(Span { text: std::mem::take(current), bold, italic, code });
+        }
+    };
+
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("**") {
+            push(&mut current, bold, italic, false, &mut spans);
+            bold = !bold;
+            rest = after;
+        } else if c == '`' {
+            match rest[1..].find('`') {
+                Some(end) => {
+                    push(&mut current, bold, italic, false, &mut spans);
+                    current.push_str(&rest[1..1 + end]);
+                    push(&mut current, bold, italic, true, &mut spans);
+                    rest = &rest[end + 2..];
+                }
+                None => {
+                    current.push(c);
+                    rest = &rest[1..];
+                }
+            }
+        } else if c == '*' && (italic || rest[1..].starts_with(|n: char| !n.is_whitespace())) {
+            push(&mut current, bold, italic, false, &mut spans);
+            italic = !italic;
+            rest = &rest[1..];
+        } else {
+            current.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    push(&mut current, bold, italic, false, &mut spans);
+    spans
+}
+
+fn plain_text(text: &str) -> String {
+    inline_spans(text).into_iter().map(|span| span.text).collect()
+}
+
+// ============================================================================
+// DOCX
+// ============================================================================
+
+const BULLET_NUMBERING: usize = 2;
+/// Twips of usable width on a Letter page with 1" margins
+const DOCX_TEXT_WIDTH: usize = 9360;
+const CODE_FONT: &str = "Consolas";
+
+fn docx_runs(paragraph: Paragraph, text: &str, size: usize) -> Paragraph {
+    inline_spans(text).into_iter().fold(paragraph, |paragraph, span| {
+        let mut run = Run::new().add_text(span.text).size(size);
+        if span.bold {
+            run = run.bold();
+        }
+        if span.italic {
+            run = run.italic();
+        }
+        if span.code {
+            run = run.fonts(RunFonts::new().ascii(CODE_FONT).hi_ansi(CODE_FONT));
+        }
+        paragraph.add_run(run)
+    })
+}
+
+fn docx_table(headers: &[String], rows: &[Vec<String>]) -> Table {
+    let cell = |text: &str, header: bool| {
+        let paragraph = if header {
+            Paragraph::new().add_run(Run::new().add_text(plain_text(text)).size(20).bold())
+        } else {
+            docx_runs(Paragraph::new(), text, 20)
+        };
+        TableCell::new().add_paragraph(paragraph)
+    };
+    let mut table_rows = vec![TableRow::new(headers.iter().map(|h| cell(h, true)).collect())];
+    table_rows.extend(rows.iter().map(|row| TableRow::new(row.iter().map(|c| cell(c, false)).collect())));
+    let columns = headers.len().max(1);
+    Table::new(table_rows).set_grid(vec![DOCX_TEXT_WIDTH / columns; columns])
+}
+
+fn list_numbering(id: usize, ordered: bool) -> AbstractNumbering {
+    (0..3).fold(AbstractNumbering::new(id), |numbering, level| {
+        let (format, text) = if ordered {
+            ("decimal", format!("%{}.", level + 1))
+        } else {
+            ("bullet", ["•", "◦", "▪"][level].to_string())
+        };
+        numbering.add_level(
+            Level::new(level, Start::new(1), NumberFormat::new(format), LevelText::new(text), LevelJc::new("left"))
+                .indent(Some(720 * (level as i32 + 1)), Some(SpecialIndentType::Hanging(360)), None, None),
+        )
+    })
+}
+
+/// Render markdown as a Word document with heading styles, numbered and
+/// bulleted lists, tables and monospaced code blocks
+pub(crate) fn markdown_to_docx(markdown: &str) -> Result<Vec<u8>, String> {
+    let mut docx = Docx::new()
+        .add_style(Style::new("Heading1", StyleType::Paragraph).name("heading 1").size(36).bold())
+        .add_style(Style::new("Heading2", StyleType::Paragraph).name("heading 2").size(30).bold())
+        .add_style(Style::new("Heading3", StyleType::Paragraph).name("heading 3").size(26).bold())
+        .add_style(Style::new("Heading4", StyleType::Paragraph).name("heading 4").size(24).bold().italic())
+        .add_abstract_numbering(list_numbering(BULLET_NUMBERING, false))
+        .add_numbering(Numbering::new(BULLET_NUMBERING, BULLET_NUMBERING));
+    // Each ordered list gets its own numbering so it restarts at 1
+    let mut next_numbering = BULLET_NUMBERING + 1;
+
+    for block in parse_markdown(markdown) {
+        match block {
+            Block::Heading { level, text } => {
+                let style = format!("Heading{}", level.min(4));
+                docx = docx.add_paragraph(Paragraph::new().style(&style).add_run(Run::new().add_text(plain_text(&text))));
+            }
+            Block::Paragraph(text) => {
+                docx = docx.add_paragraph(docx_runs(Paragraph::new(), &text, 22));
+            }
+            Block::Quote(text) => {
+                let paragraph = Paragraph::new()
+                    .indent(Some(720), None, None, None)
+                    .add_run(Run::new().add_text(plain_text(&text)).size(22).italic());
+                docx = docx.add_paragraph(paragraph);
+            }
+            Block::List { ordered, items } => {
+                let id = if ordered {
+                    let id = next_numbering;
+                    next_numbering += 1;
+                    docx = docx.add_abstract_numbering(list_numbering(id, true)).add_numbering(Numbering::new(id, id));
+                    id
+                } else {
+                    BULLET_NUMBERING
+                };
+                for item in items {
+                    let paragraph = Paragraph::new().numbering(NumberingId::new(id), IndentLevel::new(item.depth));
+                    docx = docx.add_paragraph(docx_runs(paragraph, &item.text, 22));
+                }
+            }
+            Block::Table { headers, rows } => {
+                docx = docx.add_table(docx_table(&headers, &rows)).add_paragraph(Paragraph::new());
+            }
+            Block::Chart(chart) => {
+                let (headers, rows) = chart.as_table();
+                docx = docx
+                    .add_paragraph(Paragraph::new().add_run(Run::new().add_text(&chart.title).size(22).bold()))
+                    .add_table(docx_table(&headers, &rows))
+                    .add_paragraph(Paragraph::new());
+            }
+            Block::Code { code, .. } => {
+                let mut run = Run::new().size(18).fonts(RunFonts::new().ascii(CODE_FONT).hi_ansi(CODE_FONT));
+                for (i, line) in code.lines().enumerate() {
+                    if i > 0 {
+                        run = run.add_break(BreakType::TextWrapping);
+                    }
+                    run = run.add_text(line);
+                }
+                docx = docx.add_paragraph(Paragraph::new().indent(Some(360), None, None, None).add_run(run));
+            }
+        }
+    }
+
+    let mut docx_bytes = Vec::new();
+    docx.build()
+        .pack(&mut Cursor::new(&mut docx_bytes))
+        .map_err(|e| format!("Failed to generate DOCX: {}", e))?;
+    Ok(docx_bytes)
+}
+
+// ============================================================================
+// PPTX
+// ============================================================================
+
+const NS_A: &str = "http://schemas.openxmlformats.org/drawingml/2006/main";
+const NS_R: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const NS_P: &str = "http://schemas.openxmlformats.org/presentationml/2006/main";
+const NS_C: &str = "http://schemas.openxmlformats.org/drawingml/2006/chart";
+const REL: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+/// 16:9 slide in EMU
+const SLIDE_WIDTH: i64 = 12_192_000;
+const SLIDE_HEIGHT: i64 = 6_858_000;
+const MARGIN: i64 = 457_200;
+const CONTENT_TOP: i64 = 1_371_600;
+const MAX_BODY_PARAGRAPHS: usize = 10;
+const MAX_TABLE_ROWS: usize = 12;
+const MAX_CODE_LINES: usize = 14;
+/// Medium Style 2 - Accent 1, built into PowerPoint
+const TABLE_STYLE: &str = "{5C22544A-7EE6-4342-B048-85BDC9FD1C3A}";
+
+#[derive(Debug, Clone, PartialEq)]
+enum BodyKind {
+    Text,
+    Subheading,
+    Bullet(usize),
+    Numbered(usize),
+    Code,
+}
+
+#[derive(Debug, Clone)]
+enum Visual {
+    Table { headers: Vec<String>, rows: Vec<Vec<String>> },
+    Chart(ChartData),
+}
+
+#[derive(Debug, Clone, Default)]
+struct Slide {
+    title: String,
+    body: Vec<(BodyKind, String)>,
+    visual: Option<Visual>,
+    is_title: bool,
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Group blocks into slides: the document title becomes a title slide and
+/// each top-level section heading starts a new slide. Tables and charts get
+/// a slide to themselves; long sections continue on "(cont.)" slides.
+fn build_slides(title: &str, blocks: Vec<Block>) -> Vec<Slide> {
+    let has_sections = blocks.iter().any(|b| matches!(b, Block::Heading { level: 2, .. }));
+    let slide_level = if has_sections { 2 } else { 1 };
+
+    let mut slides = vec![Slide { title: title.to_string(), is_title: true, ..Default::default() }];
+    let mut section = title.to_string();
+
+    let add_body = |slides: &mut Vec<Slide>, section: &str, kind: BodyKind, text: String| {
+        let full = slides.last().is_some_and(|s| {
+            let limit = if s.is_title { 3 } else { MAX_BODY_PARAGRAPHS };
+            s.visual.is_some() || s.body.len() >= limit
+        });
+        if full {
+            slides.push(Slide { title: continued(section), ..Default::default() });
+        }
+        if let Some(slide) = slides.last_mut() {
+            slide.body.push((kind, text));
+        }
+    };
+    let add_visual = |slides: &mut Vec<Slide>, section: &str, visual: Visual| match slides.last_mut() {
+        Some(slide) if !slide.is_title && slide.body.is_empty() && slide.visual.is_none() => {
+            slide.visual = Some(visual);
+        }
+        _ => slides.push(Slide { title: continued(section), visual: Some(visual), ..Default::default() }),
+    };
+
+    for block in blocks {
+        match block {
+            Block::Heading { level, text } if level < slide_level && slides.len() == 1 && slides[0].body.is_empty() => {
+                section = plain_text(&text);
+                slides[0].title = section.clone();
+            }
+            Block::Heading { level, text } if level <= slide_level => {
+                section = plain_text(&text);
+                slides.push(Slide { title: section.clone(), ..Default::default() });
+            }
+            Block::Heading { text, .. } => add_body(&mut slides, &section, BodyKind::Subheading, plain_text(&text)),
+            Block::Paragraph(text) | Block::Quote(text) => add_body(&mut slides, &section, BodyKind::Text, text),
+            Block::List { ordered, items } => {
+                for item in items {
+                    let kind = if ordered { BodyKind::Numbered(item.depth) } else { BodyKind::Bullet(item.depth) };
+                    add_body(&mut slides, &section, kind, item.text);
+                }
+            }
+            Block::Code { code, .. } => {
+                for line in code.lines().take(MAX_CODE_LINES) {
+                    add_body(&mut slides, &section, BodyKind::Code, line.to_string());
+                }
+            }
+            Block::Table { headers, rows } => {
+                for chunk in rows.chunks(MAX_TABLE_ROWS) {
+                    let visual = Visual::Table { headers: headers.clone(), rows: chunk.to_vec() };
+                    add_visual(&mut slides, &section, visual);
+                }
+            }
+            Block::Chart(chart) => {
+                let visual = if chart_xml(&chart).is_some() {
+                    Visual::Chart(chart)
+                } else {
+                    let (headers, rows) = chart.as_table();
+                    Visual::Table { headers, rows }
+                };
+                add_visual(&mut slides, &section, visual);
+            }
+        }
+    }
+    slides
+}
+
+fn continued(title: &str) -> String {
+    if title.ends_with("(cont.)") {
+        title.to_string()
+    } else {
+        format!("{} (cont.)", title)
+    }
+}
+
+fn pptx_runs(text: &str, size: usize, bold: bool) -> String {
+    inline_spans(text)
+        .into_iter()
+        .map(|span| {
+            let mut attrs = format!(r#"lang="en-US" sz="{}""#, size);
+            if span.bold || bold {
+                attrs.push_str(r#" b="1""#);
+            }
+            if span.italic {
+                attrs.push_str(r#" i="1""#);
+            }
+            let font = if span.code { format!(r#"<a:latin typeface="{}"/>"#, CODE_FONT) } else { String::new() };
+            format!(r#"<a:r><a:rPr {}>{}</a:rPr><a:t>{}</a:t></a:r>"#, attrs, font, xml_escape(&span.text))
+        })
+        .collect()
+}
+
+fn text_shape(id: usize, name: &str, (x, y, cx, cy): (i64, i64, i64, i64), paragraphs: &str) -> String {
+    format!(
+        r#"<p:sp><p:nvSpPr><p:cNvPr id="{id}" name="{name}"/><p:cNvSpPr txBox="1"/><p:nvPr/></p:nvSpPr><p:spPr><a:xfrm><a:off x="{x}" y="{y}"/><a:ext cx="{cx}" cy="{cy}"/></a:xfrm><a:prstGeom prst="rect"><a:avLst/></a:prstGeom><a:noFill/></p:spPr><p:txBody><a:bodyPr wrap="square" rtlCol="0"><a:normAutofit/></a:bodyPr><a:lstStyle/>{paragraphs}</p:txBody></p:sp>"#
+    )
+}
+
+fn body_paragraph(kind: &BodyKind, text: &str) -> String {
+    let bullet = |depth: &usize, marker: &str| {
+        let left = 342_900 * (*depth as i64 + 1);
+        format!(r#"<a:pPr marL="{}" indent="-342900">{}</a:pPr>"#, left, marker)
+    };
+    match kind {
+        BodyKind::Text => format!(r#"<a:p><a:pPr><a:spcBef><a:spcPts val="600"/></a:spcBef></a:pPr>{}</a:p>"#, pptx_runs(text, 1800, false)),
+        BodyKind::Subheading => format!(
+            r#"<a:p><a:pPr><a:spcBef><a:spcPts val="1200"/></a:spcBef></a:pPr><a:r><a:rPr lang="en-US" sz="2000" b="1"/><a:t>{}</a:t></a:r></a:p>"#,
+            xml_escape(text)
+        ),
+        BodyKind::Bullet(depth) => format!(
+            "<a:p>{}{}</a:p>",
+            bullet(depth, r#"<a:buFont typeface="Arial"/><a:buChar char="&#8226;"/>"#),
+            pptx_runs(text, if *depth == 0 { 1800 } else { 1600 }, false)
+        ),
+        BodyKind::Numbered(depth) => format!(
+            "<a:p>{}{}</a:p>",
+            bullet(depth, r#"<a:buFont typeface="+mj-lt"/><a:buAutoNum type="arabicPeriod"/>"#),
+            pptx_runs(text, if *depth == 0 { 1800 } else { 1600 }, false)
+        ),
+        BodyKind::Code => format!(
+            r#"<a:p><a:r><a:rPr lang="en-US" sz="1400"><a:latin typeface="{}"/></a:rPr><a:t>{}</a:t></a:r></a:p>"#,
+            CODE_FONT,
+            xml_escape(if text.is_empty() { " " } else { text })
+        ),
+    }
+}
+
+fn table_frame(id: usize, headers: &[String], rows: &[Vec<String>]) -> String {
+    let width = SLIDE_WIDTH - 2 * MARGIN;
+    let columns = headers.len().max(1) as i64;
+    let row_height = 370_840;
+    let grid: String = (0..columns).map(|_| format!(r#"<a:gridCol w="{}"/>"#, width / columns)).collect();
+    let cell = |text: &str, header: bool| {
+        let runs = if header {
+            format!(r#"<a:r><a:rPr lang="en-US" sz="1400" b="1"/><a:t>{}</a:t></a:r>"#, xml_escape(&plain_text(text)))
+        } else {
+            pptx_runs(text, 1200, false)
+        };
+        format!(r#"<a:tc><a:txBody><a:bodyPr/><a:lstStyle/><a:p>{}</a:p></a:txBody><a:tcPr/></a:tc>"#, runs)
+    };
+    let row = |cells: &[String], header: bool| {
+        format!(
+            r#"<a:tr h="{}">{}</a:tr>"#,
+            row_height,
+            cells.iter().map(|c| cell(c, header)).collect::<String>()
+        )
+    };
+    let body: String = std::iter::once(row(headers, true)).chain(rows.iter().map(|r| row(r, false))).collect();
+    format!(
+        r#"<p:graphicFrame><p:nvGraphicFramePr><p:cNvPr id="{id}" name="Table {id}"/><p:cNvGraphicFramePr><a:graphicFrameLocks noGrp="1"/></p:cNvGraphicFramePr><p:nvPr/></p:nvGraphicFramePr><p:xfrm><a:off x="{MARGIN}" y="{CONTENT_TOP}"/><a:ext cx="{width}" cy="{height}"/></p:xfrm><a:graphic><a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/table"><a:tbl><a:tblPr firstRow="1" bandRow="1"><a:tableStyleId>{TABLE_STYLE}</a:tableStyleId></a:tblPr><a:tblGrid>{grid}</a:tblGrid>{body}</a:tbl></a:graphicData></a:graphic></p:graphicFrame>"#,
+        height = row_height * (rows.len() as i64 + 1),
+    )
+}
+
+fn chart_frame(id: usize, rel_id: &str) -> String {
+    let width = SLIDE_WIDTH - 2 * MARGIN;
+    let height = SLIDE_HEIGHT - CONTENT_TOP - MARGIN;
+    format!(
+        r#"<p:graphicFrame><p:nvGraphicFramePr><p:cNvPr id="{id}" name="Chart {id}"/><p:cNvGraphicFramePr/><p:nvPr/></p:nvGraphicFramePr><p:xfrm><a:off x="{MARGIN}" y="{CONTENT_TOP}"/><a:ext cx="{width}" cy="{height}"/></p:xfrm><a:graphic><a:graphicData uri="{NS_C}"><c:chart xmlns:c="{NS_C}" r:id="{rel_id}"/></a:graphicData></a:graphic></p:graphicFrame>"#
+    )
+}
+
+/// Chart part XML, or `None` for chart types PowerPoint cannot show from
+/// labels and series alone (scatter, bubble)
+fn chart_xml(chart: &ChartData) -> Option<String> {
+    let (element, extra, has_axes) = match chart.kind.as_str() {
+        "bar" => ("barChart", r#"<c:barDir val="col"/><c:grouping val="clustered"/>"#, true),
+        "line" => ("lineChart", r#"<c:grouping val="standard"/>"#, true),
+        "area" => ("areaChart", r#"<c:grouping val="standard"/>"#, true),
+        "radar" => ("radarChart", r#"<c:radarStyle val="marker"/>"#, true),
+        "pie" => ("pieChart", "", false),
+        "doughnut" => ("doughnutChart", "", false),
+        _ => return None,
+    };
+    let vary_colors = if has_axes { "0" } else { "1" };
+
+    let categories: String = chart
+        .labels
+        .iter()
+        .enumerate()
+        .map(|(i, label)| format!(r#"<c:pt idx="{}"><c:v>{}</c:v></c:pt>"#, i, xml_escape(label)))
+        .collect();
+    let series: String = chart
+        .series
+        .iter()
+        .enumerate()
+        .map(|(i, (name, values))| {
+            let points: String = values
+                .iter()
+                .enumerate()
+                .map(|(j, v)| format!(r#"<c:pt idx="{}"><c:v>{}</c:v></c:pt>"#, j, v))
+                .collect();
+            format!(
+                r#"<c:ser><c:idx val="{i}"/><c:order val="{i}"/><c:tx><c:v>{name}</c:v></c:tx><c:cat><c:strLit><c:ptCount val="{labels}"/>{categories}</c:strLit></c:cat><c:val><c:numLit><c:formatCode>General</c:formatCode><c:ptCount val="{count}"/>{points}</c:numLit></c:val></c:ser>"#,
+                name = xml_escape(name),
+                labels = chart.labels.len(),
+                count = values.len(),
+            )
+        })
+        .collect();
+    let (axis_ids, axes, tail) = if has_axes {
+        (
+            r#"<c:axId val="500000001"/><c:axId val="500000002"/>"#,
+            r#"<c:catAx><c:axId val="500000001"/><c:scaling><c:orientation val="minMax"/></c:scaling><c:delete val="0"/><c:axPos val="b"/><c:crossAx val="500000002"/></c:catAx><c:valAx><c:axId val="500000002"/><c:scaling><c:orientation val="minMax"/></c:scaling><c:delete val="0"/><c:axPos val="l"/><c:majorGridlines/><c:crossAx val="500000001"/></c:valAx>"#,
+            "",
+        )
+    } else if element == "doughnutChart" {
+        ("", "", r#"<c:firstSliceAng val="0"/><c:holeSize val="50"/>"#)
+    } else {
+        ("", "", r#"<c:firstSliceAng val="0"/>"#)
+    };
+
+    Some(format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<c:chartSpace xmlns:c="{NS_C}" xmlns:a="{NS_A}" xmlns:r="{NS_R}"><c:chart><c:title><c:tx><c:rich><a:bodyPr/><a:lstStyle/><a:p><a:r><a:rPr lang="en-US"/><a:t>{title}</a:t></a:r></a:p></c:rich></c:tx><c:overlay val="0"/></c:title><c:autoTitleDeleted val="0"/><c:plotArea><c:layout/><c:{element}>{extra}<c:varyColors val="{vary_colors}"/>{series}{tail}{axis_ids}</c:{element}>{axes}</c:plotArea><c:legend><c:legendPos val="b"/><c:overlay val="0"/></c:legend><c:plotVisOnly val="1"/></c:chart></c:chartSpace>"#,
+        title = xml_escape(&chart.title),
+    ))
+}
+
+fn group_shape_header() -> &'static str {
+    r#"<p:nvGrpSpPr><p:cNvPr id="1" name=""/><p:cNvGrpSpPr/><p:nvPr/></p:nvGrpSpPr><p:grpSpPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="0" cy="0"/><a:chOff x="0" y="0"/><a:chExt cx="0" cy="0"/></a:xfrm></p:grpSpPr>"#
+}
+
+fn slide_xml(slide: &Slide, chart_rel: Option<&str>) -> String {
+    let width = SLIDE_WIDTH - 2 * MARGIN;
+    let mut shapes = String::new();
+    if slide.is_title {
+        let title = format!(r#"<a:p><a:pPr algn="ctr"/>{}</a:p>"#, pptx_runs(&slide.title, 4000, true));
+        shapes.push_str(&text_shape(2, "Title", (MARGIN, 2_057_400, width, 1_371_600), &title));
+        if !slide.body.is_empty() {
+            let subtitle: String = slide
+                .body
+                .iter()
+                .take(3)
+                .map(|(_, text)| format!(r#"<a:p><a:pPr algn="ctr"/>{}</a:p>"#, pptx_runs(text, 1600, false)))
+                .collect();
+            shapes.push_str(&text_shape(3, "Subtitle", (MARGIN, 3_566_160, width, 1_371_600), &subtitle));
+        }
+    } else {
+        let title = format!("<a:p>{}</a:p>", pptx_runs(&slide.title, 3200, true));
+        shapes.push_str(&text_shape(2, "Title", (MARGIN, 274_320, width, 1_005_840), &title));
+        match (&slide.visual, chart_rel) {
+            (Some(Visual::Chart(_)), Some(rel_id)) => shapes.push_str(&chart_frame(3, rel_id)),
+            (Some(Visual::Table { headers, rows }), _) => shapes.push_str(&table_frame(3, headers, rows)),
+            _ if !slide.body.is_empty() => {
+                let paragraphs: String = slide.body.iter().map(|(kind, text)| body_paragraph(kind, text)).collect();
+                let height = SLIDE_HEIGHT - CONTENT_TOP - MARGIN;
+                shapes.push_str(&text_shape(3, "Content", (MARGIN, CONTENT_TOP, width, height), &paragraphs));
+            }
+            _ => {}
+        }
+    }
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<p:sld xmlns:a="{NS_A}" xmlns:r="{NS_R}" xmlns:p="{NS_P}"><p:cSld><p:spTree>{}{}</p:spTree></p:cSld><p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sld>"#,
+        group_shape_header(),
+        shapes
+    )
+}
+
+fn rels_xml(relationships: &[(String, &str, String)]) -> String {
+    let body: String = relationships
+        .iter()
+        .map(|(id, kind, target)| format!(r#"<Relationship Id="{}" Type="{}/{}" Target="{}"/>"#, id, REL, kind, target))
+        .collect();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{}</Relationships>"#,
+        body
+    )
+}
+
+/// Render markdown as a 16:9 presentation: a title slide, one slide per
+/// top-level section, and native tables and charts on slides of their own
+pub(crate) fn markdown_to_pptx(title: &str, markdown: &str) -> Result<Vec<u8>, String> {
+    let slides = build_slides(title, parse_markdown(markdown));
+
+    let mut parts: Vec<(String, String)> = Vec::new();
+    let mut overrides = vec![
+        ("/ppt/presentation.xml".to_string(), "application/vnd.openxmlformats-officedocument.presentationml.presentation.main+xml"),
+        ("/ppt/presProps.xml".to_string(), "application/vnd.openxmlformats-officedocument.presentationml.presProps+xml"),
+        ("/ppt/tableStyles.xml".to_string(), "application/vnd.openxmlformats-officedocument.presentationml.tableStyles+xml"),
+        ("/ppt/slideMasters/slideMaster1.xml".to_string(), "application/vnd.openxmlformats-officedocument.presentationml.slideMaster+xml"),
+        ("/ppt/slideLayouts/slideLayout1.xml".to_string(), "application/vnd.openxmlformats-officedocument.presentationml.slideLayout+xml"),
+        ("/ppt/theme/theme1.xml".to_string(), "application/vnd.openxmlformats-officedocument.theme+xml"),
+        ("/docProps/core.xml".to_string(), "application/vnd.openxmlformats-package.core-properties+xml"),
+        ("/docProps/app.xml".to_string(), "application/vnd.openxmlformats-officedocument.extended-properties+xml"),
+    ];
+
+    let mut presentation_rels = vec![
+        ("rId1".to_string(), "slideMaster", "slideMasters/slideMaster1.xml".to_string()),
+        ("rId2".to_string(), "theme", "theme/theme1.xml".to_string()),
+        ("rId3".to_string(), "presProps", "presProps.xml".to_string()),
+        ("rId4".to_string(), "tableStyles", "tableStyles.xml".to_string()),
+    ];
+    let mut slide_ids = String::new();
+    let mut charts = 0;
+
+    for (i, slide) in slides.iter().enumerate() {
+        let number = i + 1;
+        let mut rels = vec![("rId1".to_string(), "slideLayout", "../slideLayouts/slideLayout1.xml".to_string())];
+        let chart_rel = match &slide.visual {
+            Some(Visual::Chart(chart)) => chart_xml(chart).map(|xml| {
+                charts += 1;
+                parts.push((format!("ppt/charts/chart{}.xml", charts), xml));
+                overrides.push((
+                    format!("/ppt/charts/chart{}.xml", charts),
+                    "application/vnd.openxmlformats-officedocument.drawingml.chart+xml",
+                ));
+                rels.push(("rId2".to_string(), "chart", format!("../charts/chart{}.xml", charts)));
+                "rId2"
+            }),
+            _ => None,
+        };
+        parts.push((format!("ppt/slides/slide{}.xml", number), slide_xml(slide, chart_rel)));
+        parts.push((format!("ppt/slides/_rels/slide{}.xml.rels", number), rels_xml(&rels)));
+        overrides.push((
+            format!("/ppt/slides/slide{}.xml", number),
+            "application/vnd.openxmlformats-officedocument.presentationml.slide+xml",
+        ));
+
+        let rel_id = format!("rId{}", presentation_rels.len() + 1);
+        slide_ids.push_str(&format!(r#"<p:sldId id="{}" r:id="{}"/>"#, 255 + number, rel_id));
+        presentation_rels.push((rel_id, "slide", format!("slides/slide{}.xml", number)));
+    }
+
+    parts.push((
+        "ppt/presentation.xml".to_string(),
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<p:presentation xmlns:a="{NS_A}" xmlns:r="{NS_R}" xmlns:p="{NS_P}" saveSubsetFonts="1"><p:sldMasterIdLst><p:sldMasterId id="2147483648" r:id="rId1"/></p:sldMasterIdLst><p:sldIdLst>{slide_ids}</p:sldIdLst><p:sldSz cx="{SLIDE_WIDTH}" cy="{SLIDE_HEIGHT}"/><p:notesSz cx="6858000" cy="9144000"/></p:presentation>"#
+        ),
+    ));
+    parts.push(("ppt/_rels/presentation.xml.rels".to_string(), rels_xml(&presentation_rels)));
+    parts.push((
+        "ppt/presProps.xml".to_string(),
+        format!(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<p:presentationPr xmlns:a="{NS_A}" xmlns:r="{NS_R}" xmlns:p="{NS_P}"/>"#),
+    ));
+    parts.push((
+        "ppt/tableStyles.xml".to_string(),
+        format!(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<a:tblStyleLst xmlns:a="{NS_A}" def="{TABLE_STYLE}"/>"#),
+    ));
+    parts.push((
+        "ppt/slideMasters/slideMaster1.xml".to_string(),
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<p:sldMaster xmlns:a="{NS_A}" xmlns:r="{NS_R}" xmlns:p="{NS_P}"><p:cSld><p:bg><p:bgRef idx="1001"><a:schemeClr val="bg1"/></p:bgRef></p:bg><p:spTree>{}</p:spTree></p:cSld><p:clrMap bg1="lt1" tx1="dk1" bg2="lt2" tx2="dk2" accent1="accent1" accent2="accent2" accent3="accent3" accent4="accent4" accent5="accent5" accent6="accent6" hlink="hlink" folHlink="folHlink"/><p:sldLayoutIdLst><p:sldLayoutId id="2147483649" r:id="rId1"/></p:sldLayoutIdLst></p:sldMaster>"#,
+            group_shape_header()
+        ),
+    ));
+    parts.push((
+        "ppt/slideMasters/_rels/slideMaster1.xml.rels".to_string(),
+        rels_xml(&[
+            ("rId1".to_string(), "slideLayout", "../slideLayouts/slideLayout1.xml".to_string()),
+            ("rId2".to_string(), "theme", "../theme/theme1.xml".to_string()),
+        ]),
+    ));
+    parts.push((
+        "ppt/slideLayouts/slideLayout1.xml".to_string(),
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<p:sldLayout xmlns:a="{NS_A}" xmlns:r="{NS_R}" xmlns:p="{NS_P}" type="blank" preserve="1"><p:cSld name="Blank"><p:spTree>{}</p:spTree></p:cSld><p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sldLayout>"#,
+            group_shape_header()
+        ),
+    ));
+    parts.push((
+        "ppt/slideLayouts/_rels/slideLayout1.xml.rels".to_string(),
+        rels_xml(&[("rId1".to_string(), "slideMaster", "../slideMasters/slideMaster1.xml".to_string())]),
+    ));
+    parts.push(("ppt/theme/theme1.xml".to_string(), theme_xml()));
+
+    let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+    parts.push((
+        "docProps/core.xml".to_string(),
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"><dc:title>{}</dc:title><dc:creator>Shodh</dc:creator><dcterms:created xsi:type="dcterms:W3CDTF">{now}</dcterms:created><dcterms:modified xsi:type="dcterms:W3CDTF">{now}</dcterms:modified></cp:coreProperties>"#,
+            xml_escape(&slides[0].title)
+        ),
+    ));
+    parts.push((
+        "docProps/app.xml".to_string(),
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/extended-properties"><Application>Shodh</Application><Slides>{}</Slides></Properties>"#,
+            slides.len()
+        ),
+    ));
+    parts.push((
+        "_rels/.rels".to_string(),
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="{REL}/officeDocument" Target="ppt/presentation.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/><Relationship Id="rId3" Type="{REL}/extended-properties" Target="docProps/app.xml"/></Relationships>"#
+        ),
+    ));
+
+    let override_xml: String = overrides
+        .iter()
+        .map(|(part, content_type)| format!(r#"<Override PartName="{}" ContentType="{}"/>"#, part, content_type))
+        .collect();
+    let content_types = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/>{}</Types>"#,
+        override_xml
+    );
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let write_error = |e: &dyn std::fmt::Display| format!("Failed to generate PPTX: {}", e);
+    // Content types first, as Office expects
+    for (name, content) in std::iter::once(("[Content_Types].xml".to_string(), content_types)).chain(parts) {
+        zip.start_file(name, options).map_err(|e| write_error(&e))?;
+        zip.write_all(content.as_bytes()).map_err(|e| write_error(&e))?;
+    }
+    Ok(zip.finish().map_err(|e| write_error(&e))?.into_inner())
+}
+
+fn theme_xml() -> String {
+    let colors = [
+        ("dk1", r#"<a:sysClr val="windowText" lastClr="000000"/>"#),
+        ("lt1", r#"<a:sysClr val="window" lastClr="FFFFFF"/>"#),
+        ("dk2", r#"<a:srgbClr val="1F2937"/>"#),
+        ("lt2", r#"<a:srgbClr val="F3F4F6"/>"#),
+        ("accent1", r#"<a:srgbClr val="2563EB"/>"#),
+        ("accent2", r#"<a:srgbClr val="16A34A"/>"#),
+        ("accent3", r#"<a:srgbClr val="F59E0B"/>"#),
+        ("accent4", r#"<a:srgbClr val="DC2626"/>"#),
+        ("accent5", r#"<a:srgbClr val="7C3AED"/>"#),
+        ("accent6", r#"<a:srgbClr val="0891B2"/>"#),
+        ("hlink", r#"<a:srgbClr val="2563EB"/>"#),
+        ("folHlink", r#"<a:srgbClr val="7C3AED"/>"#),
+    ];
+    let color_scheme: String = colors.iter().map(|(name, color)| format!("<a:{name}>{color}</a:{name}>")).collect();
+    let solid = r#"<a:solidFill><a:schemeClr val="phClr"/></a:solidFill>"#;
+    let line = |width: u32| format!(r#"<a:ln w="{width}"><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:ln>"#);
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<a:theme xmlns:a="{NS_A}" name="Shodh"><a:themeElements><a:clrScheme name="Shodh">{color_scheme}</a:clrScheme><a:fontScheme name="Shodh"><a:majorFont><a:latin typeface="Calibri"/><a:ea typeface=""/><a:cs typeface=""/></a:majorFont><a:minorFont><a:latin typeface="Calibri"/><a:ea typeface=""/><a:cs typeface=""/></a:minorFont></a:fontScheme><a:fmtScheme name="Shodh"><a:fillStyleLst>{solid}{solid}{solid}</a:fillStyleLst><a:lnStyleLst>{l1}{l2}{l3}</a:lnStyleLst><a:effectStyleLst><a:effectStyle><a:effectLst/></a:effectStyle><a:effectStyle><a:effectLst/></a:effectStyle><a:effectStyle><a:effectLst/></a:effectStyle></a:effectStyleLst><a:bgFillStyleLst>{solid}{solid}{solid}</a:bgFillStyleLst></a:fmtScheme></a:themeElements></a:theme>"#,
+        l1 = line(6350),
+        l2 = line(12700),
+        l3 = line(19050),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::io::Read;
+
+    const SAMPLE: &str = "# Quarterly Review\n\n**Date:** May 1, 2025\n\n## Summary\n\nRevenue grew **12%** [Source 1].\n\n\
+        - First point\n  - Nested *detail*\n- Second point\n\n1. Step one\n2. Step two\n\n\
+        | Region | Revenue |\n|---|---|\n| North | 10 |\n| South | 7 |\n\n## Data\n\n\
+        ```chart\n{\"type\": \"bar\", \"title\": \"Revenue\", \"data\": {\"labels\": [\"Q1\", \"Q2\"], \"datasets\": [{\"label\": \"2025\", \"data\": [3, 5]}]}}\n```\n\n\
+        ```rust\nfn main() {}\n```\n";
+
+    /// Every part named in `[Content_Types].xml` and every internal
+    /// relationship target must exist, or Office offers to repair the file
+    fn assert_package_consistent(bytes: &[u8]) -> HashSet<String> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let names: HashSet<String> = archive.file_names().map(String::from).collect();
+        let read = |archive: &mut zip::ZipArchive<Cursor<&[u8]>>, name: &str| {
+            let mut xml = String::new();
+            archive.by_name(name).unwrap().read_to_string(&mut xml).unwrap();
+            xml
+        };
+
+        let content_types = read(&mut archive, "[Content_Types].xml");
+        let part_re = regex::Regex::new(r#"PartName="/([^"]+)""#).unwrap();
+        for cap in part_re.captures_iter(&content_types) {
+            assert!(names.contains(&cap[1]), "content type for missing part {}", &cap[1]);
+        }
+
+        let target_re = regex::Regex::new(r#"Target="([^"]+)"(?: TargetMode="(\w+)")?"#).unwrap();
+        for rels in names.iter().filter(|n| n.ends_with(".rels")) {
+            let base = rels.replace("_rels/", "").trim_end_matches(".rels").to_string();
+            let dir = base.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+            for cap in target_re.captures_iter(&read(&mut archive, rels)) {
+                if cap.get(2).is_some_and(|mode| mode.as_str() == "External") {
+                    continue;
+                }
+                let absolute = cap[1].starts_with('/');
+                let mut path: Vec<&str> = if absolute || dir.is_empty() { vec![] } else { dir.split('/').collect() };
+                for segment in cap[1].trim_start_matches('/').split('/') {
+                    match segment {
+                        ".." => {
+                            path.pop();
+                        }
+                        segment => path.push(segment),
+                    }
+                }
+                let target = path.join("/");
+                assert!(names.contains(&target), "{} points at missing part {}", rels, target);
+            }
+        }
+        names
+    }
+
+    #[test]
+    fn test_docx_opens_without_repair() {
+        let bytes = markdown_to_docx(SAMPLE).unwrap();
+        assert_package_consistent(&bytes);
+        // The reader rejects malformed parts, which Word would offer to repair
+        assert!(docx_rs::read_docx(&bytes).is_ok());
+    }
+
+    #[test]
+    fn test_pptx_has_slide_per_section_and_native_chart() {
+        let slides = build_slides("Fallback", parse_markdown(SAMPLE));
+        assert_eq!(
+            slides.iter().map(|s| s.title.as_str()).collect::<Vec<_>>(),
+            vec!["Quarterly Review", "Summary", "Summary (cont.)", "Data", "Data (cont.)"]
+        );
+        assert_eq!(slides[0].body.len(), 1);
+        assert_eq!(slides[1].body[2], (BodyKind::Bullet(1), "Nested *detail*".to_string()));
+        assert!(matches!(slides[2].visual, Some(Visual::Table { ref rows, .. }) if rows.len() == 2));
+        assert!(matches!(slides[3].visual, Some(Visual::Chart(_))));
+
+        let names = assert_package_consistent(&markdown_to_pptx("Fallback", SAMPLE).unwrap());
+        assert!(names.contains("ppt/slides/slide5.xml") && !names.contains("ppt/slides/slide6.xml"));
+        assert!(names.contains("ppt/charts/chart1.xml"));
+    }
+}
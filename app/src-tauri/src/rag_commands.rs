@@ -1,13 +1,15 @@
 //! Tauri commands for RAG operations
 
 use shodh_rag::comprehensive_system::{
-    ComprehensiveRAG, Citation, DocumentFormat
+    ComprehensiveRAG, ComprehensiveResult, Citation, DocumentFormat
 };
-use shodh_rag::types::MetadataFilter;
+use shodh_rag::types::{DocumentSort, MetadataFilter, RerankOptions};
+use shodh_rag::{ReembedProgress, ReembedScope};
 use shodh_rag::agent::ConversationManager;
 use shodh_rag::memory::MemorySystem;
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::fs;
@@ -76,6 +78,10 @@ pub struct SearchRequest {
     pub max_results: usize,
     pub space_id: Option<String>,
     pub filters: Option<HashMap<String, String>>,
+    /// Cross-encoder rescoring of fused candidates (default on when the model is loaded)
+    pub cross_encoder_rerank: Option<bool>,
+    /// Limit cross-encoder reranking to this many top candidates
+    pub rerank_top_k: Option<usize>,
 }
 
 /// Search result to frontend with enhanced citation tracking
@@ -231,6 +237,17 @@ pub async fn initialize_rag(state: State<'_, RagState>) -> Result<String, String
                         tracing::info!("  Agents will need to be loaded manually");
                     }
 
+                    // Restore user-created crews now that their agents are registered
+                    let crews_dir = crate::agent_commands::crews_dir(&state);
+                    if crews_dir.exists() {
+                        let agent_system = assistant.get_agent_system();
+                        let system = agent_system.read().await;
+                        match system.load_crews_from_directory(&crews_dir.to_string_lossy()).await {
+                            Ok(loaded_ids) => tracing::info!("✓ Loaded {} crews", loaded_ids.len()),
+                            Err(e) => tracing::info!("⚠ Failed to load crews from directory: {}", e),
+                        }
+                    }
+
                     // Get the agent_system from PersonalAssistant and sync it to RagState
                     // Share the same Arc reference instead of cloning
                     let agent_system_arc = assistant.get_agent_system();
@@ -350,6 +367,10 @@ pub async fn search_documents(
             date_from: None,
             date_to: None,
             custom: None,
+            author: None,
+            year_from: None,
+            year_to: None,
+            language: None,
         };
 
         let mut custom_fields: HashMap<String, String> = HashMap::new();
@@ -372,6 +393,18 @@ pub async fn search_documents(
                 "date_to" => {
                     metadata_filter.date_to = value.parse::<i64>().ok();
                 },
+                "author" => {
+                    metadata_filter.author = Some(value);
+                },
+                "year_from" => {
+                    metadata_filter.year_from = value.parse::<i32>().ok();
+                },
+                "year_to" => {
+                    metadata_filter.year_to = value.parse::<i32>().ok();
+                },
+                "language" => {
+                    metadata_filter.language = Some(value);
+                },
                 _ => {
                     custom_fields.insert(key, value);
                 }
@@ -389,7 +422,11 @@ pub async fn search_documents(
 
     // Perform comprehensive search
     tracing::info!("Performing local document search...");
-    let results = rag.search_comprehensive(&request.query, request.max_results, filter)
+    let rerank = RerankOptions {
+        cross_encoder: request.cross_encoder_rerank.unwrap_or(true),
+        top_k: request.rerank_top_k,
+    };
+    let results = rag.search_comprehensive_with(&request.query, request.max_results, filter, rerank)
         .await
         .map_err(|e| {
             tracing::info!("Search failed with error: {}", e);
@@ -425,48 +462,7 @@ pub async fn search_documents(
             tracing::info!("  Snippet length: {}", r.snippet.len());
             tracing::info!("  Metadata keys: {:?}", r.metadata.keys().collect::<Vec<_>>());
 
-            // Extract source file from metadata
-            let source_file = r.metadata.get("file_path")
-                .or_else(|| r.metadata.get("source"))
-                .cloned()
-                .unwrap_or_else(|| r.citation.source.clone());
-
-            // Extract page number
-            let page_number = r.metadata.get("page_number")
-                .or_else(|| r.metadata.get("page"))
-                .and_then(|p| p.parse::<u32>().ok());
-
-            // Extract line range
-            let line_range = r.metadata.get("line_start")
-                .and_then(|start| start.parse::<u32>().ok())
-                .and_then(|start| {
-                    r.metadata.get("line_end")
-                        .and_then(|end| end.parse::<u32>().ok())
-                        .map(|end| (start, end))
-                });
-
-            // Get surrounding context (200 chars before/after)
-            let full_text = r.metadata.get("full_text")
-                .or_else(|| r.metadata.get("content"))
-                .cloned()
-                .unwrap_or_else(|| r.snippet.clone());
-
-            let snippet_pos = full_text.find(&r.snippet).unwrap_or(0);
-            let context_start = snippet_pos.saturating_sub(200);
-            let context_end = (snippet_pos + r.snippet.len() + 200).min(full_text.len());
-            let surrounding_context = full_text[context_start..context_end].to_string();
-
-            SearchResult {
-                id: r.id.to_string(),
-                score: r.score,
-                snippet: r.snippet.clone(),
-                citation: r.citation.clone(),
-                metadata: r.metadata.clone(),
-                source_file,
-                page_number,
-                line_range,
-                surrounding_context,
-            }
+            search_result_from(&r)
         })
         .collect();
 
@@ -493,6 +489,96 @@ pub async fn search_documents(
     })
 }
 
+/// Convert an engine result to the frontend shape with citation details
+fn search_result_from(r: &ComprehensiveResult) -> SearchResult {
+    // Extract source file from metadata
+    let source_file = r.metadata.get("file_path")
+        .or_else(|| r.metadata.get("source"))
+        .cloned()
+        .unwrap_or_else(|| r.citation.source.clone());
+
+    // Extract page number
+    let page_number = r.metadata.get("page_number")
+        .or_else(|| r.metadata.get("page"))
+        .and_then(|p| p.parse::<u32>().ok());
+
+    // Extract line range
+    let line_range = r.metadata.get("line_start")
+        .and_then(|start| start.parse::<u32>().ok())
+        .and_then(|start| {
+            r.metadata.get("line_end")
+                .and_then(|end| end.parse::<u32>().ok())
+                .map(|end| (start, end))
+        });
+
+    // Get surrounding context (200 chars before/after)
+    let full_text = r.metadata.get("full_text")
+        .or_else(|| r.metadata.get("content"))
+        .cloned()
+        .unwrap_or_else(|| r.snippet.clone());
+
+    let snippet_pos = full_text.find(&r.snippet).unwrap_or(0);
+    let context_start = snippet_pos.saturating_sub(200);
+    let context_end = (snippet_pos + r.snippet.len() + 200).min(full_text.len());
+    let surrounding_context = full_text[context_start..context_end].to_string();
+
+    SearchResult {
+        id: r.id.to_string(),
+        score: r.score,
+        snippet: r.snippet.clone(),
+        citation: r.citation.clone(),
+        metadata: r.metadata.clone(),
+        source_file,
+        page_number,
+        line_range,
+        surrounding_context,
+    }
+}
+
+/// Document listing entry: `SearchResult` without a relevance score
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilteredDocument {
+    pub id: String,
+    pub snippet: String,
+    pub citation: Citation,
+    pub metadata: HashMap<String, String>,
+    pub source_file: String,
+    pub page_number: Option<u32>,
+    pub line_range: Option<(u32, u32)>,
+    pub surrounding_context: String,
+}
+
+/// List documents by metadata alone, skipping the embedding model.
+/// Sorted newest first unless `sort_by` is `"title"`; `limit` defaults to 100.
+#[tauri::command]
+pub async fn filter_documents(
+    filter: MetadataFilter,
+    sort_by: Option<DocumentSort>,
+    limit: Option<usize>,
+    state: State<'_, RagState>,
+) -> Result<Vec<FilteredDocument>, String> {
+    let rag_guard = state.rag.read().await;
+    let results = rag_guard
+        .filter_documents(&filter, sort_by.unwrap_or_default(), limit.unwrap_or(100))
+        .await
+        .map_err(|e| format!("Filter failed: {}", e))?;
+
+    Ok(results
+        .iter()
+        .map(|r| {
+            let SearchResult {
+                id, snippet, citation, metadata, source_file, page_number, line_range,
+                surrounding_context, ..
+            } = search_result_from(r);
+            FilteredDocument {
+                id, snippet, citation, metadata, source_file, page_number, line_range,
+                surrounding_context,
+            }
+        })
+        .collect())
+}
+
 /// Add a document
 #[tauri::command]
 pub async fn add_document(
@@ -661,6 +747,162 @@ pub async fn get_statistics(state: State<'_, RagState>) -> Result<HashMap<String
     Ok(result)
 }
 
+const SEARCH_CONFIG_FILE: &str = "search_config.json";
+
+/// Search tuning saved by `set_search_config`, if present and valid
+pub fn load_search_config(data_dir: &std::path::Path) -> Option<shodh_rag::config::SearchConfig> {
+    let path = data_dir.join(SEARCH_CONFIG_FILE);
+    let json = fs::read_to_string(&path).ok()?;
+    match serde_json::from_str::<shodh_rag::config::SearchConfig>(&json) {
+        Ok(config) if config.validate().is_ok() => Some(config),
+        Ok(_) | Err(_) => {
+            tracing::warn!("Ignoring invalid search config at {:?}", path);
+            None
+        }
+    }
+}
+
+/// Current retrieval tuning: RRF k, vector/text fusion weights, thresholds
+#[tauri::command]
+pub async fn get_search_config(state: State<'_, RagState>) -> Result<shodh_rag::config::SearchConfig, String> {
+    Ok(state.rag.read().await.config().search.clone())
+}
+
+/// Apply and persist retrieval tuning. Defaults (rrf_k 60, vector_weight
+/// and text_weight 1.0) reproduce unweighted fusion.
+#[tauri::command]
+pub async fn set_search_config(
+    config: shodh_rag::config::SearchConfig,
+    state: State<'_, RagState>,
+) -> Result<shodh_rag::config::SearchConfig, String> {
+    state.rag.write().await
+        .set_search_config(config.clone())
+        .map_err(|e| e.to_string())?;
+
+    let json = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+    fs::write(state.app_paths.data_dir.join(SEARCH_CONFIG_FILE), json)
+        .map_err(|e| format!("Failed to save search config: {}", e))?;
+    Ok(config)
+}
+
+/// Labeled-query evaluation of the live index
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetrievalEvalReport {
+    /// Aggregate metrics; `per_query` holds each query's `QueryMetrics`
+    pub metrics: shodh_rag::rag::EvalMetrics,
+    /// Plain-text summary table
+    pub report: String,
+}
+
+/// Re-embed one space's chunks with the current embedding model, emitting
+/// `reembed_progress` events. Resumes an interrupted run for the same space.
+#[tauri::command]
+pub async fn reembed_space(
+    app: tauri::AppHandle,
+    space_id: String,
+    state: State<'_, RagState>,
+) -> Result<ReembedProgress, String> {
+    run_reembed(app, &state, ReembedScope::Space { space_id }).await
+}
+
+/// Re-embed every chunk with the current embedding model, e.g. after
+/// switching to a model with a different dimension
+#[tauri::command]
+pub async fn reembed_all(
+    app: tauri::AppHandle,
+    state: State<'_, RagState>,
+) -> Result<ReembedProgress, String> {
+    run_reembed(app, &state, ReembedScope::All).await
+}
+
+/// Set while a re-embedding run is in progress; runs share one staging table
+static REEMBED_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Documents embedded per read-lock acquisition during re-embedding
+const REEMBED_BATCH_DOCUMENTS: usize = 8;
+
+async fn run_reembed(
+    app: tauri::AppHandle,
+    state: &RagState,
+    scope: ReembedScope,
+) -> Result<ReembedProgress, String> {
+    use tauri::Emitter;
+
+    if REEMBED_RUNNING.swap(true, Ordering::SeqCst) {
+        return Err("A re-embedding run is already in progress".to_string());
+    }
+    let on_progress = move |progress: &ReembedProgress| {
+        let _ = app.emit("reembed_progress", progress);
+    };
+    let result = async {
+        // Embed under read locks, a few documents at a time, so searches and
+        // indexing keep running; only the swap needs the write lock
+        let mut run = state.rag.read().await.reembed_start(scope, &on_progress).await?;
+        while !state.rag.read().await
+            .reembed_batch(&mut run, REEMBED_BATCH_DOCUMENTS, &on_progress)
+            .await?
+        {}
+        state.rag.write().await.reembed_swap(run, &on_progress).await
+    }
+    .await
+    .map_err(|e| format!("Re-embedding failed: {:#}", e));
+    REEMBED_RUNNING.store(false, Ordering::SeqCst);
+    result
+}
+
+/// Run a labeled query set through `search_comprehensive` and score the
+/// rankings. `queries_json` is a JSON array of `EvalQuery`, whose relevant
+/// ids may be source paths, content hashes or doc ids; chunks are collapsed
+/// to their document before scoring. `k_values` defaults to 1, 3, 5 and 10.
+#[tauri::command]
+pub async fn evaluate_retrieval(
+    queries_json: String,
+    k_values: Option<Vec<usize>>,
+    space_id: Option<String>,
+    state: State<'_, RagState>,
+) -> Result<RetrievalEvalReport, String> {
+    use shodh_rag::rag::{evaluate, format_report, rank_documents, EvalQuery, EvalResult};
+
+    let queries: Vec<EvalQuery> = serde_json::from_str(&queries_json)
+        .map_err(|e| format!("Invalid eval queries: {}", e))?;
+    if queries.is_empty() {
+        return Err("No eval queries provided".to_string());
+    }
+    let k_values = k_values
+        .filter(|ks| !ks.is_empty() && ks.iter().all(|&k| k > 0))
+        .unwrap_or_else(|| vec![1, 3, 5, 10]);
+    let depth = k_values.iter().copied().max().unwrap_or(10);
+    let filter = space_id.map(|sid| MetadataFilter {
+        space_id: Some(sid),
+        ..Default::default()
+    });
+
+    let rag_guard = state.rag.read().await;
+    let mut rankings: HashMap<String, Vec<EvalResult>> = HashMap::new();
+    for q in &queries {
+        // Over-fetch so collapsing chunks still leaves `depth` documents
+        let results = rag_guard
+            .search_comprehensive(&q.query, depth * 3, filter.clone())
+            .await
+            .map_err(|e| format!("Search failed for '{}': {}", q.query, e))?;
+
+        let ranked = rank_documents(q, results.iter().map(|r| (&r.metadata, r.score)), depth);
+        rankings.insert(q.query.clone(), ranked);
+    }
+    drop(rag_guard);
+
+    let metrics = evaluate(&queries, &k_values, |query| {
+        rankings.get(query).cloned().unwrap_or_default()
+    });
+    tracing::info!(queries = metrics.num_queries, mrr = metrics.mrr, "Retrieval evaluation complete");
+
+    Ok(RetrievalEvalReport {
+        report: format_report(&metrics),
+        metrics,
+    })
+}
+
 /// Clear all data
 #[tauri::command]
 pub async fn clear_all_data(state: State<'_, RagState>) -> Result<String, String> {
@@ -972,7 +1214,7 @@ pub async fn list_space_documents(
 /// Helper function to check if a file is supported
 fn is_supported_file(filename: &str) -> bool {
     let supported_extensions = vec![
-        "txt", "md", "pdf", "docx", "doc", "rtf",
+        "txt", "md", "pdf", "docx", "doc", "rtf", "epub",
         "py", "js", "rs", "java", "cpp", "c", "h",
         "json", "xml", "yaml", "yml", "toml",
         "png", "jpg", "jpeg", "gif", "bmp", "svg", "webp", "tiff", "tif"
@@ -1038,7 +1280,7 @@ pub async fn link_folder(
                     // Include code files and documentation
                     if matches!(ext_str.as_str(), 
                         // Documents
-                        "txt" | "md" | "pdf" | "html" | "json" | "csv" | "docx" | "rst" | "tex" |
+                        "txt" | "md" | "pdf" | "html" | "json" | "csv" | "docx" | "epub" | "rst" | "tex" |
                         // Code files
                         "rs" | "py" | "js" | "ts" | "jsx" | "tsx" | "java" | "cpp" | "c" | "h" | 
                         "hpp" | "cs" | "go" | "rb" | "php" | "swift" | "kt" | "scala" | "r" |
@@ -1231,7 +1473,7 @@ pub async fn get_folder_stats(
                 file_count += 1;
                 if let Some(ext) = path.extension() {
                     let ext_str = ext.to_string_lossy().to_lowercase();
-                    if matches!(ext_str.as_str(), "txt" | "md" | "pdf" | "html" | "json" | "csv" | "docx") {
+                    if matches!(ext_str.as_str(), "txt" | "md" | "pdf" | "html" | "json" | "csv" | "docx" | "epub") {
                         supported_count += 1;
                     }
                 }
@@ -1300,6 +1542,7 @@ pub async fn get_daily_brief(
             "docx" | "doc" => "Word",
             "xlsx" | "xls" | "csv" => "Spreadsheet",
             "pptx" | "ppt" => "Presentation",
+            "epub" => "Ebook",
             "html" | "htm" => "HTML",
             "json" | "yaml" | "toml" => "Data",
             "rs" | "py" | "js" | "ts" | "go" | "java" | "c" | "cpp" => "Code",
@@ -1381,6 +1624,7 @@ pub async fn get_knowledge_map(
             "docx" | "doc" => ("#3b82f6", "Word"),
             "xlsx" | "xls" | "csv" => ("#10b981", "Spreadsheet"),
             "pptx" | "ppt" => ("#f97316", "Presentation"),
+            "epub" => ("#a855f7", "Ebook"),
             "html" | "htm" => ("#06b6d4", "HTML"),
             _ => ("#fbbf24", "Other"),
         };
@@ -1582,6 +1826,10 @@ pub async fn read_original_file(
         date_from: None,
         date_to: None,
         custom: None,
+        author: None,
+        year_from: None,
+        year_to: None,
+        language: None,
     };
 
     let results = rag.list_documents(Some(filter), 10000)
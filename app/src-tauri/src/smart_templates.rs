@@ -2,6 +2,6 @@
 //! All business logic lives in the backend library.
 
 pub use shodh_rag::templates::{
-    DocumentTemplate, OutputFormat, TemplateExtractionRequest,
-    TemplateExtractor, TemplateGenerationRequest,
+    DocumentTemplate, OutputFormat, PlaceholderType, TemplateExtractionRequest,
+    TemplateExtractor, TemplateGenerationRequest, TemplateInputError,
 };
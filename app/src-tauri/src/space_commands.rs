@@ -5,7 +5,10 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tauri::State;
 use crate::rag_commands::RagState;
+use crate::space_manager::SpaceManager;
 use shodh_rag::comprehensive_system::{Citation, DocumentFormat};
+use shodh_rag::llm::LLMOverrides;
+use shodh_rag::space_crypto::{SpaceKey, CHECK_METADATA_KEY, SALT_METADATA_KEY};
 use uuid::Uuid;
 use chrono::Utc;
 
@@ -23,6 +26,9 @@ pub struct Space {
     pub is_shared: bool,
     pub folder_path: Option<String>,
     pub watching_changes: bool,
+    /// Chunk text and metadata are encrypted at rest
+    #[serde(default)]
+    pub encrypted: bool,
 }
 
 impl From<shodh_rag::space::Space> for Space {
@@ -37,6 +43,7 @@ impl From<shodh_rag::space::Space> for Space {
             is_shared: s.is_shared,
             folder_path: s.folder_path,
             watching_changes: s.watching_changes,
+            encrypted: s.metadata.contains_key(SALT_METADATA_KEY),
         }
     }
 }
@@ -136,6 +143,7 @@ pub async fn get_spaces(state: State<'_, RagState>) -> Result<Vec<Space>, String
             watching_changes: metadata.get("watching_changes")
                 .and_then(|v| v.parse().ok())
                 .unwrap_or(false),
+            encrypted: false,
         });
     }
 
@@ -295,6 +303,7 @@ pub async fn delete_space_with_docs(
             }
         }
     }
+    rag_guard.forget_space(&space_id);
 
     drop(rag_guard);
 
@@ -407,3 +416,139 @@ pub async fn get_space_system_prompt(
 
     Ok(prompt)
 }
+
+/// Space metadata key holding the JSON-encoded `LLMOverrides`
+const LLM_SETTINGS_KEY: &str = "llm_settings";
+
+/// LLM overrides stored on a space, if any
+pub(crate) fn space_llm_settings(state: &RagState, space_id: &str) -> Option<LLMOverrides> {
+    let raw = state.space_manager.lock().ok()?.get_space_metadata(space_id, LLM_SETTINGS_KEY)?;
+    serde_json::from_str(&raw)
+        .map_err(|e| tracing::warn!(space_id = %space_id, error = %e, "Ignoring malformed space LLM settings"))
+        .ok()
+}
+
+/// Set per-space model/temperature/top_p/max_tokens. Fields left unset use
+/// the global LLM config; an empty override clears the space's settings.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_space_llm_settings(
+    state: State<'_, RagState>,
+    space_id: String,
+    settings: LLMOverrides,
+) -> Result<(), String> {
+    settings.validate().map_err(|e| format!("Invalid LLM settings: {}", e))?;
+    let space_manager = state.space_manager.lock()
+        .map_err(|e| format!("Lock failed: {}", e))?;
+
+    if settings.is_empty() {
+        space_manager.remove_space_metadata(&space_id, LLM_SETTINGS_KEY)
+            .map_err(|e| format!("Failed to clear LLM settings: {}", e))?;
+    } else {
+        let value = serde_json::to_string(&settings)
+            .map_err(|e| format!("Failed to serialize LLM settings: {}", e))?;
+        space_manager.set_space_metadata(&space_id, LLM_SETTINGS_KEY, &value)
+            .map_err(|e| format!("Failed to set LLM settings: {}", e))?;
+    }
+
+    Ok(())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_space_llm_settings(
+    state: State<'_, RagState>,
+    space_id: String,
+) -> Result<LLMOverrides, String> {
+    Ok(space_llm_settings(&state, &space_id).unwrap_or_default())
+}
+
+/// Encrypt a space at rest with a key derived from `passphrase`. Only the
+/// salt and an encrypted check value are stored; the passphrase never is.
+/// The space stays unlocked until `lock_space` or restart.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn enable_space_encryption(
+    state: State<'_, RagState>,
+    space_id: String,
+    passphrase: String,
+) -> Result<usize, String> {
+    let (key, salt, check) = tokio::task::spawn_blocking(move || SpaceKey::create(&passphrase))
+        .await
+        .map_err(|e| format!("Key derivation task failed: {}", e))?
+        .map_err(|e| e.to_string())?;
+
+    // Save the salt and check value before anything is sealed, so the space
+    // can always be unlocked again; take them back out if sealing fails
+    {
+        let space_manager = state.space_manager.lock()
+            .map_err(|e| format!("Lock failed: {}", e))?;
+        if let Err(e) = space_manager.set_space_metadata(&space_id, SALT_METADATA_KEY, &salt)
+            .and_then(|_| space_manager.set_space_metadata(&space_id, CHECK_METADATA_KEY, &check))
+        {
+            clear_encryption_metadata(&space_manager, &space_id);
+            return Err(format!("Failed to save encryption settings: {}", e));
+        }
+    }
+
+    let encrypted = match state.rag.write().await.encrypt_space(&space_id, key).await {
+        Ok(encrypted) => encrypted,
+        Err(e) => {
+            if let Ok(space_manager) = state.space_manager.lock() {
+                clear_encryption_metadata(&space_manager, &space_id);
+            }
+            return Err(format!("Failed to encrypt space: {}", e));
+        }
+    };
+
+    tracing::info!("🔒 Encrypted space {} ({} chunks)", space_id, encrypted);
+    Ok(encrypted)
+}
+
+fn clear_encryption_metadata(space_manager: &SpaceManager, space_id: &str) {
+    for key in [SALT_METADATA_KEY, CHECK_METADATA_KEY] {
+        if let Err(e) = space_manager.remove_space_metadata(space_id, key) {
+            tracing::error!(space_id = %space_id, error = %e, "Failed to clear encryption settings");
+        }
+    }
+}
+
+/// Derive the space key from `passphrase` and keep it in memory
+#[tauri::command(rename_all = "camelCase")]
+pub async fn unlock_space(
+    state: State<'_, RagState>,
+    space_id: String,
+    passphrase: String,
+) -> Result<(), String> {
+    let (salt, check) = {
+        let space_manager = state.space_manager.lock()
+            .map_err(|e| format!("Lock failed: {}", e))?;
+        match (
+            space_manager.get_space_metadata(&space_id, SALT_METADATA_KEY),
+            space_manager.get_space_metadata(&space_id, CHECK_METADATA_KEY),
+        ) {
+            (Some(salt), Some(check)) => (salt, check),
+            _ => return Err(format!("Space {} is not encrypted", space_id)),
+        }
+    };
+
+    // Argon2 is deliberately slow; keep it off the async runtime
+    let key = tokio::task::spawn_blocking(move || SpaceKey::unlock(&passphrase, &salt, &check))
+        .await
+        .map_err(|e| format!("Unlock task failed: {}", e))?
+        .map_err(|e| e.to_string())?;
+
+    state.rag.write().await.unlock_space(&space_id, key);
+    tracing::info!("🔓 Unlocked space {}", space_id);
+    Ok(())
+}
+
+/// Forget the space key; searches in the space fail until it is unlocked again
+#[tauri::command(rename_all = "camelCase")]
+pub async fn lock_space(
+    state: State<'_, RagState>,
+    space_id: String,
+) -> Result<(), String> {
+    if !state.rag.write().await.lock_space(&space_id) {
+        return Err(format!("Space {} is not encrypted", space_id));
+    }
+    tracing::info!("🔒 Locked space {}", space_id);
+    Ok(())
+}
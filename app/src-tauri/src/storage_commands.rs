@@ -1,8 +1,11 @@
 //! Storage management commands for proper document lifecycle
 //! Ensures consistency between spaces and documents
 
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 use crate::rag_commands::RagState;
+use crate::database_commands::remove_orphaned_chunks;
+use shodh_rag::dedup::{DedupeOutcome, DuplicateReport};
+use shodh_rag::storage::CompactionReport;
 use crate::space_manager::SpaceManager;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -177,6 +180,42 @@ pub async fn delete_documents_batch(
     Ok(total_deleted)
 }
 
+/// Find documents with identical content across all spaces
+#[tauri::command]
+pub async fn find_duplicate_documents(
+    state: State<'_, RagState>,
+) -> Result<DuplicateReport, String> {
+    let rag = state.rag.read().await;
+    let report = rag.find_duplicate_documents()
+        .await
+        .map_err(|e| format!("Duplicate scan failed: {}", e))?;
+    tracing::info!(
+        "🔍 Found {} duplicate documents in {} clusters ({} bytes reclaimable)",
+        report.duplicate_documents, report.clusters.len(), report.total_reclaimable_bytes
+    );
+    Ok(report)
+}
+
+/// Keep one copy of a duplicated document and delete the others
+#[tauri::command(rename_all = "camelCase")]
+pub async fn dedupe_documents(
+    state: State<'_, RagState>,
+    space_manager: State<'_, SpaceManager>,
+    keep_id: String,
+    remove_ids: Vec<String>,
+) -> Result<DedupeOutcome, String> {
+    let outcome = {
+        let mut rag = state.rag.write().await;
+        rag.dedupe_documents(&keep_id, &remove_ids)
+            .await
+            .map_err(|e| format!("Deduplication failed: {}", e))?
+    };
+
+    update_space_counts(&state, &space_manager).await?;
+
+    Ok(outcome)
+}
+
 /// Clear all documents from a space
 #[tauri::command]
 pub async fn clear_space_documents(
@@ -206,25 +245,80 @@ pub async fn clear_space_documents(
     Ok(deleted)
 }
 
-/// Optimize storage by triggering index creation if needed
+/// Outcome of `optimize_storage`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizeReport {
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    pub bytes_reclaimed: u64,
+    pub orphaned_spaces_removed: usize,
+    pub orphaned_chunks_removed: usize,
+    /// Net drop in data fragments (fragments merged away minus fragments written)
+    pub fragments_reduced: i64,
+    pub compaction: CompactionReport,
+    pub total_chunks: usize,
+    pub duration_ms: u64,
+}
+
+fn emit_optimize_progress(app: &AppHandle, stage: &str, message: &str) {
+    tracing::info!("🧹 Optimize storage [{}]: {}", stage, message);
+    let _ = app.emit("storage_optimize_progress", serde_json::json!({
+        "stage": stage,
+        "message": message,
+    }));
+}
+
+/// Remove orphaned chunks, compact and vacuum the store, and report what was
+/// reclaimed. Progress is emitted as `storage_optimize_progress` events.
 #[tauri::command]
 pub async fn optimize_storage(
+    app: AppHandle,
     state: State<'_, RagState>,
-) -> Result<String, String> {
-    tracing::info!("Optimizing storage...");
-
-    let rag_guard = state.rag.read().await;
-    let rag = &*rag_guard;
-
-    // Trigger index optimization
-    rag.optimize()
-        .await
-        .map_err(|e| format!("Failed to optimize: {}", e))?;
+) -> Result<OptimizeReport, String> {
+    let started = std::time::Instant::now();
+    let data_dir = state.app_paths.data_dir.clone();
+    let bytes_before = get_dir_size(&data_dir);
+
+    emit_optimize_progress(&app, "orphans", "Removing chunks from deleted spaces");
+    let (orphaned_spaces_removed, orphaned_chunks_removed) = remove_orphaned_chunks(&state).await?;
+
+    emit_optimize_progress(&app, "vacuum", "Compacting store and pruning old versions");
+    let (compaction, total_chunks) = {
+        let rag_guard = state.rag.read().await;
+        let compaction = rag_guard.vacuum()
+            .await
+            .map_err(|e| format!("Failed to optimize: {}", e))?;
+        let total_chunks = rag_guard.get_statistics().await.unwrap_or_default()
+            .get("total_chunks")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0);
+        (compaction, total_chunks)
+    };
 
-    let stats = rag.get_statistics().await.unwrap_or_default();
-    let total_chunks = stats.get("total_chunks").cloned().unwrap_or_default();
+    let bytes_after = get_dir_size(&data_dir);
+    let report = OptimizeReport {
+        bytes_before,
+        bytes_after,
+        bytes_reclaimed: bytes_before.saturating_sub(bytes_after),
+        orphaned_spaces_removed,
+        orphaned_chunks_removed,
+        fragments_reduced: compaction.fragments_removed as i64 - compaction.fragments_added as i64,
+        compaction,
+        total_chunks,
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
 
-    Ok(format!("Storage optimized. Total chunks: {}", total_chunks))
+    emit_optimize_progress(
+        &app,
+        "done",
+        &format!(
+            "Reclaimed {:.1} MB, removed {} orphaned chunks",
+            report.bytes_reclaimed as f64 / (1024.0 * 1024.0),
+            report.orphaned_chunks_removed
+        ),
+    );
+    Ok(report)
 }
 
 /// Create a backup of the current database
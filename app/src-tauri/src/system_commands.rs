@@ -1,35 +1,51 @@
 //! Thin Tauri wrapper for backend system operations
 //! Just bridges frontend ↔ backend, all logic is in shodh_rag::system
 
-use tauri::command;
+use tauri::{command, State};
 use serde::{Deserialize, Serialize};
 use shodh_rag::system::{
-    file_ops::*, command_executor::*, os_integration::*
+    file_ops::*, command_executor::*, os_integration::*, command_policy::*
 };
 
 /// Execute file system action
 #[command]
 pub async fn execute_file_action(action: FileSystemAction) -> Result<FileSystemResult, String> {
     match action {
-        FileSystemAction::CreateFolders { base_path, structure } => {
-            create_folder_structure(&base_path, &structure)
-                .map_err(|e| e.to_string())
+        FileSystemAction::CreateFolders { base_path, structure, dry_run } => {
+            if dry_run {
+                preview_folder_structure(&base_path, &structure)
+            } else {
+                create_folder_structure(&base_path, &structure)
+            }
+            .map_err(|e| e.to_string())
         }
         FileSystemAction::CreateFile { path, content, overwrite } => {
             create_file(&path, &content, overwrite)
                 .map_err(|e| e.to_string())
         }
-        FileSystemAction::Copy { source, destination } => {
-            copy_path(&source, &destination)
-                .map_err(|e| e.to_string())
+        FileSystemAction::Copy { source, destination, dry_run } => {
+            if dry_run {
+                preview_copy(&source, &destination)
+            } else {
+                copy_path(&source, &destination)
+            }
+            .map_err(|e| e.to_string())
         }
-        FileSystemAction::Move { source, destination } => {
-            move_path(&source, &destination)
-                .map_err(|e| e.to_string())
+        FileSystemAction::Move { source, destination, dry_run } => {
+            if dry_run {
+                preview_move(&source, &destination)
+            } else {
+                move_path(&source, &destination)
+            }
+            .map_err(|e| e.to_string())
         }
-        FileSystemAction::Delete { path, recursive } => {
-            delete_path(&path, recursive)
-                .map_err(|e| e.to_string())
+        FileSystemAction::Delete { path, recursive, dry_run } => {
+            if dry_run {
+                preview_delete(&path, recursive)
+            } else {
+                delete_path(&path, recursive)
+            }
+            .map_err(|e| e.to_string())
         }
         FileSystemAction::ListDirectory { path, recursive } => {
             list_directory(&path, recursive)
@@ -38,11 +54,53 @@ pub async fn execute_file_action(action: FileSystemAction) -> Result<FileSystemR
     }
 }
 
-/// Execute command (PowerShell/Bash/System)
+/// Classify a command and issue a confirmation token if it needs one
 #[command]
-pub async fn execute_command_action(action: CommandAction) -> Result<CommandResult, String> {
-    execute_command(&action)
-        .map_err(|e| e.to_string())
+pub async fn preview_command_action(
+    gate: State<'_, CommandGate>,
+    action: CommandAction,
+) -> Result<CommandPreview, String> {
+    Ok(gate.preview(&action))
+}
+
+/// Execute command (PowerShell/Bash/System). Commands at or above the risk
+/// threshold are refused unless a token from `preview_command_action` is passed.
+#[command]
+pub async fn execute_command_action(
+    gate: State<'_, CommandGate>,
+    action: CommandAction,
+    confirmation_token: Option<String>,
+) -> Result<CommandResult, String> {
+    let (risk, confirmed) = gate
+        .authorize(&action, confirmation_token.as_deref())
+        .map_err(|e| e.to_string())?;
+
+    let result = execute_command(&action);
+    let outcome = match &result {
+        Ok(r) => r.message.clone(),
+        Err(e) => format!("Error: {}", e),
+    };
+    gate.record(command_text(&action), risk, confirmed, true, outcome);
+
+    result.map_err(|e| e.to_string())
+}
+
+/// Set the lowest risk level that requires confirmation
+#[command]
+pub async fn set_command_risk_threshold(
+    gate: State<'_, CommandGate>,
+    threshold: CommandRiskLevel,
+) -> Result<(), String> {
+    gate.set_threshold(threshold);
+    Ok(())
+}
+
+/// Get the command execution audit trail
+#[command]
+pub async fn get_command_audit_log(
+    gate: State<'_, CommandGate>,
+) -> Result<Vec<CommandAuditEntry>, String> {
+    Ok(gate.get_audit_log())
 }
 
 /// Open path in file manager
@@ -63,9 +121,22 @@ pub async fn get_system_information() -> Result<SystemInfo, String> {
         .map_err(|e| e.to_string())
 }
 
-/// List running processes
+/// List running processes, optionally filtered by name/pid, sorted and capped
 #[command]
-pub async fn get_running_processes() -> Result<Vec<ProcessInfo>, String> {
-    list_running_processes()
+pub async fn get_running_processes(
+    filter: Option<String>,
+    sort_by: Option<ProcessSort>,
+    limit: Option<usize>,
+) -> Result<Vec<ProcessInfo>, String> {
+    let query = ProcessQuery {
+        filter,
+        sort_by: sort_by.unwrap_or_default(),
+        limit,
+    };
+
+    // CPU sampling sleeps between refreshes; keep it off the async runtime
+    tokio::task::spawn_blocking(move || list_running_processes(&query))
+        .await
+        .map_err(|e| format!("Process listing task failed: {}", e))?
         .map_err(|e| e.to_string())
 }
@@ -1,11 +1,15 @@
 //! Auto-start Telegram bot bridge
 
 use std::process::{Command, Child};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use tauri::State;
 
+use crate::bot_access::BotAccess;
+
 pub struct TelegramBotState {
     pub process: Mutex<Option<Child>>,
+    /// Allowlist / blocklist policy, shared with the bridge HTTP server
+    pub access: Arc<BotAccess>,
 }
 
 #[tauri::command]
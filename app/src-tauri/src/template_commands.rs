@@ -2,8 +2,9 @@
 
 use crate::smart_templates::{
     DocumentTemplate, OutputFormat, TemplateExtractor, TemplateExtractionRequest,
-    TemplateGenerationRequest,
+    TemplateGenerationRequest, TemplateInputError,
 };
+use crate::llm_commands::LLMState;
 use crate::rag_commands::RagState;
 use std::collections::HashMap;
 use std::sync::Mutex;
@@ -70,19 +71,82 @@ pub async fn extract_template(
     Ok(template)
 }
 
+/// Create a template from markdown with `{{name:kind}}` placeholders
+#[tauri::command]
+pub async fn create_template(
+    name: String,
+    description: Option<String>,
+    body: String,
+    template_store: State<'_, TemplateStore>,
+) -> Result<DocumentTemplate, String> {
+    tracing::info!("Creating template: {}", name);
+
+    let template = TemplateExtractor::new()
+        .template_from_markdown(name, description.unwrap_or_default(), &body)?;
+
+    let mut templates = template_store.templates.lock().map_err(|e| e.to_string())?;
+    templates.insert(template.id.clone(), template.clone());
+
+    tracing::info!("Template created with {} sections, {} placeholders", template.sections.len(), template.variables.len());
+    Ok(template)
+}
+
+/// Check form values against a template's placeholder schema
+#[tauri::command]
+pub async fn validate_template_inputs(
+    template_id: String,
+    variables: HashMap<String, String>,
+    fill_with_llm: Option<bool>,
+    template_store: State<'_, TemplateStore>,
+) -> Result<Vec<TemplateInputError>, String> {
+    let templates = template_store.templates.lock().map_err(|e| e.to_string())?;
+    let template = templates
+        .get(&template_id)
+        .ok_or_else(|| "Template not found".to_string())?;
+
+    Ok(TemplateExtractor::new().validate_inputs(template, &variables, fill_with_llm.unwrap_or(false)))
+}
+
 /// Generate document from template
 #[tauri::command]
 pub async fn generate_from_template(
     template_id: String,
-    variables: HashMap<String, String>,
+    mut variables: HashMap<String, String>,
     output_format: String,
+    fill_with_llm: Option<bool>,
     template_store: State<'_, TemplateStore>,
+    llm_state: State<'_, LLMState>,
 ) -> Result<String, String> {
     tracing::info!("\n=== GENERATE FROM TEMPLATE ===");
     tracing::info!("Template ID: {}", template_id);
     tracing::info!("Variables: {:?}", variables);
     tracing::info!("Output format: {}", output_format);
 
+    let extractor = TemplateExtractor::new();
+    let template = {
+        let templates = template_store.templates.lock().map_err(|e| e.to_string())?;
+        templates
+            .get(&template_id)
+            .cloned()
+            .ok_or_else(|| "Template not found".to_string())?
+    };
+
+    let fill_with_llm = fill_with_llm.unwrap_or(false);
+    let errors = extractor.validate_inputs(&template, &variables, fill_with_llm);
+    if !errors.is_empty() {
+        let messages: Vec<String> = errors.into_iter().map(|e| e.message).collect();
+        return Err(format!("Invalid template inputs: {}", messages.join("; ")));
+    }
+
+    if fill_with_llm {
+        let manager_lock = llm_state.manager.read().await;
+        let manager = manager_lock
+            .as_ref()
+            .ok_or_else(|| "LLM is not configured. Please configure an LLM provider to fill free-text sections.".to_string())?;
+        let filled = extractor.fill_rich_text(&template, &mut variables, manager).await?;
+        tracing::info!("LLM filled {} free-text placeholders: {:?}", filled.len(), filled);
+    }
+
     // Parse output format
     let format = match output_format.to_lowercase().as_str() {
         "markdown" | "md" => OutputFormat::Markdown,
@@ -102,7 +166,6 @@ pub async fn generate_from_template(
     let templates = template_store.templates.lock().map_err(|e| e.to_string())?;
 
     // Generate document
-    let extractor = TemplateExtractor::new();
     let output = extractor
         .generate_from_template(request, &templates)
         .map_err(|e| format!("Template generation failed: {}", e))?;
@@ -205,6 +268,18 @@ pub async fn preview_template(
         .get(&template_id)
         .ok_or_else(|| "Template not found".to_string())?;
 
-    // Use example content as preview
-    Ok(template.example_content.clone())
+    // Templates without placeholders keep showing the content they were learned from
+    if template.variables.is_empty() {
+        return Ok(template.example_content.clone());
+    }
+
+    let extractor = TemplateExtractor::new();
+    let request = TemplateGenerationRequest {
+        template_id,
+        variables: extractor.sample_values(&template.variables),
+        output_format: OutputFormat::Markdown,
+    };
+    extractor
+        .generate_from_template(request, &templates)
+        .map_err(|e| format!("Template preview failed: {}", e))
 }
@@ -3,6 +3,8 @@
 use tauri::{State, Manager};
 use crate::rag_commands::RagState;
 use crate::chat_engine::{ChatEngine, UserMessage, ChatContext, AssistantResponse, MessagePlatform, Artifact};
+use shodh_rag::chat::ContextUsage;
+use shodh_rag::llm::{CancellationToken, GenerationRegistry, LLMManager};
 use crate::artifact_store::ArtifactStore;
 use std::sync::Arc;
 use tokio::sync::RwLock as AsyncRwLock;
@@ -16,8 +18,18 @@ pub async fn unified_chat_internal(
     platform: MessagePlatform,
     app_handle: Option<tauri::AppHandle>,
 ) -> Result<AssistantResponse, String> {
-    tracing::info!("🔵 unified_chat_internal called from {:?}: {}", platform, message.chars().take(50).collect::<String>());
+    // Stream to the frontend when called from the app
+    let emitter = app_handle.clone().map(crate::chat_engine::TauriEventEmitter::new);
+    let emitter_ref: Option<&dyn shodh_rag::chat::EventEmitter> = emitter.as_ref().map(|e| e as &dyn shodh_rag::chat::EventEmitter);
+    unified_chat_with_emitter(rag_state, message, context, platform, app_handle, false, emitter_ref).await
+}
 
+/// Built-in tools that touch the machine or the user's calendar. Bot senders
+/// only get them when `bot_access` lists them as tool users.
+const SYSTEM_TOOLS: &[&str] = &["read_file", "write_file", "list_directory", "create_task", "create_event", "list_tasks"];
+
+/// Chat engine over the app's RAG, agent, assistant, memory and LLM state
+async fn build_chat_engine(rag_state: &RagState) -> Result<ChatEngine, String> {
     // Get components from state
     let rag = rag_state.rag.clone();
 
@@ -53,6 +65,29 @@ pub async fn unified_chat_internal(
         tracing::info!("LLM Manager Status: {}", if llm_guard.is_some() { "Initialized" } else { "Not Initialized" });
     }
 
+    Ok(ChatEngine::new(
+        rag,
+        agent_system,
+        personal_assistant,
+        llm_manager,
+        memory_system,
+    ).await)
+}
+
+/// Unified chat streaming progress to `emitter` (Tauri events, SSE, ...).
+/// Desktop chat gets MCP and system tools; bot platforms only when
+/// `bot_tools_allowed` says the sender is on the bot's tool allowlist.
+pub async fn unified_chat_with_emitter(
+    rag_state: &RagState,
+    message: String,
+    context: Option<ChatContext>,
+    platform: MessagePlatform,
+    app_handle: Option<tauri::AppHandle>,
+    bot_tools_allowed: bool,
+    emitter: Option<&dyn shodh_rag::chat::EventEmitter>,
+) -> Result<AssistantResponse, String> {
+    tracing::info!("🔵 unified_chat_internal called from {:?}: {}", platform, message.chars().take(50).collect::<String>());
+
     // Wire LLM manager + RAG engine into AgentSystem for real tool-calling execution (lazy init)
     {
         let agent_sys_guard = rag_state.agent_system.read().await;
@@ -66,17 +101,30 @@ pub async fn unified_chat_internal(
         }
     }
 
-    // Create chat engine
-    let engine = ChatEngine::new(
-        rag,
-        agent_system,
-        personal_assistant,
-        llm_manager,
-        memory_system,
-    ).await;
+    let engine = build_chat_engine(rag_state).await?;
+    let tools_enabled = matches!(platform, MessagePlatform::Desktop) || bot_tools_allowed;
+    if !tools_enabled {
+        for tool_id in SYSTEM_TOOLS {
+            engine.tool_registry().unregister(tool_id);
+        }
+    }
+
+    // Bridge tools from connected MCP servers into the chat tool loop and the
+    // agent system. Re-registered per message so newly connected servers show up.
+    if let Some(handle) = app_handle.as_ref().filter(|_| tools_enabled) {
+        if let Some(mcp) = handle.try_state::<crate::mcp_commands::MCPState>() {
+            crate::mcp_bridge::register_mcp_tools(engine.tool_registry(), mcp.manager.clone()).await;
+
+            let agent_sys_guard = rag_state.agent_system.read().await;
+            if let Some(ref agent_sys_arc) = *agent_sys_guard {
+                let agent_tools = agent_sys_arc.read().await.tool_registry();
+                crate::mcp_bridge::register_mcp_tools(&agent_tools, mcp.manager.clone()).await;
+            }
+        }
+    }
 
     // Wire calendar store path so calendar tools can persist data
-    if let Some(ref handle) = app_handle {
+    if let Some(handle) = app_handle.as_ref().filter(|_| tools_enabled) {
         if let Ok(app_dir) = handle.path().app_data_dir() {
             let _ = std::fs::create_dir_all(&app_dir);
             engine.set_calendar_path(app_dir.join("calendar_data.json")).await;
@@ -91,17 +139,42 @@ pub async fn unified_chat_internal(
         timestamp: chrono::Utc::now(),
     };
 
+    // Fill in the space's LLM settings unless the caller supplied them
+    let mut context = context.unwrap_or_default();
+    if context.space_llm_settings.is_none() {
+        if let Some(space_id) = context.space_id.as_deref() {
+            context.space_llm_settings = crate::space_commands::space_llm_settings(rag_state, space_id);
+        }
+    }
+
+    // Bots keep artifacts under their own conversation, not the desktop one
+    let context_conversation_id = context.conversation_id.clone();
+
+    // Let the UI stop this turn's generation by request id
+    let generations = app_handle.as_ref()
+        .and_then(|handle| handle.try_state::<GenerationRegistry>())
+        .map(|registry| registry.inner().clone());
+    let request_id = context.request_id.clone();
+    let cancel = match (&generations, &request_id) {
+        (Some(registry), Some(id)) => registry.register(id),
+        _ => CancellationToken::default(),
+    };
+
     // Process message with optional streaming support via EventEmitter trait
-    let emitter = app_handle.map(|h| crate::chat_engine::TauriEventEmitter::new(h));
-    let emitter_ref: Option<&dyn shodh_rag::chat::EventEmitter> = emitter.as_ref().map(|e| e as &dyn shodh_rag::chat::EventEmitter);
-    let response = engine.process_message(user_msg, context.unwrap_or_default(), emitter_ref).await
-        .map_err(|e| format!("Failed to process message: {}", e))?;
+    let result = LLMManager::with_cancellation(cancel, engine.process_message(user_msg, context, emitter)).await;
+    if let (Some(registry), Some(id)) = (&generations, &request_id) {
+        registry.finish(id);
+    }
+    let response = result.map_err(|e| format!("Failed to process message: {}", e))?;
 
     // Store artifacts in artifact store
     if !response.artifacts.is_empty() {
         let mut artifact_store = rag_state.artifact_store.write().await;
-        let conversation_id = rag_state.conversation_id.read().await.clone()
-            .unwrap_or_else(|| "default".to_string());
+        let conversation_id = match context_conversation_id {
+            Some(id) => id,
+            None => rag_state.conversation_id.read().await.clone()
+                .unwrap_or_else(|| "default".to_string()),
+        };
 
         for artifact in &response.artifacts {
             artifact_store.add_artifact(&conversation_id, artifact.clone());
@@ -123,6 +196,31 @@ pub async fn unified_chat(
     unified_chat_internal(&state, message, context, MessagePlatform::Desktop, Some(app_handle)).await
 }
 
+/// Projected token use of a search prompt for `query` against the current
+/// model's context window: system prompt, retrieved context, history and
+/// memory, and whether any of them will be truncated
+#[tauri::command]
+pub async fn estimate_context_usage(
+    state: State<'_, RagState>,
+    query: String,
+    space_id: Option<String>,
+    context: Option<ChatContext>,
+) -> Result<ContextUsage, String> {
+    let mut context = context.unwrap_or_default();
+    if space_id.is_some() {
+        context.space_id = space_id;
+    }
+    if context.space_llm_settings.is_none() {
+        if let Some(space_id) = context.space_id.as_deref() {
+            context.space_llm_settings = crate::space_commands::space_llm_settings(&state, space_id);
+        }
+    }
+
+    let engine = build_chat_engine(&state).await?;
+    engine.estimate_context_usage(&query, &context).await
+        .map_err(|e| format!("Failed to estimate context usage: {}", e))
+}
+
 /// Apply artifact content to a file
 #[tauri::command]
 pub async fn apply_artifact_to_file(
@@ -3,7 +3,8 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use chrono::{DateTime, Utc};
 
@@ -34,6 +35,21 @@ pub struct ContactPreferences {
     pub response_style: ResponseStyle,  // Formal, casual, technical
     pub max_response_length: usize,
     pub include_sources: bool,
+    /// Sustained messages per minute before the bot asks the contact to
+    /// slow down (0 = unlimited)
+    #[serde(default = "default_rate_limit_per_minute")]
+    pub rate_limit_per_minute: u32,
+    /// Messages that may arrive back to back before the rate applies
+    #[serde(default = "default_rate_limit_burst")]
+    pub rate_limit_burst: u32,
+}
+
+fn default_rate_limit_per_minute() -> u32 {
+    10
+}
+
+fn default_rate_limit_burst() -> u32 {
+    5
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,10 +67,78 @@ impl Default for ContactPreferences {
             response_style: ResponseStyle::Casual,
             max_response_length: 500,
             include_sources: true,
+            rate_limit_per_minute: default_rate_limit_per_minute(),
+            rate_limit_burst: default_rate_limit_burst(),
         }
     }
 }
 
+/// Token bucket: holds up to `burst` messages and refills at
+/// `per_minute` / 60 messages per second
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn full(burst: u32, now: Instant) -> Self {
+        Self { tokens: burst.max(1) as f64, last_refill: now }
+    }
+
+    /// Take one token, or return how long until one is available
+    fn try_take(&mut self, per_minute: u32, burst: u32, now: Instant) -> Result<(), Duration> {
+        let capacity = burst.max(1) as f64;
+        let per_second = per_minute as f64 / 60.0;
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_second).min(capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / per_second))
+        }
+    }
+}
+
+/// Per-contact message rate limiting, so one noisy contact cannot run up
+/// LLM cost or throttle everyone else
+#[derive(Default)]
+pub struct ContactRateLimiter {
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl ContactRateLimiter {
+    /// Count a message from `phone` against its limit. `Err` carries the wait
+    /// before the contact may send again.
+    pub fn check(&self, phone: &str, preferences: &ContactPreferences) -> Result<(), Duration> {
+        self.check_at(phone, preferences, Instant::now())
+    }
+
+    fn check_at(&self, phone: &str, preferences: &ContactPreferences, now: Instant) -> Result<(), Duration> {
+        if preferences.rate_limit_per_minute == 0 {
+            return Ok(());
+        }
+        let Ok(mut buckets) = self.buckets.lock() else {
+            return Ok(());
+        };
+        buckets
+            .entry(phone.to_string())
+            .or_insert_with(|| TokenBucket::full(preferences.rate_limit_burst, now))
+            .try_take(preferences.rate_limit_per_minute, preferences.rate_limit_burst, now)
+    }
+}
+
+/// Reply sent instead of an answer when a contact is over its limit
+pub fn slow_down_reply(retry_after: Duration) -> String {
+    format!(
+        "You're sending messages a little too quickly. Please wait about {} seconds and try again 🙏",
+        retry_after.as_secs_f64().ceil().max(1.0) as u64
+    )
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BotResponse {
     pub message: String,
@@ -234,3 +318,31 @@ impl Default for WhatsAppBot {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limit_is_per_contact_and_refills() {
+        let limiter = ContactRateLimiter::default();
+        let prefs = ContactPreferences { rate_limit_per_minute: 6, rate_limit_burst: 2, ..Default::default() };
+        let start = Instant::now();
+
+        assert!(limiter.check_at("alice", &prefs, start).is_ok());
+        assert!(limiter.check_at("alice", &prefs, start).is_ok());
+        let wait = limiter.check_at("alice", &prefs, start).unwrap_err();
+        assert_eq!(wait.as_secs(), 10);
+        assert!(slow_down_reply(wait).contains("10 seconds"));
+
+        // Another contact has its own bucket
+        assert!(limiter.check_at("bob", &prefs, start).is_ok());
+
+        // One token back after 10s at 6/min
+        assert!(limiter.check_at("alice", &prefs, start + Duration::from_secs(10)).is_ok());
+        assert!(limiter.check_at("alice", &prefs, start + Duration::from_secs(11)).is_err());
+
+        let unlimited = ContactPreferences { rate_limit_per_minute: 0, ..Default::default() };
+        assert!((0..100).all(|_| limiter.check_at("carol", &unlimited, start).is_ok()));
+    }
+}
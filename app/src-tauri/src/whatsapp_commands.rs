@@ -1,8 +1,12 @@
 //! Tauri commands for WhatsApp Bot
 
-use crate::whatsapp_bot::{WhatsAppBot, WhatsAppContact, WhatsAppMessage, BotResponse, ContactPreferences, ResponseStyle, BotStats};
+use crate::whatsapp_bot::{WhatsAppBot, WhatsAppContact, WhatsAppMessage, BotResponse, ContactPreferences, ContactRateLimiter, ResponseStyle, BotStats, slow_down_reply};
 use crate::rag_commands::RagState;
-use crate::space_commands;
+use crate::bot_access::{AccessDecision, BotAccess};
+use crate::bot_artifacts::{append_inline, render_artifacts, ArtifactAttachment};
+use crate::bot_chat::{run_turn, BotTurn};
+use crate::bot_format::{format_error, format_reply, reply_confidence, reply_sources};
+use crate::chat_engine::{AssistantResponse, MessagePlatform};
 use tauri::State;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -13,6 +17,10 @@ use chrono::Utc;
 pub struct WhatsAppBotState {
     pub bot: Arc<WhatsAppBot>,
     pub bridge_process: std::sync::Mutex<Option<std::process::Child>>,
+    /// Per-contact token buckets, shared with the bridge HTTP server
+    pub rate_limiter: Arc<ContactRateLimiter>,
+    /// Allowlist / blocklist policy, shared with the bridge HTTP server
+    pub access: Arc<BotAccess>,
 }
 
 impl Default for WhatsAppBotState {
@@ -20,6 +28,8 @@ impl Default for WhatsAppBotState {
         Self {
             bot: Arc::new(WhatsAppBot::new()),
             bridge_process: std::sync::Mutex::new(None),
+            rate_limiter: Arc::new(ContactRateLimiter::default()),
+            access: Arc::new(BotAccess::default()),
         }
     }
 }
@@ -170,6 +180,8 @@ pub async fn whatsapp_update_contact_preferences(
     response_style: Option<String>,
     max_response_length: Option<usize>,
     include_sources: Option<bool>,
+    rate_limit_per_minute: Option<u32>,
+    rate_limit_burst: Option<u32>,
 ) -> Result<String, String> {
     let bot = &bot_state.bot;
 
@@ -192,6 +204,12 @@ pub async fn whatsapp_update_contact_preferences(
         if let Some(sources) = include_sources {
             contact.preferences.include_sources = sources;
         }
+        if let Some(per_minute) = rate_limit_per_minute {
+            contact.preferences.rate_limit_per_minute = per_minute;
+        }
+        if let Some(burst) = rate_limit_burst {
+            contact.preferences.rate_limit_burst = burst.max(1);
+        }
 
         bot.add_contact(contact).await;
         Ok("Contact preferences updated".to_string())
@@ -218,17 +236,23 @@ pub async fn whatsapp_assign_space(
     }
 }
 
-/// Process incoming WhatsApp message and generate RAG response
-#[tauri::command]
-pub async fn whatsapp_process_message(
-    bot_state: State<'_, WhatsAppBotState>,
-    rag_state: State<'_, RagState>,
-    from: String,
-    from_name: String,
-    body: String,
-    chat_id: String,
-    is_group: bool,
-) -> Result<BotResponse, String> {
+/// How to handle an incoming WhatsApp message
+pub(crate) enum WhatsAppTurn {
+    /// Answer it through the shared bot chat path
+    Chat(WhatsAppContact, BotTurn),
+    /// The contact is over its rate limit; send this reply instead
+    SlowDown(String),
+    /// The access policy refuses the sender: reply with this, or stay silent
+    Reject(Option<String>),
+}
+
+/// Check the bot is active and the sender allowed, resolve (or auto-create)
+/// the contact, apply its rate limit, and record the message in its
+/// conversation. Shared by `whatsapp_process_message` and the bridge server.
+pub(crate) async fn begin_turn(
+    bot_state: &WhatsAppBotState,
+    message: WhatsAppMessage,
+) -> Result<WhatsAppTurn, String> {
     let bot = &bot_state.bot;
 
     // Check if bot is active
@@ -236,16 +260,21 @@ pub async fn whatsapp_process_message(
         return Err("Bot is not active".to_string());
     }
 
-    // Get or auto-create contact (no authorization check - reply to everyone)
-    let contact = match bot.get_contact(&from).await {
+    if let AccessDecision::Reject(reply) = bot_state.access.check(&[&message.from]) {
+        tracing::info!("Not serving WhatsApp sender {} (access policy)", message.from);
+        return Ok(WhatsAppTurn::Reject(reply));
+    }
+
+    // Get or auto-create contact
+    let contact = match bot.get_contact(&message.from).await {
         Some(c) => c,
         None => {
             // Auto-create contact for anyone who messages
             let new_contact = WhatsAppContact {
-                phone: from.clone(),
-                name: from_name.clone(),
+                phone: message.from.clone(),
+                name: message.from_name.clone(),
                 assigned_space: None, // Global access
-                is_authorized: true,  // Auto-authorize everyone
+                is_authorized: true,
                 conversation_id: None,
                 preferences: ContactPreferences::default(),
             };
@@ -254,112 +283,112 @@ pub async fn whatsapp_process_message(
         }
     };
 
-    // Create message object
-    let message = WhatsAppMessage {
-        id: Uuid::new_v4().to_string(),
-        from: from.clone(),
-        from_name,
-        body: body.clone(),
-        timestamp: Utc::now(),
-        chat_id,
-        is_group,
-    };
+    // Over the contact's limit: answer politely without searching or
+    // calling the LLM
+    if let Err(retry_after) = bot_state.rate_limiter.check(&message.from, &contact.preferences) {
+        tracing::info!("WhatsApp contact {} rate limited for {:?}", message.from, retry_after);
+        return Ok(WhatsAppTurn::SlowDown(slow_down_reply(retry_after)));
+    }
 
     // Get or create conversation
-    let conversation_id = if let Some(conv) = bot.get_conversation(&from).await {
+    let conversation_id = if let Some(conv) = bot.get_conversation(&message.from).await {
         conv.id
     } else {
-        bot.start_conversation(from.clone()).await
+        bot.start_conversation(message.from.clone()).await
     };
 
-    // Add message to conversation
-    bot.add_message(&conversation_id, message).await;
-
-    // Perform RAG search to get context
-    let search_results = if let Some(space_id) = &contact.assigned_space {
-        space_commands::search_in_space(
-            rag_state.clone(),
-            space_id.clone(),
-            body.clone(),
-            5
-        ).await.unwrap_or_default()
-    } else {
-        space_commands::search_global(
-            rag_state.clone(),
-            body.clone(),
-            5
-        ).await.unwrap_or_default()
+    let turn = BotTurn {
+        platform: MessagePlatform::WhatsApp,
+        sender: message.from_name.clone(),
+        conversation_id: conversation_id.clone(),
+        space_id: contact.assigned_space.clone(),
+        message: message.body.clone(),
+        tools_allowed: bot_state.access.allows_tools(&[&message.from]),
     };
+    bot.add_message(&conversation_id, message).await;
 
-    // Build a professional response with proper formatting and citations
-    let (response_text, sources, confidence) = if search_results.is_empty() {
-        (
-            format!(
-                "I apologize, but I couldn't find relevant information in my knowledge base to answer your question about '{}'.\n\nCould you please rephrase your question or ask about something else I might be able to help with?",
-                body.chars().take(50).collect::<String>()
-            ),
-            Vec::new(),
-            0.0
-        )
-    } else {
-        // Extract sources
-        let mut sources_list = Vec::new();
-        for result in &search_results {
-            if let Some(file_path) = result.metadata.get("file_path") {
-                if !sources_list.contains(file_path) {
-                    sources_list.push(file_path.clone());
-                }
-            }
-        }
-
-        // Build comprehensive response with multiple relevant chunks
-        let mut full_response = String::from("Based on the information in my knowledge base:\n\n");
+    Ok(WhatsAppTurn::Chat(contact, turn))
+}
 
-        // Add the top results with inline citations
-        for (idx, result) in search_results.iter().take(3).enumerate() {
-            if idx > 0 {
-                full_response.push_str("\n\n");
-            }
-            full_response.push_str(&result.snippet);
-
-            // Add inline citation
-            if let Some(file_path) = result.metadata.get("file_path") {
-                let file_name = std::path::Path::new(file_path)
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or(file_path);
-                full_response.push_str(&format!(" _[{}]_", file_name));
-            }
+/// Format the chat result for WhatsApp and store it as the bot's reply.
+/// Artifacts that fit are appended to the text; the rest come back as files.
+pub(crate) async fn finish_turn(
+    bot: &WhatsAppBot,
+    contact: &WhatsAppContact,
+    turn: &BotTurn,
+    result: Result<AssistantResponse, String>,
+) -> (BotResponse, Vec<ArtifactAttachment>) {
+    let (bot_response, attachments) = match result {
+        Ok(response) => {
+            let text = format_reply(&turn.platform, &response);
+            let sources = reply_sources(&response);
+            let confidence = reply_confidence(&response);
+            let (inline, mut attachments) = render_artifacts(response.artifacts, turn.platform.clone()).await;
+            let bot_response = BotResponse {
+                message: append_inline(text, inline, &turn.platform, &mut attachments),
+                sources,
+                confidence,
+                used_space: contact.assigned_space.clone(),
+            };
+            (bot_response, attachments)
         }
-
-        // Add sources summary at the end
-        if !sources_list.is_empty() {
-            full_response.push_str("\n\n📚 *Sources:*\n");
-            for (idx, source) in sources_list.iter().enumerate() {
-                let file_name = std::path::Path::new(source)
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or(source);
-                full_response.push_str(&format!("{}. {}\n", idx + 1, file_name));
-            }
+        Err(e) => {
+            let bot_response = BotResponse {
+                message: format_error(&turn.platform, &e),
+                sources: Vec::new(),
+                confidence: 0.0,
+                used_space: contact.assigned_space.clone(),
+            };
+            (bot_response, Vec::new())
         }
-
-        // Calculate average confidence
-        let avg_confidence = search_results.iter().map(|r| r.score).sum::<f32>() / search_results.len() as f32;
-
-        (full_response, sources_list, avg_confidence)
     };
+    bot.add_response(&turn.conversation_id, bot_response.clone()).await;
+    tracing::info!("✅ WhatsApp reply stored (confidence: {:.1}%)", bot_response.confidence * 100.0);
+    (bot_response, attachments)
+}
 
-    let bot_response = BotResponse {
-        message: response_text,
-        sources: sources.clone(),
-        confidence,
-        used_space: contact.assigned_space.clone(),
+/// Process incoming WhatsApp message and generate a reply through the
+/// shared bot chat path. Refused senders get an empty message (stay silent)
+/// or the configured rejection text.
+#[tauri::command]
+pub async fn whatsapp_process_message(
+    app_handle: tauri::AppHandle,
+    bot_state: State<'_, WhatsAppBotState>,
+    rag_state: State<'_, RagState>,
+    from: String,
+    from_name: String,
+    body: String,
+    chat_id: String,
+    is_group: bool,
+) -> Result<BotResponse, String> {
+    let message = WhatsAppMessage {
+        id: Uuid::new_v4().to_string(),
+        from,
+        from_name,
+        body,
+        timestamp: Utc::now(),
+        chat_id,
+        is_group,
     };
 
-    // Store response in conversation
-    bot.add_response(&conversation_id, bot_response.clone()).await;
+    let (contact, turn) = match begin_turn(&bot_state, message).await? {
+        WhatsAppTurn::Chat(contact, turn) => (contact, turn),
+        WhatsAppTurn::SlowDown(message) => {
+            return Ok(BotResponse { message, sources: Vec::new(), confidence: 0.0, used_space: None });
+        }
+        WhatsAppTurn::Reject(reply) => {
+            return Ok(BotResponse {
+                message: reply.unwrap_or_default(),
+                sources: Vec::new(),
+                confidence: 0.0,
+                used_space: None,
+            });
+        }
+    };
 
+    let result = run_turn(&rag_state, Some(&app_handle), &turn, None).await;
+    // The desktop test path shows artifacts in the app; only the text is returned
+    let (bot_response, _attachments) = finish_turn(&bot_state.bot, &contact, &turn, result).await;
     Ok(bot_response)
 }
 
@@ -444,12 +473,14 @@ pub async fn whatsapp_get_conversation(
 /// Test the bot with a sample message (for debugging)
 #[tauri::command]
 pub async fn whatsapp_test_message(
+    app_handle: tauri::AppHandle,
     bot_state: State<'_, WhatsAppBotState>,
     rag_state: State<'_, RagState>,
     message: String,
 ) -> Result<BotResponse, String> {
     // Use a test contact
     whatsapp_process_message(
+        app_handle,
         bot_state,
         rag_state,
         "test_user".to_string(),
@@ -3,7 +3,7 @@
 use axum::{
     extract::State as AxumState,
     http::StatusCode,
-    response::Json,
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
@@ -12,10 +12,13 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use tower_http::cors::{Any, CorsLayer};
 
-use crate::whatsapp_commands::WhatsAppBotState;
+use crate::whatsapp_bot::WhatsAppMessage;
+use crate::whatsapp_commands::{begin_turn, finish_turn, WhatsAppBotState, WhatsAppTurn};
 use crate::rag_commands::RagState;
-use crate::unified_chat_commands::unified_chat_internal;
-use crate::chat_engine::{ChatContext, MessagePlatform};
+use crate::bot_artifacts::ArtifactAttachment;
+use crate::bot_chat::run_turn;
+use crate::bot_sse::stream_chat;
+use crate::bot_access::stream_rejection;
 
 #[derive(Debug, Deserialize)]
 struct IncomingMessage {
@@ -26,156 +29,90 @@ struct IncomingMessage {
     is_group: bool,
 }
 
+impl From<IncomingMessage> for WhatsAppMessage {
+    fn from(payload: IncomingMessage) -> Self {
+        WhatsAppMessage {
+            id: uuid::Uuid::new_v4().to_string(),
+            from: payload.from,
+            from_name: payload.from_name,
+            body: payload.body,
+            timestamp: chrono::Utc::now(),
+            chat_id: payload.chat_id,
+            is_group: payload.is_group,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct BridgeResponse {
     message: String,
     sources: Vec<String>,
     confidence: f32,
+    /// Rendered charts, diagrams and long code/tables, sent after `message`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<ArtifactAttachment>,
 }
 
 #[derive(Clone)]
 struct AppState {
     bot_state: Arc<RwLock<WhatsAppBotState>>,
     rag_state: Arc<RwLock<RagState>>,
+    app_handle: Option<tauri::AppHandle>,
+}
+
+async fn start_turn(state: &AppState, payload: IncomingMessage) -> Result<WhatsAppTurn, (StatusCode, String)> {
+    let bot_state = state.bot_state.read().await;
+    begin_turn(&bot_state, payload.into())
+        .await
+        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e))
 }
 
 async fn handle_whatsapp_message(
     AxumState(state): AxumState<AppState>,
     Json(payload): Json<IncomingMessage>,
-) -> Result<Json<BridgeResponse>, (StatusCode, String)> {
-    tracing::info!("📨 Received WhatsApp message from {}: {}", payload.from, payload.body);
-
-    // Get bot and RAG state
-    let bot_state_guard = state.bot_state.read().await;
-    let rag_state_guard = state.rag_state.read().await;
-
-    // Process message using the existing command logic
-    // We need to wrap it in a tauri::State-like structure
-    // For now, let's call the logic directly
-
-    let bot = &bot_state_guard.bot;
-
-    // Check if bot is active
-    if !bot.is_bot_active().await {
-        return Err((StatusCode::SERVICE_UNAVAILABLE, "Bot is not active".to_string()));
-    }
-
-    // Get or auto-create contact
-    let contact = match bot.get_contact(&payload.from).await {
-        Some(c) => c,
-        None => {
-            // Auto-create contact
-            let new_contact = crate::whatsapp_bot::WhatsAppContact {
-                phone: payload.from.clone(),
-                name: payload.from_name.clone(),
-                assigned_space: None,
-                is_authorized: true,
-                conversation_id: None,
-                preferences: crate::whatsapp_bot::ContactPreferences::default(),
-            };
-            bot.add_contact(new_contact.clone()).await;
-            new_contact
+) -> Result<Response, (StatusCode, String)> {
+    let (contact, turn) = match start_turn(&state, payload).await? {
+        WhatsAppTurn::Chat(contact, turn) => (contact, turn),
+        WhatsAppTurn::SlowDown(message) | WhatsAppTurn::Reject(Some(message)) => {
+            let reply = BridgeResponse { message, sources: Vec::new(), confidence: 0.0, attachments: Vec::new() };
+            return Ok(Json(reply).into_response());
         }
+        WhatsAppTurn::Reject(None) => return Ok(StatusCode::NO_CONTENT.into_response()),
     };
 
-    // Create message
-    let message = crate::whatsapp_bot::WhatsAppMessage {
-        id: uuid::Uuid::new_v4().to_string(),
-        from: payload.from.clone(),
-        from_name: payload.from_name.clone(),
-        body: payload.body.clone(),
-        timestamp: chrono::Utc::now(),
-        chat_id: payload.chat_id,
-        is_group: payload.is_group,
+    let result = {
+        let rag_state_guard = state.rag_state.read().await;
+        run_turn(&rag_state_guard, state.app_handle.as_ref(), &turn, None).await
     };
+    let bot = state.bot_state.read().await.bot.clone();
+    let (reply, attachments) = finish_turn(&bot, &contact, &turn, result).await;
 
-    // Get or create conversation
-    let conversation_id = if let Some(conv) = bot.get_conversation(&payload.from).await {
-        conv.id
-    } else {
-        bot.start_conversation(payload.from.clone()).await
-    };
-
-    // Add message to conversation
-    bot.add_message(&conversation_id, message).await;
-
-    // Create chat context
-    let context = ChatContext {
-        agent_id: None,
-        project: None,
-        space_id: contact.assigned_space.as_ref().map(|uuid| uuid.to_string()),
-        conversation_id: Some(conversation_id.clone()),
-        conversation_history: None,
-        max_results: None,
-        streaming: None,
-        custom_system_prompt: None,
-    };
-
-    // Use unified chat system with full Memory + GraphRAG + LLM
-    let result = unified_chat_internal(
-        &rag_state_guard,
-        payload.body.clone(),
-        Some(context),
-        MessagePlatform::WhatsApp,
-        None, // No app_handle for HTTP servers (no streaming)
-    ).await;
-
-    let (response_text, sources, confidence) = match result {
-        Ok(response) => {
-            // Extract sources from citations (using title field as source name)
-            let sources: Vec<String> = response.citations.iter()
-                .map(|c| c.title.clone())
-                .collect();
-
-            // Calculate average confidence from citations (using score field)
-            let confidence = if response.citations.is_empty() {
-                0.8  // Default confidence for generated responses
-            } else {
-                response.citations.iter()
-                    .map(|c| c.score)
-                    .sum::<f32>() / response.citations.len() as f32
-            };
-
-            // Format response with metadata
-            let mut message = response.content.clone();
-
-            let meta = &response.metadata;
-            if let (Some(model), Some(input_tokens), Some(output_tokens), Some(duration_ms)) =
-                (&meta.model, meta.input_tokens, meta.output_tokens, meta.duration_ms) {
-                let duration_s = duration_ms as f64 / 1000.0;
-                let tok_per_s = output_tokens as f64 / duration_s;
-
-                message.push_str(&format!(
-                    "\n\n🤖 {} │ ↓ {} │ ↑ {} │ ⏱ {:.1}s │ ⚡ {:.1} tok/s",
-                    model, input_tokens, output_tokens, duration_s, tok_per_s
-                ));
-            }
-
-            (message, sources, confidence)
-        },
-        Err(e) => {
-            tracing::info!("❌ Error from unified_chat: {}", e);
-            (format!("Sorry, I encountered an error: {}", e), Vec::new(), 0.0)
-        }
-    };
+    Ok(Json(BridgeResponse {
+        message: reply.message,
+        sources: reply.sources,
+        confidence: reply.confidence,
+        attachments,
+    }).into_response())
+}
 
-    // Store bot response
-    let bot_response = crate::whatsapp_bot::BotResponse {
-        message: response_text.clone(),
-        sources: sources.clone(),
-        confidence,
-        used_space: contact.assigned_space,
+/// Same as `/whatsapp/process`, streamed as Server-Sent Events. The reply is
+/// stored in the contact's conversation once generation finishes.
+async fn handle_whatsapp_stream(
+    AxumState(state): AxumState<AppState>,
+    Json(payload): Json<IncomingMessage>,
+) -> Result<Response, (StatusCode, String)> {
+    let (contact, turn) = match start_turn(&state, payload).await? {
+        WhatsAppTurn::Chat(contact, turn) => (contact, turn),
+        WhatsAppTurn::SlowDown(message) => return Err((StatusCode::TOO_MANY_REQUESTS, message)),
+        WhatsAppTurn::Reject(reply) => return Ok(stream_rejection(reply)),
     };
 
-    bot.add_response(&conversation_id, bot_response).await;
-
-    tracing::info!("✅ Sent response (confidence: {:.1}%)", confidence * 100.0);
-
-    Ok(Json(BridgeResponse {
-        message: response_text,
-        sources,
-        confidence,
-    }))
+    let bot = state.bot_state.read().await.bot.clone();
+    let stored_turn = turn.clone();
+    Ok(stream_chat(state.rag_state.clone(), state.app_handle.clone(), turn, move |result| async move {
+        finish_turn(&bot, &contact, &stored_turn, result).await;
+    })
+    .into_response())
 }
 
 async fn health_check() -> &'static str {
@@ -185,10 +122,12 @@ async fn health_check() -> &'static str {
 pub async fn start_server(
     bot_state: WhatsAppBotState,
     rag_state: RagState,
+    app_handle: Option<tauri::AppHandle>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let app_state = AppState {
         bot_state: Arc::new(RwLock::new(bot_state)),
         rag_state: Arc::new(RwLock::new(rag_state)),
+        app_handle,
     };
 
     let cors = CorsLayer::new()
@@ -199,6 +138,7 @@ pub async fn start_server(
     let app = Router::new()
         .route("/", get(health_check))
         .route("/whatsapp/process", post(handle_whatsapp_message))
+        .route("/whatsapp/process/stream", post(handle_whatsapp_stream))
         .layer(cors)
         .with_state(app_state);
 
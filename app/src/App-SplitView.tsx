@@ -113,6 +113,7 @@ interface Message {
   image?: string; // Base64 image data for displaying images
   platform?: string; // Platform where the message originated (telegram, discord, etc.)
   artifacts?: any[]; // Artifacts embedded in this message
+  metadata?: any; // ResponseMetadata of an assistant reply (model, tokens, timing)
   toolInvocations?: Array<{
     tool_name: string;
     arguments: Record<string, any>;
@@ -213,6 +214,7 @@ function MessageContentRenderer({ content, searchResults, artifacts, onFollowUpQ
         pageNumber: result.pageNumber,
         lineRange: result.lineRange,
         snippet: result.snippet || result.text?.substring(0, 150) || '',
+        origin: result.citation?.origin === 'web' ? 'web' : 'local',
       });
     });
 
@@ -640,6 +642,7 @@ function AppSplitView() {
         timestamp: m.timestamp,
         artifacts: m.artifacts,
         searchResults: m.searchResults,
+        metadata: m.metadata,
       }));
       setMessages(loaded);
     } else {
@@ -663,6 +666,7 @@ function AppSplitView() {
           timestamp: m.timestamp,
           artifacts: m.artifacts,
           searchResults: m.searchResults,
+          metadata: m.metadata,
         }))
     );
   }, [messages, updateActiveMessages]);
@@ -743,6 +747,9 @@ function AppSplitView() {
 
   // Derive system prompt and active space from the active conversation
   const activeSpaceId = sources.find(s => s.selected)?.id || null;
+  // Read by the drop and paste listeners, which are registered once
+  const activeSpaceIdRef = useRef(activeSpaceId);
+  activeSpaceIdRef.current = activeSpaceId;
   const activeSourceName = sources.find(s => s.selected)?.name || null;
   const spaceSystemPrompt = activeConversation?.systemPrompt || '';
 
@@ -1024,7 +1031,8 @@ function AppSplitView() {
           debugLog('[FILE PICKER] Invoking process_image_from_file...');
           // Process the image from file path
           const result = await invoke<any>('process_image_from_file', {
-            filePath: selected
+            filePath: selected,
+            spaceId: activeSpaceId
           });
           debugLog('[FILE PICKER] Got result:', result);
 
@@ -1105,7 +1113,8 @@ function AppSplitView() {
               if (imageFiles.length > 0) {
                 for (const imageFile of imageFiles) {
                   const result = await invoke<any>('process_image_from_file', {
-                    filePath: imageFile
+                    filePath: imageFile,
+                    spaceId: activeSpaceIdRef.current
                   });
 
                   const extractedText = result.extractedText || result.extracted_text || '';
@@ -1303,7 +1312,8 @@ function AppSplitView() {
 
             // Process the image with OCR
             const result = await invoke<any>('process_image_from_base64', {
-              imageData: base64Data
+              imageData: base64Data,
+              spaceId: activeSpaceIdRef.current
             });
 
             debugLog('📊 OCR Result received:', result);
@@ -2310,16 +2320,24 @@ function AppSplitView() {
       // Get current space ID
       const currentSpaceId = sources.find(s => s.selected)?.id || null;
 
-      // Build conversation history from messages
-      const conversationHistory = messages.slice(-10).map(msg => ({
+      // Build conversation history from messages; turns covered by the
+      // summary checkpoint are replaced by the summary
+      const checkpoint = activeConversation?.summaryCheckpoint;
+      const historyStart = checkpoint && checkpoint.coveredMessages <= messages.length
+        ? checkpoint.coveredMessages
+        : Math.max(messages.length - 10, 0);
+      const conversationHistory = messages.slice(historyStart).map(msg => ({
         role: msg.role,
         content: msg.content,
       }));
 
-      // Set up cancellation mechanism
+      // Set up cancellation mechanism; the backend stops generating (and
+      // aborts any external API request) for this request id
       let cancelled = false;
+      const requestId = crypto.randomUUID();
       currentOperationAbortRef.current = () => {
         cancelled = true;
+        invoke('cancel_generation', { requestId }).catch(err => debugLog('cancel_generation failed:', err));
         debugLog('🛑 Marked operation as cancelled');
       };
 
@@ -2344,12 +2362,14 @@ function AppSplitView() {
         context: {
           agent_id: activeAgentId || null,
           conversation_history: conversationHistory,
+          history_summary: historyStart === checkpoint?.coveredMessages ? checkpoint?.summary ?? null : null,
           space_id: currentSpaceId,
           conversation_id: null,
           user_info: null,
           variables: {},
           metadata: {},
           custom_system_prompt: spaceSystemPrompt || null,
+          request_id: requestId,
         }
       });
 
@@ -2437,6 +2457,7 @@ function AppSplitView() {
             content: finalContent + metadataFooter,
             artifacts: allMsgArtifacts,
             searchResults: responseObj.search_results || [],
+            metadata: responseObj.metadata,
           });
         }
         return [...prev, {
@@ -2446,6 +2467,7 @@ function AppSplitView() {
           timestamp: new Date().toISOString(),
           artifacts: allMsgArtifacts,
           searchResults: responseObj.search_results || [],
+          metadata: responseObj.metadata,
         }];
       });
 
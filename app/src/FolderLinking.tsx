@@ -52,6 +52,8 @@ interface IndexingResult {
   totalChunks?: number;
   failed_files?: string[];
   failedFiles?: string[];
+  files_skipped?: number;
+  filesSkipped?: number;
   duration: number;
 }
 
@@ -216,6 +218,7 @@ const FolderLinking: React.FC<FolderLinkingProps> = ({
         filesProcessed: result.filesProcessed || result.files_processed || 0,
         totalChunks: result.totalChunks || result.total_chunks || 0,
         failedFiles: result.failedFiles || result.failed_files || [],
+        filesSkipped: result.filesSkipped || result.files_skipped || 0,
         duration: result.duration || 0
       });
       setCurrentStep('complete');
@@ -573,6 +576,12 @@ const FolderLinking: React.FC<FolderLinkingProps> = ({
                 <div className="stat-value">{result.filesProcessed}</div>
                 <div className="stat-label">Files Processed</div>
               </div>
+              {!!result.filesSkipped && (
+                <div className="stat-card">
+                  <div className="stat-value">{result.filesSkipped}</div>
+                  <div className="stat-label">Unchanged (Skipped)</div>
+                </div>
+              )}
               <div className="stat-card">
                 <div className="stat-value">{result.totalChunks}</div>
                 <div className="stat-label">Chunks Created</div>
@@ -28,16 +28,41 @@ interface ModelProgress {
   percentage: number;
   downloaded_mb: number;
   total_mb: number;
+  downloaded_bytes: number;
+  total_bytes: number;
+  speed_mb_per_sec: number;
+  eta_seconds: number | null;
+  resumed: boolean;
+  is_verifying: boolean;
   is_complete: boolean;
   error?: string;
+  files: { filename: string; downloaded: number; total_size: number; is_complete: boolean }[];
 }
 
+interface ModelInspection {
+  version: number;
+  architecture?: string;
+  name?: string;
+  context_length?: number;
+  quantization?: string;
+  chat_template?: string;
+  vocab_size?: number;
+  tokenizer_model?: string;
+  supports_tool_calling: boolean;
+  warnings: string[];
+}
+
+const formatEta = (seconds: number) =>
+  seconds >= 3600
+    ? `${Math.floor(seconds / 3600)}h ${Math.floor((seconds % 3600) / 60)}m`
+    : seconds >= 60 ? `${Math.floor(seconds / 60)}m ${seconds % 60}s` : `${seconds}s`;
+
 interface LLMSettingsProps {
   onClose: () => void;
   onStatusChange?: () => void;
 }
 
-type Provider = 'openai' | 'anthropic' | 'openrouter' | 'kimi' | 'grok' | 'perplexity' | 'google' | 'baseten';
+type Provider = 'openai' | 'anthropic' | 'openrouter' | 'kimi' | 'grok' | 'perplexity' | 'google' | 'baseten' | 'replicate';
 
 const PROVIDERS: { id: Provider; label: string; defaultModel: string; keyPlaceholder: string; helpUrl: string; models: { value: string; label: string }[] }[] = [
   {
@@ -109,6 +134,13 @@ const PROVIDERS: { id: Provider; label: string; defaultModel: string; keyPlaceho
       { value: 'deepseek-ai/DeepSeek-V3-0324', label: 'DeepSeek V3' },
     ],
   },
+  {
+    id: 'replicate', label: 'Replicate', defaultModel: 'meta/meta-llama-3-8b-instruct', keyPlaceholder: 'r8_...', helpUrl: 'replicate.com',
+    models: [
+      { value: 'meta/meta-llama-3-8b-instruct', label: 'Llama 3 8B Instruct' },
+      { value: 'meta/meta-llama-3-70b-instruct', label: 'Llama 3 70B Instruct' },
+    ],
+  },
 ];
 
 export default function LLMSettings({ onClose, onStatusChange }: LLMSettingsProps) {
@@ -117,7 +149,7 @@ export default function LLMSettings({ onClose, onStatusChange }: LLMSettingsProp
   const [inferenceBackend, setInferenceBackend] = useState<'onnx' | 'llamacpp'>('llamacpp');
   const [selectedProvider, setSelectedProvider] = useState<Provider>('openai');
   const [apiKeys, setApiKeys] = useState<Record<Provider, string>>({
-    openai: '', anthropic: '', openrouter: '', kimi: '', grok: '', perplexity: '', google: '', baseten: '',
+    openai: '', anthropic: '', openrouter: '', kimi: '', grok: '', perplexity: '', google: '', baseten: '', replicate: '',
   });
   const [externalModel, setExternalModel] = useState('grok-2-1212');
   const [llmInfo, setLlmInfo] = useState<LLMInfo | null>(null);
@@ -125,6 +157,7 @@ export default function LLMSettings({ onClose, onStatusChange }: LLMSettingsProp
   const [downloadProgress, setDownloadProgress] = useState<ModelProgress | null>(null);
   const [customModelPath, setCustomModelPath] = useState<string | null>(null);
   const [customTokenizerPath, setCustomTokenizerPath] = useState<string | null>(null);
+  const [modelInspection, setModelInspection] = useState<ModelInspection | null>(null);
   const [temperature, setTemperature] = useState(0.7);
   const [maxTokens, setMaxTokens] = useState(1024);
   const [topP, setTopP] = useState(0.95);
@@ -159,8 +192,16 @@ export default function LLMSettings({ onClose, onStatusChange }: LLMSettingsProp
     try {
       const modelPath = await invoke<string>('browse_model_file', { backend: inferenceBackend });
       if (modelPath) {
+        // Inspect first: selecting a GGUF model resizes the context window
+        // it would be checked against
+        let inspection: ModelInspection | null = null;
+        if (modelPath.toLowerCase().endsWith('.gguf')) {
+          inspection = await invoke<ModelInspection>('inspect_model_file', { path: modelPath });
+        }
         await invoke<string>('set_custom_model_path', { modelPath });
         setCustomModelPath(modelPath);
+        setModelInspection(inspection);
+        inspection?.warnings.forEach(w => notify.warning(w));
         if (inferenceBackend === 'onnx') {
           try {
             const modelDir = modelPath.substring(0, modelPath.lastIndexOf('\\'));
@@ -526,6 +567,24 @@ export default function LLMSettings({ onClose, onStatusChange }: LLMSettingsProp
                     {inferenceBackend === 'onnx' && (
                       <FileRow label="Tokenizer" path={customTokenizerPath} fallback={customModelPath ? 'Not found (select manually)' : 'Auto-detects from model folder'} colors={colors} />
                     )}
+                    {modelInspection && (
+                      <div style={{ fontSize: '11px', color: colors.textMuted, display: 'flex', flexDirection: 'column', gap: '4px' }}>
+                        <div>
+                          {[
+                            modelInspection.architecture,
+                            modelInspection.quantization,
+                            modelInspection.context_length && `${modelInspection.context_length.toLocaleString()} ctx`,
+                            modelInspection.vocab_size && `${modelInspection.vocab_size.toLocaleString()} tokens`,
+                            modelInspection.supports_tool_calling ? 'tool calling' : 'no tool calling',
+                          ].filter(Boolean).join(' · ')}
+                        </div>
+                        {modelInspection.warnings.map(w => (
+                          <div key={w} style={{ display: 'flex', alignItems: 'flex-start', gap: '6px', color: colors.warning }}>
+                            <AlertCircle size={12} style={{ marginTop: '1px', flexShrink: 0 }} /> {w}
+                          </div>
+                        ))}
+                      </div>
+                    )}
                   </div>
 
                   {/* Action buttons */}
@@ -585,15 +644,32 @@ export default function LLMSettings({ onClose, onStatusChange }: LLMSettingsProp
                 {downloadProgress && !downloadProgress.is_complete && (
                   <div style={{ marginTop: '14px', padding: '12px', background: colors.bgSecondary, borderRadius: '6px', border: `1px solid ${colors.border}` }}>
                     <div style={{ display: 'flex', justifyContent: 'space-between', marginBottom: '6px', fontSize: '13px', color: colors.text }}>
-                      <span>Downloading {downloadProgress.model}...</span>
+                      <span>
+                        {downloadProgress.is_verifying
+                          ? `Verifying ${downloadProgress.model}...`
+                          : `${downloadProgress.resumed ? 'Resuming' : 'Downloading'} ${downloadProgress.model}...`}
+                      </span>
                       <span>{downloadProgress.percentage.toFixed(1)}%</span>
                     </div>
                     <div style={{ height: '4px', background: colors.bgTertiary, borderRadius: '2px', overflow: 'hidden', marginBottom: '4px' }}>
                       <div style={{ height: '100%', background: colors.primary, width: `${downloadProgress.percentage}%`, transition: 'width 0.3s' }} />
                     </div>
-                    <div style={{ fontSize: '11px', color: colors.textMuted }}>
-                      {downloadProgress.downloaded_mb} MB / {downloadProgress.total_mb} MB
+                    <div style={{ display: 'flex', justifyContent: 'space-between', fontSize: '11px', color: colors.textMuted }}>
+                      <span>
+                        {downloadProgress.downloaded_mb} MB / {downloadProgress.total_mb} MB
+                        {downloadProgress.files.length > 1 &&
+                          ` · ${downloadProgress.files.filter(f => f.is_complete).length}/${downloadProgress.files.length} files`}
+                      </span>
+                      {!downloadProgress.is_verifying && downloadProgress.speed_mb_per_sec > 0 && (
+                        <span>
+                          {downloadProgress.speed_mb_per_sec.toFixed(1)} MB/s
+                          {downloadProgress.eta_seconds != null && ` · ${formatEta(downloadProgress.eta_seconds)} left`}
+                        </span>
+                      )}
                     </div>
+                    {downloadProgress.error && (
+                      <div style={{ fontSize: '11px', color: colors.error, marginTop: '4px' }}>{downloadProgress.error}</div>
+                    )}
                   </div>
                 )}
               </div>
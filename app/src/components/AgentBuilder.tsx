@@ -28,6 +28,8 @@ interface AgentConfig {
   timeout_seconds: number;
   auto_use_rag: boolean;
   rag_top_k: number;
+  max_total_tokens?: number | null;
+  max_wall_clock_secs?: number | null;
 }
 
 interface AgentBuilderProps {
@@ -398,6 +400,36 @@ export const AgentBuilder: React.FC<AgentBuilderProps> = ({
                         />
                       </div>
 
+                      {/* Token Budget */}
+                      <div>
+                        <label className="text-[10px] font-medium mb-1 block" style={{ color: colors.textMuted }}>
+                          Token Budget (optional)
+                        </label>
+                        <input
+                          type="number"
+                          placeholder="Unlimited"
+                          value={config.max_total_tokens ?? ''}
+                          onChange={(e) => setConfig(c => ({ ...c, max_total_tokens: parseInt(e.target.value) || null }))}
+                          className="w-full px-2 py-1.5 rounded text-xs outline-none"
+                          style={inputStyle}
+                        />
+                      </div>
+
+                      {/* Wall-clock Budget */}
+                      <div>
+                        <label className="text-[10px] font-medium mb-1 block" style={{ color: colors.textMuted }}>
+                          Time Budget (seconds, optional)
+                        </label>
+                        <input
+                          type="number"
+                          placeholder="Unlimited"
+                          value={config.max_wall_clock_secs ?? ''}
+                          onChange={(e) => setConfig(c => ({ ...c, max_wall_clock_secs: parseInt(e.target.value) || null }))}
+                          className="w-full px-2 py-1.5 rounded text-xs outline-none"
+                          style={inputStyle}
+                        />
+                      </div>
+
                       {/* RAG Top K */}
                       <div>
                         <label className="text-[10px] font-medium mb-1 block" style={{ color: colors.textMuted }}>
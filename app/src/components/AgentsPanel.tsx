@@ -5,6 +5,7 @@ import {
   ChevronDown, ChevronRight, Wrench, Zap, ToggleLeft, ToggleRight,
   RefreshCw, Loader2, Circle, Play, Search, FileText, Code, Brain,
   Sparkles, Wand2, Plus, Trash2, Send, Edit3, MessageSquare, ArrowRight, Users, Crown, GitBranch, ListOrdered,
+  Layers, MessagesSquare,
 } from 'lucide-react';
 import { invoke } from '@tauri-apps/api/core';
 import { useTheme } from '../contexts/ThemeContext';
@@ -64,6 +65,7 @@ interface AgentDashboard {
   total_runs: number;
   successful_runs: number;
   failed_runs: number;
+  budget_exceeded_runs: number;
   success_rate: number;
   active_now: number;
   recent_executions: ExecutionLogEntry[];
@@ -106,6 +108,8 @@ interface AgentDefinition {
     timeout_seconds: number;
     auto_use_rag: boolean;
     rag_top_k: number;
+    max_total_tokens?: number | null;
+    max_wall_clock_secs?: number | null;
   };
   capabilities: string[];
   tools: string[];
@@ -137,6 +141,7 @@ interface CrewDefinition {
   agents: CrewMember[];
   process: string;
   coordinator_id?: string;
+  consensus_rounds?: number;
   config: { timeout_seconds: number; verbose: boolean };
 }
 
@@ -153,6 +158,7 @@ interface CrewExecutionResult {
   agent_outputs: CrewAgentOutput[];
   execution_time_ms: number;
   error: string | null;
+  rounds?: CrewAgentOutput[][];
 }
 
 interface CrewAgentOutput {
@@ -390,7 +396,7 @@ export default function AgentsPanel({ onRunAgent }: AgentsPanelProps = {}) {
         </div>
 
         {/* Summary Cards */}
-        <div className="grid grid-cols-5 gap-3">
+        <div className="grid grid-cols-6 gap-3">
           {[
             {
               label: 'Agents',
@@ -422,6 +428,12 @@ export default function AgentsPanel({ onRunAgent }: AgentsPanelProps = {}) {
               icon: XCircle,
               color: colors.error,
             },
+            {
+              label: 'Over Budget',
+              value: dashboard?.budget_exceeded_runs ?? 0,
+              icon: AlertTriangle,
+              color: colors.warning,
+            },
           ].map(card => {
             const Icon = card.icon;
             return (
@@ -790,7 +802,10 @@ export default function AgentsPanel({ onRunAgent }: AgentsPanelProps = {}) {
                             <span>{crew.agents.length} agents</span>
                             <span>|</span>
                             <span className="flex items-center gap-1">
-                              {crew.process === 'hierarchical' ? <GitBranch className="w-3 h-3" /> : <ListOrdered className="w-3 h-3" />}
+                              {crew.process === 'hierarchical' ? <GitBranch className="w-3 h-3" />
+                                : crew.process === 'parallel' ? <Layers className="w-3 h-3" />
+                                : crew.process === 'consensus' ? <MessagesSquare className="w-3 h-3" />
+                                : <ListOrdered className="w-3 h-3" />}
                               {crew.process}
                             </span>
                           </div>
@@ -913,6 +928,8 @@ export default function AgentsPanel({ onRunAgent }: AgentsPanelProps = {}) {
                         <span className="flex items-center gap-1">
                           {exec.status === 'completed' && exec.success ? (
                             <CheckCircle2 className="w-3 h-3" style={{ color: '#10b981' }} />
+                          ) : exec.status === 'budget_exceeded' ? (
+                            <AlertTriangle className="w-3 h-3" style={{ color: colors.warning }} />
                           ) : exec.status === 'failed' || !exec.success ? (
                             <XCircle className="w-3 h-3" style={{ color: colors.error }} />
                           ) : exec.status === 'running' ? (
@@ -923,10 +940,12 @@ export default function AgentsPanel({ onRunAgent }: AgentsPanelProps = {}) {
                           <span
                             className="text-[10px]"
                             style={{
-                              color: exec.success ? '#10b981' : colors.error,
+                              color: exec.success
+                                ? '#10b981'
+                                : exec.status === 'budget_exceeded' ? colors.warning : colors.error,
                             }}
                           >
-                            {exec.status}
+                            {exec.status === 'budget_exceeded' ? 'over budget' : exec.status}
                           </span>
                         </span>
                         <span className="font-mono text-[10px]" style={{ color: colors.textMuted }}>
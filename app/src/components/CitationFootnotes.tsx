@@ -1,5 +1,5 @@
 import React, { useState } from 'react';
-import { FileText, ExternalLink, ChevronDown, ChevronUp, Layers } from 'lucide-react';
+import { FileText, ExternalLink, ChevronDown, ChevronUp, Layers, Globe } from 'lucide-react';
 import { useTheme } from '../contexts/ThemeContext';
 import { invoke } from '@tauri-apps/api/core';
 
@@ -14,6 +14,8 @@ interface CitationInfo {
   pageNumber?: number;
   lineRange?: [number, number];
   snippet: string;
+  /** 'web' for pages found by an online model rather than indexed documents */
+  origin?: 'local' | 'web';
 }
 
 interface CitationFootnotesProps {
@@ -64,6 +66,14 @@ export const CitationFootnotes: React.FC<CitationFootnotesProps> = ({ citations,
     return parts[parts.length - 1];
   };
 
+  const getHostName = (url: string) => {
+    try {
+      return new URL(url).hostname;
+    } catch {
+      return url;
+    }
+  };
+
   const getLocationText = (citation: CitationInfo) => {
     if (citation.lineRange) return `L${citation.lineRange[0]}-${citation.lineRange[1]}`;
     if (citation.pageNumber) return `p.${citation.pageNumber}`;
@@ -94,7 +104,8 @@ export const CitationFootnotes: React.FC<CitationFootnotesProps> = ({ citations,
       <div style={{ display: 'flex', flexDirection: 'column', gap: '4px' }}>
         {citations.map((citation) => {
           const isExpanded = expandedId === citation.number;
-          const fileName = getFileName(citation.sourceFile);
+          const isWeb = citation.origin === 'web';
+          const fileName = isWeb ? getHostName(citation.sourceFile) : getFileName(citation.sourceFile);
           const location = getLocationText(citation);
 
           return (
@@ -129,7 +140,7 @@ export const CitationFootnotes: React.FC<CitationFootnotesProps> = ({ citations,
                     minWidth: '16px',
                     height: '16px',
                     borderRadius: '4px',
-                    backgroundColor: colors.primary,
+                    backgroundColor: isWeb ? '#0ea5e9' : colors.primary,
                     color: '#ffffff',
                     fontSize: '9px',
                     fontWeight: 700,
@@ -139,6 +150,13 @@ export const CitationFootnotes: React.FC<CitationFootnotesProps> = ({ citations,
                   {citation.number}
                 </span>
 
+                {/* Web sources come from search, not the user's documents */}
+                {isWeb && (
+                  <span title="Web source" style={{ display: 'flex', color: '#0ea5e9', flexShrink: 0 }}>
+                    <Globe size={11} />
+                  </span>
+                )}
+
                 {/* Title — truncated */}
                 <span
                   style={{
@@ -173,7 +191,7 @@ export const CitationFootnotes: React.FC<CitationFootnotesProps> = ({ citations,
                 </span>
 
                 {/* View in Artifacts button */}
-                {onViewInArtifact && (
+                {onViewInArtifact && !isWeb && (
                   <button
                     onClick={(e) => handleViewInArtifact(citation, e)}
                     style={{
@@ -256,7 +274,7 @@ export const CitationFootnotes: React.FC<CitationFootnotesProps> = ({ citations,
                   )}
 
                   {/* View in Artifacts — expanded action */}
-                  {onViewInArtifact && (
+                  {onViewInArtifact && !isWeb && (
                     <button
                       onClick={(e) => handleViewInArtifact(citation, e)}
                       style={{
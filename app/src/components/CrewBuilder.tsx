@@ -2,7 +2,7 @@ import React, { useState, useEffect } from 'react';
 import { motion, AnimatePresence } from 'framer-motion';
 import {
   X, Users, ArrowRight, GripVertical, Plus, Trash2,
-  GitBranch, ListOrdered, Crown, Save, AlertCircle,
+  GitBranch, ListOrdered, Crown, Save, AlertCircle, Layers, MessagesSquare,
 } from 'lucide-react';
 import { invoke } from '@tauri-apps/api/core';
 import { useTheme } from '../contexts/ThemeContext';
@@ -28,12 +28,15 @@ interface CrewDefinition {
   agents: CrewMember[];
   process: string;
   coordinator_id?: string;
+  consensus_rounds?: number;
   config: {
     timeout_seconds: number;
     verbose: boolean;
   };
 }
 
+type CrewProcess = 'sequential' | 'hierarchical' | 'parallel' | 'consensus';
+
 interface CrewBuilderProps {
   isOpen: boolean;
   onClose: () => void;
@@ -45,7 +48,8 @@ export function CrewBuilder({ isOpen, onClose, onCrewCreated, editingCrew }: Cre
   const { colors } = useTheme();
   const [name, setName] = useState('');
   const [description, setDescription] = useState('');
-  const [process, setProcess] = useState<'sequential' | 'hierarchical'>('sequential');
+  const [process, setProcess] = useState<CrewProcess>('sequential');
+  const [consensusRounds, setConsensusRounds] = useState(2);
   const [members, setMembers] = useState<CrewMember[]>([]);
   const [coordinatorId, setCoordinatorId] = useState<string>('');
   const [timeout, setTimeout] = useState(300);
@@ -67,7 +71,8 @@ export function CrewBuilder({ isOpen, onClose, onCrewCreated, editingCrew }: Cre
     if (editingCrew) {
       setName(editingCrew.name);
       setDescription(editingCrew.description);
-      setProcess(editingCrew.process as 'sequential' | 'hierarchical');
+      setProcess(editingCrew.process as CrewProcess);
+      setConsensusRounds(editingCrew.consensus_rounds ?? 2);
       setMembers(editingCrew.agents);
       setCoordinatorId(editingCrew.coordinator_id || '');
       setTimeout(editingCrew.config.timeout_seconds);
@@ -75,6 +80,7 @@ export function CrewBuilder({ isOpen, onClose, onCrewCreated, editingCrew }: Cre
       setName('');
       setDescription('');
       setProcess('sequential');
+      setConsensusRounds(2);
       setMembers([]);
       setCoordinatorId('');
       setTimeout(300);
@@ -128,6 +134,7 @@ export function CrewBuilder({ isOpen, onClose, onCrewCreated, editingCrew }: Cre
         agents: members,
         process,
         coordinator_id: process === 'hierarchical' ? coordinatorId : undefined,
+        consensus_rounds: process === 'consensus' ? consensusRounds : undefined,
         config: { timeout_seconds: timeout, verbose: false },
       };
 
@@ -207,34 +214,45 @@ export function CrewBuilder({ isOpen, onClose, onCrewCreated, editingCrew }: Cre
             {/* Process Type */}
             <div>
               <label className="text-xs font-medium mb-2 block" style={{ color: colors.textMuted }}>Execution Process</label>
-              <div className="flex gap-2">
-                <button
-                  onClick={() => setProcess('sequential')}
-                  className="flex-1 flex items-center gap-2 px-3 py-2.5 rounded-lg text-xs font-medium transition-all"
-                  style={{
-                    backgroundColor: process === 'sequential' ? `${colors.primary}15` : colors.bgSecondary,
-                    color: process === 'sequential' ? colors.primary : colors.textMuted,
-                    border: `1px solid ${process === 'sequential' ? colors.primary : colors.border}`,
-                  }}
-                >
-                  <ListOrdered className="w-4 h-4" />
-                  Sequential
-                  <span className="text-[10px] opacity-70">A → B → C</span>
-                </button>
-                <button
-                  onClick={() => setProcess('hierarchical')}
-                  className="flex-1 flex items-center gap-2 px-3 py-2.5 rounded-lg text-xs font-medium transition-all"
-                  style={{
-                    backgroundColor: process === 'hierarchical' ? `${colors.primary}15` : colors.bgSecondary,
-                    color: process === 'hierarchical' ? colors.primary : colors.textMuted,
-                    border: `1px solid ${process === 'hierarchical' ? colors.primary : colors.border}`,
-                  }}
-                >
-                  <GitBranch className="w-4 h-4" />
-                  Hierarchical
-                  <span className="text-[10px] opacity-70">Coordinator delegates</span>
-                </button>
+              <div className="grid grid-cols-2 gap-2">
+                {([
+                  { value: 'sequential', label: 'Sequential', hint: 'A → B → C', Icon: ListOrdered },
+                  { value: 'hierarchical', label: 'Hierarchical', hint: 'Coordinator delegates', Icon: GitBranch },
+                  { value: 'parallel', label: 'Parallel', hint: 'All at once, merged', Icon: Layers },
+                  { value: 'consensus', label: 'Consensus', hint: 'Revise over rounds', Icon: MessagesSquare },
+                ] as const).map(({ value, label, hint, Icon }) => (
+                  <button
+                    key={value}
+                    onClick={() => setProcess(value)}
+                    className="flex items-center gap-2 px-3 py-2.5 rounded-lg text-xs font-medium transition-all"
+                    style={{
+                      backgroundColor: process === value ? `${colors.primary}15` : colors.bgSecondary,
+                      color: process === value ? colors.primary : colors.textMuted,
+                      border: `1px solid ${process === value ? colors.primary : colors.border}`,
+                    }}
+                  >
+                    <Icon className="w-4 h-4" />
+                    {label}
+                    <span className="text-[10px] opacity-70">{hint}</span>
+                  </button>
+                ))}
               </div>
+              {process === 'consensus' && (
+                <div className="mt-3">
+                  <label className="text-xs font-medium mb-1 block" style={{ color: colors.textMuted }}>
+                    Rounds: {consensusRounds}
+                  </label>
+                  <input
+                    type="range"
+                    min={1}
+                    max={5}
+                    step={1}
+                    value={consensusRounds}
+                    onChange={e => setConsensusRounds(Number(e.target.value))}
+                    className="w-full"
+                  />
+                </div>
+              )}
             </div>
 
             {/* Members */}
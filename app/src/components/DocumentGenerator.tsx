@@ -21,7 +21,7 @@ import { useTheme } from "../contexts/ThemeContext";
 import SmartTemplates from "./SmartTemplates";
 
 type GenerationStep = 'input' | 'searching' | 'sources' | 'generating' | 'preview';
-type OutputFormat = 'md' | 'pdf' | 'docx' | 'html' | 'txt' | 'xlsx' | 'json';
+type OutputFormat = 'md' | 'pdf' | 'docx' | 'pptx' | 'html' | 'txt' | 'xlsx' | 'json';
 type DocumentLength = 'Brief' | 'Standard' | 'Detailed' | 'Maximum';
 
 interface Source {
@@ -70,6 +70,7 @@ const FORMAT_OPTIONS: { value: OutputFormat; label: string }[] = [
   { value: 'md', label: 'MD' },
   { value: 'pdf', label: 'PDF' },
   { value: 'docx', label: 'DOCX' },
+  { value: 'pptx', label: 'PPTX' },
   { value: 'html', label: 'HTML' },
   { value: 'txt', label: 'TXT' },
   { value: 'xlsx', label: 'XLSX' },
@@ -83,10 +84,11 @@ const MIME_TYPES: Record<string, string> = {
   json: 'application/json',
   pdf: 'application/pdf',
   docx: 'application/vnd.openxmlformats-officedocument.wordprocessingml.document',
+  pptx: 'application/vnd.openxmlformats-officedocument.presentationml.presentation',
   xlsx: 'application/vnd.openxmlformats-officedocument.spreadsheetml.sheet',
 };
 
-const BINARY_FORMATS = new Set(['pdf', 'docx', 'xlsx']);
+const BINARY_FORMATS = new Set(['pdf', 'docx', 'pptx', 'xlsx']);
 
 export default function DocumentGenerator() {
   const { colors } = useTheme();
@@ -44,11 +44,14 @@ export interface Artifact {
   editable: boolean;
   version: number;
   created_at: string;
+  /** False when the backend found the SVG/Mermaid content malformed */
+  valid?: boolean;
   metadata?: {
     filePath?: string;
     pageNumber?: number;
     snippet?: string;
     lineRange?: [number, number];
+    validation_error?: string;
   };
 }
 
@@ -63,7 +63,8 @@ export function MermaidArtifact({
       console.error('❌ Mermaid rendering error:', err);
       console.error('   Error message:', err?.message);
       console.error('   Error details:', err);
-      setError(err?.message || 'Failed to render diagram');
+      const validationError = artifact.valid === false ? artifact.metadata?.validation_error : undefined;
+      setError(validationError || err?.message || 'Failed to render diagram');
     }
   };
 
@@ -10,6 +10,7 @@ export interface PermissionRequest {
   reason: string;
   agent_id: string;
   agent_name?: string;
+  preview?: string;  // e.g. the diff a write_file would apply
 }
 
 interface PermissionDialogProps {
@@ -193,6 +194,33 @@ export function PermissionDialog({ isOpen, request, onDecision, onClose }: Permi
               </div>
             )}
 
+            {/* Preview of the change */}
+            {request.preview && (
+              <div>
+                <label className="block text-xs font-semibold mb-2 uppercase tracking-wide" style={{ color: colors.textMuted }}>
+                  Preview
+                </label>
+                <pre className="p-3 rounded-lg font-mono text-xs overflow-auto max-h-64 whitespace-pre" style={{
+                  background: colors.bgTertiary,
+                  color: colors.text,
+                  border: `1px solid ${colors.border}`,
+                }}>
+                  {request.preview.split('\n').map((line, i) => (
+                    <div
+                      key={i}
+                      style={{
+                        color: line.startsWith('+') && !line.startsWith('+++') ? colors.success
+                          : line.startsWith('-') && !line.startsWith('---') ? colors.error
+                          : undefined,
+                      }}
+                    >
+                      {line || ' '}
+                    </div>
+                  ))}
+                </pre>
+              </div>
+            )}
+
             {/* Warning for dangerous operations */}
             {isDangerous && (
               <div className="flex items-start gap-3 p-4 rounded-lg border-2" style={{
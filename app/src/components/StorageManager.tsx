@@ -120,8 +120,11 @@ export function StorageManager() {
   const handleOptimizeStorage = async () => {
     setLoading(true);
     try {
-      await invoke('optimize_storage');
-      showSuccessAnimation('Storage optimized!');
+      const report = await invoke<{ bytesReclaimed: number; orphanedChunksRemoved: number }>('optimize_storage');
+      const mb = (report.bytesReclaimed / (1024 * 1024)).toFixed(1);
+      showSuccessAnimation(
+        `Reclaimed ${mb} MB, removed ${report.orphanedChunksRemoved.toLocaleString()} orphaned chunks`
+      );
       await loadStorageData();
     } catch (error) {
       console.error('Failed to optimize storage:', error);
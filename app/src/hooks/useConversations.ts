@@ -1,5 +1,6 @@
 import { useState, useCallback, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { notify } from '../lib/notify';
 
 export interface ConversationMessage {
@@ -9,6 +10,8 @@ export interface ConversationMessage {
   timestamp: string;
   artifacts?: any[];
   searchResults?: any[];
+  artifactSource?: string;
+  metadata?: any;
 }
 
 export interface Conversation {
@@ -21,6 +24,17 @@ export interface Conversation {
   spaceId?: string;
   spaceName?: string;
   systemPrompt?: string;
+  parentConversationId?: string;
+  forkMessageId?: string;
+  titleSource?: 'placeholder' | 'generated' | 'user';
+  /** Summary of the first `coveredMessages` messages, written by the backend */
+  summaryCheckpoint?: SummaryCheckpoint;
+}
+
+export interface SummaryCheckpoint {
+  summary: string;
+  coveredMessages: number;
+  updatedAt: string;
 }
 
 function generateId(): string {
@@ -84,6 +98,40 @@ export function useConversations() {
       });
   }, []);
 
+  // Titles generated by the LLM after the first exchange
+  useEffect(() => {
+    const unlisten = listen<{ conversationId: string; title: string; titleSource: Conversation['titleSource'] }>(
+      'conversation_title_updated',
+      ({ payload }) => {
+        setConversations(prev =>
+          prev.map(c =>
+            c.id === payload.conversationId ? { ...c, title: payload.title, titleSource: payload.titleSource } : c
+          )
+        );
+      }
+    );
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
+  // Summary checkpoints written after long conversations are saved
+  useEffect(() => {
+    const unlisten = listen<{ conversationId: string; summaryCheckpoint: SummaryCheckpoint }>(
+      'conversation_summary_updated',
+      ({ payload }) => {
+        setConversations(prev =>
+          prev.map(c =>
+            c.id === payload.conversationId ? { ...c, summaryCheckpoint: payload.summaryCheckpoint } : c
+          )
+        );
+      }
+    );
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
   const activeConversation = conversations.find(c => c.id === activeConversationId) || null;
 
   // Debounced save
@@ -152,7 +200,7 @@ export function useConversations() {
 
   const renameConversation = useCallback((id: string, title: string) => {
     setConversations(prev =>
-      prev.map(c => (c.id === id ? { ...c, title, updatedAt: new Date().toISOString() } : c))
+      prev.map(c => (c.id === id ? { ...c, title, titleSource: 'user', updatedAt: new Date().toISOString() } : c))
     );
     invoke('rename_conversation', { conversationId: id, newTitle: title }).catch(console.error);
     notify.success('Conversation renamed');
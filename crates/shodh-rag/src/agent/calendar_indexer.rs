@@ -14,6 +14,10 @@ use crate::rag_engine::RAGEngine;
 use crate::types::{Citation, DocumentFormat};
 use super::calendar_tools::{TodoItem, CalendarEvent};
 
+/// Space calendar items are indexed under. It is not a user space, so it
+/// never shows up in `SpaceManager`.
+pub const CALENDAR_SPACE_ID: &str = "calendar";
+
 /// Compose rich searchable text for a task.
 ///
 /// Includes a structured context prefix (similar to contextual chunking)
@@ -149,7 +149,7 @@ impl CalendarStore {
             let rag = rag.clone();
             tokio::spawn(async move {
                 let mut engine = rag.write().await;
-                if let Err(e) = super::calendar_indexer::index_task(&mut engine, &task, "calendar").await {
+                if let Err(e) = super::calendar_indexer::index_task(&mut engine, &task, super::calendar_indexer::CALENDAR_SPACE_ID).await {
                     tracing::warn!(task_id = %task.id, error = %e, "Failed to index task in RAG");
                 }
             });
@@ -162,7 +162,7 @@ impl CalendarStore {
             let rag = rag.clone();
             tokio::spawn(async move {
                 let mut engine = rag.write().await;
-                if let Err(e) = super::calendar_indexer::index_event(&mut engine, &event, "calendar").await {
+                if let Err(e) = super::calendar_indexer::index_event(&mut engine, &event, super::calendar_indexer::CALENDAR_SPACE_ID).await {
                     tracing::warn!(event_id = %event.id, error = %e, "Failed to index event in RAG");
                 }
             });
@@ -332,10 +332,12 @@ impl AgentTool for CreateTaskTool {
             title, priority, due_str
         );
 
+        let data = serde_json::to_value(&task)?;
         Ok(ToolResult {
             success: true,
             output,
-            data: serde_json::to_value(&task)?,
+            structured: Some(data.clone()),
+            data,
             error: None,
         })
     }
@@ -437,10 +439,12 @@ impl AgentTool for CreateEventTool {
             title, start_time
         );
 
+        let data = serde_json::to_value(&event)?;
         Ok(ToolResult {
             success: true,
             output,
-            data: serde_json::to_value(&event)?,
+            structured: Some(data.clone()),
+            data,
             error: None,
         })
     }
@@ -503,6 +507,7 @@ impl AgentTool for ListTasksTool {
                 output: "No tasks found matching the criteria.".to_string(),
                 data: serde_json::json!([]),
                 error: None,
+                structured: Some(serde_json::json!([])),
             });
         }
 
@@ -523,10 +528,12 @@ impl AgentTool for ListTasksTool {
             .map(|t| serde_json::to_value(t).unwrap_or_default())
             .collect();
 
+        let data = serde_json::json!(data);
         Ok(ToolResult {
             success: true,
             output,
-            data: serde_json::json!(data),
+            structured: Some(data.clone()),
+            data,
             error: None,
         })
     }
@@ -5,13 +5,20 @@
 
 use anyhow::{Result, Context as AnyhowContext, anyhow};
 use serde::{Deserialize, Serialize};
-use std::process::{Command, Stdio};
+use std::process::Stdio;
 use std::time::{Duration, Instant};
 use std::collections::HashMap;
+use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::process::Command as AsyncCommand;
+use tokio::sync::watch;
+
+/// How long to keep reading output once the child has exited or been
+/// killed. A grandchild outside the killed process group can hold the pipes
+/// open indefinitely; after this the readers stop with what they have.
+const PIPE_DRAIN_GRACE: Duration = Duration::from_secs(2);
 
 /// Programming language for code generation
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum CodeLanguage {
     Python,
     TypeScript,
@@ -24,17 +31,46 @@ pub enum CodeLanguage {
     PHP,
     Kotlin,
     Swift,
+    Bash,
+}
+
+impl CodeLanguage {
+    /// Language from a name ("python", "rust") or a file name / extension
+    /// ("main.rs", "js") as found on code artifacts
+    pub fn from_hint(hint: &str) -> Option<Self> {
+        let hint = hint.trim().to_lowercase();
+        let key = hint.rsplit_once('.').map(|(_, ext)| ext).unwrap_or(&hint);
+        match key {
+            "python" | "py" | "python3" => Some(Self::Python),
+            "typescript" | "ts" | "deno" => Some(Self::TypeScript),
+            "javascript" | "js" | "mjs" | "cjs" | "node" => Some(Self::JavaScript),
+            "rust" | "rs" => Some(Self::Rust),
+            "java" => Some(Self::Java),
+            "csharp" | "c#" | "cs" | "csx" => Some(Self::CSharp),
+            "go" | "golang" => Some(Self::Go),
+            "ruby" | "rb" => Some(Self::Ruby),
+            "php" => Some(Self::PHP),
+            "kotlin" | "kt" | "kts" => Some(Self::Kotlin),
+            "swift" => Some(Self::Swift),
+            "bash" | "sh" | "shell" | "zsh" => Some(Self::Bash),
+            _ => None,
+        }
+    }
 }
 
 /// Configuration for code execution
 #[derive(Debug, Clone)]
 pub struct ExecutionConfig {
-    /// Maximum execution time
-    pub timeout: Duration,
+    /// Maximum execution time before the process is killed
+    pub timeout_secs: u64,
 
-    /// Maximum memory usage (MB)
+    /// Maximum memory usage (MB). Enforced with `setrlimit` on Unix and a
+    /// Job Object on Windows.
     pub max_memory_mb: usize,
 
+    /// Maximum bytes captured from each of stdout and stderr
+    pub max_output_bytes: usize,
+
     /// Allow network access
     pub allow_network: bool,
 
@@ -48,8 +84,9 @@ pub struct ExecutionConfig {
 impl Default for ExecutionConfig {
     fn default() -> Self {
         Self {
-            timeout: Duration::from_secs(30),
+            timeout_secs: 30,
             max_memory_mb: 256,
+            max_output_bytes: 1024 * 1024,
             allow_network: false,
             allow_filesystem: false,
             working_dir: None,
@@ -57,6 +94,12 @@ impl Default for ExecutionConfig {
     }
 }
 
+impl ExecutionConfig {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+}
+
 /// Result of code execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodeExecutionResult {
@@ -77,6 +120,14 @@ pub struct CodeExecutionResult {
 
     /// Error message if failed
     pub error: Option<String>,
+
+    /// Process was killed for exceeding the timeout
+    #[serde(default)]
+    pub killed_by_limit: bool,
+
+    /// Stdout or stderr was cut at `max_output_bytes`
+    #[serde(default)]
+    pub output_truncated: bool,
 }
 
 /// Code executor for running AI-generated scripts
@@ -150,6 +201,27 @@ impl CodeExecutor {
         self.execute_code(code, CodeLanguage::Swift).await
     }
 
+    /// Execute Bash script
+    pub async fn execute_bash(&self, code: &str) -> Result<CodeExecutionResult> {
+        self.execute_code(code, CodeLanguage::Bash).await
+    }
+
+    /// Execute code whose language may be unknown. Falls back to the
+    /// artifact hint (language name or file name), then to detection from
+    /// the source; ambiguous detection is an error.
+    pub async fn execute_detected(
+        &self,
+        code: &str,
+        language: Option<CodeLanguage>,
+        hint: Option<&str>,
+    ) -> Result<CodeExecutionResult> {
+        let language = match language {
+            Some(language) => language,
+            None => resolve_language(code, hint)?,
+        };
+        self.execute_code(code, language).await
+    }
+
     /// Execute code in specified language
     pub async fn execute_code(&self, code: &str, language: CodeLanguage) -> Result<CodeExecutionResult> {
         let start_time = Instant::now();
@@ -253,6 +325,8 @@ edition = "2021"
                         exit_code: compile_result.status.code(),
                         execution_time_ms: 0,
                         error: Some("Java compilation failed".to_string()),
+                        killed_by_limit: false,
+                        output_truncated: false,
                     });
                 }
 
@@ -315,10 +389,25 @@ edition = "2021"
 
                 (path, "swift".to_string(), args)
             }
+            CodeLanguage::Bash => {
+                let path = temp_dir.join(format!("agent_script_{}.sh", script_id));
+                std::fs::write(&path, code)?;
+
+                let args = vec![path.to_string_lossy().to_string()];
+
+                (path, "bash".to_string(), args)
+            }
+        };
+
+        // `cargo run` compiles inside the same process tree, and rustc needs
+        // far more memory than the script itself, so only the timeout applies
+        let memory_limit_mb = match language {
+            CodeLanguage::Rust => None,
+            _ => Some(self.config.max_memory_mb),
         };
 
         // Execute with timeout
-        let result = self.execute_with_timeout(&command_name, &args).await?;
+        let result = self.execute_with_timeout(&command_name, &args, memory_limit_mb).await?;
 
         // Cleanup temp files/directories
         match language {
@@ -343,37 +432,47 @@ edition = "2021"
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         Ok(CodeExecutionResult {
-            success: result.success,
-            stdout: result.stdout,
-            stderr: result.stderr,
-            exit_code: result.exit_code,
             execution_time_ms: execution_time,
-            error: result.error,
+            ..result
         })
     }
 
-    /// Execute command with timeout
+    /// Execute command with timeout, memory and output limits. On timeout
+    /// the whole process tree is killed.
     async fn execute_with_timeout(
         &self,
         command: &str,
         args: &[String],
+        memory_limit_mb: Option<usize>,
     ) -> Result<CodeExecutionResult> {
-        use tokio::time::timeout;
-
         let mut cmd = AsyncCommand::new(command);
         cmd.args(args)
+            .stdin(Stdio::null())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
 
         // Set working directory if specified
         if let Some(ref wd) = self.config.working_dir {
             cmd.current_dir(wd);
         }
 
-        // Execute with timeout
-        let output = match timeout(self.config.timeout, cmd.output()).await {
-            Ok(Ok(output)) => output,
-            Ok(Err(e)) => {
+        #[cfg(unix)]
+        {
+            // Own process group so a timeout also kills grandchildren
+            cmd.process_group(0);
+            if let Some(mb) = memory_limit_mb {
+                let bytes = (mb as libc::rlim_t).saturating_mul(1024 * 1024);
+                // SAFETY: only async-signal-safe setrlimit runs between fork and exec
+                unsafe {
+                    cmd.pre_exec(move || limits::set_memory_limit(bytes));
+                }
+            }
+        }
+
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(e) => {
                 return Ok(CodeExecutionResult {
                     success: false,
                     stdout: String::new(),
@@ -381,24 +480,71 @@ edition = "2021"
                     exit_code: None,
                     execution_time_ms: 0,
                     error: Some(format!("Execution error: {}", e)),
+                    killed_by_limit: false,
+                    output_truncated: false,
                 });
             }
+        };
+
+        // Held until the child exits; closing the job kills anything left in it
+        #[cfg(windows)]
+        let _job = child.raw_handle().and_then(|handle| {
+            limits::JobObject::assign(handle, memory_limit_mb.map(|mb| mb * 1024 * 1024))
+                .map_err(|e| tracing::warn!("Failed to apply Job Object limits: {}", e))
+                .ok()
+        });
+        #[cfg(not(any(unix, windows)))]
+        let _ = memory_limit_mb;
+
+        let max_output = self.config.max_output_bytes;
+        let (stop_readers, stop) = watch::channel(false);
+        let stdout_task = child.stdout.take().map(|out| tokio::spawn(read_capped(out, max_output, stop.clone())));
+        let stderr_task = child.stderr.take().map(|err| tokio::spawn(read_capped(err, max_output, stop)));
+
+        let timeout = self.config.timeout();
+        let status = match tokio::time::timeout(timeout, child.wait()).await {
+            Ok(status) => Some(status.context("Failed to wait for child process")?),
             Err(_) => {
-                return Ok(CodeExecutionResult {
-                    success: false,
-                    stdout: String::new(),
-                    stderr: "Execution timeout".to_string(),
-                    exit_code: None,
-                    execution_time_ms: self.config.timeout.as_millis() as u64,
-                    error: Some(format!("Timeout after {:?}", self.config.timeout)),
-                });
+                #[cfg(unix)]
+                if let Some(pid) = child.id() {
+                    // SAFETY: plain syscall on the group created above
+                    unsafe {
+                        libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+                    }
+                }
+                let _ = child.kill().await;
+                None
             }
         };
 
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        let success = output.status.success();
-        let exit_code = output.status.code();
+        let drain = async { (join_output(stdout_task).await, join_output(stderr_task).await) };
+        tokio::pin!(drain);
+        let ((stdout, stdout_truncated), (stderr, stderr_truncated)) =
+            match tokio::time::timeout(PIPE_DRAIN_GRACE, &mut drain).await {
+                Ok(output) => output,
+                Err(_) => {
+                    tracing::warn!("Output pipes still open {:?} after the process ended; a child process may have escaped", PIPE_DRAIN_GRACE);
+                    let _ = stop_readers.send(true);
+                    drain.await
+                }
+            };
+        let output_truncated = stdout_truncated || stderr_truncated;
+
+        let Some(status) = status else {
+            return Ok(CodeExecutionResult {
+                success: false,
+                stdout,
+                stderr,
+                exit_code: None,
+                execution_time_ms: timeout.as_millis() as u64,
+                error: Some(format!("Timeout after {:?}", timeout)),
+                killed_by_limit: true,
+                output_truncated,
+            });
+        };
+
+        let success = status.success();
+        let exit_code = status.code();
         let error = if !success { Some(stderr.clone()) } else { None };
 
         Ok(CodeExecutionResult {
@@ -408,6 +554,8 @@ edition = "2021"
             exit_code,
             execution_time_ms: 0, // Will be set by caller
             error,
+            killed_by_limit: false,
+            output_truncated,
         })
     }
 
@@ -509,6 +657,249 @@ pub fn validate_code_safety(code: &str, language: &CodeLanguage) -> Result<()> {
     Ok(())
 }
 
+/// Detect the language of a code snippet from its shebang or common syntax
+/// markers. Returns None when nothing matches or the markers are ambiguous.
+pub fn detect_language(source: &str) -> Option<CodeLanguage> {
+    match detection_candidates(source).as_slice() {
+        [language] => Some(*language),
+        _ => None,
+    }
+}
+
+/// Resolve the language for a snippet: an artifact hint wins, otherwise
+/// detection. Errors list the candidates instead of guessing.
+pub fn resolve_language(source: &str, hint: Option<&str>) -> Result<CodeLanguage> {
+    if let Some(language) = hint.and_then(CodeLanguage::from_hint) {
+        return Ok(language);
+    }
+    match detection_candidates(source).as_slice() {
+        [] => Err(anyhow!("Could not detect code language; specify it explicitly")),
+        [language] => Ok(*language),
+        candidates => Err(anyhow!(
+            "Ambiguous code language, candidates: {}; specify it explicitly",
+            candidates.iter().map(|l| format!("{:?}", l)).collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+/// Languages tied for the highest marker score, or the shebang interpreter
+fn detection_candidates(source: &str) -> Vec<CodeLanguage> {
+    if let Some(language) = shebang_language(source) {
+        return vec![language];
+    }
+
+    let mut scores: HashMap<CodeLanguage, u32> = HashMap::new();
+    for line in source.lines() {
+        let t = line.trim();
+        if t.is_empty() || t.starts_with("//") || (t.starts_with('#') && !t.starts_with("#[")) {
+            continue;
+        }
+        for (language, weight) in line_markers(t) {
+            *scores.entry(language).or_insert(0) += weight;
+        }
+    }
+
+    let best = scores.values().copied().max().unwrap_or(0);
+    if best == 0 {
+        return Vec::new();
+    }
+    let mut candidates: Vec<CodeLanguage> = scores
+        .into_iter()
+        .filter(|(_, score)| *score == best)
+        .map(|(language, _)| language)
+        .collect();
+    candidates.sort_by_key(|l| format!("{:?}", l));
+    candidates
+}
+
+fn shebang_language(source: &str) -> Option<CodeLanguage> {
+    let first = source.trim_start().lines().next()?;
+    let interpreter = first.strip_prefix("#!")?.trim();
+    // `#![...]` is a Rust inner attribute, not a shebang
+    if !interpreter.starts_with('/') {
+        return None;
+    }
+    // "/usr/bin/env python3" -> "python3", "/bin/bash -e" -> "bash"
+    let mut parts = interpreter.split_whitespace();
+    let mut program = parts.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = parts.find(|p| !p.starts_with('-'))?;
+    }
+    let program = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    match program {
+        "sh" | "bash" | "zsh" | "dash" | "ksh" => Some(CodeLanguage::Bash),
+        other => CodeLanguage::from_hint(other),
+    }
+}
+
+/// Syntax markers found on one trimmed line, with a weight per language
+fn line_markers(t: &str) -> Vec<(CodeLanguage, u32)> {
+    use CodeLanguage::*;
+    let mut found = Vec::new();
+
+    // Python
+    if (t.starts_with("def ") || t.starts_with("class ") || t.starts_with("elif "))
+        && t.ends_with(':')
+    {
+        found.push((Python, 3));
+    }
+    if t.starts_with("from ") && t.contains(" import ") || t.starts_with("if __name__") {
+        found.push((Python, 3));
+    }
+    if (t.starts_with("for ") || t.starts_with("while ") || t.starts_with("if ")) && t.ends_with(':')
+        || t == "else:"
+        || t == "pass"
+    {
+        found.push((Python, 2));
+    }
+    if t.starts_with("import ") && !t.ends_with(';') && !t.contains(" from ")
+        || t.starts_with("print(") && !t.ends_with(';')
+    {
+        found.push((Python, 1));
+    }
+
+    // JavaScript
+    if t.contains("console.log(") || t.contains("require(") || t.contains("module.exports") {
+        found.push((JavaScript, 3));
+    }
+    if t.starts_with("function ") || t.contains("() =>") || t.contains("===") || t.contains("!==") {
+        found.push((JavaScript, 2));
+    }
+    if t.starts_with("const ") && t.contains(" = ") && !t.contains(": ") || t.starts_with("var ") {
+        found.push((JavaScript, 1));
+    }
+
+    // Rust
+    if t.starts_with("fn ") || t.starts_with("pub fn ") || t.contains("println!") || t.contains("vec![")
+        || t.starts_with("use ") && t.contains("::")
+    {
+        found.push((Rust, 3));
+    }
+    if t.contains("let mut ") || t.starts_with("impl ") || t.starts_with("#[") || t.contains("&str") {
+        found.push((Rust, 2));
+    }
+    if t.starts_with("match ") && t.ends_with('{') || t.contains("::new(") {
+        found.push((Rust, 1));
+    }
+
+    // Bash
+    if t == "fi" || t == "done" || t == "esac" || t.starts_with("if [") {
+        found.push((Bash, 3));
+    }
+    if t == "then" || t.ends_with("; then") || t == "do" || t.ends_with("; do") || t.starts_with("local ")
+        || t.starts_with("echo ")
+    {
+        found.push((Bash, 2));
+    }
+    if t.contains("$(") || t.starts_with("export ") && t.contains('=') && !t.contains(" = ") {
+        found.push((Bash, 1));
+    }
+
+    found
+}
+
+/// Read a pipe to EOF, keeping at most `max` bytes. The rest is drained so
+/// the child never blocks on a full pipe.
+async fn read_capped<R: AsyncRead + Unpin>(
+    mut reader: R,
+    max: usize,
+    mut stop: watch::Receiver<bool>,
+) -> (Vec<u8>, bool) {
+    let mut kept = Vec::new();
+    let mut truncated = false;
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = tokio::select! {
+            read = reader.read(&mut buf) => match read {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            },
+            _ = stop.changed() => break,
+        };
+        let room = max.saturating_sub(kept.len());
+        if n > room {
+            truncated = true;
+        }
+        kept.extend_from_slice(&buf[..n.min(room)]);
+    }
+    (kept, truncated)
+}
+
+async fn join_output(task: Option<tokio::task::JoinHandle<(Vec<u8>, bool)>>) -> (String, bool) {
+    match task {
+        Some(task) => match task.await {
+            Ok((bytes, truncated)) => (String::from_utf8_lossy(&bytes).to_string(), truncated),
+            Err(_) => (String::new(), false),
+        },
+        None => (String::new(), false),
+    }
+}
+
+#[cfg(unix)]
+mod limits {
+    /// Cap the data segment, which covers heap and private writable
+    /// mappings. Unlike `RLIMIT_AS` this doesn't count the large address
+    /// space reservations made by V8 and the JVM.
+    pub fn set_memory_limit(bytes: libc::rlim_t) -> std::io::Result<()> {
+        let limit = libc::rlimit { rlim_cur: bytes, rlim_max: bytes };
+        // SAFETY: setrlimit only reads the struct passed by reference
+        if unsafe { libc::setrlimit(libc::RLIMIT_DATA, &limit) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod limits {
+    use std::os::windows::io::RawHandle;
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_LIMIT_JOB_MEMORY, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+
+    /// Job Object bounding the child's memory; processes still in the job
+    /// are killed when it is dropped
+    pub struct JobObject(HANDLE);
+
+    impl JobObject {
+        pub fn assign(process: RawHandle, max_memory_bytes: Option<usize>) -> windows::core::Result<Self> {
+            // SAFETY: the job handle is owned by the returned guard and the
+            // process handle is valid while the tokio Child is alive
+            unsafe {
+                let job = Self(CreateJobObjectW(None, PCWSTR::null())?);
+
+                let mut info = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+                info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+                if let Some(bytes) = max_memory_bytes {
+                    info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_JOB_MEMORY;
+                    info.JobMemoryLimit = bytes;
+                }
+                SetInformationJobObject(
+                    job.0,
+                    JobObjectExtendedLimitInformation,
+                    &info as *const _ as *const std::ffi::c_void,
+                    std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                )?;
+                AssignProcessToJobObject(job.0, HANDLE(process))?;
+                Ok(job)
+            }
+        }
+    }
+
+    impl Drop for JobObject {
+        fn drop(&mut self) {
+            // SAFETY: handle came from CreateJobObjectW and is closed once
+            unsafe {
+                let _ = CloseHandle(self.0);
+            }
+        }
+    }
+}
+
 /// Extract Java class name from code
 fn extract_java_class_name(code: &str) -> Option<String> {
     // Look for: public class ClassName
@@ -549,7 +940,7 @@ print(f"2 + 2 = {result}")
     #[tokio::test]
     async fn test_timeout() {
         let mut config = ExecutionConfig::default();
-        config.timeout = Duration::from_millis(100);
+        config.timeout_secs = 1;
 
         let executor = CodeExecutor::new(config);
 
@@ -563,4 +954,87 @@ time.sleep(10)  # Will timeout
         assert!(!result.success);
         assert!(result.error.unwrap().contains("Timeout"));
     }
+
+    #[tokio::test]
+    async fn test_infinite_loop_is_killed_at_timeout() {
+        let executor = CodeExecutor::new(ExecutionConfig { timeout_secs: 1, ..Default::default() });
+
+        let started = Instant::now();
+        let result = executor.execute_python("while True:\n    pass\n").await.unwrap();
+
+        assert!(!result.success);
+        assert!(result.killed_by_limit);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_escaped_grandchild_does_not_hold_output_open() {
+        let executor = CodeExecutor::new(ExecutionConfig { timeout_secs: 1, ..Default::default() });
+        let code = "import subprocess, time\n\
+                    subprocess.Popen(['sleep', '15'], start_new_session=True)\n\
+                    print('started', flush=True)\n\
+                    time.sleep(10)\n";
+
+        let started = Instant::now();
+        let result = executor.execute_python(code).await.unwrap();
+
+        assert!(result.killed_by_limit);
+        assert_eq!(result.stdout.trim(), "started");
+        assert!(started.elapsed() < Duration::from_secs(8));
+    }
+
+    #[tokio::test]
+    async fn test_output_is_capped() {
+        let executor = CodeExecutor::new(ExecutionConfig { max_output_bytes: 1024, ..Default::default() });
+
+        let result = executor.execute_python("print('x' * 100000)").await.unwrap();
+
+        assert!(result.success);
+        assert!(result.output_truncated);
+        assert_eq!(result.stdout.len(), 1024);
+    }
+
+    #[test]
+    fn test_detect_python() {
+        let code = "import json\n\ndef greet(name):\n    return f\"hi {name}\"\n\nif __name__ == \"__main__\":\n    print(greet(\"x\"))\n";
+        assert_eq!(detect_language(code), Some(CodeLanguage::Python));
+    }
+
+    #[test]
+    fn test_detect_javascript() {
+        let code = "const fs = require('fs');\nfunction add(a, b) {\n  return a + b;\n}\nconsole.log(add(1, 2));\n";
+        assert_eq!(detect_language(code), Some(CodeLanguage::JavaScript));
+    }
+
+    #[test]
+    fn test_detect_rust() {
+        let code = "#![allow(unused)]\nuse std::collections::HashMap;\n\nfn main() {\n    let mut m = HashMap::new();\n    m.insert(1, 2);\n    println!(\"{:?}\", m);\n}\n";
+        assert_eq!(detect_language(code), Some(CodeLanguage::Rust));
+    }
+
+    #[test]
+    fn test_detect_bash() {
+        let code = "for f in *.txt; do\n  echo \"$f\"\ndone\nif [ -z \"$HOME\" ]; then\n  exit 1\nfi\n";
+        assert_eq!(detect_language(code), Some(CodeLanguage::Bash));
+    }
+
+    #[test]
+    fn test_detect_from_shebang() {
+        assert_eq!(detect_language("#!/usr/bin/env python3\nx = 1\n"), Some(CodeLanguage::Python));
+        assert_eq!(detect_language("#!/usr/bin/env node\nx = 1\n"), Some(CodeLanguage::JavaScript));
+        assert_eq!(detect_language("#!/bin/sh\nls\n"), Some(CodeLanguage::Bash));
+    }
+
+    #[test]
+    fn test_resolve_language_prefers_hint_and_reports_ambiguity() {
+        assert_eq!(resolve_language("x = 1", Some("script.rs")).unwrap(), CodeLanguage::Rust);
+        assert_eq!(resolve_language("x = 1", Some("Python")).unwrap(), CodeLanguage::Python);
+
+        // Equal marker weight for Bash and Rust
+        let err = resolve_language("echo hi\nlet mut x = 1;\n", None).unwrap_err().to_string();
+        assert!(err.contains("Bash") && err.contains("Rust"), "{}", err);
+
+        assert!(resolve_language("hello world", None).is_err());
+    }
 }
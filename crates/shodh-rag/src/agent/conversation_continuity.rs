@@ -124,7 +124,7 @@ impl ConversationManager {
         };
 
         let memory = self.memory_system.write().await;
-        memory.record(experience)?;
+        memory.record(experience).await?;
         drop(memory);
 
         *self.current_conversation.write().await = Some(conversation);
@@ -164,7 +164,7 @@ impl ConversationManager {
                 };
 
                 let memory = self.memory_system.write().await;
-                memory.record(experience)?;
+                memory.record(experience).await?;
             }
         }
 
@@ -211,7 +211,7 @@ impl ConversationManager {
             };
 
             let memory = self.memory_system.write().await;
-            memory.record(experience)?;
+            memory.record(experience).await?;
         }
 
         Ok(())
@@ -227,6 +227,8 @@ impl ConversationManager {
                 query_text: Some(format!("conversation_id:{}", last_snapshot.conversation_id)),
                 query_embedding: None,
                 retrieval_mode: RetrievalMode::Similarity,
+                causal_seed: None,
+                causal_depth: 0,
                 max_results: 10,
                 importance_threshold: Some(0.0),
                 time_range: None,
@@ -272,6 +274,8 @@ impl ConversationManager {
             query_text: Some(query.to_string()),
             query_embedding: None,
             retrieval_mode: RetrievalMode::Similarity,
+            causal_seed: None,
+            causal_depth: 0,
             max_results: 20,
             importance_threshold: Some(0.5),
             time_range: None,
@@ -340,6 +344,8 @@ impl ConversationManager {
             query_text: Some(String::new()),
             query_embedding: None,
             retrieval_mode: RetrievalMode::Temporal,
+            causal_seed: None,
+            causal_depth: 0,
             max_results: 5,
             importance_threshold: Some(0.0),
             time_range: Some((Utc::now() - chrono::Duration::days(7), Utc::now())),
@@ -452,6 +458,8 @@ impl ConversationManager {
             query_text: Some(format!("conversation_id:{}", id)),
             query_embedding: None,
             retrieval_mode: RetrievalMode::Similarity,
+            causal_seed: None,
+            causal_depth: 0,
             max_results: 1,
             importance_threshold: Some(0.0),
             time_range: None,
@@ -1,11 +1,14 @@
 //! Multi-Agent Crew System
 //!
 //! Enables assembling teams of agents that collaborate on tasks, inspired by
-//! CrewAI. Supports two execution processes:
+//! CrewAI. Supports four execution processes:
 //!
 //! - **Sequential**: Agents execute in order, each receiving previous outputs.
 //! - **Hierarchical**: A coordinator agent delegates to specialists using the
 //!   existing AgentDelegateTool pattern from `orchestrator.rs`.
+//! - **Parallel**: All agents work on the task concurrently; outputs are merged.
+//! - **Consensus**: Agents work concurrently, then revise over several rounds
+//!   after seeing each other's previous answers.
 
 use anyhow::{Result, bail};
 use serde::{Deserialize, Serialize};
@@ -56,6 +59,47 @@ pub enum CrewProcess {
         /// Agent ID of the coordinator (must be in crew.agents).
         coordinator_id: String,
     },
+    /// All agents run the task concurrently and their outputs are merged.
+    /// Member `order` is ignored.
+    Parallel,
+    /// Agents run concurrently, then revise their answers after reading every
+    /// member's previous-round output. Member `order` is ignored.
+    Consensus {
+        /// Number of rounds, including the first independent one (at least 1).
+        rounds: usize,
+    },
+}
+
+impl CrewDefinition {
+    /// Check the crew is executable: it has members, a hierarchical
+    /// coordinator is one of them, sequential members have distinct orders,
+    /// and consensus runs at least one round.
+    pub fn validate(&self) -> Result<()> {
+        if self.agents.is_empty() {
+            bail!("Crew '{}' has no agents", self.name);
+        }
+        match &self.process {
+            CrewProcess::Sequential => {
+                let mut orders: Vec<usize> = self.agents.iter().map(|a| a.order).collect();
+                orders.sort_unstable();
+                if orders.windows(2).any(|w| w[0] == w[1]) {
+                    bail!("Sequential crew '{}' has members with the same order", self.name);
+                }
+            }
+            CrewProcess::Hierarchical { coordinator_id } => {
+                if !self.agents.iter().any(|a| a.agent_id == *coordinator_id) {
+                    bail!("Coordinator '{}' must be a member of the crew", coordinator_id);
+                }
+            }
+            CrewProcess::Parallel => {}
+            CrewProcess::Consensus { rounds } => {
+                if *rounds == 0 {
+                    bail!("Consensus crew '{}' needs at least one round", self.name);
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Configuration for crew execution.
@@ -90,6 +134,9 @@ pub struct CrewExecutionResult {
     pub agent_outputs: Vec<CrewAgentOutput>,
     pub execution_time_ms: u64,
     pub error: Option<String>,
+    /// Per-round outputs of a Consensus crew; `agent_outputs` holds the last round.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rounds: Vec<Vec<CrewAgentOutput>>,
 }
 
 /// Output from a single agent within a crew execution.
@@ -121,9 +168,7 @@ pub async fn execute_crew(
 ) -> Result<CrewExecutionResult> {
     let start = Instant::now();
 
-    if crew.agents.is_empty() {
-        bail!("Crew '{}' has no agents", crew.name);
-    }
+    crew.validate()?;
 
     let result = match &crew.process {
         CrewProcess::Sequential => {
@@ -132,6 +177,12 @@ pub async fn execute_crew(
         CrewProcess::Hierarchical { coordinator_id } => {
             execute_hierarchical(crew, task, space_id, agent_system, coordinator_id, emitter).await
         }
+        CrewProcess::Parallel => {
+            execute_parallel(crew, task, space_id, agent_system, emitter).await
+        }
+        CrewProcess::Consensus { rounds } => {
+            execute_consensus(crew, task, space_id, agent_system, *rounds, emitter).await
+        }
     };
 
     match result {
@@ -145,6 +196,7 @@ pub async fn execute_crew(
             agent_outputs: vec![],
             execution_time_ms: start.elapsed().as_millis() as u64,
             error: Some(e.to_string()),
+            rounds: vec![],
         }),
     }
 }
@@ -309,6 +361,7 @@ async fn execute_sequential(
         agent_outputs,
         execution_time_ms: 0, // Will be set by caller
         error: None,
+        rounds: vec![],
     })
 }
 
@@ -425,9 +478,177 @@ async fn execute_hierarchical(
         agent_outputs,
         execution_time_ms: 0, // Will be set by caller
         error: result.error,
+        rounds: vec![],
+    })
+}
+
+/// Parallel execution: every member works on the task independently and
+/// concurrently; the final output merges all answers in member order.
+async fn execute_parallel(
+    crew: &CrewDefinition,
+    task: &str,
+    space_id: Option<&str>,
+    agent_system: &super::AgentSystem,
+    emitter: Option<&dyn EventEmitter>,
+) -> Result<CrewExecutionResult> {
+    tracing::info!(crew = %crew.name, members = crew.agents.len(), "Crew parallel: running all agents");
+
+    let agent_outputs = run_round(crew, task, space_id, agent_system, None, "parallel", emitter).await?;
+    let final_output = merge_outputs(&agent_outputs);
+    emit_event(emitter, "chat_token", serde_json::json!({
+        "token": final_output,
+        "accumulated": final_output,
+    }));
+
+    Ok(CrewExecutionResult {
+        success: true,
+        final_output,
+        agent_outputs,
+        execution_time_ms: 0, // Will be set by caller
+        error: None,
+        rounds: vec![],
     })
 }
 
+/// Consensus execution: an independent parallel round, then `rounds - 1`
+/// revision rounds in which each member sees every answer from the previous
+/// round. The final output merges the last round's answers.
+async fn execute_consensus(
+    crew: &CrewDefinition,
+    task: &str,
+    space_id: Option<&str>,
+    agent_system: &super::AgentSystem,
+    rounds: usize,
+    emitter: Option<&dyn EventEmitter>,
+) -> Result<CrewExecutionResult> {
+    let mut all_rounds: Vec<Vec<CrewAgentOutput>> = Vec::with_capacity(rounds);
+    let mut accumulated_stream = String::new();
+
+    for round in 1..=rounds {
+        tracing::info!(crew = %crew.name, "Crew consensus: round {}/{}", round, rounds);
+
+        let header = format!("---\n### Round {}/{}\n\n", round, rounds);
+        accumulated_stream.push_str(&header);
+        emit_event(emitter, "chat_token", serde_json::json!({
+            "token": header,
+            "accumulated": accumulated_stream,
+        }));
+
+        let peer_outputs = all_rounds.last().map(|prev| merge_outputs(prev));
+        let step = format!("round {}/{}", round, rounds);
+        let outputs = run_round(crew, task, space_id, agent_system, peer_outputs.as_deref(), &step, emitter).await?;
+
+        let merged = merge_outputs(&outputs);
+        accumulated_stream.push_str(&merged);
+        accumulated_stream.push_str("\n\n");
+        emit_event(emitter, "chat_token", serde_json::json!({
+            "token": format!("{}\n\n", merged),
+            "accumulated": accumulated_stream,
+        }));
+
+        all_rounds.push(outputs);
+    }
+
+    let agent_outputs = all_rounds.last().cloned().unwrap_or_default();
+    Ok(CrewExecutionResult {
+        success: true,
+        final_output: merge_outputs(&agent_outputs),
+        agent_outputs,
+        execution_time_ms: 0, // Will be set by caller
+        error: None,
+        rounds: all_rounds,
+    })
+}
+
+/// Run every crew member on the task concurrently. `peer_outputs` carries the
+/// previous consensus round's answers for members to revise against. Outputs
+/// are returned in member order; any agent failure fails the round.
+async fn run_round(
+    crew: &CrewDefinition,
+    task: &str,
+    space_id: Option<&str>,
+    agent_system: &super::AgentSystem,
+    peer_outputs: Option<&str>,
+    step: &str,
+    emitter: Option<&dyn EventEmitter>,
+) -> Result<Vec<CrewAgentOutput>> {
+    let runs = crew.agents.iter().map(|member| async move {
+        let agent_start = Instant::now();
+        let agent_name = agent_system.get_agent(&member.agent_id).await
+            .map_err(|e| anyhow::anyhow!("Agent '{}' not found: {}", member.agent_id, e))?
+            .name;
+        let tool_label = format!("{} ({})", agent_name, member.role);
+
+        emit_event(emitter, "tool_call_start", serde_json::json!({
+            "tool_name": tool_label,
+            "arguments": serde_json::json!({
+                "role": member.role,
+                "goal": member.goal,
+                "step": step,
+            }).to_string(),
+        }));
+
+        let mut ctx = AgentContext::with_query(task.to_string());
+        if let Some(sid) = space_id {
+            ctx = ctx.with_space_id(sid.to_string());
+        }
+        if let Some(peers) = peer_outputs {
+            ctx.add_variable(
+                "crew_previous_outputs".to_string(),
+                serde_json::Value::String(peers.to_string()),
+            );
+        }
+        ctx.add_metadata("crew_role".to_string(), member.role.clone());
+        ctx.add_metadata("crew_goal".to_string(), member.goal.clone());
+        ctx.add_metadata("crew_name".to_string(), crew.name.clone());
+        ctx.query = Some(format!(
+            "{}\n\nYour role: {}\nYour goal: {}{}",
+            task,
+            member.role,
+            member.goal,
+            match peer_outputs {
+                Some(peers) => format!(
+                    "\n\nAnswers from every team member in the previous round:\n{}\n\n\
+                     Review them, then give your revised answer. Keep what holds up, \
+                     correct what doesn't, and note any remaining disagreement.",
+                    peers
+                ),
+                None => String::new(),
+            }
+        ));
+
+        let result = agent_system.execute_agent(&member.agent_id, ctx).await?;
+        let duration_ms = agent_start.elapsed().as_millis() as u64;
+
+        emit_event(emitter, "tool_call_complete", serde_json::json!({
+            "tool_name": tool_label,
+            "result": result.response.chars().take(200).collect::<String>(),
+            "success": result.success,
+            "duration_ms": duration_ms,
+        }));
+
+        Ok::<_, anyhow::Error>(CrewAgentOutput {
+            agent_id: member.agent_id.clone(),
+            agent_name,
+            role: member.role.clone(),
+            output: result.response,
+            execution_time_ms: duration_ms,
+            tools_used: result.tools_used,
+        })
+    });
+
+    futures::future::join_all(runs).await.into_iter().collect()
+}
+
+/// Merge agent outputs into one markdown document, one section per agent.
+fn merge_outputs(outputs: &[CrewAgentOutput]) -> String {
+    outputs
+        .iter()
+        .map(|o| format!("#### {} ({})\n{}", o.agent_name, o.role, o.output))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -476,6 +697,62 @@ mod tests {
         assert!(json.contains("coord-1"));
     }
 
+    fn member(agent_id: &str, order: usize) -> CrewMember {
+        CrewMember {
+            agent_id: agent_id.to_string(),
+            role: "analyst".to_string(),
+            goal: "Answer the question".to_string(),
+            order,
+        }
+    }
+
+    fn crew(process: CrewProcess, agents: Vec<CrewMember>) -> CrewDefinition {
+        CrewDefinition {
+            id: "crew-1".to_string(),
+            name: "Panel".to_string(),
+            description: String::new(),
+            agents,
+            process,
+            config: CrewConfig::default(),
+        }
+    }
+
+    #[test]
+    fn test_parallel_crews_ignore_member_order() {
+        let unordered = vec![member("agent-1", 0), member("agent-2", 0)];
+        assert!(crew(CrewProcess::Parallel, unordered.clone()).validate().is_ok());
+        assert!(crew(CrewProcess::Consensus { rounds: 2 }, unordered.clone()).validate().is_ok());
+        assert!(crew(CrewProcess::Sequential, unordered).validate().is_err());
+    }
+
+    #[test]
+    fn test_consensus_validation_and_serialization() {
+        let agents = vec![member("agent-1", 0), member("agent-2", 1)];
+        assert!(crew(CrewProcess::Consensus { rounds: 0 }, agents.clone()).validate().is_err());
+        assert!(crew(CrewProcess::Parallel, vec![]).validate().is_err());
+
+        let json = serde_json::to_string(&CrewProcess::Consensus { rounds: 3 }).unwrap();
+        assert_eq!(json, r#"{"type":"consensus","rounds":3}"#);
+        let parsed: CrewProcess = serde_json::from_str(r#"{"type":"parallel"}"#).unwrap();
+        assert!(matches!(parsed, CrewProcess::Parallel));
+    }
+
+    #[test]
+    fn test_merge_outputs_keeps_member_order() {
+        let output = |name: &str, text: &str| CrewAgentOutput {
+            agent_id: name.to_string(),
+            agent_name: name.to_string(),
+            role: "analyst".to_string(),
+            output: text.to_string(),
+            execution_time_ms: 0,
+            tools_used: vec![],
+        };
+        assert_eq!(
+            merge_outputs(&[output("A", "yes"), output("B", "no")]),
+            "#### A (analyst)\nyes\n\n#### B (analyst)\nno"
+        );
+    }
+
     #[test]
     fn test_crew_config_defaults() {
         let config = CrewConfig::default();
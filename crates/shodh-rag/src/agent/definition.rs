@@ -70,6 +70,16 @@ pub struct AgentConfig {
     /// Number of RAG results to retrieve
     #[serde(default = "default_rag_results")]
     pub rag_top_k: usize,
+
+    /// Estimated LLM tokens (prompt + completion) one execution may spend
+    #[serde(default)]
+    pub max_total_tokens: Option<usize>,
+
+    /// Wall-clock limit for one execution in seconds, checked between steps
+    /// and LLM calls. Unlike `timeout_seconds` it stops the execution with a
+    /// budget-exceeded status instead of synthesizing a normal answer.
+    #[serde(default)]
+    pub max_wall_clock_secs: Option<u64>,
 }
 
 impl Default for AgentConfig {
@@ -83,6 +93,8 @@ impl Default for AgentConfig {
             timeout_seconds: default_timeout(),
             auto_use_rag: true,
             rag_top_k: default_rag_results(),
+            max_total_tokens: None,
+            max_wall_clock_secs: None,
         }
     }
 }
@@ -218,6 +230,9 @@ impl AgentDefinition {
         if self.config.max_tokens == 0 {
             anyhow::bail!("Max tokens must be greater than 0");
         }
+        if self.config.max_total_tokens == Some(0) || self.config.max_wall_clock_secs == Some(0) {
+            anyhow::bail!("Execution budgets must be greater than 0");
+        }
         Ok(())
     }
 }
@@ -76,7 +76,7 @@ impl AgentTool for DynamicTool {
 /// Takes a list of tool definitions and a shared caller function,
 /// and registers each as a DynamicTool in the given registry.
 pub fn register_dynamic_tools(
-    registry: &mut super::tools::ToolRegistry,
+    registry: &super::tools::ToolRegistry,
     tools: Vec<DynamicToolDef>,
 ) {
     for def in tools {
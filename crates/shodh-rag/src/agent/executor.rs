@@ -3,7 +3,7 @@
 use super::definition::AgentDefinition;
 use super::tools::{ToolRegistry, ToolInput, ToolResult};
 use super::context::AgentContext;
-use super::tool_loop::{run_tool_loop, tool_descriptions_to_schemas, ToolLoopConfig, ToolLoopResult};
+use super::tool_loop::{run_tool_loop, tool_descriptions_to_schemas, BudgetExceeded, ToolLoopConfig, ToolLoopResult};
 use crate::llm::{ChatMessage, LLMManager};
 use tokio::sync::RwLock;
 use anyhow::{Result, Context as AnyhowContext};
@@ -159,6 +159,8 @@ impl AgentExecutor {
         // Build execution plan
         let plan = self.build_execution_plan(&context).await?;
         metadata.insert("plan_steps".to_string(), serde_json::json!(plan.len()));
+        let deadline = self.definition.config.max_wall_clock_secs
+            .map(|secs| start_time + Duration::from_secs(secs));
 
         // Execute plan steps
         let mut current_context = context.clone();
@@ -192,6 +194,7 @@ impl AgentExecutor {
                 step_num + 1,
                 step,
                 &mut current_context,
+                deadline,
             ).await;
 
             match step_result {
@@ -232,6 +235,10 @@ impl AgentExecutor {
                 }
             }
 
+            if let Some(exceeded) = budget_exceeded(&current_context, deadline) {
+                return Ok(self.budget_exceeded_result(exceeded, steps, tools_used, &current_context, start_time, metadata));
+            }
+
             // Check timeout
             if start_time.elapsed().as_secs() >= self.definition.config.timeout_seconds {
                 metadata.insert("timeout".to_string(), serde_json::json!(true));
@@ -247,6 +254,7 @@ impl AgentExecutor {
 
         // Synthesize final response
         let final_response = self.synthesize_response(&steps, &current_context).await?;
+        record_token_estimate(&current_context, &mut metadata);
 
         let execution_time_ms = start_time.elapsed().as_millis() as u64;
 
@@ -281,6 +289,8 @@ impl AgentExecutor {
         let plan = self.build_execution_plan(&context).await?;
         let total_steps = plan.len();
         metadata.insert("plan_steps".to_string(), serde_json::json!(total_steps));
+        let deadline = self.definition.config.max_wall_clock_secs
+            .map(|secs| start_time + Duration::from_secs(secs));
 
         // Send initial progress
         if let Some(ref tx) = progress_tx {
@@ -340,6 +350,7 @@ impl AgentExecutor {
                 step_num + 1,
                 step,
                 &mut current_context,
+                deadline,
             ).await;
 
             match step_result {
@@ -379,6 +390,10 @@ impl AgentExecutor {
                 }
             }
 
+            if let Some(exceeded) = budget_exceeded(&current_context, deadline) {
+                return Ok(self.budget_exceeded_result(exceeded, steps, tools_used, &current_context, start_time, metadata));
+            }
+
             // Check timeout
             if start_time.elapsed().as_secs() >= self.definition.config.timeout_seconds {
                 metadata.insert("timeout".to_string(), serde_json::json!(true));
@@ -406,6 +421,7 @@ impl AgentExecutor {
 
         // Synthesize final response
         let final_response = self.synthesize_response(&steps, &current_context).await?;
+        record_token_estimate(&current_context, &mut metadata);
 
         let execution_time_ms = start_time.elapsed().as_millis() as u64;
 
@@ -513,6 +529,7 @@ impl AgentExecutor {
         step_number: usize,
         step: PlannedStep,
         context: &mut AgentContext,
+        deadline: Option<Instant>,
     ) -> Result<ExecutionStep> {
         let step_start = Instant::now();
         let timestamp = current_timestamp();
@@ -553,6 +570,7 @@ impl AgentExecutor {
                         "query": query,
                         "top_k": top_k,
                     }),
+                    dry_run: false,
                 };
 
                 let result = tool.execute(tool_input, context.clone()).await?;
@@ -638,6 +656,10 @@ impl AgentExecutor {
                         max_iterations: self.definition.config.max_tool_calls.min(10),
                         tool_timeout_secs: 30,
                         streaming: false,
+                        max_total_tokens: self.definition.config.max_total_tokens,
+                        deadline,
+                        cancel_token: Some(LLMManager::current_cancellation()),
+                        ..Default::default()
                     };
 
                     // Run the ReAct tool-calling loop
@@ -669,6 +691,16 @@ impl AgentExecutor {
                         "llm_response".to_string(),
                         serde_json::Value::String(loop_result.content.clone()),
                     );
+                    context.add_variable(
+                        "llm_estimated_tokens".to_string(),
+                        serde_json::json!(loop_result.estimated_tokens),
+                    );
+                    if let Some(ref exceeded) = loop_result.budget_exceeded {
+                        context.add_variable(
+                            "budget_exceeded".to_string(),
+                            serde_json::to_value(exceeded).unwrap_or_default(),
+                        );
+                    }
 
                     Ok(ExecutionStep {
                         step_number,
@@ -678,7 +710,7 @@ impl AgentExecutor {
                         input: user_content,
                         output: loop_result.content,
                         tool_used: if tools_used.is_empty() { None } else { Some(tools_used.join(", ")) },
-                        success: true,
+                        success: loop_result.budget_exceeded.is_none(),
                     })
                 } else {
                     // Fallback when no LLM manager — descriptive placeholder
@@ -764,6 +796,50 @@ impl AgentExecutor {
         Ok(response)
     }
 
+    /// Result for an execution stopped by `max_total_tokens` or
+    /// `max_wall_clock_secs`. Keeps whatever the LLM produced so far.
+    fn budget_exceeded_result(
+        &self,
+        exceeded: BudgetExceeded,
+        steps: Vec<ExecutionStep>,
+        tools_used: Vec<String>,
+        context: &AgentContext,
+        start_time: Instant,
+        mut metadata: std::collections::HashMap<String, serde_json::Value>,
+    ) -> ExecutionResult {
+        let error = match exceeded {
+            BudgetExceeded::WallClock => format!(
+                "{} (limit {}s)",
+                exceeded,
+                self.definition.config.max_wall_clock_secs.unwrap_or_default()
+            ),
+            BudgetExceeded::Tokens { .. } => exceeded.to_string(),
+        };
+        tracing::warn!(agent = %self.definition.name, error = %error, "Agent execution stopped");
+
+        let response = context.get_variable("llm_response")
+            .and_then(|v| v.as_str())
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("Execution stopped: {}", error));
+
+        record_token_estimate(context, &mut metadata);
+        metadata.insert(
+            "budget_exceeded".to_string(),
+            serde_json::to_value(&exceeded).unwrap_or_default(),
+        );
+
+        ExecutionResult {
+            response,
+            steps,
+            tools_used,
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+            success: false,
+            error: Some(error),
+            metadata,
+        }
+    }
+
     /// Build initial prompt for reasoning
     fn build_initial_prompt(&self, context: &AgentContext) -> String {
         let mut prompt = self.definition.system_prompt.clone();
@@ -793,6 +869,28 @@ enum PlannedStep {
     FinalSynthesis,
 }
 
+/// The budget an execution has run out of: one the tool loop reported via
+/// the context, or the wall-clock deadline passing between steps.
+fn budget_exceeded(context: &AgentContext, deadline: Option<Instant>) -> Option<BudgetExceeded> {
+    if let Some(exceeded) = context.get_variable("budget_exceeded") {
+        return serde_json::from_value(exceeded.clone()).ok();
+    }
+    deadline
+        .filter(|deadline| Instant::now() >= *deadline)
+        .map(|_| BudgetExceeded::WallClock)
+}
+
+/// Copy the tool loop's token estimate into the result metadata, where the
+/// metrics collector reads `total_tokens`.
+fn record_token_estimate(
+    context: &AgentContext,
+    metadata: &mut std::collections::HashMap<String, serde_json::Value>,
+) {
+    if let Some(tokens) = context.get_variable("llm_estimated_tokens") {
+        metadata.insert("total_tokens".to_string(), tokens.clone());
+    }
+}
+
 /// Get current timestamp in milliseconds
 fn current_timestamp() -> u64 {
     std::time::SystemTime::now()
@@ -828,6 +926,19 @@ mod tests {
         assert_eq!(executor.definition.name, "TestAgent");
     }
 
+    #[test]
+    fn test_budget_exceeded_from_context_or_deadline() {
+        let mut context = AgentContext::new();
+        let future = Instant::now() + Duration::from_secs(60);
+        assert_eq!(budget_exceeded(&context, None), None);
+        assert_eq!(budget_exceeded(&context, Some(future)), None);
+        assert_eq!(budget_exceeded(&context, Some(Instant::now())), Some(BudgetExceeded::WallClock));
+
+        let exceeded = BudgetExceeded::Tokens { limit: 1000, used: 1200 };
+        context.add_variable("budget_exceeded".to_string(), serde_json::to_value(&exceeded).unwrap());
+        assert_eq!(budget_exceeded(&context, Some(future)), Some(exceeded));
+    }
+
     #[test]
     fn test_visual_query_detection() {
         // Visual queries - should return true
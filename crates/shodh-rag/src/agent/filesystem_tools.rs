@@ -5,6 +5,7 @@
 //! - Session-level permissions
 //! - Path sandboxing
 //! - Audit logging
+//! - Dry-run previews of writes (diff against the current file)
 
 use super::tools::{AgentTool, ToolInput, ToolResult};
 use super::context::AgentContext;
@@ -56,6 +57,9 @@ pub struct PermissionRequest {
     pub path: PathBuf,
     pub reason: String,
     pub agent_id: String,
+    /// What the operation would change, e.g. a diff for file writes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
 }
 
 /// Permission decision
@@ -90,6 +94,9 @@ pub struct AuditEntry {
     pub path: PathBuf,
     pub allowed: bool,
     pub result: String,
+    /// The action was only previewed; nothing was changed on disk
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 impl PermissionManager {
@@ -221,6 +228,29 @@ impl PermissionManager {
         path: PathBuf,
         allowed: bool,
         result: String,
+    ) {
+        self.push_audit(agent_id, operation, path, allowed, result, false).await;
+    }
+
+    /// Log a dry-run preview to the audit trail
+    pub async fn log_dry_run(
+        &self,
+        agent_id: String,
+        operation: FilePermission,
+        path: PathBuf,
+        result: String,
+    ) {
+        self.push_audit(agent_id, operation, path, true, result, true).await;
+    }
+
+    async fn push_audit(
+        &self,
+        agent_id: String,
+        operation: FilePermission,
+        path: PathBuf,
+        allowed: bool,
+        result: String,
+        dry_run: bool,
     ) {
         let entry = AuditEntry {
             timestamp: Utc::now(),
@@ -229,6 +259,7 @@ impl PermissionManager {
             path,
             allowed,
             result,
+            dry_run,
         };
 
         self.audit_log.write().await.push(entry);
@@ -300,6 +331,7 @@ impl AgentTool for ReadFileTool {
             path: path.clone(),
             reason: format!("Agent wants to read file: {}", path.display()),
             agent_id: user_id.clone(),
+            preview: None,
         }).await?;
 
         if !permission.allowed {
@@ -316,6 +348,7 @@ impl AgentTool for ReadFileTool {
                 output: "Permission denied by user or sandboxing rules".to_string(),
                 data: serde_json::json!({}),
                 error: Some("Permission denied".to_string()),
+                structured: None,
             });
         }
 
@@ -339,6 +372,7 @@ impl AgentTool for ReadFileTool {
                         "size_bytes": content.len(),
                     }),
                     error: None,
+                    structured: None,
                 })
             }
             Err(e) => {
@@ -355,6 +389,7 @@ impl AgentTool for ReadFileTool {
                     output: format!("Failed to read file: {}", e),
                     data: serde_json::json!({}),
                     error: Some(e.to_string()),
+                    structured: None,
                 })
             }
         }
@@ -383,7 +418,8 @@ impl AgentTool for WriteFileTool {
     }
 
     fn description(&self) -> &str {
-        "Write content to a file. Creates file if it doesn't exist. Requires user permission."
+        "Write content to a file. Creates file if it doesn't exist. Requires user permission. \
+         With dry_run, returns the diff it would apply without writing."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -397,6 +433,10 @@ impl AgentTool for WriteFileTool {
                 "content": {
                     "type": "string",
                     "description": "Content to write to the file"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "Return the diff the write would apply without writing"
                 }
             },
             "required": ["path", "content"]
@@ -419,12 +459,47 @@ impl AgentTool for WriteFileTool {
             .map(|u| u.user_id.clone())
             .unwrap_or_else(|| "unknown".to_string());
 
+        // Only build a preview inside the sandbox, since it reads the current file
+        let preview = self.permission_manager.is_path_allowed(&path)
+            .then(|| write_preview(&path, content));
+
+        if input.dry_run {
+            if let Some(preview) = preview {
+                self.permission_manager.log_dry_run(
+                    user_id,
+                    FilePermission::WriteFile,
+                    path.clone(),
+                    format!("Previewed write of {} bytes", content.len()),
+                ).await;
+
+                return Ok(ToolResult {
+                    success: true,
+                    output: format!(
+                        "Dry run: would write {} bytes to '{}'\n\n{}",
+                        content.len(),
+                        path.display(),
+                        preview
+                    ),
+                    data: serde_json::json!({
+                        "path": path_str,
+                        "size_bytes": content.len(),
+                        "creates_file": !path.exists(),
+                        "diff": preview,
+                        "dry_run": true,
+                    }),
+                    error: None,
+                    structured: None,
+                });
+            }
+        }
+
         // Request permission
         let permission = self.permission_manager.request_permission(PermissionRequest {
             operation: FilePermission::WriteFile,
             path: path.clone(),
             reason: format!("Agent wants to write {} bytes to: {}", content.len(), path.display()),
             agent_id: user_id.clone(),
+            preview,
         }).await?;
 
         if !permission.allowed {
@@ -441,6 +516,7 @@ impl AgentTool for WriteFileTool {
                 output: "Permission denied by user or sandboxing rules".to_string(),
                 data: serde_json::json!({}),
                 error: Some("Permission denied".to_string()),
+                structured: None,
             });
         }
 
@@ -470,6 +546,7 @@ impl AgentTool for WriteFileTool {
                         "size_bytes": content.len(),
                     }),
                     error: None,
+                    structured: None,
                 })
             }
             Err(e) => {
@@ -486,12 +563,107 @@ impl AgentTool for WriteFileTool {
                     output: format!("Failed to write file: {}", e),
                     data: serde_json::json!({}),
                     error: Some(e.to_string()),
+                    structured: None,
                 })
             }
         }
     }
 }
 
+/// Lines of context kept around each change in a write preview
+const DIFF_CONTEXT_LINES: usize = 2;
+
+/// Longest new-file preview, in lines
+const MAX_PREVIEW_LINES: usize = 200;
+
+/// Line diffs are quadratic; above this many line pairs only sizes are shown
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// What writing `new_content` to `path` would change: a line diff against
+/// the current file, or the content itself for a new file.
+fn write_preview(path: &Path, new_content: &str) -> String {
+    match std::fs::read_to_string(path) {
+        Ok(old) if old == new_content => "No changes: file already has this content".to_string(),
+        Ok(old) => format!("--- {0}\n+++ {0}\n{1}", path.display(), line_diff(&old, new_content)),
+        Err(_) => {
+            let total = new_content.lines().count();
+            let mut preview = format!("New file: {}\n", path.display());
+            for line in new_content.lines().take(MAX_PREVIEW_LINES) {
+                preview.push('+');
+                preview.push_str(line);
+                preview.push('\n');
+            }
+            if total > MAX_PREVIEW_LINES {
+                preview.push_str(&format!("... {} more lines\n", total - MAX_PREVIEW_LINES));
+            }
+            preview
+        }
+    }
+}
+
+/// Unified-style line diff (`-` removed, `+` added, ` ` context) showing only
+/// changed lines and `DIFF_CONTEXT_LINES` around them, with `...` for gaps.
+fn line_diff(old: &str, new: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    if a.len().saturating_mul(b.len()) > MAX_DIFF_CELLS {
+        return format!("(too large to diff: {} lines -> {} lines)\n", a.len(), b.len());
+    }
+
+    // lcs[i][j] = longest common subsequence of a[i..] and b[j..]
+    let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            ops.push((' ', a[i]));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(('-', a[i]));
+            i += 1;
+        } else {
+            ops.push(('+', b[j]));
+            j += 1;
+        }
+    }
+
+    let mut keep = vec![false; ops.len()];
+    for (k, (op, _)) in ops.iter().enumerate() {
+        if *op != ' ' {
+            let end = (k + DIFF_CONTEXT_LINES + 1).min(ops.len());
+            keep[k.saturating_sub(DIFF_CONTEXT_LINES)..end].fill(true);
+        }
+    }
+
+    let mut out = String::new();
+    let mut skipped = false;
+    for (k, (op, line)) in ops.iter().enumerate() {
+        if !keep[k] {
+            skipped = true;
+            continue;
+        }
+        if skipped && !out.is_empty() {
+            out.push_str("...\n");
+        }
+        skipped = false;
+        out.push(*op);
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
 /// List directory tool
 pub struct ListDirectoryTool {
     permission_manager: Arc<PermissionManager>,
@@ -514,7 +686,8 @@ impl AgentTool for ListDirectoryTool {
     }
 
     fn description(&self) -> &str {
-        "List contents of a directory. Returns files and subdirectories."
+        "List contents of a directory. Returns files and subdirectories, optionally \
+         filtered by globs and .gitignore rules."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -524,6 +697,24 @@ impl AgentTool for ListDirectoryTool {
                 "path": {
                     "type": "string",
                     "description": "Path to the directory to list"
+                },
+                "include_globs": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Only list files matching one of these globs, e.g. [\"*.rs\"]. Directories are always listed."
+                },
+                "exclude_globs": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Skip files and directories matching any of these globs, e.g. [\"node_modules\"]"
+                },
+                "respect_gitignore": {
+                    "type": "boolean",
+                    "description": "Skip entries ignored by .gitignore files, and the .git directory (default false)"
+                },
+                "max_entries": {
+                    "type": "integer",
+                    "description": "Maximum number of entries to return (default 500)"
                 }
             },
             "required": ["path"]
@@ -548,6 +739,7 @@ impl AgentTool for ListDirectoryTool {
             path: path.clone(),
             reason: format!("Agent wants to list directory: {}", path.display()),
             agent_id: user_id.clone(),
+            preview: None,
         }).await?;
 
         if !permission.allowed {
@@ -564,54 +756,60 @@ impl AgentTool for ListDirectoryTool {
                 output: "Permission denied by user or sandboxing rules".to_string(),
                 data: serde_json::json!({}),
                 error: Some("Permission denied".to_string()),
+                structured: None,
             });
         }
 
-        // List directory
-        match std::fs::read_dir(&path) {
-            Ok(entries) => {
-                let mut files = Vec::new();
-                let mut dirs = Vec::new();
-
-                for entry in entries {
-                    if let Ok(entry) = entry {
-                        let path = entry.path();
-                        let name = entry.file_name().to_string_lossy().to_string();
-
-                        if path.is_dir() {
-                            dirs.push(name);
-                        } else {
-                            let size = entry.metadata().ok().map(|m| m.len()).unwrap_or(0);
-                            files.push(serde_json::json!({
-                                "name": name,
-                                "size": size,
-                            }));
-                        }
-                    }
-                }
+        let filter = match ListFilter::from_parameters(&path, &input.parameters) {
+            Ok(filter) => filter,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: format!("Invalid list_directory parameters: {}", e),
+                    data: serde_json::json!({}),
+                    error: Some(e.to_string()),
+                    structured: None,
+                });
+            }
+        };
 
+        // List directory
+        match filter.list(&path) {
+            Ok(listing) => {
+                let shown = listing.files.len() + listing.directories.len();
                 self.permission_manager.log_operation(
                     user_id,
                     FilePermission::ListDirectory,
                     path.clone(),
                     true,
-                    format!("Found {} files, {} directories", files.len(), dirs.len()),
+                    format!("Found {} files, {} directories", listing.files.len(), listing.directories.len()),
                 ).await;
 
+                let mut output = format!(
+                    "Listed directory '{}': {} files, {} directories",
+                    path.display(),
+                    listing.files.len(),
+                    listing.directories.len()
+                );
+                if listing.truncated {
+                    output.push_str(&format!(
+                        " (truncated: showing {} of {} entries, raise max_entries or narrow the globs)",
+                        shown, listing.total
+                    ));
+                }
+
                 Ok(ToolResult {
                     success: true,
-                    output: format!(
-                        "Listed directory '{}': {} files, {} directories",
-                        path.display(),
-                        files.len(),
-                        dirs.len()
-                    ),
+                    output,
                     data: serde_json::json!({
                         "path": path_str,
-                        "files": files,
-                        "directories": dirs,
+                        "files": listing.files,
+                        "directories": listing.directories,
+                        "truncated": listing.truncated,
+                        "total_entries": listing.total,
                     }),
                     error: None,
+                    structured: None,
                 })
             }
             Err(e) => {
@@ -628,8 +826,196 @@ impl AgentTool for ListDirectoryTool {
                     output: format!("Failed to list directory: {}", e),
                     data: serde_json::json!({}),
                     error: Some(e.to_string()),
+                    structured: None,
                 })
             }
         }
     }
 }
+
+/// Default cap on entries returned by `list_directory`
+const DEFAULT_MAX_ENTRIES: usize = 500;
+
+/// Filtering options for `ListDirectoryTool`
+struct ListFilter {
+    include: Option<ignore::overrides::Override>,
+    exclude: Option<ignore::overrides::Override>,
+    respect_gitignore: bool,
+    max_entries: usize,
+}
+
+/// Entries of one directory after filtering
+struct DirectoryListing {
+    files: Vec<serde_json::Value>,
+    directories: Vec<String>,
+    /// Matching entries before the `max_entries` cap
+    total: usize,
+    truncated: bool,
+}
+
+impl ListFilter {
+    fn from_parameters(dir: &Path, params: &serde_json::Value) -> Result<Self> {
+        Ok(Self {
+            include: glob_set(dir, &params["include_globs"])?,
+            exclude: glob_set(dir, &params["exclude_globs"])?,
+            respect_gitignore: params["respect_gitignore"].as_bool().unwrap_or(false),
+            max_entries: params["max_entries"]
+                .as_u64()
+                .map(|n| n as usize)
+                .unwrap_or(DEFAULT_MAX_ENTRIES),
+        })
+    }
+
+    /// List the immediate children of `dir`, sorted by name
+    fn list(&self, dir: &Path) -> Result<DirectoryListing> {
+        if !dir.is_dir() {
+            anyhow::bail!("Not a directory: {}", dir.display());
+        }
+
+        let respect_gitignore = self.respect_gitignore;
+        let walker = ignore::WalkBuilder::new(dir)
+            .max_depth(Some(1))
+            .standard_filters(false)
+            .git_ignore(respect_gitignore)
+            .git_exclude(respect_gitignore)
+            .ignore(respect_gitignore)
+            .parents(respect_gitignore)
+            .require_git(false)
+            .filter_entry(move |entry| !(respect_gitignore && entry.file_name() == ".git"))
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .build();
+
+        let mut listing = DirectoryListing {
+            files: Vec::new(),
+            directories: Vec::new(),
+            total: 0,
+            truncated: false,
+        };
+
+        for entry in walker {
+            // An unreadable entry (permissions, broken symlink) shouldn't
+            // hide the rest of the directory
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    tracing::warn!("Skipping entry in {}: {}", dir.display(), e);
+                    continue;
+                }
+            };
+            if entry.depth() == 0 {
+                continue;
+            }
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            let matches = |set: &Option<ignore::overrides::Override>| {
+                set.as_ref().is_some_and(|set| set.matched(entry.path(), is_dir).is_whitelist())
+            };
+            if matches(&self.exclude) || (!is_dir && self.include.is_some() && !matches(&self.include)) {
+                continue;
+            }
+
+            listing.total += 1;
+            if listing.files.len() + listing.directories.len() >= self.max_entries {
+                listing.truncated = true;
+                continue;
+            }
+
+            let name = entry.file_name().to_string_lossy().to_string();
+            if is_dir {
+                listing.directories.push(name);
+            } else {
+                let size = entry.metadata().ok().map(|m| m.len()).unwrap_or(0);
+                listing.files.push(serde_json::json!({
+                    "name": name,
+                    "size": size,
+                }));
+            }
+        }
+
+        Ok(listing)
+    }
+}
+
+/// Compile a JSON array of globs, relative to `dir`. `None` when absent or empty.
+fn glob_set(dir: &Path, globs: &serde_json::Value) -> Result<Option<ignore::overrides::Override>> {
+    let Some(globs) = globs.as_array().filter(|globs| !globs.is_empty()) else {
+        return Ok(None);
+    };
+
+    let mut builder = ignore::overrides::OverrideBuilder::new(dir);
+    for glob in globs {
+        let glob = glob.as_str().ok_or_else(|| anyhow::anyhow!("Globs must be strings"))?;
+        builder.add(glob).with_context(|| format!("Invalid glob '{}'", glob))?;
+    }
+    Ok(Some(builder.build()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_diff_shows_changes_with_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\n";
+        let new = "a\nb\nc\nd\ne\nf\nG\nh\ni\n";
+        assert_eq!(line_diff(old, new), " e\n f\n-g\n+G\n h\n+i\n");
+
+        let new = "A\nb\nc\nd\ne\nf\ng\nH\n";
+        assert_eq!(line_diff(old, new), "-a\n+A\n b\n c\n...\n f\n g\n-h\n+H\n");
+    }
+
+    #[test]
+    fn test_list_filter_applies_gitignore_globs_and_cap() {
+        let dir = std::env::temp_dir().join(format!("shodh_list_{}", uuid::Uuid::new_v4()));
+        for sub in ["src", "node_modules", "target", ".git"] {
+            std::fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        for file in ["a.rs", "b.log", "c.rs"] {
+            std::fs::write(dir.join(file), "x").unwrap();
+        }
+        std::fs::write(dir.join(".gitignore"), "target/\n*.log\n").unwrap();
+
+        let filter = ListFilter::from_parameters(&dir, &serde_json::json!({
+            "respect_gitignore": true,
+            "include_globs": ["*.rs"],
+            "exclude_globs": ["node_modules"],
+        })).unwrap();
+        let listing = filter.list(&dir).unwrap();
+        let files: Vec<&str> = listing.files.iter().map(|f| f["name"].as_str().unwrap()).collect();
+        assert_eq!(files, vec!["a.rs", "c.rs"]);
+        assert_eq!(listing.directories, vec!["src"]);
+        assert!(!listing.truncated);
+
+        let capped = ListFilter::from_parameters(&dir, &serde_json::json!({ "max_entries": 2 })).unwrap();
+        let listing = capped.list(&dir).unwrap();
+        assert_eq!(listing.files.len() + listing.directories.len(), 2);
+        assert_eq!(listing.total, 8);
+        assert!(listing.truncated);
+
+        assert!(ListFilter::from_parameters(&dir, &serde_json::json!({ "include_globs": ["a[b"] })).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_write_file_dry_run_leaves_disk_untouched() {
+        let dir = std::env::temp_dir().join(format!("shodh_dry_run_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notes.txt");
+        std::fs::write(&path, "keep\nold line\n").unwrap();
+
+        let manager = Arc::new(PermissionManager::new());
+        let tool = WriteFileTool::new(manager.clone());
+        let result = tool.execute(ToolInput {
+            tool_id: "write_file".to_string(),
+            parameters: serde_json::json!({ "path": path.to_str().unwrap(), "content": "keep\nnew line\n" }),
+            dry_run: true,
+        }, AgentContext::new()).await.unwrap();
+
+        assert!(result.success);
+        assert!(result.data["diff"].as_str().unwrap().contains("-old line\n+new line"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep\nold line\n");
+        let audit = manager.get_audit_log().await;
+        assert_eq!(audit.len(), 1);
+        assert!(audit[0].dry_run);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
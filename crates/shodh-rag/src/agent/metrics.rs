@@ -53,7 +53,8 @@ pub struct ExecutionRecord {
     pub success: bool,
     pub error_message: Option<String>,
 
-    /// Detailed steps (optional, for drill-down)
+    /// Detailed steps (optional, for drill-down). Only the most recent
+    /// executions keep their trace; older records have this cleared.
     pub steps: Option<Vec<ExecutionStep>>,
 
     /// Additional metadata
@@ -68,6 +69,21 @@ pub enum ExecutionStatus {
     Completed,
     Failed,
     Cancelled,
+    /// Stopped by the agent's token or wall-clock budget
+    #[serde(rename = "budget_exceeded")]
+    BudgetExceeded,
+}
+
+impl ExecutionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+            Self::BudgetExceeded => "budget_exceeded",
+        }
+    }
 }
 
 /// Aggregated metrics for an agent
@@ -81,6 +97,8 @@ pub struct AgentMetrics {
     pub successful_executions: usize,
     pub failed_executions: usize,
     pub cancelled_executions: usize,
+    #[serde(default)]
+    pub budget_exceeded_executions: usize,
 
     // Performance
     pub avg_execution_time_ms: u64,
@@ -118,6 +136,24 @@ pub enum HealthState {
     Down,
 }
 
+/// Step-by-step trace of a past execution, for timeline/debugging views
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionTrace {
+    pub execution_id: String,
+    pub agent_id: String,
+    pub agent_name: String,
+    pub query: String,
+    pub response: Option<String>,
+    pub status: ExecutionStatus,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub execution_time_ms: Option<u64>,
+    pub error_message: Option<String>,
+    /// Steps in execution order, each with its timestamp, duration, tool,
+    /// input and output
+    pub steps: Vec<ExecutionStep>,
+}
+
 // ============================================================================
 // Metrics Collector
 // ============================================================================
@@ -135,6 +171,9 @@ pub struct AgentMetricsCollector {
 
     /// Maximum number of recent executions to keep in memory
     max_recent_executions: usize,
+
+    /// How many of the most recent executions keep their full step trace
+    max_retained_traces: usize,
 }
 
 impl AgentMetricsCollector {
@@ -145,6 +184,7 @@ impl AgentMetricsCollector {
             metrics_cache: Arc::new(RwLock::new(HashMap::new())),
             health_cache: Arc::new(RwLock::new(HashMap::new())),
             max_recent_executions: 1000, // Keep last 1000 executions in memory
+            max_retained_traces: 100,
         }
     }
 
@@ -171,6 +211,8 @@ impl AgentMetricsCollector {
             response: Some(result.response.clone()),
             status: if result.success {
                 ExecutionStatus::Completed
+            } else if result.metadata.contains_key("budget_exceeded") {
+                ExecutionStatus::BudgetExceeded
             } else {
                 ExecutionStatus::Failed
             },
@@ -197,13 +239,21 @@ impl AgentMetricsCollector {
             if recent.len() > self.max_recent_executions {
                 recent.remove(0);
             }
+
+            // Drop the trace of the execution that just left the trace window
+            if let Some(idx) = recent.len().checked_sub(self.max_retained_traces + 1) {
+                recent[idx].steps = None;
+            }
         }
 
         // Update metrics
         self.update_metrics(&record).await?;
 
         // Update health status
-        self.update_health(agent_id, result.success).await?;
+        // A budget stop is the agent's own limit at work, not a fault
+        if record.status != ExecutionStatus::BudgetExceeded {
+            self.update_health(agent_id, result.success).await?;
+        }
 
         Ok(execution_id)
     }
@@ -224,6 +274,7 @@ impl AgentMetricsCollector {
                 successful_executions: 0,
                 failed_executions: 0,
                 cancelled_executions: 0,
+                budget_exceeded_executions: 0,
                 avg_execution_time_ms: 0,
                 p95_execution_time_ms: 0,
                 p99_execution_time_ms: 0,
@@ -241,6 +292,7 @@ impl AgentMetricsCollector {
             ExecutionStatus::Completed => metrics.successful_executions += 1,
             ExecutionStatus::Failed => metrics.failed_executions += 1,
             ExecutionStatus::Cancelled => metrics.cancelled_executions += 1,
+            ExecutionStatus::BudgetExceeded => metrics.budget_exceeded_executions += 1,
             _ => {}
         }
 
@@ -363,6 +415,37 @@ impl AgentMetricsCollector {
         Ok(recent.iter().rev().take(limit).cloned().collect())
     }
 
+    /// Get the step trace of a past execution by its record ID
+    pub async fn get_execution_trace(&self, execution_id: &str) -> Result<ExecutionTrace> {
+        let recent = self.recent_executions.read().await;
+        let record = recent
+            .iter()
+            .rev()
+            .find(|e| e.id == execution_id)
+            .ok_or_else(|| anyhow::anyhow!("Execution '{}' not found", execution_id))?;
+        let steps = record.steps.clone().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Trace for execution '{}' is no longer retained (only the last {} are kept)",
+                execution_id,
+                self.max_retained_traces
+            )
+        })?;
+
+        Ok(ExecutionTrace {
+            execution_id: record.id.clone(),
+            agent_id: record.agent_id.clone(),
+            agent_name: record.agent_name.clone(),
+            query: record.query.clone(),
+            response: record.response.clone(),
+            status: record.status.clone(),
+            started_at: record.started_at,
+            completed_at: record.completed_at,
+            execution_time_ms: record.execution_time_ms,
+            error_message: record.error_message.clone(),
+            steps,
+        })
+    }
+
     /// Get metrics for an agent on a specific date
     pub async fn get_agent_metrics(
         &self,
@@ -422,7 +505,10 @@ impl AgentMetricsCollector {
 
         let total_runs = recent_24h.len();
         let successful_runs = recent_24h.iter().filter(|e| e.success).count();
-        let failed_runs = recent_24h.iter().filter(|e| !e.success).count();
+        let budget_exceeded_runs = recent_24h.iter()
+            .filter(|e| e.status == ExecutionStatus::BudgetExceeded)
+            .count();
+        let failed_runs = total_runs - successful_runs - budget_exceeded_runs;
 
         let success_rate = if total_runs > 0 {
             (successful_runs as f32 / total_runs as f32) * 100.0
@@ -434,6 +520,7 @@ impl AgentMetricsCollector {
             total_runs,
             successful_runs,
             failed_runs,
+            budget_exceeded_runs,
             success_rate,
             active_now: 0, // Will be set by monitor
         })
@@ -452,6 +539,54 @@ pub struct DashboardSummary {
     pub total_runs: usize,
     pub successful_runs: usize,
     pub failed_runs: usize,
+    #[serde(default)]
+    pub budget_exceeded_runs: usize,
     pub success_rate: f32,
     pub active_now: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::executor::StepType;
+
+    fn result_with_step(output: &str) -> ExecutionResult {
+        ExecutionResult {
+            response: output.to_string(),
+            steps: vec![ExecutionStep {
+                step_number: 1,
+                step_type: StepType::LLMGeneration,
+                timestamp: 1,
+                duration_ms: 5,
+                input: "query".to_string(),
+                output: output.to_string(),
+                tool_used: Some("rag_search".to_string()),
+                success: true,
+            }],
+            tools_used: vec!["rag_search".to_string()],
+            execution_time_ms: 5,
+            success: true,
+            error: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execution_traces_are_bounded() {
+        let collector = AgentMetricsCollector { max_retained_traces: 2, ..AgentMetricsCollector::new() };
+        let context = AgentContext::new();
+        let mut ids = Vec::new();
+        for i in 0..3 {
+            let result = result_with_step(&format!("answer {}", i));
+            ids.push(collector.record_execution("a", "Agent", &context, &result, Utc::now()).await.unwrap());
+        }
+
+        assert!(collector.get_execution_trace(&ids[0]).await.is_err());
+        let trace = collector.get_execution_trace(&ids[2]).await.unwrap();
+        assert_eq!(trace.status, ExecutionStatus::Completed);
+        assert_eq!(trace.steps.len(), 1);
+        assert_eq!(trace.steps[0].output, "answer 2");
+        assert_eq!(trace.steps[0].tool_used.as_deref(), Some("rag_search"));
+        assert!(collector.get_execution_trace("missing").await.is_err());
+    }
+}
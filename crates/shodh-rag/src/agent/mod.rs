@@ -14,7 +14,7 @@
 
 use anyhow::{Result, Context as AnyhowContext};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
@@ -91,16 +91,16 @@ pub use workflow::{
 };
 pub use metrics::{
     AgentMetricsCollector, ExecutionRecord, ExecutionStatus, AgentMetrics,
-    AgentHealthStatus, HealthState, DashboardSummary,
+    AgentHealthStatus, HealthState, DashboardSummary, ExecutionTrace,
 };
 pub use monitor::{AgentMonitor, ActiveExecution};
 pub use code_executor::{
     CodeExecutor, CodeLanguage, ExecutionConfig, CodeExecutionResult,
-    validate_code_safety,
+    validate_code_safety, detect_language, resolve_language,
 };
 pub use tool_loop::{
     run_tool_loop, run_tool_loop_stream, tool_descriptions_to_schemas,
-    ToolLoopConfig, ToolLoopResult, ToolLoopEvent, ToolInvocation, ToolLoopEmitter,
+    ToolLoopConfig, ToolLoopResult, ToolLoopEvent, ToolInvocation, ToolLoopEmitter, BudgetExceeded,
 };
 pub use rag_tools::register_rag_tools;
 pub use dynamic_tool::{DynamicTool, DynamicToolDef, ToolCallback, register_dynamic_tools};
@@ -129,6 +129,8 @@ pub struct AgentSystem {
     llm_manager_ref: Option<Arc<RwLock<Option<LLMManager>>>>,
     /// Registered crews (multi-agent teams)
     crews: Arc<RwLock<HashMap<String, crew::CrewDefinition>>>,
+    /// Crews deleted since the last save, whose files should go too
+    deleted_crews: Arc<RwLock<HashSet<String>>>,
 }
 
 impl AgentSystem {
@@ -142,6 +144,7 @@ impl AgentSystem {
             monitor: Arc::new(AgentMonitor::new()),
             llm_manager_ref: None,
             crews: Arc::new(RwLock::new(HashMap::new())),
+            deleted_crews: Arc::new(RwLock::new(HashSet::new())),
         }
     }
 
@@ -328,15 +331,11 @@ impl AgentSystem {
                 .map_err(|_| anyhow::anyhow!("Agent '{}' not found in registry", member.agent_id))?;
         }
 
-        // Validate hierarchical coordinator
-        if let crew::CrewProcess::Hierarchical { ref coordinator_id } = crew_def.process {
-            if !crew_def.agents.iter().any(|a| a.agent_id == *coordinator_id) {
-                anyhow::bail!("Coordinator '{}' must be a member of the crew", coordinator_id);
-            }
-        }
+        crew_def.validate()?;
 
         let mut crews = self.crews.write().await;
         crews.insert(id.clone(), crew_def);
+        self.deleted_crews.write().await.remove(&id);
         tracing::info!(crew_id = %id, "Registered crew");
         Ok(id)
     }
@@ -361,10 +360,90 @@ impl AgentSystem {
         if crews.remove(crew_id).is_none() {
             anyhow::bail!("Crew '{}' not found", crew_id);
         }
+        self.deleted_crews.write().await.insert(crew_id.to_string());
         tracing::info!(crew_id = %crew_id, "Deleted crew");
         Ok(())
     }
 
+    /// Save all crews to a directory as `<crew_id>.json`. Files of crews
+    /// deleted through `delete_crew` are removed so deletions persist too;
+    /// other files (e.g. crews skipped at load for missing agents) are kept.
+    pub async fn save_crews_to_directory(&self, dir_path: &str) -> Result<()> {
+        let path = std::path::Path::new(dir_path);
+        std::fs::create_dir_all(path)?;
+
+        let crews = self.crews.read().await;
+        for (crew_id, crew_def) in crews.iter() {
+            let content = serde_json::to_string_pretty(crew_def)
+                .context("Failed to serialize crew")?;
+            std::fs::write(path.join(format!("{}.json", crew_id)), content)
+                .context("Failed to write crew file")?;
+        }
+
+        let mut deleted = self.deleted_crews.write().await;
+        for crew_id in deleted.iter().filter(|id| !crews.contains_key(*id)) {
+            let file_path = path.join(format!("{}.json", crew_id));
+            match std::fs::remove_file(&file_path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e).context("Failed to remove deleted crew file"),
+            }
+        }
+        deleted.clear();
+
+        Ok(())
+    }
+
+    /// Load crews from a directory of JSON files. Crews referencing agents
+    /// that are no longer registered, or that fail validation, are skipped
+    /// with a warning. Returns the IDs of the crews loaded.
+    pub async fn load_crews_from_directory(&self, dir_path: &str) -> Result<Vec<String>> {
+        let path = std::path::Path::new(dir_path);
+        if !path.exists() {
+            anyhow::bail!("Directory does not exist: {}", dir_path);
+        }
+
+        let mut loaded_ids = Vec::new();
+        for entry in std::fs::read_dir(path)? {
+            let file_path = entry?.path();
+            if !file_path.is_file() || file_path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+
+            let crew_def: crew::CrewDefinition = match std::fs::read_to_string(&file_path)
+                .context("Failed to read crew file")
+                .and_then(|content| serde_json::from_str(&content).context("Failed to parse crew definition"))
+            {
+                Ok(def) => def,
+                Err(e) => {
+                    tracing::error!(path = ?file_path, error = %e, "Failed to load crew from file");
+                    continue;
+                }
+            };
+
+            let mut missing = Vec::new();
+            for member in &crew_def.agents {
+                if self.get_agent(&member.agent_id).await.is_err() {
+                    missing.push(member.agent_id.clone());
+                }
+            }
+            if !missing.is_empty() {
+                tracing::warn!(crew = %crew_def.name, missing = ?missing, "Skipping crew with unknown agents");
+                continue;
+            }
+            if let Err(e) = crew_def.validate() {
+                tracing::warn!(crew = %crew_def.name, error = %e, "Skipping invalid crew");
+                continue;
+            }
+
+            loaded_ids.push(crew_def.id.clone());
+            self.deleted_crews.write().await.remove(&crew_def.id);
+            self.crews.write().await.insert(crew_def.id.clone(), crew_def);
+        }
+
+        Ok(loaded_ids)
+    }
+
     /// Execute a crew task with optional streaming progress
     pub async fn execute_crew(
         &self,
@@ -421,4 +500,66 @@ mod tests {
         let retrieved = system.get_agent(&agent_id).await.unwrap();
         assert_eq!(retrieved.name, "TestAgent");
     }
+
+    #[tokio::test]
+    async fn test_crews_round_trip_through_directory() {
+        let system = AgentSystem::new();
+        let mut agent_ids = Vec::new();
+        for name in ["Lead", "Researcher"] {
+            let id = system.register_agent(AgentDefinition {
+                id: Uuid::new_v4().to_string(),
+                name: name.to_string(),
+                description: String::new(),
+                system_prompt: "You are a helpful assistant".to_string(),
+                config: AgentConfig::default(),
+                capabilities: vec![],
+                tools: vec![],
+                metadata: HashMap::new(),
+            }).await.unwrap();
+            agent_ids.push(id);
+        }
+
+        let crew_id = system.register_crew(crew::CrewDefinition {
+            id: String::new(),
+            name: "Research Team".to_string(),
+            description: "Lead delegates to a researcher".to_string(),
+            agents: agent_ids.iter().enumerate().map(|(i, id)| crew::CrewMember {
+                agent_id: id.clone(),
+                role: format!("role {}", i),
+                goal: String::new(),
+                order: i,
+            }).collect(),
+            process: crew::CrewProcess::Hierarchical { coordinator_id: agent_ids[0].clone() },
+            config: crew::CrewConfig::default(),
+        }).await.unwrap();
+
+        let dir = std::env::temp_dir().join(format!("shodh_crews_{}", Uuid::new_v4()));
+        let dir_str = dir.to_str().unwrap();
+        system.save_crews_to_directory(dir_str).await.unwrap();
+
+        // A crew pointing at an agent this system doesn't know is skipped
+        let mut dangling = system.get_crew(&crew_id).await.unwrap();
+        dangling.id = "dangling".to_string();
+        dangling.agents[1].agent_id = "missing-agent".to_string();
+        std::fs::write(dir.join("dangling.json"), serde_json::to_string(&dangling).unwrap()).unwrap();
+
+        system.delete_crew(&crew_id).await.unwrap();
+        let loaded = system.load_crews_from_directory(dir_str).await.unwrap();
+        assert_eq!(loaded, vec![crew_id.clone()]);
+
+        let restored = system.get_crew(&crew_id).await.unwrap();
+        assert_eq!(restored.name, "Research Team");
+        assert_eq!(restored.agents.len(), 2);
+        match restored.process {
+            crew::CrewProcess::Hierarchical { coordinator_id } => assert_eq!(coordinator_id, agent_ids[0]),
+            other => panic!("expected hierarchical process, got {:?}", other),
+        }
+
+        // Saving again removes the deleted crew's file but keeps the skipped one
+        system.delete_crew(&crew_id).await.unwrap();
+        system.save_crews_to_directory(dir_str).await.unwrap();
+        assert!(!dir.join(format!("{}.json", crew_id)).exists());
+        assert!(dir.join("dangling.json").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -134,6 +134,7 @@ impl AgentTool for AgentDelegateTool {
                         "execution_time_ms": result.execution_time_ms,
                     }),
                     error: result.error,
+                    structured: None,
                 })
             }
             Err(e) => Ok(ToolResult {
@@ -141,6 +142,7 @@ impl AgentTool for AgentDelegateTool {
                 output: format!("Agent '{}' failed: {}", self.agent_name, e),
                 data: serde_json::json!({}),
                 error: Some(e.to_string()),
+                structured: None,
             }),
         }
     }
@@ -94,14 +94,17 @@ impl AgentTool for LiveRAGSearchTool {
             )
         };
 
+        let data = serde_json::json!({
+            "query": query,
+            "results": formatted,
+            "total": results.len(),
+        });
+
         Ok(ToolResult {
             success: !results.is_empty(),
             output: summary,
-            data: serde_json::json!({
-                "query": query,
-                "results": formatted,
-                "total": results.len(),
-            }),
+            structured: Some(data.clone()),
+            data,
             error: None,
         })
     }
@@ -161,14 +164,17 @@ impl AgentTool for ListSourcesTool {
             doc_count, total_chunks,
         );
 
+        let data = serde_json::json!({
+            "total_documents": doc_count,
+            "total_chunks": total_chunks,
+            "documents": sources,
+        });
+
         Ok(ToolResult {
             success: true,
             output: summary,
-            data: serde_json::json!({
-                "total_documents": doc_count,
-                "total_chunks": total_chunks,
-                "documents": sources,
-            }),
+            structured: Some(data.clone()),
+            data,
             error: None,
         })
     }
@@ -261,6 +267,17 @@ impl AgentTool for GetDocumentChunksTool {
             } else {
                 full_text
             },
+            // Text is already in `output`; the payload carries only chunk metadata
+            structured: Some(serde_json::json!({
+                "doc_id": doc_id,
+                "total": results.len(),
+                "chunks": results.iter().map(|r| serde_json::json!({
+                    "chunk_id": r.chunk_id,
+                    "title": r.title,
+                    "source": r.source,
+                    "heading": r.heading,
+                })).collect::<Vec<_>>(),
+            })),
             data: serde_json::json!({
                 "doc_id": doc_id,
                 "chunks": chunks,
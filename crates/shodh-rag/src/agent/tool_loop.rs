@@ -7,10 +7,12 @@
 use anyhow::{Result, anyhow};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Instant;
+use tokio::sync::{RwLock, Semaphore};
 
+use crate::chat::estimate_tokens;
 use crate::llm::{
-    ChatMessage, ChatResponse, ChatStreamEvent, LLMManager, ToolCall, ToolSchema,
+    CancellationToken, ChatMessage, ChatResponse, ChatStreamEvent, LLMManager, ToolCall, ToolSchema,
 };
 use super::tools::{AgentTool, ToolRegistry};
 use super::context::AgentContext;
@@ -24,6 +26,15 @@ pub struct ToolLoopConfig {
     pub tool_timeout_secs: u64,
     /// If true, emit streaming events via the callback.
     pub streaming: bool,
+    /// Maximum tool calls from one assistant message executed concurrently.
+    pub max_parallel_tools: usize,
+    /// Cancelled to stop the loop before it dispatches the next batch of tool calls.
+    pub cancel_token: Option<CancellationToken>,
+    /// Stop once the estimated prompt + completion tokens across all LLM
+    /// calls reach this many.
+    pub max_total_tokens: Option<usize>,
+    /// Stop once this instant has passed.
+    pub deadline: Option<Instant>,
 }
 
 impl Default for ToolLoopConfig {
@@ -32,6 +43,46 @@ impl Default for ToolLoopConfig {
             max_iterations: 10,
             tool_timeout_secs: 30,
             streaming: true,
+            max_parallel_tools: 4,
+            cancel_token: None,
+            max_total_tokens: None,
+            deadline: None,
+        }
+    }
+}
+
+impl ToolLoopConfig {
+    fn is_cancelled(&self) -> bool {
+        self.cancel_token.as_ref().is_some_and(|t| t.is_cancelled())
+    }
+
+    /// The budget that is used up, if any, given the tokens spent so far.
+    fn budget_exceeded(&self, tokens_used: usize) -> Option<BudgetExceeded> {
+        if let Some(limit) = self.max_total_tokens.filter(|&limit| tokens_used >= limit) {
+            return Some(BudgetExceeded::Tokens { limit, used: tokens_used });
+        }
+        if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Some(BudgetExceeded::WallClock);
+        }
+        None
+    }
+}
+
+/// An execution budget the loop ran out of before the LLM finished.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BudgetExceeded {
+    Tokens { limit: usize, used: usize },
+    WallClock,
+}
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tokens { limit, used } => {
+                write!(f, "token budget exceeded (~{} of {} tokens used)", used, limit)
+            }
+            Self::WallClock => write!(f, "wall-clock budget exceeded"),
         }
     }
 }
@@ -55,6 +106,10 @@ pub struct ToolLoopResult {
     pub tool_invocations: Vec<ToolInvocation>,
     /// Total number of LLM round-trips.
     pub iterations: usize,
+    /// Estimated prompt + completion tokens across all LLM calls.
+    pub estimated_tokens: usize,
+    /// Set when the loop stopped early because a budget ran out.
+    pub budget_exceeded: Option<BudgetExceeded>,
 }
 
 /// Callback for streaming events during the loop.
@@ -68,8 +123,11 @@ pub trait ToolLoopEmitter: Send + Sync {
 /// Run the ReAct tool-calling loop.
 ///
 /// 1. Send `messages` + `tool_schemas` to the LLM via `chat()`.
-/// 2. If the LLM returns `ToolCalls` → execute each tool → append results → loop.
+/// 2. If the LLM returns `ToolCalls` → execute them concurrently → append results
+///    in call order → loop.
 /// 3. If the LLM returns `Content` → done.
+///
+/// Stops early, without another LLM call, once a budget in `config` runs out.
 pub async fn run_tool_loop(
     llm: &LLMManager,
     tool_registry: &ToolRegistry,
@@ -81,8 +139,14 @@ pub async fn run_tool_loop(
 ) -> Result<ToolLoopResult> {
     let mut invocations = Vec::new();
     let mut iterations = 0;
+    let mut tokens_used = 0;
 
     loop {
+        if let Some(exceeded) = config.budget_exceeded(tokens_used) {
+            tracing::warn!(%exceeded, iterations, "Tool loop stopped early");
+            return Ok(budget_exceeded_result(exceeded, invocations, iterations, tokens_used));
+        }
+
         iterations += 1;
         if iterations > config.max_iterations {
             tracing::warn!(
@@ -90,7 +154,9 @@ pub async fn run_tool_loop(
                 "Tool loop hit max iterations, forcing text response"
             );
             // Ask LLM to respond without tools
+            let prompt_tokens = estimate_messages_tokens(messages);
             let response = llm.chat(messages, &[], ).await?;
+            tokens_used += prompt_tokens + estimate_response_tokens(&response);
             let content = match response {
                 ChatResponse::Content(text) => text,
                 ChatResponse::ToolCalls(_) => {
@@ -103,6 +169,8 @@ pub async fn run_tool_loop(
                 content,
                 tool_invocations: invocations,
                 iterations,
+                estimated_tokens: tokens_used,
+                budget_exceeded: None,
             });
         }
 
@@ -115,7 +183,9 @@ pub async fn run_tool_loop(
         }
 
         // Call LLM with tools
+        let prompt_tokens = estimate_messages_tokens(messages);
         let response = llm.chat(messages, tool_schemas).await?;
+        tokens_used += prompt_tokens + estimate_response_tokens(&response);
 
         match response {
             ChatResponse::Content(text) => {
@@ -124,6 +194,8 @@ pub async fn run_tool_loop(
                     content: text,
                     tool_invocations: invocations,
                     iterations,
+                    estimated_tokens: tokens_used,
+                    budget_exceeded: None,
                 });
             }
             ChatResponse::ToolCalls(tool_calls) => {
@@ -137,48 +209,22 @@ pub async fn run_tool_loop(
                 // Append the assistant's tool call message to history
                 messages.push(ChatMessage::assistant_tool_calls(tool_calls.clone()));
 
-                // Execute each tool call
-                for tc in &tool_calls {
-                    if let Some(em) = emitter {
+                if config.is_cancelled() {
+                    return Err(anyhow!("Tool loop cancelled"));
+                }
+                if let Some(em) = emitter {
+                    for tc in &tool_calls {
                         em.on_tool_start(&tc.name, &tc.arguments);
                     }
+                }
 
-                    let start = std::time::Instant::now();
-                    let result = execute_tool_call(
-                        tool_registry,
-                        tc,
-                        agent_context,
-                        config.tool_timeout_secs,
-                    )
-                    .await;
-                    let duration_ms = start.elapsed().as_millis() as u64;
-
-                    let (output, success) = match result {
-                        Ok(tool_result) => (tool_result.output.clone(), tool_result.success),
-                        Err(e) => (format!("Tool execution error: {}", e), false),
-                    };
-
-                    let invocation = ToolInvocation {
-                        tool_name: tc.name.clone(),
-                        arguments: serde_json::from_str(&tc.arguments)
-                            .unwrap_or(serde_json::json!({})),
-                        result: output.clone(),
-                        success,
-                        duration_ms,
-                    };
-
+                let executed = execute_tool_calls(tool_registry, &tool_calls, agent_context, config).await;
+                for (tc, (invocation, content)) in tool_calls.iter().zip(executed) {
                     if let Some(em) = emitter {
                         em.on_tool_complete(&invocation);
                     }
-
                     invocations.push(invocation);
-
-                    // Append tool result message
-                    messages.push(ChatMessage::tool_result(
-                        &tc.id,
-                        &tc.name,
-                        &output,
-                    ));
+                    messages.push(ChatMessage::tool_result(&tc.id, &tc.name, &content));
                 }
             }
         }
@@ -198,11 +244,20 @@ pub async fn run_tool_loop_stream(
 ) -> Result<ToolLoopResult> {
     let mut invocations = Vec::new();
     let mut iterations = 0;
+    let mut tokens_used = 0;
 
     loop {
+        if let Some(exceeded) = config.budget_exceeded(tokens_used) {
+            tracing::warn!(%exceeded, iterations, "Tool loop stopped early");
+            let _ = event_tx.send(ToolLoopEvent::Done).await;
+            return Ok(budget_exceeded_result(exceeded, invocations, iterations, tokens_used));
+        }
+
         iterations += 1;
         if iterations > config.max_iterations {
+            let prompt_tokens = estimate_messages_tokens(messages);
             let response = llm.chat(messages, &[]).await?;
+            tokens_used += prompt_tokens + estimate_response_tokens(&response);
             let content = match response {
                 ChatResponse::Content(text) => text,
                 ChatResponse::ToolCalls(_) => "Max tool iterations reached.".to_string(),
@@ -212,10 +267,13 @@ pub async fn run_tool_loop_stream(
                 content,
                 tool_invocations: invocations,
                 iterations,
+                estimated_tokens: tokens_used,
+                budget_exceeded: None,
             });
         }
 
         // Use streaming chat
+        tokens_used += estimate_messages_tokens(messages);
         let mut rx = llm.chat_stream(messages, tool_schemas).await?;
 
         let mut content_acc = String::new();
@@ -229,6 +287,11 @@ pub async fn run_tool_loop_stream(
                         .send(ToolLoopEvent::ContentDelta(delta))
                         .await;
                 }
+                ChatStreamEvent::ToolCallStarted { name, .. } => {
+                    let _ = event_tx
+                        .send(ToolLoopEvent::ToolCallStarted { name })
+                        .await;
+                }
                 ChatStreamEvent::ToolCallComplete(tc) => {
                     let _ = event_tx
                         .send(ToolLoopEvent::ToolCallRequested {
@@ -241,6 +304,7 @@ pub async fn run_tool_loop_stream(
                 ChatStreamEvent::Done => break,
             }
         }
+        tokens_used += estimate_tokens(&content_acc) + estimate_tool_calls_tokens(&tool_calls);
 
         // If LLM returned content (no tool calls), we're done
         if tool_calls.is_empty() {
@@ -249,43 +313,27 @@ pub async fn run_tool_loop_stream(
                 content: content_acc,
                 tool_invocations: invocations,
                 iterations,
+                estimated_tokens: tokens_used,
+                budget_exceeded: None,
             });
         }
 
         // LLM wants tool calls — execute them
         messages.push(ChatMessage::assistant_tool_calls(tool_calls.clone()));
 
-        for tc in &tool_calls {
-            let start = std::time::Instant::now();
-            let result = execute_tool_call(
-                tool_registry,
-                tc,
-                agent_context,
-                config.tool_timeout_secs,
-            )
-            .await;
-            let duration_ms = start.elapsed().as_millis() as u64;
-
-            let (output, success) = match result {
-                Ok(tr) => (tr.output.clone(), tr.success),
-                Err(e) => (format!("Tool error: {}", e), false),
-            };
-
-            let invocation = ToolInvocation {
-                tool_name: tc.name.clone(),
-                arguments: serde_json::from_str(&tc.arguments)
-                    .unwrap_or(serde_json::json!({})),
-                result: output.clone(),
-                success,
-                duration_ms,
-            };
+        if config.is_cancelled() {
+            let _ = event_tx.send(ToolLoopEvent::Done).await;
+            return Err(anyhow!("Tool loop cancelled"));
+        }
 
+        let executed = execute_tool_calls(tool_registry, &tool_calls, agent_context, config).await;
+        for (tc, (invocation, content)) in tool_calls.iter().zip(executed) {
             let _ = event_tx
                 .send(ToolLoopEvent::ToolCallCompleted(invocation.clone()))
                 .await;
 
             invocations.push(invocation);
-            messages.push(ChatMessage::tool_result(&tc.id, &tc.name, &output));
+            messages.push(ChatMessage::tool_result(&tc.id, &tc.name, &content));
         }
     }
 }
@@ -295,6 +343,8 @@ pub async fn run_tool_loop_stream(
 pub enum ToolLoopEvent {
     /// A token of the LLM's text response.
     ContentDelta(String),
+    /// The LLM started emitting a tool call; arguments are still streaming.
+    ToolCallStarted { name: String },
     /// The LLM requested a tool call (before execution).
     ToolCallRequested { name: String, arguments: String },
     /// A tool call completed.
@@ -303,6 +353,108 @@ pub enum ToolLoopEvent {
     Done,
 }
 
+/// Result for a loop that ran out of budget. No further LLM call is made, so
+/// the content just says why the answer is incomplete.
+fn budget_exceeded_result(
+    exceeded: BudgetExceeded,
+    invocations: Vec<ToolInvocation>,
+    iterations: usize,
+    tokens_used: usize,
+) -> ToolLoopResult {
+    ToolLoopResult {
+        content: format!(
+            "Stopped before finishing: {}. {} tool call(s) completed before the limit.",
+            exceeded,
+            invocations.len()
+        ),
+        tool_invocations: invocations,
+        iterations,
+        estimated_tokens: tokens_used,
+        budget_exceeded: Some(exceeded),
+    }
+}
+
+fn estimate_tool_calls_tokens(tool_calls: &[ToolCall]) -> usize {
+    tool_calls
+        .iter()
+        .map(|tc| estimate_tokens(&tc.name) + estimate_tokens(&tc.arguments))
+        .sum()
+}
+
+/// Rough prompt size of a message history, text plus tool-call arguments
+fn estimate_messages_tokens(messages: &[ChatMessage]) -> usize {
+    messages
+        .iter()
+        .map(|m| {
+            m.content.as_deref().map_or(0, estimate_tokens)
+                + m.tool_calls.as_deref().map_or(0, estimate_tool_calls_tokens)
+        })
+        .sum()
+}
+
+fn estimate_response_tokens(response: &ChatResponse) -> usize {
+    match response {
+        ChatResponse::Content(text) => estimate_tokens(text),
+        ChatResponse::ToolCalls(calls) => estimate_tool_calls_tokens(calls),
+    }
+}
+
+/// Tool-result message text for the LLM: the human-readable output, followed
+/// by the structured payload as JSON when the tool provides one.
+fn tool_message_content(result: &super::tools::ToolResult) -> String {
+    match &result.structured {
+        Some(structured) => format!(
+            "{}\n\nStructured result (JSON):\n{}",
+            result.output,
+            serde_json::to_string(structured).unwrap_or_default(),
+        ),
+        None => result.output.clone(),
+    }
+}
+
+/// Execute the tool calls from one assistant message concurrently, at most
+/// `max_parallel_tools` at a time. Returns each call's invocation record and
+/// tool-result message text, in call order.
+async fn execute_tool_calls(
+    registry: &ToolRegistry,
+    tool_calls: &[ToolCall],
+    agent_context: &AgentContext,
+    config: &ToolLoopConfig,
+) -> Vec<(ToolInvocation, String)> {
+    let semaphore = Semaphore::new(config.max_parallel_tools.max(1));
+
+    let calls = tool_calls.iter().map(|tc| {
+        let semaphore = &semaphore;
+        async move {
+            // The semaphore is never closed, so acquire can't fail
+            let _permit = semaphore.acquire().await;
+            let start = std::time::Instant::now();
+            let result = execute_tool_call(registry, tc, agent_context, config.tool_timeout_secs).await;
+            let duration_ms = start.elapsed().as_millis() as u64;
+
+            let (output, success, content) = match result {
+                Ok(tr) => (tr.output.clone(), tr.success, tool_message_content(&tr)),
+                Err(e) => {
+                    let output = format!("Tool execution error: {}", e);
+                    (output.clone(), false, output)
+                }
+            };
+
+            let invocation = ToolInvocation {
+                tool_name: tc.name.clone(),
+                arguments: serde_json::from_str(&tc.arguments)
+                    .unwrap_or(serde_json::json!({})),
+                result: output,
+                success,
+                duration_ms,
+            };
+            (invocation, content)
+        }
+    });
+
+    futures::future::join_all(calls).await
+}
+
 /// Execute a single tool call against the registry.
 async fn execute_tool_call(
     registry: &ToolRegistry,
@@ -317,9 +469,12 @@ async fn execute_tool_call(
     let parameters: serde_json::Value =
         serde_json::from_str(&tool_call.arguments).unwrap_or(serde_json::json!({}));
 
+    // Tools that support previews take `dry_run` as an ordinary argument
+    let dry_run = parameters["dry_run"].as_bool().unwrap_or(false);
     let input = super::tools::ToolInput {
         tool_id: tool_call.name.clone(),
         parameters,
+        dry_run,
     };
 
     let future = tool.execute(input, agent_context.clone());
@@ -336,6 +491,7 @@ async fn execute_tool_call(
             output: format!("Tool '{}' timed out after {}s", tool_call.name, timeout_secs),
             data: serde_json::json!({}),
             error: Some("timeout".to_string()),
+            structured: None,
         }),
     }
 }
@@ -351,3 +507,111 @@ pub fn tool_descriptions_to_schemas(descriptions: &[super::tools::ToolDescriptio
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::tools::{ToolInput, ToolResult};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Sleeps for `ms` milliseconds, then echoes its id. `running` counts the
+    /// SleepTools executing right now and `peak` the most seen at once.
+    struct SleepTool {
+        id: String,
+        ms: u64,
+        running: Arc<AtomicUsize>,
+        peak: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl AgentTool for SleepTool {
+        fn id(&self) -> &str { &self.id }
+        fn name(&self) -> &str { &self.id }
+        fn description(&self) -> &str { "sleeps" }
+        fn parameters_schema(&self) -> serde_json::Value { serde_json::json!({ "type": "object" }) }
+
+        async fn execute(&self, _input: ToolInput, _context: AgentContext) -> Result<ToolResult> {
+            let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(self.ms)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(ToolResult {
+                success: true,
+                output: self.id.clone(),
+                data: serde_json::json!({}),
+                error: None,
+                structured: None,
+            })
+        }
+    }
+
+    fn call(id: &str, name: &str) -> ToolCall {
+        ToolCall { id: id.to_string(), name: name.to_string(), arguments: "{}".to_string() }
+    }
+
+    #[test]
+    fn test_budget_exceeded_checks_tokens_then_deadline() {
+        let config = ToolLoopConfig {
+            max_total_tokens: Some(100),
+            deadline: Some(Instant::now() + std::time::Duration::from_secs(60)),
+            ..Default::default()
+        };
+        assert_eq!(config.budget_exceeded(99), None);
+        assert_eq!(config.budget_exceeded(120), Some(BudgetExceeded::Tokens { limit: 100, used: 120 }));
+
+        let expired = ToolLoopConfig { deadline: Some(Instant::now()), ..Default::default() };
+        assert_eq!(expired.budget_exceeded(0), Some(BudgetExceeded::WallClock));
+        assert_eq!(ToolLoopConfig::default().budget_exceeded(usize::MAX), None);
+
+        let messages = vec![
+            ChatMessage::user("abcdefgh"),
+            ChatMessage::assistant_tool_calls(vec![call("1", "abcd")]),
+        ];
+        // "abcdefgh" = 2, "abcd" = 1, "{}" = 1
+        assert_eq!(estimate_messages_tokens(&messages), 4);
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls_run_concurrently_in_call_order() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let registry = ToolRegistry::new();
+        for (id, ms) in [("slow", 300), ("fast", 50)] {
+            registry.register(Arc::new(SleepTool {
+                id: id.to_string(),
+                ms,
+                running: running.clone(),
+                peak: peak.clone(),
+            }));
+        }
+        let calls = vec![call("1", "slow"), call("2", "fast"), call("3", "missing")];
+
+        let executed = execute_tool_calls(&registry, &calls, &AgentContext::new(), &ToolLoopConfig::default()).await;
+
+        // "fast" starts while "slow" is still sleeping
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        let outputs: Vec<_> = executed.iter().map(|(inv, _)| inv.result.as_str()).collect();
+        assert_eq!(outputs, vec!["slow", "fast", "Tool execution error: Unknown tool: missing"]);
+        assert!(!executed[2].0.success);
+    }
+
+    fn result(structured: Option<serde_json::Value>) -> ToolResult {
+        ToolResult {
+            success: true,
+            output: "Found 1 result".to_string(),
+            data: serde_json::json!({}),
+            error: None,
+            structured,
+        }
+    }
+
+    #[test]
+    fn test_tool_message_content_appends_structured_json() {
+        assert_eq!(tool_message_content(&result(None)), "Found 1 result");
+        assert_eq!(
+            tool_message_content(&result(Some(serde_json::json!({ "total": 1 })))),
+            "Found 1 result\n\nStructured result (JSON):\n{\"total\":1}"
+        );
+    }
+}
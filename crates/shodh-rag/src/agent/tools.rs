@@ -16,6 +16,11 @@ pub struct ToolInput {
 
     /// Parameters for the tool
     pub parameters: serde_json::Value,
+
+    /// Preview the effect without performing it. Tools that change state
+    /// return what they would do; read-only tools ignore this.
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 /// Result from tool execution
@@ -32,6 +37,10 @@ pub struct ToolResult {
 
     /// Error message if failed
     pub error: Option<String>,
+
+    /// Machine-readable payload sent to the LLM alongside `output`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured: Option<serde_json::Value>,
 }
 
 /// Trait for tools that agents can use
@@ -62,8 +71,11 @@ fn new_shared_rag_engine() -> SharedRAGEngine {
 }
 
 /// Registry of available tools
+///
+/// Tools can be registered through a shared reference, so bridged tools
+/// (e.g. MCP) can be added to a registry that is already behind an `Arc`.
 pub struct ToolRegistry {
-    tools: HashMap<String, Arc<dyn AgentTool>>,
+    tools: parking_lot::RwLock<HashMap<String, Arc<dyn AgentTool>>>,
     /// Shared RAG engine reference — set at runtime, used by RAGSearchTool.
     rag_engine_ref: SharedRAGEngine,
     /// Shared calendar store — used by calendar tools and Tauri commands.
@@ -80,7 +92,7 @@ impl ToolRegistry {
         let calendar_store = super::calendar_tools::new_calendar_store();
 
         let mut registry = Self {
-            tools: HashMap::new(),
+            tools: parking_lot::RwLock::new(HashMap::new()),
             rag_engine_ref: rag_engine_ref.clone(),
             calendar_store: calendar_store.clone(),
         };
@@ -125,23 +137,37 @@ impl ToolRegistry {
     }
 
     /// Register a tool
-    pub fn register(&mut self, tool: Arc<dyn AgentTool>) {
-        self.tools.insert(tool.id().to_string(), tool);
+    pub fn register(&self, tool: Arc<dyn AgentTool>) {
+        self.tools.write().insert(tool.id().to_string(), tool);
+    }
+
+    /// Remove a tool by ID. Returns whether it was registered.
+    pub fn unregister(&self, tool_id: &str) -> bool {
+        self.tools.write().remove(tool_id).is_some()
+    }
+
+    /// Remove every tool whose ID starts with `prefix`. Returns how many were removed.
+    pub fn unregister_prefix(&self, prefix: &str) -> usize {
+        let mut tools = self.tools.write();
+        let before = tools.len();
+        tools.retain(|id, _| !id.starts_with(prefix));
+        before - tools.len()
     }
 
     /// Get a tool by ID
     pub fn get(&self, tool_id: &str) -> Option<Arc<dyn AgentTool>> {
-        self.tools.get(tool_id).cloned()
+        self.tools.read().get(tool_id).cloned()
     }
 
     /// List all available tools
     pub fn list(&self) -> Vec<String> {
-        self.tools.keys().cloned().collect()
+        self.tools.read().keys().cloned().collect()
     }
 
     /// Get tool descriptions for prompting
     pub fn get_tool_descriptions(&self) -> Vec<ToolDescription> {
         self.tools
+            .read()
             .values()
             .map(|tool| ToolDescription {
                 id: tool.id().to_string(),
@@ -231,6 +257,7 @@ impl AgentTool for RAGSearchTool {
                     output: "RAG engine not available — documents not indexed yet.".to_string(),
                     data: serde_json::json!({ "query": query, "results": [], "total": 0 }),
                     error: Some("RAG engine not initialized".to_string()),
+                    structured: None,
                 });
             }
         };
@@ -284,6 +311,18 @@ impl AgentTool for RAGSearchTool {
                     } else {
                         format!("No documents found for query: '{}'", query)
                     },
+                    // Passage text is already in `output`; the payload indexes it
+                    structured: Some(serde_json::json!({
+                        "query": query,
+                        "total": result_count,
+                        "results": results.iter().enumerate().map(|(i, r)| serde_json::json!({
+                            "index": i + 1,
+                            "score": r.score,
+                            "title": r.title,
+                            "source": r.source,
+                            "heading": r.heading,
+                        })).collect::<Vec<_>>(),
+                    })),
                     data: serde_json::json!({
                         "query": query,
                         "results": results_json,
@@ -299,6 +338,7 @@ impl AgentTool for RAGSearchTool {
                     output: format!("Search failed for '{}': {}", query, e),
                     data: serde_json::json!({ "query": query, "results": [], "total": 0 }),
                     error: Some(e.to_string()),
+                    structured: None,
                 })
             }
         }
@@ -373,6 +413,7 @@ impl AgentTool for CodeAnalysisTool {
             output: format!("Analyzed {} using {} analysis", file_path, analysis_type),
             data: analysis_result,
             error: None,
+            structured: None,
         })
     }
 }
@@ -446,6 +487,7 @@ impl AgentTool for DocumentGenerationTool {
                 output: format!("Unsupported format: {}", format),
                 data: serde_json::json!({ "error": "Invalid format" }),
                 error: Some(format!("Format must be one of: {}", valid_formats.join(", "))),
+                structured: None,
             });
         }
 
@@ -458,6 +500,7 @@ impl AgentTool for DocumentGenerationTool {
                 "size_bytes": content.len()
             }),
             error: None,
+            structured: None,
         })
     }
 }
@@ -488,6 +531,7 @@ mod tests {
                 "query": "test query",
                 "top_k": 5
             }),
+            dry_run: false,
         };
 
         let context = AgentContext::new();
@@ -0,0 +1,578 @@
+//! Incremental artifact detection for streamed LLM output
+//!
+//! `extract_artifacts` needs the whole response. While tokens are still
+//! arriving, `ArtifactStreamParser` spots `<artifact>` tags and artifact
+//! fences (mermaid, table, chart, and code blocks over five lines), keeps
+//! their raw text out of the visible content, and reports the artifact as
+//! start/delta/complete events so the UI can render it while it builds.
+//!
+//! Whether a fence is an artifact depends on the whole response (fences are
+//! only a fallback when no `<artifact>` tag is present), so `finish` checks
+//! the streamed result against `extract_artifacts` and sends a reset with
+//! the batch result when they differ.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use chrono::Utc;
+
+use super::{
+    artifact_type_from_tag, chart_artifact, extract_artifacts, is_mermaid_language, mermaid_title,
+    strip_code_fence, table_title, validate_artifact, Artifact, ArtifactType, EventEmitter,
+};
+
+static ATTR_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r#"([\w-]+)="([^"]*)""#).expect("artifact attribute regex is valid")
+});
+
+const TAG_OPEN: &str = "<artifact";
+const TAG_CLOSE: &str = "</artifact>";
+const FENCE: &str = "```";
+const FENCE_CLOSE: &str = "\n```";
+
+/// Code fences with more lines than this become artifacts, as in `extract_artifacts`
+const CODE_ARTIFACT_MIN_LINES: usize = 5;
+
+#[derive(Debug, Clone)]
+pub enum ArtifactStreamEvent {
+    /// Text to show in the message body
+    Text(String),
+    ArtifactStart {
+        id: String,
+        artifact_type: ArtifactType,
+        title: String,
+        language: Option<String>,
+    },
+    ArtifactDelta { id: String, delta: String },
+    ArtifactComplete(Artifact),
+    /// The streamed text and artifacts were wrong; these replace them
+    Reset { text: String, artifacts: Vec<Artifact> },
+}
+
+struct OpenArtifact {
+    id: String,
+    artifact_type: ArtifactType,
+    title: String,
+    language: Option<String>,
+    content: String,
+    /// Opened by a fence rather than an `<artifact>` tag, so the title is a
+    /// placeholder until the body is known
+    fenced: bool,
+}
+
+enum Mode {
+    Text,
+    /// Inside `<artifact ...>`, waiting for `</artifact>`
+    Tag(OpenArtifact),
+    /// Inside an artifact fence, waiting for the closing fence
+    Fence(OpenArtifact),
+    /// Inside a plain code fence that may stay inline; `raw` holds
+    /// everything since the opening fence
+    Undecided { language: Option<String>, raw: String, body: String },
+}
+
+pub struct ArtifactStreamParser {
+    pending: String,
+    mode: Mode,
+    /// Fence artifacts seen so far, for `extract_artifacts`-compatible ids
+    fence_idx: usize,
+    /// An `<artifact>` tag has opened, so later fences stay plain text
+    tagged: bool,
+    /// Everything pushed so far
+    raw: String,
+    /// Visible text and completed artifacts reported so far
+    shown: String,
+    completed: Vec<Artifact>,
+}
+
+impl Default for ArtifactStreamParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArtifactStreamParser {
+    pub fn new() -> Self {
+        Self {
+            pending: String::new(),
+            mode: Mode::Text,
+            fence_idx: 0,
+            tagged: false,
+            raw: String::new(),
+            shown: String::new(),
+            completed: Vec::new(),
+        }
+    }
+
+    /// Feed the next token
+    pub fn push(&mut self, token: &str) -> Vec<ArtifactStreamEvent> {
+        self.raw.push_str(token);
+        self.pending.push_str(token);
+        let mut events = Vec::new();
+        while self.step(&mut events) {}
+        self.record(&events);
+        events
+    }
+
+    /// End of stream: flush held-back text, then reset to the
+    /// `extract_artifacts` result if the stream reported something else.
+    /// An artifact still open here has no closing tag or fence, so it is
+    /// left for the reset to turn back into text.
+    pub fn finish(mut self) -> Vec<ArtifactStreamEvent> {
+        let mut events = Vec::new();
+        while self.step(&mut events) {}
+        let rest = std::mem::take(&mut self.pending);
+        match std::mem::replace(&mut self.mode, Mode::Text) {
+            Mode::Text => push_text(&mut events, rest),
+            Mode::Tag(mut open) | Mode::Fence(mut open) => append_delta(&mut events, &mut open, rest),
+            Mode::Undecided { raw, .. } => push_text(&mut events, raw + &rest),
+        }
+        self.record(&events);
+
+        let (artifacts, text) = extract_artifacts(&self.raw);
+        if !self.matches(&text, &artifacts) {
+            events.push(ArtifactStreamEvent::Reset { text, artifacts });
+        }
+        events
+    }
+
+    fn record(&mut self, events: &[ArtifactStreamEvent]) {
+        for event in events {
+            match event {
+                ArtifactStreamEvent::Text(text) => self.shown.push_str(text),
+                ArtifactStreamEvent::ArtifactComplete(artifact) => self.completed.push(artifact.clone()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether the streamed result is the batch one
+    fn matches(&self, text: &str, artifacts: &[Artifact]) -> bool {
+        self.shown.trim() == text
+            && self.completed.len() == artifacts.len()
+            && self.completed.iter().zip(artifacts).all(|(a, b)| {
+                a.id == b.id
+                    && a.artifact_type == b.artifact_type
+                    && a.title == b.title
+                    && a.content == b.content
+                    && a.language == b.language
+            })
+    }
+
+    /// Consume as much of `pending` as can be classified. Returns true if
+    /// the mode changed and another step may make progress.
+    fn step(&mut self, events: &mut Vec<ArtifactStreamEvent>) -> bool {
+        match std::mem::replace(&mut self.mode, Mode::Text) {
+            Mode::Text => self.step_text(events),
+            Mode::Tag(mut open) => match self.pending.find(TAG_CLOSE) {
+                Some(end) => {
+                    let chunk: String = self.pending.drain(..end + TAG_CLOSE.len()).collect();
+                    append_delta(events, &mut open, chunk[..end].to_string());
+                    events.push(ArtifactStreamEvent::ArtifactComplete(complete(open)));
+                    true
+                }
+                None => {
+                    let chunk = self.take_safe(TAG_CLOSE);
+                    append_delta(events, &mut open, chunk);
+                    self.mode = Mode::Tag(open);
+                    false
+                }
+            },
+            Mode::Fence(mut open) => match self.pending.find(FENCE_CLOSE) {
+                Some(end) => {
+                    let chunk: String = self.pending.drain(..end + FENCE_CLOSE.len()).collect();
+                    append_delta(events, &mut open, chunk[..end].to_string());
+                    events.push(ArtifactStreamEvent::ArtifactComplete(complete(open)));
+                    true
+                }
+                None => {
+                    let chunk = self.take_safe(FENCE_CLOSE);
+                    append_delta(events, &mut open, chunk);
+                    self.mode = Mode::Fence(open);
+                    false
+                }
+            },
+            Mode::Undecided { language, mut raw, mut body } => {
+                if let Some(end) = self.pending.find(FENCE_CLOSE) {
+                    let chunk: String = self.pending.drain(..end + FENCE_CLOSE.len()).collect();
+                    body.push_str(&chunk[..end]);
+                    raw.push_str(&chunk);
+                    if body.lines().count() > CODE_ARTIFACT_MIN_LINES {
+                        let mut open = self.open_code(language, events);
+                        append_delta(events, &mut open, body);
+                        events.push(ArtifactStreamEvent::ArtifactComplete(complete(open)));
+                    } else {
+                        push_text(events, raw);
+                    }
+                    return true;
+                }
+
+                let chunk = self.take_safe(FENCE_CLOSE);
+                body.push_str(&chunk);
+                raw.push_str(&chunk);
+                // Enough complete lines to know it will be an artifact
+                if body.matches('\n').count() > CODE_ARTIFACT_MIN_LINES {
+                    let mut open = self.open_code(language, events);
+                    append_delta(events, &mut open, body);
+                    self.mode = Mode::Fence(open);
+                    return true;
+                }
+                self.mode = Mode::Undecided { language, raw, body };
+                false
+            }
+        }
+    }
+
+    fn step_text(&mut self, events: &mut Vec<ArtifactStreamEvent>) -> bool {
+        let tag = self.pending.find(TAG_OPEN);
+        let fence = if self.tagged { None } else { self.pending.find(FENCE) };
+        let (start, is_tag) = match (tag, fence) {
+            (Some(t), Some(f)) if t < f => (t, true),
+            (Some(t), None) => (t, true),
+            (_, Some(f)) => (f, false),
+            (None, None) => {
+                let markers: &[&str] = if self.tagged { &[TAG_OPEN] } else { &[TAG_OPEN, FENCE] };
+                let text = self.take_safe_any(markers);
+                push_text(events, text);
+                return false;
+            }
+        };
+
+        let header_end = if is_tag {
+            self.pending[start..].find('>').map(|i| start + i + 1)
+        } else {
+            self.pending[start..].find('\n').map(|i| start + i + 1)
+        };
+        let text: String = self.pending.drain(..start).collect();
+        push_text(events, text);
+        let Some(header_end) = header_end.map(|end| end - start) else {
+            // Wait for the rest of the header
+            return false;
+        };
+        let header: String = self.pending.drain(..header_end).collect();
+
+        if is_tag {
+            self.open_tag(&header, events);
+        } else {
+            self.open_fence(header, events);
+        }
+        true
+    }
+
+    fn open_tag(&mut self, header: &str, events: &mut Vec<ArtifactStreamEvent>) {
+        let attr = |names: &[&str]| {
+            ATTR_RE
+                .captures_iter(header)
+                .find(|cap| names.contains(&&cap[1]))
+                .map(|cap| cap[2].to_string())
+        };
+        let Some(artifact_type) = attr(&["type"]).as_deref().and_then(artifact_type_from_tag) else {
+            // Not an artifact we render; show it as text
+            push_text(events, header.to_string());
+            return;
+        };
+        let open = OpenArtifact {
+            id: attr(&["id", "identifier"]).unwrap_or_default(),
+            artifact_type,
+            title: attr(&["title"]).unwrap_or_default(),
+            language: attr(&["language"]),
+            content: String::new(),
+            fenced: false,
+        };
+        self.tagged = true;
+        events.push(start_event(&open));
+        self.mode = Mode::Tag(open);
+    }
+
+    fn open_fence(&mut self, header: String, events: &mut Vec<ArtifactStreamEvent>) {
+        let language = header[FENCE.len()..].trim().to_string();
+        let idx = self.fence_idx;
+        let open = match language.as_str() {
+            lang if is_mermaid_language(lang) => OpenArtifact {
+                id: format!("mermaid-{}", idx),
+                artifact_type: ArtifactType::Mermaid,
+                title: mermaid_title(lang).to_string(),
+                language: None,
+                content: String::new(),
+                fenced: true,
+            },
+            "table" => OpenArtifact {
+                id: format!("table-{}", idx),
+                artifact_type: ArtifactType::Table,
+                title: format!("Table {}", idx + 1),
+                language: Some("table".to_string()),
+                content: String::new(),
+                fenced: true,
+            },
+            "chart" => OpenArtifact {
+                id: format!("chart-{}", idx),
+                artifact_type: ArtifactType::Chart,
+                title: format!("Chart {}", idx + 1),
+                language: Some("chart".to_string()),
+                content: String::new(),
+                fenced: true,
+            },
+            _ => {
+                let language = (!language.is_empty()).then_some(language);
+                self.mode = Mode::Undecided { language, raw: header, body: String::new() };
+                return;
+            }
+        };
+        self.fence_idx += 1;
+        events.push(start_event(&open));
+        self.mode = Mode::Fence(open);
+    }
+
+    fn open_code(&mut self, language: Option<String>, events: &mut Vec<ArtifactStreamEvent>) -> OpenArtifact {
+        let idx = self.fence_idx;
+        self.fence_idx += 1;
+        let open = OpenArtifact {
+            id: format!("code-{}", idx),
+            artifact_type: ArtifactType::Code,
+            title: format!("Code snippet {}", idx + 1),
+            language,
+            content: String::new(),
+            fenced: true,
+        };
+        events.push(start_event(&open));
+        open
+    }
+
+    /// Drain `pending` except a tail that could be the start of `marker`
+    fn take_safe(&mut self, marker: &str) -> String {
+        self.take_safe_any(&[marker])
+    }
+
+    fn take_safe_any(&mut self, markers: &[&str]) -> String {
+        let hold = markers
+            .iter()
+            .filter_map(|m| (1..m.len()).rev().find(|&n| self.pending.ends_with(&m[..n])))
+            .max()
+            .unwrap_or(0);
+        // Markers are ASCII, so the held tail starts on a char boundary
+        let keep_from = self.pending.len() - hold;
+        self.pending.drain(..keep_from).collect()
+    }
+}
+
+fn push_text(events: &mut Vec<ArtifactStreamEvent>, text: String) {
+    if text.is_empty() {
+        return;
+    }
+    if let Some(ArtifactStreamEvent::Text(last)) = events.last_mut() {
+        last.push_str(&text);
+    } else {
+        events.push(ArtifactStreamEvent::Text(text));
+    }
+}
+
+fn append_delta(events: &mut Vec<ArtifactStreamEvent>, open: &mut OpenArtifact, delta: String) {
+    if delta.is_empty() {
+        return;
+    }
+    open.content.push_str(&delta);
+    events.push(ArtifactStreamEvent::ArtifactDelta { id: open.id.clone(), delta });
+}
+
+fn start_event(open: &OpenArtifact) -> ArtifactStreamEvent {
+    ArtifactStreamEvent::ArtifactStart {
+        id: open.id.clone(),
+        artifact_type: open.artifact_type.clone(),
+        title: open.title.clone(),
+        language: open.language.clone(),
+    }
+}
+
+fn complete(open: OpenArtifact) -> Artifact {
+    let content = strip_code_fence(&open.content);
+    // Same titles as `extract_artifacts`, which sees the whole body
+    let idx = open.id.rsplit('-').next().and_then(|n| n.parse().ok()).unwrap_or(0);
+    let title = match open.artifact_type {
+        ArtifactType::Chart => {
+            return chart_artifact(open.id, (!open.fenced).then_some(open.title), &content, idx);
+        }
+        ArtifactType::Table if open.fenced => table_title(&content, idx),
+        _ => open.title,
+    };
+    let mut artifact = Artifact {
+        id: open.id,
+        artifact_type: open.artifact_type,
+        title,
+        content,
+        language: open.language,
+        editable: true,
+        version: 1,
+        created_at: Utc::now(),
+        valid: true,
+        metadata: HashMap::new(),
+    };
+    validate_artifact(&mut artifact);
+    artifact
+}
+
+/// Send parser events to the frontend. Visible text is appended to
+/// `visible` and sent as `chat_token`; artifacts go out as
+/// `artifact_start`, `artifact_delta` and `artifact_complete`, and a
+/// reset replaces `visible` and is sent as `artifact_reset`.
+pub fn emit_stream_events(
+    emitter: &dyn EventEmitter,
+    events: Vec<ArtifactStreamEvent>,
+    visible: &mut String,
+) {
+    for event in events {
+        match event {
+            ArtifactStreamEvent::Text(text) => {
+                visible.push_str(&text);
+                emitter.emit(
+                    "chat_token",
+                    serde_json::json!({ "token": text, "accumulated": &*visible }),
+                );
+            }
+            ArtifactStreamEvent::ArtifactStart { id, artifact_type, title, language } => {
+                emitter.emit(
+                    "artifact_start",
+                    serde_json::json!({
+                        "id": id,
+                        "artifact_type": artifact_type,
+                        "title": title,
+                        "language": language,
+                    }),
+                );
+            }
+            ArtifactStreamEvent::ArtifactDelta { id, delta } => {
+                emitter.emit("artifact_delta", serde_json::json!({ "id": id, "delta": delta }));
+            }
+            ArtifactStreamEvent::ArtifactComplete(artifact) => {
+                emitter.emit(
+                    "artifact_complete",
+                    serde_json::to_value(&artifact).unwrap_or_default(),
+                );
+            }
+            ArtifactStreamEvent::Reset { text, artifacts } => {
+                *visible = text;
+                emitter.emit(
+                    "artifact_reset",
+                    serde_json::json!({ "content": &*visible, "artifacts": artifacts }),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed `response` in `chunk`-byte tokens and collect the visible text
+    /// plus the completed artifacts
+    fn run(response: &str, chunk: usize) -> (String, Vec<Artifact>, Vec<ArtifactStreamEvent>) {
+        let mut parser = ArtifactStreamParser::new();
+        let mut events = Vec::new();
+        let chars: Vec<char> = response.chars().collect();
+        for token in chars.chunks(chunk) {
+            events.extend(parser.push(&token.iter().collect::<String>()));
+        }
+        events.extend(parser.finish());
+
+        let mut visible = String::new();
+        let mut artifacts = Vec::new();
+        for event in &events {
+            match event {
+                ArtifactStreamEvent::Text(t) => visible.push_str(t),
+                ArtifactStreamEvent::ArtifactComplete(a) => artifacts.push(a.clone()),
+                ArtifactStreamEvent::Reset { text, artifacts: reset } => {
+                    visible = text.clone();
+                    artifacts = reset.clone();
+                }
+                _ => {}
+            }
+        }
+        (visible, artifacts, events)
+    }
+
+    #[test]
+    fn test_tag_artifact_is_hidden_and_streamed() {
+        let response = "Here you go:\n<artifact id=\"fib\" type=\"code\" language=\"python\" title=\"Fibonacci\">\n```python\ndef fib(n):\n    return n\n```\n</artifact>\nDone.";
+        for chunk in [1, 3, 7, 500] {
+            let (visible, artifacts, events) = run(response, chunk);
+            assert_eq!(visible, "Here you go:\n\nDone.", "chunk {}", chunk);
+            assert_eq!(artifacts.len(), 1);
+            assert_eq!(artifacts[0].id, "fib");
+            assert_eq!(artifacts[0].title, "Fibonacci");
+            assert_eq!(artifacts[0].language.as_deref(), Some("python"));
+            assert_eq!(artifacts[0].content, "def fib(n):\n    return n");
+
+            let streamed: String = events
+                .iter()
+                .filter_map(|e| match e {
+                    ArtifactStreamEvent::ArtifactDelta { delta, .. } => Some(delta.as_str()),
+                    _ => None,
+                })
+                .collect();
+            assert!(streamed.contains("def fib(n):"));
+            assert!(matches!(events.iter().find(|e| !matches!(e, ArtifactStreamEvent::Text(_))),
+                Some(ArtifactStreamEvent::ArtifactStart { .. })));
+            assert!(!events.iter().any(|e| matches!(e, ArtifactStreamEvent::Reset { .. })));
+        }
+    }
+
+    #[test]
+    fn test_fenced_artifacts_match_batch_extraction() {
+        let response = "Flow:\n```mermaid\ngraph TD\n  A --> B\n```\nand data:\n```chart\n{\"type\": \"bar\", \"title\": \"Sales\"}\n```\nend";
+        let (visible, artifacts, _) = run(response, 4);
+        assert_eq!(visible, "Flow:\n\nand data:\n\nend");
+
+        let (batch, _) = super::super::extract_artifacts(response);
+        let ids: Vec<_> = artifacts.iter().map(|a| (&a.id, &a.title, &a.content)).collect();
+        let batch_ids: Vec<_> = batch.iter().map(|a| (&a.id, &a.title, &a.content)).collect();
+        assert_eq!(ids, batch_ids);
+    }
+
+    #[test]
+    fn test_short_code_fence_stays_inline() {
+        let short = "Run:\n```bash\nls -la\n```\nthen check.";
+        let (visible, artifacts, _) = run(short, 2);
+        assert_eq!(visible, short);
+        assert!(artifacts.is_empty());
+
+        let long = "Code:\n```rust\nfn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\nfn e() {}\nfn f() {}\nfn g() {}\n```\n";
+        let (visible, artifacts, _) = run(long, 5);
+        assert_eq!(visible, "Code:\n\n");
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].id, "code-0");
+        assert_eq!(artifacts[0].language.as_deref(), Some("rust"));
+        assert_eq!(artifacts[0].content.lines().count(), 7);
+    }
+
+    #[test]
+    fn test_unterminated_fence_is_text_on_finish() {
+        let response = "Diagram:\n```mermaid\ngraph LR\n  X --> Y";
+        let (visible, artifacts, _) = run(response, 3);
+        assert_eq!(visible, response);
+        assert!(artifacts.is_empty());
+    }
+
+    #[test]
+    fn test_streamed_result_matches_batch_extraction() {
+        let long_code = "```rust\nfn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\nfn e() {}\nfn f() {}\n```";
+        let responses = [
+            "No artifacts here, just `inline` code.".to_string(),
+            format!("Before:\n{}\nafter", long_code),
+            // Fences are only a fallback: with a tag present they stay text
+            format!("<artifact id=\"a\" type=\"markdown\" title=\"Notes\">\n# Hi\n</artifact>\n{}", long_code),
+            format!("{}\nthen\n<artifact id=\"a\" type=\"markdown\" title=\"Notes\">\n# Hi\n</artifact>", long_code),
+            "Open tag <artifact id=\"x\" type=\"code\" title=\"X\">\nnever closed".to_string(),
+            "Start:\n```chart\n{\"type\": \"bar\"}".to_string(),
+        ];
+        for response in &responses {
+            let (batch, cleaned) = super::super::extract_artifacts(response);
+            let batch: Vec<_> = batch.iter().map(|a| (a.id.clone(), a.title.clone(), a.content.clone())).collect();
+            for chunk in [1, 3, 7, 500] {
+                let (visible, artifacts, _) = run(response, chunk);
+                let streamed: Vec<_> = artifacts.iter().map(|a| (a.id.clone(), a.title.clone(), a.content.clone())).collect();
+                assert_eq!(visible.trim(), cleaned, "{:?} in chunks of {}", response, chunk);
+                assert_eq!(streamed, batch, "{:?} in chunks of {}", response, chunk);
+            }
+        }
+    }
+}
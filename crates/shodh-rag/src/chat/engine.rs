@@ -9,7 +9,7 @@ use crate::agent::{
     AgentContext, AgentDefinition, AgentSystem, ConversationTurn, PersonalAssistant,
     ToolDescription, ToolInput, ToolRegistry, ToolResult, UserInfo,
 };
-use crate::llm::LLMManager;
+use crate::llm::{join_system_prompt, LLMManager, LLMTimeoutError, WebSource};
 use crate::memory::{
     CodeContext, ContextId, ConversationContext as MemConversationContext, DocumentContext,
     EnvironmentContext, Experience, ExperienceType, Memory, MemorySystem, ProjectContext, Query,
@@ -22,13 +22,53 @@ use crate::rag::{
 use crate::rag_engine::RAGEngine;
 
 use super::{
-    build_corpus_stats, estimate_tokens, extract_artifacts, force_bullet_format,
-    validate_citations, AssistantResponse, ChatContext, Citation,
-    ConversationMessage, EventEmitter, Intent, ResponseMetadata, SearchResult, UserMessage,
-    CODE_GENERATION_PROMPT, GENERAL_CHAT_PROMPT, RAG_SYSTEM_PROMPT,
+    build_corpus_stats, estimate_tokens, extract_artifacts, fence_markdown_tables, force_bullet_format,
+    enforce_grounding, merge_citations, renumber_web_citations, validate_citations, AssistantResponse,
+    ChatContext, Citation, CitationOrigin, ContextPart, ContextUsage, ConversationMessage, EventEmitter,
+    Intent, MessagePlatform, ResponseMetadata, SearchResult, UserMessage, CODE_GENERATION_PROMPT, GENERAL_CHAT_PROMPT,
+    RAG_SYSTEM_PROMPT,
 };
+use super::artifact_stream::{emit_stream_events, ArtifactStreamParser};
 use crate::rag::structured_output::STRUCTURED_OUTPUT_INSTRUCTIONS;
 
+/// Tokens held back for the instructions in search prompts
+const SYSTEM_PROMPT_BUDGET: usize = 2000;
+/// Assumed when the LLM does not report its context window
+const DEFAULT_CONTEXT_WINDOW: usize = 128_000;
+/// Best search score below which an answer is flagged as low confidence
+const LOW_CONFIDENCE_SCORE: f32 = 0.2;
+
+/// How a search prompt's context window is split: fixed reserves for the
+/// instructions, answer and question, then 60/25/15 of the rest for
+/// retrieved context, history and memory
+struct ContextBudget {
+    response: usize,
+    query: usize,
+    available: usize,
+    context: usize,
+    history: usize,
+    memory: usize,
+}
+
+impl ContextBudget {
+    fn new(context_window: usize, query: &str, is_broad_query: bool) -> Self {
+        let response = if is_broad_query { 8192 } else { 4096 };
+        let query = estimate_tokens(query) + 100;
+        let available = context_window
+            .saturating_sub(SYSTEM_PROMPT_BUDGET)
+            .saturating_sub(response)
+            .saturating_sub(query);
+        Self {
+            response,
+            query,
+            available,
+            context: (available * 60) / 100,
+            history: (available * 25) / 100,
+            memory: (available * 15) / 100,
+        }
+    }
+}
+
 pub struct ChatEngine {
     rag: Arc<AsyncRwLock<RAGEngine>>,
     agent_system: Arc<AsyncRwLock<Option<Arc<AsyncRwLock<AgentSystem>>>>>,
@@ -102,6 +142,7 @@ impl ChatEngine {
         let tool_input = ToolInput {
             tool_id: tool_name.to_string(),
             parameters,
+            dry_run: false,
         };
 
         tool.execute(tool_input, agent_context).await
@@ -113,6 +154,116 @@ impl ChatEngine {
         message: UserMessage,
         context: ChatContext,
         emitter: Option<&dyn EventEmitter>,
+    ) -> Result<AssistantResponse> {
+        // Tag requests with the conversation so local models can reuse the
+        // prompt prefix evaluated for earlier turns
+        match context.conversation_id.clone() {
+            Some(conversation_id) => {
+                LLMManager::with_conversation(conversation_id, self.process_message_scoped(message, context, emitter)).await
+            }
+            None => self.process_message_scoped(message, context, emitter).await,
+        }
+    }
+
+    async fn process_message_scoped(
+        &self,
+        message: UserMessage,
+        context: ChatContext,
+        emitter: Option<&dyn EventEmitter>,
+    ) -> Result<AssistantResponse> {
+        // Space overrides cover every LLM call made while handling the message
+        let overrides = context.space_llm_settings.clone()
+            .filter(|o| context.space_id.is_some() && !o.is_empty());
+        match overrides {
+            Some(overrides) => {
+                tracing::debug!(space_id = ?context.space_id, ?overrides, "Applying space LLM settings");
+                LLMManager::with_overrides(overrides, self.process_message_inner(message, context, emitter)).await
+            }
+            None => self.process_message_inner(message, context, emitter).await,
+        }
+    }
+
+    /// Token use of the prompt a search for `query` would build, without
+    /// calling the LLM. Retrieval uses rule-based query rewriting instead of
+    /// the LLM router and skips reranking, so chunk counts are close to, not
+    /// exactly, what the real answer sees.
+    pub async fn estimate_context_usage(&self, query: &str, context: &ChatContext) -> Result<ContextUsage> {
+        let overrides = context.space_llm_settings.clone()
+            .filter(|o| context.space_id.is_some() && !o.is_empty());
+        match overrides {
+            Some(overrides) => {
+                LLMManager::with_overrides(overrides, self.estimate_context_usage_inner(query, context)).await
+            }
+            None => self.estimate_context_usage_inner(query, context).await,
+        }
+    }
+
+    async fn estimate_context_usage_inner(&self, query: &str, context: &ChatContext) -> Result<ContextUsage> {
+        let is_broad_query = Self::is_broad_query(query);
+        let max_results = if is_broad_query {
+            context.max_results.unwrap_or(40)
+        } else {
+            context.max_results.unwrap_or(20)
+        };
+
+        let conversation_ctx = Self::build_conversation_context(context);
+        let rewriter = QueryRewriter::new();
+        let primary_query = rewriter.rewrite_rule_based(query, &conversation_ctx).rewritten_query;
+        let variants = rewriter.expand_query(&primary_query, &conversation_ctx);
+        let results = {
+            let rag = self.rag.read().await;
+            Self::search_variants(&rag, &primary_query, &variants, max_results, Self::rerank_options(context)).await?
+        };
+        let search_results = Self::curate_results(Self::to_search_results(&results), is_broad_query);
+        let context_text = Self::build_context_text(&search_results);
+
+        let message = UserMessage {
+            content: query.to_string(),
+            images: None,
+            platform: MessagePlatform::Desktop,
+            timestamp: Utc::now(),
+        };
+        let memory_text = Self::build_memory_text(&self.retrieve_relevant_memories(&message).await?);
+        let history_text = Self::build_history_text(context);
+
+        let (context_window, model) = match self.llm_manager.as_ref() {
+            Some(llm_arc) => match llm_arc.read().await.as_ref() {
+                Some(llm_manager) => (
+                    Self::get_context_window_from_llm(llm_manager),
+                    llm_manager.info().map(|info| info.model),
+                ),
+                None => (DEFAULT_CONTEXT_WINDOW, None),
+            },
+            None => (DEFAULT_CONTEXT_WINDOW, None),
+        };
+
+        let budget = ContextBudget::new(context_window, query, is_broad_query);
+        let system_prompt_tokens = estimate_tokens(RAG_SYSTEM_PROMPT)
+            + context.custom_system_prompt.as_deref().map(estimate_tokens).unwrap_or(0);
+        let retrieved = ContextPart::new(estimate_tokens(&context_text), budget.context);
+        let history = ContextPart::new(estimate_tokens(&history_text), budget.history);
+        let memory = ContextPart::new(estimate_tokens(&memory_text), budget.memory);
+
+        Ok(ContextUsage {
+            model,
+            context_window,
+            response_reserve: budget.response,
+            system_prompt_tokens,
+            query_tokens: budget.query,
+            total_tokens: system_prompt_tokens + budget.query + retrieved.sent() + history.sent() + memory.sent(),
+            will_truncate: retrieved.truncated || history.truncated || memory.truncated,
+            retrieved_chunks: search_results.len(),
+            retrieved,
+            history,
+            memory,
+        })
+    }
+
+    async fn process_message_inner(
+        &self,
+        message: UserMessage,
+        context: ChatContext,
+        emitter: Option<&dyn EventEmitter>,
     ) -> Result<AssistantResponse> {
         let start_time = std::time::Instant::now();
 
@@ -403,29 +554,7 @@ impl ChatEngine {
                 (primary, expanded, None)
             };
 
-        // Broad queries need more results to cover the entire corpus.
-        // Detect patterns: explicit "all/every/list/each" + plural extraction patterns
-        // like "emails from invoices", "names and phones", "documents about X"
-        let content_lower = message.content.to_lowercase();
-        let has_explicit_broad = content_lower.contains("all ")
-            || content_lower.contains("every ")
-            || content_lower.contains("list ")
-            || content_lower.contains("each ")
-            || content_lower.contains("everyone")
-            || content_lower.contains("everything");
-        // Plural nouns requesting extraction from a collection (e.g. "emails from invoices")
-        let has_plural_extraction = (content_lower.contains("from ")
-            || content_lower.contains("in the ")
-            || content_lower.contains("across "))
-            && (content_lower.contains("emails")
-                || content_lower.contains("names")
-                || content_lower.contains("phones")
-                || content_lower.contains("numbers")
-                || content_lower.contains("addresses")
-                || content_lower.contains("ids")
-                || content_lower.contains("details")
-                || content_lower.contains("records"));
-        let is_broad_query = has_explicit_broad || has_plural_extraction;
+        let is_broad_query = Self::is_broad_query(&message.content);
         let max_results = if is_broad_query {
             context.max_results.unwrap_or(40)
         } else {
@@ -440,43 +569,50 @@ impl ChatEngine {
         );
 
         // Search all variants and merge results
-        let mut results = if expanded_queries.len() > 1 {
-            let mut all_result_sets = Vec::new();
-            for variant in &expanded_queries {
-                match rag.search(variant, max_results).await {
-                    Ok(variant_results) => {
-                        tracing::debug!(
-                            variant = %variant,
-                            hits = variant_results.len(),
-                            "Variant search complete"
-                        );
-                        all_result_sets.push(variant_results);
-                    }
-                    Err(e) => {
-                        tracing::warn!(variant = %variant, error = %e, "Variant search failed");
+        let mut results =
+            Self::search_variants(&rag, &primary_query, &expanded_queries, max_results, Self::rerank_options(context)).await?;
+
+        // Rerankers only look at the top `rerank_top_k`; the tail keeps its order
+        let rerank_head = context.rerank_top_k.unwrap_or(results.len()).min(results.len());
+        let mut rerankers = Vec::new();
+
+        // Cross-encoder pass: cheap, and gives the merged variants comparable scores
+        if context.cross_encoder_rerank.unwrap_or(false) && rerank_head > 1 {
+            let candidates: Vec<(String, String)> = results[..rerank_head]
+                .iter()
+                .map(|r| (r.id.to_string(), r.text.clone()))
+                .collect();
+            if let Some(scores) = rag.cross_encoder_scores(&message.content, &candidates) {
+                for result in &mut results[..rerank_head] {
+                    if let Some(&score) = scores.get(&result.id.to_string()) {
+                        result.score = score;
                     }
                 }
+                results[..rerank_head].sort_by(|a, b| {
+                    b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal)
+                });
+                rerankers.push("cross_encoder".to_string());
             }
-            Self::merge_expanded_results(all_result_sets, max_results)
-        } else {
-            rag.search(&primary_query, max_results).await?
-        };
+        }
 
         // Drop RAG read lock before acquiring LLM lock for reranking
         drop(rag);
 
         // LLM-based reranking: judge relevance to the original user question
         let mut rerank_latency_ms = None;
-        if results.len() > 1 {
+        if context.llm_rerank.unwrap_or(true) && rerank_head > 1 {
             if let Some(llm_arc) = self.llm_manager.as_ref() {
                 let llm_guard = llm_arc.read().await;
                 if let Some(ref llm_manager) = *llm_guard {
                     let rerank_start = std::time::Instant::now();
+                    let tail = results.split_off(rerank_head);
                     results = crate::reranking::llm_rerank(
                         llm_manager,
                         &message.content,
                         results,
                     ).await;
+                    results.extend(tail);
+                    rerankers.push("llm".to_string());
                     let elapsed = rerank_start.elapsed().as_millis() as u64;
                     rerank_latency_ms = Some(elapsed);
                     tracing::info!(
@@ -512,45 +648,7 @@ impl ChatEngine {
             );
         }
 
-        // Convert to SearchResult
-        let search_results: Vec<SearchResult> = results
-            .iter()
-            .map(|r| {
-                let snippet_text: String = r.text.chars().take(200).collect();
-                let source_file = r
-                    .metadata
-                    .get("file_path")
-                    .or_else(|| r.metadata.get("source_file"))
-                    .or_else(|| r.metadata.get("original_path"))
-                    .cloned()
-                    .unwrap_or_else(|| {
-                        r.source
-                            .strip_prefix("Folder: ")
-                            .unwrap_or(&r.source)
-                            .to_string()
-                    });
-
-                SearchResult {
-                    text: r.text.clone(),
-                    score: r.score,
-                    source_file,
-                    page_number: r.citation.as_ref().and_then(|c| c.page_numbers.clone()),
-                    line_range: None,
-                    snippet: snippet_text.clone(),
-                    citation: r.citation.as_ref().map(|c| Citation {
-                        title: c.title.clone(),
-                        snippet: snippet_text.clone(),
-                        score: r.score,
-                        url: c.url.clone(),
-                        authors: c.authors.clone(),
-                        source: r.source.clone(),
-                        year: c.year.clone(),
-                        page_numbers: c.page_numbers.clone(),
-                    }),
-                    metadata: r.metadata.clone(),
-                }
-            })
-            .collect();
+        let search_results = Self::to_search_results(&results);
 
         let mut metadata = ResponseMetadata {
             model: None,
@@ -562,10 +660,18 @@ impl ChatEngine {
             router_latency_ms: router_token_usage.map(|t| t.latency_ms),
             search_queries_used: Some(expanded_queries.clone()),
             rerank_latency_ms,
+            rerankers: (!rerankers.is_empty()).then_some(rerankers),
+            uncited_sentences: None,
+            cache_read_tokens: None,
+            cache_write_tokens: None,
+            actual_tokens: false,
         };
 
-        // Grounding: refuse when no results found
-        if search_results.is_empty() {
+        let web_capable = context.allow_web_augmentation.unwrap_or(false)
+            && self.llm_supports_web_search().await;
+
+        // Grounding: refuse when no results found, unless the web may have the answer
+        if search_results.is_empty() && !web_capable {
             return Ok(AssistantResponse {
                 content: "I could not find relevant information about this in your indexed documents. \
                           Try rephrasing your question, or ensure the relevant documents have been indexed."
@@ -583,96 +689,14 @@ impl ChatEngine {
         }
 
         let best_score = search_results.iter().map(|r| r.score).fold(0.0f32, f32::max);
-        let low_confidence = best_score < 0.2;
-
-        // === Context Curation Pipeline ===
-        // Goal: send only chunks that add genuine information value.
-        // Three stages: relevance filter → content dedup → information gain cutoff.
-        //
-        // For broad queries ("list all emails from invoices"), skip aggressive
-        // curation — the user wants exhaustive coverage, not just the top hits.
-        // The hybrid search score gap (items in both vector+FTS score ~2x those
-        // in only one index) creates artificial cliffs that would cut valid results.
-
-        let pre_filter_count = search_results.len();
-
-        // Stage 1: Relevance filter — drop truly irrelevant chunks.
-        // Broad queries use a very low threshold (5% of best) since all matching
-        // documents from the target collection are relevant.
-        let score_threshold = if is_broad_query {
-            best_score * 0.05
-        } else {
-            best_score * 0.15
-        };
-        let mut search_results: Vec<SearchResult> = search_results
-            .into_iter()
-            .filter(|r| r.score >= score_threshold)
-            .collect();
-
-        // Stage 2: Content deduplication — chunks from overlapping document regions
-        // or multi-variant search often contain near-identical text. Keep only the
-        // highest-scored version when two chunks share >60% of their words.
-        search_results = Self::deduplicate_by_content(search_results);
-
-        // Stage 3: Score-gap cutoff — skip for broad queries where exhaustive
-        // coverage matters more than precision. For focused queries, detect
-        // genuine relevance cliffs to avoid wasting LLM context tokens.
-        if !is_broad_query {
-            search_results = Self::cut_at_score_cliff(search_results);
-        }
-
-        {
-            let curation_sources: std::collections::HashSet<&str> = search_results
-                .iter()
-                .map(|r| r.source_file.as_str())
-                .collect();
-            tracing::info!(
-                best_score = best_score,
-                score_threshold = score_threshold,
-                is_broad_query = is_broad_query,
-                pre_filter = pre_filter_count,
-                post_curation = search_results.len(),
-                unique_sources = curation_sources.len(),
-                sources = ?curation_sources,
-                "Context curation complete"
-            );
-        }
+        let low_confidence = best_score < LOW_CONFIDENCE_SCORE;
+        let web_augment = low_confidence && web_capable;
+        let mut web_sources: Vec<WebSource> = Vec::new();
 
+        let search_results = Self::curate_results(search_results, is_broad_query);
         let num_sources = search_results.len();
 
-        // Build context for LLM, annotating spreadsheet data for chart generation
-        let context_text: String = search_results
-            .iter()
-            .enumerate()
-            .map(|(i, r)| {
-                let citation_info = r
-                    .citation
-                    .as_ref()
-                    .map(|c| format!(" [Source: {}]", c.title))
-                    .unwrap_or_default();
-
-                // Hint for spreadsheet/table data so LLM knows it can generate charts
-                let data_hint = if r.text.contains("| --- |") || r.text.contains("|---|") {
-                    let has_numbers = r.text.lines().skip(2).any(|line| {
-                        line.split('|')
-                            .any(|cell| cell.trim().parse::<f64>().is_ok())
-                    });
-                    if has_numbers {
-                        " [DATA: This is tabular data with numeric columns — suitable for chart visualization]"
-                    } else {
-                        ""
-                    }
-                } else {
-                    ""
-                };
-
-                // Append extracted structured fields so the LLM sees them explicitly
-                let extracted_fields = Self::format_extracted_fields(&r.metadata);
-
-                format!("[{}]{}{}\n{}{}", i + 1, citation_info, data_hint, r.text, extracted_fields)
-            })
-            .collect::<Vec<_>>()
-            .join("\n\n");
+        let context_text = Self::build_context_text(&search_results);
 
         // Generate LLM response
         let mut content = if let Some(llm_guard_opt) = self.llm_manager.as_ref() {
@@ -686,28 +710,18 @@ impl ChatEngine {
 
                 // Context window management
                 let context_window = Self::get_context_window_from_llm(llm_manager);
-                let system_prompt_budget = 2000;
-                let response_budget = if is_broad_query { 8192 } else { 4096 };
-                let query_budget = estimate_tokens(&message.content) + 100;
-                let available = context_window
-                    .saturating_sub(system_prompt_budget)
-                    .saturating_sub(response_budget)
-                    .saturating_sub(query_budget);
-
-                let context_budget = (available * 60) / 100;
-                let history_budget = (available * 25) / 100;
-                let memory_budget = (available * 15) / 100;
+                let budget = ContextBudget::new(context_window, &message.content, is_broad_query);
 
                 let pre_truncate_tokens = estimate_tokens(&context_text);
-                let context_text = Self::truncate_context_to_budget(&context_text, context_budget);
+                let context_text = Self::truncate_context_to_budget(&context_text, budget.context);
                 let post_truncate_tokens = estimate_tokens(&context_text);
-                let history_text = Self::truncate_to_budget(&history_text, history_budget);
-                let memory_text = Self::truncate_to_budget(&memory_text, memory_budget);
+                let history_text = Self::truncate_to_budget(&history_text, budget.history);
+                let memory_text = Self::truncate_to_budget(&memory_text, budget.memory);
 
                 tracing::info!(
                     context_window = context_window,
-                    available = available,
-                    context_budget = context_budget,
+                    available = budget.available,
+                    context_budget = budget.context,
                     context_tokens_pre = pre_truncate_tokens,
                     context_tokens_post = post_truncate_tokens,
                     num_search_results = num_sources,
@@ -727,22 +741,45 @@ impl ChatEngine {
                     String::new()
                 };
 
+                let (context_role, reminder) = if web_augment {
+                    (
+                        "your primary source of facts",
+                        "IMPORTANT REMINDER: Prefer facts from the DOCUMENT CONTEXT above and cite them as [N]. \
+                        The documents may not cover this question, so you may also use your web search results. \
+                        Cite each web source as [W1], [W2], ... in the order of your search results, and never \
+                        state a fact from the web without its [W] citation. \
+                        Do NOT use conversation history or memory as sources of facts.",
+                    )
+                } else {
+                    (
+                        "your ONLY source of facts",
+                        "IMPORTANT REMINDER: Answer using ONLY facts from the DOCUMENT CONTEXT above. \
+                        Do NOT use conversation history, memory, or your own knowledge as sources of facts. \
+                        If information is not in the DOCUMENT CONTEXT, say you don't have it.",
+                    )
+                };
+
+                // Instructions and document context go first as separate system
+                // blocks, so providers with prompt caching can cache that prefix
+                let system_blocks = vec![
+                    context.custom_system_prompt.as_ref()
+                        .map(|custom| format!("{}\n\n{}", custom, RAG_SYSTEM_PROMPT))
+                        .unwrap_or_else(|| RAG_SYSTEM_PROMPT.to_string()),
+                    format!(
+                        "===== DOCUMENT CONTEXT ({context_role}) =====\n\
+                        {context}\n\
+                        ===== END OF DOCUMENT CONTEXT =====",
+                        context_role = context_role,
+                        context = context_text,
+                    ),
+                ];
                 let prompt = format!(
-                    "{instructions}\n\n\
-                    ===== DOCUMENT CONTEXT (your ONLY source of facts) =====\n\
-                    {context}\n\
-                    ===== END OF DOCUMENT CONTEXT =====\n\n\
-                    {history}{memory}\
+                    "{history}{memory}\
                     User Question: \"{question}\"\n\n\
-                    IMPORTANT REMINDER: Answer using ONLY facts from the DOCUMENT CONTEXT above. \
-                    Do NOT use conversation history, memory, or your own knowledge as sources of facts. \
-                    If information is not in the DOCUMENT CONTEXT, say you don't have it.\
+                    {reminder}\
                     {broad_hint}\n\n\
                     Answer:",
-                    instructions = context.custom_system_prompt.as_ref()
-                        .map(|custom| format!("{}\n\n{}", custom, RAG_SYSTEM_PROMPT))
-                        .unwrap_or_else(|| RAG_SYSTEM_PROMPT.to_string()),
-                    context = context_text,
+                    reminder = reminder,
                     history = history_text,
                     memory = memory_text,
                     question = message.content,
@@ -756,68 +793,76 @@ impl ChatEngine {
 
                 let start_time = std::time::Instant::now();
 
-                // Streaming mode if emitter provided.
-                // Cap the LLM generation at 90 seconds — if the provider is
-                // unreachable, fall back to showing search results directly
-                // rather than hanging the UI.
-                let generation_timeout = std::time::Duration::from_secs(90);
+                // Streaming mode if emitter provided. LLMManager enforces
+                // `request_timeout_secs`; on timeout we fall back to showing
+                // search results directly rather than hanging the UI.
                 let llm_response = if emitter.is_some() {
-                    match tokio::time::timeout(
-                        generation_timeout,
-                        llm_manager.generate_stream(&prompt),
-                    ).await {
-                        Ok(Ok(mut token_stream)) => {
+                    match llm_manager.generate_stream_with_system(&system_blocks, &prompt).await {
+                        Ok(mut token_stream) => {
+                            // Raw text for post-processing; the UI only sees `visible`,
+                            // with artifacts streamed through their own events
                             let mut accumulated = String::new();
+                            let mut visible = String::new();
+                            let mut artifact_parser = ArtifactStreamParser::new();
                             while let Some(token) = token_stream.next().await {
                                 accumulated.push_str(&token);
                                 if let Some(em) = emitter {
+                                    emit_stream_events(em, artifact_parser.push(&token), &mut visible);
+                                }
+                            }
+                            if let Some(err) = token_stream.failure() {
+                                Err(err.context("LLM stream failed"))
+                            } else {
+                                if let Some(em) = emitter {
+                                    emit_stream_events(em, artifact_parser.finish(), &mut visible);
                                     em.emit(
-                                        "chat_token",
-                                        serde_json::json!({
-                                            "token": token,
-                                            "accumulated": &accumulated,
-                                        }),
+                                        "chat_complete",
+                                        serde_json::json!({ "content": &visible }),
                                     );
                                 }
+                                Ok((accumulated, token_stream.usage()))
                             }
-                            if let Some(em) = emitter {
-                                em.emit(
-                                    "chat_complete",
-                                    serde_json::json!({ "content": &accumulated }),
-                                );
-                            }
-                            Ok(accumulated)
-                        }
-                        Ok(Err(e)) => Err(e),
-                        Err(_) => {
-                            tracing::warn!("LLM generation timed out after 90s");
-                            Err(anyhow::anyhow!("LLM generation timed out"))
                         }
+                        Err(e) => Err(e),
                     }
                 } else {
-                    match tokio::time::timeout(
-                        generation_timeout,
-                        llm_manager.generate(&prompt),
-                    ).await {
-                        Ok(result) => result,
-                        Err(_) => {
-                            tracing::warn!("LLM generation timed out after 90s");
-                            Err(anyhow::anyhow!("LLM generation timed out"))
-                        }
-                    }
+                    llm_manager
+                        .generate_with_system(&system_blocks, &prompt)
+                        .await
+                        .map(|text| (text, llm_manager.last_usage()))
                 };
 
                 match llm_response {
-                    Ok(response_text) => {
+                    Ok((response_text, usage)) => {
                         let duration = start_time.elapsed();
-                        metadata.input_tokens = Some(estimate_tokens(&prompt));
-                        metadata.output_tokens = Some(estimate_tokens(&response_text));
                         metadata.duration_ms = Some(duration.as_millis() as u64);
                         metadata.model = Some(model_name);
+                        match usage.as_ref().and_then(|u| Some((u.input_tokens?, u.output_tokens?))) {
+                            Some((input, output)) => {
+                                metadata.input_tokens = Some(input);
+                                metadata.output_tokens = Some(output);
+                                metadata.actual_tokens = true;
+                            }
+                            None => {
+                                metadata.input_tokens = Some(estimate_tokens(&join_system_prompt(&system_blocks, &prompt)));
+                                metadata.output_tokens = Some(estimate_tokens(&response_text));
+                            }
+                        }
+                        if let Some(usage) = usage {
+                            metadata.cache_read_tokens = usage.cache_read_tokens;
+                            metadata.cache_write_tokens = usage.cache_write_tokens;
+                        }
+                        if web_augment {
+                            web_sources = llm_manager.last_web_sources();
+                        }
                         response_text
                     }
                     Err(e) => {
-                        tracing::warn!("LLM generation failed: {}, falling back to results", e);
+                        if LLMTimeoutError::is_timeout(&e) {
+                            tracing::warn!("{}, falling back to results", e);
+                        } else {
+                            tracing::warn!("LLM generation failed: {}, falling back to results", e);
+                        }
                         Self::format_fallback_results(&search_results)
                     }
                 }
@@ -830,9 +875,32 @@ impl ChatEngine {
 
         // Post-processing
         content = force_bullet_format(&content);
-        content = validate_citations(&content, num_sources);
+        if web_augment {
+            content = renumber_web_citations(&content, num_sources, web_sources.len());
+        }
+        content = validate_citations(&content, num_sources + web_sources.len());
+        if context.strict_grounding.unwrap_or(false) {
+            let (grounded, uncited) =
+                enforce_grounding(&content, context.uncited_action.unwrap_or_default());
+            if uncited > 0 {
+                tracing::info!(uncited = uncited, "Strict grounding flagged uncited sentences");
+            }
+            content = grounded;
+            metadata.uncited_sentences = Some(uncited);
+        }
 
-        if low_confidence {
+        if !web_sources.is_empty() {
+            tracing::info!(
+                best_score = best_score,
+                web_sources = web_sources.len(),
+                "🌐 Low-confidence answer augmented with web search"
+            );
+            content = format!(
+                "> **Note:** Your documents have low relevance to this query, so this answer \
+                also draws on web search. Web sources are marked as such in the source list.\n\n{}",
+                content
+            );
+        } else if low_confidence {
             content = format!(
                 "> **Note:** The retrieved documents have low relevance to your query. \
                 The following answer may be incomplete.\n\n{}",
@@ -840,10 +908,20 @@ impl ChatEngine {
             );
         }
 
+        let mut citations: Vec<Citation> =
+            search_results.iter().filter_map(|r| r.citation.clone()).collect();
+        if !context.separate_citations.unwrap_or(false) {
+            citations = merge_citations(citations);
+        }
+        // Web sources go last so `[N]` markers index local results first
+        citations.extend(web_sources.iter().map(Citation::from_web));
+        let mut search_results = search_results;
+        search_results.extend(web_sources.iter().map(Self::web_search_result));
+
         Ok(AssistantResponse {
             content,
             artifacts: Vec::new(),
-            citations: search_results.iter().filter_map(|r| r.citation.clone()).collect(),
+            citations,
             suggestions: vec![
                 "Tell me more".to_string(),
                 "Show related information".to_string(),
@@ -1564,23 +1642,18 @@ RULES:
         let tool_descriptions = self.tool_registry.get_tool_descriptions();
         let tool_schemas = crate::agent::tool_loop::tool_descriptions_to_schemas(&tool_descriptions);
 
-        // Build the system prompt with tool-calling instructions
-        let now = chrono::Utc::now();
-        let system_prompt = format!(
-            "You are a helpful personal assistant with access to tools. \
-             Use the provided tools to fulfill the user's request. \
-             Today's date is {}. Current time is {} UTC.\n\n\
+        // Build the system prompt with tool-calling instructions. It stays free of
+        // the current time so providers can cache it across requests.
+        let system_prompt = "You are a helpful personal assistant with access to tools. \
+             Use the provided tools to fulfill the user's request.\n\n\
              When the user asks you to create tasks, events, reminders, or perform \
              any action, use the appropriate tool. Do NOT output code or JSON — \
              call the tool directly.\n\n\
              After executing a tool successfully, provide a brief, friendly confirmation \
-             to the user describing what you did.",
-            now.format("%Y-%m-%d"),
-            now.format("%H:%M"),
-        );
+             to the user describing what you did.";
 
         // Build conversation history as ChatMessages
-        let mut messages = vec![crate::llm::ChatMessage::system(&system_prompt)];
+        let mut messages = vec![crate::llm::ChatMessage::system(system_prompt)];
 
         if let Some(history) = &context.conversation_history {
             for msg in history.iter().rev().take(6).collect::<Vec<_>>().into_iter().rev() {
@@ -1592,7 +1665,14 @@ RULES:
             }
         }
 
-        messages.push(crate::llm::ChatMessage::user(&message.content));
+        // The date goes with the latest turn, after the cacheable prefix
+        let now = chrono::Utc::now();
+        messages.push(crate::llm::ChatMessage::user(format!(
+            "[Today's date is {}. Current time is {} UTC.]\n\n{}",
+            now.format("%Y-%m-%d"),
+            now.format("%H:%M"),
+            message.content,
+        )));
 
         // Build agent context for tool execution
         let agent_context = AgentContext {
@@ -1612,6 +1692,8 @@ RULES:
             max_iterations: 5,
             tool_timeout_secs: 30,
             streaming: emitter.is_some(),
+            cancel_token: Some(LLMManager::current_cancellation()),
+            ..Default::default()
         };
 
         // Bridge EventEmitter to ToolLoopEmitter for streaming
@@ -1792,6 +1874,8 @@ RULES:
             importance_threshold: Some(0.5),
             max_results: 5,
             retrieval_mode: RetrievalMode::Temporal,
+            causal_seed: None,
+            causal_depth: 0,
         };
 
         Ok(memory_system.retrieve(&query).unwrap_or_default())
@@ -1910,7 +1994,7 @@ RULES:
             causal_chain: Vec::new(),
             outcomes: Vec::new(),
         };
-        memory_system.record(user_exp).ok();
+        memory_system.record(user_exp).await.ok();
 
         // Store assistant response
         let mut response_context = rich_context;
@@ -1940,7 +2024,7 @@ RULES:
             causal_chain: Vec::new(),
             outcomes: Vec::new(),
         };
-        memory_system.record(assistant_exp).ok();
+        memory_system.record(assistant_exp).await.ok();
 
         Ok(())
     }
@@ -2118,14 +2202,245 @@ RULES:
         )
     }
 
+    /// Broad queries need more results to cover the entire corpus.
+    /// Detect patterns: explicit "all/every/list/each" + plural extraction patterns
+    /// like "emails from invoices", "names and phones", "documents about X"
+    fn is_broad_query(content: &str) -> bool {
+        let content_lower = content.to_lowercase();
+        let has_explicit_broad = content_lower.contains("all ")
+            || content_lower.contains("every ")
+            || content_lower.contains("list ")
+            || content_lower.contains("each ")
+            || content_lower.contains("everyone")
+            || content_lower.contains("everything");
+        // Plural nouns requesting extraction from a collection (e.g. "emails from invoices")
+        let has_plural_extraction = (content_lower.contains("from ")
+            || content_lower.contains("in the ")
+            || content_lower.contains("across "))
+            && (content_lower.contains("emails")
+                || content_lower.contains("names")
+                || content_lower.contains("phones")
+                || content_lower.contains("numbers")
+                || content_lower.contains("addresses")
+                || content_lower.contains("ids")
+                || content_lower.contains("details")
+                || content_lower.contains("records"));
+        has_explicit_broad || has_plural_extraction
+    }
+
+    /// Search every query variant and merge the hits; a single variant is
+    /// searched directly so its errors surface
+    /// Per-variant reranking settings from the chat context
+    fn rerank_options(context: &ChatContext) -> crate::types::RerankOptions {
+        crate::types::RerankOptions {
+            cross_encoder: context.cross_encoder_rerank.unwrap_or(false),
+            top_k: context.rerank_top_k,
+        }
+    }
+
+    async fn search_variants(
+        rag: &RAGEngine,
+        primary_query: &str,
+        variants: &[String],
+        max_results: usize,
+        rerank: crate::types::RerankOptions,
+    ) -> Result<Vec<crate::types::SimpleSearchResult>> {
+        if variants.len() > 1 {
+            let mut all_result_sets = Vec::new();
+            for variant in variants {
+                match rag.search_with(variant, max_results, rerank).await {
+                    Ok(variant_results) => {
+                        tracing::debug!(
+                            variant = %variant,
+                            hits = variant_results.len(),
+                            "Variant search complete"
+                        );
+                        all_result_sets.push(variant_results);
+                    }
+                    Err(e) => {
+                        tracing::warn!(variant = %variant, error = %e, "Variant search failed");
+                    }
+                }
+            }
+            Ok(Self::merge_expanded_results(all_result_sets, max_results))
+        } else {
+            rag.search_with(primary_query, max_results, rerank).await
+        }
+    }
+
+    fn to_search_results(results: &[crate::types::SimpleSearchResult]) -> Vec<SearchResult> {
+        results
+            .iter()
+            .map(|r| {
+                let snippet_text: String = r.text.chars().take(200).collect();
+                let source_file = r
+                    .metadata
+                    .get("file_path")
+                    .or_else(|| r.metadata.get("source_file"))
+                    .or_else(|| r.metadata.get("original_path"))
+                    .cloned()
+                    .unwrap_or_else(|| {
+                        r.source
+                            .strip_prefix("Folder: ")
+                            .unwrap_or(&r.source)
+                            .to_string()
+                    });
+
+                SearchResult {
+                    text: r.text.clone(),
+                    score: r.score,
+                    source_file,
+                    page_number: r.citation.as_ref().and_then(|c| c.page_numbers.clone()),
+                    line_range: None,
+                    snippet: snippet_text.clone(),
+                    citation: r.citation.as_ref().map(|c| Citation {
+                        title: c.title.clone(),
+                        snippet: snippet_text.clone(),
+                        score: r.score,
+                        url: c.url.clone(),
+                        authors: c.authors.clone(),
+                        source: r.source.clone(),
+                        year: c.year.clone(),
+                        page_numbers: c.page_numbers.clone(),
+                        origin: CitationOrigin::Local,
+                    }),
+                    metadata: r.metadata.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Send only chunks that add genuine information value.
+    /// Three stages: relevance filter → content dedup → information gain cutoff.
+    ///
+    /// For broad queries ("list all emails from invoices"), skip aggressive
+    /// curation — the user wants exhaustive coverage, not just the top hits.
+    /// The hybrid search score gap (items in both vector+FTS score ~2x those
+    /// in only one index) creates artificial cliffs that would cut valid results.
+    fn curate_results(search_results: Vec<SearchResult>, is_broad_query: bool) -> Vec<SearchResult> {
+        let best_score = search_results.iter().map(|r| r.score).fold(0.0f32, f32::max);
+        let pre_filter_count = search_results.len();
+
+        // Stage 1: Relevance filter — drop truly irrelevant chunks.
+        // Broad queries use a very low threshold (5% of best) since all matching
+        // documents from the target collection are relevant.
+        let score_threshold = if is_broad_query {
+            best_score * 0.05
+        } else {
+            best_score * 0.15
+        };
+        let mut search_results: Vec<SearchResult> = search_results
+            .into_iter()
+            .filter(|r| r.score >= score_threshold)
+            .collect();
+
+        // Stage 2: Content deduplication — chunks from overlapping document regions
+        // or multi-variant search often contain near-identical text. Keep only the
+        // highest-scored version when two chunks share >60% of their words.
+        search_results = Self::deduplicate_by_content(search_results);
+
+        // Stage 3: Score-gap cutoff — skip for broad queries where exhaustive
+        // coverage matters more than precision. For focused queries, detect
+        // genuine relevance cliffs to avoid wasting LLM context tokens.
+        if !is_broad_query {
+            search_results = Self::cut_at_score_cliff(search_results);
+        }
+
+        {
+            let curation_sources: std::collections::HashSet<&str> = search_results
+                .iter()
+                .map(|r| r.source_file.as_str())
+                .collect();
+            tracing::info!(
+                best_score = best_score,
+                score_threshold = score_threshold,
+                is_broad_query = is_broad_query,
+                pre_filter = pre_filter_count,
+                post_curation = search_results.len(),
+                unique_sources = curation_sources.len(),
+                sources = ?curation_sources,
+                "Context curation complete"
+            );
+        }
+
+        search_results
+    }
+
+    /// Numbered context block for the LLM, annotating spreadsheet data for chart generation
+    fn build_context_text(search_results: &[SearchResult]) -> String {
+        search_results
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                // Ebook chunks cite their chapter
+                let citation_info = r
+                    .citation
+                    .as_ref()
+                    .map(|c| match r.metadata.get("chapter") {
+                        Some(chapter) => format!(" [Source: {}, {}]", c.title, chapter),
+                        None => format!(" [Source: {}]", c.title),
+                    })
+                    .unwrap_or_default();
+
+                // Hint for spreadsheet/table data so LLM knows it can generate charts.
+                // Sheet and CSV chunks carry their column schema; `has_table` is set at
+                // ingest for other tables; the text check covers older chunks.
+                let numeric_columns = r.metadata.get("numeric_columns").filter(|c| !c.is_empty());
+                let is_table = numeric_columns.is_some()
+                    || r.metadata.get("has_table").is_some_and(|v| v == "true")
+                    || r.text.contains("| --- |")
+                    || r.text.contains("|---|");
+                let data_hint = if let Some(columns) = numeric_columns {
+                    format!(
+                        " [DATA: Tabular data with numeric columns {} — suitable for chart visualization]",
+                        columns.replace(',', ", ")
+                    )
+                } else if is_table {
+                    // Tolerate thousands separators, currency and percent signs
+                    let has_numbers = r.text.lines().skip(2).any(|line| {
+                        line.split('|').any(|cell| {
+                            let cell: String = cell.trim()
+                                .chars()
+                                .filter(|c| !matches!(c, ',' | '$' | '€' | '£' | '₹' | '%'))
+                                .collect();
+                            cell.parse::<f64>().is_ok()
+                        })
+                    });
+                    if has_numbers {
+                        " [DATA: This is tabular data with numeric columns — suitable for chart visualization]".to_string()
+                    } else {
+                        String::new()
+                    }
+                } else {
+                    String::new()
+                };
+                let text = if is_table { fence_markdown_tables(&r.text) } else { r.text.clone() };
+
+                // Append extracted structured fields so the LLM sees them explicitly
+                let extracted_fields = Self::format_extracted_fields(&r.metadata);
+
+                format!("[{}]{}{}\n{}{}", i + 1, citation_info, data_hint, text, extracted_fields)
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
     fn build_history_text(context: &ChatContext) -> String {
+        let checkpoint = context.history_summary.as_deref().filter(|s| !s.trim().is_empty());
         if let Some(history) = &context.conversation_history {
             if !history.is_empty() {
                 let messages: Vec<(String, String)> = history
                     .iter()
                     .map(|msg| (msg.role.clone(), msg.content.clone()))
                     .collect();
-                let compressed = compress_history(&messages, 5);
+                let mut compressed = compress_history(&messages, 5);
+                // The stored checkpoint covers turns older than anything passed in
+                if let Some(checkpoint) = checkpoint {
+                    compressed.summary = Some(match compressed.summary {
+                        Some(recent) => format!("{} {}", checkpoint, recent),
+                        None => checkpoint.to_string(),
+                    });
+                }
                 return format_compressed_history(&compressed);
             }
         }
@@ -2155,6 +2470,28 @@ RULES:
         )
     }
 
+    /// Whether the configured LLM searches the web while answering
+    async fn llm_supports_web_search(&self) -> bool {
+        match self.llm_manager.as_ref() {
+            Some(llm) => llm.read().await.as_ref().is_some_and(|m| m.supports_web_search()),
+            None => false,
+        }
+    }
+
+    /// Search-result entry for a web page an online model cited
+    fn web_search_result(source: &WebSource) -> SearchResult {
+        SearchResult {
+            text: String::new(),
+            score: 0.0,
+            citation: Some(Citation::from_web(source)),
+            source_file: source.url.clone(),
+            page_number: None,
+            line_range: None,
+            snippet: String::new(),
+            metadata: HashMap::from([("origin".to_string(), "web".to_string())]),
+        }
+    }
+
     fn get_context_window_from_llm(llm_manager: &LLMManager) -> usize {
         let window = llm_manager
             .info()
@@ -2165,7 +2502,7 @@ RULES:
                     None
                 }
             })
-            .unwrap_or(128_000); // Modern LLMs support at least 128K
+            .unwrap_or(DEFAULT_CONTEXT_WINDOW); // Modern LLMs support at least 128K
         tracing::debug!(context_window = window, "Resolved LLM context window");
         window
     }
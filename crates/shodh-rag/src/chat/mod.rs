@@ -1,14 +1,17 @@
+pub mod artifact_stream;
 pub mod engine;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::LazyLock;
 
+use crate::llm::{LLMOverrides, WebSource};
+
 // Pre-compiled regexes — compiled once, reused on every call.
 static ARTIFACT_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
     regex::Regex::new(
-        r#"(?s)<artifact[^>]*\s+(?:id|identifier)="([^"]+)"[^>]*\s+type="([^"]+)"[^>]*(?:\s+language="([^"]+)")?[^>]*\s+title="([^"]+)"[^>]*>(.*?)</artifact>"#
+        r#"(?s)<artifact[^>]*\s+(?:id|identifier)="([^"]+)"[^>]*\s+type="([^"]+)"[^>]*?(?:\s+language="([^"]+)")?[^>]*\s+title="([^"]+)"[^>]*>(.*?)</artifact>"#
     ).expect("artifact regex is valid")
 });
 static STRIP_ARTIFACT_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
@@ -17,6 +20,28 @@ static STRIP_ARTIFACT_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
 static CITATION_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
     regex::Regex::new(r"\[(\d+)\]").expect("citation regex is valid")
 });
+/// A citation group such as `[2]` or `[1, 3]`, allowing leading whitespace,
+/// at the start of the text
+static LEADING_CITATION_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"^\s*\[\d+(?:\s*,\s*\d+)*\]").expect("leading citation regex is valid")
+});
+/// A citation marker with the whitespace before it
+static BARE_CITATION_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"\s*\[\d+\]").expect("bare citation regex is valid")
+});
+/// `[W2]`-style marker for the second web source in a web-augmented answer
+static WEB_CITATION_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"\[W(\d+)\]").expect("web citation regex is valid")
+});
+static MULTI_CITATION_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"\[\d+(?:\s*,\s*\d+)+\]").expect("multi citation regex is valid")
+});
+static LIST_PREFIX_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"^\s*(?:(?:[-*+]|\d+[.)]|>)\s+)*").expect("list prefix regex is valid")
+});
+static PAGE_RANGE_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"(\d+)(?:\s*[-–—]\s*(\d+))?").expect("page range regex is valid")
+});
 
 // ============================================================================
 // Types
@@ -49,6 +74,53 @@ pub struct ChatContext {
     pub max_results: Option<usize>,
     pub streaming: Option<bool>,
     pub custom_system_prompt: Option<String>,
+    /// Rescore merged search results with the cross-encoder (default off)
+    #[serde(default)]
+    pub cross_encoder_rerank: Option<bool>,
+    /// LLM listwise reranking of search results (default on)
+    #[serde(default)]
+    pub llm_rerank: Option<bool>,
+    /// How many top results the rerankers see; with both enabled the LLM
+    /// only reorders the cross-encoder's top `rerank_top_k`
+    #[serde(default)]
+    pub rerank_top_k: Option<usize>,
+    /// Keep one citation per retrieved chunk instead of merging citations
+    /// from the same document (default off)
+    #[serde(default)]
+    pub separate_citations: Option<bool>,
+    /// Check every factual sentence in a search answer for a `[N]` citation
+    /// (default off)
+    #[serde(default)]
+    pub strict_grounding: Option<bool>,
+    /// What strict grounding does with uncited sentences (default mark)
+    #[serde(default)]
+    pub uncited_action: Option<UncitedAction>,
+    /// LLM overrides stored on the space, applied over the global config
+    /// when `space_id` is set
+    #[serde(default)]
+    pub space_llm_settings: Option<LLMOverrides>,
+    /// Stored summary of the turns before `conversation_history`
+    #[serde(default)]
+    pub history_summary: Option<String>,
+    /// Let a web-search-capable model (Perplexity) consult the web when the
+    /// best local result is below the low-confidence threshold (default off)
+    #[serde(default)]
+    pub allow_web_augmentation: Option<bool>,
+    /// Caller-chosen id for this turn, so it can be stopped with
+    /// `cancel_generation`
+    #[serde(default)]
+    pub request_id: Option<String>,
+}
+
+/// Handling of uncited factual sentences under strict grounding
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UncitedAction {
+    /// Append an "⚠ uncited" marker to the sentence
+    #[default]
+    Mark,
+    /// Remove the sentence
+    Strip,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,9 +149,19 @@ pub struct Artifact {
     pub editable: bool,
     pub version: u32,
     pub created_at: DateTime<Utc>,
+    /// False when the content failed validation (malformed SVG, undeclared
+    /// Mermaid diagram type); the reason is in `metadata["validation_error"]`
+    #[serde(default = "default_valid")]
+    pub valid: bool,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_valid() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ArtifactType {
     Code,
@@ -102,6 +184,36 @@ pub struct Citation {
     pub source: String,
     pub year: String,
     pub page_numbers: Option<String>,
+    #[serde(default)]
+    pub origin: CitationOrigin,
+}
+
+/// Where a cited fact came from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CitationOrigin {
+    /// An indexed document
+    #[default]
+    Local,
+    /// A web page found by an online model; `url` is always set
+    Web,
+}
+
+impl Citation {
+    /// Citation for a page an online model consulted
+    pub fn from_web(source: &WebSource) -> Self {
+        Self {
+            title: source.title.clone().unwrap_or_else(|| source.url.clone()),
+            snippet: String::new(),
+            score: 0.0,
+            url: Some(source.url.clone()),
+            authors: Vec::new(),
+            source: source.url.clone(),
+            year: source.date.clone().unwrap_or_default(),
+            page_numbers: None,
+            origin: CitationOrigin::Web,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -139,6 +251,61 @@ pub struct ResponseMetadata {
     /// Latency (ms) for LLM-based reranking of merged results.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rerank_latency_ms: Option<u64>,
+    /// Rerankers applied to the search results, in order ("cross_encoder", "llm").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rerankers: Option<Vec<String>>,
+    /// Factual sentences strict grounding found without a citation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uncited_sentences: Option<usize>,
+    /// Prompt tokens served from the provider's prompt cache (Anthropic).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_read_tokens: Option<usize>,
+    /// Prompt tokens written to the provider's prompt cache (Anthropic).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_write_tokens: Option<usize>,
+    /// True when `input_tokens`/`output_tokens` came from the provider rather
+    /// than the character-based estimate.
+    #[serde(default)]
+    pub actual_tokens: bool,
+}
+
+/// Projected token use of a search prompt, from `ChatEngine::estimate_context_usage`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextUsage {
+    pub model: Option<String>,
+    pub context_window: usize,
+    /// Held back for the answer
+    pub response_reserve: usize,
+    pub system_prompt_tokens: usize,
+    pub query_tokens: usize,
+    pub retrieved: ContextPart,
+    /// Chunks left after curation, before any truncation
+    pub retrieved_chunks: usize,
+    pub history: ContextPart,
+    pub memory: ContextPart,
+    /// Prompt size after truncation
+    pub total_tokens: usize,
+    /// True when any part exceeds its share and will be cut
+    pub will_truncate: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextPart {
+    pub tokens: usize,
+    pub budget: usize,
+    pub truncated: bool,
+}
+
+impl ContextPart {
+    pub fn new(tokens: usize, budget: usize) -> Self {
+        Self { tokens, budget, truncated: tokens > budget }
+    }
+
+    /// Tokens actually sent
+    pub fn sent(&self) -> usize {
+        self.tokens.min(self.budget)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -268,7 +435,14 @@ For explanations or short code snippets (< 5 lines), just write them normally wi
 /// Extract artifacts from LLM response content and return cleaned content
 /// with artifact blocks removed. Handles both `<artifact>` XML tags and
 /// standalone code blocks (```mermaid, ```flowchart, ```code, etc.)
+/// SVG and Mermaid artifacts are validated; see `validate_artifact`.
 pub fn extract_artifacts(content: &str) -> (Vec<Artifact>, String) {
+    let (mut artifacts, cleaned) = extract_unvalidated(content);
+    artifacts.iter_mut().for_each(validate_artifact);
+    (artifacts, cleaned)
+}
+
+fn extract_unvalidated(content: &str) -> (Vec<Artifact>, String) {
     let mut artifacts = Vec::new();
 
     for cap in ARTIFACT_RE.captures_iter(content) {
@@ -278,29 +452,16 @@ pub fn extract_artifacts(content: &str) -> (Vec<Artifact>, String) {
         let title = cap.get(4).map(|m| m.as_str()).unwrap_or("");
         let artifact_content = cap.get(5).map(|m| m.as_str()).unwrap_or("");
 
-        let artifact_type = match type_str {
-            "code" => ArtifactType::Code,
-            "markdown" => ArtifactType::Markdown,
-            "mermaid" => ArtifactType::Mermaid,
-            "table" => ArtifactType::Table,
-            "chart" => ArtifactType::Chart,
-            "html" => ArtifactType::Html,
-            "svg" => ArtifactType::Svg,
-            _ => continue,
+        let Some(artifact_type) = artifact_type_from_tag(type_str) else {
+            continue;
         };
+        let clean_content = strip_code_fence(artifact_content);
 
-        let clean_content = if artifact_content.trim().starts_with("```") {
-            artifact_content
-                .trim()
-                .strip_prefix("```")
-                .and_then(|s| s.split_once('\n').map(|(_, rest)| rest))
-                .and_then(|s| s.strip_suffix("```"))
-                .unwrap_or(artifact_content)
-                .trim()
-                .to_string()
-        } else {
-            artifact_content.trim().to_string()
-        };
+        if artifact_type == ArtifactType::Chart {
+            let idx = artifacts.len();
+            artifacts.push(chart_artifact(id.to_string(), Some(title.to_string()), &clean_content, idx));
+            continue;
+        }
 
         artifacts.push(Artifact {
             id: id.to_string(),
@@ -311,6 +472,8 @@ pub fn extract_artifacts(content: &str) -> (Vec<Artifact>, String) {
             editable: true,
             version: 1,
             created_at: Utc::now(),
+            valid: true,
+            metadata: HashMap::new(),
         });
     }
 
@@ -346,82 +509,43 @@ pub fn extract_artifacts(content: &str) -> (Vec<Artifact>, String) {
                     let block_end = content_end + 4; // includes "\n```"
                     let code_content = &content[content_start..content_end];
 
-                    let is_mermaid = matches!(
-                        language_opt,
-                        Some("mermaid" | "flowchart" | "sequence" | "class"
-                            | "erdiagram" | "er" | "state" | "gantt"
-                            | "gitgraph" | "git" | "journey")
-                    );
+                    let is_mermaid = language_opt.is_some_and(is_mermaid_language);
 
                     let is_table = language_opt == Some("table");
                     let is_chart = language_opt == Some("chart");
 
                     if is_mermaid {
-                        let title = match language_opt.unwrap_or("mermaid") {
-                            "flowchart" => "Flowchart",
-                            "sequence" => "Sequence Diagram",
-                            "class" => "Class Diagram",
-                            "erdiagram" | "er" => "ER Diagram",
-                            "state" => "State Diagram",
-                            "gantt" => "Gantt Chart",
-                            "gitgraph" | "git" => "Git Graph",
-                            "journey" => "User Journey",
-                            _ => "Mermaid Diagram",
-                        };
                         artifacts.push(Artifact {
                             id: format!("mermaid-{}", idx),
                             artifact_type: ArtifactType::Mermaid,
-                            title: title.to_string(),
+                            title: mermaid_title(language_opt.unwrap_or("mermaid")).to_string(),
                             content: code_content.trim().to_string(),
                             language: None,
                             editable: true,
                             version: 1,
                             created_at: Utc::now(),
+                            valid: true,
+                            metadata: HashMap::new(),
                         });
                         block_ranges.push((abs_start, block_end));
                         idx += 1;
                     } else if is_table {
-                        let table_title = code_content.lines().next()
-                            .filter(|line| line.contains('|'))
-                            .map(|line| {
-                                let cols: Vec<&str> = line.split('|')
-                                    .map(|s| s.trim())
-                                    .filter(|s| !s.is_empty())
-                                    .collect();
-                                if cols.len() <= 3 {
-                                    cols.join(" / ")
-                                } else {
-                                    format!("{} (+{} cols)", cols[..2].join(" / "), cols.len() - 2)
-                                }
-                            })
-                            .unwrap_or_else(|| format!("Table {}", idx + 1));
                         artifacts.push(Artifact {
                             id: format!("table-{}", idx),
                             artifact_type: ArtifactType::Table,
-                            title: table_title,
+                            title: table_title(code_content, idx),
                             content: code_content.trim().to_string(),
                             language: Some("table".to_string()),
                             editable: true,
                             version: 1,
                             created_at: Utc::now(),
+                            valid: true,
+                            metadata: HashMap::new(),
                         });
                         block_ranges.push((abs_start, block_end));
                         idx += 1;
                     } else if is_chart {
-                        let chart_title = serde_json::from_str::<serde_json::Value>(code_content.trim())
-                            .ok()
-                            .and_then(|v| v.get("title").and_then(|t| t.as_str()).map(String::from))
-                            .unwrap_or_else(|| format!("Chart {}", idx + 1));
-                        artifacts.push(Artifact {
-                            id: format!("chart-{}", idx),
-                            artifact_type: ArtifactType::Chart,
-                            title: chart_title,
-                            content: code_content.trim().to_string(),
-                            language: Some("chart".to_string()),
-                            editable: true,
-                            version: 1,
-                            created_at: Utc::now(),
-                        });
+                        artifacts.push(chart_artifact(format!("chart-{}", idx), None, code_content, idx));
                         block_ranges.push((abs_start, block_end));
                         idx += 1;
                     } else if code_content.lines().count() > 5 {
@@ -434,6 +558,8 @@ pub fn extract_artifacts(content: &str) -> (Vec<Artifact>, String) {
                             editable: true,
                             version: 1,
                             created_at: Utc::now(),
+                            valid: true,
+                            metadata: HashMap::new(),
                         });
                         block_ranges.push((abs_start, block_end));
                         idx += 1;
@@ -465,11 +591,335 @@ pub fn extract_artifacts(content: &str) -> (Vec<Artifact>, String) {
     (artifacts, content.to_string())
 }
 
+/// `ArtifactType` for an `<artifact type="...">` attribute
+pub(crate) fn artifact_type_from_tag(type_str: &str) -> Option<ArtifactType> {
+    Some(match type_str {
+        "code" => ArtifactType::Code,
+        "markdown" => ArtifactType::Markdown,
+        "mermaid" => ArtifactType::Mermaid,
+        "table" => ArtifactType::Table,
+        "chart" => ArtifactType::Chart,
+        "html" => ArtifactType::Html,
+        "svg" => ArtifactType::Svg,
+        _ => return None,
+    })
+}
+
+/// Trimmed artifact body, without a code fence wrapped around all of it
+pub(crate) fn strip_code_fence(content: &str) -> String {
+    let trimmed = content.trim();
+    if !trimmed.starts_with("```") {
+        return trimmed.to_string();
+    }
+    trimmed
+        .strip_prefix("```")
+        .and_then(|s| s.split_once('\n').map(|(_, rest)| rest))
+        .and_then(|s| s.strip_suffix("```"))
+        .unwrap_or(content)
+        .trim()
+        .to_string()
+}
+
+/// Fence languages rendered as Mermaid diagrams
+pub(crate) fn is_mermaid_language(lang: &str) -> bool {
+    matches!(
+        lang,
+        "mermaid" | "flowchart" | "sequence" | "class" | "erdiagram" | "er" | "state" | "gantt"
+            | "gitgraph" | "git" | "journey"
+    )
+}
+
+pub(crate) fn mermaid_title(lang: &str) -> &'static str {
+    match lang {
+        "flowchart" => "Flowchart",
+        "sequence" => "Sequence Diagram",
+        "class" => "Class Diagram",
+        "erdiagram" | "er" => "ER Diagram",
+        "state" => "State Diagram",
+        "gantt" => "Gantt Chart",
+        "gitgraph" | "git" => "Git Graph",
+        "journey" => "User Journey",
+        _ => "Mermaid Diagram",
+    }
+}
+
+/// Table title from its header row, e.g. "Name / Email / Phone"
+pub(crate) fn table_title(content: &str, idx: usize) -> String {
+    content
+        .lines()
+        .next()
+        .filter(|line| line.contains('|'))
+        .map(|line| {
+            let cols: Vec<&str> = line.split('|').map(|s| s.trim()).filter(|s| !s.is_empty()).collect();
+            if cols.len() <= 3 {
+                cols.join(" / ")
+            } else {
+                format!("{} (+{} cols)", cols[..2].join(" / "), cols.len() - 2)
+            }
+        })
+        .unwrap_or_else(|| format!("Table {}", idx + 1))
+}
+
+/// Wrap bare markdown tables in ```table fences, so a model that quotes a
+/// table from its context emits a block `extract_artifacts` turns into a
+/// Table artifact
+pub(crate) fn fence_markdown_tables(text: &str) -> String {
+    let is_separator = |line: &str| {
+        line.starts_with('|') && line.contains("---") && line.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '))
+    };
+    let lines: Vec<&str> = text.lines().collect();
+    let mut out = Vec::with_capacity(lines.len() + 2);
+    let mut in_fence = false;
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        let starts_table = !in_fence
+            && line.trim_start().starts_with('|')
+            && lines.get(i + 1).is_some_and(|next| is_separator(next.trim()));
+        if !starts_table {
+            out.push(line);
+            i += 1;
+            continue;
+        }
+        let end = (i..lines.len())
+            .find(|&j| !lines[j].trim_start().starts_with('|'))
+            .unwrap_or(lines.len());
+        out.push("```table");
+        out.extend(&lines[i..end]);
+        out.push("```");
+        i = end;
+    }
+    out.join("\n")
+}
+
+pub(crate) fn chart_title(content: &str, idx: usize) -> String {
+    serde_json::from_str::<serde_json::Value>(content.trim())
+        .ok()
+        .and_then(|v| v.get("title").and_then(|t| t.as_str()).map(String::from))
+        .unwrap_or_else(|| format!("Chart {}", idx + 1))
+}
+
+/// Chart types the frontend renders
+const CHART_TYPES: &[&str] = &["bar", "line", "pie", "scatter", "area", "radar", "doughnut", "bubble"];
+
+/// Chart artifact for a chart JSON body. Trailing commas and single quotes
+/// are repaired; JSON that still doesn't parse or lacks `type`,
+/// `data.labels` or `data.datasets` becomes a `json` Code artifact whose
+/// title carries the error, instead of a chart that renders blank.
+pub(crate) fn chart_artifact(id: String, title: Option<String>, content: &str, idx: usize) -> Artifact {
+    let (artifact_type, title, content, language) = match validate_chart_json(content) {
+        Ok(json) => {
+            let title = title.unwrap_or_else(|| chart_title(&json, idx));
+            (ArtifactType::Chart, title, json, "chart")
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Chart JSON invalid, emitting as code");
+            let title = title.unwrap_or_else(|| format!("Chart {}", idx + 1));
+            (ArtifactType::Code, format!("{} (invalid chart: {})", title, e), content.trim().to_string(), "json")
+        }
+    };
+    Artifact {
+        id,
+        artifact_type,
+        title,
+        content,
+        language: Some(language.to_string()),
+        editable: true,
+        version: 1,
+        created_at: Utc::now(),
+        valid: true,
+        metadata: HashMap::new(),
+    }
+}
+
+/// Parse and schema-check chart JSON, repairing it if needed. Returns the
+/// JSON to render: the original text when it was valid, else the repaired
+/// value pretty-printed.
+fn validate_chart_json(content: &str) -> Result<String, String> {
+    let trimmed = content.trim();
+    let (value, repaired) = match serde_json::from_str::<serde_json::Value>(trimmed) {
+        Ok(value) => (value, false),
+        Err(original) => match serde_json::from_str(&repair_json(trimmed)) {
+            Ok(value) => (value, true),
+            Err(_) => return Err(original.to_string()),
+        },
+    };
+
+    let chart_type = value.get("type").and_then(|t| t.as_str()).ok_or("missing \"type\"")?;
+    if !CHART_TYPES.contains(&chart_type) {
+        return Err(format!("unsupported chart type \"{}\"", chart_type));
+    }
+    if value.get("title").is_some_and(|t| !t.is_string()) {
+        return Err("\"title\" must be a string".to_string());
+    }
+    let data = value.get("data").ok_or("missing \"data\"")?;
+    if !data.get("labels").is_some_and(|l| l.is_array()) {
+        return Err("\"data.labels\" must be an array".to_string());
+    }
+    let datasets = data
+        .get("datasets")
+        .and_then(|d| d.as_array())
+        .filter(|d| !d.is_empty())
+        .ok_or("\"data.datasets\" must be a non-empty array")?;
+    if !datasets.iter().all(|d| d.get("data").is_some_and(|v| v.is_array())) {
+        return Err("every dataset needs a \"data\" array".to_string());
+    }
+
+    if repaired {
+        serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+    } else {
+        Ok(trimmed.to_string())
+    }
+}
+
+/// Fix the JSON mistakes LLMs commonly make: trailing commas before `}`/`]`
+/// and single-quoted strings. Text inside strings is left alone.
+fn repair_json(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut out = String::with_capacity(input.len());
+    let mut quote: Option<char> = None;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match quote {
+            Some(q) => {
+                if c == '\\' && i + 1 < chars.len() {
+                    // `\'` is not a JSON escape
+                    if chars[i + 1] != '\'' {
+                        out.push(c);
+                    }
+                    out.push(chars[i + 1]);
+                    i += 2;
+                    continue;
+                }
+                if c == q {
+                    out.push('"');
+                    quote = None;
+                } else if c == '"' {
+                    out.push_str("\\\"");
+                } else {
+                    out.push(c);
+                }
+            }
+            None => match c {
+                '"' | '\'' => {
+                    quote = Some(c);
+                    out.push('"');
+                }
+                ',' => {
+                    let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+                    if !matches!(next, Some('}' | ']')) {
+                        out.push(c);
+                    }
+                }
+                _ => out.push(c),
+            },
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Mermaid diagram declarations accepted on the first line
+const MERMAID_DIAGRAM_TYPES: &[&str] = &[
+    "graph", "flowchart", "sequenceDiagram", "classDiagram", "classDiagram-v2", "stateDiagram",
+    "stateDiagram-v2", "erDiagram", "journey", "gantt", "pie", "quadrantChart",
+    "requirementDiagram", "gitGraph", "C4Context", "C4Container", "C4Component", "C4Dynamic",
+    "C4Deployment", "mindmap", "timeline", "sankey-beta", "xychart-beta", "block-beta",
+    "packet-beta", "kanban", "architecture-beta",
+];
+
+/// Check SVG and Mermaid artifacts so the UI can explain a failed render
+/// instead of showing a blank one. Invalid artifacts are kept, with
+/// `valid` false and the error in `metadata["validation_error"]`.
+pub(crate) fn validate_artifact(artifact: &mut Artifact) {
+    let result = match artifact.artifact_type {
+        ArtifactType::Svg => validate_svg(&artifact.content),
+        ArtifactType::Mermaid => validate_mermaid(&artifact.content),
+        _ => return,
+    };
+    if let Err(e) = result {
+        tracing::warn!(artifact = %artifact.id, error = %e, "Artifact failed validation");
+        artifact.valid = false;
+        artifact.metadata.insert("validation_error".to_string(), e);
+    }
+}
+
+fn validate_svg(content: &str) -> Result<(), String> {
+    let doc = roxmltree::Document::parse(content.trim())
+        .map_err(|e| format!("SVG is not well-formed XML: {}", e))?;
+    let root = doc.root_element().tag_name().name();
+    if root != "svg" {
+        return Err(format!("SVG root element is <{}>, expected <svg>", root));
+    }
+    Ok(())
+}
+
+/// Diagram types MermaidArtifact.tsx recognises at the start of an artifact;
+/// anything else is rendered with `flowchart TD` prepended.
+const MERMAID_RENDERER_TYPES: &[&str] = &[
+    "graph", "flowchart", "sequenceDiagram", "classDiagram", "stateDiagram", "erDiagram",
+    "journey", "gantt", "pie", "gitGraph",
+];
+
+/// Validate the diagram as the UI will render it, so a bare body like
+/// `A --> B` (which renders as a flowchart) isn't reported as broken.
+fn validate_mermaid(content: &str) -> Result<(), String> {
+    let trimmed = content.trim();
+    if trimmed.is_empty() {
+        return Err("Mermaid diagram is empty".to_string());
+    }
+    let rendered;
+    let content = if MERMAID_RENDERER_TYPES.iter().any(|t| trimmed.starts_with(t)) {
+        trimmed
+    } else {
+        rendered = format!("flowchart TD\n{}", trimmed);
+        &rendered
+    };
+
+    let mut lines = content.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with("%%"));
+    let mut first = lines.next().ok_or("Mermaid diagram is empty")?;
+    // Skip YAML front matter (`---` ... `---`)
+    if first == "---" {
+        lines.by_ref().find(|l| *l == "---");
+        first = lines.next().ok_or("Mermaid diagram has no content after front matter")?;
+    }
+    let keyword = first.split_whitespace().next().unwrap_or_default();
+    if MERMAID_DIAGRAM_TYPES.contains(&keyword) {
+        Ok(())
+    } else {
+        Err(format!("Mermaid diagram must start with a diagram type (e.g. \"graph TD\"), found \"{}\"", keyword))
+    }
+}
+
 /// Strip artifact tags from content for display.
 pub fn strip_artifact_tags(content: &str) -> String {
     STRIP_ARTIFACT_RE.replace_all(content, "").trim().to_string()
 }
 
+/// Turn `[Wk]` web markers into `[num_local + k]`, so web sources number on
+/// from the local ones. Markers past `num_web` are dropped.
+///
+/// Providers that search on their own (Perplexity) may ignore `[Wk]` and
+/// number their results `[1]`, `[2]`, ... like local sources. When web
+/// sources came back but no `[Wk]` marker did, bare markers can't be told
+/// apart, so they are all dropped rather than credited to the wrong source.
+pub fn renumber_web_citations(response: &str, num_local: usize, num_web: usize) -> String {
+    if num_web > 0 && !WEB_CITATION_RE.is_match(response) {
+        return BARE_CITATION_RE.replace_all(response, "").into_owned();
+    }
+    WEB_CITATION_RE
+        .replace_all(response, |cap: &regex::Captures| match cap[1].parse::<usize>() {
+            Ok(k) if (1..=num_web).contains(&k) => format!("[{}]", num_local + k),
+            _ => String::new(),
+        })
+        .into_owned()
+}
+
 /// Validate citations — strip references to non-existent sources.
 pub fn validate_citations(response: &str, num_sources: usize) -> String {
     let mut result = response.to_string();
@@ -489,6 +939,213 @@ pub fn validate_citations(response: &str, num_sources: usize) -> String {
     result
 }
 
+/// Marker appended to uncited sentences by `enforce_grounding`
+pub const UNCITED_MARKER: &str = "⚠ uncited";
+
+/// Phrases that report missing information rather than claim a fact
+const NO_INFO_PHRASES: &[&str] = &[
+    "not mentioned",
+    "no information",
+    "not found in",
+    "could not find",
+    "couldn't find",
+    "do not contain",
+    "does not contain",
+    "don't contain",
+    "doesn't contain",
+    "not specified",
+];
+
+/// Scan `response` sentence by sentence and mark or strip factual sentences
+/// that carry no `[N]` citation. Headings, tables, code blocks, questions and
+/// short lead-ins are left alone. Returns the new text and the number of
+/// uncited sentences found. Run after `validate_citations` so citations to
+/// non-existent sources don't count.
+pub fn enforce_grounding(response: &str, action: UncitedAction) -> (String, usize) {
+    let mut out = Vec::new();
+    let mut uncited = 0;
+    let mut in_code = false;
+
+    for line in response.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+        }
+        if in_code || trimmed.starts_with("```") || trimmed.starts_with('#') || trimmed.starts_with('|') {
+            out.push(line.to_string());
+            continue;
+        }
+
+        let prefix_len = LIST_PREFIX_RE.find(line).map_or(0, |m| m.end());
+        let (prefix, body) = line.split_at(prefix_len);
+        let mut kept = String::new();
+        let mut changed = false;
+        for sentence in split_sentences(body) {
+            if is_cited(sentence) || !is_factual_claim(sentence) {
+                kept.push_str(sentence);
+                continue;
+            }
+            uncited += 1;
+            changed = true;
+            if action == UncitedAction::Mark {
+                let text = sentence.trim_end();
+                kept.push_str(text);
+                kept.push(' ');
+                kept.push_str(UNCITED_MARKER);
+                kept.push_str(&sentence[text.len()..]);
+            }
+        }
+
+        if !changed {
+            out.push(line.to_string());
+        } else if !kept.trim().is_empty() {
+            out.push(format!("{}{}", prefix, kept.trim_start()));
+        }
+    }
+
+    let mut content = out.join("\n");
+    if response.ends_with('\n') {
+        content.push('\n');
+    }
+    (content, uncited)
+}
+
+fn is_cited(sentence: &str) -> bool {
+    CITATION_RE.is_match(sentence) || MULTI_CITATION_RE.is_match(sentence)
+}
+
+/// Split a line after `.`, `!` or `?` followed by whitespace. Citations
+/// written after the punctuation ("... grew 10%. [2]") stay with their
+/// sentence. Concatenating the pieces gives back the line.
+fn split_sentences(line: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+        let mut end = i + 1;
+        while let Some(m) = LEADING_CITATION_RE.find(&line[end..]) {
+            end += m.end();
+        }
+        let at_boundary = line[end..].chars().next().is_none_or(char::is_whitespace);
+        if !at_boundary || (c == '.' && ends_with_abbreviation(&line[start..i])) {
+            continue;
+        }
+        pieces.push(&line[start..end]);
+        start = end;
+        while chars.peek().is_some_and(|&(j, _)| j < end) {
+            chars.next();
+        }
+    }
+    if start < line.len() {
+        pieces.push(&line[start..]);
+    }
+    pieces
+}
+
+fn ends_with_abbreviation(text: &str) -> bool {
+    let last = text.rsplit(|c: char| c.is_whitespace()).next().unwrap_or("");
+    matches!(
+        last.to_lowercase().as_str(),
+        "e.g" | "i.e" | "etc" | "vs" | "mr" | "mrs" | "dr" | "inc" | "ltd" | "no" | "fig" | "approx"
+    )
+}
+
+fn is_factual_claim(sentence: &str) -> bool {
+    let text = sentence.trim().trim_matches(|c: char| c == '*' || c == '_').trim();
+    if text.ends_with('?') || text.ends_with(':') {
+        return false;
+    }
+    if text.split_whitespace().count() < 5 {
+        return false;
+    }
+    let lower = text.to_lowercase();
+    !NO_INFO_PHRASES.iter().any(|p| lower.contains(p))
+}
+
+/// Merge citations that point at the same document (same source, or same
+/// title when the source is empty). Page numbers are combined into a range
+/// list such as "pp. 3, 7–9" and the best score and its snippet are kept.
+/// The merged list is ordered by best score.
+pub fn merge_citations(citations: Vec<Citation>) -> Vec<Citation> {
+    let mut merged: Vec<(Citation, BTreeSet<u32>)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for citation in citations {
+        let key = if citation.source.is_empty() {
+            citation.title.to_lowercase()
+        } else {
+            citation.source.replace('\\', "/")
+        };
+        let pages = citation.page_numbers.as_deref().map(parse_pages).unwrap_or_default();
+
+        match index.get(&key) {
+            Some(&i) => {
+                let (best, all_pages) = &mut merged[i];
+                all_pages.extend(pages);
+                if citation.score > best.score {
+                    best.score = citation.score;
+                    best.snippet = citation.snippet;
+                }
+            }
+            None => {
+                index.insert(key, merged.len());
+                merged.push((citation, pages));
+            }
+        }
+    }
+
+    let mut merged: Vec<Citation> = merged
+        .into_iter()
+        .map(|(mut citation, pages)| {
+            if !pages.is_empty() {
+                citation.page_numbers = Some(format_pages(&pages));
+            }
+            citation
+        })
+        .collect();
+    merged.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    merged
+}
+
+/// Page numbers in strings like "3", "7-9" or "pp. 3, 7–9"
+fn parse_pages(pages: &str) -> BTreeSet<u32> {
+    let mut set = BTreeSet::new();
+    for cap in PAGE_RANGE_RE.captures_iter(pages) {
+        let Ok(start) = cap[1].parse::<u32>() else { continue };
+        let end = cap.get(2).and_then(|m| m.as_str().parse::<u32>().ok()).unwrap_or(start);
+        // Guard against garbage like "1-99999" expanding into a huge set
+        if end >= start && end - start <= 1000 {
+            set.extend(start..=end);
+        } else {
+            set.insert(start);
+        }
+    }
+    set
+}
+
+/// "p. 3" or "pp. 3, 7–9"
+fn format_pages(pages: &BTreeSet<u32>) -> String {
+    let mut runs: Vec<(u32, u32)> = Vec::new();
+    for &page in pages {
+        match runs.last_mut() {
+            Some((_, end)) if *end + 1 == page => *end = page,
+            _ => runs.push((page, page)),
+        }
+    }
+    let parts: Vec<String> = runs
+        .iter()
+        .map(|&(start, end)| {
+            if start == end { start.to_string() } else { format!("{}–{}", start, end) }
+        })
+        .collect();
+    let prefix = if pages.len() == 1 { "p." } else { "pp." };
+    format!("{} {}", prefix, parts.join(", "))
+}
+
 /// Force bullet point formatting when LLM returns wall-of-text.
 /// Only triggers when the content has no structure (no headers, no bullets, no newlines).
 pub fn force_bullet_format(content: &str) -> String {
@@ -543,6 +1200,10 @@ pub async fn build_corpus_stats(
         date_from: None,
         date_to: None,
         custom: None,
+        author: None,
+        year_from: None,
+        year_to: None,
+        language: None,
     });
 
     let chunks = match rag.list_documents(filter, 100_000).await {
@@ -632,3 +1293,197 @@ pub async fn build_corpus_stats(
 pub fn estimate_tokens(text: &str) -> usize {
     (text.len() + 3) / 4
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn citation(source: &str, pages: Option<&str>, score: f32) -> Citation {
+        Citation {
+            title: source.trim_end_matches(".pdf").to_string(),
+            snippet: format!("{} @ {}", source, score),
+            score,
+            url: None,
+            authors: Vec::new(),
+            source: source.to_string(),
+            year: String::new(),
+            page_numbers: pages.map(str::to_string),
+            origin: CitationOrigin::Local,
+        }
+    }
+
+    #[test]
+    fn test_renumber_web_citations() {
+        let response = "Local fact [2]. Web fact [W1]. Another [W2][W3].";
+        assert_eq!(
+            renumber_web_citations(response, 3, 2),
+            "Local fact [2]. Web fact [4]. Another [5]."
+        );
+
+        // Native provider numbering is ambiguous without any `[Wk]`
+        assert_eq!(renumber_web_citations("Fact [1]. More [2].", 3, 2), "Fact. More.");
+        assert_eq!(renumber_web_citations("Fact [1].", 3, 0), "Fact [1].");
+    }
+
+    #[test]
+    fn test_merge_citations_combines_pages_and_keeps_best_score() {
+        let merged = merge_citations(vec![
+            citation("report.pdf", Some("7"), 0.4),
+            citation("memo.pdf", None, 0.6),
+            citation("report.pdf", Some("3"), 0.9),
+            citation("report.pdf", Some("8-9"), 0.2),
+        ]);
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].source, "report.pdf");
+        assert_eq!(merged[0].page_numbers.as_deref(), Some("pp. 3, 7–9"));
+        assert_eq!(merged[0].score, 0.9);
+        assert_eq!(merged[0].snippet, "report.pdf @ 0.9");
+        assert_eq!(merged[1].source, "memo.pdf");
+        assert_eq!(merged[1].page_numbers, None);
+    }
+
+    #[test]
+    fn test_enforce_grounding_marks_uncited_claims() {
+        let response = "## Revenue\n\
+            - Revenue grew 12% to $4.1M in Q3 [1]. The board approved a new plan for 2025.\n\
+            - Operating costs fell by 3% year over year.[2]\n\
+            - Headcount rose from 40 to 52 people across both offices.\n\
+            The documents do not contain figures for the prior year.\n\
+            Want a breakdown by region?\n";
+
+        let (marked, uncited) = enforce_grounding(response, UncitedAction::Mark);
+        assert_eq!(uncited, 2);
+        assert_eq!(
+            marked,
+            "## Revenue\n\
+             - Revenue grew 12% to $4.1M in Q3 [1]. The board approved a new plan for 2025. ⚠ uncited\n\
+             - Operating costs fell by 3% year over year.[2]\n\
+             - Headcount rose from 40 to 52 people across both offices. ⚠ uncited\n\
+             The documents do not contain figures for the prior year.\n\
+             Want a breakdown by region?\n"
+        );
+
+        let (stripped, uncited) = enforce_grounding(response, UncitedAction::Strip);
+        assert_eq!(uncited, 2);
+        assert_eq!(
+            stripped,
+            "## Revenue\n\
+             - Revenue grew 12% to $4.1M in Q3 [1].\n\
+             - Operating costs fell by 3% year over year.[2]\n\
+             The documents do not contain figures for the prior year.\n\
+             Want a breakdown by region?\n"
+        );
+    }
+
+    #[test]
+    fn test_enforce_grounding_skips_code_and_grouped_citations() {
+        let response = "Both reports describe the same rollout schedule [1, 3].\n\
+            ```\nlet total = revenue - costs; // five words or more here.\n```";
+        let (content, uncited) = enforce_grounding(response, UncitedAction::Mark);
+        assert_eq!(uncited, 0);
+        assert_eq!(content, response);
+    }
+
+    fn single_chart(response: &str) -> Artifact {
+        let (artifacts, _) = extract_artifacts(response);
+        assert_eq!(artifacts.len(), 1);
+        artifacts.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn test_chart_json_repaired() {
+        let cases = [
+            // Trailing commas
+            r#"{"type": "bar", "title": "Sales", "data": {"labels": ["N", "S",], "datasets": [{"label": "Q1", "data": [1, 2,]},]},}"#,
+            // Single quotes, with an apostrophe and a double quote inside
+            r#"{'type': 'bar', 'title': 'Sales', 'data': {'labels': ['Bob\'s', 'say "hi"'], 'datasets': [{'label': 'Q1', 'data': [1, 2]}]}}"#,
+            // Both, across lines
+            "{\n  'type': 'bar',\n  'title': 'Sales',\n  'data': {\n    'labels': ['N', 'S'],\n    'datasets': [{'label': 'Q1', 'data': [1, 2],}],\n  },\n}",
+        ];
+        for case in cases {
+            let artifact = single_chart(&format!("```chart\n{}\n```", case));
+            assert_eq!(artifact.artifact_type, ArtifactType::Chart, "{}", case);
+            assert_eq!(artifact.title, "Sales");
+            let value: serde_json::Value = serde_json::from_str(&artifact.content).unwrap();
+            assert_eq!(value["data"]["datasets"][0]["data"], serde_json::json!([1, 2]));
+        }
+
+        let quoted = single_chart(&format!("```chart\n{}\n```", cases[1]));
+        let value: serde_json::Value = serde_json::from_str(&quoted.content).unwrap();
+        assert_eq!(value["data"]["labels"], serde_json::json!(["Bob's", "say \"hi\""]));
+    }
+
+    #[test]
+    fn test_valid_chart_content_kept_verbatim() {
+        let json = r#"{"type": "pie", "title": "Mix", "data": {"labels": ["a"], "datasets": [{"label": "x", "data": [1]}]}}"#;
+        let artifact = single_chart(&format!("```chart\n{}\n```", json));
+        assert_eq!(artifact.artifact_type, ArtifactType::Chart);
+        assert_eq!(artifact.content, json);
+    }
+
+    #[test]
+    fn test_broken_chart_downgraded_to_json_code() {
+        let unparseable = single_chart("```chart\n{\"type\": \"bar\", \"data\": {\n```");
+        assert_eq!(unparseable.artifact_type, ArtifactType::Code);
+        assert_eq!(unparseable.language.as_deref(), Some("json"));
+        assert!(unparseable.title.starts_with("Chart 1 (invalid chart:"));
+
+        let no_datasets = single_chart(
+            "```chart\n{\"type\": \"bar\", \"title\": \"T\", \"data\": {\"labels\": []}}\n```",
+        );
+        assert_eq!(no_datasets.artifact_type, ArtifactType::Code);
+        assert!(no_datasets.title.contains("data.datasets"));
+
+        let bad_type = single_chart(
+            "```chart\n{\"type\": \"sankey\", \"data\": {\"labels\": [], \"datasets\": [{\"data\": []}]}}\n```",
+        );
+        assert!(bad_type.title.contains("unsupported chart type"));
+    }
+
+    #[test]
+    fn test_svg_and_mermaid_validation() {
+        let response = "<artifact id=\"logo\" type=\"svg\" title=\"Logo\">\n<svg xmlns=\"http://www.w3.org/2000/svg\"><circle r=\"4\"/></svg>\n</artifact>\n\
+            <artifact id=\"broken\" type=\"svg\" title=\"Broken\">\n<svg><g></svg>\n</artifact>\n\
+            <artifact id=\"html\" type=\"svg\" title=\"Wrong root\">\n<div>hi</div>\n</artifact>\n\
+            <artifact id=\"flow\" type=\"mermaid\" title=\"Flow\">\n%% comment\ngraph TD\n  A --> B\n</artifact>\n\
+            <artifact id=\"bare\" type=\"mermaid\" title=\"Bare\">\nA --> B\n</artifact>";
+        let (artifacts, _) = extract_artifacts(response);
+        let validity: Vec<(&str, bool)> = artifacts.iter().map(|a| (a.id.as_str(), a.valid)).collect();
+        assert_eq!(
+            validity,
+            [("logo", true), ("broken", false), ("html", false), ("flow", true), ("bare", true)]
+        );
+        assert!(artifacts[1].metadata["validation_error"].contains("well-formed"));
+        assert!(artifacts[2].metadata["validation_error"].contains("<div>"));
+        assert!(artifacts[0].metadata.is_empty());
+        assert!(artifacts[4].metadata.is_empty());
+        assert!(validate_mermaid(" \n ").is_err());
+    }
+
+    #[test]
+    fn test_fenced_context_tables_become_table_artifacts() {
+        let chunk = "Revenue by region.\n| Region | Q1 |\n| --- | --- |\n| North | 1,200 |\nFigures are unaudited.";
+        let fenced = fence_markdown_tables(chunk);
+        assert_eq!(
+            fenced,
+            "Revenue by region.\n```table\n| Region | Q1 |\n| --- | --- |\n| North | 1,200 |\n```\nFigures are unaudited."
+        );
+        // Already-fenced tables are left alone
+        assert_eq!(fence_markdown_tables(&fenced), fenced);
+
+        let (artifacts, _) = extract_artifacts(&fenced);
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].artifact_type, ArtifactType::Table);
+        assert_eq!(artifacts[0].title, "Region / Q1");
+    }
+
+    #[test]
+    fn test_merge_citations_single_page() {
+        let merged = merge_citations(vec![
+            citation("a.pdf", Some("p. 5"), 0.5),
+            citation("a.pdf", Some("5"), 0.3),
+        ]);
+        assert_eq!(merged[0].page_numbers.as_deref(), Some("p. 5"));
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+use crate::processing::ChunkStrategy;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RAGConfig {
     pub data_dir: PathBuf,
@@ -8,6 +10,29 @@ pub struct RAGConfig {
     pub chunking: ChunkingConfig,
     pub search: SearchConfig,
     pub features: FeatureFlags,
+    #[serde(default)]
+    pub cache: QueryCacheConfig,
+}
+
+/// Cache of ranked search results, keyed by query, space and filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryCacheConfig {
+    /// false always runs the full pipeline
+    pub enabled: bool,
+    /// Maximum cached searches
+    pub capacity: usize,
+    /// Seconds a cached result stays fresh
+    pub ttl_secs: u64,
+}
+
+impl Default for QueryCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            capacity: 256,
+            ttl_secs: 120,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +41,21 @@ pub struct EmbeddingConfig {
     pub dimension: usize,
     pub use_e5: bool,
     pub cache_size: usize,
+    /// Documents per inference call when embedding during indexing
+    #[serde(default = "default_embedding_batch_size")]
+    pub batch_size: usize,
+    /// L2-normalize embeddings. Stored vectors and query vectors must agree,
+    /// so changing this requires reindexing.
+    #[serde(default = "default_true")]
+    pub normalize: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_embedding_batch_size() -> usize {
+    32
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +63,8 @@ pub struct ChunkingConfig {
     pub chunk_size: usize,
     pub chunk_overlap: usize,
     pub min_chunk_size: usize,
+    #[serde(default)]
+    pub strategy: ChunkStrategy,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,9 +73,73 @@ pub struct SearchConfig {
     pub candidate_multiplier: usize,
     pub min_score_threshold: f32,
     pub hybrid_alpha: f32,
+    /// RRF rank constant; larger values flatten the gap between top and lower ranks
     pub rrf_k: usize,
     /// Weight for original similarity scores in RRF fusion (0.0 = pure RRF, higher = more score influence)
     pub score_weight: f32,
+    /// Multiplier on the vector list's RRF contribution. 1.0 with `text_weight`
+    /// 1.0 is the original unweighted fusion.
+    #[serde(default = "default_fusion_weight")]
+    pub vector_weight: f32,
+    /// Multiplier on the full-text list's RRF contribution
+    #[serde(default = "default_fusion_weight")]
+    pub text_weight: f32,
+    /// Rescore full-text matches with BM25 using `bm25_k1`/`bm25_b`.
+    /// false keeps Tantivy's own scores.
+    #[serde(default = "default_true")]
+    pub bm25_enabled: bool,
+    /// BM25 term frequency saturation
+    #[serde(default = "default_bm25_k1")]
+    pub bm25_k1: f32,
+    /// BM25 length normalization
+    #[serde(default = "default_bm25_b")]
+    pub bm25_b: f32,
+}
+
+fn default_fusion_weight() -> f32 {
+    1.0
+}
+
+fn default_bm25_k1() -> f32 {
+    1.2
+}
+
+fn default_bm25_b() -> f32 {
+    0.75
+}
+
+impl SearchConfig {
+    /// Validate the retrieval tuning knobs
+    pub fn validate(&self) -> Result<(), String> {
+        if self.default_k == 0 {
+            return Err("search.default_k must be > 0".into());
+        }
+        if self.candidate_multiplier == 0 {
+            return Err("search.candidate_multiplier must be > 0".into());
+        }
+        if !(0.0..=1.0).contains(&self.min_score_threshold) {
+            return Err("search.min_score_threshold must be in [0.0, 1.0]".into());
+        }
+        if self.rrf_k == 0 {
+            return Err("search.rrf_k must be > 0".into());
+        }
+        if !(0.0..=2.0).contains(&self.vector_weight) || !(0.0..=2.0).contains(&self.text_weight) {
+            return Err("search.vector_weight and search.text_weight must be in [0.0, 2.0]".into());
+        }
+        if self.vector_weight + self.text_weight < 0.1 {
+            return Err("search.vector_weight + search.text_weight must be >= 0.1".into());
+        }
+        if !(0.0..=5.0).contains(&self.score_weight) {
+            return Err("search.score_weight must be in [0.0, 5.0]".into());
+        }
+        if !(0.0..=5.0).contains(&self.bm25_k1) {
+            return Err("search.bm25_k1 must be in [0.0, 5.0]".into());
+        }
+        if !(0.0..=1.0).contains(&self.bm25_b) {
+            return Err("search.bm25_b must be in [0.0, 1.0]".into());
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +147,10 @@ pub struct FeatureFlags {
     pub enable_reranking: bool,
     pub enable_knowledge_graph: bool,
     pub enable_cross_encoder: bool,
+    /// Rescore the top candidates with token-level MaxSim (late interaction).
+    /// Token vectors are computed at query time, so no reindexing is needed.
+    #[serde(default)]
+    pub enable_maxsim_rerank: bool,
 }
 
 impl RAGConfig {
@@ -49,22 +159,16 @@ impl RAGConfig {
         if self.embedding.dimension == 0 {
             return Err("embedding.dimension must be > 0".into());
         }
+        if self.embedding.batch_size == 0 {
+            return Err("embedding.batch_size must be > 0".into());
+        }
         if self.chunking.chunk_size < 50 {
             return Err("chunking.chunk_size must be >= 50".into());
         }
         if self.chunking.chunk_overlap >= self.chunking.chunk_size {
             return Err("chunking.chunk_overlap must be < chunk_size".into());
         }
-        if self.search.default_k == 0 {
-            return Err("search.default_k must be > 0".into());
-        }
-        if self.search.candidate_multiplier == 0 {
-            return Err("search.candidate_multiplier must be > 0".into());
-        }
-        if !(0.0..=1.0).contains(&self.search.min_score_threshold) {
-            return Err("search.min_score_threshold must be in [0.0, 1.0]".into());
-        }
-        Ok(())
+        self.search.validate()
     }
 
     /// Load config from a JSON file, falling back to defaults for missing fields.
@@ -102,11 +206,14 @@ impl Default for RAGConfig {
                 dimension,
                 use_e5: e5_available,
                 cache_size: 1000,
+                batch_size: default_embedding_batch_size(),
+                normalize: true,
             },
             chunking: ChunkingConfig {
                 chunk_size: 1750,
                 chunk_overlap: 200,
                 min_chunk_size: 100,
+                strategy: ChunkStrategy::FixedSize,
             },
             search: SearchConfig {
                 default_k: 10,
@@ -115,12 +222,19 @@ impl Default for RAGConfig {
                 hybrid_alpha: 0.7,
                 rrf_k: 60,
                 score_weight: 0.3,
+                vector_weight: default_fusion_weight(),
+                text_weight: default_fusion_weight(),
+                bm25_enabled: true,
+                bm25_k1: default_bm25_k1(),
+                bm25_b: default_bm25_b(),
             },
             features: FeatureFlags {
                 enable_reranking: true,
                 enable_knowledge_graph: false,
                 enable_cross_encoder: true,
+                enable_maxsim_rerank: false,
             },
+            cache: QueryCacheConfig::default(),
         }
     }
 }
@@ -40,7 +40,7 @@ pub async fn track_search(
         outcomes: results.iter().take(3).cloned().collect(),
     };
 
-    mem.record(experience)
+    mem.record(experience).await
         .map(|_| ())
         .map_err(|e| format!("Failed to record search: {}", e))
 }
@@ -69,7 +69,7 @@ pub async fn track_document_view(
         outcomes: Vec::new(),
     };
 
-    mem.record(experience)
+    mem.record(experience).await
         .map(|_| ())
         .map_err(|e| format!("Failed to record document view: {}", e))
 }
@@ -100,7 +100,7 @@ pub async fn track_search_refinement(
         outcomes: Vec::new(),
     };
 
-    mem.record(experience)
+    mem.record(experience).await
         .map(|_| ())
         .map_err(|e| format!("Failed to record refinement: {}", e))
 }
@@ -130,7 +130,7 @@ pub async fn track_filter(
         outcomes: Vec::new(),
     };
 
-    mem.record(experience)
+    mem.record(experience).await
         .map(|_| ())
         .map_err(|e| format!("Failed to record filter: {}", e))
 }
@@ -161,7 +161,7 @@ pub async fn track_task_start(
         outcomes: Vec::new(),
     };
 
-    mem.record(experience)
+    mem.record(experience).await
         .map(|_| ())
         .map_err(|e| format!("Failed to record task: {}", e))
 }
@@ -230,6 +230,8 @@ pub async fn build_llm_context(
             query_text: None,
             query_embedding: None,
             retrieval_mode: RetrievalMode::Temporal,
+            causal_seed: None,
+            causal_depth: 0,
             max_results: 5,
             importance_threshold: Some(0.6),
             time_range: Some((
@@ -298,6 +300,8 @@ pub async fn search_memory(
         query_text: Some(query.to_string()),
         query_embedding: None,
         retrieval_mode: RetrievalMode::Hybrid,
+        causal_seed: None,
+        causal_depth: 0,
         max_results,
         time_range: None,
         importance_threshold: None,
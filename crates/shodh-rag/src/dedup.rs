@@ -0,0 +1,118 @@
+//! Duplicate document detection across spaces, keyed by the content hash
+//! stored at index time.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// One indexed document, summarised from its chunks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentFingerprint {
+    pub doc_id: String,
+    pub title: String,
+    pub source: String,
+    pub space_id: String,
+    pub content_hash: String,
+    pub chunk_count: usize,
+    /// Stored chunk text, in bytes
+    pub text_bytes: u64,
+    /// Unix seconds the document was indexed
+    pub created_at: i64,
+}
+
+/// Documents sharing a content hash, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateCluster {
+    pub content_hash: String,
+    pub documents: Vec<DocumentFingerprint>,
+    pub space_ids: Vec<String>,
+    /// Bytes freed by keeping only one copy
+    pub reclaimable_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateReport {
+    pub clusters: Vec<DuplicateCluster>,
+    /// Documents beyond the first in each cluster
+    pub duplicate_documents: usize,
+    pub total_reclaimable_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DedupeOutcome {
+    pub removed_documents: usize,
+    pub removed_chunks: usize,
+    pub reclaimed_bytes: u64,
+}
+
+/// Group documents by content hash. Documents without a hash are ignored.
+/// Clusters are ordered by reclaimable bytes, largest first.
+pub fn group_duplicates(documents: Vec<DocumentFingerprint>) -> DuplicateReport {
+    let mut by_hash: HashMap<String, Vec<DocumentFingerprint>> = HashMap::new();
+    for doc in documents.into_iter().filter(|d| !d.content_hash.is_empty()) {
+        by_hash.entry(doc.content_hash.clone()).or_default().push(doc);
+    }
+
+    let mut clusters: Vec<DuplicateCluster> = by_hash
+        .into_iter()
+        .filter(|(_, docs)| docs.len() > 1)
+        .map(|(content_hash, mut documents)| {
+            documents.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.doc_id.cmp(&b.doc_id)));
+            let reclaimable_bytes = documents.iter().skip(1).map(|d| d.text_bytes).sum();
+            let mut space_ids: Vec<String> = documents.iter().map(|d| d.space_id.clone()).collect();
+            space_ids.sort();
+            space_ids.dedup();
+            DuplicateCluster { content_hash, documents, space_ids, reclaimable_bytes }
+        })
+        .collect();
+    clusters.sort_by(|a, b| {
+        b.reclaimable_bytes.cmp(&a.reclaimable_bytes).then_with(|| a.content_hash.cmp(&b.content_hash))
+    });
+
+    DuplicateReport {
+        duplicate_documents: clusters.iter().map(|c| c.documents.len() - 1).sum(),
+        total_reclaimable_bytes: clusters.iter().map(|c| c.reclaimable_bytes).sum(),
+        clusters,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(id: &str, space: &str, hash: &str, bytes: u64, created_at: i64) -> DocumentFingerprint {
+        DocumentFingerprint {
+            doc_id: id.to_string(),
+            title: id.to_string(),
+            source: format!("/docs/{}.pdf", id),
+            space_id: space.to_string(),
+            content_hash: hash.to_string(),
+            chunk_count: 1,
+            text_bytes: bytes,
+            created_at,
+        }
+    }
+
+    #[test]
+    fn test_group_duplicates() {
+        let report = group_duplicates(vec![
+            doc("a", "work", "h1", 100, 3),
+            doc("b", "personal", "h1", 100, 1),
+            doc("c", "work", "h1", 100, 2),
+            doc("d", "work", "h2", 50, 1),
+            doc("e", "work", "", 70, 1),
+            doc("f", "legal", "", 70, 1),
+        ]);
+
+        assert_eq!(report.clusters.len(), 1);
+        let cluster = &report.clusters[0];
+        let ids: Vec<&str> = cluster.documents.iter().map(|d| d.doc_id.as_str()).collect();
+        assert_eq!(ids, vec!["b", "c", "a"]);
+        assert_eq!(cluster.space_ids, vec!["personal", "work"]);
+        assert_eq!(report.duplicate_documents, 2);
+        assert_eq!(report.total_reclaimable_bytes, 200);
+    }
+}
@@ -0,0 +1,389 @@
+//! Section-aware comparison of two documents
+//!
+//! Documents are split into sections at markdown headings, sections are paired
+//! by title (falling back to content similarity for renamed ones), and each
+//! pair is diffed sentence by sentence. Prose rarely keeps its line breaks
+//! between revisions, so lines are the wrong unit here.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Unmatched sections at least this similar are treated as renamed
+const RENAME_THRESHOLD: f32 = 0.5;
+/// A removed and an added sentence at least this similar are shown as one edit
+const REPLACE_THRESHOLD: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SectionStatus {
+    Added,
+    Removed,
+    Changed,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum SentenceChange {
+    Equal { text: String },
+    Added { text: String },
+    Removed { text: String },
+    /// A sentence reworded in place
+    Replaced { left: String, right: String, similarity: f32 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionDiff {
+    /// Title on the right side, or the left one for removed sections
+    pub title: String,
+    /// Set when a section was renamed
+    pub left_title: Option<String>,
+    pub status: SectionStatus,
+    /// 0.0 (nothing shared) to 1.0 (identical), by word overlap of sentences
+    pub similarity: f32,
+    pub changes: Vec<SentenceChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentDiff {
+    pub sections: Vec<SectionDiff>,
+    /// Word-weighted similarity over the whole document
+    pub similarity: f32,
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Section {
+    title: String,
+    sentences: Vec<String>,
+}
+
+impl Section {
+    fn words(&self) -> usize {
+        self.sentences.iter().map(|s| word_count(s)).sum()
+    }
+}
+
+/// Compare `left` (the earlier or reference document) against `right`
+pub fn compare(left: &str, right: &str) -> DocumentDiff {
+    let left = split_sections(left);
+    let right = split_sections(right);
+    let pairs = pair_sections(&left, &right);
+
+    let mut sections = Vec::new();
+    let mut emitted_left = vec![false; left.len()];
+    let (mut shared_words, mut total_words) = (0.0f32, 0usize);
+
+    // Removed sections go where they sat on the left: ahead of the next
+    // paired section, and ahead of additions in the same gap
+    let emit_removed_before = |limit: usize, sections: &mut Vec<SectionDiff>, emitted: &mut [bool]| {
+        for i in 0..limit {
+            if !emitted[i] && !pairs.iter().any(|&(l, _)| l == i) {
+                emitted[i] = true;
+                sections.push(one_sided(&left[i], SectionStatus::Removed));
+            }
+        }
+    };
+
+    for (j, section) in right.iter().enumerate() {
+        match pairs.iter().find(|&&(_, r)| r == j) {
+            Some(&(i, _)) => {
+                emit_removed_before(i, &mut sections, &mut emitted_left);
+                emitted_left[i] = true;
+                let diff = diff_section(&left[i], section);
+                let words = left[i].words() + section.words();
+                shared_words += diff.similarity * words as f32;
+                total_words += words;
+                sections.push(diff);
+            }
+            None => {
+                let next_pair = pairs.iter().filter(|&&(_, r)| r > j).min_by_key(|&&(_, r)| r);
+                emit_removed_before(next_pair.map_or(left.len(), |&(i, _)| i), &mut sections, &mut emitted_left);
+                total_words += section.words();
+                sections.push(one_sided(section, SectionStatus::Added));
+            }
+        }
+    }
+    emit_removed_before(left.len(), &mut sections, &mut emitted_left);
+    total_words += left
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !pairs.iter().any(|&(l, _)| l == *i))
+        .map(|(_, s)| s.words())
+        .sum::<usize>();
+
+    let count = |status| sections.iter().filter(|s| s.status == status).count();
+    DocumentDiff {
+        similarity: if total_words == 0 { 1.0 } else { shared_words / total_words as f32 },
+        added: count(SectionStatus::Added),
+        removed: count(SectionStatus::Removed),
+        changed: count(SectionStatus::Changed),
+        unchanged: count(SectionStatus::Unchanged),
+        sections,
+    }
+}
+
+/// Split at markdown headings outside code fences. Text before the first
+/// heading becomes an untitled section; a document without headings is one
+/// section.
+fn split_sections(text: &str) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let mut title = String::new();
+    let mut body = String::new();
+    let mut in_fence = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+        }
+        let heading = (!in_fence)
+            .then(|| trimmed.trim_start_matches('#'))
+            .filter(|rest| trimmed.starts_with('#') && rest.starts_with(' '));
+        match heading {
+            Some(rest) => {
+                push_section(&mut sections, std::mem::take(&mut title), &body);
+                body.clear();
+                title = rest.trim().to_string();
+            }
+            None => {
+                body.push_str(line);
+                body.push('\n');
+            }
+        }
+    }
+    push_section(&mut sections, title, &body);
+    sections
+}
+
+fn push_section(sections: &mut Vec<Section>, title: String, body: &str) {
+    let sentences = split_sentences(body);
+    if !title.is_empty() || !sentences.is_empty() {
+        sections.push(Section { title, sentences });
+    }
+}
+
+/// Sentences of a section body. List items, table rows and code lines are
+/// kept whole; paragraphs are joined and split after `.`, `!` or `?`.
+fn split_sentences(body: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    for block in body.split("\n\n") {
+        let lines: Vec<&str> = block.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+        if lines.is_empty() {
+            continue;
+        }
+        let structured = lines.iter().any(|l| {
+            l.starts_with(['-', '*', '|', '>', '`'])
+                || l.split_once(". ").is_some_and(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        });
+        if structured {
+            sentences.extend(lines.iter().map(|l| l.to_string()));
+            continue;
+        }
+
+        let paragraph = lines.join(" ");
+        let mut current = String::new();
+        let mut chars = paragraph.chars().peekable();
+        while let Some(c) = chars.next() {
+            current.push(c);
+            if matches!(c, '.' | '!' | '?') && chars.peek().is_some_and(|n| n.is_whitespace()) {
+                sentences.push(current.trim().to_string());
+                current.clear();
+            }
+        }
+        if !current.trim().is_empty() {
+            sentences.push(current.trim().to_string());
+        }
+    }
+    sentences
+}
+
+/// Pair left and right section indices: same title first, then the most
+/// similar remaining sections above `RENAME_THRESHOLD`
+fn pair_sections(left: &[Section], right: &[Section]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    let mut used_left = HashSet::new();
+    let mut used_right = HashSet::new();
+
+    for (j, r) in right.iter().enumerate() {
+        let key = normalize(&r.title);
+        if let Some(i) = (0..left.len()).find(|i| !used_left.contains(i) && normalize(&left[*i].title) == key) {
+            used_left.insert(i);
+            used_right.insert(j);
+            pairs.push((i, j));
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for (i, l) in left.iter().enumerate().filter(|(i, _)| !used_left.contains(i)) {
+        for (j, r) in right.iter().enumerate().filter(|(j, _)| !used_right.contains(j)) {
+            let score = text_similarity(&l.sentences.join(" "), &r.sentences.join(" "));
+            if score >= RENAME_THRESHOLD {
+                candidates.push((score, i, j));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    for (_, i, j) in candidates {
+        if !used_left.contains(&i) && !used_right.contains(&j) {
+            used_left.insert(i);
+            used_right.insert(j);
+            pairs.push((i, j));
+        }
+    }
+    pairs
+}
+
+fn one_sided(section: &Section, status: SectionStatus) -> SectionDiff {
+    let changes = section
+        .sentences
+        .iter()
+        .map(|text| match status {
+            SectionStatus::Removed => SentenceChange::Removed { text: text.clone() },
+            _ => SentenceChange::Added { text: text.clone() },
+        })
+        .collect();
+    SectionDiff {
+        title: section.title.clone(),
+        left_title: None,
+        status,
+        similarity: 0.0,
+        changes,
+    }
+}
+
+fn diff_section(left: &Section, right: &Section) -> SectionDiff {
+    let changes = diff_sentences(&left.sentences, &right.sentences);
+
+    let (mut shared, mut total) = (0.0f32, 0usize);
+    for change in &changes {
+        match change {
+            SentenceChange::Equal { text } => {
+                shared += 2.0 * word_count(text) as f32;
+                total += 2 * word_count(text);
+            }
+            SentenceChange::Added { text } | SentenceChange::Removed { text } => total += word_count(text),
+            SentenceChange::Replaced { left, right, similarity } => {
+                let words = word_count(left) + word_count(right);
+                shared += similarity * words as f32;
+                total += words;
+            }
+        }
+    }
+    let similarity = if total == 0 { 1.0 } else { shared / total as f32 };
+    let renamed = normalize(&left.title) != normalize(&right.title);
+    let unchanged = !renamed && changes.iter().all(|c| matches!(c, SentenceChange::Equal { .. }));
+
+    SectionDiff {
+        title: right.title.clone(),
+        left_title: renamed.then(|| left.title.clone()),
+        status: if unchanged { SectionStatus::Unchanged } else { SectionStatus::Changed },
+        similarity,
+        changes,
+    }
+}
+
+/// LCS over normalized sentences; adjacent removed/added runs are zipped
+/// into `Replaced` where the sentences are close enough
+fn diff_sentences(left: &[String], right: &[String]) -> Vec<SentenceChange> {
+    let a: Vec<String> = left.iter().map(|s| normalize(s)).collect();
+    let b: Vec<String> = right.iter().map(|s| normalize(s)).collect();
+    let (n, m) = (a.len(), b.len());
+
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a[i] == b[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut removed, mut added): (Vec<&String>, Vec<&String>) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && a[i] == b[j] {
+            flush_run(&mut changes, &mut removed, &mut added);
+            changes.push(SentenceChange::Equal { text: right[j].clone() });
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            added.push(&right[j]);
+            j += 1;
+        } else {
+            removed.push(&left[i]);
+            i += 1;
+        }
+    }
+    flush_run(&mut changes, &mut removed, &mut added);
+    changes
+}
+
+fn flush_run(changes: &mut Vec<SentenceChange>, removed: &mut Vec<&String>, added: &mut Vec<&String>) {
+    let mut added_iter = added.drain(..).peekable();
+    for left in removed.drain(..) {
+        let similarity = added_iter.peek().map(|right| text_similarity(left, right)).unwrap_or(0.0);
+        if similarity >= REPLACE_THRESHOLD {
+            let right = added_iter.next().unwrap_or(left);
+            changes.push(SentenceChange::Replaced { left: left.clone(), right: right.clone(), similarity });
+        } else {
+            changes.push(SentenceChange::Removed { text: left.clone() });
+        }
+    }
+    changes.extend(added_iter.map(|text| SentenceChange::Added { text: text.clone() }));
+}
+
+/// Dice coefficient over lowercased word sets
+fn text_similarity(a: &str, b: &str) -> f32 {
+    let words = |s: &str| -> HashSet<String> { tokens(s).collect() };
+    let (a, b) = (words(a), words(b));
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    2.0 * a.intersection(&b).count() as f32 / (a.len() + b.len()) as f32
+}
+
+fn tokens(s: &str) -> impl Iterator<Item = String> + '_ {
+    s.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).map(str::to_lowercase)
+}
+
+fn word_count(s: &str) -> usize {
+    tokens(s).count().max(1)
+}
+
+fn normalize(s: &str) -> String {
+    tokens(s).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_reports_section_status_and_sentence_edits() {
+        let left = "# Report\n\n## Summary\nRevenue grew in Q3. Costs were flat.\n\n\
+                    ## Risks\nSupply chain delays remain.\n\n## Legacy\nThis section goes away entirely.";
+        let right = "# Report\n\n## Summary\nRevenue grew strongly in Q3. Costs were flat. Margins improved.\n\n\
+                     ## Risks\nSupply chain delays remain.\n\n## Outlook\nWe expect steady demand next year.";
+
+        let diff = compare(left, right);
+        let status = |title: &str| diff.sections.iter().find(|s| s.title == title).map(|s| s.status);
+        assert_eq!(status("Summary"), Some(SectionStatus::Changed));
+        assert_eq!(status("Risks"), Some(SectionStatus::Unchanged));
+        assert_eq!(status("Legacy"), Some(SectionStatus::Removed));
+        assert_eq!(status("Outlook"), Some(SectionStatus::Added));
+
+        let summary = diff.sections.iter().find(|s| s.title == "Summary").unwrap();
+        assert!(matches!(&summary.changes[0], SentenceChange::Replaced { right, .. } if right.contains("strongly")));
+        assert!(matches!(&summary.changes[1], SentenceChange::Equal { .. }));
+        assert!(matches!(&summary.changes[2], SentenceChange::Added { text } if text == "Margins improved."));
+        assert!(summary.similarity > 0.6 && summary.similarity < 1.0);
+        assert!(diff.similarity > 0.0 && diff.similarity < 1.0);
+
+        // Removed sections keep their place, ahead of additions in the same gap
+        let order: Vec<&str> = diff.sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(order, vec!["Report", "Summary", "Risks", "Legacy", "Outlook"]);
+    }
+}
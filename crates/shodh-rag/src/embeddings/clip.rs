@@ -0,0 +1,154 @@
+//! CLIP image/text embeddings for visual image search
+//!
+//! Expects an ONNX export with projection heads (`vision_model.onnx` ->
+//! `image_embeds`, `text_model.onnx` -> `text_embeds`) and the CLIP
+//! `tokenizer.json` in one directory, e.g. `models/clip-vit-base-patch32`.
+//! Images and text land in the same space, so a text query can be scored
+//! directly against image vectors.
+
+use anyhow::{anyhow, Result};
+use image::imageops::FilterType;
+use image::DynamicImage;
+use ort::session::builder::GraphOptimizationLevel;
+use ort::session::Session;
+use ort::value::Value;
+use parking_lot::Mutex;
+use std::path::Path;
+use std::sync::Arc;
+
+use super::l2_normalize;
+
+/// Directory name looked up under the model dir
+pub const CLIP_MODEL_DIR: &str = "clip-vit-base-patch32";
+
+const IMAGE_SIZE: u32 = 224;
+/// CLIP's text context length
+const MAX_TEXT_TOKENS: usize = 77;
+const MEAN: [f32; 3] = [0.481_454_66, 0.457_827_5, 0.408_210_73];
+const STD: [f32; 3] = [0.268_629_54, 0.261_302_6, 0.275_777_1];
+
+#[derive(Clone)]
+pub struct ClipEmbeddings {
+    vision: Arc<Mutex<Session>>,
+    text: Arc<Mutex<Session>>,
+    tokenizer: Arc<tokenizers::Tokenizer>,
+}
+
+fn load_session(path: &Path) -> Result<Session> {
+    if !path.exists() {
+        return Err(anyhow!("CLIP model not found at: {}", path.display()));
+    }
+    let model_bytes = std::fs::read(path)?;
+    Session::builder()
+        .map_err(|e| anyhow!("Session builder: {:?}", e))?
+        .with_optimization_level(GraphOptimizationLevel::Level3)
+        .map_err(|e| anyhow!("Opt level: {:?}", e))?
+        .commit_from_memory(&model_bytes)
+        .map_err(|e| anyhow!("Failed to load {}: {:?}", path.display(), e))
+}
+
+impl ClipEmbeddings {
+    pub fn new(model_dir: &Path) -> Result<Self> {
+        let tokenizer_path = model_dir.join("tokenizer.json");
+        if !tokenizer_path.exists() {
+            return Err(anyhow!("Tokenizer not found at: {}", tokenizer_path.display()));
+        }
+        let tokenizer = tokenizers::Tokenizer::from_file(&tokenizer_path)
+            .map_err(|e| anyhow!("Failed to load tokenizer: {:?}", e))?;
+
+        Ok(Self {
+            vision: Arc::new(Mutex::new(load_session(&model_dir.join("vision_model.onnx"))?)),
+            text: Arc::new(Mutex::new(load_session(&model_dir.join("text_model.onnx"))?)),
+            tokenizer: Arc::new(tokenizer),
+        })
+    }
+
+    /// Unit-length embedding of an encoded image (PNG, JPEG, ...)
+    pub fn embed_image(&self, image_bytes: &[u8]) -> Result<Vec<f32>> {
+        let image = image::load_from_memory(image_bytes)
+            .map_err(|e| anyhow!("Failed to decode image: {}", e))?;
+        let pixels = preprocess(&image);
+        let size = IMAGE_SIZE as usize;
+        let pixel_values = Value::from_array((vec![1, 3, size, size], pixels))
+            .map_err(|e| anyhow!("pixel_values tensor: {:?}", e))?;
+
+        let mut session = self.vision.lock();
+        let outputs = session
+            .run(ort::inputs!["pixel_values" => pixel_values])
+            .map_err(|e| anyhow!("CLIP vision inference failed: {:?}", e))?;
+        let (_shape, data) = outputs["image_embeds"]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| anyhow!("Failed to extract image_embeds: {:?}", e))?;
+        Ok(l2_normalize(data.to_vec()))
+    }
+
+    /// Unit-length embedding of a text query, comparable with `embed_image`
+    pub fn embed_text(&self, text: &str) -> Result<Vec<f32>> {
+        let encoding = self
+            .tokenizer
+            .encode(text, true)
+            .map_err(|e| anyhow!("Tokenization failed: {:?}", e))?;
+        let len = encoding.get_ids().len().min(MAX_TEXT_TOKENS);
+        let ids: Vec<i64> = encoding.get_ids()[..len].iter().map(|&id| id as i64).collect();
+        let mask: Vec<i64> = encoding.get_attention_mask()[..len].iter().map(|&m| m as i64).collect();
+
+        let shape = vec![1, len];
+        let input_ids = Value::from_array((shape.clone(), ids))
+            .map_err(|e| anyhow!("input_ids: {:?}", e))?;
+        let attention_mask = Value::from_array((shape, mask))
+            .map_err(|e| anyhow!("attention_mask: {:?}", e))?;
+
+        let mut session = self.text.lock();
+        let outputs = session
+            .run(ort::inputs![
+                "input_ids" => input_ids,
+                "attention_mask" => attention_mask,
+            ])
+            .map_err(|e| anyhow!("CLIP text inference failed: {:?}", e))?;
+        let (_shape, data) = outputs["text_embeds"]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| anyhow!("Failed to extract text_embeds: {:?}", e))?;
+        Ok(l2_normalize(data.to_vec()))
+    }
+}
+
+/// CLIP preprocessing: resize the short side to 224 (bicubic), center-crop
+/// 224x224, scale to [0, 1] and normalize per channel. Returns CHW floats.
+fn preprocess(image: &DynamicImage) -> Vec<f32> {
+    let (width, height) = (image.width().max(1), image.height().max(1));
+    let scale = IMAGE_SIZE as f32 / width.min(height) as f32;
+    let resized_w = ((width as f32 * scale).round() as u32).max(IMAGE_SIZE);
+    let resized_h = ((height as f32 * scale).round() as u32).max(IMAGE_SIZE);
+    let rgb = image
+        .resize_exact(resized_w, resized_h, FilterType::CatmullRom)
+        .crop_imm((resized_w - IMAGE_SIZE) / 2, (resized_h - IMAGE_SIZE) / 2, IMAGE_SIZE, IMAGE_SIZE)
+        .to_rgb8();
+
+    let plane = (IMAGE_SIZE * IMAGE_SIZE) as usize;
+    let mut pixels = vec![0.0f32; 3 * plane];
+    for (i, pixel) in rgb.pixels().enumerate() {
+        for channel in 0..3 {
+            pixels[channel * plane + i] = (pixel[channel] as f32 / 255.0 - MEAN[channel]) / STD[channel];
+        }
+    }
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn test_preprocess_crops_and_normalizes_channels() {
+        // Wide image: red left third, blue elsewhere; the center crop is all blue
+        let image = RgbImage::from_fn(900, 300, |x, _| if x < 300 { Rgb([255, 0, 0]) } else { Rgb([0, 0, 255]) });
+        let pixels = preprocess(&DynamicImage::ImageRgb8(image));
+
+        let plane = (IMAGE_SIZE * IMAGE_SIZE) as usize;
+        assert_eq!(pixels.len(), 3 * plane);
+        let center = plane / 2 + IMAGE_SIZE as usize / 2;
+        assert!((pixels[center] - (0.0 - MEAN[0]) / STD[0]).abs() < 1e-4);
+        assert!((pixels[2 * plane + center] - (1.0 - MEAN[2]) / STD[2]).abs() < 1e-4);
+    }
+}
@@ -7,16 +7,22 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use super::tokenizer::SentencePieceTokenizer;
-use super::EmbeddingModel;
+use super::{l2_normalize, EmbeddingModel, MultiVectorEmbedding};
 
 #[derive(Clone)]
 pub struct E5Config {
     pub model_path: PathBuf,
     pub dimension: usize,
     pub max_length: usize,
+    /// L2-normalize outputs. Disable only if a caller needs raw magnitudes;
+    /// `EmbeddingModel::*_normalized` still return unit vectors either way.
     pub normalize: bool,
+    /// Documents per inference call in `embed_documents`
+    pub batch_size: usize,
 }
 
+pub const DEFAULT_BATCH_SIZE: usize = 32;
+
 impl E5Config {
     pub fn auto_detect(model_dir: &Path) -> Option<Self> {
         let base_path = if model_dir.join("multilingual-e5-base").exists() {
@@ -51,6 +57,7 @@ impl E5Config {
             dimension,
             max_length: 512,
             normalize: true,
+            batch_size: DEFAULT_BATCH_SIZE,
         })
     }
 }
@@ -61,6 +68,7 @@ pub enum E5Mode {
     Passage,
 }
 
+#[derive(Clone)]
 pub struct E5Embeddings {
     session: Arc<Mutex<Session>>,
     tokenizer: Arc<SentencePieceTokenizer>,
@@ -163,12 +171,73 @@ impl E5Embeddings {
         Ok(embedding)
     }
 
+    /// Per-token hidden states (normalized) for late-interaction scoring.
+    /// Padding is excluded; requires a model exposing token-level outputs.
+    pub fn embed_tokens_with_mode(&self, text: &str, mode: E5Mode) -> Result<Vec<Vec<f32>>> {
+        let prefixed = match mode {
+            E5Mode::Query => format!("query: {}", text),
+            E5Mode::Passage => format!("passage: {}", text),
+        };
+
+        let mut token_ids = self.tokenizer.encode(&prefixed, true)?;
+        token_ids.truncate(self.config.max_length.min(512));
+        let (ids_vec, mask_vec, seq_len) = pad_batch(std::slice::from_ref(&token_ids), self.tokenizer.pad_id());
+
+        let shape = vec![1, seq_len];
+        let input_ids = Value::from_array((shape.clone(), ids_vec))
+            .map_err(|e| anyhow!("input_ids tensor: {:?}", e))?;
+        let attention_mask = Value::from_array((shape, mask_vec))
+            .map_err(|e| anyhow!("attention_mask tensor: {:?}", e))?;
+
+        let inputs = ort::inputs![
+            "input_ids" => input_ids,
+            "attention_mask" => attention_mask,
+        ];
+
+        let mut session = self.session.lock();
+        let outputs = session
+            .run(inputs)
+            .map_err(|e| anyhow!("Inference failed: {:?}", e))?;
+
+        let output_name = outputs
+            .iter()
+            .find(|(name, _)| *name == "last_hidden_state" || *name == "token_embeddings")
+            .map(|(name, _)| name.to_string())
+            .ok_or_else(|| anyhow!("Model has no token-level output for multi-vector embedding"))?;
+
+        let (shape, data) = outputs[output_name.as_str()]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| anyhow!("Failed to extract output '{}': {:?}", output_name, e))?;
+        let hidden_dim = shape[2] as usize;
+
+        Ok(data
+            .chunks(hidden_dim)
+            .take(token_ids.len())
+            .map(|row| l2_normalize(row.to_vec()))
+            .collect())
+    }
+
     fn extract_embedding(
         &self,
         outputs: &ort::session::SessionOutputs,
         attention_mask: &[i64],
     ) -> Result<Vec<f32>> {
-        // Check available output names and try "sentence_embedding" if present (already pooled)
+        let mut embeddings = self.extract_batch(outputs, attention_mask, 1, attention_mask.len())?;
+        embeddings
+            .pop()
+            .ok_or_else(|| anyhow!("Model returned no embedding"))
+    }
+
+    /// Pull one embedding per sample out of a `[batch, ...]` output, preferring
+    /// an already-pooled `sentence_embedding` and otherwise mean-pooling the
+    /// token states under `attention_mask` (`batch * padded_len`, row-major).
+    fn extract_batch(
+        &self,
+        outputs: &ort::session::SessionOutputs,
+        attention_mask: &[i64],
+        batch_size: usize,
+        padded_len: usize,
+    ) -> Result<Vec<Vec<f32>>> {
         let has_sentence_embedding = outputs
             .iter()
             .any(|(name, _)| name == "sentence_embedding");
@@ -178,8 +247,12 @@ impl E5Embeddings {
                 outputs["sentence_embedding"].try_extract_tensor::<f32>()
             {
                 if shape.len() == 2 {
-                    let embedding: Vec<f32> = data.to_vec();
-                    return self.normalize_vec(embedding);
+                    let hidden_dim = shape[1] as usize;
+                    return data
+                        .chunks(hidden_dim)
+                        .take(batch_size)
+                        .map(|row| self.normalize_vec(row.to_vec()))
+                        .collect();
                 }
             }
         }
@@ -205,92 +278,51 @@ impl E5Embeddings {
         let seq_len = shape[1] as usize;
         let hidden_dim = shape[2] as usize;
 
-        let mut pooled = vec![0.0f32; hidden_dim];
-        let mut mask_sum = 0.0f32;
-
-        for pos in 0..seq_len {
-            let mask_val = if pos < attention_mask.len() {
-                attention_mask[pos] as f32
-            } else {
-                0.0
-            };
-            if mask_val > 0.0 {
-                mask_sum += mask_val;
-                let offset = pos * hidden_dim; // batch=0, so offset = pos * dim
-                for dim in 0..hidden_dim {
-                    pooled[dim] += data[offset + dim] * mask_val;
-                }
-            }
-        }
-
-        if mask_sum > 0.0 {
-            for dim in 0..hidden_dim {
-                pooled[dim] /= mask_sum;
-            }
-        }
-
-        self.normalize_vec(pooled)
+        (0..batch_size)
+            .map(|sample_idx| {
+                let states = &data[sample_idx * seq_len * hidden_dim..(sample_idx + 1) * seq_len * hidden_dim];
+                let mask = &attention_mask[sample_idx * padded_len..(sample_idx + 1) * padded_len];
+                self.normalize_vec(mean_pool(states, mask, hidden_dim))
+            })
+            .collect()
     }
 
-    fn normalize_vec(&self, mut vec: Vec<f32>) -> Result<Vec<f32>> {
+    fn normalize_vec(&self, vec: Vec<f32>) -> Result<Vec<f32>> {
         if self.config.normalize {
-            let norm: f32 = vec.iter().map(|x| x * x).sum::<f32>().sqrt();
-            if norm > 1e-12 {
-                for v in &mut vec {
-                    *v /= norm;
-                }
-            }
+            Ok(l2_normalize(vec))
+        } else {
+            Ok(vec)
         }
-        Ok(vec)
     }
 
+    /// Embed many texts with true batched inference, `config.batch_size` texts
+    /// per run. Each batch is padded only to its own longest sequence.
     pub fn embed_batch_with_mode(&self, texts: &[&str], mode: E5Mode) -> Result<Vec<Vec<f32>>> {
         if texts.is_empty() {
             return Ok(Vec::new());
         }
 
-        const MAX_BATCH_SIZE: usize = 8;
+        let started = std::time::Instant::now();
+        let batch_size = self.config.batch_size.max(1);
+        let max_len = self.config.max_length.min(512);
         let mut all_embeddings = Vec::with_capacity(texts.len());
 
-        for batch in texts.chunks(MAX_BATCH_SIZE) {
-            let prefixed: Vec<String> = batch
-                .iter()
-                .map(|text| match mode {
+        for batch in texts.chunks(batch_size) {
+            let mut all_token_ids = Vec::with_capacity(batch.len());
+            for text in batch {
+                let prefixed = match mode {
                     E5Mode::Query => format!("query: {}", text),
                     E5Mode::Passage => format!("passage: {}", text),
-                })
-                .collect();
-
-            let mut all_token_ids = Vec::new();
-            let mut max_len = 0;
-
-            for text in &prefixed {
-                let mut token_ids = self.tokenizer.encode(text, true)?;
-                if token_ids.len() > 512 {
-                    token_ids.truncate(512);
-                }
-                max_len = max_len.max(token_ids.len());
+                };
+                let mut token_ids = self.tokenizer.encode(&prefixed, true)?;
+                token_ids.truncate(max_len);
                 all_token_ids.push(token_ids);
             }
 
-            let padded_len = max_len.min(512);
-            let batch_size = all_token_ids.len();
-
-            let mut input_ids_flat = Vec::with_capacity(batch_size * padded_len);
-            let mut attention_mask_flat = Vec::with_capacity(batch_size * padded_len);
-
-            for token_ids in &all_token_ids {
-                for &id in token_ids {
-                    input_ids_flat.push(id as i64);
-                    attention_mask_flat.push(1i64);
-                }
-                for _ in token_ids.len()..padded_len {
-                    input_ids_flat.push(0i64);
-                    attention_mask_flat.push(0i64);
-                }
-            }
+            let (input_ids_flat, attention_mask_flat, padded_len) =
+                pad_batch(&all_token_ids, self.tokenizer.pad_id());
 
-            let shape = vec![batch_size, padded_len];
+            let shape = vec![batch.len(), padded_len];
             let input_ids = Value::from_array((shape.clone(), input_ids_flat))
                 .map_err(|e| anyhow!("input_ids tensor: {:?}", e))?;
             let attention_mask = Value::from_array((shape, attention_mask_flat.clone()))
@@ -306,59 +338,73 @@ impl E5Embeddings {
                 .run(inputs)
                 .map_err(|e| anyhow!("Batch inference failed: {:?}", e))?;
 
-            // Extract per-sample embeddings from batch output
-            // Check for sentence_embedding first (already pooled), then fall back to last_hidden_state
-            let has_sentence_embedding = outputs.iter().any(|(name, _)| name == "sentence_embedding");
-
-            if has_sentence_embedding {
-                if let Ok((shape, data)) = outputs["sentence_embedding"].try_extract_tensor::<f32>() {
-                    let hidden_dim = shape[1] as usize;
-                    for sample_idx in 0..batch_size {
-                        let offset = sample_idx * hidden_dim;
-                        let embedding = data[offset..offset + hidden_dim].to_vec();
-                        all_embeddings.push(self.normalize_vec(embedding)?);
-                    }
-                }
-            } else if let Ok((shape, data)) = outputs["last_hidden_state"].try_extract_tensor::<f32>() {
-                let seq_len = shape[1] as usize;
-                let hidden_dim = shape[2] as usize;
-
-                for sample_idx in 0..batch_size {
-                    let mask_offset = sample_idx * padded_len;
-                    let sample_offset = sample_idx * seq_len * hidden_dim;
-                    let mut pooled = vec![0.0f32; hidden_dim];
-                    let mut mask_sum = 0.0f32;
-
-                    for pos in 0..seq_len {
-                        let mask_val = if mask_offset + pos < attention_mask_flat.len() {
-                            attention_mask_flat[mask_offset + pos] as f32
-                        } else {
-                            0.0
-                        };
-                        if mask_val > 0.0 {
-                            mask_sum += mask_val;
-                            let offset = sample_offset + pos * hidden_dim;
-                            for dim in 0..hidden_dim {
-                                pooled[dim] += data[offset + dim] * mask_val;
-                            }
-                        }
-                    }
-
-                    if mask_sum > 0.0 {
-                        for dim in 0..hidden_dim {
-                            pooled[dim] /= mask_sum;
-                        }
-                    }
-
-                    all_embeddings.push(self.normalize_vec(pooled)?);
-                }
+            let embeddings = self.extract_batch(&outputs, &attention_mask_flat, batch.len(), padded_len)?;
+            if embeddings.len() != batch.len() {
+                return Err(anyhow!(
+                    "Batch inference returned {} embeddings for {} inputs",
+                    embeddings.len(),
+                    batch.len()
+                ));
             }
+            all_embeddings.extend(embeddings);
         }
 
+        let elapsed = started.elapsed();
+        tracing::info!(
+            docs = texts.len(),
+            batch_size = batch_size,
+            elapsed_ms = elapsed.as_millis() as u64,
+            docs_per_sec = format!("{:.1}", texts.len() as f64 / elapsed.as_secs_f64().max(1e-9)),
+            "E5 batch embedding complete"
+        );
+
         Ok(all_embeddings)
     }
 }
 
+/// Right-pad token sequences to the longest one, returning flattened
+/// `input_ids`, `attention_mask` and the padded length.
+fn pad_batch(token_ids: &[Vec<u32>], pad_id: u32) -> (Vec<i64>, Vec<i64>, usize) {
+    let padded_len = token_ids.iter().map(|ids| ids.len()).max().unwrap_or(0);
+    let mut input_ids = Vec::with_capacity(token_ids.len() * padded_len);
+    let mut attention_mask = Vec::with_capacity(token_ids.len() * padded_len);
+
+    for ids in token_ids {
+        input_ids.extend(ids.iter().map(|&id| id as i64));
+        attention_mask.extend(std::iter::repeat(1i64).take(ids.len()));
+        input_ids.extend(std::iter::repeat(pad_id as i64).take(padded_len - ids.len()));
+        attention_mask.extend(std::iter::repeat(0i64).take(padded_len - ids.len()));
+    }
+
+    (input_ids, attention_mask, padded_len)
+}
+
+/// Mask-weighted mean of one sample's token states (`[seq, hidden_dim]`).
+/// Positions past the end of `mask` count as padding.
+fn mean_pool(states: &[f32], mask: &[i64], hidden_dim: usize) -> Vec<f32> {
+    let seq_len = states.len() / hidden_dim.max(1);
+    let mut pooled = vec![0.0f32; hidden_dim];
+    let mut mask_sum = 0.0f32;
+
+    for pos in 0..seq_len {
+        let mask_val = mask.get(pos).copied().unwrap_or(0) as f32;
+        if mask_val > 0.0 {
+            mask_sum += mask_val;
+            let offset = pos * hidden_dim;
+            for dim in 0..hidden_dim {
+                pooled[dim] += states[offset + dim] * mask_val;
+            }
+        }
+    }
+
+    if mask_sum > 0.0 {
+        for v in &mut pooled {
+            *v /= mask_sum;
+        }
+    }
+    pooled
+}
+
 impl EmbeddingModel for E5Embeddings {
     fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
         self.embed_with_mode(text, E5Mode::Query)
@@ -376,3 +422,68 @@ impl EmbeddingModel for E5Embeddings {
         self.config.dimension
     }
 }
+
+impl MultiVectorEmbedding for E5Embeddings {
+    fn embed_query_tokens(&self, text: &str) -> Result<Vec<Vec<f32>>> {
+        self.embed_tokens_with_mode(text, E5Mode::Query)
+    }
+
+    fn embed_document_tokens(&self, text: &str) -> Result<Vec<Vec<f32>>> {
+        self.embed_tokens_with_mode(text, E5Mode::Passage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fake token states: each position's vector depends only on the token id,
+    /// like an encoder that ignores padded positions via the attention mask.
+    fn fake_states(input_ids: &[i64], hidden_dim: usize) -> Vec<f32> {
+        input_ids
+            .iter()
+            .flat_map(|&id| (0..hidden_dim).map(move |d| (id as f32 * 0.37 + d as f32 * 0.11).sin()))
+            .collect()
+    }
+
+    #[test]
+    fn test_batched_pooling_matches_single_path() {
+        let hidden_dim = 8;
+        let docs: Vec<Vec<u32>> = vec![vec![0, 15, 27, 2], vec![0, 9, 2], vec![0, 44, 12, 81, 63, 2]];
+
+        // Batched: padded to the longest sequence in the batch
+        let (ids, mask, padded_len) = pad_batch(&docs, 1);
+        assert_eq!(padded_len, 6);
+        assert_eq!(ids.len(), docs.len() * padded_len);
+        let states = fake_states(&ids, hidden_dim);
+
+        for (i, doc) in docs.iter().enumerate() {
+            let row = i * padded_len;
+            let batched = mean_pool(
+                &states[row * hidden_dim..(row + padded_len) * hidden_dim],
+                &mask[row..row + padded_len],
+                hidden_dim,
+            );
+
+            // Single path: one sequence padded to the fixed model length
+            let (single_ids, single_mask, _) = pad_batch(std::slice::from_ref(doc), 1);
+            let mut single_ids = single_ids;
+            let mut single_mask = single_mask;
+            single_ids.resize(16, 1);
+            single_mask.resize(16, 0);
+            let single = mean_pool(&fake_states(&single_ids, hidden_dim), &single_mask, hidden_dim);
+
+            for (a, b) in batched.iter().zip(&single) {
+                assert!((a - b).abs() < 1e-6, "doc {}: {} vs {}", i, a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pad_batch_uses_pad_id_and_zero_mask() {
+        let (ids, mask, len) = pad_batch(&[vec![5, 6], vec![7]], 1);
+        assert_eq!(len, 2);
+        assert_eq!(ids, vec![5, 6, 7, 1]);
+        assert_eq!(mask, vec![1, 1, 1, 0]);
+    }
+}
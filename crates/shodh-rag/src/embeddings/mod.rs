@@ -1,9 +1,16 @@
+pub mod clip;
 pub mod e5;
 pub mod tokenizer;
 
 use anyhow::Result;
 
 /// Unified embedding model trait
+///
+/// Whether `embed_query`/`embed_document` return unit-length vectors depends on
+/// the model config (`E5Config::normalize`). `LanceStore::vector_search` uses
+/// cosine distance, so its ranking and the `1 - distance` scores fed into hybrid
+/// fusion ignore magnitude. Code that compares vectors with a raw dot product
+/// needs unit vectors and should use the `_normalized` variants.
 pub trait EmbeddingModel: Send + Sync {
     /// Embed a search query (with appropriate prefix for the model)
     fn embed_query(&self, text: &str) -> Result<Vec<f32>>;
@@ -16,6 +23,77 @@ pub trait EmbeddingModel: Send + Sync {
         texts.iter().map(|t| self.embed_document(t)).collect()
     }
 
+    /// Query embedding guaranteed to be L2-normalized, regardless of model config
+    fn embed_query_normalized(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed_query(text).map(l2_normalize)
+    }
+
+    /// Document embedding guaranteed to be L2-normalized, regardless of model config
+    fn embed_document_normalized(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed_document(text).map(l2_normalize)
+    }
+
     /// Embedding vector dimension
     fn dimension(&self) -> usize;
 }
+
+/// Late-interaction (ColBERT-style) embeddings: one vector per token, scored
+/// against each other with `search::maxsim_score`. Token vectors are
+/// L2-normalized so MaxSim sums cosine similarities.
+pub trait MultiVectorEmbedding: Send + Sync {
+    /// Per-token vectors for a search query
+    fn embed_query_tokens(&self, text: &str) -> Result<Vec<Vec<f32>>>;
+
+    /// Per-token vectors for a document/passage
+    fn embed_document_tokens(&self, text: &str) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Scale a vector to unit length. Near-zero vectors are returned unchanged.
+pub fn l2_normalize(mut vec: Vec<f32>) -> Vec<f32> {
+    let norm: f32 = vec.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 1e-12 {
+        for v in &mut vec {
+            *v /= norm;
+        }
+    }
+    vec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a fixed, non-unit vector — like a model with normalization off
+    struct RawModel;
+
+    impl EmbeddingModel for RawModel {
+        fn embed_query(&self, _text: &str) -> Result<Vec<f32>> {
+            Ok(vec![3.0, 4.0, 0.0])
+        }
+        fn embed_document(&self, _text: &str) -> Result<Vec<f32>> {
+            Ok(vec![0.0, 6.0, 8.0])
+        }
+        fn dimension(&self) -> usize {
+            3
+        }
+    }
+
+    fn norm(v: &[f32]) -> f32 {
+        v.iter().map(|x| x * x).sum::<f32>().sqrt()
+    }
+
+    #[test]
+    fn test_normalized_variants_are_unit_length() {
+        let model = RawModel;
+        assert!((norm(&model.embed_query_normalized("q").unwrap()) - 1.0).abs() < 1e-6);
+        assert!((norm(&model.embed_document_normalized("d").unwrap()) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_raw_variants_keep_magnitude() {
+        let model = RawModel;
+        assert_eq!(norm(&model.embed_query("q").unwrap()), 5.0);
+        assert_eq!(norm(&model.embed_document("d").unwrap()), 10.0);
+        assert_eq!(l2_normalize(vec![0.0, 0.0]), vec![0.0, 0.0]);
+    }
+}
@@ -118,6 +118,10 @@ impl SentencePieceTokenizer {
         Ok(token_ids)
     }
 
+    pub fn pad_id(&self) -> u32 {
+        self.pad_id
+    }
+
     pub fn prepare_for_model(&self, token_ids: &[u32], max_len: usize) -> (Vec<i64>, Vec<i64>) {
         let len = token_ids.len().min(max_len);
         let mut ids = Vec::with_capacity(max_len);
@@ -0,0 +1,109 @@
+//! Knowledge graph of indexed documents
+//!
+//! Each document is linked to its file-type topic, the programming language
+//! its extension implies, its folder and its citation authors — the same
+//! relationships the graph view draws. Every edge records the documents that
+//! evidence it.
+
+use std::path::Path;
+
+use super::KnowledgeGraph;
+use crate::types::ComprehensiveResult;
+
+/// Programming language implied by a file extension
+pub fn technology_for_extension(ext: &str) -> Option<&'static str> {
+    match ext {
+        "rs" => Some("Rust"),
+        "py" => Some("Python"),
+        "js" | "jsx" => Some("JavaScript"),
+        "ts" | "tsx" => Some("TypeScript"),
+        "java" => Some("Java"),
+        "go" => Some("Go"),
+        "cpp" | "cc" | "cxx" | "c" | "h" => Some("C/C++"),
+        _ => None,
+    }
+}
+
+/// Build the graph from listed documents (one result per document, or chunks —
+/// repeats of a document merge into the same node and edges).
+pub fn build_document_graph(documents: &[ComprehensiveResult]) -> KnowledgeGraph {
+    // Already bounded by the size of the document listing
+    let mut graph = KnowledgeGraph::new(usize::MAX);
+
+    for doc in documents {
+        let Some(path) = doc.metadata.get("file_path").or_else(|| doc.metadata.get("source_file")) else {
+            continue;
+        };
+        graph.add_entity(path, "document", path);
+
+        if let Some(file_type) = doc.metadata.get("file_type") {
+            graph.add_entity(file_type, "topic", path);
+            graph.add_evidenced_relationship(path, file_type, "category", 0.8, path);
+        }
+
+        let ext = doc.metadata.get("file_extension").map(|e| e.to_lowercase()).or_else(|| {
+            Path::new(path).extension().map(|e| e.to_string_lossy().to_lowercase())
+        });
+        if let Some(tech) = ext.as_deref().and_then(technology_for_extension) {
+            graph.add_entity(tech, "language", path);
+            graph.add_evidenced_relationship(path, tech, "language", 0.7, path);
+        }
+
+        // Folder nodes instead of pairwise same-folder edges, which grow
+        // quadratically in big folders
+        if let Some(folder) = Path::new(path).parent().map(|p| p.display().to_string()).filter(|f| !f.is_empty()) {
+            graph.add_entity(&folder, "folder", path);
+            graph.add_evidenced_relationship(path, &folder, "in_folder", 0.8, path);
+        }
+
+        for author in doc.citation.authors.iter().map(|a| a.trim()).filter(|a| !a.is_empty()) {
+            graph.add_entity(author, "person", path);
+            graph.add_evidenced_relationship(path, author, "authored_by", 1.0, path);
+        }
+    }
+
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Citation;
+    use std::collections::HashMap;
+
+    fn doc(path: &str, file_type: &str, authors: &[&str]) -> ComprehensiveResult {
+        ComprehensiveResult {
+            id: uuid::Uuid::new_v4(),
+            score: 0.0,
+            metadata: HashMap::from([
+                ("file_path".to_string(), path.to_string()),
+                ("file_type".to_string(), file_type.to_string()),
+            ]),
+            citation: Citation {
+                authors: authors.iter().map(|a| a.to_string()).collect(),
+                ..Citation::default()
+            },
+            snippet: String::new(),
+            source_index: "filter".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_documents_link_to_shared_entities_with_provenance() {
+        let docs = vec![
+            doc("/src/lib.rs", "Code", &["Ada Lovelace"]),
+            doc("/src/main.rs", "Code", &[]),
+            // A second chunk of the same document merges into the same edges
+            doc("/src/lib.rs", "Code", &["Ada Lovelace"]),
+        ];
+        let graph = build_document_graph(&docs);
+
+        // 2 documents + Code + Rust + /src + Ada Lovelace
+        assert_eq!(graph.node_count(), 6);
+        let rust_edges: Vec<_> = graph.relationships().filter(|(_, to, _)| to.name == "Rust").collect();
+        assert_eq!(rust_edges.len(), 2);
+        assert!(rust_edges.iter().all(|(from, _, rel)| rel.doc_ids == vec![from.name.clone()]));
+        let code = graph.find_entity("code").unwrap();
+        assert_eq!(code.doc_ids, vec!["/src/lib.rs".to_string(), "/src/main.rs".to_string()]);
+    }
+}
@@ -0,0 +1,180 @@
+//! Knowledge graph export for Gephi, Neo4j and networkx
+//!
+//! Both formats are written node by node and edge by edge to the given
+//! writer, so exporting a large graph never builds the whole document in
+//! memory.
+//!
+//! - GraphML: `type` and `documents` (`|`-separated) attributes on nodes;
+//!   `type`, `weight` and `documents` on edges
+//! - Node-link JSON (networkx `node_link_data` layout): `nodes` with `id`,
+//!   `type`, `documents`; `links` with `source`, `target`, `type`, `weight`,
+//!   `documents`
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::io::Write;
+
+use super::KnowledgeGraph;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphExportFormat {
+    Graphml,
+    Json,
+}
+
+impl GraphExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Graphml => "graphml",
+            Self::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphExportStats {
+    pub nodes: usize,
+    pub edges: usize,
+}
+
+/// Write `graph` to `out` in `format`
+pub fn export_graph<W: Write>(graph: &KnowledgeGraph, format: GraphExportFormat, out: W) -> Result<GraphExportStats> {
+    match format {
+        GraphExportFormat::Graphml => write_graphml(graph, out),
+        GraphExportFormat::Json => write_node_link_json(graph, out),
+    }
+}
+
+fn write_graphml<W: Write>(graph: &KnowledgeGraph, mut out: W) -> Result<GraphExportStats> {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#)?;
+    writeln!(out, r#"  <key id="n_type" for="node" attr.name="type" attr.type="string"/>"#)?;
+    writeln!(out, r#"  <key id="n_docs" for="node" attr.name="documents" attr.type="string"/>"#)?;
+    writeln!(out, r#"  <key id="e_type" for="edge" attr.name="type" attr.type="string"/>"#)?;
+    writeln!(out, r#"  <key id="e_weight" for="edge" attr.name="weight" attr.type="double"/>"#)?;
+    writeln!(out, r#"  <key id="e_docs" for="edge" attr.name="documents" attr.type="string"/>"#)?;
+    writeln!(out, r#"  <graph id="knowledge_graph" edgedefault="directed">"#)?;
+
+    let mut stats = GraphExportStats::default();
+    for entity in graph.entities() {
+        writeln!(
+            out,
+            r#"    <node id="{}"><data key="n_type">{}</data><data key="n_docs">{}</data></node>"#,
+            escape_xml(&entity.name),
+            escape_xml(&entity.entity_type),
+            escape_xml(&entity.doc_ids.join("|")),
+        )?;
+        stats.nodes += 1;
+    }
+    for (from, to, rel) in graph.relationships() {
+        writeln!(
+            out,
+            r#"    <edge id="e{}" source="{}" target="{}"><data key="e_type">{}</data><data key="e_weight">{}</data><data key="e_docs">{}</data></edge>"#,
+            stats.edges,
+            escape_xml(&from.name),
+            escape_xml(&to.name),
+            escape_xml(&rel.relation_type),
+            rel.weight,
+            escape_xml(&rel.doc_ids.join("|")),
+        )?;
+        stats.edges += 1;
+    }
+
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</graphml>")?;
+    out.flush()?;
+    Ok(stats)
+}
+
+fn write_node_link_json<W: Write>(graph: &KnowledgeGraph, mut out: W) -> Result<GraphExportStats> {
+    let mut stats = GraphExportStats::default();
+    out.write_all(br#"{"directed":true,"multigraph":true,"graph":{},"nodes":["#)?;
+    for entity in graph.entities() {
+        if stats.nodes > 0 {
+            out.write_all(b",")?;
+        }
+        out.write_all(b"\n")?;
+        serde_json::to_writer(
+            &mut out,
+            &json!({ "id": entity.name, "type": entity.entity_type, "documents": entity.doc_ids }),
+        )?;
+        stats.nodes += 1;
+    }
+    out.write_all(b"\n],\"links\":[")?;
+    for (from, to, rel) in graph.relationships() {
+        if stats.edges > 0 {
+            out.write_all(b",")?;
+        }
+        out.write_all(b"\n")?;
+        serde_json::to_writer(
+            &mut out,
+            &json!({
+                "source": from.name,
+                "target": to.name,
+                "type": rel.relation_type,
+                "weight": rel.weight,
+                "documents": rel.doc_ids,
+            }),
+        )?;
+        stats.edges += 1;
+    }
+    out.write_all(b"\n]}\n")?;
+    out.flush()?;
+    Ok(stats)
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> KnowledgeGraph {
+        let mut graph = KnowledgeGraph::new(100);
+        graph.add_entity("a.rs", "document", "a.rs");
+        graph.add_entity("Rust", "language", "a.rs");
+        graph.add_entity("R&D <team>", "folder", "a.rs");
+        graph.add_entity("unrelated.pdf", "document", "unrelated.pdf");
+        graph.add_evidenced_relationship("a.rs", "Rust", "language", 0.7, "a.rs");
+        graph.add_evidenced_relationship("a.rs", "R&D <team>", "in_folder", 0.8, "a.rs");
+        graph
+    }
+
+    #[test]
+    fn test_graphml_is_well_formed_with_typed_edges() {
+        let mut out = Vec::new();
+        let stats = export_graph(&sample(), GraphExportFormat::Graphml, &mut out).unwrap();
+        assert_eq!(stats, GraphExportStats { nodes: 4, edges: 2 });
+
+        let xml = String::from_utf8(out).unwrap();
+        let doc = roxmltree::Document::parse(&xml).unwrap();
+        let edge = doc.descendants().find(|n| n.has_tag_name("edge")).unwrap();
+        assert_eq!(edge.attribute("source"), Some("a.rs"));
+        let values: Vec<&str> = edge.children().filter_map(|d| d.text()).collect();
+        assert_eq!(values, vec!["language", "0.7", "a.rs"]);
+        assert!(doc.descendants().any(|n| n.attribute("id") == Some("R&D <team>")));
+    }
+
+    #[test]
+    fn test_node_link_json_of_subgraph() {
+        let sub = sample().subgraph("rust", 1).unwrap();
+        let mut out = Vec::new();
+        export_graph(&sub, GraphExportFormat::Json, &mut out).unwrap();
+
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let ids: Vec<&str> = value["nodes"].as_array().unwrap().iter().map(|n| n["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, vec!["a.rs", "Rust"]);
+        assert_eq!(value["links"][0]["type"], "language");
+        assert_eq!(value["links"][0]["documents"][0], "a.rs");
+        assert!(sample().subgraph("missing", 2).is_none());
+    }
+}
@@ -1,7 +1,7 @@
-use anyhow::Result;
 use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Entity {
@@ -14,6 +14,9 @@ pub struct Entity {
 pub struct Relationship {
     pub relation_type: String,
     pub weight: f32,
+    /// Documents that evidence this relationship
+    #[serde(default)]
+    pub doc_ids: Vec<String>,
 }
 
 pub struct KnowledgeGraph {
@@ -85,10 +88,116 @@ impl KnowledgeGraph {
             Relationship {
                 relation_type: relation_type.to_string(),
                 weight,
+                doc_ids: Vec::new(),
             },
         );
     }
 
+    /// Add a relationship evidenced by `doc_id`. Seeing the same typed edge
+    /// again records the extra document and keeps the higher weight.
+    pub fn add_evidenced_relationship(
+        &mut self,
+        from: &str,
+        to: &str,
+        relation_type: &str,
+        weight: f32,
+        doc_id: &str,
+    ) {
+        let (Some(&from_idx), Some(&to_idx)) = (self.name_to_node.get(from), self.name_to_node.get(to)) else {
+            return;
+        };
+
+        let existing = self
+            .graph
+            .edges_connecting(from_idx, to_idx)
+            .find(|e| e.weight().relation_type == relation_type)
+            .map(|e| e.id());
+        if let Some(edge) = existing.and_then(|id| self.graph.edge_weight_mut(id)) {
+            edge.weight = edge.weight.max(weight);
+            if !edge.doc_ids.iter().any(|d| d == doc_id) {
+                edge.doc_ids.push(doc_id.to_string());
+            }
+            return;
+        }
+
+        self.graph.add_edge(
+            from_idx,
+            to_idx,
+            Relationship {
+                relation_type: relation_type.to_string(),
+                weight,
+                doc_ids: vec![doc_id.to_string()],
+            },
+        );
+    }
+
+    /// Entity by exact name, falling back to a case-insensitive match
+    pub fn find_entity(&self, name: &str) -> Option<&Entity> {
+        self.find_node(name).and_then(|idx| self.graph.node_weight(idx))
+    }
+
+    /// When several entities match case-insensitively ("apple", "Apple"),
+    /// the one evidenced by the most documents wins, then the oldest, so the
+    /// answer doesn't depend on hash map order
+    pub(super) fn find_node(&self, name: &str) -> Option<NodeIndex> {
+        self.name_to_node.get(name).copied().or_else(|| {
+            let name = name.trim().to_lowercase();
+            self.name_to_node
+                .iter()
+                .filter(|(n, _)| n.to_lowercase() == name)
+                .map(|(_, &idx)| idx)
+                .min_by_key(|&idx| (std::cmp::Reverse(self.graph[idx].doc_ids.len()), idx))
+        })
+    }
+
+    /// The entities within `hops` edges of `entity` (in either direction) and
+    /// the relationships among them. None if the entity is unknown.
+    pub fn subgraph(&self, entity: &str, hops: usize) -> Option<KnowledgeGraph> {
+        let start = self.find_node(entity)?;
+        let mut keep = HashSet::from([start]);
+        let mut queue = VecDeque::from([(start, 0)]);
+        while let Some((node, depth)) = queue.pop_front() {
+            if depth == hops {
+                continue;
+            }
+            for neighbor in self.graph.neighbors_undirected(node) {
+                if keep.insert(neighbor) {
+                    queue.push_back((neighbor, depth + 1));
+                }
+            }
+        }
+
+        let mut sub = KnowledgeGraph::new(keep.len());
+        // Node order of the original graph, so exports are deterministic
+        for idx in self.graph.node_indices().filter(|i| keep.contains(i)) {
+            let entity = self.graph[idx].clone();
+            let new_idx = sub.graph.add_node(entity.clone());
+            sub.name_to_node.insert(entity.name, new_idx);
+        }
+        for edge in self.graph.edge_references() {
+            let (from, to) = (&self.graph[edge.source()].name, &self.graph[edge.target()].name);
+            if let (Some(&a), Some(&b)) = (sub.name_to_node.get(from), sub.name_to_node.get(to)) {
+                sub.graph.add_edge(a, b, edge.weight().clone());
+            }
+        }
+        Some(sub)
+    }
+
+    pub(super) fn inner(&self) -> &DiGraph<Entity, Relationship> {
+        &self.graph
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = &Entity> {
+        self.graph.node_weights()
+    }
+
+    /// Every relationship as (from, to, relationship)
+    pub fn relationships(&self) -> impl Iterator<Item = (&Entity, &Entity, &Relationship)> {
+        self.graph
+            .edge_references()
+            .map(|e| (&self.graph[e.source()], &self.graph[e.target()], e.weight()))
+    }
+
     pub fn get_related_doc_ids(&self, entity_name: &str, max_hops: usize) -> Vec<String> {
         let Some(&start) = self.name_to_node.get(entity_name) else {
             return Vec::new();
@@ -1,3 +1,9 @@
+pub mod document_graph;
+pub mod export;
 pub mod knowledge_graph;
+pub mod neighborhood;
 
+pub use document_graph::{build_document_graph, technology_for_extension};
+pub use export::{export_graph, GraphExportFormat, GraphExportStats};
 pub use knowledge_graph::KnowledgeGraph;
+pub use neighborhood::{neighborhood, Neighborhood, NeighborhoodEdge, NeighborhoodNode};
@@ -0,0 +1,199 @@
+//! N-hop entity neighborhoods ("who is connected to X")
+//!
+//! Expansion is breadth-first, one hop at a time. Within a hop, candidates
+//! are ranked by how strongly they connect to what is already kept — the sum
+//! of edge weight × evidencing documents — and the expansion stops once
+//! `max_nodes` entities are kept, so a hub never drags in the whole graph.
+
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::knowledge_graph::Relationship;
+use super::KnowledgeGraph;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NeighborhoodNode {
+    pub name: String,
+    pub entity_type: String,
+    /// Edges from the center (0 for the center itself)
+    pub hops: usize,
+    /// Connection strength that ranked this node within its hop
+    pub score: f32,
+    pub doc_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NeighborhoodEdge {
+    pub source: String,
+    pub target: String,
+    pub relation_type: String,
+    pub weight: f32,
+    /// Documents that evidence the edge, for citation click-through
+    pub doc_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Neighborhood {
+    pub center: String,
+    /// Center first, then by hop and descending score
+    pub nodes: Vec<NeighborhoodNode>,
+    /// Edges among the kept nodes, strongest first
+    pub edges: Vec<NeighborhoodEdge>,
+    /// Reachable entities were left out to respect `max_nodes`
+    pub truncated: bool,
+}
+
+/// Strength of one edge: its weight scaled by how many documents evidence it
+fn strength(rel: &Relationship) -> f32 {
+    rel.weight * rel.doc_ids.len().max(1) as f32
+}
+
+/// Entities within `hops` edges of `entity` (either direction), at most
+/// `max_nodes` including the entity itself. None if the entity is unknown.
+pub fn neighborhood(graph: &KnowledgeGraph, entity: &str, hops: usize, max_nodes: usize) -> Option<Neighborhood> {
+    let start = graph.find_node(entity)?;
+    let g = graph.inner();
+    let max_nodes = max_nodes.max(1);
+
+    let mut kept: Vec<(NodeIndex, usize, f32)> = vec![(start, 0, 0.0)];
+    let mut kept_set: HashMap<NodeIndex, usize> = HashMap::from([(start, 0)]);
+    let mut frontier = vec![start];
+    let mut truncated = false;
+
+    for hop in 1..=hops {
+        let mut candidates: HashMap<NodeIndex, f32> = HashMap::new();
+        for &node in &frontier {
+            let edges = g
+                .edges_directed(node, Direction::Outgoing)
+                .map(|e| (e.target(), e.weight()))
+                .chain(g.edges_directed(node, Direction::Incoming).map(|e| (e.source(), e.weight())));
+            for (neighbor, rel) in edges {
+                if !kept_set.contains_key(&neighbor) {
+                    *candidates.entry(neighbor).or_insert(0.0) += strength(rel);
+                }
+            }
+        }
+        if candidates.is_empty() {
+            break;
+        }
+
+        let mut ranked: Vec<(NodeIndex, f32)> = candidates.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| g[a.0].name.cmp(&g[b.0].name)));
+        let room = max_nodes - kept.len();
+        if ranked.len() > room {
+            truncated = true;
+            ranked.truncate(room);
+        }
+
+        frontier = ranked.iter().map(|(idx, _)| *idx).collect();
+        for (idx, score) in ranked {
+            kept_set.insert(idx, kept.len());
+            kept.push((idx, hop, score));
+        }
+        if kept.len() >= max_nodes {
+            break;
+        }
+    }
+
+    let mut edges: Vec<(f32, NeighborhoodEdge)> = g
+        .edge_references()
+        .filter(|e| kept_set.contains_key(&e.source()) && kept_set.contains_key(&e.target()))
+        .map(|e| {
+            let rel = e.weight();
+            (strength(rel), NeighborhoodEdge {
+                source: g[e.source()].name.clone(),
+                target: g[e.target()].name.clone(),
+                relation_type: rel.relation_type.clone(),
+                weight: rel.weight,
+                doc_ids: rel.doc_ids.clone(),
+            })
+        })
+        .collect();
+    edges.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let nodes = kept
+        .into_iter()
+        .map(|(idx, hops, score)| NeighborhoodNode {
+            name: g[idx].name.clone(),
+            entity_type: g[idx].entity_type.clone(),
+            hops,
+            score,
+            doc_ids: g[idx].doc_ids.clone(),
+        })
+        .collect();
+
+    Some(Neighborhood {
+        center: g[start].name.clone(),
+        nodes,
+        edges: edges.into_iter().map(|(_, e)| e).collect(),
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two papers by Ada, one shared with Grace; a third paper by Alan only
+    fn authors_graph() -> KnowledgeGraph {
+        let mut graph = KnowledgeGraph::new(100);
+        for (doc, authors) in [
+            ("engines.pdf", &["Ada", "Grace"][..]),
+            ("notes.pdf", &["Ada"][..]),
+            ("computing.pdf", &["Alan"][..]),
+        ] {
+            graph.add_entity(doc, "document", doc);
+            graph.add_entity("PDF", "topic", doc);
+            graph.add_evidenced_relationship(doc, "PDF", "category", 0.8, doc);
+            for author in authors {
+                graph.add_entity(author, "person", doc);
+                graph.add_evidenced_relationship(doc, author, "authored_by", 1.0, doc);
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn test_neighborhood_ranks_and_bounds_hops() {
+        let graph = authors_graph();
+
+        let one_hop = neighborhood(&graph, "ada", 1, 10).unwrap();
+        assert_eq!(one_hop.center, "Ada");
+        let names: Vec<&str> = one_hop.nodes.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["Ada", "engines.pdf", "notes.pdf"]);
+        assert!(one_hop.edges.iter().all(|e| e.relation_type == "authored_by"));
+        assert_eq!(one_hop.edges[0].doc_ids.len(), 1);
+        assert!(!one_hop.truncated);
+
+        // Two hops reach co-author and topic; the PDF hub's other papers are three away
+        let two_hops = neighborhood(&graph, "Ada", 2, 10).unwrap();
+        let second: Vec<&str> = two_hops.nodes.iter().filter(|n| n.hops == 2).map(|n| n.name.as_str()).collect();
+        assert_eq!(second, vec!["PDF", "Grace"]);
+        assert!(!two_hops.nodes.iter().any(|n| n.name == "Alan"));
+
+        let bounded = neighborhood(&graph, "Ada", 3, 4).unwrap();
+        assert_eq!(bounded.nodes.len(), 4);
+        assert!(bounded.truncated);
+        assert!(neighborhood(&graph, "Nobody", 2, 10).is_none());
+    }
+
+    #[test]
+    fn test_case_insensitive_lookup_prefers_best_evidenced_entity() {
+        let mut graph = KnowledgeGraph::new(100);
+        graph.add_entity("apple", "fruit", "recipes.pdf");
+        graph.add_entity("Apple", "organization", "q1.pdf");
+        graph.add_entity("Apple", "organization", "q2.pdf");
+        graph.add_entity("APPLE", "organization", "memo.pdf");
+
+        assert_eq!(graph.find_entity("aPPle").unwrap().name, "Apple");
+        // Exact names still win
+        assert_eq!(graph.find_entity("APPLE").unwrap().name, "APPLE");
+        assert_eq!(neighborhood(&graph, " Apple ", 1, 10).unwrap().center, "Apple");
+    }
+}
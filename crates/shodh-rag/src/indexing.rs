@@ -13,9 +13,12 @@ use chrono::Utc;
 use walkdir::WalkDir;
 use futures::FutureExt;
 
-use crate::rag_engine::RAGEngine;
+use crate::rag_engine::{normalize_source_path, DocumentPreparer, PreparedDocument, RAGEngine};
 use crate::chat::EventEmitter;
 
+/// Chunks accumulated from prepared files before an embedding pass
+const EMBED_BATCH_CHUNKS: usize = 256;
+
 // ── Types ──────────────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +49,9 @@ pub struct IndexingProgress {
     pub current_action: String,
     pub eta_seconds: f32,
     pub speed: f32,
+    /// Chunks embedded and stored so far in this run
+    #[serde(default)]
+    pub chunks_embedded: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,14 +65,29 @@ pub struct IndexingOptions {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexingResult {
+    /// Files embedded and stored (new or changed since the last run)
     pub files_processed: usize,
     pub total_chunks: usize,
     pub failed_files: Vec<String>,
     pub duration: u64,
+    /// Files left untouched because their content hash matched the index
+    #[serde(default)]
+    pub files_skipped: usize,
+}
+
+/// Metadata key holding the blake3 hash of a file's bytes at index time
+pub const CONTENT_HASH_KEY: &str = "content_hash";
+
+/// Outcome of preparing one file in a folder run
+enum FileOutcome {
+    Prepared(PreparedDocument),
+    Unchanged,
+    Failed(String),
 }
 
 /// Shared state for pause/cancel signalling across async boundaries.
-#[derive(Debug)]
+/// Clones share the same flags.
+#[derive(Debug, Clone)]
 pub struct IndexingState {
     pub is_paused: Arc<Mutex<bool>>,
     pub should_cancel: Arc<Mutex<bool>>,
@@ -106,6 +127,14 @@ impl IndexingState {
     pub fn is_paused(&self) -> bool {
         *self.is_paused.lock().unwrap()
     }
+
+    /// Wait while paused. Returns false if cancelled (paused or not).
+    pub async fn wait_if_paused(&self) -> bool {
+        while self.is_paused() && !self.is_cancelled() {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        !self.is_cancelled()
+    }
 }
 
 // ── Public API ─────────────────────────────────────────────────────────────
@@ -178,10 +207,12 @@ pub fn check_path_type(path: &str) -> Result<(bool, bool), String> {
 
 /// Index a single file into a space.
 ///
-/// Returns (chunks_created, duration_ms).
+/// Unless `force` is set, a file whose content hash matches the one stored at
+/// its last indexing is skipped and reported in `files_skipped`.
 pub async fn index_single_file(
     file_path: &str,
     space_id: &str,
+    force: bool,
     rag: &mut RAGEngine,
     emitter: Option<&dyn EventEmitter>,
 ) -> Result<IndexingResult, String> {
@@ -205,6 +236,26 @@ pub async fn index_single_file(
     }
 
     emit_progress(emitter, file_path, 0, 1, 0.0, "Reading file...");
+
+    let content_hash = file_content_hash(&path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    if !force {
+        let stored = rag.indexed_content_hashes(file_path)
+            .await
+            .map_err(|e| format!("Failed to look up indexed file: {}", e))?;
+        if stored.get(&normalize_source_path(&path)) == Some(&content_hash) {
+            emit_progress(emitter, file_path, 1, 1, 100.0, "Unchanged, skipped");
+            return Ok(IndexingResult {
+                files_processed: 0,
+                total_chunks: 0,
+                failed_files: vec![],
+                duration: start_time.elapsed().as_millis() as u64,
+                files_skipped: 1,
+            });
+        }
+    }
+
     emit_progress(emitter, file_path, 0, 1, 50.0, "Indexing...");
 
     let file_name = path.file_name()
@@ -220,6 +271,7 @@ pub async fn index_single_file(
     metadata.insert("filename".to_string(), file_name.to_string());
     metadata.insert("doc_type".to_string(), "document".to_string());
     metadata.insert("indexed_at".to_string(), Utc::now().to_rfc3339());
+    metadata.insert(CONTENT_HASH_KEY.to_string(), content_hash);
 
     let ids = rag.add_document_from_file(&path, metadata)
         .await
@@ -236,14 +288,19 @@ pub async fn index_single_file(
         total_chunks: chunks_created,
         failed_files: vec![],
         duration,
+        files_skipped: 0,
     })
 }
 
 /// Batch-index a folder into a space with pause/resume/cancel support.
+///
+/// Files whose content hash matches the stored one are skipped unless
+/// `force` is set; changed files replace their previous chunks.
 pub async fn index_folder(
     folder_path: &str,
     space_id: &str,
     options: &IndexingOptions,
+    force: bool,
     rag: &mut RAGEngine,
     indexing_state: &IndexingState,
     emitter: Option<&dyn EventEmitter>,
@@ -287,6 +344,7 @@ pub async fn index_folder(
                 total_chunks: 0,
                 failed_files: vec![],
                 duration: start_time.elapsed().as_millis() as u64,
+                files_skipped: 0,
             });
         }
     }
@@ -299,96 +357,255 @@ pub async fn index_folder(
             total_chunks: 0,
             failed_files: vec![],
             duration: start_time.elapsed().as_millis() as u64,
+            files_skipped: 0,
         });
     }
 
+    // Parse + chunk in a bounded pool of blocking workers; this task embeds
+    // and stores prepared files in batches as they arrive.
+    let workers = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(workers));
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<(PathBuf, FileOutcome)>(workers * 2);
+    let preparer = Arc::new(rag.document_preparer());
+    let stored_hashes = if force {
+        HashMap::new()
+    } else {
+        rag.indexed_content_hashes(folder_path).await.unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Failed to load content hashes; re-indexing all files");
+            HashMap::new()
+        })
+    };
+    let stored_hashes = Arc::new(stored_hashes);
+
+    let producer = {
+        let state = indexing_state.clone();
+        let space_id = space_id.to_string();
+        tokio::spawn(async move {
+            for file_path in files_to_process {
+                if !state.wait_if_paused().await {
+                    break;
+                }
+                let Ok(permit) = semaphore.clone().acquire_owned().await else {
+                    break;
+                };
+                let tx = tx.clone();
+                let preparer = preparer.clone();
+                let stored_hashes = stored_hashes.clone();
+                let metadata = file_metadata(&file_path, &space_id);
+                tokio::task::spawn_blocking(move || {
+                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        prepare_if_changed(&preparer, &file_path, metadata, &stored_hashes)
+                    }));
+                    let outcome = match result {
+                        Ok(outcome) => outcome,
+                        Err(panic_info) => FileOutcome::Failed(format!("Panic: {}", panic_message(&panic_info))),
+                    };
+                    drop(permit);
+                    let _ = tx.blocking_send((file_path, outcome));
+                });
+            }
+        })
+    };
+
+    let mut progress = FolderProgress {
+        total_files,
+        files_done: 0,
+        chunks_embedded: 0,
+        start_time,
+        last_emit: Instant::now(),
+    };
     let mut files_processed = 0;
-    let mut total_chunks = 0;
+    let mut files_skipped = 0;
     let mut failed_files = Vec::new();
-    let mut last_progress_time = Instant::now();
-
-    for (index, file_path) in files_to_process.iter().enumerate() {
-        // Pause loop
-        while indexing_state.is_paused() {
-            tokio::time::sleep(Duration::from_millis(100)).await;
-            if indexing_state.is_cancelled() {
-                break;
-            }
-        }
+    let mut batch: Vec<PreparedDocument> = Vec::new();
+    let mut batch_chunks = 0;
+    let mut discarded = 0;
 
+    // The channel closes once the producer and every in-flight worker are
+    // done, so this loop also drains outstanding work after a cancel.
+    while let Some((file_path, result)) = rx.recv().await {
         if indexing_state.is_cancelled() {
-            break;
+            // Nothing from a cancelled run is stored, so no file is left half-indexed
+            discarded += 1;
+            continue;
         }
 
-        // Throttled progress update
-        if last_progress_time.elapsed() > Duration::from_millis(100) {
-            let elapsed = start_time.elapsed().as_secs_f32();
-            let speed = if elapsed > 0.0 { files_processed as f32 / elapsed } else { 0.0 };
-            let eta = if speed > 0.0 { (total_files - files_processed) as f32 / speed } else { 0.0 };
-
-            let current_file = file_path.file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown");
-
-            emit_progress(
-                emitter,
-                current_file,
-                files_processed,
-                total_files,
-                (files_processed as f32 / total_files as f32) * 100.0,
-                &format!("Processing file {} of {}", index + 1, total_files),
-            );
-
-            last_progress_time = Instant::now();
-        }
-
-        // Process file with panic protection
-        let process_result = {
-            let result = std::panic::AssertUnwindSafe(
-                process_file_with_options(file_path, space_id, rag)
-            );
-            match result.catch_unwind().await {
-                Ok(r) => r,
-                Err(panic_info) => {
-                    let msg = if let Some(s) = panic_info.downcast_ref::<String>() {
-                        s.clone()
-                    } else if let Some(s) = panic_info.downcast_ref::<&str>() {
-                        s.to_string()
-                    } else {
-                        "Unknown panic during file processing".to_string()
-                    };
-                    Err(format!("Panic: {}", msg))
-                }
+        match result {
+            FileOutcome::Prepared(doc) => {
+                batch_chunks += doc.chunk_count();
+                batch.push(doc);
             }
-        };
-
-        match process_result {
-            Ok(chunks) => {
-                files_processed += 1;
-                total_chunks += chunks;
+            FileOutcome::Unchanged => {
+                files_skipped += 1;
+                progress.files_done += 1;
             }
-            Err(_e) => {
+            FileOutcome::Failed(e) => {
+                tracing::warn!(file = %file_path.display(), error = %e, "Failed to prepare file");
                 failed_files.push(file_path.to_string_lossy().to_string());
+                progress.files_done += 1;
             }
         }
+
+        if batch_chunks >= EMBED_BATCH_CHUNKS {
+            let (ok, failed, chunks) = ingest_batch(rag, std::mem::take(&mut batch)).await;
+            batch_chunks = 0;
+            files_processed += ok;
+            progress.files_done += ok + failed.len();
+            progress.chunks_embedded += chunks;
+            failed_files.extend(failed);
+        }
+
+        let current_file = file_path.file_name().and_then(|n| n.to_str()).unwrap_or("unknown");
+        progress.emit_throttled(emitter, current_file);
     }
 
-    emit_progress(emitter, "Completed", files_processed, total_files, 100.0, "Indexing complete");
+    let _ = producer.await;
+
+    if indexing_state.is_cancelled() {
+        discarded += batch.len();
+        tracing::info!(
+            stored_files = files_processed,
+            discarded_files = discarded,
+            "Indexing cancelled; discarded prepared but unstored files"
+        );
+    } else if !batch.is_empty() {
+        let (ok, failed, chunks) = ingest_batch(rag, batch).await;
+        files_processed += ok;
+        progress.files_done += ok + failed.len();
+        progress.chunks_embedded += chunks;
+        failed_files.extend(failed);
+    }
+
+    tracing::info!(
+        indexed = files_processed,
+        skipped = files_skipped,
+        failed = failed_files.len(),
+        "Folder indexing finished"
+    );
+    emit_progress(emitter, "Completed", progress.files_done, total_files, 100.0, "Indexing complete");
 
     Ok(IndexingResult {
         files_processed,
-        total_chunks,
+        total_chunks: progress.chunks_embedded,
         failed_files,
         duration: start_time.elapsed().as_millis() as u64,
+        files_skipped,
     })
 }
 
+/// Hash a file and, if it changed since it was last indexed, parse and chunk it
+fn prepare_if_changed(
+    preparer: &DocumentPreparer,
+    file_path: &Path,
+    mut metadata: HashMap<String, String>,
+    stored_hashes: &HashMap<String, String>,
+) -> FileOutcome {
+    let hash = match file_content_hash(file_path) {
+        Ok(hash) => hash,
+        Err(e) => return FileOutcome::Failed(format!("Failed to read file: {}", e)),
+    };
+    if stored_hashes.get(&normalize_source_path(file_path)) == Some(&hash) {
+        return FileOutcome::Unchanged;
+    }
+    metadata.insert(CONTENT_HASH_KEY.to_string(), hash);
+
+    match preparer.prepare(file_path, metadata) {
+        Ok(doc) => FileOutcome::Prepared(doc),
+        Err(e) => FileOutcome::Failed(format!("Failed to process file: {}", e)),
+    }
+}
+
+/// Aggregate progress for a folder run, emitted at most every 100ms
+struct FolderProgress {
+    total_files: usize,
+    files_done: usize,
+    chunks_embedded: usize,
+    start_time: Instant,
+    last_emit: Instant,
+}
+
+impl FolderProgress {
+    fn emit_throttled(&mut self, emitter: Option<&dyn EventEmitter>, current_file: &str) {
+        if self.last_emit.elapsed() < Duration::from_millis(100) {
+            return;
+        }
+        self.last_emit = Instant::now();
+        let Some(e) = emitter else { return };
+
+        let elapsed = self.start_time.elapsed().as_secs_f32();
+        let speed = if elapsed > 0.0 { self.files_done as f32 / elapsed } else { 0.0 };
+        let eta = if speed > 0.0 { (self.total_files - self.files_done) as f32 / speed } else { 0.0 };
+        let progress = IndexingProgress {
+            current_file: current_file.to_string(),
+            processed_files: self.files_done,
+            total_files: self.total_files,
+            percentage: (self.files_done as f32 / self.total_files as f32) * 100.0,
+            current_action: format!(
+                "Indexed {} of {} files ({} chunks)",
+                self.files_done, self.total_files, self.chunks_embedded
+            ),
+            eta_seconds: eta,
+            speed,
+            chunks_embedded: self.chunks_embedded,
+        };
+        e.emit("indexing-progress", serde_json::to_value(&progress).unwrap_or_default());
+    }
+}
+
+/// Embed and store a batch. Returns (files stored, failed paths, chunks stored).
+async fn ingest_batch(rag: &mut RAGEngine, batch: Vec<PreparedDocument>) -> (usize, Vec<String>, usize) {
+    let sources: Vec<String> = batch.iter().map(|doc| doc.source.clone()).collect();
+    let result = std::panic::AssertUnwindSafe(rag.ingest_prepared(batch))
+        .catch_unwind()
+        .await;
+    let results = match result {
+        Ok(results) => results,
+        Err(panic_info) => {
+            // Which files were stored before the panic is unknown, so the
+            // whole batch counts as failed
+            let message = panic_message(&panic_info);
+            tracing::error!(files = sources.len(), "Panic while embedding batch: {}", message);
+            for source in &sources {
+                tracing::warn!(source = %source, error = %message, "Failed to store file");
+            }
+            return (0, sources, 0);
+        }
+    };
+
+    let mut ok = 0;
+    let mut chunks = 0;
+    let mut failed = Vec::new();
+    for (source, result) in results {
+        match result {
+            Ok(ids) => {
+                ok += 1;
+                chunks += ids.len();
+            }
+            Err(e) => {
+                tracing::warn!(source = %source, error = %e, "Failed to store file");
+                failed.push(source);
+            }
+        }
+    }
+    (ok, failed, chunks)
+}
+
+fn panic_message(panic_info: &Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = panic_info.downcast_ref::<String>() {
+        s.clone()
+    } else if let Some(s) = panic_info.downcast_ref::<&str>() {
+        s.to_string()
+    } else {
+        "Unknown panic during file processing".to_string()
+    }
+}
+
 // ── Helpers ────────────────────────────────────────────────────────────────
 
 pub fn is_supported_file_type(extension: &str) -> bool {
     matches!(
         extension,
-        "txt" | "md" | "pdf" | "html" | "json" | "csv" | "docx" | "xlsx" | "pptx" | "rst" | "tex" |
+        "txt" | "md" | "pdf" | "html" | "json" | "csv" | "docx" | "xlsx" | "pptx" | "epub" | "rst" | "tex" |
         "rs" | "py" | "js" | "ts" | "jsx" | "tsx" | "java" | "cpp" | "c" | "h" |
         "hpp" | "cs" | "go" | "rb" | "php" | "swift" | "kt" | "scala" | "r" |
         "sh" | "bash" | "zsh" | "ps1" | "bat" | "cmd" |
@@ -399,15 +616,16 @@ pub fn is_supported_file_type(extension: &str) -> bool {
     )
 }
 
-async fn process_file_with_options(
-    file_path: &Path,
-    space_id: &str,
-    rag: &mut RAGEngine,
-) -> Result<usize, String> {
-    if !file_path.exists() {
-        return Err(format!("File does not exist: {}", file_path.display()));
-    }
+/// blake3 hash of a file's bytes, hex encoded
+pub fn file_content_hash(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
 
+/// Per-file metadata attached when indexing a folder
+fn file_metadata(file_path: &Path, space_id: &str) -> HashMap<String, String> {
     let mut metadata = HashMap::new();
     metadata.insert("space_id".to_string(), space_id.to_string());
     metadata.insert("file_path".to_string(), file_path.to_string_lossy().to_string());
@@ -427,12 +645,7 @@ async fn process_file_with_options(
             metadata.insert("filename".to_string(), filename.to_string_lossy().to_string());
         }
     }
-
-    let ids = rag.add_document_from_file(file_path, metadata)
-        .await
-        .map_err(|e| format!("Failed to process file: {}", e))?;
-
-    Ok(ids.len())
+    metadata
 }
 
 fn emit_progress(
@@ -452,6 +665,7 @@ fn emit_progress(
             current_action: action.to_string(),
             eta_seconds: 0.0,
             speed: 0.0,
+            chunks_embedded: 0,
         };
         e.emit("indexing-progress", serde_json::to_value(&progress).unwrap_or_default());
     }
@@ -6,6 +6,8 @@
 pub mod chat;
 pub mod config;
 pub mod context;
+pub mod dedup;
+pub mod document_diff;
 pub mod embeddings;
 pub mod graph;
 pub mod indexing;
@@ -14,6 +16,8 @@ pub mod rag_engine;
 pub mod reranking;
 pub mod search;
 pub mod space;
+pub mod space_archive;
+pub mod space_crypto;
 pub mod storage;
 pub mod templates;
 pub mod types;
@@ -27,9 +31,12 @@ pub mod rag;
 
 // Re-export primary types for convenience
 pub use config::RAGConfig;
-pub use rag_engine::RAGEngine;
+pub use rag_engine::{
+    EmbeddingDimensionMismatch, RAGEngine, ReembedPhase, ReembedProgress, ReembedRun, ReembedScope,
+};
 pub use types::{
-    Citation, ComprehensiveResult, DocumentFormat, MetadataFilter, SimpleSearchResult,
+    Citation, ComprehensiveResult, DocumentFormat, DocumentSort, MetadataFilter, RerankOptions,
+    SimpleSearchResult,
 };
 
 // Re-export comprehensive_system types for backward compatibility
@@ -43,7 +50,7 @@ pub mod comprehensive_system {
 pub use llm::{
     LLMManager, LLMConfig, LLMMode, LocalModel, ApiProvider,
     DeviceType, QuantizationType, GenerationConfig,
-    ProviderInfo, MemoryUsage, ModelManager,
+    ProviderInfo, MemoryUsage, ModelManager, LLMTimeoutError,
 };
 
 // Re-export common types
@@ -160,4 +160,51 @@ pub async fn download_model_tokenizer(model: &str, cache_dir: &Path) -> Result<(
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Fetch `tokenizer.json` (and `tokenizer_config.json` when the repo has
+/// one) from a Hugging Face repo into `dest_dir`. Returns the tokenizer path.
+pub async fn download_repo_tokenizer(repo_id: &str, dest_dir: &Path) -> Result<std::path::PathBuf> {
+    let client = reqwest::Client::builder()
+        .user_agent("Mozilla/5.0")
+        .timeout(std::time::Duration::from_secs(300))
+        .build()?;
+    let token = std::env::var("HUGGINGFACE_TOKEN").ok()
+        .or_else(|| std::env::var("HF_TOKEN").ok());
+    let fetch = |file: &str| {
+        let mut request = client.get(format!("https://huggingface.co/{}/resolve/main/{}", repo_id, file));
+        if let Some(token) = &token {
+            request = request.bearer_auth(token);
+        }
+        request.send()
+    };
+
+    fs::create_dir_all(dest_dir).await?;
+
+    let response = fetch("tokenizer.json").await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("{} has no tokenizer.json (HTTP {})", repo_id, response.status()));
+    }
+    let content = response.bytes().await?;
+    // Reject HTML error pages and truncated bodies before caching
+    serde_json::from_slice::<serde_json::Value>(&content)
+        .map_err(|e| anyhow!("tokenizer.json from {} is not valid JSON: {}", repo_id, e))?;
+    let tokenizer_path = dest_dir.join("tokenizer.json");
+    let tmp = dest_dir.join("tokenizer.json.tmp");
+    fs::write(&tmp, &content).await?;
+    fs::rename(&tmp, &tokenizer_path).await?;
+
+    let config_path = dest_dir.join("tokenizer_config.json");
+    if !config_path.exists() {
+        if let Ok(response) = fetch("tokenizer_config.json").await {
+            if response.status().is_success() {
+                if let Ok(content) = response.bytes().await {
+                    let _ = fs::write(&config_path, content).await;
+                }
+            }
+        }
+    }
+
+    tracing::info!(repo = repo_id, path = %tokenizer_path.display(), "Downloaded tokenizer for model");
+    Ok(tokenizer_path)
+}
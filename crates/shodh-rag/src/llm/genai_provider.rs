@@ -1,5 +1,9 @@
 //! ONNX Runtime GenAI LLM Provider - Stubbed for lightweight build
 //! Use API providers (OpenAI, Anthropic, etc.) instead.
+//!
+//! When re-enabled, `GenerationConfig::logit_bias` must be applied to the
+//! logits before sampling, after `super::check_generation_config` has
+//! rejected ids outside the vocabulary.
 
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
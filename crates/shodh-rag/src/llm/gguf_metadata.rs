@@ -0,0 +1,382 @@
+//! GGUF header inspection — reads model metadata without loading tensors.
+//!
+//! Only the key/value section at the start of the file is parsed, so this is
+//! cheap enough to run on every model the user picks.
+
+use anyhow::{Result, anyhow, bail};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+
+/// Strings longer than this are treated as a corrupt header (chat templates
+/// are a few KB; nothing legitimate comes close).
+const MAX_STRING_LEN: u64 = 64 * 1024 * 1024;
+
+/// Metadata read from a GGUF header
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GgufMetadata {
+    pub version: u32,
+    pub architecture: Option<String>,
+    pub name: Option<String>,
+    /// Training context length (`<arch>.context_length`)
+    pub context_length: Option<usize>,
+    /// Quantization derived from `general.file_type`, e.g. "Q4_K_M"
+    pub quantization: Option<String>,
+    pub chat_template: Option<String>,
+    pub vocab_size: Option<usize>,
+    /// `tokenizer.ggml.model`, e.g. "llama" or "gpt2"
+    pub tokenizer_model: Option<String>,
+    /// Whether the chat template understands tool definitions
+    pub supports_tool_calling: bool,
+}
+
+impl GgufMetadata {
+    pub fn has_embedded_tokenizer(&self) -> bool {
+        self.tokenizer_model.is_some()
+    }
+
+    /// Human-readable problems with running this model under the given settings
+    pub fn compatibility_warnings(&self, tokenizer_path: Option<&Path>, context_window: usize) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        if let Some(ctx) = self.context_length {
+            if ctx < context_window {
+                warnings.push(format!(
+                    "Model was trained with a {} token context, smaller than the configured {} tokens",
+                    ctx, context_window
+                ));
+            }
+        }
+
+        if let (Some(path), Some(vocab)) = (tokenizer_path, self.vocab_size) {
+            match tokenizer_json_vocab_size(path) {
+                Some(tok_vocab) if tok_vocab != vocab => warnings.push(format!(
+                    "Tokenizer {} has {} tokens but the model expects {}",
+                    path.display(), tok_vocab, vocab
+                )),
+                Some(_) => {}
+                None => warnings.push(format!("Could not read vocabulary from {}", path.display())),
+            }
+        }
+
+        if self.chat_template.is_none() {
+            warnings.push("Model has no embedded chat template; prompts will be sent as plain text".to_string());
+        }
+
+        warnings
+    }
+}
+
+/// Read the metadata section of a GGUF file
+pub fn read_gguf_metadata(path: &Path) -> Result<GgufMetadata> {
+    let file = File::open(path).map_err(|e| anyhow!("Cannot open {}: {}", path.display(), e))?;
+    let mut reader = BufReader::new(file);
+
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic).map_err(|_| anyhow!("{} is too short to be a GGUF file", path.display()))?;
+    if &magic != b"GGUF" {
+        bail!("{} is not a GGUF file", path.display());
+    }
+
+    let version = read_u32(&mut reader)?;
+    if version < 2 {
+        bail!("GGUF version {} is not supported; re-convert the model with a recent llama.cpp", version);
+    }
+    let _tensor_count = read_u64(&mut reader)?;
+    let kv_count = read_u64(&mut reader)?;
+
+    let mut meta = GgufMetadata { version, ..Default::default() };
+    let mut context_lengths = Vec::new();
+    let mut file_type = None;
+
+    for _ in 0..kv_count {
+        let key = read_string(&mut reader)?;
+        let value_type = read_u32(&mut reader)?;
+
+        match key.as_str() {
+            "general.architecture" => meta.architecture = read_value(&mut reader, value_type)?.into_string(),
+            "general.name" => meta.name = read_value(&mut reader, value_type)?.into_string(),
+            "general.file_type" => file_type = read_value(&mut reader, value_type)?.as_u64(),
+            "tokenizer.chat_template" => meta.chat_template = read_value(&mut reader, value_type)?.into_string(),
+            "tokenizer.ggml.model" => meta.tokenizer_model = read_value(&mut reader, value_type)?.into_string(),
+            "tokenizer.ggml.tokens" if value_type == TYPE_ARRAY => {
+                meta.vocab_size = Some(skip_array(&mut reader)? as usize);
+            }
+            k if k.ends_with(".context_length") => {
+                if let Some(ctx) = read_value(&mut reader, value_type)?.as_u64() {
+                    context_lengths.push((k.trim_end_matches(".context_length").to_string(), ctx as usize));
+                }
+            }
+            _ => skip_value(&mut reader, value_type)?,
+        }
+    }
+
+    // Keys can appear in any order, so match the context length to the architecture afterwards
+    meta.context_length = context_lengths.iter()
+        .find(|(arch, _)| Some(arch) == meta.architecture.as_ref())
+        .or(context_lengths.first())
+        .map(|(_, ctx)| *ctx);
+    meta.quantization = file_type.map(|t| file_type_name(t as u32).to_string());
+    meta.supports_tool_calling = meta.chat_template.as_deref()
+        .map(crate::llm::llamacpp_provider::template_has_tool_support)
+        .unwrap_or(false);
+
+    Ok(meta)
+}
+
+/// Number of entries in a tokenizer.json vocabulary, counting added tokens
+fn tokenizer_json_vocab_size(path: &Path) -> Option<usize> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let model_vocab = &json["model"]["vocab"];
+    let base = model_vocab.as_object().map(|v| v.len())
+        .or_else(|| model_vocab.as_array().map(|v| v.len()))?;
+    let max_added = json["added_tokens"].as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|t| t["id"].as_u64())
+        .map(|id| id as usize + 1)
+        .max()
+        .unwrap_or(0);
+    Some(base.max(max_added))
+}
+
+const TYPE_UINT8: u32 = 0;
+const TYPE_INT8: u32 = 1;
+const TYPE_UINT16: u32 = 2;
+const TYPE_INT16: u32 = 3;
+const TYPE_UINT32: u32 = 4;
+const TYPE_INT32: u32 = 5;
+const TYPE_FLOAT32: u32 = 6;
+const TYPE_BOOL: u32 = 7;
+const TYPE_STRING: u32 = 8;
+const TYPE_ARRAY: u32 = 9;
+const TYPE_UINT64: u32 = 10;
+const TYPE_INT64: u32 = 11;
+const TYPE_FLOAT64: u32 = 12;
+
+enum GgufValue {
+    Int(i128),
+    String(String),
+    Other,
+}
+
+impl GgufValue {
+    fn into_string(self) -> Option<String> {
+        match self {
+            GgufValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        match self {
+            GgufValue::Int(v) => u64::try_from(*v).ok(),
+            _ => None,
+        }
+    }
+}
+
+fn read_value<R: Read + Seek>(reader: &mut R, value_type: u32) -> Result<GgufValue> {
+    Ok(match value_type {
+        TYPE_UINT8 => GgufValue::Int(read_bytes::<1, _>(reader)?[0] as i128),
+        TYPE_INT8 => GgufValue::Int(read_bytes::<1, _>(reader)?[0] as i8 as i128),
+        TYPE_UINT16 => GgufValue::Int(u16::from_le_bytes(read_bytes(reader)?) as i128),
+        TYPE_INT16 => GgufValue::Int(i16::from_le_bytes(read_bytes(reader)?) as i128),
+        TYPE_UINT32 => GgufValue::Int(read_u32(reader)? as i128),
+        TYPE_INT32 => GgufValue::Int(i32::from_le_bytes(read_bytes(reader)?) as i128),
+        TYPE_UINT64 => GgufValue::Int(read_u64(reader)? as i128),
+        TYPE_INT64 => GgufValue::Int(i64::from_le_bytes(read_bytes(reader)?) as i128),
+        TYPE_STRING => GgufValue::String(read_string(reader)?),
+        _ => {
+            skip_value(reader, value_type)?;
+            GgufValue::Other
+        }
+    })
+}
+
+fn skip_value<R: Read + Seek>(reader: &mut R, value_type: u32) -> Result<()> {
+    match value_type {
+        TYPE_STRING => {
+            let len = read_string_len(reader)?;
+            reader.seek_relative(len as i64)?;
+        }
+        TYPE_ARRAY => {
+            skip_array(reader)?;
+        }
+        other => {
+            let size = scalar_size(other)?;
+            reader.seek_relative(size)?;
+        }
+    }
+    Ok(())
+}
+
+/// Skip over an array value, returning its element count
+fn skip_array<R: Read + Seek>(reader: &mut R) -> Result<u64> {
+    let elem_type = read_u32(reader)?;
+    let count = read_u64(reader)?;
+    match elem_type {
+        TYPE_STRING | TYPE_ARRAY => {
+            for _ in 0..count {
+                skip_value(reader, elem_type)?;
+            }
+        }
+        other => {
+            let total = count.checked_mul(scalar_size(other)? as u64)
+                .filter(|t| *t <= i64::MAX as u64)
+                .ok_or_else(|| anyhow!("GGUF array too large"))?;
+            reader.seek_relative(total as i64)?;
+        }
+    }
+    Ok(count)
+}
+
+fn scalar_size(value_type: u32) -> Result<i64> {
+    Ok(match value_type {
+        TYPE_UINT8 | TYPE_INT8 | TYPE_BOOL => 1,
+        TYPE_UINT16 | TYPE_INT16 => 2,
+        TYPE_UINT32 | TYPE_INT32 | TYPE_FLOAT32 => 4,
+        TYPE_UINT64 | TYPE_INT64 | TYPE_FLOAT64 => 8,
+        other => bail!("Unknown GGUF value type {}", other),
+    })
+}
+
+fn read_bytes<const N: usize, R: Read>(reader: &mut R) -> Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    reader.read_exact(&mut buf).map_err(|_| anyhow!("Unexpected end of GGUF header"))?;
+    Ok(buf)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32> {
+    Ok(u32::from_le_bytes(read_bytes(reader)?))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64> {
+    Ok(u64::from_le_bytes(read_bytes(reader)?))
+}
+
+fn read_string_len<R: Read>(reader: &mut R) -> Result<u64> {
+    let len = read_u64(reader)?;
+    if len > MAX_STRING_LEN {
+        bail!("GGUF string of {} bytes; header is corrupt", len);
+    }
+    Ok(len)
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String> {
+    let len = read_string_len(reader)?;
+    let mut buf = vec![0u8; len as usize];
+    reader.read_exact(&mut buf).map_err(|_| anyhow!("Unexpected end of GGUF header"))?;
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+/// Name for llama.cpp's `llama_ftype` values
+fn file_type_name(file_type: u32) -> &'static str {
+    match file_type {
+        0 => "F32",
+        1 => "F16",
+        2 => "Q4_0",
+        3 => "Q4_1",
+        7 => "Q8_0",
+        8 => "Q5_0",
+        9 => "Q5_1",
+        10 => "Q2_K",
+        11 => "Q3_K_S",
+        12 => "Q3_K_M",
+        13 => "Q3_K_L",
+        14 => "Q4_K_S",
+        15 => "Q4_K_M",
+        16 => "Q5_K_S",
+        17 => "Q5_K_M",
+        18 => "Q6_K",
+        19 => "IQ2_XXS",
+        20 => "IQ2_XS",
+        21 => "Q2_K_S",
+        22 => "IQ3_XS",
+        23 => "IQ3_XXS",
+        24 => "IQ1_S",
+        25 => "IQ4_NL",
+        26 => "IQ3_S",
+        27 => "IQ3_M",
+        28 => "IQ2_S",
+        29 => "IQ2_M",
+        30 => "IQ4_XS",
+        31 => "IQ1_M",
+        32 => "BF16",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Minimal GGUF v3 header with the given string/u32/string-array entries
+    pub(crate) fn write_gguf(path: &Path, strings: &[(&str, &str)], u32s: &[(&str, u32)], tokens: usize) {
+        fn put_str(buf: &mut Vec<u8>, s: &str) {
+            buf.extend_from_slice(&(s.len() as u64).to_le_bytes());
+            buf.extend_from_slice(s.as_bytes());
+        }
+        let mut buf = b"GGUF".to_vec();
+        buf.extend_from_slice(&3u32.to_le_bytes());
+        buf.extend_from_slice(&0u64.to_le_bytes());
+        buf.extend_from_slice(&((strings.len() + u32s.len() + 1) as u64).to_le_bytes());
+        for (k, v) in strings {
+            put_str(&mut buf, k);
+            buf.extend_from_slice(&TYPE_STRING.to_le_bytes());
+            put_str(&mut buf, v);
+        }
+        for (k, v) in u32s {
+            put_str(&mut buf, k);
+            buf.extend_from_slice(&TYPE_UINT32.to_le_bytes());
+            buf.extend_from_slice(&v.to_le_bytes());
+        }
+        put_str(&mut buf, "tokenizer.ggml.tokens");
+        buf.extend_from_slice(&TYPE_ARRAY.to_le_bytes());
+        buf.extend_from_slice(&TYPE_STRING.to_le_bytes());
+        buf.extend_from_slice(&(tokens as u64).to_le_bytes());
+        for i in 0..tokens {
+            put_str(&mut buf, &format!("tok{}", i));
+        }
+        std::fs::write(path, buf).unwrap();
+    }
+
+    #[test]
+    fn test_read_gguf_metadata() {
+        let dir = std::env::temp_dir().join(format!("shodh-gguf-meta-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("model.gguf");
+        write_gguf(
+            &path,
+            &[
+                ("general.architecture", "qwen2"),
+                ("tokenizer.ggml.model", "gpt2"),
+                ("tokenizer.chat_template", "{% if tools %}<tool_call>{% endif %}"),
+            ],
+            &[("llama.context_length", 2048), ("qwen2.context_length", 4096), ("general.file_type", 15)],
+            5,
+        );
+
+        let meta = read_gguf_metadata(&path).unwrap();
+        assert_eq!(meta.architecture.as_deref(), Some("qwen2"));
+        assert_eq!(meta.context_length, Some(4096));
+        assert_eq!(meta.quantization.as_deref(), Some("Q4_K_M"));
+        assert_eq!(meta.vocab_size, Some(5));
+        assert!(meta.has_embedded_tokenizer());
+        assert!(meta.supports_tool_calling);
+
+        let tokenizer = dir.join("tokenizer.json");
+        std::fs::write(&tokenizer, r#"{"model":{"vocab":{"a":0,"b":1}},"added_tokens":[{"id":2}]}"#).unwrap();
+        let warnings = meta.compatibility_warnings(Some(&tokenizer), 8192);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("4096"));
+        assert!(warnings[1].contains("has 3 tokens but the model expects 5"));
+
+        std::fs::write(&path, b"not a model").unwrap();
+        assert!(read_gguf_metadata(&path).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
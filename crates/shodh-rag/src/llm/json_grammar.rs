@@ -0,0 +1,172 @@
+//! JSON Schema → GBNF conversion for grammar-constrained local generation
+//!
+//! Covers the subset of JSON Schema used for structured output: objects with
+//! `properties`, arrays with `items`, primitive types, `enum`/`const` and
+//! `anyOf`/`oneOf`. Anything else falls back to an arbitrary JSON value.
+//! Every declared property is emitted, in key order; properties not listed in
+//! `required` may be `null`.
+
+use serde_json::Value as JsonValue;
+
+/// Shared rules for unconstrained JSON values and primitives
+const PRIMITIVE_RULES: &str = r#"value ::= object | array | string | number | ("true" | "false" | "null") ws
+object ::= "{" ws ( string ":" ws value ("," ws string ":" ws value)* )? "}" ws
+array ::= "[" ws ( value ("," ws value)* )? "]" ws
+string ::= "\"" ( [^"\\\x7F\x00-\x1F] | "\\" (["\\/bfnrt] | "u" [0-9a-fA-F] [0-9a-fA-F] [0-9a-fA-F] [0-9a-fA-F]) )* "\"" ws
+number ::= ("-"? ([0-9] | [1-9] [0-9]*)) ("." [0-9]+)? ([eE] [-+]? [0-9]+)? ws
+integer ::= "-"? ([0-9] | [1-9] [0-9]*) ws
+boolean ::= ("true" | "false") ws
+null ::= "null" ws
+ws ::= [ \t\n]*
+"#;
+
+/// Build a GBNF grammar whose `root` rule only accepts JSON matching `schema`
+pub fn json_schema_to_gbnf(schema: &JsonValue) -> String {
+    let mut builder = GrammarBuilder::default();
+    let root = builder.visit(schema, "root");
+    let mut grammar = format!("root ::= ws {}\n", root);
+    for (name, body) in &builder.rules {
+        grammar.push_str(&format!("{} ::= {}\n", name, body));
+    }
+    grammar.push_str(PRIMITIVE_RULES);
+    grammar
+}
+
+#[derive(Default)]
+struct GrammarBuilder {
+    rules: Vec<(String, String)>,
+}
+
+impl GrammarBuilder {
+    /// GBNF expression for `schema`, adding named rules for composite nodes
+    fn visit(&mut self, schema: &JsonValue, hint: &str) -> String {
+        if let Some(values) = schema.get("enum").and_then(|v| v.as_array()) {
+            return alternatives(values.iter().map(json_literal));
+        }
+        if let Some(value) = schema.get("const") {
+            return json_literal(value);
+        }
+        if let Some(options) = schema.get("anyOf").or_else(|| schema.get("oneOf")).and_then(|v| v.as_array()) {
+            let exprs: Vec<String> = options.iter()
+                .enumerate()
+                .map(|(i, option)| self.visit(option, &format!("{}-{}", hint, i)))
+                .collect();
+            return alternatives(exprs.into_iter());
+        }
+
+        match schema.get("type") {
+            Some(JsonValue::String(ty)) => self.visit_type(ty, schema, hint),
+            Some(JsonValue::Array(types)) => {
+                let exprs: Vec<String> = types.iter()
+                    .filter_map(|t| t.as_str())
+                    .map(|ty| self.visit_type(ty, schema, hint))
+                    .collect();
+                alternatives(exprs.into_iter())
+            }
+            _ => "value".to_string(),
+        }
+    }
+
+    fn visit_type(&mut self, ty: &str, schema: &JsonValue, hint: &str) -> String {
+        match ty {
+            "object" => self.visit_object(schema, hint),
+            "array" => self.visit_array(schema, hint),
+            "string" | "number" | "integer" | "boolean" | "null" => ty.to_string(),
+            _ => "value".to_string(),
+        }
+    }
+
+    fn visit_object(&mut self, schema: &JsonValue, hint: &str) -> String {
+        let Some(properties) = schema.get("properties").and_then(|v| v.as_object()).filter(|p| !p.is_empty()) else {
+            return "object".to_string();
+        };
+        let required: Vec<&str> = schema.get("required")
+            .and_then(|v| v.as_array())
+            .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default();
+
+        let members: Vec<String> = properties.iter()
+            .map(|(key, prop)| {
+                let mut expr = self.visit(prop, &format!("{}-{}", hint, key));
+                if !required.contains(&key.as_str()) {
+                    expr = format!("{} | null", expr);
+                }
+                format!("{} \":\" ws ({})", json_literal(&JsonValue::String(key.clone())), expr)
+            })
+            .collect();
+        let body = format!("\"{{\" ws {} \"}}\" ws", members.join(" \",\" ws "));
+        self.add_rule(hint, body)
+    }
+
+    fn visit_array(&mut self, schema: &JsonValue, hint: &str) -> String {
+        let Some(items) = schema.get("items") else {
+            return "array".to_string();
+        };
+        let item = self.visit(items, &format!("{}-item", hint));
+        let body = format!("\"[\" ws ( {item} (\",\" ws {item})* )? \"]\" ws", item = item);
+        self.add_rule(hint, body)
+    }
+
+    /// Register a rule under a unique name derived from `hint`
+    fn add_rule(&mut self, hint: &str, body: String) -> String {
+        let base: String = hint.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+            .collect();
+        let base = format!("s-{}", base.trim_matches('-'));
+        let mut name = base.clone();
+        let mut n = 1;
+        while self.rules.iter().any(|(existing, _)| *existing == name) {
+            n += 1;
+            name = format!("{}-{}", base, n);
+        }
+        self.rules.push((name.clone(), body));
+        name
+    }
+}
+
+fn alternatives(exprs: impl Iterator<Item = String>) -> String {
+    let exprs: Vec<String> = exprs.collect();
+    match exprs.len() {
+        0 => "value".to_string(),
+        1 => exprs.into_iter().next().unwrap_or_default(),
+        _ => format!("({})", exprs.join(" | ")),
+    }
+}
+
+/// GBNF literal matching the serialized JSON `value`, followed by whitespace
+fn json_literal(value: &JsonValue) -> String {
+    let text = value.to_string().replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{}\" ws", text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_object_properties_and_enums() {
+        let grammar = json_schema_to_gbnf(&json!({
+            "type": "object",
+            "properties": {
+                "label": {"type": "string"},
+                "kind": {"enum": ["text", "date"]},
+                "tags": {"type": "array", "items": {"type": "string"}}
+            },
+            "required": ["label", "kind"]
+        }));
+        assert!(grammar.starts_with("root ::= ws s-root\n"));
+        assert!(grammar.contains(r#""\"label\"" ws ":" ws (string) "," ws"#));
+        assert!(grammar.contains(r#"("\"text\"" ws | "\"date\"" ws)"#));
+        // `tags` is optional, so it may be null
+        assert!(grammar.contains(r#""\"tags\"" ws ":" ws (s-root-tags | null)"#));
+        assert!(grammar.contains(r#"s-root-tags ::= "[" ws ( string ("," ws string)* )? "]" ws"#));
+    }
+
+    #[test]
+    fn test_untyped_schema_accepts_any_value() {
+        let grammar = json_schema_to_gbnf(&json!({}));
+        assert!(grammar.starts_with("root ::= ws value\n"));
+        assert!(grammar.contains("value ::= object | array"));
+    }
+}
@@ -3,26 +3,45 @@
 //! Loads GGUF models directly and runs inference on CPU.
 //! Streaming is handled via `spawn_blocking` + mpsc channels since
 //! llama.cpp is synchronous and CPU-bound.
+//!
+//! Tool calling uses the model's embedded chat template (Hermes/Qwen style
+//! `<tool_call>` tags) with a GBNF grammar that constrains tool-call JSON.
+//! Structured output (`GenerationConfig::json_schema`) is likewise enforced
+//! with a grammar built from the schema.
 
 use anyhow::{Result, Context as AnyhowContext, anyhow};
 use async_trait::async_trait;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
+use parking_lot::Mutex;
+use regex::Regex;
 use tokio::sync::mpsc;
 
 use llama_cpp_2::context::params::LlamaContextParams;
 use llama_cpp_2::llama_backend::LlamaBackend;
 use llama_cpp_2::llama_batch::LlamaBatch;
 use llama_cpp_2::model::params::LlamaModelParams;
-use llama_cpp_2::model::LlamaModel;
+use llama_cpp_2::model::{LlamaChatMessage, LlamaChatTemplate, LlamaModel};
 use llama_cpp_2::sampling::LlamaSampler;
+use llama_cpp_2::token::logit_bias::LlamaLogitBias;
 use llama_cpp_2::token::LlamaToken;
 
 use super::{
-    DeviceType, GenerationConfig, LLMProvider, LocalModel, MemoryUsage, ProviderInfo,
-    QuantizationType,
+    check_generation_config, ChatMessage, ChatResponse, ChatRole, DeviceType, GenerationConfig,
+    LLMProvider, LocalModel, MemoryUsage, ProviderInfo, QuantizationType, ToolCall, ToolSchema,
 };
 use super::streaming::TokenStream;
+use super::json_grammar::json_schema_to_gbnf;
+use super::prompt_cache::{common_prefix_len, CachedPrompt, PromptCache};
+
+/// Tokens in each inference context. Models trained on longer contexts
+/// still run with this many, so it caps the reported context window.
+pub const LLAMACPP_CONTEXT_TOKENS: usize = 4096;
+
+/// A `<tool_call>{...}</tool_call>` block in model output (Hermes/Qwen style)
+static TOOL_CALL_TAG_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<tool_call>\s*(.*?)\s*</tool_call>").expect("tool call tag regex is valid")
+});
 
 /// Information about the loaded model for metadata/info reporting.
 struct ModelInfo {
@@ -35,6 +54,12 @@ pub struct LlamaCppProvider {
     model: Arc<LlamaModel>,
     backend: Arc<LlamaBackend>,
     info: ModelInfo,
+    /// Chat template embedded in the GGUF metadata, if any
+    chat_template: Option<LlamaChatTemplate>,
+    /// True when the chat template understands tool definitions / `<tool_call>` tags
+    template_supports_tools: bool,
+    /// Context state from earlier turns, keyed by conversation
+    prompt_cache: Arc<Mutex<PromptCache>>,
 }
 
 // SAFETY: LlamaModel and LlamaBackend are thread-safe for read-only operations.
@@ -68,15 +93,34 @@ impl LlamaCppProvider {
         let model = LlamaModel::load_from_file(&backend, &gguf_path, &model_params)
             .map_err(|e| anyhow!("Failed to load GGUF model from {}: {:?}", gguf_path.display(), e))?;
 
+        // Custom models report their own training context in the GGUF header
+        let trained_context = match &model_variant {
+            LocalModel::Custom { .. } => super::gguf_metadata::read_gguf_metadata(&gguf_path)
+                .ok()
+                .and_then(|meta| meta.context_length)
+                .unwrap_or_else(|| Self::model_context_window(&model_variant)),
+            _ => Self::model_context_window(&model_variant),
+        };
+        let context_window = trained_context.min(LLAMACPP_CONTEXT_TOKENS);
+
         let info = ModelInfo {
             name: Self::model_display_name(&model_variant),
-            context_window: Self::model_context_window(&model_variant),
+            context_window,
             size_mb: (model_variant.size_gb() * 1024.0) as usize,
         };
 
+        let chat_template = model.chat_template(None).ok();
+        let template_supports_tools = chat_template
+            .as_ref()
+            .and_then(|t| t.to_str().ok())
+            .map(template_has_tool_support)
+            .unwrap_or(false);
+
         tracing::info!(
             model = %info.name,
             context_window = info.context_window,
+            has_chat_template = chat_template.is_some(),
+            tool_calling = template_supports_tools,
             "llama.cpp model loaded successfully"
         );
 
@@ -84,9 +128,89 @@ impl LlamaCppProvider {
             model: Arc::new(model),
             backend: Arc::new(backend),
             info,
+            chat_template,
+            template_supports_tools,
+            prompt_cache: Arc::new(Mutex::new(PromptCache::new(0))),
         })
     }
 
+    /// Keep up to `max_bytes` of per-conversation context state so follow-up
+    /// turns only decode the new part of the prompt (0 disables)
+    pub fn with_prompt_cache(self, max_bytes: usize) -> Self {
+        *self.prompt_cache.lock() = PromptCache::new(max_bytes);
+        self
+    }
+
+    /// Render chat messages through the model's chat template. Tool schemas are
+    /// injected into the system message; prior tool calls and results are
+    /// rendered as `<tool_call>` / `<tool_response>` blocks.
+    fn build_chat_prompt(&self, messages: &[ChatMessage], tools: &[ToolSchema]) -> Result<String> {
+        let template = match &self.chat_template {
+            Some(t) => t,
+            None => {
+                // No template in GGUF metadata: flatten like the trait default
+                return Ok(messages.iter()
+                    .filter_map(|m| m.content.as_ref().map(|c| format!("{:?}: {}", m.role, c)))
+                    .collect::<Vec<_>>()
+                    .join("\n"));
+            }
+        };
+
+        let tool_block = if tools.is_empty() { None } else { Some(format_tool_system_block(tools)) };
+        let mut chat = Vec::with_capacity(messages.len() + 1);
+        let mut system_seen = false;
+
+        for m in messages {
+            let (role, content) = match m.role {
+                ChatRole::System => {
+                    system_seen = true;
+                    let mut content = m.content.clone().unwrap_or_default();
+                    if let Some(ref block) = tool_block {
+                        content.push_str("\n\n");
+                        content.push_str(block);
+                    }
+                    ("system", content)
+                }
+                ChatRole::User => ("user", m.content.clone().unwrap_or_default()),
+                ChatRole::Assistant => {
+                    let content = match &m.tool_calls {
+                        Some(calls) => calls.iter()
+                            .map(|tc| format!(
+                                "<tool_call>\n{{\"name\": \"{}\", \"arguments\": {}}}\n</tool_call>",
+                                tc.name, tc.arguments
+                            ))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                        None => m.content.clone().unwrap_or_default(),
+                    };
+                    ("assistant", content)
+                }
+                ChatRole::Tool => (
+                    "user",
+                    format!("<tool_response>\n{}\n</tool_response>", m.content.clone().unwrap_or_default()),
+                ),
+            };
+            chat.push(
+                LlamaChatMessage::new(role.to_string(), content)
+                    .map_err(|e| anyhow!("Invalid chat message: {:?}", e))?,
+            );
+        }
+
+        if !system_seen {
+            if let Some(block) = tool_block {
+                chat.insert(
+                    0,
+                    LlamaChatMessage::new("system".to_string(), block)
+                        .map_err(|e| anyhow!("Invalid chat message: {:?}", e))?,
+                );
+            }
+        }
+
+        self.model
+            .apply_chat_template(template, &chat, true)
+            .map_err(|e| anyhow!("Failed to apply chat template: {:?}", e))
+    }
+
     /// Resolve GGUF file path from LocalModel variant and cache directory.
     fn resolve_model_path(model: &LocalModel, cache_dir: &Path) -> Result<PathBuf> {
         // If cache_dir itself is a GGUF file, use it directly
@@ -185,10 +309,14 @@ impl LlamaCppProvider {
         backend: &LlamaBackend,
         prompt: &str,
         config: &GenerationConfig,
+        grammar: Option<&str>,
         token_sender: Option<mpsc::Sender<String>>,
+        prompt_cache: &Mutex<PromptCache>,
     ) -> Result<String> {
+        check_generation_config(config, model.n_vocab().max(0) as u32)?;
+
         // Limit context to a reasonable size for inference (not the model's max)
-        let n_ctx = 4096u32.min(8192);
+        let n_ctx = LLAMACPP_CONTEXT_TOKENS as u32;
 
         let ctx_params = LlamaContextParams::default().with_n_ctx(std::num::NonZeroU32::new(n_ctx));
         let mut ctx = model
@@ -218,6 +346,34 @@ impl LlamaCppProvider {
             tokens
         };
         let n_prompt = tokens.len();
+        let mut kv_tokens: Vec<i32> = tokens.iter().map(|t| t.0).collect();
+
+        // Restore the conversation's previous state and drop whatever follows
+        // the prefix it shares with this prompt (edited turns, a changed
+        // system prompt, or the previous reply as sampled rather than rendered)
+        let cache_key = config.conversation_id.as_deref()
+            .filter(|_| prompt_cache.lock().is_enabled())
+            .map(|id| PromptCache::key(id, &kv_tokens));
+        let cached = cache_key.as_deref().and_then(|key| prompt_cache.lock().take(key));
+        let mut n_reused = 0usize;
+        if let Some(cached) = cached {
+            // Always decode at least the last prompt token to get fresh logits
+            let common = common_prefix_len(&cached.tokens, &kv_tokens).min(n_prompt - 1);
+            if common > 0 {
+                // SAFETY: the state was copied from a context of this model created with the same params
+                let restored = unsafe { ctx.set_state_data(&cached.state) } > 0;
+                if restored && ctx.clear_kv_cache_seq(Some(0), Some(common as u32), None).unwrap_or(false) {
+                    n_reused = common;
+                } else {
+                    ctx.clear_kv_cache();
+                }
+            }
+            tracing::debug!(
+                reused_tokens = n_reused,
+                prompt_tokens = n_prompt,
+                "Restored cached prompt prefix"
+            );
+        }
 
         // Feed prompt tokens into context via batch.
         // Process in chunks of n_batch (default 2048) to avoid exceeding
@@ -225,7 +381,7 @@ impl LlamaCppProvider {
         let n_batch = 2048usize;
         let mut batch = LlamaBatch::new(n_batch, 1);
 
-        let mut processed = 0usize;
+        let mut processed = n_reused;
         while processed < n_prompt {
             batch.clear();
             let chunk_end = (processed + n_batch).min(n_prompt);
@@ -245,13 +401,27 @@ impl LlamaCppProvider {
 
         // Set up sampler chain with repetition penalty to prevent loops.
         // penalties(last_n, repeat_penalty, freq_penalty, presence_penalty)
-        let mut sampler = LlamaSampler::chain_simple([
+        let mut samplers = Vec::with_capacity(7);
+        if let Some(grammar) = grammar {
+            // Grammar goes first so later samplers only see grammar-legal tokens
+            samplers.push(LlamaSampler::grammar(model, grammar, "root"));
+        }
+        if !config.logit_bias.is_empty() {
+            let biases: Vec<LlamaLogitBias> = config
+                .logit_bias
+                .iter()
+                .map(|(&token, &bias)| LlamaLogitBias::new(LlamaToken::new(token as i32), bias))
+                .collect();
+            samplers.push(LlamaSampler::logit_bias(model.n_vocab(), &biases));
+        }
+        samplers.extend([
             LlamaSampler::penalties(256, 1.15, 0.0, 0.0),
             LlamaSampler::temp(config.temperature),
             LlamaSampler::top_p(config.top_p, 1),
             LlamaSampler::top_k(config.top_k as i32),
             LlamaSampler::dist(config.seed.unwrap_or(0) as u32),
         ]);
+        let mut sampler = LlamaSampler::chain_simple(samplers);
 
         // Generation loop
         let max_tokens = config.max_tokens.min(2048);
@@ -278,6 +448,10 @@ impl LlamaCppProvider {
             if n_decoded >= max_tokens {
                 break;
             }
+            if config.cancel.is_cancelled() {
+                tracing::info!(tokens = n_decoded, "Generation cancelled, stopping decode loop");
+                break;
+            }
 
             // Sample next token
             let new_token = sampler.sample(&ctx, -1);
@@ -352,10 +526,20 @@ impl LlamaCppProvider {
 
             ctx.decode(&mut batch)
                 .map_err(|e| anyhow!("Decode step {} failed: {:?}", n_decoded, e))?;
+            kv_tokens.push(new_token.0);
+        }
+
+        if let Some(key) = cache_key {
+            let mut state = vec![0u8; ctx.get_state_size()];
+            // SAFETY: `state` is sized by `get_state_size` for this context
+            let written = unsafe { ctx.copy_state_data(state.as_mut_ptr()) };
+            state.truncate(written);
+            prompt_cache.lock().insert(key, CachedPrompt { tokens: kv_tokens, state });
         }
 
         tracing::debug!(
             prompt_tokens = n_prompt,
+            reused_tokens = n_reused,
             generated_tokens = n_decoded,
             "llama.cpp inference complete"
         );
@@ -392,16 +576,155 @@ fn has_repetition(text: &str) -> bool {
     false
 }
 
+/// Heuristic check that a Jinja chat template renders tool definitions.
+/// Hermes/Qwen-style templates reference the `tools` variable and emit
+/// `<tool_call>` tags. `tools` must be a whole identifier, so names such as
+/// `toolset` or `no_tools` don't count.
+pub(crate) fn template_has_tool_support(template: &str) -> bool {
+    template.contains("<tool_call>") || contains_identifier(template, "tools")
+}
+
+/// Whether `ident` occurs in `text` with no identifier character on either side
+fn contains_identifier(text: &str, ident: &str) -> bool {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    text.match_indices(ident).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + ident.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
+/// System-prompt block describing the available tools (Hermes format).
+fn format_tool_system_block(tools: &[ToolSchema]) -> String {
+    let defs = tools.iter()
+        .map(|t| serde_json::json!({
+            "type": "function",
+            "function": {
+                "name": t.name,
+                "description": t.description,
+                "parameters": t.parameters,
+            }
+        }).to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "# Tools\n\nYou may call one or more functions to assist with the user query.\n\n\
+         You are provided with function signatures within <tools></tools> XML tags:\n<tools>\n{}\n</tools>\n\n\
+         For each function call, return a json object with function name and arguments within \
+         <tool_call></tool_call> XML tags:\n<tool_call>\n{{\"name\": <function-name>, \"arguments\": <args-json-object>}}\n</tool_call>\n\n\
+         If no function is needed, answer the user directly in plain text.",
+        defs
+    )
+}
+
+/// GBNF grammar that lets the model either answer in free text or emit one or
+/// more `<tool_call>` blocks whose `name` is restricted to the known tools.
+fn build_tool_grammar(tools: &[ToolSchema]) -> String {
+    let names = tools.iter()
+        .map(|t| format!("\"\\\"{}\\\"\"", t.name.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    format!(
+        r#"root ::= toolcall+ | freetext
+toolcall ::= "<tool_call>" ws "{{" ws "\"name\"" ws ":" ws toolname ws "," ws "\"arguments\"" ws ":" ws object ws "}}" ws "</tool_call>" ws
+toolname ::= {names}
+freetext ::= [^<] [^\x00]*
+value ::= object | array | string | number | ("true" | "false" | "null") ws
+object ::= "{{" ws ( string ":" ws value ("," ws string ":" ws value)* )? "}}" ws
+array ::= "[" ws ( value ("," ws value)* )? "]" ws
+string ::= "\"" ( [^"\\\x7F\x00-\x1F] | "\\" (["\\/bfnrt] | "u" [0-9a-fA-F] [0-9a-fA-F] [0-9a-fA-F] [0-9a-fA-F]) )* "\"" ws
+number ::= ("-"? ([0-9] | [1-9] [0-9]*)) ("." [0-9]+)? ([eE] [-+]? [0-9]+)? ws
+ws ::= [ \t\n]*
+"#,
+        names = names
+    )
+}
+
+/// Extract tool calls from model output. Prefers `<tool_call>` blocks; falls
+/// back to any bare JSON object whose `name` matches a known tool.
+fn parse_tool_calls(output: &str, tools: &[ToolSchema]) -> Vec<ToolCall> {
+    let known = |name: &str| tools.iter().any(|t| t.name == name);
+    let to_call = |value: &serde_json::Value| -> Option<ToolCall> {
+        let name = value["name"].as_str()?;
+        if !known(name) {
+            return None;
+        }
+        let args = value.get("arguments").or_else(|| value.get("parameters"));
+        let arguments = match args {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(v) => v.to_string(),
+            None => "{}".to_string(),
+        };
+        Some(ToolCall {
+            id: format!("call_{}", uuid::Uuid::new_v4().to_string().split('-').next().unwrap_or("0")),
+            name: name.to_string(),
+            arguments,
+        })
+    };
+
+    let tagged: Vec<ToolCall> = TOOL_CALL_TAG_RE.captures_iter(output)
+        .filter_map(|c| serde_json::from_str::<serde_json::Value>(&c[1]).ok())
+        .filter_map(|v| to_call(&v))
+        .collect();
+    if !tagged.is_empty() {
+        return tagged;
+    }
+
+    // Fallback: scan for balanced top-level JSON objects
+    let mut calls = Vec::new();
+    let bytes = output.as_bytes();
+    let mut start = None;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, &b) in bytes.iter().enumerate() {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match b {
+            b'"' if depth > 0 => in_string = true,
+            b'{' => {
+                if depth == 0 {
+                    start = Some(i);
+                }
+                depth += 1;
+            }
+            b'}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    if let Some(s) = start.take() {
+                        if let Ok(v) = serde_json::from_str::<serde_json::Value>(&output[s..=i]) {
+                            if let Some(tc) = to_call(&v) {
+                                calls.push(tc);
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    calls
+}
+
 #[async_trait]
 impl LLMProvider for LlamaCppProvider {
     async fn generate(&self, prompt: &str, config: &GenerationConfig) -> Result<String> {
         let model = Arc::clone(&self.model);
         let backend = Arc::clone(&self.backend);
+        let prompt_cache = Arc::clone(&self.prompt_cache);
         let prompt = prompt.to_string();
         let config = config.clone();
+        let grammar = config.json_schema.as_ref().map(json_schema_to_gbnf);
 
         tokio::task::spawn_blocking(move || {
-            Self::run_inference(&model, &backend, &prompt, &config, None)
+            Self::run_inference(&model, &backend, &prompt, &config, grammar.as_deref(), None, &prompt_cache)
         })
         .await
         .map_err(|e| anyhow!("Inference task panicked: {}", e))?
@@ -414,13 +737,14 @@ impl LLMProvider for LlamaCppProvider {
     ) -> Result<TokenStream> {
         let model = Arc::clone(&self.model);
         let backend = Arc::clone(&self.backend);
+        let prompt_cache = Arc::clone(&self.prompt_cache);
         let prompt = prompt.to_string();
         let config = config.clone();
 
         let (tx, rx) = mpsc::channel(256);
 
         tokio::task::spawn_blocking(move || {
-            if let Err(e) = Self::run_inference(&model, &backend, &prompt, &config, Some(tx)) {
+            if let Err(e) = Self::run_inference(&model, &backend, &prompt, &config, None, Some(tx), &prompt_cache) {
                 tracing::error!("Streaming inference failed: {}", e);
             }
         });
@@ -438,13 +762,51 @@ impl LLMProvider for LlamaCppProvider {
         self.generate(&prompt, config).await
     }
 
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[ToolSchema],
+        config: &GenerationConfig,
+    ) -> Result<ChatResponse> {
+        let use_tools = !tools.is_empty() && self.template_supports_tools;
+        let prompt = self.build_chat_prompt(messages, if use_tools { tools } else { &[] })?;
+        let grammar = if use_tools { Some(build_tool_grammar(tools)) } else { None };
+
+        let model = Arc::clone(&self.model);
+        let backend = Arc::clone(&self.backend);
+        let prompt_cache = Arc::clone(&self.prompt_cache);
+        let config = config.clone();
+
+        let output = tokio::task::spawn_blocking(move || {
+            Self::run_inference(&model, &backend, &prompt, &config, grammar.as_deref(), None, &prompt_cache)
+        })
+        .await
+        .map_err(|e| anyhow!("Inference task panicked: {}", e))??;
+
+        if use_tools {
+            let calls = parse_tool_calls(&output, tools);
+            if !calls.is_empty() {
+                return Ok(ChatResponse::ToolCalls(calls));
+            }
+        }
+        Ok(ChatResponse::Content(output))
+    }
+
+    fn supports_json_schema(&self) -> bool {
+        true
+    }
+
+    fn forget_conversation(&self, conversation_id: &str) {
+        self.prompt_cache.lock().invalidate(conversation_id);
+    }
+
     fn info(&self) -> ProviderInfo {
         ProviderInfo {
             name: format!("llama.cpp ({})", self.info.name),
             model: self.info.name.clone(),
             context_window: self.info.context_window,
             supports_streaming: true,
-            supports_functions: false,
+            supports_functions: self.template_supports_tools,
             is_local: true,
         }
     }
@@ -461,3 +823,49 @@ impl LLMProvider for LlamaCppProvider {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search_tool() -> ToolSchema {
+        ToolSchema {
+            name: "search_documents".to_string(),
+            description: "Search the knowledge base".to_string(),
+            parameters: serde_json::json!({"type": "object", "properties": {"query": {"type": "string"}}}),
+        }
+    }
+
+    #[test]
+    fn test_parse_tagged_tool_call() {
+        let output = "<tool_call>\n{\"name\": \"search_documents\", \"arguments\": {\"query\": \"rust\"}}\n</tool_call>";
+        let calls = parse_tool_calls(output, &[search_tool()]);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "search_documents");
+        assert_eq!(calls[0].arguments, r#"{"query":"rust"}"#);
+    }
+
+    #[test]
+    fn test_parse_untagged_fallback_ignores_unknown_tools() {
+        let output = r#"Sure! {"name": "delete_everything", "arguments": {}} then {"name": "search_documents", "arguments": {"query": "a {b}"}}"#;
+        let calls = parse_tool_calls(output, &[search_tool()]);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "search_documents");
+    }
+
+    #[test]
+    fn test_template_tool_detection_matches_whole_words() {
+        assert!(template_has_tool_support("{%- if tools %}{{ tools | tojson }}{% endif %}"));
+        assert!(template_has_tool_support("{% if messages[0].tools is defined %}"));
+        assert!(template_has_tool_support("<tool_call>\n{{ message.content }}"));
+        assert!(!template_has_tool_support("{% set toolset = [] %}{{ no_tools }}"));
+        assert!(!template_has_tool_support("Use the available toolsmith notes."));
+    }
+
+    #[test]
+    fn test_tool_grammar_restricts_names() {
+        let grammar = build_tool_grammar(&[search_tool()]);
+        assert!(grammar.contains(r#"toolname ::= "\"search_documents\"""#));
+        assert!(grammar.starts_with("root ::="));
+    }
+}
@@ -3,7 +3,9 @@
 
 use anyhow::{Result, anyhow};
 use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::LazyLock;
 use async_trait::async_trait;
 use tokio::sync::mpsc;
 use serde_json::Value as JsonValue;
@@ -26,14 +28,18 @@ pub mod hf_tokenizer;
 pub mod download_tokenizers;
 pub mod tokenizer_loader;
 pub mod gqa_cache;
+pub mod json_grammar;
+pub mod gguf_metadata;
+pub mod prompt_cache;
 
-pub use llamacpp_provider::LlamaCppProvider;
+pub use llamacpp_provider::{LlamaCppProvider, LLAMACPP_CONTEXT_TOKENS};
 pub use genai_provider::GenAIProvider;
 pub use local::LocalModelProvider;
 pub use external::ExternalProvider;
 pub use simple_external::SimpleExternalProvider;
-pub use streaming::{StreamingResponse, TokenStream};
-pub use model_manager::{ModelManager, ModelDownloader};
+pub use streaming::{StreamingResponse, TokenStream, StreamErrorSlot, StreamUsageSlot, CancellationToken, GenerationRegistry};
+pub use model_manager::{ModelManager, ModelDownloader, ModelManifest, ModelFile, PartialDownload, RepairReport, FileProgress};
+pub use gguf_metadata::{GgufMetadata, read_gguf_metadata};
 
 
 /// LLM operation mode
@@ -117,11 +123,45 @@ pub enum ApiProvider {
 }
 
 /// Device type for local models
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum DeviceType {
     Cpu,
     Cuda(usize),  // GPU index
     Metal,        // Apple Silicon
+    Auto,         // Resolved via HardwareInfo at initialize time
+}
+
+impl DeviceType {
+    /// Resolve `Auto` to the recommended concrete device; other variants pass through.
+    pub fn resolve(&self, hw: &HardwareInfo) -> DeviceType {
+        match self {
+            DeviceType::Auto => hw.recommended_device.clone(),
+            other => other.clone(),
+        }
+    }
+
+    /// Resolve `Auto` for `model`: the recommended device, unless that is a
+    /// GPU whose free VRAM is known and can't hold even the 4-bit model, in
+    /// which case the CPU. Other variants pass through.
+    pub fn resolve_for(&self, hw: &HardwareInfo, model: &LocalModel) -> DeviceType {
+        let resolved = self.resolve(hw);
+        let too_big = hw.vram_free_mb.is_some() && hw.recommend_quantization(model).is_none();
+        if matches!(self, DeviceType::Auto) && resolved.uses_gpu_backend() && too_big {
+            tracing::warn!(
+                vram_free_mb = ?hw.vram_free_mb,
+                model_gb = model.size_gb(),
+                "Model does not fit in free VRAM, using CPU"
+            );
+            return DeviceType::Cpu;
+        }
+        resolved
+    }
+
+    /// Whether this device runs on the GPU backend (ONNX Runtime GenAI)
+    /// rather than llama.cpp on CPU. `Auto` must be resolved first.
+    pub fn uses_gpu_backend(&self) -> bool {
+        matches!(self, DeviceType::Cuda(_) | DeviceType::Metal)
+    }
 }
 
 /// Quantization type for model compression
@@ -146,6 +186,73 @@ pub struct LLMConfig {
     pub streaming: bool,
     pub context_window: usize,
     pub system_prompt: Option<String>,
+    /// Mark system prompt blocks as cacheable (Anthropic prompt caching)
+    #[serde(default)]
+    pub cache_system_prompt: bool,
+    /// Upper bound on a single LLM request (0 disables). For streaming this
+    /// covers the wait for the first token; a long answer that keeps
+    /// producing tokens is not cut off by it.
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    /// Give up on a stream that produces no token for this long after its
+    /// first one (0 disables)
+    #[serde(default = "default_stream_idle_timeout_secs")]
+    pub stream_idle_timeout_secs: u64,
+    /// Upper bound on a whole streamed generation, from the request to the
+    /// last token (0, the default, disables)
+    #[serde(default)]
+    pub stream_total_timeout_secs: u64,
+    /// Run a one-token throwaway generation after loading a local model so
+    /// the first real query doesn't pay for cold weights and caches
+    #[serde(default = "default_warmup_on_init")]
+    pub warmup_on_init: bool,
+    /// Memory budget for per-conversation prompt caches on local models, in
+    /// MB (0 disables). Lets follow-up turns skip re-evaluating the history.
+    #[serde(default = "default_prompt_cache_mb")]
+    pub prompt_cache_mb: usize,
+}
+
+fn default_request_timeout_secs() -> u64 {
+    90
+}
+
+fn default_stream_idle_timeout_secs() -> u64 {
+    30
+}
+
+fn default_warmup_on_init() -> bool {
+    true
+}
+
+fn default_prompt_cache_mb() -> usize {
+    1024
+}
+
+/// Returned (inside `anyhow::Error`) when a request exceeds
+/// `LLMConfig::request_timeout_secs`. Check with `LLMTimeoutError::is_timeout`.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("LLM request timed out after {}s", .timeout.as_secs())]
+pub struct LLMTimeoutError {
+    pub timeout: std::time::Duration,
+}
+
+impl LLMTimeoutError {
+    /// Whether `err` (or anything it wraps) is a generation timeout
+    pub fn is_timeout(err: &anyhow::Error) -> bool {
+        err.downcast_ref::<LLMTimeoutError>().is_some()
+    }
+}
+
+/// Returned (inside `anyhow::Error`) when a request is stopped through its
+/// `CancellationToken` before the provider responded
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("LLM generation cancelled")]
+pub struct LLMCancelledError;
+
+impl LLMCancelledError {
+    pub fn is_cancelled(err: &anyhow::Error) -> bool {
+        err.downcast_ref::<LLMCancelledError>().is_some()
+    }
 }
 
 impl Default for LLMConfig {
@@ -160,10 +267,48 @@ impl Default for LLMConfig {
             streaming: true,
             context_window: 8192,
             system_prompt: None,
+            cache_system_prompt: false,
+            request_timeout_secs: default_request_timeout_secs(),
+            stream_idle_timeout_secs: default_stream_idle_timeout_secs(),
+            stream_total_timeout_secs: 0,
+            warmup_on_init: default_warmup_on_init(),
+            prompt_cache_mb: default_prompt_cache_mb(),
         }
     }
 }
 
+impl LLMConfig {
+    /// Copy of this config with any API key blanked out, safe to write to disk.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        if let LLMMode::External { api_key, .. } = &mut config.mode {
+            api_key.clear();
+        }
+        config
+    }
+
+    /// Persist the config as JSON. API keys are redacted — store them separately.
+    pub fn save_to(&self, path: &std::path::Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(&self.redacted())?;
+        // Write to a temp file and rename so a crash can't leave a truncated config
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Load a config saved with `save_to`. External-mode API keys come back empty.
+    pub fn load_from(path: &std::path::Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read LLM config {}: {}", path.display(), e))?;
+        serde_json::from_str(&json)
+            .map_err(|e| anyhow!("Failed to parse LLM config {}: {}", path.display(), e))
+    }
+}
+
 /// Core trait for LLM providers
 #[async_trait]
 pub trait LLMProvider: Send + Sync {
@@ -181,6 +326,28 @@ pub trait LLMProvider: Send + Sync {
         config: &GenerationConfig,
     ) -> Result<TokenStream>;
 
+    /// Generate with fixed system blocks (instructions, document context) ahead
+    /// of the prompt. Providers with prompt caching send the blocks separately
+    /// so they can be cached; the default prepends them to the prompt.
+    async fn generate_with_system(
+        &self,
+        system: &[String],
+        prompt: &str,
+        config: &GenerationConfig,
+    ) -> Result<String> {
+        self.generate(&join_system_prompt(system, prompt), config).await
+    }
+
+    /// Streaming variant of `generate_with_system`
+    async fn generate_stream_with_system(
+        &self,
+        system: &[String],
+        prompt: &str,
+        config: &GenerationConfig,
+    ) -> Result<TokenStream> {
+        self.generate_stream(&join_system_prompt(system, prompt), config).await
+    }
+
     /// Generate with RAG context
     async fn generate_with_context(
         &self,
@@ -209,13 +376,35 @@ pub trait LLMProvider: Send + Sync {
 
     /// Streaming chat completion with tool support.
     /// Returns a channel that yields ChatStreamEvent items.
-    /// Default implementation falls back to generate_stream().
+    /// Default implementation falls back to generate_stream(), or to a single
+    /// non-streaming chat() call when tools are supplied so tool calls still surface.
     async fn chat_stream(
         &self,
         messages: &[ChatMessage],
         tools: &[ToolSchema],
         config: &GenerationConfig,
     ) -> Result<tokio::sync::mpsc::Receiver<ChatStreamEvent>> {
+        if !tools.is_empty() {
+            let response = self.chat(messages, tools, config).await?;
+            let (tx, rx) = tokio::sync::mpsc::channel(256);
+            match response {
+                ChatResponse::Content(text) => {
+                    let _ = tx.send(ChatStreamEvent::ContentDelta(text)).await;
+                }
+                ChatResponse::ToolCalls(calls) => {
+                    for tc in calls {
+                        let _ = tx.send(ChatStreamEvent::ToolCallStarted {
+                            id: tc.id.clone(),
+                            name: tc.name.clone(),
+                        }).await;
+                        let _ = tx.send(ChatStreamEvent::ToolCallComplete(tc)).await;
+                    }
+                }
+            }
+            let _ = tx.send(ChatStreamEvent::Done).await;
+            return Ok(rx);
+        }
+
         let prompt = messages.iter()
             .filter_map(|m| m.content.as_ref().map(|c| format!("{:?}: {}", m.role, c)))
             .collect::<Vec<_>>()
@@ -233,6 +422,29 @@ pub trait LLMProvider: Send + Sync {
         Ok(rx)
     }
 
+    /// Token usage reported by the provider for its most recent request, if any.
+    fn last_usage(&self) -> Option<TokenUsage> {
+        None
+    }
+
+    /// Whether the model searches the web while answering (Perplexity online models)
+    fn supports_web_search(&self) -> bool {
+        false
+    }
+
+    /// Web pages the provider cited in its most recent response
+    fn last_web_sources(&self) -> Vec<WebSource> {
+        Vec::new()
+    }
+
+    /// Whether `generate` honours `GenerationConfig::json_schema`
+    fn supports_json_schema(&self) -> bool {
+        false
+    }
+
+    /// Drop any per-conversation state (e.g. cached prompt prefixes)
+    fn forget_conversation(&self, _conversation_id: &str) {}
+
     /// Get provider info
     fn info(&self) -> ProviderInfo;
 
@@ -253,6 +465,116 @@ pub struct GenerationConfig {
     pub repetition_penalty: f32,
     pub stop_sequences: Vec<String>,
     pub seed: Option<u64>,
+    /// Retries for transient API failures (429/5xx, connect/timeout errors)
+    #[serde(default = "default_max_retries")]
+    pub max_retries: usize,
+    /// Base delay for exponential backoff between retries, in milliseconds
+    #[serde(default = "default_base_backoff_ms")]
+    pub base_backoff_ms: u64,
+    /// Request provider-side caching of system prompt blocks where supported
+    #[serde(default)]
+    pub cache_system_prompt: bool,
+    /// Additive logit bias per token id. Token ids are specific to the model's
+    /// tokenizer — an id that means "```" for one model is unrelated text in another.
+    #[serde(default)]
+    pub logit_bias: HashMap<u32, f32>,
+    /// Constrain output to JSON matching this schema, on providers that support it
+    #[serde(default)]
+    pub json_schema: Option<JsonValue>,
+    /// Model id overriding the provider's configured model, for external APIs
+    /// that select the model per request
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Fires when the caller stops the generation; see `LLMManager::with_cancellation`
+    #[serde(skip)]
+    pub cancel: CancellationToken,
+    /// Conversation this request belongs to; local providers reuse the
+    /// evaluated prompt prefix between requests with the same id
+    #[serde(default)]
+    pub conversation_id: Option<String>,
+}
+
+/// Per-space overrides of the global generation settings. Unset fields fall
+/// back to `LLMConfig`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LLMOverrides {
+    /// Model id for external APIs that take the model per request
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub top_p: Option<f32>,
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
+}
+
+tokio::task_local! {
+    static LLM_OVERRIDES: LLMOverrides;
+    static LLM_CANCEL: CancellationToken;
+    static LLM_CONVERSATION: String;
+}
+
+impl LLMOverrides {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(t) = self.temperature {
+            if !(0.0..=2.0).contains(&t) {
+                return Err(anyhow!("temperature must be between 0 and 2, got {}", t));
+            }
+        }
+        if let Some(p) = self.top_p {
+            if !(0.0..=1.0).contains(&p) || p == 0.0 {
+                return Err(anyhow!("top_p must be in (0, 1], got {}", p));
+            }
+        }
+        if self.max_tokens == Some(0) {
+            return Err(anyhow!("max_tokens must be positive"));
+        }
+        if self.model.as_deref().is_some_and(|m| m.trim().is_empty()) {
+            return Err(anyhow!("model must not be empty"));
+        }
+        Ok(())
+    }
+
+    pub fn apply(&self, config: &mut GenerationConfig) {
+        if let Some(model) = &self.model {
+            config.model = Some(model.clone());
+        }
+        if let Some(temperature) = self.temperature {
+            config.temperature = temperature;
+        }
+        if let Some(top_p) = self.top_p {
+            config.top_p = top_p;
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            config.max_tokens = max_tokens;
+        }
+    }
+}
+
+/// Validate per-request settings against the loaded model's vocabulary.
+/// A `logit_bias` map meant for another tokenizer fails loudly instead of
+/// biasing unrelated tokens.
+pub fn check_generation_config(config: &GenerationConfig, vocab_size: u32) -> Result<()> {
+    if let Some(bad) = config.logit_bias.keys().find(|&&id| id >= vocab_size) {
+        return Err(anyhow!(
+            "logit_bias token id {} is outside the model vocabulary ({} tokens)",
+            bad, vocab_size
+        ));
+    }
+    Ok(())
+}
+
+fn default_max_retries() -> usize {
+    3
+}
+
+fn default_base_backoff_ms() -> u64 {
+    500
 }
 
 impl From<&LLMConfig> for GenerationConfig {
@@ -265,6 +587,14 @@ impl From<&LLMConfig> for GenerationConfig {
             repetition_penalty: config.repetition_penalty,
             stop_sequences: vec![],
             seed: None,
+            max_retries: default_max_retries(),
+            base_backoff_ms: default_base_backoff_ms(),
+            cache_system_prompt: config.cache_system_prompt,
+            logit_bias: HashMap::new(),
+            json_schema: None,
+            model: None,
+            cancel: CancellationToken::default(),
+            conversation_id: None,
         }
     }
 }
@@ -350,6 +680,8 @@ pub enum ChatResponse {
 pub enum ChatStreamEvent {
     /// A token of text content
     ContentDelta(String),
+    /// The LLM began a tool call; arguments are still streaming
+    ToolCallStarted { id: String, name: String },
     /// A tool call was fully received (streamed tool calls are assembled first)
     ToolCallComplete(ToolCall),
     /// Stream is done
@@ -367,6 +699,26 @@ pub struct ProviderInfo {
     pub is_local: bool,
 }
 
+/// Token counts reported by a provider (as opposed to char-based estimates)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub input_tokens: Option<usize>,
+    pub output_tokens: Option<usize>,
+    /// Prompt tokens served from the provider's prompt cache
+    pub cache_read_tokens: Option<usize>,
+    /// Prompt tokens written into the provider's prompt cache
+    pub cache_write_tokens: Option<usize>,
+}
+
+/// A web page an online model consulted while answering
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebSource {
+    pub url: String,
+    pub title: Option<String>,
+    /// Publication date as reported by the provider
+    pub date: Option<String>,
+}
+
 /// Memory usage stats
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryUsage {
@@ -382,15 +734,39 @@ pub struct HardwareInfo {
     pub has_directml: bool,
     pub has_metal: bool,
     pub recommended_device: DeviceType,
+    /// Total VRAM of GPU 0 in MB (NVIDIA only, via nvidia-smi)
+    pub vram_total_mb: Option<usize>,
+    /// Free VRAM of GPU 0 in MB at detection time
+    pub vram_free_mb: Option<usize>,
 }
 
+/// Memoized hardware probe — nvidia-smi is slow to spawn, so run it once.
+static HARDWARE_INFO: LazyLock<parking_lot::RwLock<Option<HardwareInfo>>> =
+    LazyLock::new(|| parking_lot::RwLock::new(None));
+
 impl HardwareInfo {
-    /// Auto-detect available hardware acceleration
+    /// Auto-detect available hardware acceleration (cached after the first call)
     pub fn detect() -> Self {
+        if let Some(info) = HARDWARE_INFO.read().as_ref() {
+            return info.clone();
+        }
+        Self::force_refresh()
+    }
+
+    /// Re-run hardware detection, replacing the cached result.
+    /// Use after driver changes or when free VRAM needs to be current.
+    pub fn force_refresh() -> Self {
+        let info = Self::probe();
+        *HARDWARE_INFO.write() = Some(info.clone());
+        info
+    }
+
+    fn probe() -> Self {
         tracing::info!("Detecting available hardware acceleration");
 
-        // Check for NVIDIA GPU (CUDA)
-        let has_cuda = Self::check_cuda();
+        // Check for NVIDIA GPU (CUDA) and its memory
+        let vram = Self::query_nvidia_vram();
+        let has_cuda = vram.is_some() || Self::check_cuda();
 
         // Check for DirectML (Windows GPU - AMD/Intel/NVIDIA)
         let has_directml = Self::check_directml();
@@ -418,7 +794,43 @@ impl HardwareInfo {
             has_directml,
             has_metal,
             recommended_device,
+            vram_total_mb: vram.map(|(total, _)| total),
+            vram_free_mb: vram.map(|(_, free)| free),
+        }
+    }
+
+    /// Pick the highest-precision quantization whose estimated footprint fits in
+    /// free VRAM, keeping 20% headroom for KV cache and activations.
+    /// Returns None when free VRAM is unknown or even Q4 won't fit.
+    pub fn recommend_quantization(&self, model: &LocalModel) -> Option<QuantizationType> {
+        let budget_mb = self.vram_free_mb? as f32 * 0.8;
+        // LocalModel::size_gb() reports the ~4-bit footprint
+        let q4_mb = model.size_gb() * 1024.0;
+        [
+            (QuantizationType::F16, 4.0),
+            (QuantizationType::Q8, 2.0),
+            (QuantizationType::Q4_K_M, 1.0),
+        ]
+        .into_iter()
+        .find(|(_, scale)| q4_mb * scale <= budget_mb)
+        .map(|(quant, _)| quant)
+    }
+
+    /// Query total/free memory of GPU 0 via nvidia-smi
+    fn query_nvidia_vram() -> Option<(usize, usize)> {
+        #[cfg(not(target_os = "windows"))]
+        let binary = "nvidia-smi";
+        #[cfg(target_os = "windows")]
+        let binary = "nvidia-smi.exe";
+
+        let output = std::process::Command::new(binary)
+            .args(["--query-gpu=memory.total,memory.free", "--format=csv,noheader,nounits"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
         }
+        parse_nvidia_smi_memory(&String::from_utf8_lossy(&output.stdout))
     }
 
     fn check_cuda() -> bool {
@@ -466,6 +878,15 @@ impl HardwareInfo {
     }
 }
 
+/// Parse `memory.total, memory.free` (MiB, no units) for the first GPU listed.
+fn parse_nvidia_smi_memory(output: &str) -> Option<(usize, usize)> {
+    let line = output.lines().find(|l| !l.trim().is_empty())?;
+    let mut fields = line.split(',').map(|f| f.trim().parse::<usize>());
+    let total = fields.next()?.ok()?;
+    let free = fields.next()?.ok()?;
+    Some((total, free))
+}
+
 /// Main LLM manager
 pub struct LLMManager {
     config: LLMConfig,
@@ -530,28 +951,31 @@ impl LLMManager {
                 // HYBRID BACKEND SELECTION
                 // llama.cpp for CPU (has KV caching) + ONNX Runtime GenAI for GPU
 
-                let use_gpu = match device {
-                    DeviceType::Cpu => {
-                        tracing::info!("Device explicitly set to CPU, using llama.cpp");
-                        false
-                    }
-                    DeviceType::Cuda(_) => {
-                        tracing::info!("Device explicitly set to CUDA GPU, using ONNX Runtime GenAI");
-                        true
-                    }
-                    DeviceType::Metal => {
-                        tracing::info!("Device explicitly set to Metal, using ONNX Runtime GenAI");
-                        true
+                let device = match device {
+                    DeviceType::Auto => {
+                        // Free VRAM decides whether the model fits, so take a fresh reading
+                        let resolved = device.resolve_for(&HardwareInfo::force_refresh(), model);
+                        tracing::info!(resolved = ?resolved, "Device set to Auto, resolved from hardware detection");
+                        resolved
                     }
+                    other => other.clone(),
                 };
 
+                let use_gpu = device.uses_gpu_backend();
+                if use_gpu {
+                    let recommended = HardwareInfo::detect().recommend_quantization(model);
+                    tracing::info!(device = ?device, recommended_quantization = ?recommended, "GPU device selected, using ONNX Runtime GenAI");
+                } else {
+                    tracing::info!("CPU device selected, using llama.cpp");
+                }
+
                 // Create appropriate provider based on device
                 let provider: Box<dyn LLMProvider> = if use_gpu {
                     // GPU path: Use ONNX Runtime GenAI (CUDA, DirectML, TensorRT)
                     tracing::info!("Initializing ONNX Runtime GenAI for GPU acceleration");
                     Box::new(GenAIProvider::new(
                         model.clone(),
-                        device.clone(),
+                        device,
                         quantization.clone(),
                         &self.model_cache_dir,
                     )?)
@@ -560,12 +984,16 @@ impl LLMManager {
                     tracing::info!("Initializing llama.cpp for CPU with prompt caching");
                     Box::new(LlamaCppProvider::new(
                         model.clone(),
-                        device.clone(),
+                        device,
                         quantization.clone(),
                         &self.model_cache_dir,
-                    )?)
+                    )?.with_prompt_cache(self.config.prompt_cache_mb * 1024 * 1024))
                 };
 
+                if self.config.warmup_on_init {
+                    Self::warm_up(provider.as_ref(), &self.config).await;
+                }
+
                 self.provider = Some(provider);
                 Ok(())
             }
@@ -587,6 +1015,38 @@ impl LLMManager {
         }
     }
 
+    /// Throwaway one-token generation to load weights and warm caches,
+    /// bounded by `request_timeout_secs`. Failures are logged only; the
+    /// provider is still usable.
+    async fn warm_up(provider: &dyn LLMProvider, llm_config: &LLMConfig) {
+        let config = GenerationConfig {
+            max_tokens: 1,
+            max_retries: 0,
+            ..GenerationConfig::from(llm_config)
+        };
+        let started = std::time::Instant::now();
+        let generation = provider.generate("Hi", &config);
+        let result = if llm_config.request_timeout_secs == 0 {
+            generation.await
+        } else {
+            let timeout = std::time::Duration::from_secs(llm_config.request_timeout_secs);
+            tokio::time::timeout(timeout, generation)
+                .await
+                .unwrap_or_else(|_| Err(LLMTimeoutError { timeout }.into()))
+        };
+        match result {
+            Ok(_) => tracing::info!(
+                elapsed_ms = started.elapsed().as_millis() as u64,
+                "Local model warmed up"
+            ),
+            Err(e) => tracing::warn!(
+                error = %e,
+                elapsed_ms = started.elapsed().as_millis() as u64,
+                "Local model warmup failed, continuing without it"
+            ),
+        }
+    }
+
     /// Switch to a different mode
     pub async fn switch_mode(&mut self, new_mode: LLMMode) -> Result<()> {
         // Clean up current provider
@@ -599,14 +1059,91 @@ impl LLMManager {
         self.initialize().await
     }
 
+    /// Settings for one request: the global config with `max_tokens` raised
+    /// to at least `min_max_tokens`, then any overrides scoped by
+    /// `with_overrides`
+    fn generation_config(&self, min_max_tokens: usize) -> GenerationConfig {
+        let mut config = GenerationConfig::from(&self.config);
+        config.max_tokens = config.max_tokens.max(min_max_tokens);
+        let _ = LLM_OVERRIDES.try_with(|overrides| overrides.apply(&mut config));
+        config.cancel = Self::current_cancellation();
+        config.conversation_id = LLM_CONVERSATION.try_with(|id| id.clone()).ok();
+        config
+    }
+
+    /// Run `fut` with `overrides` applied to every request it makes through
+    /// any `LLMManager`. Scoped to the task, so concurrent chats in other
+    /// spaces are unaffected.
+    pub async fn with_overrides<F: std::future::Future>(overrides: LLMOverrides, fut: F) -> F::Output {
+        LLM_OVERRIDES.scope(overrides, fut).await
+    }
+
+    /// Run `fut` with every request it makes through any `LLMManager` tied to
+    /// `cancel`: pending requests fail with `LLMCancelledError` and streams
+    /// stop producing tokens once it fires
+    pub async fn with_cancellation<F: std::future::Future>(cancel: CancellationToken, fut: F) -> F::Output {
+        LLM_CANCEL.scope(cancel, fut).await
+    }
+
+    /// Run `fut` with every request it makes through any `LLMManager` tagged
+    /// with `conversation_id`, so local models can reuse the prompt prefix
+    /// from the conversation's previous turn
+    pub async fn with_conversation<F: std::future::Future>(conversation_id: String, fut: F) -> F::Output {
+        LLM_CONVERSATION.scope(conversation_id, fut).await
+    }
+
+    /// Token passed to `with_cancellation` for this task; outside one, a
+    /// token that never fires
+    pub fn current_cancellation() -> CancellationToken {
+        LLM_CANCEL.try_with(|cancel| cancel.clone()).unwrap_or_default()
+    }
+
+    /// Run a provider call under `request_timeout_secs`, abandoning it
+    /// (and so dropping any in-flight HTTP request) if the scoped
+    /// cancellation token fires
+    async fn with_timeout<T>(&self, fut: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+        let cancel = Self::current_cancellation();
+        let timed = async {
+            if self.config.request_timeout_secs == 0 {
+                return fut.await;
+            }
+            let timeout = std::time::Duration::from_secs(self.config.request_timeout_secs);
+            match tokio::time::timeout(timeout, fut).await {
+                Ok(result) => result,
+                Err(_) => {
+                    tracing::warn!(timeout_secs = timeout.as_secs(), "LLM request timed out");
+                    Err(LLMTimeoutError { timeout }.into())
+                }
+            }
+        };
+        tokio::select! {
+            result = timed => result,
+            _ = cancel.cancelled() => Err(LLMCancelledError.into()),
+        }
+    }
+
+    /// Open a stream under `with_timeout`, then keep `request_timeout_secs`
+    /// (counted from now) on its first token, `stream_idle_timeout_secs` on
+    /// the gaps after it, and `stream_total_timeout_secs` on the whole stream
+    async fn stream_with_timeout(&self, fut: impl std::future::Future<Output = Result<TokenStream>>) -> Result<TokenStream> {
+        let started = tokio::time::Instant::now();
+        let secs = |secs: u64| (secs > 0).then(|| std::time::Duration::from_secs(secs));
+        let stream = self.with_timeout(fut).await?;
+        Ok(stream.with_deadlines(
+            secs(self.config.request_timeout_secs),
+            secs(self.config.stream_idle_timeout_secs),
+            secs(self.config.stream_total_timeout_secs),
+            started,
+        ))
+    }
+
     /// Generate completion
     pub async fn generate(&self, prompt: &str) -> Result<String> {
         match &self.provider {
             Some(provider) => {
-                let mut config = GenerationConfig::from(&self.config);
-                // Ensure sufficient tokens for complete responses (floor at 4096)
-                config.max_tokens = config.max_tokens.max(8192);
-                provider.generate(prompt, &config).await
+                // Ensure sufficient tokens for complete responses
+                let config = self.generation_config(8192);
+                self.with_timeout(provider.generate(prompt, &config)).await
             }
             None => Err(anyhow!("LLM is disabled or not initialized"))
         }
@@ -616,9 +1153,9 @@ impl LLMManager {
     pub async fn generate_custom(&self, prompt: &str, max_tokens: usize) -> Result<String> {
         match &self.provider {
             Some(provider) => {
-                let mut config = GenerationConfig::from(&self.config);
+                let mut config = self.generation_config(0);
                 config.max_tokens = max_tokens;
-                provider.generate(prompt, &config).await
+                self.with_timeout(provider.generate(prompt, &config)).await
             }
             None => Err(anyhow!("LLM is disabled or not initialized"))
         }
@@ -628,10 +1165,9 @@ impl LLMManager {
     pub async fn generate_stream(&self, prompt: &str) -> Result<TokenStream> {
         match &self.provider {
             Some(provider) => {
-                let mut config = GenerationConfig::from(&self.config);
                 // Ensure sufficient tokens for complete responses
-                config.max_tokens = config.max_tokens.max(8192);
-                provider.generate_stream(prompt, &config).await
+                let config = self.generation_config(8192);
+                self.stream_with_timeout(provider.generate_stream(prompt, &config)).await
             }
             None => Err(anyhow!("LLM is disabled or not initialized"))
         }
@@ -641,9 +1177,31 @@ impl LLMManager {
     pub async fn generate_stream_custom(&self, prompt: &str, max_tokens: usize) -> Result<TokenStream> {
         match &self.provider {
             Some(provider) => {
-                let mut config = GenerationConfig::from(&self.config);
+                let mut config = self.generation_config(0);
                 config.max_tokens = max_tokens;
-                provider.generate_stream(prompt, &config).await
+                self.stream_with_timeout(provider.generate_stream(prompt, &config)).await
+            }
+            None => Err(anyhow!("LLM is disabled or not initialized"))
+        }
+    }
+
+    /// Generate completion behind fixed system blocks; see `LLMProvider::generate_with_system`
+    pub async fn generate_with_system(&self, system: &[String], prompt: &str) -> Result<String> {
+        match &self.provider {
+            Some(provider) => {
+                let config = self.generation_config(8192);
+                self.with_timeout(provider.generate_with_system(system, prompt, &config)).await
+            }
+            None => Err(anyhow!("LLM is disabled or not initialized"))
+        }
+    }
+
+    /// Streaming variant of `generate_with_system`
+    pub async fn generate_stream_with_system(&self, system: &[String], prompt: &str) -> Result<TokenStream> {
+        match &self.provider {
+            Some(provider) => {
+                let config = self.generation_config(8192);
+                self.stream_with_timeout(provider.generate_stream_with_system(system, prompt, &config)).await
             }
             None => Err(anyhow!("LLM is disabled or not initialized"))
         }
@@ -657,10 +1215,9 @@ impl LLMManager {
     ) -> Result<String> {
         match &self.provider {
             Some(provider) => {
-                let mut config = GenerationConfig::from(&self.config);
                 // RAG responses need more tokens for citations and structured output
-                config.max_tokens = config.max_tokens.max(8192);
-                provider.generate_with_context(query, search_results, &config).await
+                let config = self.generation_config(8192);
+                self.with_timeout(provider.generate_with_context(query, search_results, &config)).await
             }
             None => {
                 // Fallback to simple concatenation if LLM is disabled
@@ -682,9 +1239,9 @@ impl LLMManager {
     ) -> Result<String> {
         match &self.provider {
             Some(provider) => {
-                let mut config = GenerationConfig::from(&self.config);
+                let mut config = self.generation_config(0);
                 config.max_tokens = max_tokens;  // Override with custom token limit
-                provider.generate_with_context(query, search_results, &config).await
+                self.with_timeout(provider.generate_with_context(query, search_results, &config)).await
             }
             None => {
                 // Fallback to simple concatenation if LLM is disabled
@@ -697,6 +1254,39 @@ impl LLMManager {
         }
     }
 
+    /// Generate JSON matching `schema` and deserialize it. Providers that can
+    /// constrain decoding get the schema directly; otherwise the JSON is
+    /// parsed out of the free-text response. A constrained request the
+    /// provider rejects (e.g. a model without `response_format` support) is
+    /// retried once without the constraint.
+    pub async fn generate_structured<T: serde::de::DeserializeOwned>(
+        &self,
+        prompt: &str,
+        schema: &JsonValue,
+    ) -> Result<T> {
+        let provider = self.provider.as_ref()
+            .ok_or_else(|| anyhow!("LLM is disabled or not initialized"))?;
+        let mut config = self.generation_config(0);
+        let constrained = provider.supports_json_schema();
+        if constrained {
+            config.json_schema = Some(schema.clone());
+        }
+        let prompt = format!(
+            "{}\n\nRespond with only a JSON value matching this JSON schema, without any other text:\n{}",
+            prompt, schema
+        );
+        let output = match self.with_timeout(provider.generate(&prompt, &config)).await {
+            Err(e) if constrained && !LLMTimeoutError::is_timeout(&e) && !LLMCancelledError::is_cancelled(&e) => {
+                tracing::warn!(error = %e, "Schema-constrained generation failed, retrying unconstrained");
+                config.json_schema = None;
+                self.with_timeout(provider.generate(&prompt, &config)).await?
+            }
+            result => result?,
+        };
+        tracing::debug!(constrained, output_len = output.len(), "Structured generation finished");
+        parse_json_output(&output)
+    }
+
     /// Chat completion with message history and optional tool calling.
     pub async fn chat(
         &self,
@@ -705,9 +1295,8 @@ impl LLMManager {
     ) -> Result<ChatResponse> {
         match &self.provider {
             Some(provider) => {
-                let mut config = GenerationConfig::from(&self.config);
-                config.max_tokens = config.max_tokens.max(8192);
-                provider.chat(messages, tools, &config).await
+                let config = self.generation_config(8192);
+                self.with_timeout(provider.chat(messages, tools, &config)).await
             }
             None => Err(anyhow!("LLM is disabled or not initialized")),
         }
@@ -721,9 +1310,8 @@ impl LLMManager {
     ) -> Result<tokio::sync::mpsc::Receiver<ChatStreamEvent>> {
         match &self.provider {
             Some(provider) => {
-                let mut config = GenerationConfig::from(&self.config);
-                config.max_tokens = config.max_tokens.max(8192);
-                provider.chat_stream(messages, tools, &config).await
+                let config = self.generation_config(8192);
+                self.with_timeout(provider.chat_stream(messages, tools, &config)).await
             }
             None => Err(anyhow!("LLM is disabled or not initialized")),
         }
@@ -736,6 +1324,39 @@ impl LLMManager {
             .unwrap_or(false)
     }
 
+    /// Check if the current provider can constrain output to a JSON schema.
+    pub fn supports_json_schema(&self) -> bool {
+        self.provider.as_ref()
+            .map(|p| p.supports_json_schema())
+            .unwrap_or(false)
+    }
+
+    /// Release the provider's cached state for a deleted or abandoned conversation
+    pub fn forget_conversation(&self, conversation_id: &str) {
+        if let Some(p) = self.provider.as_ref() {
+            p.forget_conversation(conversation_id);
+        }
+    }
+
+    /// Token usage reported by the provider for its most recent request
+    pub fn last_usage(&self) -> Option<TokenUsage> {
+        self.provider.as_ref().and_then(|p| p.last_usage())
+    }
+
+    /// Check if the current provider searches the web while answering.
+    pub fn supports_web_search(&self) -> bool {
+        self.provider.as_ref()
+            .map(|p| p.supports_web_search())
+            .unwrap_or(false)
+    }
+
+    /// Web pages cited by the provider in its most recent response
+    pub fn last_web_sources(&self) -> Vec<WebSource> {
+        self.provider.as_ref()
+            .map(|p| p.last_web_sources())
+            .unwrap_or_default()
+    }
+
     /// Get current provider info
     pub fn info(&self) -> Option<ProviderInfo> {
         self.provider.as_ref().map(|p| p.info())
@@ -755,6 +1376,32 @@ impl LLMManager {
     }
 }
 
+/// Deserialize JSON from model output, tolerating code fences and prose
+/// around the first JSON object or array
+pub(crate) fn parse_json_output<T: serde::de::DeserializeOwned>(output: &str) -> Result<T> {
+    let trimmed = output.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Ok(value);
+    }
+    let start = trimmed.find(['{', '[']).ok_or_else(|| anyhow!("Model output contains no JSON"))?;
+    let closer = if trimmed[start..].starts_with('{') { '}' } else { ']' };
+    let end = trimmed.rfind(closer).filter(|&end| end > start)
+        .ok_or_else(|| anyhow!("Model output contains unterminated JSON"))?;
+    serde_json::from_str(&trimmed[start..=end])
+        .map_err(|e| anyhow!("Model output does not match the requested schema: {}", e))
+}
+
+/// Flatten system blocks and a prompt into one prompt, for providers without
+/// a separate system field
+pub fn join_system_prompt(system: &[String], prompt: &str) -> String {
+    system.iter()
+        .map(String::as_str)
+        .chain(std::iter::once(prompt))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
 /// Format prompt for RAG
 pub fn format_rag_prompt(query: &str, context: &[String], system_prompt: Option<&str>) -> String {
     let system = system_prompt.unwrap_or(
@@ -864,6 +1511,296 @@ mod tests {
         assert_eq!(config.max_tokens, 1024);
     }
 
+    #[test]
+    fn test_check_generation_config_logit_bias() {
+        let mut config = GenerationConfig::from(&LLMConfig::default());
+        assert!(check_generation_config(&config, 32_000).is_ok());
+
+        config.logit_bias = HashMap::from([(0, -100.0), (31_999, 5.0)]);
+        assert!(check_generation_config(&config, 32_000).is_ok());
+
+        config.logit_bias.insert(32_000, 1.0);
+        let err = check_generation_config(&config, 32_000).unwrap_err();
+        assert!(err.to_string().contains("token id 32000"));
+    }
+
+    #[test]
+    fn test_join_system_prompt() {
+        let system = vec!["instructions".to_string(), "documents".to_string()];
+        assert_eq!(join_system_prompt(&system, "question"), "instructions\n\ndocuments\n\nquestion");
+        assert_eq!(join_system_prompt(&[], "question"), "question");
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi_memory() {
+        assert_eq!(parse_nvidia_smi_memory("24564, 23012\n8192, 8000\n"), Some((24564, 23012)));
+        assert_eq!(parse_nvidia_smi_memory("NVIDIA-SMI has failed"), None);
+        assert_eq!(parse_nvidia_smi_memory(""), None);
+    }
+
+    #[test]
+    fn test_recommend_quantization_fits_free_vram() {
+        let mut hw = HardwareInfo {
+            has_cuda: true,
+            has_directml: false,
+            has_metal: false,
+            recommended_device: DeviceType::Cuda(0),
+            vram_total_mb: Some(8192),
+            vram_free_mb: Some(6000),
+        };
+        // Mistral 7B is ~4 GB at Q4: F16 (16 GB) and Q8 (8 GB) don't fit in 4.8 GB
+        assert!(matches!(hw.recommend_quantization(&LocalModel::Mistral7B), Some(QuantizationType::Q4_K_M)));
+        assert_eq!(DeviceType::Auto.resolve_for(&hw, &LocalModel::Mistral7B), DeviceType::Cuda(0));
+
+        // Phi-4 (~8 GB at Q4) can't fit: Auto falls back to llama.cpp on CPU,
+        // an explicit GPU choice is kept
+        assert_eq!(DeviceType::Auto.resolve_for(&hw, &LocalModel::Phi4), DeviceType::Cpu);
+        assert_eq!(DeviceType::Cuda(0).resolve_for(&hw, &LocalModel::Phi4), DeviceType::Cuda(0));
+
+        hw.vram_free_mb = None;
+        assert!(hw.recommend_quantization(&LocalModel::Mistral7B).is_none());
+        assert_eq!(DeviceType::Auto.resolve_for(&hw, &LocalModel::Phi4), DeviceType::Cuda(0));
+    }
+
+    #[test]
+    fn test_auto_device_on_cpu_only_machine_uses_llamacpp() {
+        let hw = HardwareInfo {
+            has_cuda: false,
+            has_directml: false,
+            has_metal: false,
+            recommended_device: DeviceType::Cpu,
+            vram_total_mb: None,
+            vram_free_mb: None,
+        };
+        let resolved = DeviceType::Auto.resolve(&hw);
+        assert_eq!(resolved, DeviceType::Cpu);
+        assert!(!resolved.uses_gpu_backend());
+    }
+
+    #[test]
+    fn test_auto_device_serde_round_trip() {
+        let json = serde_json::to_string(&DeviceType::Auto).unwrap();
+        assert_eq!(json, "\"Auto\"");
+        let back: DeviceType = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, DeviceType::Auto);
+    }
+
+    #[test]
+    fn test_request_timeout_default_and_error_type() {
+        let json = serde_json::to_value(LLMConfig::default()).unwrap();
+        let mut obj = json.as_object().unwrap().clone();
+        obj.remove("request_timeout_secs");
+        let legacy: LLMConfig = serde_json::from_value(serde_json::Value::Object(obj)).unwrap();
+        assert_eq!(legacy.request_timeout_secs, 90);
+        assert_eq!(legacy.stream_total_timeout_secs, 0);
+
+        let err: anyhow::Error = LLMTimeoutError { timeout: std::time::Duration::from_secs(90) }.into();
+        let wrapped = err.context("search generation");
+        assert!(LLMTimeoutError::is_timeout(&wrapped));
+        assert!(!LLMTimeoutError::is_timeout(&anyhow!("connection refused")));
+    }
+
+    #[tokio::test]
+    async fn test_stream_deadlines_end_stalled_streams() {
+        let first = std::time::Duration::from_millis(200);
+        let idle = std::time::Duration::from_millis(50);
+
+        // Nothing ever arrives: first-token deadline
+        let (tx, rx) = tokio::sync::mpsc::channel::<String>(4);
+        let err = TokenStream::new(rx)
+            .with_deadlines(Some(first), Some(idle), None, tokio::time::Instant::now())
+            .try_collect()
+            .await
+            .unwrap_err();
+        assert!(LLMTimeoutError::is_timeout(&err));
+        assert!(tx.is_closed());
+
+        // A slow first token is fine; silence after it hits the idle deadline
+        let (tx, rx) = tokio::sync::mpsc::channel(4);
+        let mut stream = TokenStream::new(rx)
+            .with_deadlines(Some(first), Some(idle), None, tokio::time::Instant::now());
+        let producer = tokio::spawn(async move {
+            tokio::time::sleep(idle * 2).await;
+            tx.send("a".to_string()).await.unwrap();
+            tx
+        });
+        assert_eq!(stream.next().await.as_deref(), Some("a"));
+        assert!(stream.next().await.is_none());
+        assert!(stream.timed_out());
+        assert!(producer.await.unwrap().is_closed());
+
+        // A steady producer outlives the first-token deadline unless a total is set
+        let steady = || {
+            let (tx, rx) = tokio::sync::mpsc::channel(4);
+            tokio::spawn(async move {
+                for _ in 0..30 {
+                    if tx.send("x".to_string()).await.is_err() {
+                        break;
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                }
+            });
+            rx
+        };
+        let text = TokenStream::new(steady())
+            .with_deadlines(Some(first), Some(idle), None, tokio::time::Instant::now())
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(text.len(), 30);
+
+        let total = std::time::Duration::from_millis(100);
+        let err = TokenStream::new(steady())
+            .with_deadlines(Some(first), Some(idle), Some(total), tokio::time::Instant::now())
+            .try_collect()
+            .await
+            .unwrap_err();
+        assert!(LLMTimeoutError::is_timeout(&err));
+    }
+
+    /// Rejects schema-constrained requests the way an API does for a model
+    /// without `response_format` support
+    struct SchemaRejectingProvider;
+
+    #[async_trait]
+    impl LLMProvider for SchemaRejectingProvider {
+        async fn generate(&self, _prompt: &str, config: &GenerationConfig) -> Result<String> {
+            match config.json_schema {
+                Some(_) => Err(anyhow!("400 Bad Request: response_format is not supported")),
+                None => Ok("```json\n{\"title\": \"Intake\"}\n```".to_string()),
+            }
+        }
+
+        async fn generate_stream(&self, _prompt: &str, _config: &GenerationConfig) -> Result<TokenStream> {
+            Err(anyhow!("not streaming"))
+        }
+
+        async fn generate_with_context(&self, _query: &str, _context: Vec<String>, _config: &GenerationConfig) -> Result<String> {
+            Err(anyhow!("no context"))
+        }
+
+        fn supports_json_schema(&self) -> bool {
+            true
+        }
+
+        fn info(&self) -> ProviderInfo {
+            ProviderInfo {
+                name: "schema-rejecting".to_string(),
+                model: "test".to_string(),
+                context_window: 0,
+                supports_streaming: false,
+                supports_functions: false,
+                is_local: false,
+            }
+        }
+
+        async fn is_ready(&self) -> bool { true }
+
+        fn memory_usage(&self) -> MemoryUsage {
+            MemoryUsage { ram_mb: 0, vram_mb: None, model_size_mb: 0 }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_structured_retries_without_schema() {
+        #[derive(Deserialize)]
+        struct Out { title: String }
+
+        let manager = LLMManager {
+            config: LLMConfig::default(),
+            provider: Some(Box::new(SchemaRejectingProvider)),
+            model_cache_dir: PathBuf::new(),
+        };
+        let schema = serde_json::json!({"type": "object", "properties": {"title": {"type": "string"}}});
+        let out: Out = manager.generate_structured("Name this form", &schema).await.unwrap();
+        assert_eq!(out.title, "Intake");
+    }
+
+    #[test]
+    fn test_parse_json_output_tolerates_fences_and_prose() {
+        #[derive(Deserialize)]
+        struct Out { title: String }
+
+        let fenced: Out = parse_json_output("Here you go:\n```json\n{\"title\": \"Intake\"}\n```").unwrap();
+        assert_eq!(fenced.title, "Intake");
+        let bare: Vec<u32> = parse_json_output(" [1, 2] ").unwrap();
+        assert_eq!(bare, vec![1, 2]);
+        assert!(parse_json_output::<Out>("no json here").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_space_overrides_apply_within_scope() {
+        let manager = LLMManager::new(LLMConfig::default());
+        let base = manager.generation_config(0);
+        let overrides = LLMOverrides { temperature: Some(0.1), max_tokens: Some(512), ..Default::default() };
+        assert!(overrides.validate().is_ok());
+
+        let scoped = LLMManager::with_overrides(overrides, async { manager.generation_config(8192) }).await;
+        assert_eq!(scoped.temperature, 0.1);
+        assert_eq!(scoped.max_tokens, 512);
+        assert_eq!(scoped.top_p, base.top_p);
+        assert!(scoped.model.is_none());
+        assert_eq!(manager.generation_config(0).temperature, base.temperature);
+
+        let tagged = LLMManager::with_conversation("conv-1".to_string(), async { manager.generation_config(0) }).await;
+        assert_eq!(tagged.conversation_id.as_deref(), Some("conv-1"));
+        assert!(base.conversation_id.is_none());
+
+        assert!(LLMOverrides { top_p: Some(1.5), ..Default::default() }.validate().is_err());
+        assert!(LLMOverrides::default().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancellation_aborts_pending_request() {
+        let manager = LLMManager::new(LLMConfig::default());
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            trigger.cancel();
+        });
+
+        let result: Result<()> = LLMManager::with_cancellation(cancel.clone(), async {
+            assert!(!manager.generation_config(0).cancel.is_cancelled());
+            manager.with_timeout(std::future::pending()).await
+        })
+        .await;
+        assert!(LLMCancelledError::is_cancelled(&result.unwrap_err()));
+
+        let scoped = LLMManager::with_cancellation(cancel, async { manager.generation_config(0) }).await;
+        assert!(scoped.cancel.is_cancelled());
+        assert!(!manager.generation_config(0).cancel.is_cancelled());
+    }
+
+    #[test]
+    fn test_llm_config_save_load_redacts_api_key() {
+        let dir = std::env::temp_dir().join(format!("shodh-llm-config-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("llm_config.json");
+        let config = LLMConfig {
+            mode: LLMMode::External {
+                provider: ApiProvider::Anthropic,
+                api_key: "sk-secret".to_string(),
+                model: "claude-3-haiku-20240307".to_string(),
+            },
+            temperature: 0.2,
+            ..Default::default()
+        };
+
+        config.save_to(&path).unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("sk-secret"));
+
+        let loaded = LLMConfig::load_from(&path).unwrap();
+        assert_eq!(loaded.temperature, 0.2);
+        match loaded.mode {
+            LLMMode::External { api_key, model, .. } => {
+                assert!(api_key.is_empty());
+                assert_eq!(model, "claude-3-haiku-20240307");
+            }
+            other => panic!("unexpected mode {:?}", other),
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_local_model_info() {
         let model = LocalModel::Phi3Mini;
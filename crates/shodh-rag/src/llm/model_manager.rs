@@ -10,10 +10,152 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::io::AsyncWriteExt;
 use bytes::Bytes;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use super::LocalModel;
 use super::model_config;
 
+/// How often download progress is published while streaming
+const PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Files of one model fetched at the same time
+const MAX_CONCURRENT_FILES: usize = 3;
+
+/// Written into the model directory once every file is present and
+/// verified; a model without it is incomplete
+const COMMIT_MARKER: &str = "manifest.json";
+
+/// Predefined models that `download_model` can fetch
+const DOWNLOADABLE_MODELS: [LocalModel; 6] = [
+    LocalModel::Phi3Mini,
+    LocalModel::Mistral7B,
+    LocalModel::Orca2_7B,
+    LocalModel::Qwen2_5B,
+    LocalModel::Gemma2B,
+    LocalModel::Sarvam1,
+];
+
+/// One file of a predefined model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModelFile {
+    pub url: &'static str,
+    pub filename: &'static str,
+    /// Pinned SHA256 of the file. When unset, the digest Hugging Face
+    /// publishes for the LFS object is used; weight files with neither are
+    /// not installed.
+    pub sha256: Option<&'static str>,
+}
+
+/// Every file a predefined model needs to load
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelManifest {
+    pub files: Vec<ModelFile>,
+}
+
+macro_rules! phi3_file {
+    ($name:literal) => {
+        ModelFile {
+            url: concat!(
+                "https://huggingface.co/microsoft/Phi-3-mini-4k-instruct-onnx/resolve/main/cpu_and_mobile/cpu-int4-rtn-block-32-acc-level-4/",
+                $name
+            ),
+            filename: $name,
+            sha256: None,
+        }
+    };
+}
+
+impl ModelManifest {
+    pub fn for_model(model: &LocalModel) -> Option<Self> {
+        let single = |url, filename| vec![ModelFile { url, filename, sha256: None }];
+        let files = match model {
+            // Microsoft Phi-3 ONNX GenAI model: graph, external weights,
+            // GenAI config and tokenizer
+            LocalModel::Phi3Mini => vec![
+                phi3_file!("phi3-mini-4k-instruct-cpu-int4-rtn-block-32-acc-level-4.onnx"),
+                phi3_file!("phi3-mini-4k-instruct-cpu-int4-rtn-block-32-acc-level-4.onnx.data"),
+                phi3_file!("genai_config.json"),
+                phi3_file!("tokenizer.json"),
+                phi3_file!("tokenizer_config.json"),
+                phi3_file!("special_tokens_map.json"),
+                phi3_file!("added_tokens.json"),
+            ],
+            LocalModel::Mistral7B => single(
+                "https://huggingface.co/mistralai/Mistral-7B-Instruct-v0.2/resolve/main/model.onnx",
+                "mistral-7b.onnx",
+            ),
+            LocalModel::Orca2_7B => single(
+                "https://huggingface.co/microsoft/Orca-2-7b/resolve/main/model.onnx",
+                "orca-2-7b.onnx",
+            ),
+            LocalModel::Qwen2_5B => single(
+                "https://huggingface.co/Qwen/Qwen2-0.5B-Instruct/resolve/main/model.onnx",
+                "qwen-2.5b.onnx",
+            ),
+            LocalModel::Gemma2B => single(
+                "https://huggingface.co/google/gemma-2b/resolve/main/model.onnx",
+                "gemma-2b.onnx",
+            ),
+            LocalModel::Sarvam1 => single(
+                "https://huggingface.co/MaziyarPanahi/sarvam-1-GGUF/resolve/main/sarvam-1.Q5_K_M.gguf",
+                "sarvam-1.Q5_K_M.gguf",
+            ),
+            LocalModel::Phi4 | LocalModel::Custom { .. } => return None,
+        };
+        Some(Self { files })
+    }
+}
+
+/// Contents of the commit marker: what was verified, and its size
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CommittedModel {
+    files: Vec<CommittedFile>,
+    committed_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CommittedFile {
+    filename: String,
+    size: u64,
+    sha256: Option<String>,
+}
+
+/// A manifest file in place under its final name
+struct FetchedFile {
+    filename: String,
+    size: u64,
+    sha256: Option<String>,
+    /// Fetched now rather than already present
+    downloaded: bool,
+}
+
+/// What the server says a file should be
+#[derive(Debug, Default)]
+struct PublishedFile {
+    sha256: Option<String>,
+    size: Option<u64>,
+}
+
+/// A model file whose download was interrupted
+#[derive(Debug, Clone, Serialize)]
+pub struct PartialDownload {
+    pub model_id: String,
+    pub filename: String,
+    pub downloaded_bytes: u64,
+    /// Approximate full size, from the model's nominal size
+    pub expected_bytes: u64,
+}
+
+/// Outcome of `repair_model`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RepairReport {
+    pub checked_files: usize,
+    /// Files that were missing or failed verification and were fetched again
+    pub redownloaded: Vec<String>,
+}
+
 /// Model manager for handling model downloads and caching
 pub struct ModelManager {
     cache_dir: PathBuf,
@@ -47,16 +189,7 @@ impl ModelManager {
     
     /// Check if model is cached
     pub async fn is_cached(&self, model: &LocalModel) -> bool {
-        match model {
-            LocalModel::Custom { .. } => {
-                // For custom models, always return true (user provides the path)
-                true
-            },
-            _ => {
-                // For predefined models, return false to prompt user to browse
-                false
-            }
-        }
+        self.is_model_downloaded(model)
     }
     
     /// Check if model is downloaded (synchronous version for use in Tauri commands)
@@ -66,13 +199,27 @@ impl ModelManager {
                 // For custom models, always return true (user provides the path)
                 true
             },
-            _ => {
-                // For predefined models, return false to prompt user to browse
-                false
-            }
+            _ => self.is_committed(model),
         }
     }
     
+    /// Whether the model was committed and every committed file is still
+    /// there at its verified size
+    fn is_committed(&self, model: &LocalModel) -> bool {
+        let model_path = self.get_model_path(model);
+        let Ok(raw) = std::fs::read_to_string(model_path.join(COMMIT_MARKER)) else {
+            return false;
+        };
+        let Ok(committed) = serde_json::from_str::<CommittedModel>(&raw) else {
+            return false;
+        };
+        committed.files.iter().all(|file| {
+            std::fs::metadata(model_path.join(&file.filename))
+                .map(|m| m.len() == file.size)
+                .unwrap_or(false)
+        })
+    }
+    
     /// Get model path
     pub fn get_model_path(&self, model: &LocalModel) -> PathBuf {
         self.cache_dir.join(model.model_id())
@@ -89,122 +236,333 @@ impl ModelManager {
         Ok(model_path)
     }
     
-    /// Download model - production implementation with chunked streaming
+    /// Download every file of a model, a few at a time. Each file streams
+    /// into `<file>.part`, resumes with a Range request after an
+    /// interruption and only takes its final name once its SHA256 matches.
+    /// The model counts as cached once all files are in place.
     pub async fn download_model(&self, model: &LocalModel) -> Result<()> {
-        let model_id = model.model_id();
-        let model_path = self.get_model_path(model);
+        let manifest = Self::manifest(model)?;
+        let Some(manifest) = manifest else {
+            // Phi-4 is already available locally
+            return Ok(());
+        };
         
-        // Create model directory
+        if self.is_committed(model) {
+            let size = dir_size(&self.get_model_path(model)).await.unwrap_or(0);
+            {
+                let mut progress = self.download_progress.write().await;
+                *progress = DownloadProgress {
+                    model_name: model.model_id().to_string(),
+                    total_size: size,
+                    downloaded: size,
+                    is_complete: true,
+                    ..Default::default()
+                };
+            }
+            tracing::info!(model = model.model_id(), "Model already cached");
+            return Ok(());
+        }
+        
+        // Files already under their final name were verified when renamed
+        self.fetch_manifest(model, &manifest, false).await?;
+        Ok(())
+    }
+    
+    /// Re-check every file of a model, fetching again only the ones that
+    /// are missing or fail checksum verification
+    pub async fn repair_model(&self, model: &LocalModel) -> Result<RepairReport> {
+        let Some(manifest) = Self::manifest(model)? else {
+            return Ok(RepairReport::default());
+        };
+        let redownloaded = self.fetch_manifest(model, &manifest, true).await?;
+        Ok(RepairReport {
+            checked_files: manifest.files.len(),
+            redownloaded,
+        })
+    }
+    
+    /// Manifest of a predefined model; None for models that are not downloaded
+    fn manifest(model: &LocalModel) -> Result<Option<ModelManifest>> {
+        match model {
+            LocalModel::Phi4 => Ok(None),
+            LocalModel::Custom { filename, .. } => {
+                Err(anyhow!("Custom model download not supported: {}", filename))
+            }
+            _ => ModelManifest::for_model(model)
+                .map(Some)
+                .ok_or_else(|| anyhow!("No download source for {}", model.model_id())),
+        }
+    }
+    
+    /// Bring every file in `manifest` into place, then commit the model.
+    /// Returns the files that had to be downloaded.
+    async fn fetch_manifest(
+        &self,
+        model: &LocalModel,
+        manifest: &ModelManifest,
+        verify_existing: bool,
+    ) -> Result<Vec<String>> {
+        let model_path = self.get_model_path(model);
         fs::create_dir_all(&model_path).await?;
         
-        // Update progress
+        // Incomplete until every file checks out again. Files the previous
+        // commit verified don't need hashing again unless asked to.
+        let marker = model_path.join(COMMIT_MARKER);
+        let previous: Vec<CommittedFile> = fs::read_to_string(&marker).await
+            .ok()
+            .and_then(|raw| serde_json::from_str::<CommittedModel>(&raw).ok())
+            .map(|committed| committed.files)
+            .unwrap_or_default();
+        if marker.exists() {
+            fs::remove_file(&marker).await?;
+        }
+        
         {
             let mut progress = self.download_progress.write().await;
-            progress.model_name = model_id.to_string();
-            progress.total_size = (model.size_gb() * 1024.0 * 1024.0 * 1024.0) as u64;
-            progress.downloaded = 0;
-            progress.is_downloading = true;
-            progress.is_complete = false;
-            progress.error = None;
-        }
-        
-        // Get the actual download URL based on model (ONNX versions)
-        let (url, filename) = match model {
-            LocalModel::Phi3Mini => (
-                // Microsoft Phi-3 ONNX model
-                "https://huggingface.co/microsoft/Phi-3-mini-4k-instruct-onnx/resolve/main/cpu_and_mobile/cpu-int4-rtn-block-32-acc-level-4/phi3-mini-4k-instruct-cpu-int4-rtn-block-32-acc-level-4.onnx",
-                "phi-3-mini.onnx"
-            ),
-            LocalModel::Phi4 => {
-                // Phi-4 is already available locally
-                return Ok(());
+            *progress = DownloadProgress {
+                model_name: model.model_id().to_string(),
+                total_size: (model.size_gb() * 1024.0 * 1024.0 * 1024.0) as u64,
+                estimated_size: (model.size_gb() * 1024.0 * 1024.0 * 1024.0) as u64,
+                is_downloading: true,
+                files: manifest.files.iter().map(|f| FileProgress {
+                    filename: f.filename.to_string(),
+                    ..Default::default()
+                }).collect(),
+                started_at: Some(std::time::Instant::now()),
+                ..Default::default()
+            };
+        }
+        
+        let pb = ProgressBar::new(0);
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+        
+        let results: Vec<Result<FetchedFile>> = futures::stream::iter(manifest.files.iter().copied().enumerate())
+            .map(|(index, file)| {
+                let committed = previous.iter().find(|c| c.filename == file.filename);
+                self.fetch_file(&model_path, index, file, committed, verify_existing, &pb)
+            })
+            .buffer_unordered(MAX_CONCURRENT_FILES)
+            .collect()
+            .await;
+        
+        let mut fetched = Vec::new();
+        let mut failures = Vec::new();
+        for result in results {
+            match result {
+                Ok(file) => fetched.push(file),
+                Err(e) => failures.push(e.to_string()),
             }
-            LocalModel::Mistral7B => (
-                // Mistral ONNX model
-                "https://huggingface.co/mistralai/Mistral-7B-Instruct-v0.2/resolve/main/model.onnx",
-                "mistral-7b.onnx"
-            ),
-            LocalModel::Orca2_7B => (
-                // Microsoft Orca 2 ONNX model
-                "https://huggingface.co/microsoft/Orca-2-7b/resolve/main/model.onnx",
-                "orca-2-7b.onnx"
-            ),
-            LocalModel::Qwen2_5B => (
-                // Qwen ONNX model
-                "https://huggingface.co/Qwen/Qwen2-0.5B-Instruct/resolve/main/model.onnx",
-                "qwen-2.5b.onnx"
-            ),
-            LocalModel::Gemma2B => (
-                // Google Gemma ONNX model
-                "https://huggingface.co/google/gemma-2b/resolve/main/model.onnx",
-                "gemma-2b.onnx"
-            ),
-            LocalModel::Sarvam1 => (
-                "https://huggingface.co/MaziyarPanahi/sarvam-1-GGUF/resolve/main/sarvam-1.Q5_K_M.gguf",
-                "sarvam-1.Q5_K_M.gguf"
-            ),
-            LocalModel::Custom { filename, .. } => {
-                return Err(anyhow!("Custom model download not supported: {}", filename));
+        }
+        
+        if !failures.is_empty() {
+            pb.abandon_with_message("Download failed");
+            let error = format!(
+                "{} of {} model files failed: {}",
+                failures.len(),
+                manifest.files.len(),
+                failures.join("; ")
+            );
+            {
+                let mut progress = self.download_progress.write().await;
+                progress.error = Some(error.clone());
+                progress.is_downloading = false;
+                progress.is_verifying = false;
             }
+            return Err(anyhow!(error));
+        }
+        pb.finish_with_message("Download complete");
+        
+        // Commit: write the marker atomically so a crash never leaves a
+        // half-written one that passes for complete
+        let committed = CommittedModel {
+            files: fetched.iter().map(|f| CommittedFile {
+                filename: f.filename.clone(),
+                size: f.size,
+                sha256: f.sha256.clone(),
+            }).collect(),
+            committed_at: chrono::Utc::now().timestamp(),
         };
+        let tmp = model_path.join(format!("{}.tmp", COMMIT_MARKER));
+        fs::write(&tmp, serde_json::to_vec_pretty(&committed)?).await?;
+        fs::rename(&tmp, &marker).await?;
         
-        let file_path = model_path.join(filename);
+        {
+            let mut progress = self.download_progress.write().await;
+            progress.is_downloading = false;
+            progress.is_verifying = false;
+            progress.is_complete = true;
+        }
+        let downloaded: Vec<String> = fetched.into_iter()
+            .filter(|f| f.downloaded)
+            .map(|f| f.filename)
+            .collect();
+        tracing::info!(
+            model = model.model_id(),
+            files = manifest.files.len(),
+            downloaded = downloaded.len(),
+            "Model files verified and committed"
+        );
+        Ok(downloaded)
+    }
+    
+    /// Make sure one manifest file is in place and verified. A file already
+    /// under its final name must match the published size; its SHA256 is
+    /// checked too unless the last commit recorded it (or always, with
+    /// `verify_existing`).
+    async fn fetch_file(
+        &self,
+        model_path: &Path,
+        index: usize,
+        file: ModelFile,
+        committed: Option<&CommittedFile>,
+        verify_existing: bool,
+        pb: &ProgressBar,
+    ) -> Result<FetchedFile> {
+        let file_path = model_path.join(file.filename);
+        let part_path = partial_path(&file_path);
+        let published = self.published_file(file.url).await;
+        // The last commit verified its digest, so it still counts offline
+        let expected_sha256 = file.sha256.map(str::to_string)
+            .or(published.sha256)
+            .or_else(|| committed.and_then(|c| c.sha256.clone()));
+        let expected_size = published.size.or(committed.map(|c| c.size));
+        if expected_sha256.is_none() && is_weight_file(file.filename) {
+            return Err(anyhow!(
+                "{}: no SHA256 is published for this file, so it can't be verified; refusing to install unverified model weights",
+                file.filename
+            ));
+        }
         
-        // Check if file exists and is complete
         if let Ok(metadata) = fs::metadata(&file_path).await {
-            let expected_min_size = (model.size_gb() * 1024.0 * 1024.0 * 1024.0 * 0.8) as u64;
-            if metadata.len() >= expected_min_size {
-                // File exists and looks complete
-                {
-                    let mut progress = self.download_progress.write().await;
-                    progress.downloaded = metadata.len();
-                    progress.total_size = metadata.len();
-                    progress.is_downloading = false;
-                    progress.is_complete = true;
+            let len = metadata.len();
+            let intact = match expected_size {
+                Some(size) if size != len => false,
+                _ => {
+                    let recorded = committed.is_some_and(|c| c.size == len);
+                    match &expected_sha256 {
+                        Some(expected) if verify_existing || !recorded => {
+                            self.download_progress.write().await.is_verifying = true;
+                            sha256_file(&file_path).await?.eq_ignore_ascii_case(expected)
+                        }
+                        _ => true,
+                    }
                 }
-                tracing::info!(path = %file_path.display(), "Model already cached");
-                return Ok(());
+            };
+            if intact {
+                self.record_progress(index, len, len, Some(len), pb).await;
+                self.mark_file_done(index).await;
+                return Ok(FetchedFile {
+                    filename: file.filename.to_string(),
+                    size: len,
+                    sha256: expected_sha256,
+                    downloaded: false,
+                });
+            }
+            
+            // Left behind by an interrupted download from before `.part`
+            // files: resume it rather than starting over
+            let truncated = expected_size.is_some_and(|size| len < size) && committed.is_none();
+            if truncated && !part_path.exists() {
+                tracing::info!(file = file.filename, bytes = len, "Resuming partial model file");
+                fs::rename(&file_path, &part_path).await?;
+            } else {
+                tracing::warn!(file = file.filename, "Model file failed verification, downloading again");
+                fs::remove_file(&file_path).await?;
             }
         }
         
-        // Perform the download with retries
+        // Each attempt resumes the last; the partial file is kept on failure
         for attempt in 1..=3 {
-            match self.download_with_resume(&url, &file_path, model).await {
-                Ok(_) => {
-                    // Mark complete
-                    {
-                        let mut progress = self.download_progress.write().await;
-                        progress.is_downloading = false;
-                        progress.is_complete = true;
-                    }
-                    tracing::info!(path = %file_path.display(), "Model downloaded successfully");
-                    return Ok(());
-                }
+            match self.download_with_resume(file.url, &part_path, index, pb).await {
+                Ok(_) => break,
                 Err(e) => {
                     if attempt == 3 {
-                        // Final attempt failed
-                        {
-                            let mut progress = self.download_progress.write().await;
-                            progress.error = Some(format!("Download failed after 3 attempts: {}", e));
-                            progress.is_downloading = false;
-                        }
-                        return Err(e);
+                        return Err(anyhow!("{}: download failed after 3 attempts: {}", file.filename, e));
                     }
-                    tracing::warn!(attempt = attempt, error = %e, "Download attempt failed, retrying");
+                    tracing::warn!(file = file.filename, attempt = attempt, error = %e, "Download attempt failed, retrying");
                     tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
                 }
             }
         }
         
-        Err(anyhow!("Download failed after all attempts"))
+        if let Err(e) = self.verify_download(&part_path, expected_sha256.as_deref()).await {
+            let _ = fs::remove_file(&part_path).await;
+            return Err(e);
+        }
+        
+        fs::rename(&part_path, &file_path).await?;
+        let size = fs::metadata(&file_path).await?.len();
+        self.mark_file_done(index).await;
+        tracing::info!(path = %file_path.display(), "Model file downloaded and verified");
+        Ok(FetchedFile {
+            filename: file.filename.to_string(),
+            size,
+            sha256: expected_sha256,
+            downloaded: true,
+        })
     }
     
-    /// Download with resume support and streaming
-    async fn download_with_resume(&self, url: &str, file_path: &Path, model: &LocalModel) -> Result<()> {
+    /// Check a finished download against its expected SHA256. Without a known
+    /// digest (small non-LFS files) the file is accepted on size alone.
+    async fn verify_download(&self, path: &Path, expected_sha256: Option<&str>) -> Result<()> {
+        let Some(expected) = expected_sha256 else {
+            tracing::debug!(path = %path.display(), "No published SHA256 for model file, skipping checksum verification");
+            return Ok(());
+        };
+        
+        self.download_progress.write().await.is_verifying = true;
+        let actual = sha256_file(path).await?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(anyhow!(
+                "Checksum mismatch for {}: expected SHA256 {}, got {}. The corrupt download was deleted; please download again.",
+                path.file_name().and_then(|n| n.to_str()).unwrap_or("model"),
+                expected,
+                actual
+            ));
+        }
+        tracing::info!(sha256 = %actual, "Model checksum verified");
+        Ok(())
+    }
+    
+    /// SHA256 and size Hugging Face publishes for a file, read from the
+    /// `X-Linked-Etag`/`X-Linked-Size` of the unredirected `resolve` response
+    /// (LFS files) or its `Content-Length` (small files served directly)
+    async fn published_file(&self, url: &str) -> PublishedFile {
+        let Ok(client) = Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .connect_timeout(std::time::Duration::from_secs(30))
+            .timeout(std::time::Duration::from_secs(60))
+            .build()
+        else {
+            return PublishedFile::default();
+        };
+        let mut request = client.head(url);
+        if let Some(token) = &self.hf_token {
+            request = request.bearer_auth(token);
+        }
+        let Ok(response) = request.send().await else {
+            return PublishedFile::default();
+        };
+        let header = |name: &str| response.headers().get(name).and_then(|v| v.to_str().ok());
+        let size = |name: &str| header(name).and_then(|v| v.trim().parse().ok());
+        // A redirect's Content-Length is that of the redirect body
+        let direct_size = if response.status().is_success() { size("content-length") } else { None };
+        PublishedFile {
+            sha256: header("x-linked-etag").and_then(parse_sha256_etag),
+            size: size("x-linked-size").or(direct_size),
+        }
+    }
+    
+    /// Download into `part_path`, continuing from its current length
+    async fn download_with_resume(&self, url: &str, part_path: &Path, index: usize, pb: &ProgressBar) -> Result<()> {
         // Check if partial file exists
-        let mut resume_from = 0u64;
-        if let Ok(metadata) = fs::metadata(&file_path).await {
-            resume_from = metadata.len();
+        let mut resume_from = fs::metadata(part_path).await.map(|m| m.len()).unwrap_or(0);
+        if resume_from > 0 {
             tracing::info!(resume_from = resume_from, "Resuming download");
         }
         
@@ -222,94 +580,123 @@ impl ModelManager {
         }
         
         let response = request.send().await?;
+        let status = response.status();
         
-        // Check response status
-        if !response.status().is_success() && response.status().as_u16() != 206 {
-            let status = response.status();
+        // Nothing left past the end of the partial file: it's already whole
+        if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && resume_from > 0 {
+            self.record_progress(index, resume_from, resume_from, Some(resume_from), pb).await;
+            return Ok(());
+        }
+        if !status.is_success() {
             let error_text = response.text().await.unwrap_or_default();
             return Err(anyhow!("Download failed: {} - {}", status, error_text));
         }
-        
-        let total_size = if resume_from > 0 {
-            // If resuming, add the already downloaded size
-            response.content_length().unwrap_or(0) + resume_from
-        } else {
-            response.content_length()
-                .unwrap_or((model.size_gb() * 1024.0 * 1024.0 * 1024.0) as u64)
-        };
-        
-        // Update total size
-        {
-            let mut progress = self.download_progress.write().await;
-            progress.total_size = total_size;
-            progress.downloaded = resume_from;
+        // A server that ignores Range sends the whole file again
+        if resume_from > 0 && status != reqwest::StatusCode::PARTIAL_CONTENT {
+            tracing::warn!("Server ignored the range request, restarting download");
+            resume_from = 0;
         }
         
-        // Create progress bar
-        let pb = ProgressBar::new(total_size);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")
-                .unwrap()
-                .progress_chars("#>-"),
-        );
-        pb.set_position(resume_from);
+        let total_size = response
+            .headers()
+            .get(reqwest::header::CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(content_range_total)
+            .or_else(|| response.content_length().map(|len| len + resume_from));
+        self.record_progress(index, resume_from, resume_from, total_size, pb).await;
         
         // Open file for writing (append if resuming)
         let mut file = if resume_from > 0 {
             tokio::fs::OpenOptions::new()
-                .write(true)
                 .append(true)
-                .open(&file_path)
+                .open(part_path)
                 .await?
         } else {
-            tokio::fs::File::create(&file_path).await?
+            tokio::fs::File::create(part_path).await?
         };
         
-        // Stream download with proper chunking
+        // Stream the body to disk chunk by chunk
         let mut downloaded = resume_from;
-        
-        // Get the response body as bytes
-        let content = response.bytes().await?;
-        
-        // Write all content at once
-        file.write_all(&content).await?;
-        downloaded += content.len() as u64;
-        
-        // Update progress
-        pb.set_position(downloaded);
-        
-        // Update global progress
-        {
-            let mut progress = self.download_progress.write().await;
-            progress.downloaded = downloaded;
+        let mut last_report = std::time::Instant::now();
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk: Bytes = chunk?;
+            file.write_all(&chunk).await?;
+            downloaded += chunk.len() as u64;
             
-            // Calculate speed
-            if pb.elapsed().as_secs() > 0 {
-                let speed = downloaded / pb.elapsed().as_secs();
-                progress.download_speed = Some(speed);
+            if last_report.elapsed() >= PROGRESS_INTERVAL {
+                last_report = std::time::Instant::now();
+                self.record_progress(index, downloaded, resume_from, total_size, pb).await;
             }
         }
+        self.record_progress(index, downloaded, resume_from, total_size, pb).await;
         
         // Final flush
         file.flush().await?;
         file.sync_all().await?;
         
-        pb.finish_with_message("Download complete");
-        
-        // Verify file size
-        let final_metadata = fs::metadata(&file_path).await?;
-        if final_metadata.len() < (model.size_gb() * 1024.0 * 1024.0 * 1024.0 * 0.8) as u64 {
-            return Err(anyhow!(
-                "Downloaded file is too small: {} bytes, expected ~{} GB",
-                final_metadata.len(),
-                model.size_gb()
-            ));
+        if let Some(total) = total_size {
+            if downloaded != total {
+                return Err(anyhow!(
+                    "Download interrupted at {} of {} bytes",
+                    downloaded,
+                    total
+                ));
+            }
         }
         
         Ok(())
     }
     
+    /// Publish one file's byte count and refresh the totals across all files
+    async fn record_progress(
+        &self,
+        index: usize,
+        downloaded: u64,
+        resumed_from: u64,
+        total_size: Option<u64>,
+        pb: &ProgressBar,
+    ) {
+        let mut progress = self.download_progress.write().await;
+        if let Some(file) = progress.files.get_mut(index) {
+            file.downloaded = downloaded;
+            file.resumed_from = resumed_from;
+            if let Some(total) = total_size {
+                file.total_size = total;
+            }
+        }
+        progress.refresh_totals();
+        pb.set_length(progress.total_size);
+        pb.set_position(progress.downloaded);
+    }
+    
+    async fn mark_file_done(&self, index: usize) {
+        let mut progress = self.download_progress.write().await;
+        if let Some(file) = progress.files.get_mut(index) {
+            file.is_complete = true;
+        }
+    }
+    
+    /// Partially downloaded model files, with bytes fetched so far
+    pub async fn partial_downloads(&self) -> Vec<PartialDownload> {
+        let mut partials = Vec::new();
+        for model in &DOWNLOADABLE_MODELS {
+            let Some(manifest) = ModelManifest::for_model(model) else { continue };
+            for file in &manifest.files {
+                let part_path = partial_path(&self.get_model_path(model).join(file.filename));
+                if let Ok(metadata) = fs::metadata(&part_path).await {
+                    partials.push(PartialDownload {
+                        model_id: model.model_id().to_string(),
+                        filename: file.filename.to_string(),
+                        downloaded_bytes: metadata.len(),
+                        expected_bytes: (model.size_gb() * 1024.0 * 1024.0 * 1024.0) as u64,
+                    });
+                }
+            }
+        }
+        partials
+    }
+    
     /// Get download progress
     pub async fn get_progress(&self) -> DownloadProgress {
         self.download_progress.read().await.clone()
@@ -379,9 +766,48 @@ pub struct DownloadProgress {
     pub is_complete: bool,
     pub error: Option<String>,
     pub download_speed: Option<u64>, // bytes per second
+    /// Bytes already on disk when the current attempts started, all files
+    pub resumed_from: u64,
+    /// Download finished, checksum being computed
+    pub is_verifying: bool,
+    /// Per-file progress; the fields above aggregate across these
+    pub files: Vec<FileProgress>,
+    /// Nominal model size, the total until every file's length is known
+    pub estimated_size: u64,
+    pub started_at: Option<std::time::Instant>,
+}
+
+/// Progress of one file in a model's manifest
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FileProgress {
+    pub filename: String,
+    pub downloaded: u64,
+    pub total_size: u64,
+    pub resumed_from: u64,
+    pub is_complete: bool,
 }
 
 impl DownloadProgress {
+    /// Recompute the aggregate byte counts and speed from `files`
+    fn refresh_totals(&mut self) {
+        let known: u64 = self.files.iter().map(|f| f.total_size).sum();
+        self.total_size = if self.files.iter().any(|f| f.total_size == 0) {
+            known.max(self.estimated_size)
+        } else {
+            known
+        };
+        self.downloaded = self.files.iter().map(|f| f.downloaded).sum();
+        self.resumed_from = self.files.iter().map(|f| f.resumed_from).sum();
+        if let Some(started) = self.started_at {
+            let elapsed = started.elapsed().as_secs_f64();
+            if elapsed > 0.0 {
+                let fetched = self.downloaded.saturating_sub(self.resumed_from);
+                self.download_speed = Some((fetched as f64 / elapsed) as u64);
+            }
+        }
+    }
+    
+
     pub fn percentage(&self) -> f32 {
         if self.total_size == 0 {
             return 0.0;
@@ -433,6 +859,53 @@ impl ModelDownloader {
     }
 }
 
+/// Where a file's bytes are kept until the download is verified
+fn partial_path(file_path: &Path) -> PathBuf {
+    let mut name = file_path.as_os_str().to_owned();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+/// Total length from a `Content-Range: bytes 100-199/1000` header
+fn content_range_total(header: &str) -> Option<u64> {
+    header.rsplit_once('/')?.1.trim().parse().ok()
+}
+
+/// SHA256 from a (possibly quoted or weak) ETag, if it is one
+/// Model weights, which are always stored in LFS and must be checksummed
+fn is_weight_file(filename: &str) -> bool {
+    [".onnx", ".onnx.data", ".gguf", ".bin", ".safetensors"]
+        .iter()
+        .any(|ext| filename.ends_with(ext))
+}
+
+fn parse_sha256_etag(etag: &str) -> Option<String> {
+    let value = etag.trim().trim_start_matches("W/").trim_matches('"');
+    (value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| value.to_ascii_lowercase())
+}
+
+/// Hex SHA256 of a file, hashed off the async runtime
+async fn sha256_file(path: &Path) -> Result<String> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || -> Result<String> {
+        use std::io::Read;
+        let mut file = std::fs::File::open(&path)?;
+        let mut hasher = Sha256::new();
+        let mut buf = vec![0u8; 1 << 20];
+        loop {
+            let n = file.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    })
+    .await
+    .map_err(|e| anyhow!("Checksum task panicked: {}", e))?
+}
+
 /// Calculate directory size recursively
 fn dir_size(path: &Path) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<u64>> + Send + '_>> {
     Box::pin(async move {
@@ -457,4 +930,120 @@ fn dir_size(path: &Path) -> std::pin::Pin<Box<dyn std::future::Future<Output = R
         
         Ok(size)
     })
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_resume_helpers_and_checksum_verification() {
+        assert_eq!(content_range_total("bytes 100-199/1000"), Some(1000));
+        assert_eq!(content_range_total("bytes */*"), None);
+        assert_eq!(
+            partial_path(Path::new("/models/qwen/qwen-2.5b.onnx")),
+            PathBuf::from("/models/qwen/qwen-2.5b.onnx.part")
+        );
+
+        let digest = "2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824";
+        assert_eq!(parse_sha256_etag(&format!("\"{}\"", digest)), Some(digest.to_ascii_lowercase()));
+        assert_eq!(parse_sha256_etag("W/\"abc123\""), None);
+
+        let dir = std::env::temp_dir().join(format!("shodh-model-dl-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("model.gguf.part");
+        std::fs::write(&path, "hello").unwrap();
+        let manager = ModelManager::new(dir.clone());
+
+        assert!(manager.verify_download(&path, Some(digest)).await.is_ok());
+        let err = manager.verify_download(&path, Some(&"0".repeat(64))).await.unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Serves `files` by path to any number of GET/HEAD requests
+    async fn file_server(files: Vec<(&'static str, &'static str)>) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0u8; 4096];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                let head = String::from_utf8_lossy(&buf[..n]).to_string();
+                let mut parts = head.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or_default().to_string();
+                let reply = match files.iter().find(|(p, _)| *p == path) {
+                    Some((_, body)) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        if method == "HEAD" { "" } else { body }
+                    ),
+                    None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+                };
+                let _ = socket.write_all(reply.as_bytes()).await;
+            }
+        });
+        base
+    }
+
+    #[tokio::test]
+    async fn test_manifest_commits_only_when_complete_and_repairs_missing_files() {
+        let base = file_server(vec![("/model.onnx", "weights"), ("/tokenizer.json", "{}")]).await;
+        let url = |path: &str| -> &'static str { Box::leak(format!("{}{}", base, path).into_boxed_str()) };
+        let file = |path: &str, filename| ModelFile { url: url(path), filename, sha256: None };
+        // sha256("weights")
+        let weights = |path: &str, filename| ModelFile {
+            sha256: Some("9a129038d9a00aed0cf6a7ea059ca50a813449061ab87848cf1a13eafdf33b2c"),
+            ..file(path, filename)
+        };
+
+        let dir = std::env::temp_dir().join(format!("shodh-model-manifest-{}", uuid::Uuid::new_v4()));
+        let manager = ModelManager::new(dir.clone());
+        let model = LocalModel::Qwen2_5B;
+        let model_path = manager.get_model_path(&model);
+
+        // One file missing upstream: nothing is committed
+        let broken = ModelManifest {
+            files: vec![weights("/model.onnx", "model.onnx"), weights("/missing.bin", "missing.bin")],
+        };
+        assert!(manager.fetch_manifest(&model, &broken, false).await.is_err());
+        assert!(!manager.is_model_downloaded(&model));
+        assert!(model_path.join("model.onnx").exists());
+
+        let manifest = ModelManifest {
+            files: vec![weights("/model.onnx", "model.onnx"), file("/tokenizer.json", "tokenizer.json")],
+        };
+        let downloaded = manager.fetch_manifest(&model, &manifest, false).await.unwrap();
+        assert_eq!(downloaded, vec!["tokenizer.json".to_string()]);
+        assert!(manager.is_model_downloaded(&model));
+        let progress = manager.get_progress().await;
+        assert!(progress.is_complete);
+        assert_eq!(progress.total_size, 9);
+        assert_eq!(progress.downloaded, 9);
+
+        // Losing a file un-caches the model; repair fetches just that file
+        std::fs::remove_file(model_path.join("tokenizer.json")).unwrap();
+        assert!(!manager.is_model_downloaded(&model));
+        let repaired = manager.fetch_manifest(&model, &manifest, true).await.unwrap();
+        assert_eq!(repaired, vec!["tokenizer.json".to_string()]);
+        assert!(manager.is_model_downloaded(&model));
+
+        // Without a commit marker, a short file is not taken as complete
+        std::fs::remove_file(model_path.join(COMMIT_MARKER)).unwrap();
+        std::fs::write(model_path.join("model.onnx"), "wei").unwrap();
+        let downloaded = manager.fetch_manifest(&model, &manifest, false).await.unwrap();
+        assert_eq!(downloaded, vec!["model.onnx".to_string()]);
+        assert_eq!(std::fs::read_to_string(model_path.join("model.onnx")).unwrap(), "weights");
+
+        // Weights without any known digest are refused
+        let unverifiable = ModelManifest { files: vec![file("/model.onnx", "other.onnx")] };
+        let err = manager.fetch_manifest(&model, &unverifiable, false).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("refusing to install unverified model weights"));
+        assert!(!model_path.join("other.onnx").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
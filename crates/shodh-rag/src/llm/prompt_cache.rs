@@ -0,0 +1,152 @@
+//! Prompt-prefix cache for local inference.
+//!
+//! Holds the llama.cpp context state left behind by a conversation's last
+//! generation together with the tokens it covers, so the next turn only has
+//! to decode the part of the prompt that changed. Entries are evicted least
+//! recently used first once their combined size passes the byte budget.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Leading tokens folded into the cache key. Different prompt templates used
+/// within one conversation (query routing, reranking, the answer itself)
+/// start differently, so each gets its own slot instead of evicting the others.
+const KEY_PREFIX_TOKENS: usize = 32;
+
+/// Context state snapshot and the token sequence it holds
+pub struct CachedPrompt {
+    pub tokens: Vec<i32>,
+    pub state: Vec<u8>,
+}
+
+pub struct PromptCache {
+    max_bytes: usize,
+    used_bytes: usize,
+    clock: u64,
+    entries: HashMap<String, (u64, CachedPrompt)>,
+}
+
+impl PromptCache {
+    /// A cache holding at most `max_bytes` of state; 0 disables it
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            used_bytes: 0,
+            clock: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_bytes > 0
+    }
+
+    /// Slot for a prompt within a conversation
+    pub fn key(conversation_id: &str, tokens: &[i32]) -> String {
+        let mut hasher = DefaultHasher::new();
+        tokens[..tokens.len().min(KEY_PREFIX_TOKENS)].hash(&mut hasher);
+        format!("{}:{:016x}", conversation_id, hasher.finish())
+    }
+
+    /// Remove and return the entry for `key`. Callers put it back with
+    /// `insert` once done, so concurrent requests never share one state.
+    pub fn take(&mut self, key: &str) -> Option<CachedPrompt> {
+        let (_, entry) = self.entries.remove(key)?;
+        self.used_bytes -= entry.state.len();
+        Some(entry)
+    }
+
+    pub fn insert(&mut self, key: String, entry: CachedPrompt) {
+        self.take(&key);
+        if entry.state.len() > self.max_bytes {
+            return;
+        }
+        while self.used_bytes + entry.state.len() > self.max_bytes {
+            let Some(oldest) = self.entries.iter()
+                .min_by_key(|(_, (used, _))| *used)
+                .map(|(k, _)| k.clone())
+            else {
+                break;
+            };
+            self.take(&oldest);
+        }
+        self.clock += 1;
+        self.used_bytes += entry.state.len();
+        self.entries.insert(key, (self.clock, entry));
+    }
+
+    /// Drop every entry belonging to `conversation_id`
+    pub fn invalidate(&mut self, conversation_id: &str) {
+        let prefix = format!("{}:", conversation_id);
+        let keys: Vec<String> = self.entries.keys().filter(|k| k.starts_with(&prefix)).cloned().collect();
+        for key in keys {
+            self.take(&key);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn used_bytes(&self) -> usize {
+        self.used_bytes
+    }
+}
+
+/// Number of leading tokens `a` and `b` share
+pub fn common_prefix_len(a: &[i32], b: &[i32]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(tokens: &[i32], bytes: usize) -> CachedPrompt {
+        CachedPrompt { tokens: tokens.to_vec(), state: vec![0; bytes] }
+    }
+
+    #[test]
+    fn test_prompt_cache_evicts_least_recently_used() {
+        let mut cache = PromptCache::new(100);
+        cache.insert("a".into(), entry(&[1], 40));
+        cache.insert("b".into(), entry(&[2], 40));
+
+        // Touch "a" so "b" becomes the oldest
+        let a = cache.take("a").unwrap();
+        cache.insert("a".into(), a);
+        cache.insert("c".into(), entry(&[3], 40));
+
+        assert!(cache.take("b").is_none());
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.used_bytes(), 80);
+
+        // Larger than the whole budget: not cached, nothing evicted
+        cache.insert("d".into(), entry(&[4], 200));
+        assert_eq!(cache.len(), 2);
+
+        cache.insert(PromptCache::key("conv", &[1, 2]), entry(&[1, 2], 10));
+        assert_eq!(cache.len(), 3);
+        cache.invalidate("conv");
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.used_bytes(), 80);
+    }
+
+    #[test]
+    fn test_prompt_cache_key_and_prefix() {
+        let system: Vec<i32> = (0..40).collect();
+        let mut turn2 = system.clone();
+        turn2.extend([100, 101]);
+        assert_eq!(PromptCache::key("c", &system), PromptCache::key("c", &turn2));
+        assert_ne!(PromptCache::key("c", &system), PromptCache::key("c", &[9, 9, 9]));
+        assert_ne!(PromptCache::key("c", &system), PromptCache::key("d", &system));
+
+        assert_eq!(common_prefix_len(&system, &turn2), 40);
+        assert_eq!(common_prefix_len(&[1, 2, 3], &[1, 5, 3]), 1);
+    }
+}
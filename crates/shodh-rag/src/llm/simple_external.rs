@@ -10,18 +10,51 @@ use tokio::sync::mpsc;
 
 use super::{
     LLMProvider, GenerationConfig,
-    ProviderInfo, MemoryUsage, TokenStream,
+    ProviderInfo, MemoryUsage, TokenStream, StreamErrorSlot, StreamUsageSlot,
     streaming::StreamingResponse,
     ApiProvider, ChatMessage, ChatRole, ToolCall, ToolSchema,
-    ChatResponse, ChatStreamEvent,
+    ChatResponse, ChatStreamEvent, TokenUsage, WebSource,
 };
 
+/// Anthropic allows at most four `cache_control` breakpoints per request
+const ANTHROPIC_MAX_CACHE_BREAKPOINTS: usize = 4;
+
+/// Base URL of a local Ollama server (native API, not the OpenAI-compatible shim)
+const OLLAMA_BASE_URL: &str = "http://localhost:11434";
+
+/// How many times to wait out a HuggingFace 503 "model is loading" response
+const HF_MAX_LOADING_WAITS: usize = 5;
+
+/// Upper bound on a single HuggingFace cold-start wait
+const HF_MAX_LOADING_WAIT: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// Replicate API base URL
+const REPLICATE_BASE_URL: &str = "https://api.replicate.com";
+
+/// How long a Replicate prediction may take, cold boot included
+const REPLICATE_PREDICTION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Delay between polls of a running Replicate prediction
+const REPLICATE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How many times to wait out a Baseten deployment waking from scale-to-zero
+const BASETEN_MAX_COLD_START_WAITS: usize = 6;
+
+/// Wait between cold-start retries when Baseten sends no `Retry-After`
+const BASETEN_COLD_START_DELAY: std::time::Duration = std::time::Duration::from_secs(10);
+
 /// External API provider (simplified for reliability)
 pub struct SimpleExternalProvider {
     provider: ApiProvider,
     api_key: String,
     model: String,
     client: Client,
+    /// Usage reported by the most recent request (shared with streaming tasks)
+    last_usage: std::sync::Arc<parking_lot::Mutex<Option<TokenUsage>>>,
+    /// Web pages cited by the most recent response (Perplexity)
+    last_web_sources: std::sync::Arc<parking_lot::Mutex<Vec<WebSource>>>,
+    /// Replaces the Replicate / Baseten deployment host (tests point it at a mock server)
+    api_base: Option<String>,
 }
 
 impl SimpleExternalProvider {
@@ -55,6 +88,99 @@ impl SimpleExternalProvider {
         })
     }
 
+    /// Send a request, retrying transient failures (connect/timeout errors and
+    /// 429/5xx responses) with exponential backoff and jitter. A `Retry-After`
+    /// header takes precedence over the computed delay. The final response is
+    /// returned as-is so each call site keeps its own error reporting.
+    async fn send_with_retry<F>(
+        build: F,
+        config: &GenerationConfig,
+    ) -> std::result::Result<reqwest::Response, reqwest::Error>
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+    {
+        Self::send_with_retry_where(build, config, is_retryable_status).await
+    }
+
+    /// `send_with_retry`, retrying only the statuses `retryable` accepts
+    async fn send_with_retry_where<F, R>(
+        build: F,
+        config: &GenerationConfig,
+        retryable: R,
+    ) -> std::result::Result<reqwest::Response, reqwest::Error>
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+        R: Fn(reqwest::StatusCode) -> bool,
+    {
+        let mut attempt = 0usize;
+        loop {
+            let result = build().send().await;
+            let retry_after = match &result {
+                Ok(resp) if retryable(resp.status()) => parse_retry_after(resp.headers()),
+                Err(e) if e.is_timeout() || e.is_connect() => None,
+                _ => return result,
+            };
+            if attempt >= config.max_retries {
+                return result;
+            }
+            attempt += 1;
+
+            let delay = retry_after.unwrap_or_else(|| backoff_delay(config.base_backoff_ms, attempt));
+            match &result {
+                Ok(resp) => tracing::warn!(
+                    attempt = attempt,
+                    max_retries = config.max_retries,
+                    status = %resp.status(),
+                    delay_ms = delay.as_millis() as u64,
+                    "Transient API error, retrying"
+                ),
+                Err(e) => tracing::warn!(
+                    attempt = attempt,
+                    max_retries = config.max_retries,
+                    error = %e,
+                    delay_ms = delay.as_millis() as u64,
+                    "API request failed, retrying"
+                ),
+            }
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Add OpenAI's `logit_bias` parameter (token id string -> bias in [-100, 100]).
+    /// Only OpenAI documents this field; other compatible APIs may reject it.
+    fn apply_logit_bias(&self, request: &mut serde_json::Value, config: &GenerationConfig) {
+        if config.logit_bias.is_empty() || !matches!(self.provider, ApiProvider::OpenAI) {
+            return;
+        }
+        let bias: serde_json::Map<String, serde_json::Value> = config.logit_bias.iter()
+            .map(|(token, bias)| (token.to_string(), json!(bias.clamp(-100.0, 100.0))))
+            .collect();
+        request["logit_bias"] = serde_json::Value::Object(bias);
+    }
+
+    /// Per-request model override, else the configured model. Google selects
+    /// the model in the endpoint URL, so it always uses the configured one.
+    fn model_for<'a>(&'a self, config: &'a GenerationConfig) -> &'a str {
+        config.model.as_deref().unwrap_or(&self.model)
+    }
+
+    /// Providers that accept `response_format: {type: "json_schema"}`
+    fn accepts_json_schema(&self) -> bool {
+        matches!(self.provider, ApiProvider::OpenAI | ApiProvider::OpenRouter)
+    }
+
+    /// Add `response_format` for schema-constrained output
+    fn apply_response_format(&self, request: &mut serde_json::Value, config: &GenerationConfig) {
+        let Some(schema) = config.json_schema.as_ref().filter(|_| self.accepts_json_schema()) else {
+            return;
+        };
+        // Non-strict: strict mode rejects schemas with optional properties
+        request["response_format"] = json!({
+            "type": "json_schema",
+            "json_schema": {"name": "structured_output", "schema": schema, "strict": false}
+        });
+    }
+
     pub fn new(provider: ApiProvider, api_key: String, model: String) -> Result<Self> {
         let client = Client::builder()
             .connect_timeout(std::time::Duration::from_secs(15))
@@ -74,8 +200,30 @@ impl SimpleExternalProvider {
             api_key,
             model,
             client,
+            last_usage: Default::default(),
+            last_web_sources: Default::default(),
+            api_base: None,
         })
     }
+
+    #[cfg(test)]
+    fn with_api_base(mut self, base: &str) -> Self {
+        self.api_base = Some(base.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Baseten model id when the configured model is a dedicated deployment
+    /// (e.g. `abcd1234`) rather than a Model APIs slug like `deepseek-ai/DeepSeek-V3-0324`.
+    /// Deployments are called on their own predict endpoint.
+    fn baseten_deployment<'a>(&'a self, config: &'a GenerationConfig) -> Option<&'a str> {
+        let model = self.model_for(config);
+        (matches!(self.provider, ApiProvider::Baseten) && !model.contains('/')).then_some(model)
+    }
+
+    /// APIs that take a single prompt: chat is flattened and tools are not offered
+    fn is_prompt_only(&self, config: &GenerationConfig) -> bool {
+        matches!(self.provider, ApiProvider::Replicate) || self.baseten_deployment(config).is_some()
+    }
     
     fn get_endpoint(&self) -> String {
         match &self.provider {
@@ -88,7 +236,7 @@ impl SimpleExternalProvider {
             ApiProvider::Google => format!("https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent", self.model),
             ApiProvider::Replicate => "https://api.replicate.com/v1/predictions".to_string(),
             ApiProvider::Baseten => "https://inference.baseten.co/v1/chat/completions".to_string(),
-            ApiProvider::Ollama => "http://localhost:11434/v1/chat/completions".to_string(),
+            ApiProvider::Ollama => format!("{}/v1/chat/completions", OLLAMA_BASE_URL),
             ApiProvider::HuggingFace { model_id } => format!("https://api-inference.huggingface.co/models/{}", model_id),
             ApiProvider::Custom { endpoint } => endpoint.clone(),
         }
@@ -102,12 +250,18 @@ impl LLMProvider for SimpleExternalProvider {
         prompt: &str,
         config: &GenerationConfig,
     ) -> Result<String> {
+        // Clear usage from the previous request so callers never see stale counts
+        *self.last_usage.lock() = None;
+        self.last_web_sources.lock().clear();
+        if let Some(model_id) = self.baseten_deployment(config) {
+            return self.baseten_generate(model_id, prompt, config).await;
+        }
         match &self.provider {
             ApiProvider::OpenAI | ApiProvider::OpenRouter | ApiProvider::Together | ApiProvider::Grok | ApiProvider::Perplexity | ApiProvider::Baseten | ApiProvider::Ollama => {
                 self.openai_compatible_generate(prompt, config).await
             }
             ApiProvider::Anthropic => {
-                self.anthropic_generate(prompt, config).await
+                self.anthropic_generate(&[], prompt, config).await
             }
             ApiProvider::Google => {
                 self.google_generate(prompt, config).await
@@ -119,7 +273,7 @@ impl LLMProvider for SimpleExternalProvider {
                 self.openai_compatible_generate(prompt, config).await
             }
             ApiProvider::Replicate => {
-                Err(anyhow!("Replicate requires streaming implementation - use non-blocking generation"))
+                self.replicate_generate(prompt, config).await
             }
         }
     }
@@ -129,38 +283,56 @@ impl LLMProvider for SimpleExternalProvider {
         prompt: &str,
         config: &GenerationConfig,
     ) -> Result<TokenStream> {
+        self.last_web_sources.lock().clear();
+        if self.baseten_deployment(config).is_some() {
+            return self.simulated_stream(prompt, config).await;
+        }
         match &self.provider {
             ApiProvider::OpenAI | ApiProvider::OpenRouter | ApiProvider::Together
             | ApiProvider::Grok | ApiProvider::Perplexity | ApiProvider::Baseten
-            | ApiProvider::Ollama | ApiProvider::Custom { .. } => {
+            | ApiProvider::Custom { .. } => {
                 self.openai_stream(prompt, config).await
             }
-            _ => {
-                // Providers without SSE: fall back to chunked non-streaming.
-                // Send word-by-word to simulate streaming (safe for any UTF-8).
-                let response = self.generate(prompt, config).await?;
-                let (sender, receiver) = tokio::sync::mpsc::channel(256);
-                tokio::spawn(async move {
-                    // Split on whitespace boundaries to avoid breaking UTF-8 chars
-                    let mut chars = response.chars().peekable();
-                    let mut chunk = String::with_capacity(40);
-                    while let Some(c) = chars.next() {
-                        chunk.push(c);
-                        // Flush at word boundaries (~30 chars per chunk)
-                        if chunk.len() >= 30 && (c == ' ' || c == '\n') {
-                            if sender.send(std::mem::take(&mut chunk)).await.is_err() {
-                                break;
-                            }
-                        }
-                    }
-                    // Flush remainder
-                    if !chunk.is_empty() {
-                        let _ = sender.send(chunk).await;
-                    }
-                });
-                Ok(TokenStream::new(receiver))
+            ApiProvider::Ollama => {
+                self.ollama_stream(prompt, config).await
+            }
+            ApiProvider::HuggingFace { .. } => {
+                self.huggingface_stream(prompt, config).await
+            }
+            ApiProvider::Replicate => {
+                self.replicate_stream(prompt, config).await
             }
+            _ => self.simulated_stream(prompt, config).await,
+        }
+    }
+
+    async fn generate_with_system(
+        &self,
+        system: &[String],
+        prompt: &str,
+        config: &GenerationConfig,
+    ) -> Result<String> {
+        if matches!(self.provider, ApiProvider::Anthropic) {
+            *self.last_usage.lock() = None;
+            self.last_web_sources.lock().clear();
+            return self.anthropic_generate(system, prompt, config).await;
+        }
+        self.generate(&super::join_system_prompt(system, prompt), config).await
+    }
+
+    async fn generate_stream_with_system(
+        &self,
+        system: &[String],
+        prompt: &str,
+        config: &GenerationConfig,
+    ) -> Result<TokenStream> {
+        if matches!(self.provider, ApiProvider::Anthropic) {
+            *self.last_usage.lock() = None;
+            self.last_web_sources.lock().clear();
+            let response = self.anthropic_generate(system, prompt, config).await?;
+            return Ok(self.simulate_stream(response, config));
         }
+        self.generate_stream(&super::join_system_prompt(system, prompt), config).await
     }
 
     async fn generate_with_context(
@@ -191,7 +363,11 @@ impl LLMProvider for SimpleExternalProvider {
 
         ProviderInfo {
             name: provider_name.to_string(),
-            model: self.model.clone(),
+            model: match &self.provider {
+                // The endpoint is keyed by model id; `model` may be a display name
+                ApiProvider::HuggingFace { model_id } => model_id.clone(),
+                _ => self.model.clone(),
+            },
             context_window: match &self.provider {
                 ApiProvider::OpenAI => 128000,
                 ApiProvider::Anthropic => 200000,
@@ -200,7 +376,9 @@ impl LLMProvider for SimpleExternalProvider {
                 ApiProvider::Grok => 131072,  // Grok supports 128k context
                 ApiProvider::Perplexity => 16384,
                 ApiProvider::Google => 1000000,  // Gemini 2.5 Pro supports 1M context
-                ApiProvider::Replicate => 4096,
+                ApiProvider::Replicate => 8192,  // Llama 3 family; varies by model
+                // Dedicated deployments vary; Model APIs serve 128k models
+                ApiProvider::Baseten if !self.model.contains('/') => 32768,
                 ApiProvider::Baseten => 128000,
                 ApiProvider::Ollama => 32768,
                 ApiProvider::HuggingFace { .. } => 4096,
@@ -217,6 +395,22 @@ impl LLMProvider for SimpleExternalProvider {
         }
     }
 
+    fn supports_json_schema(&self) -> bool {
+        self.accepts_json_schema()
+    }
+
+    fn last_usage(&self) -> Option<TokenUsage> {
+        self.last_usage.lock().clone()
+    }
+
+    fn supports_web_search(&self) -> bool {
+        matches!(self.provider, ApiProvider::Perplexity) && perplexity_searches(&self.model)
+    }
+
+    fn last_web_sources(&self) -> Vec<WebSource> {
+        self.last_web_sources.lock().clone()
+    }
+
     async fn is_ready(&self) -> bool {
         true
     }
@@ -235,6 +429,12 @@ impl LLMProvider for SimpleExternalProvider {
         tools: &[ToolSchema],
         config: &GenerationConfig,
     ) -> Result<ChatResponse> {
+        *self.last_usage.lock() = None;
+        self.last_web_sources.lock().clear();
+        if self.is_prompt_only(config) {
+            let text = self.generate(&flatten_messages(messages), config).await?;
+            return Ok(ChatResponse::Content(text));
+        }
         match &self.provider {
             ApiProvider::Anthropic => self.anthropic_chat(messages, tools, config).await,
             ApiProvider::Google => self.google_chat(messages, tools, config).await,
@@ -248,6 +448,20 @@ impl LLMProvider for SimpleExternalProvider {
         tools: &[ToolSchema],
         config: &GenerationConfig,
     ) -> Result<tokio::sync::mpsc::Receiver<ChatStreamEvent>> {
+        self.last_web_sources.lock().clear();
+        if self.is_prompt_only(config) {
+            let mut token_stream = self.generate_stream(&flatten_messages(messages), config).await?;
+            let (tx, rx) = tokio::sync::mpsc::channel(256);
+            tokio::spawn(async move {
+                while let Some(token) = token_stream.next().await {
+                    if tx.send(ChatStreamEvent::ContentDelta(token)).await.is_err() {
+                        break;
+                    }
+                }
+                let _ = tx.send(ChatStreamEvent::Done).await;
+            });
+            return Ok(rx);
+        }
         match &self.provider {
             ApiProvider::Anthropic => self.anthropic_chat_stream(messages, tools, config).await,
             _ => self.openai_chat_stream(messages, tools, config).await,
@@ -256,16 +470,54 @@ impl LLMProvider for SimpleExternalProvider {
 }
 
 impl SimpleExternalProvider {
+    /// For providers without SSE: generate the full response, then send it
+    /// word-by-word to simulate streaming (safe for any UTF-8).
+    async fn simulated_stream(
+        &self,
+        prompt: &str,
+        config: &GenerationConfig,
+    ) -> Result<TokenStream> {
+        let response = self.generate(prompt, config).await?;
+        Ok(self.simulate_stream(response, config))
+    }
+
+    /// Replay an already generated response as a token stream
+    fn simulate_stream(&self, response: String, config: &GenerationConfig) -> TokenStream {
+        let usage_slot = StreamUsageSlot::new(parking_lot::Mutex::new(self.last_usage.lock().clone()));
+        let (sender, receiver) = tokio::sync::mpsc::channel(256);
+        let cancel = config.cancel.clone();
+        tokio::spawn(async move {
+            // Split on whitespace boundaries to avoid breaking UTF-8 chars
+            let mut chars = response.chars().peekable();
+            let mut chunk = String::with_capacity(40);
+            while let Some(c) = chars.next() {
+                if cancel.is_cancelled() {
+                    return;
+                }
+                chunk.push(c);
+                // Flush at word boundaries (~30 chars per chunk)
+                if chunk.len() >= 30 && (c == ' ' || c == '\n') {
+                    if sender.send(std::mem::take(&mut chunk)).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            // Flush remainder
+            if !chunk.is_empty() {
+                let _ = sender.send(chunk).await;
+            }
+        });
+        TokenStream::new(receiver).with_usage_slot(usage_slot)
+    }
+
     /// Real SSE streaming for OpenAI-compatible APIs
     async fn openai_stream(
         &self,
         prompt: &str,
         config: &GenerationConfig,
     ) -> Result<TokenStream> {
-        use futures::StreamExt;
-
-        let request = json!({
-            "model": self.model,
+        let mut request = json!({
+            "model": self.model_for(config),
             "messages": [
                 {"role": "user", "content": prompt}
             ],
@@ -275,23 +527,30 @@ impl SimpleExternalProvider {
             "frequency_penalty": (config.repetition_penalty - 1.0).max(0.0),
             "stream": true
         });
+        self.apply_logit_bias(&mut request, config);
+        if matches!(self.provider, ApiProvider::OpenAI | ApiProvider::OpenRouter) {
+            // Ask for a final chunk carrying real token counts
+            request["stream_options"] = json!({ "include_usage": true });
+        }
 
         let endpoint = self.get_endpoint();
-        let response = self.client
-            .post(&endpoint)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| {
-                if e.is_timeout() {
-                    anyhow!("Streaming request to {} timed out — check network connectivity", endpoint)
-                } else if e.is_connect() {
-                    anyhow!("Failed to connect to {} for streaming: {}", endpoint, e)
-                } else {
-                    anyhow!("Streaming request to {} failed: {}", endpoint, e)
-                }
-            })?;
+        let response = Self::send_with_retry(
+            || self.client
+                .post(&endpoint)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .json(&request),
+            config,
+        )
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                anyhow!("Streaming request to {} timed out — check network connectivity", endpoint)
+            } else if e.is_connect() {
+                anyhow!("Failed to connect to {} for streaming: {}", endpoint, e)
+            } else {
+                anyhow!("Streaming request to {} failed: {}", endpoint, e)
+            }
+        })?;
 
         let status = response.status();
         // Check content-type: if the server returned HTML instead of SSE, bail early
@@ -306,12 +565,16 @@ impl SimpleExternalProvider {
         }
 
         let (sender, receiver) = tokio::sync::mpsc::channel::<String>(256);
+        let usage_slot = StreamUsageSlot::default();
+        let task_usage = usage_slot.clone();
+        let task_sources = self.last_web_sources.clone();
         let mut byte_stream = response.bytes_stream();
+        let cancel = config.cancel.clone();
 
         tokio::spawn(async move {
             let mut buffer = String::new();
 
-            while let Some(chunk_result) = byte_stream.next().await {
+            while let Some(chunk_result) = cancel.next_unless_cancelled(&mut byte_stream).await {
                 let chunk = match chunk_result {
                     Ok(c) => c,
                     Err(_) => break,
@@ -334,6 +597,13 @@ impl SimpleExternalProvider {
                     }
 
                     if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(data) {
+                        if parsed["usage"].is_object() {
+                            *task_usage.lock() = Some(openai_usage(&parsed["usage"]));
+                        }
+                        let sources = parse_web_sources(&parsed);
+                        if !sources.is_empty() {
+                            *task_sources.lock() = sources;
+                        }
                         if let Some(content) = parsed["choices"][0]["delta"]["content"].as_str() {
                             if !content.is_empty() {
                                 if sender.send(content.to_string()).await.is_err() {
@@ -346,7 +616,126 @@ impl SimpleExternalProvider {
             }
         });
 
-        Ok(TokenStream::new(receiver))
+        Ok(TokenStream::new(receiver).with_usage_slot(usage_slot))
+    }
+
+    /// Native NDJSON streaming for Ollama's `/api/generate` endpoint.
+    /// Each line is a JSON object; tokens arrive until one carries `done: true`.
+    async fn ollama_stream(
+        &self,
+        prompt: &str,
+        config: &GenerationConfig,
+    ) -> Result<TokenStream> {
+        let request = json!({
+            "model": self.model_for(config),
+            "prompt": prompt,
+            "stream": true,
+            "options": Self::ollama_options(config),
+        });
+
+        let endpoint = format!("{}/api/generate", OLLAMA_BASE_URL);
+        let response = Self::send_with_retry(
+            || self.client
+                .post(&endpoint)
+                .json(&request),
+            config,
+        )
+        .await
+        .map_err(|e| {
+            if e.is_connect() {
+                anyhow!("Failed to connect to Ollama at {} — is `ollama serve` running? {}", endpoint, e)
+            } else {
+                anyhow!("Ollama streaming request to {} failed: {}", endpoint, e)
+            }
+        })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error = response.text().await?;
+            let preview: String = error.chars().take(300).collect();
+            return Err(anyhow!("Ollama streaming error (HTTP {}): {}", status, preview));
+        }
+
+        let (sender, receiver) = tokio::sync::mpsc::channel::<String>(256);
+        let error_slot = StreamErrorSlot::default();
+        let task_error = error_slot.clone();
+        let usage_slot = StreamUsageSlot::default();
+        let task_usage = usage_slot.clone();
+        let mut byte_stream = response.bytes_stream();
+        let cancel = config.cancel.clone();
+
+        tokio::spawn(async move {
+            let mut lines = LineBuffer::default();
+
+            while let Some(chunk_result) = cancel.next_unless_cancelled(&mut byte_stream).await {
+                let chunk = match chunk_result {
+                    Ok(c) => c,
+                    Err(e) => {
+                        *task_error.lock() = Some(format!("Ollama stream interrupted: {}", e));
+                        return;
+                    }
+                };
+
+                for line in lines.push(&chunk) {
+                    match parse_ollama_line(line.trim()) {
+                        Ok(Some(parsed)) => {
+                            if !parsed.text.is_empty() && sender.send(parsed.text).await.is_err() {
+                                return;
+                            }
+                            if parsed.done {
+                                *task_usage.lock() = parsed.usage;
+                                return;
+                            }
+                        }
+                        Ok(None) => continue,
+                        Err(e) => {
+                            tracing::warn!(error = %e, "Ollama returned an error mid-stream");
+                            *task_error.lock() = Some(format!("Ollama error: {}", e));
+                            return;
+                        }
+                    }
+                }
+            }
+
+            // Flush a trailing line that arrived without a newline
+            match parse_ollama_line(lines.finish().trim()) {
+                Ok(Some(parsed)) => {
+                    if !parsed.text.is_empty() {
+                        let _ = sender.send(parsed.text).await;
+                    }
+                    if parsed.done {
+                        *task_usage.lock() = parsed.usage;
+                        return;
+                    }
+                }
+                Err(e) => {
+                    *task_error.lock() = Some(format!("Ollama error: {}", e));
+                    return;
+                }
+                Ok(None) => {}
+            }
+            *task_error.lock() = Some("Ollama stream ended before `done: true`".to_string());
+        });
+
+        Ok(TokenStream::with_error_slot(receiver, error_slot).with_usage_slot(usage_slot))
+    }
+
+    /// Map GenerationConfig onto Ollama's `options` object
+    fn ollama_options(config: &GenerationConfig) -> serde_json::Value {
+        let mut options = json!({
+            "num_predict": config.max_tokens,
+            "temperature": config.temperature,
+            "top_p": config.top_p,
+            "top_k": config.top_k,
+            "repeat_penalty": config.repetition_penalty,
+        });
+        if !config.stop_sequences.is_empty() {
+            options["stop"] = json!(config.stop_sequences);
+        }
+        if let Some(seed) = config.seed {
+            options["seed"] = json!(seed);
+        }
+        options
     }
 
     /// OpenAI-compatible generation
@@ -358,14 +747,14 @@ impl SimpleExternalProvider {
         let endpoint = self.get_endpoint();
         tracing::debug!(
             endpoint = %endpoint,
-            model = %self.model,
+            model = %self.model_for(config),
             max_tokens = config.max_tokens,
             prompt_len = prompt.len(),
             "Sending OpenAI-compatible request"
         );
 
-        let request = json!({
-            "model": self.model,
+        let mut request = json!({
+            "model": self.model_for(config),
             "messages": [
                 {"role": "user", "content": prompt}
             ],
@@ -375,25 +764,29 @@ impl SimpleExternalProvider {
             "frequency_penalty": (config.repetition_penalty - 1.0).max(0.0),
             "stream": false
         });
-
-        let response = self.client
-            .post(&endpoint)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| {
-                if e.is_timeout() {
-                    tracing::error!(endpoint = %endpoint, "Request timed out (connect or response timeout)");
-                    anyhow!("Request to {} timed out — check network connectivity and whether the endpoint is reachable", endpoint)
-                } else if e.is_connect() {
-                    tracing::error!(endpoint = %endpoint, error = %e, "Connection failed");
-                    anyhow!("Failed to connect to {} — check network/firewall/proxy settings: {}", endpoint, e)
-                } else {
-                    tracing::error!(endpoint = %endpoint, error = %e, "Request failed");
-                    anyhow!("Request to {} failed: {}", endpoint, e)
-                }
-            })?;
+        self.apply_logit_bias(&mut request, config);
+        self.apply_response_format(&mut request, config);
+
+        let response = Self::send_with_retry(
+            || self.client
+                .post(&endpoint)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .json(&request),
+            config,
+        )
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                tracing::error!(endpoint = %endpoint, "Request timed out (connect or response timeout)");
+                anyhow!("Request to {} timed out — check network connectivity and whether the endpoint is reachable", endpoint)
+            } else if e.is_connect() {
+                tracing::error!(endpoint = %endpoint, error = %e, "Connection failed");
+                anyhow!("Failed to connect to {} — check network/firewall/proxy settings: {}", endpoint, e)
+            } else {
+                tracing::error!(endpoint = %endpoint, error = %e, "Request failed");
+                anyhow!("Request to {} failed: {}", endpoint, e)
+            }
+        })?;
 
         let status = response.status();
         if !status.is_success() {
@@ -402,7 +795,13 @@ impl SimpleExternalProvider {
             return Err(anyhow!("API error ({}): {}", status, error));
         }
 
-        let result: OpenAIResponse = Self::parse_json_response(response, &endpoint).await?;
+        let body: serde_json::Value = Self::parse_json_response(response, &endpoint).await?;
+        *self.last_web_sources.lock() = parse_web_sources(&body);
+        let result: OpenAIResponse = serde_json::from_value(body)
+            .map_err(|e| anyhow!("Unexpected response shape from {}: {}", endpoint, e))?;
+        if let Some(ref usage) = result.usage {
+            *self.last_usage.lock() = Some(openai_usage(usage));
+        }
 
         if result.choices.is_empty() {
             return Err(anyhow!("No choices returned from API"));
@@ -412,14 +811,16 @@ impl SimpleExternalProvider {
         Ok(result.choices[0].message.content.clone())
     }
 
-    /// Anthropic generation
+    /// Anthropic generation. `system` blocks go in the top-level system field,
+    /// each marked cacheable when `cache_system_prompt` is set.
     async fn anthropic_generate(
         &self,
+        system: &[String],
         prompt: &str,
         config: &GenerationConfig,
     ) -> Result<String> {
-        let request = json!({
-            "model": self.model,
+        let mut request = json!({
+            "model": self.model_for(config),
             "messages": [
                 {"role": "user", "content": prompt}
             ],
@@ -427,14 +828,19 @@ impl SimpleExternalProvider {
             "temperature": config.temperature,
             "top_p": config.top_p
         });
+        if let Some(sys) = Self::format_anthropic_system(system, config.cache_system_prompt) {
+            request["system"] = sys;
+        }
         
-        let response = self.client
-            .post(self.get_endpoint())
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .json(&request)
-            .send()
-            .await?;
+        let response = Self::send_with_retry(
+            || self.client
+                .post(self.get_endpoint())
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", "2023-06-01")
+                .json(&request),
+            config,
+        )
+        .await?;
         
         let status = response.status();
         if !status.is_success() {
@@ -444,6 +850,9 @@ impl SimpleExternalProvider {
         
         let endpoint = self.get_endpoint();
         let result: AnthropicResponse = Self::parse_json_response(response, &endpoint).await?;
+        if let Some(ref usage) = result.usage {
+            *self.last_usage.lock() = Some(anthropic_usage(usage));
+        }
 
         if result.content.is_empty() {
             return Err(anyhow!("No content returned from Anthropic API"));
@@ -470,13 +879,15 @@ impl SimpleExternalProvider {
             }
         });
 
-        let response = self.client
-            .post(self.get_endpoint())
-            .header("Content-Type", "application/json")
-            .header("x-goog-api-key", &self.api_key)
-            .json(&request)
-            .send()
-            .await?;
+        let response = Self::send_with_retry(
+            || self.client
+                .post(self.get_endpoint())
+                .header("Content-Type", "application/json")
+                .header("x-goog-api-key", &self.api_key)
+                .json(&request),
+            config,
+        )
+        .await?;
 
         let status = response.status();
         if !status.is_success() {
@@ -495,38 +906,384 @@ impl SimpleExternalProvider {
         Err(anyhow!("No response from Google Gemini"))
     }
 
-    /// HuggingFace generation
-    async fn huggingface_generate(
+    // ==================== Replicate ====================
+
+    /// Map `GenerationConfig` onto the input schema shared by Replicate's
+    /// language models. Older models read `max_new_tokens`; inputs a model
+    /// doesn't declare are ignored.
+    fn replicate_input(prompt: &str, config: &GenerationConfig) -> serde_json::Value {
+        let mut input = json!({
+            "prompt": prompt,
+            "max_tokens": config.max_tokens,
+            "max_new_tokens": config.max_tokens,
+            "temperature": config.temperature,
+            "top_p": config.top_p,
+            "repetition_penalty": config.repetition_penalty
+        });
+        if config.top_k > 0 {
+            input["top_k"] = json!(config.top_k);
+        }
+        if !config.stop_sequences.is_empty() {
+            input["stop_sequences"] = json!(config.stop_sequences.join(","));
+        }
+        if let Some(seed) = config.seed {
+            input["seed"] = json!(seed);
+        }
+        input
+    }
+
+    /// Create a prediction. Non-streaming requests ask Replicate to hold the
+    /// connection (`Prefer: wait`) so a warm model answers in one round trip.
+    async fn replicate_create(
         &self,
         prompt: &str,
         config: &GenerationConfig,
-        _model_id: &str,
+        stream: bool,
+    ) -> Result<serde_json::Value> {
+        let base = self.api_base.as_deref().unwrap_or(REPLICATE_BASE_URL);
+        let (endpoint, version) = replicate_target(base, self.model_for(config));
+        let mut request = json!({ "input": Self::replicate_input(prompt, config) });
+        if let Some(version) = version {
+            request["version"] = json!(version);
+        }
+        if stream {
+            request["stream"] = json!(true);
+        }
+
+        let response = Self::send_with_retry(
+            || {
+                let builder = self.client
+                    .post(&endpoint)
+                    .header("Authorization", format!("Bearer {}", self.api_key))
+                    .json(&request);
+                if stream { builder } else { builder.header("Prefer", "wait=60") }
+            },
+            config,
+        )
+        .await
+        .map_err(|e| anyhow!("Replicate request to {} failed: {}", endpoint, e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error = response.text().await?;
+            return Err(anyhow!("Replicate API error ({}): {}", status, error));
+        }
+        Self::parse_json_response(response, &endpoint).await
+    }
+
+    /// Poll a prediction until it finishes, fails or times out. A prediction
+    /// sits in `starting` while Replicate boots a cold model.
+    async fn replicate_wait(
+        &self,
+        mut prediction: serde_json::Value,
+        config: &GenerationConfig,
     ) -> Result<String> {
-        let request = json!({
-            "inputs": prompt,
-            "parameters": {
-                "max_new_tokens": config.max_tokens,
-                "temperature": config.temperature,
-                "top_p": config.top_p,
-                "repetition_penalty": config.repetition_penalty,
-                "do_sample": true,
-                "return_full_text": false
+        let deadline = std::time::Instant::now() + REPLICATE_PREDICTION_TIMEOUT;
+        let mut logged_boot = false;
+        loop {
+            if let Some(usage) = replicate_usage(&prediction) {
+                *self.last_usage.lock() = Some(usage);
             }
-        });
-        
+            if let Some(output) = replicate_outcome(&prediction)? {
+                return Ok(output);
+            }
+            let id = prediction["id"].as_str().unwrap_or_default().to_string();
+            if prediction["status"] == "starting" && !logged_boot {
+                logged_boot = true;
+                tracing::info!(prediction = %id, "Replicate model is booting (cold start), waiting");
+            }
+            if config.cancel.is_cancelled() {
+                cancel_replicate_prediction(&self.client, &self.api_key, &prediction).await;
+                return Err(anyhow!("Replicate prediction {} was cancelled", id));
+            }
+            if std::time::Instant::now() >= deadline {
+                cancel_replicate_prediction(&self.client, &self.api_key, &prediction).await;
+                return Err(anyhow!(
+                    "Replicate prediction {} did not finish within {}s",
+                    id,
+                    REPLICATE_PREDICTION_TIMEOUT.as_secs()
+                ));
+            }
+
+            tokio::time::sleep(REPLICATE_POLL_INTERVAL).await;
+            let url = match prediction["urls"]["get"].as_str() {
+                Some(url) => url.to_string(),
+                None => format!(
+                    "{}/v1/predictions/{}",
+                    self.api_base.as_deref().unwrap_or(REPLICATE_BASE_URL),
+                    id
+                ),
+            };
+            let response = Self::send_with_retry(
+                || self.client
+                    .get(&url)
+                    .header("Authorization", format!("Bearer {}", self.api_key)),
+                config,
+            )
+            .await
+            .map_err(|e| anyhow!("Polling Replicate prediction {} failed: {}", id, e))?;
+            let status = response.status();
+            if !status.is_success() {
+                let error = response.text().await?;
+                return Err(anyhow!("Replicate API error ({}): {}", status, error));
+            }
+            prediction = Self::parse_json_response(response, &url).await?;
+        }
+    }
+
+    /// Replicate generation: create a prediction and wait for its output
+    async fn replicate_generate(
+        &self,
+        prompt: &str,
+        config: &GenerationConfig,
+    ) -> Result<String> {
+        let prediction = self.replicate_create(prompt, config, false).await?;
+        self.replicate_wait(prediction, config).await
+    }
+
+    /// Replicate streaming via the prediction's `urls.stream` SSE endpoint.
+    /// Models without streaming support are polled and sent as one chunk.
+    async fn replicate_stream(
+        &self,
+        prompt: &str,
+        config: &GenerationConfig,
+    ) -> Result<TokenStream> {
+        let prediction = self.replicate_create(prompt, config, true).await?;
+        let Some(stream_url) = prediction["urls"]["stream"].as_str().map(str::to_string) else {
+            let output = self.replicate_wait(prediction, config).await?;
+            let (sender, receiver) = tokio::sync::mpsc::channel(1);
+            let _ = sender.send(output).await;
+            return Ok(TokenStream::new(receiver));
+        };
+
+        // The stream stays open through a cold boot, so no retry here
         let response = self.client
-            .post(self.get_endpoint())
+            .get(&stream_url)
+            .header("Accept", "text/event-stream")
+            .header("Cache-Control", "no-store")
             .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&request)
             .send()
-            .await?;
-        
+            .await
+            .map_err(|e| anyhow!("Replicate stream request to {} failed: {}", stream_url, e))?;
         let status = response.status();
         if !status.is_success() {
             let error = response.text().await?;
+            return Err(anyhow!("Replicate streaming error ({}): {}", status, error));
+        }
+
+        let (sender, receiver) = tokio::sync::mpsc::channel::<String>(256);
+        let error_slot = StreamErrorSlot::default();
+        let task_error = error_slot.clone();
+        let mut byte_stream = response.bytes_stream();
+        let cancel = config.cancel.clone();
+        let client = self.client.clone();
+        let api_key = self.api_key.clone();
+
+        tokio::spawn(async move {
+            let mut events = SseEvents::default();
+            while let Some(chunk_result) = cancel.next_unless_cancelled(&mut byte_stream).await {
+                let chunk = match chunk_result {
+                    Ok(c) => c,
+                    Err(e) => {
+                        *task_error.lock() = Some(format!("Replicate stream interrupted: {}", e));
+                        return;
+                    }
+                };
+                for (event, data) in events.push(&chunk) {
+                    match event.as_str() {
+                        "output" => {
+                            let delivered = data.is_empty() || sender.send(data).await.is_ok();
+                            if !delivered {
+                                return;
+                            }
+                        }
+                        "error" => {
+                            *task_error.lock() = Some(format!("Replicate prediction failed: {}", data));
+                            return;
+                        }
+                        "done" => return,
+                        _ => {}
+                    }
+                }
+            }
+            // Closing the stream doesn't stop the prediction, which keeps billing
+            if cancel.is_cancelled() {
+                cancel_replicate_prediction(&client, &api_key, &prediction).await;
+            }
+        });
+
+        Ok(TokenStream::with_error_slot(receiver, error_slot))
+    }
+
+    // ==================== Baseten deployments ====================
+
+    /// Predict endpoint of a dedicated Baseten deployment
+    fn baseten_predict_url(&self, model_id: &str) -> String {
+        match &self.api_base {
+            Some(base) => format!("{}/environments/production/predict", base),
+            None => format!("https://model-{}.api.baseten.co/environments/production/predict", model_id),
+        }
+    }
+
+    /// Request body for a Baseten deployment. LLMs packaged with Baseten's
+    /// engine builder accept the OpenAI chat schema on `/predict`.
+    fn baseten_request(prompt: &str, config: &GenerationConfig) -> serde_json::Value {
+        let mut request = json!({
+            "messages": [
+                {"role": "user", "content": prompt}
+            ],
+            "max_tokens": config.max_tokens,
+            "temperature": config.temperature,
+            "top_p": config.top_p,
+            "stream": false
+        });
+        if config.top_k > 0 {
+            request["top_k"] = json!(config.top_k);
+        }
+        if !config.stop_sequences.is_empty() {
+            request["stop"] = json!(config.stop_sequences);
+        }
+        if let Some(seed) = config.seed {
+            request["seed"] = json!(seed);
+        }
+        request
+    }
+
+    /// POST to a Baseten deployment, waiting out the 502/503/504 responses a
+    /// scaled-to-zero model returns while it wakes up. Sent without
+    /// `send_with_retry`, whose backoff would otherwise stack on these waits.
+    async fn baseten_send(
+        &self,
+        endpoint: &str,
+        request: &serde_json::Value,
+    ) -> Result<reqwest::Response> {
+        let mut waits = 0usize;
+        loop {
+            let response = self.client
+                .post(endpoint)
+                .header("Authorization", format!("Api-Key {}", self.api_key))
+                .json(request)
+                .send()
+                .await
+                .map_err(|e| anyhow!("Baseten request to {} failed: {}", endpoint, e))?;
+
+            let status = response.status();
+            if status.is_success() {
+                return Ok(response);
+            }
+            let cold_start = matches!(status.as_u16(), 502..=504);
+            if cold_start && waits < BASETEN_MAX_COLD_START_WAITS {
+                waits += 1;
+                let delay = parse_retry_after(response.headers()).unwrap_or(BASETEN_COLD_START_DELAY);
+                tracing::info!(
+                    endpoint = %endpoint,
+                    attempt = waits,
+                    delay_ms = delay.as_millis() as u64,
+                    "Baseten deployment is waking up, waiting"
+                );
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+            let error = response.text().await?;
+            return Err(anyhow!("Baseten API error ({}): {}", status, error));
+        }
+    }
+
+    /// Generation on a dedicated Baseten deployment
+    async fn baseten_generate(
+        &self,
+        model_id: &str,
+        prompt: &str,
+        config: &GenerationConfig,
+    ) -> Result<String> {
+        let endpoint = self.baseten_predict_url(model_id);
+        let request = Self::baseten_request(prompt, config);
+        let response = self.baseten_send(&endpoint, &request).await?;
+
+        let body: serde_json::Value = Self::parse_json_response(response, &endpoint).await?;
+        if body["usage"].is_object() {
+            *self.last_usage.lock() = Some(openai_usage(&body["usage"]));
+        }
+        baseten_output_text(&body)
+            .ok_or_else(|| anyhow!("Unrecognized response from Baseten deployment {}", model_id))
+    }
+
+    /// Build a HuggingFace `text-generation` request body
+    fn huggingface_request(prompt: &str, config: &GenerationConfig, stream: bool) -> serde_json::Value {
+        let mut parameters = json!({
+            "max_new_tokens": config.max_tokens,
+            "temperature": config.temperature,
+            "top_p": config.top_p,
+            "repetition_penalty": config.repetition_penalty,
+            "do_sample": config.temperature > 0.0,
+            "return_full_text": false
+        });
+        if !config.stop_sequences.is_empty() {
+            parameters["stop"] = json!(config.stop_sequences);
+        }
+        if let Some(seed) = config.seed {
+            parameters["seed"] = json!(seed);
+        }
+        json!({
+            "inputs": prompt,
+            "parameters": parameters,
+            "stream": stream
+        })
+    }
+
+    /// POST to the HuggingFace Inference API through `send_with_retry`, except
+    /// that 503s come back here: HF answers 503 while a cold model loads, so
+    /// they wait for the `estimated_time` the API reports (or back off when it
+    /// reports none), up to `HF_MAX_LOADING_WAITS` times.
+    async fn huggingface_send(&self, request: &serde_json::Value, config: &GenerationConfig) -> Result<reqwest::Response> {
+        let endpoint = self.get_endpoint();
+        let mut waits = 0usize;
+        loop {
+            let response = Self::send_with_retry_where(
+                || self.client
+                    .post(&endpoint)
+                    .header("Authorization", format!("Bearer {}", self.api_key))
+                    .json(request),
+                config,
+                |status| status != reqwest::StatusCode::SERVICE_UNAVAILABLE && is_retryable_status(status),
+            )
+            .await?;
+
+            let status = response.status();
+            if status.is_success() {
+                return Ok(response);
+            }
+
+            let retry_after = parse_retry_after(response.headers());
+            let error = response.text().await?;
+            if status == reqwest::StatusCode::SERVICE_UNAVAILABLE && waits < HF_MAX_LOADING_WAITS {
+                waits += 1;
+                let delay = hf_loading_delay(&error)
+                    .or(retry_after)
+                    .unwrap_or_else(|| backoff_delay(config.base_backoff_ms, waits));
+                tracing::info!(
+                    endpoint = %endpoint,
+                    attempt = waits,
+                    delay_ms = delay.as_millis() as u64,
+                    "HuggingFace model is loading, waiting"
+                );
+                tokio::time::sleep(delay).await;
+                continue;
+            }
             return Err(anyhow!("HuggingFace API error ({}): {}", status, error));
         }
-        
+    }
+
+    /// HuggingFace generation
+    async fn huggingface_generate(
+        &self,
+        prompt: &str,
+        config: &GenerationConfig,
+        _model_id: &str,
+    ) -> Result<String> {
+        let request = Self::huggingface_request(prompt, config, false);
+        let response = self.huggingface_send(&request, config).await?;
+
         let endpoint = self.get_endpoint();
         let result: Vec<HuggingFaceResponse> = Self::parse_json_response(response, &endpoint).await?;
 
@@ -537,6 +1294,61 @@ impl SimpleExternalProvider {
         Ok(result[0].generated_text.clone())
     }
 
+    /// HuggingFace streaming via the `text-generation` SSE response
+    async fn huggingface_stream(
+        &self,
+        prompt: &str,
+        config: &GenerationConfig,
+    ) -> Result<TokenStream> {
+        let request = Self::huggingface_request(prompt, config, true);
+        let response = self.huggingface_send(&request, config).await?;
+
+        let (sender, receiver) = tokio::sync::mpsc::channel::<String>(256);
+        let error_slot = StreamErrorSlot::default();
+        let task_error = error_slot.clone();
+        let usage_slot = StreamUsageSlot::default();
+        let task_usage = usage_slot.clone();
+        let mut byte_stream = response.bytes_stream();
+        let cancel = config.cancel.clone();
+
+        tokio::spawn(async move {
+            let mut lines = LineBuffer::default();
+
+            while let Some(chunk_result) = cancel.next_unless_cancelled(&mut byte_stream).await {
+                let chunk = match chunk_result {
+                    Ok(c) => c,
+                    Err(e) => {
+                        *task_error.lock() = Some(format!("HuggingFace stream interrupted: {}", e));
+                        return;
+                    }
+                };
+
+                for line in lines.push(&chunk) {
+                    match parse_hf_stream_line(line.trim()) {
+                        Ok(Some(event)) => {
+                            if !event.text.is_empty() && sender.send(event.text).await.is_err() {
+                                return;
+                            }
+                            if event.generated_tokens.is_some() {
+                                *task_usage.lock() = Some(TokenUsage {
+                                    output_tokens: event.generated_tokens,
+                                    ..Default::default()
+                                });
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            *task_error.lock() = Some(e.to_string());
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(TokenStream::with_error_slot(receiver, error_slot).with_usage_slot(usage_slot))
+    }
+
     // ==================== Tool-calling: OpenAI-compatible ====================
 
     fn format_openai_messages(messages: &[ChatMessage]) -> Vec<serde_json::Value> {
@@ -590,7 +1402,7 @@ impl SimpleExternalProvider {
         config: &GenerationConfig,
     ) -> Result<ChatResponse> {
         let mut request = json!({
-            "model": self.model,
+            "model": self.model_for(config),
             "messages": Self::format_openai_messages(messages),
             "max_tokens": config.max_tokens,
             "temperature": config.temperature,
@@ -603,23 +1415,26 @@ impl SimpleExternalProvider {
             request["tools"] = json!(Self::format_openai_tools(tools));
             request["tool_choice"] = json!("auto");
         }
+        self.apply_logit_bias(&mut request, config);
 
         let endpoint = self.get_endpoint();
-        let response = self.client
-            .post(&endpoint)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| {
-                if e.is_timeout() {
-                    anyhow!("Chat request to {} timed out — check network connectivity", endpoint)
-                } else if e.is_connect() {
-                    anyhow!("Failed to connect to {} — check network/firewall/proxy: {}", endpoint, e)
-                } else {
-                    anyhow!("Chat request to {} failed: {}", endpoint, e)
-                }
-            })?;
+        let response = Self::send_with_retry(
+            || self.client
+                .post(&endpoint)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .json(&request),
+            config,
+        )
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                anyhow!("Chat request to {} timed out — check network connectivity", endpoint)
+            } else if e.is_connect() {
+                anyhow!("Failed to connect to {} — check network/firewall/proxy: {}", endpoint, e)
+            } else {
+                anyhow!("Chat request to {} failed: {}", endpoint, e)
+            }
+        })?;
 
         let status = response.status();
         if !status.is_success() {
@@ -628,6 +1443,7 @@ impl SimpleExternalProvider {
         }
 
         let body: serde_json::Value = Self::parse_json_response(response, &endpoint).await?;
+        *self.last_web_sources.lock() = parse_web_sources(&body);
         let choice = &body["choices"][0]["message"];
 
         // Check for tool calls
@@ -659,10 +1475,8 @@ impl SimpleExternalProvider {
         tools: &[ToolSchema],
         config: &GenerationConfig,
     ) -> Result<tokio::sync::mpsc::Receiver<ChatStreamEvent>> {
-        use futures::StreamExt;
-
         let mut request = json!({
-            "model": self.model,
+            "model": self.model_for(config),
             "messages": Self::format_openai_messages(messages),
             "max_tokens": config.max_tokens,
             "temperature": config.temperature,
@@ -675,23 +1489,26 @@ impl SimpleExternalProvider {
             request["tools"] = json!(Self::format_openai_tools(tools));
             request["tool_choice"] = json!("auto");
         }
+        self.apply_logit_bias(&mut request, config);
 
         let endpoint = self.get_endpoint();
-        let response = self.client
-            .post(&endpoint)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| {
-                if e.is_timeout() {
-                    anyhow!("Chat stream to {} timed out — check network connectivity", endpoint)
-                } else if e.is_connect() {
-                    anyhow!("Failed to connect to {} for chat stream: {}", endpoint, e)
-                } else {
-                    anyhow!("Chat stream request to {} failed: {}", endpoint, e)
-                }
-            })?;
+        let response = Self::send_with_retry(
+            || self.client
+                .post(&endpoint)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .json(&request),
+            config,
+        )
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                anyhow!("Chat stream to {} timed out — check network connectivity", endpoint)
+            } else if e.is_connect() {
+                anyhow!("Failed to connect to {} for chat stream: {}", endpoint, e)
+            } else {
+                anyhow!("Chat stream request to {} failed: {}", endpoint, e)
+            }
+        })?;
 
         let status = response.status();
         let content_type = response.headers().get("content-type")
@@ -705,15 +1522,15 @@ impl SimpleExternalProvider {
         }
 
         let (tx, rx) = tokio::sync::mpsc::channel::<ChatStreamEvent>(256);
+        let task_sources = self.last_web_sources.clone();
         let mut byte_stream = response.bytes_stream();
+        let cancel = config.cancel.clone();
 
         tokio::spawn(async move {
             let mut buffer = String::new();
-            // Accumulate streamed tool calls: index -> (id, name, arguments_buffer)
-            let mut tool_call_acc: std::collections::HashMap<u64, (String, String, String)> =
-                std::collections::HashMap::new();
+            let mut assembler = ToolCallAssembler::default();
 
-            while let Some(chunk_result) = byte_stream.next().await {
+            while let Some(chunk_result) = cancel.next_unless_cancelled(&mut byte_stream).await {
                 let chunk = match chunk_result {
                     Ok(c) => c,
                     Err(_) => break,
@@ -729,20 +1546,20 @@ impl SimpleExternalProvider {
                     }
                     let data = &line[6..];
                     if data == "[DONE]" {
-                        // Flush any accumulated tool calls
-                        let mut calls: Vec<(u64, ToolCall)> = tool_call_acc.drain()
-                            .map(|(idx, (id, name, args))| (idx, ToolCall { id, name, arguments: args }))
-                            .collect();
-                        calls.sort_by_key(|(idx, _)| *idx);
-                        for (_, tc) in calls {
-                            let _ = tx.send(ChatStreamEvent::ToolCallComplete(tc)).await;
+                        for event in assembler.finish() {
+                            let _ = tx.send(event).await;
                         }
                         let _ = tx.send(ChatStreamEvent::Done).await;
                         return;
                     }
 
                     if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(data) {
-                        let delta = &parsed["choices"][0]["delta"];
+                        let sources = parse_web_sources(&parsed);
+                        if !sources.is_empty() {
+                            *task_sources.lock() = sources;
+                        }
+                        let choice = &parsed["choices"][0];
+                        let delta = &choice["delta"];
 
                         // Content delta
                         if let Some(content) = delta["content"].as_str() {
@@ -756,37 +1573,27 @@ impl SimpleExternalProvider {
                         // Tool call deltas
                         if let Some(tcs) = delta["tool_calls"].as_array() {
                             for tc_delta in tcs {
-                                let idx = tc_delta["index"].as_u64().unwrap_or(0);
-                                let entry = tool_call_acc.entry(idx).or_insert_with(|| {
-                                    let id = tc_delta["id"].as_str().unwrap_or("").to_string();
-                                    let name = tc_delta["function"]["name"].as_str().unwrap_or("").to_string();
-                                    (id, name, String::new())
-                                });
-                                // Accumulate id/name if provided in later deltas
-                                if let Some(id) = tc_delta["id"].as_str() {
-                                    if !id.is_empty() { entry.0 = id.to_string(); }
-                                }
-                                if let Some(name) = tc_delta["function"]["name"].as_str() {
-                                    if !name.is_empty() { entry.1 = name.to_string(); }
-                                }
-                                if let Some(args) = tc_delta["function"]["arguments"].as_str() {
-                                    entry.2.push_str(args);
+                                for event in assembler.push_delta(tc_delta) {
+                                    if tx.send(event).await.is_err() {
+                                        return;
+                                    }
                                 }
                             }
                         }
+
+                        // finish_reason marks every pending call as fully received
+                        if !choice["finish_reason"].is_null() {
+                            for event in assembler.finish() {
+                                let _ = tx.send(event).await;
+                            }
+                        }
                     }
                 }
             }
 
             // Stream ended without [DONE] — flush accumulated tool calls
-            if !tool_call_acc.is_empty() {
-                let mut calls: Vec<(u64, ToolCall)> = tool_call_acc.drain()
-                    .map(|(idx, (id, name, args))| (idx, ToolCall { id, name, arguments: args }))
-                    .collect();
-                calls.sort_by_key(|(idx, _)| *idx);
-                for (_, tc) in calls {
-                    let _ = tx.send(ChatStreamEvent::ToolCallComplete(tc)).await;
-                }
+            for event in assembler.finish() {
+                let _ = tx.send(event).await;
             }
             let _ = tx.send(ChatStreamEvent::Done).await;
         });
@@ -796,14 +1603,19 @@ impl SimpleExternalProvider {
 
     // ==================== Tool-calling: Anthropic ====================
 
-    fn format_anthropic_messages(messages: &[ChatMessage]) -> (Option<String>, Vec<serde_json::Value>) {
-        let mut system_prompt = None;
+    /// Split messages into Anthropic's top-level system blocks and the message list.
+    /// Each system message becomes its own block, so callers can pass the fixed
+    /// instructions and the document context separately and cache both.
+    fn format_anthropic_messages(messages: &[ChatMessage]) -> (Vec<String>, Vec<serde_json::Value>) {
+        let mut system_blocks = Vec::new();
         let mut api_messages = Vec::new();
 
         for m in messages {
             match m.role {
                 ChatRole::System => {
-                    system_prompt = m.content.clone();
+                    if let Some(ref content) = m.content {
+                        system_blocks.push(content.clone());
+                    }
                 }
                 ChatRole::User => {
                     if let Some(ref content) = m.content {
@@ -852,7 +1664,26 @@ impl SimpleExternalProvider {
                 }
             }
         }
-        (system_prompt, api_messages)
+        (system_blocks, api_messages)
+    }
+
+    /// Build the `system` request field. With caching enabled every block carries
+    /// an ephemeral `cache_control` breakpoint so the prefix is billed at cache rates.
+    fn format_anthropic_system(blocks: &[String], cache: bool) -> Option<serde_json::Value> {
+        if blocks.is_empty() {
+            return None;
+        }
+        if !cache {
+            return Some(json!(blocks.join("\n\n")));
+        }
+        let breakpoint_start = blocks.len().saturating_sub(ANTHROPIC_MAX_CACHE_BREAKPOINTS);
+        Some(json!(blocks.iter().enumerate().map(|(i, text)| {
+            let mut block = json!({ "type": "text", "text": text });
+            if i >= breakpoint_start {
+                block["cache_control"] = json!({ "type": "ephemeral" });
+            }
+            block
+        }).collect::<Vec<_>>()))
     }
 
     fn format_anthropic_tools(tools: &[ToolSchema]) -> Vec<serde_json::Value> {
@@ -869,30 +1700,32 @@ impl SimpleExternalProvider {
         tools: &[ToolSchema],
         config: &GenerationConfig,
     ) -> Result<ChatResponse> {
-        let (system_prompt, api_messages) = Self::format_anthropic_messages(messages);
+        let (system_blocks, api_messages) = Self::format_anthropic_messages(messages);
 
         let mut request = json!({
-            "model": self.model,
+            "model": self.model_for(config),
             "messages": api_messages,
             "max_tokens": config.max_tokens,
             "temperature": config.temperature,
             "top_p": config.top_p
         });
 
-        if let Some(ref sys) = system_prompt {
-            request["system"] = json!(sys);
+        if let Some(sys) = Self::format_anthropic_system(&system_blocks, config.cache_system_prompt) {
+            request["system"] = sys;
         }
         if !tools.is_empty() {
             request["tools"] = json!(Self::format_anthropic_tools(tools));
         }
 
-        let response = self.client
-            .post(self.get_endpoint())
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .json(&request)
-            .send()
-            .await?;
+        let response = Self::send_with_retry(
+            || self.client
+                .post(self.get_endpoint())
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", "2023-06-01")
+                .json(&request),
+            config,
+        )
+        .await?;
 
         let status = response.status();
         if !status.is_success() {
@@ -902,6 +1735,9 @@ impl SimpleExternalProvider {
 
         let endpoint = self.get_endpoint();
         let body: serde_json::Value = Self::parse_json_response(response, &endpoint).await?;
+        if body["usage"].is_object() {
+            *self.last_usage.lock() = Some(anthropic_usage(&body["usage"]));
+        }
 
         // Parse Anthropic response content blocks
         let mut text_parts = Vec::new();
@@ -944,12 +1780,10 @@ impl SimpleExternalProvider {
         tools: &[ToolSchema],
         config: &GenerationConfig,
     ) -> Result<tokio::sync::mpsc::Receiver<ChatStreamEvent>> {
-        use futures::StreamExt;
-
-        let (system_prompt, api_messages) = Self::format_anthropic_messages(messages);
+        let (system_blocks, api_messages) = Self::format_anthropic_messages(messages);
 
         let mut request = json!({
-            "model": self.model,
+            "model": self.model_for(config),
             "messages": api_messages,
             "max_tokens": config.max_tokens,
             "temperature": config.temperature,
@@ -957,20 +1791,22 @@ impl SimpleExternalProvider {
             "stream": true
         });
 
-        if let Some(ref sys) = system_prompt {
-            request["system"] = json!(sys);
+        if let Some(sys) = Self::format_anthropic_system(&system_blocks, config.cache_system_prompt) {
+            request["system"] = sys;
         }
         if !tools.is_empty() {
             request["tools"] = json!(Self::format_anthropic_tools(tools));
         }
 
-        let response = self.client
-            .post(self.get_endpoint())
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .json(&request)
-            .send()
-            .await?;
+        let response = Self::send_with_retry(
+            || self.client
+                .post(self.get_endpoint())
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", "2023-06-01")
+                .json(&request),
+            config,
+        )
+        .await?;
 
         let status = response.status();
         let content_type = response.headers().get("content-type")
@@ -985,6 +1821,8 @@ impl SimpleExternalProvider {
 
         let (tx, rx) = tokio::sync::mpsc::channel::<ChatStreamEvent>(256);
         let mut byte_stream = response.bytes_stream();
+        let last_usage = self.last_usage.clone();
+        let cancel = config.cancel.clone();
 
         tokio::spawn(async move {
             let mut buffer = String::new();
@@ -994,7 +1832,7 @@ impl SimpleExternalProvider {
             let mut current_tool_args = String::new();
             let mut in_tool_use = false;
 
-            while let Some(chunk_result) = byte_stream.next().await {
+            while let Some(chunk_result) = cancel.next_unless_cancelled(&mut byte_stream).await {
                 let chunk = match chunk_result {
                     Ok(c) => c,
                     Err(_) => break,
@@ -1012,6 +1850,19 @@ impl SimpleExternalProvider {
 
                     if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(data) {
                         match parsed["type"].as_str() {
+                            Some("message_start") => {
+                                let usage = &parsed["message"]["usage"];
+                                if usage.is_object() {
+                                    *last_usage.lock() = Some(anthropic_usage(usage));
+                                }
+                            }
+                            Some("message_delta") => {
+                                // Final output count arrives here; input/cache counts came with message_start
+                                if let Some(output) = parsed["usage"]["output_tokens"].as_u64() {
+                                    let mut guard = last_usage.lock();
+                                    guard.get_or_insert_with(TokenUsage::default).output_tokens = Some(output as usize);
+                                }
+                            }
                             Some("content_block_start") => {
                                 let block = &parsed["content_block"];
                                 if block["type"].as_str() == Some("tool_use") {
@@ -1019,6 +1870,10 @@ impl SimpleExternalProvider {
                                     current_tool_id = block["id"].as_str().unwrap_or("").to_string();
                                     current_tool_name = block["name"].as_str().unwrap_or("").to_string();
                                     current_tool_args.clear();
+                                    let _ = tx.send(ChatStreamEvent::ToolCallStarted {
+                                        id: current_tool_id.clone(),
+                                        name: current_tool_name.clone(),
+                                    }).await;
                                 }
                             }
                             Some("content_block_delta") => {
@@ -1152,13 +2007,15 @@ impl SimpleExternalProvider {
             request["tools"] = json!([{ "functionDeclarations": functions }]);
         }
 
-        let response = self.client
-            .post(self.get_endpoint())
-            .header("Content-Type", "application/json")
-            .header("x-goog-api-key", &self.api_key)
-            .json(&request)
-            .send()
-            .await?;
+        let response = Self::send_with_retry(
+            || self.client
+                .post(self.get_endpoint())
+                .header("Content-Type", "application/json")
+                .header("x-goog-api-key", &self.api_key)
+                .json(&request),
+            config,
+        )
+        .await?;
 
         let status = response.status();
         if !status.is_success() {
@@ -1199,10 +2056,332 @@ impl SimpleExternalProvider {
     }
 }
 
+/// A streamed OpenAI tool call still receiving argument fragments
+#[derive(Default)]
+struct PendingToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+    announced: bool,
+}
+
+/// Assembles OpenAI-style streamed tool calls. Fragments are keyed by the
+/// `index` field; a delta for a higher index means every lower-indexed call is
+/// complete, since OpenAI streams parallel calls one after another.
+#[derive(Default)]
+struct ToolCallAssembler {
+    pending: std::collections::BTreeMap<u64, PendingToolCall>,
+}
+
+impl ToolCallAssembler {
+    /// Apply one `tool_calls[]` delta, returning events that became ready.
+    fn push_delta(&mut self, tc_delta: &serde_json::Value) -> Vec<ChatStreamEvent> {
+        let idx = tc_delta["index"].as_u64().unwrap_or(0);
+        let mut events = Vec::new();
+
+        let finished: Vec<u64> = self.pending.range(..idx).map(|(k, _)| *k).collect();
+        for k in finished {
+            if let Some(call) = self.pending.remove(&k) {
+                events.push(ChatStreamEvent::ToolCallComplete(ToolCall {
+                    id: call.id,
+                    name: call.name,
+                    arguments: call.arguments,
+                }));
+            }
+        }
+
+        let entry = self.pending.entry(idx).or_default();
+        if let Some(id) = tc_delta["id"].as_str() {
+            if !id.is_empty() { entry.id = id.to_string(); }
+        }
+        if let Some(name) = tc_delta["function"]["name"].as_str() {
+            if !name.is_empty() { entry.name = name.to_string(); }
+        }
+        if let Some(args) = tc_delta["function"]["arguments"].as_str() {
+            entry.arguments.push_str(args);
+        }
+        if !entry.announced && !entry.name.is_empty() {
+            entry.announced = true;
+            events.push(ChatStreamEvent::ToolCallStarted {
+                id: entry.id.clone(),
+                name: entry.name.clone(),
+            });
+        }
+        events
+    }
+
+    /// Complete every pending call in index order.
+    fn finish(&mut self) -> Vec<ChatStreamEvent> {
+        std::mem::take(&mut self.pending)
+            .into_values()
+            .map(|call| ChatStreamEvent::ToolCallComplete(ToolCall {
+                id: call.id,
+                name: call.name,
+                arguments: call.arguments,
+            }))
+            .collect()
+    }
+}
+
+/// Upper bound on any single retry delay, including server-provided `Retry-After`
+const MAX_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Rate limiting and gateway/overload errors are worth retrying; other 4xx are not.
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || matches!(status.as_u16(), 500 | 502 | 503 | 504 | 529)
+}
+
+/// Parse a `Retry-After` header given in seconds.
+fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<std::time::Duration> {
+    headers.get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|secs| *secs >= 0.0)
+        .map(|secs| std::time::Duration::from_millis((secs * 1000.0) as u64).min(MAX_RETRY_DELAY))
+}
+
+/// Exponential backoff (`base * 2^(attempt-1)`) plus up to 25% jitter.
+fn backoff_delay(base_ms: u64, attempt: usize) -> std::time::Duration {
+    let exp = base_ms.saturating_mul(1u64 << (attempt.saturating_sub(1)).min(16));
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    let jitter = if exp >= 4 { nanos % (exp / 4) } else { 0 };
+    std::time::Duration::from_millis(exp + jitter).min(MAX_RETRY_DELAY)
+}
+
+/// One decoded line of an Ollama NDJSON stream
+#[derive(Debug)]
+struct OllamaChunk {
+    text: String,
+    done: bool,
+    /// Present on the final `done` line (`prompt_eval_count` / `eval_count`)
+    usage: Option<TokenUsage>,
+}
+
+/// Parse a single NDJSON line from Ollama's `/api/generate` (`response` field)
+/// or `/api/chat` (`message.content` field). Returns `Ok(None)` for blank or
+/// unparseable lines and `Err` when Ollama reports an error object.
+fn parse_ollama_line(line: &str) -> std::result::Result<Option<OllamaChunk>, String> {
+    if line.is_empty() {
+        return Ok(None);
+    }
+    let parsed: serde_json::Value = match serde_json::from_str(line) {
+        Ok(v) => v,
+        Err(_) => return Ok(None),
+    };
+    if let Some(err) = parsed["error"].as_str() {
+        return Err(err.to_string());
+    }
+    let text = parsed["response"].as_str()
+        .or_else(|| parsed["message"]["content"].as_str())
+        .unwrap_or("")
+        .to_string();
+    let done = parsed["done"].as_bool().unwrap_or(false);
+    let usage = if done {
+        Some(TokenUsage {
+            input_tokens: parsed["prompt_eval_count"].as_u64().map(|n| n as usize),
+            output_tokens: parsed["eval_count"].as_u64().map(|n| n as usize),
+            ..Default::default()
+        })
+    } else {
+        None
+    };
+    Ok(Some(OllamaChunk { text, done, usage }))
+}
+
+/// Flatten a conversation into one prompt for APIs without a chat schema
+fn flatten_messages(messages: &[ChatMessage]) -> String {
+    messages.iter()
+        .filter_map(|m| m.content.as_ref().map(|c| format!("{:?}: {}", m.role, c)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Prediction-creation URL for a Replicate model, plus the version to pin.
+/// Official models run by name (`owner/name`); community models need a
+/// version, given as `owner/name:version` or a bare version id.
+fn replicate_target(base: &str, model: &str) -> (String, Option<String>) {
+    match model.split_once(':') {
+        Some((_, version)) => (format!("{}/v1/predictions", base), Some(version.to_string())),
+        None if model.contains('/') => (format!("{}/v1/models/{}/predictions", base, model), None),
+        None => (format!("{}/v1/predictions", base), Some(model.to_string())),
+    }
+}
+
+/// Ask Replicate to stop a prediction. Best effort: a prediction that has
+/// already finished just ignores it.
+async fn cancel_replicate_prediction(client: &reqwest::Client, api_key: &str, prediction: &serde_json::Value) {
+    if let Some(url) = prediction["urls"]["cancel"].as_str() {
+        let _ = client
+            .post(url)
+            .header("Authorization", format!("Bearer {}", api_key))
+            .send()
+            .await;
+    }
+}
+
+/// Output of a finished prediction, `None` while it is still starting or
+/// processing, or an error if it failed or was canceled
+fn replicate_outcome(prediction: &serde_json::Value) -> Result<Option<String>> {
+    match prediction["status"].as_str().unwrap_or_default() {
+        "succeeded" => match &prediction["output"] {
+            // Language models return their output as a list of tokens
+            serde_json::Value::Array(parts) => {
+                Ok(Some(parts.iter().filter_map(|p| p.as_str()).collect()))
+            }
+            serde_json::Value::String(text) => Ok(Some(text.clone())),
+            serde_json::Value::Null => Err(anyhow!("Replicate prediction succeeded but returned no output")),
+            other => Ok(Some(other.to_string())),
+        },
+        "failed" => Err(anyhow!("Replicate prediction failed: {}", prediction["error"])),
+        "canceled" => Err(anyhow!("Replicate prediction was canceled")),
+        _ => Ok(None),
+    }
+}
+
+/// Token counts from a prediction's `metrics`, once Replicate reports them
+fn replicate_usage(prediction: &serde_json::Value) -> Option<TokenUsage> {
+    let metrics = &prediction["metrics"];
+    let output_tokens = metrics["output_token_count"].as_u64()?;
+    Some(TokenUsage {
+        input_tokens: metrics["input_token_count"].as_u64().map(|n| n as usize),
+        output_tokens: Some(output_tokens as usize),
+        cache_read_tokens: None,
+        cache_write_tokens: None,
+    })
+}
+
+/// Text from a Baseten deployment response: an OpenAI-style completion, or
+/// the bare string / `output` / `generated_text` that custom models return
+fn baseten_output_text(body: &serde_json::Value) -> Option<String> {
+    let choice = &body["choices"][0];
+    if let Some(text) = choice["message"]["content"].as_str().or_else(|| choice["text"].as_str()) {
+        return Some(text.to_string());
+    }
+    if let Some(text) = body.as_str() {
+        return Some(text.to_string());
+    }
+    ["output", "generated_text", "text", "completion"]
+        .iter()
+        .find_map(|key| match &body[*key] {
+            serde_json::Value::String(text) => Some(text.clone()),
+            serde_json::Value::Array(parts) if parts.iter().all(|p| p.is_string()) => {
+                Some(parts.iter().filter_map(|p| p.as_str()).collect())
+            }
+            _ => None,
+        })
+}
+
+/// Splits a byte stream into lines. Bytes are only decoded once their line
+/// is complete, so a multi-byte character split across chunks survives.
+#[derive(Default)]
+struct LineBuffer {
+    buffer: Vec<u8>,
+}
+
+impl LineBuffer {
+    /// Feed a chunk and return the lines it completed, without `\r\n` / `\n`
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+        let mut lines = Vec::new();
+        while let Some(line_end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let raw: Vec<u8> = self.buffer.drain(..=line_end).collect();
+            lines.push(String::from_utf8_lossy(&raw[..line_end]).trim_end_matches('\r').to_string());
+        }
+        lines
+    }
+
+    /// Whatever followed the last newline, for streams that end without one
+    fn finish(&mut self) -> String {
+        String::from_utf8_lossy(&std::mem::take(&mut self.buffer)).into_owned()
+    }
+}
+
+/// Incremental parser for `event:` / `data:` server-sent events
+#[derive(Default)]
+struct SseEvents {
+    lines: LineBuffer,
+    event: String,
+    data: Vec<String>,
+}
+
+impl SseEvents {
+    /// Feed a chunk and return the (event, data) pairs it completed
+    fn push(&mut self, chunk: &[u8]) -> Vec<(String, String)> {
+        let mut events = Vec::new();
+        for line in self.lines.push(chunk) {
+            if line.is_empty() {
+                if !self.event.is_empty() || !self.data.is_empty() {
+                    let event = std::mem::take(&mut self.event);
+                    let event = if event.is_empty() { "message".to_string() } else { event };
+                    events.push((event, std::mem::take(&mut self.data).join("\n")));
+                }
+            } else if let Some(event) = line.strip_prefix("event:") {
+                self.event = event.trim().to_string();
+            } else if let Some(data) = line.strip_prefix("data:") {
+                // Only the single space after the colon is framing; tokens keep theirs
+                self.data.push(data.strip_prefix(' ').unwrap_or(data).to_string());
+            }
+        }
+        events
+    }
+}
+
 /// Response structures
 #[derive(Deserialize)]
 struct OpenAIResponse {
     choices: Vec<OpenAIChoice>,
+    #[serde(default)]
+    usage: Option<serde_json::Value>,
+}
+
+/// Convert an OpenAI-style `usage` object
+fn openai_usage(usage: &serde_json::Value) -> TokenUsage {
+    TokenUsage {
+        input_tokens: usage["prompt_tokens"].as_u64().map(|n| n as usize),
+        output_tokens: usage["completion_tokens"].as_u64().map(|n| n as usize),
+        cache_read_tokens: usage["prompt_tokens_details"]["cached_tokens"].as_u64().map(|n| n as usize),
+        cache_write_tokens: None,
+    }
+}
+
+/// Whether a Perplexity model searches the web. Only the Sonar family (and
+/// the older `-online` ids) does; the rest are offline chat models.
+fn perplexity_searches(model: &str) -> bool {
+    let model = model.to_ascii_lowercase();
+    model.contains("sonar") || model.ends_with("-online")
+}
+
+/// Web sources from a Perplexity response or stream chunk. Newer responses
+/// carry `search_results` with titles; older ones only a `citations` URL list.
+fn parse_web_sources(body: &serde_json::Value) -> Vec<WebSource> {
+    if let Some(results) = body["search_results"].as_array() {
+        let sources: Vec<WebSource> = results
+            .iter()
+            .filter_map(|r| {
+                Some(WebSource {
+                    url: r["url"].as_str()?.to_string(),
+                    title: r["title"].as_str().map(str::to_string),
+                    date: r["date"].as_str().map(str::to_string),
+                })
+            })
+            .collect();
+        if !sources.is_empty() {
+            return sources;
+        }
+    }
+    body["citations"]
+        .as_array()
+        .map(|urls| {
+            urls.iter()
+                .filter_map(|u| u.as_str())
+                .map(|url| WebSource { url: url.to_string(), title: None, date: None })
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 #[derive(Deserialize)]
@@ -1218,6 +2397,19 @@ struct OpenAIMessage {
 #[derive(Deserialize)]
 struct AnthropicResponse {
     content: Vec<AnthropicContent>,
+    #[serde(default)]
+    usage: Option<serde_json::Value>,
+}
+
+/// Convert an Anthropic `usage` object, including prompt-cache counters
+fn anthropic_usage(usage: &serde_json::Value) -> TokenUsage {
+    let count = |key: &str| usage[key].as_u64().map(|n| n as usize);
+    TokenUsage {
+        input_tokens: count("input_tokens"),
+        output_tokens: count("output_tokens"),
+        cache_read_tokens: count("cache_read_input_tokens"),
+        cache_write_tokens: count("cache_creation_input_tokens"),
+    }
 }
 
 #[derive(Deserialize)]
@@ -1248,4 +2440,450 @@ struct GooglePart {
 #[derive(Deserialize)]
 struct HuggingFaceResponse {
     generated_text: String,
-}
\ No newline at end of file
+}
+
+/// Delay before retrying a 503 whose body is HF's `{"error": "... is currently
+/// loading", "estimated_time": 20.0}`; None for any other 503.
+fn hf_loading_delay(body: &str) -> Option<std::time::Duration> {
+    let parsed: serde_json::Value = serde_json::from_str(body).ok()?;
+    let seconds = parsed["estimated_time"].as_f64()?;
+    if !seconds.is_finite() || seconds < 0.0 {
+        return None;
+    }
+    Some(std::time::Duration::from_secs_f64(seconds.max(1.0)).min(HF_MAX_LOADING_WAIT))
+}
+
+/// One decoded `text-generation` stream event
+#[derive(Debug)]
+struct HfStreamEvent {
+    text: String,
+    /// Present on the final event when the server reports `details`
+    generated_tokens: Option<usize>,
+}
+
+/// Parse one SSE line of a HuggingFace `text-generation` stream. Special
+/// tokens (e.g. `</s>`) are dropped; an `error` event becomes `Err`.
+fn parse_hf_stream_line(line: &str) -> Result<Option<HfStreamEvent>> {
+    let Some(data) = line.strip_prefix("data:") else {
+        return Ok(None);
+    };
+    let data = data.trim();
+    if data.is_empty() || data == "[DONE]" {
+        return Ok(None);
+    }
+    let parsed: serde_json::Value = serde_json::from_str(data)
+        .map_err(|e| anyhow!("Invalid HuggingFace stream event: {}", e))?;
+    if let Some(error) = parsed["error"].as_str() {
+        return Err(anyhow!("HuggingFace stream error: {}", error));
+    }
+    let token = &parsed["token"];
+    let text = if token["special"].as_bool().unwrap_or(false) {
+        String::new()
+    } else {
+        token["text"].as_str().unwrap_or("").to_string()
+    };
+    let generated_tokens = parsed["details"]["generated_tokens"].as_u64().map(|n| n as usize);
+    Ok(Some(HfStreamEvent { text, generated_tokens }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ollama_generate_and_chat_lines() {
+        let gen = parse_ollama_line(r#"{"model":"llama3","response":"Hel","done":false}"#).unwrap().unwrap();
+        assert_eq!(gen.text, "Hel");
+        assert!(!gen.done);
+
+        let chat = parse_ollama_line(r#"{"message":{"role":"assistant","content":"lo"},"done":false}"#).unwrap().unwrap();
+        assert_eq!(chat.text, "lo");
+
+        let done = parse_ollama_line(r#"{"response":"","done":true,"prompt_eval_count":30,"eval_count":12}"#).unwrap().unwrap();
+        assert!(done.done);
+        let usage = done.usage.unwrap();
+        assert_eq!(usage.input_tokens, Some(30));
+        assert_eq!(usage.output_tokens, Some(12));
+
+        assert!(parse_ollama_line("").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_ollama_error_line() {
+        let err = parse_ollama_line(r#"{"error":"model 'foo' not found"}"#).unwrap_err();
+        assert!(err.contains("not found"));
+    }
+
+    #[test]
+    fn test_ollama_lines_survive_split_characters() {
+        let bytes = "{\"response\":\"caf\u{e9}\",\"done\":false}\n{\"response\":\"\",\"done\":true}".as_bytes();
+        // Split between the two bytes of the 'é'
+        let split = bytes.iter().position(|&b| b == 0xc3).unwrap() + 1;
+        let mut lines = LineBuffer::default();
+        assert!(lines.push(&bytes[..split]).is_empty());
+
+        let complete = lines.push(&bytes[split..]);
+        assert_eq!(complete.len(), 1);
+        assert_eq!(parse_ollama_line(&complete[0]).unwrap().unwrap().text, "caf\u{e9}");
+        assert!(parse_ollama_line(&lines.finish()).unwrap().unwrap().done);
+    }
+
+    #[test]
+    fn test_ollama_options_maps_stop_and_seed() {
+        let mut config = GenerationConfig::from(&super::super::LLMConfig::default());
+        config.max_tokens = 256;
+        config.stop_sequences = vec!["</s>".to_string()];
+        config.seed = Some(42);
+        let options = SimpleExternalProvider::ollama_options(&config);
+        assert_eq!(options["num_predict"], 256);
+        assert_eq!(options["stop"][0], "</s>");
+        assert_eq!(options["seed"], 42);
+    }
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(is_retryable_status(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(reqwest::StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable_status(reqwest::StatusCode::UNAUTHORIZED));
+        assert!(!is_retryable_status(reqwest::StatusCode::BAD_REQUEST));
+    }
+
+    #[test]
+    fn test_backoff_grows_and_honors_retry_after() {
+        let first = backoff_delay(500, 1).as_millis();
+        let third = backoff_delay(500, 3).as_millis();
+        assert!((500..625).contains(&first));
+        assert!((2000..2500).contains(&third));
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, "2".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(std::time::Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_anthropic_system_cache_breakpoints() {
+        let blocks = vec!["instructions".to_string(), "document context".to_string()];
+
+        let plain = SimpleExternalProvider::format_anthropic_system(&blocks, false).unwrap();
+        assert_eq!(plain, json!("instructions\n\ndocument context"));
+
+        let cached = SimpleExternalProvider::format_anthropic_system(&blocks, true).unwrap();
+        assert_eq!(cached[0]["cache_control"]["type"], "ephemeral");
+        assert_eq!(cached[1]["text"], "document context");
+        assert_eq!(cached[1]["cache_control"]["type"], "ephemeral");
+    }
+
+    #[test]
+    fn test_anthropic_usage_reads_cache_counters() {
+        let usage = anthropic_usage(&json!({
+            "input_tokens": 12,
+            "output_tokens": 80,
+            "cache_read_input_tokens": 4000,
+            "cache_creation_input_tokens": 0
+        }));
+        assert_eq!(usage.cache_read_tokens, Some(4000));
+        assert_eq!(usage.cache_write_tokens, Some(0));
+    }
+
+    #[test]
+    fn test_tool_call_assembler_emits_parallel_calls_separately() {
+        let mut asm = ToolCallAssembler::default();
+
+        let events = asm.push_delta(&json!({"index": 0, "id": "call_a", "function": {"name": "create_task", "arguments": ""}}));
+        assert!(matches!(&events[..], [ChatStreamEvent::ToolCallStarted { name, .. }] if name == "create_task"));
+        assert!(asm.push_delta(&json!({"index": 0, "function": {"arguments": "{\"title\":"}})).is_empty());
+        assert!(asm.push_delta(&json!({"index": 0, "function": {"arguments": "\"x\"}"}})).is_empty());
+
+        // Second call starts: the first is now complete
+        let events = asm.push_delta(&json!({"index": 1, "id": "call_b", "function": {"name": "search", "arguments": "{}"}}));
+        assert_eq!(events.len(), 2);
+        match &events[0] {
+            ChatStreamEvent::ToolCallComplete(tc) => {
+                assert_eq!(tc.id, "call_a");
+                assert_eq!(tc.arguments, r#"{"title":"x"}"#);
+            }
+            other => panic!("expected completion, got {:?}", other),
+        }
+        assert!(matches!(&events[1], ChatStreamEvent::ToolCallStarted { name, .. } if name == "search"));
+
+        let rest = asm.finish();
+        assert!(matches!(&rest[..], [ChatStreamEvent::ToolCallComplete(tc)] if tc.id == "call_b"));
+        assert!(asm.finish().is_empty());
+    }
+
+    #[test]
+    fn test_openai_usage_from_final_chunk() {
+        let chunk: serde_json::Value = serde_json::from_str(
+            r#"{"choices":[],"usage":{"prompt_tokens":120,"completion_tokens":45,"prompt_tokens_details":{"cached_tokens":64}}}"#,
+        ).unwrap();
+        let usage = openai_usage(&chunk["usage"]);
+        assert_eq!(usage.input_tokens, Some(120));
+        assert_eq!(usage.output_tokens, Some(45));
+        assert_eq!(usage.cache_read_tokens, Some(64));
+        assert_eq!(usage.cache_write_tokens, None);
+    }
+
+    #[test]
+    fn test_perplexity_searches_only_with_online_models() {
+        assert!(perplexity_searches("sonar"));
+        assert!(perplexity_searches("sonar-reasoning-pro"));
+        assert!(perplexity_searches("llama-3.1-sonar-small-128k-online"));
+        assert!(!perplexity_searches("r1-1776"));
+        assert!(!perplexity_searches("llama-3.1-8b-instruct"));
+    }
+
+    #[test]
+    fn test_parse_perplexity_web_sources() {
+        let body: serde_json::Value = serde_json::from_str(
+            r#"{"citations":["https://a.example","https://b.example"],
+                "search_results":[{"title":"A","url":"https://a.example","date":"2025-01-02"},{"url":"https://b.example"}]}"#,
+        ).unwrap();
+        let sources = parse_web_sources(&body);
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].title.as_deref(), Some("A"));
+        assert_eq!(sources[0].date.as_deref(), Some("2025-01-02"));
+        assert_eq!(sources[1].title, None);
+
+        let legacy: serde_json::Value = serde_json::from_str(r#"{"citations":["https://c.example"]}"#).unwrap();
+        assert_eq!(parse_web_sources(&legacy)[0].url, "https://c.example");
+        assert!(parse_web_sources(&serde_json::json!({"choices": []})).is_empty());
+    }
+
+    /// One canned HTTP response of the mock server
+    struct MockResponse {
+        status: u16,
+        headers: &'static str,
+        body: String,
+    }
+
+    fn mock(status: u16, headers: &'static str, body: serde_json::Value) -> MockResponse {
+        MockResponse { status, headers, body: body.to_string() }
+    }
+
+    /// Serve `responses` in order, one per connection, on a local port.
+    /// `build` gets the base URL so bodies can link back to the server.
+    /// Returns the base URL and the raw requests received.
+    async fn mock_server(
+        build: impl FnOnce(&str) -> Vec<MockResponse>,
+    ) -> (String, std::sync::Arc<parking_lot::Mutex<Vec<String>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let responses = build(&base);
+        let requests = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let seen = requests.clone();
+
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut raw = Vec::new();
+                let mut buf = [0u8; 4096];
+                // Read the head, then as much body as Content-Length announces
+                loop {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    raw.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&raw).to_string();
+                    if let Some(head_end) = text.find("\r\n\r\n") {
+                        let length = text[..head_end]
+                            .lines()
+                            .find_map(|l| l.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                            .and_then(|v| v.parse::<usize>().ok())
+                            .unwrap_or(0);
+                        if raw.len() >= head_end + 4 + length || n == 0 {
+                            break;
+                        }
+                    }
+                    if n == 0 {
+                        break;
+                    }
+                }
+                seen.lock().push(String::from_utf8_lossy(&raw).to_string());
+
+                let reply = format!(
+                    "HTTP/1.1 {} MOCK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n{}",
+                    response.status,
+                    response.body.len(),
+                    response.headers,
+                    response.body
+                );
+                socket.write_all(reply.as_bytes()).await.unwrap();
+                socket.shutdown().await.ok();
+            }
+        });
+
+        (base, requests)
+    }
+
+    fn test_config() -> GenerationConfig {
+        let mut config = GenerationConfig::from(&crate::llm::LLMConfig::default());
+        config.max_retries = 0;
+        config
+    }
+
+    #[test]
+    fn test_replicate_target_outcome_and_sse() {
+        let base = "https://api.replicate.com";
+        assert_eq!(
+            replicate_target(base, "meta/meta-llama-3-8b-instruct"),
+            ("https://api.replicate.com/v1/models/meta/meta-llama-3-8b-instruct/predictions".to_string(), None)
+        );
+        assert_eq!(
+            replicate_target(base, "acme/llm:5c7d"),
+            ("https://api.replicate.com/v1/predictions".to_string(), Some("5c7d".to_string()))
+        );
+
+        let done = serde_json::json!({"status": "succeeded", "output": ["Hel", "lo"]});
+        assert_eq!(replicate_outcome(&done).unwrap().as_deref(), Some("Hello"));
+        assert!(replicate_outcome(&serde_json::json!({"status": "starting"})).unwrap().is_none());
+        assert!(replicate_outcome(&serde_json::json!({"status": "failed", "error": "OOM"})).is_err());
+
+        // Stream events may be split anywhere across chunks
+        let mut events = SseEvents::default();
+        assert!(events.push(b"event: output\ndata:  Hel").is_empty());
+        assert_eq!(events.push(b"lo\n\nevent: done\ndata: {}\n\n"), vec![
+            ("output".to_string(), " Hello".to_string()),
+            ("done".to_string(), "{}".to_string()),
+        ]);
+
+        // ...including inside a multi-byte character
+        let bytes = "event: output\ndata: caf\u{e9}\n\n".as_bytes();
+        let split = bytes.len() - 3;
+        assert!(events.push(&bytes[..split]).is_empty());
+        assert_eq!(events.push(&bytes[split..]), vec![("output".to_string(), "caf\u{e9}".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_replicate_generate_waits_out_cold_start() {
+        let (base, requests) = mock_server(|base| vec![
+            mock(201, "", serde_json::json!({
+                "id": "p1", "status": "starting",
+                "urls": {"get": format!("{}/v1/predictions/p1", base)}
+            })),
+            mock(200, "", serde_json::json!({
+                "id": "p1", "status": "succeeded", "output": ["Hi", " there"],
+                "metrics": {"input_token_count": 12, "output_token_count": 2}
+            })),
+        ]).await;
+
+        let provider = SimpleExternalProvider::new(
+            ApiProvider::Replicate, "r8_test".to_string(), "meta/meta-llama-3-8b-instruct".to_string(),
+        ).unwrap().with_api_base(&base);
+        let text = provider.generate("Say hi", &test_config()).await.unwrap();
+
+        assert_eq!(text, "Hi there");
+        assert_eq!(provider.last_usage().unwrap().output_tokens, Some(2));
+        let requests = requests.lock();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("POST /v1/models/meta/meta-llama-3-8b-instruct/predictions"));
+        assert!(requests[0].contains("\"prompt\":\"Say hi\""));
+        assert!(requests[1].starts_with("GET /v1/predictions/p1"));
+    }
+
+    #[tokio::test]
+    async fn test_replicate_generate_answers_in_one_round_trip() {
+        let (base, requests) = mock_server(|_| vec![
+            mock(201, "", serde_json::json!({"id": "p2", "status": "succeeded", "output": "Done"})),
+        ]).await;
+
+        let provider = SimpleExternalProvider::new(
+            ApiProvider::Replicate, "r8_test".to_string(), "acme/llm:abc123".to_string(),
+        ).unwrap().with_api_base(&base);
+
+        assert_eq!(provider.generate("Go", &test_config()).await.unwrap(), "Done");
+        let requests = requests.lock();
+        assert!(requests[0].starts_with("POST /v1/predictions"));
+        assert!(requests[0].contains("\"version\":\"abc123\""));
+    }
+
+    #[tokio::test]
+    async fn test_replicate_cancellation_cancels_prediction() {
+        let (base, requests) = mock_server(|base| vec![
+            mock(201, "", serde_json::json!({
+                "id": "p3", "status": "starting",
+                "urls": {"cancel": format!("{}/v1/predictions/p3/cancel", base)}
+            })),
+            mock(200, "", serde_json::json!({"id": "p3", "status": "canceled"})),
+        ]).await;
+
+        let provider = SimpleExternalProvider::new(
+            ApiProvider::Replicate, "r8_test".to_string(), "meta/meta-llama-3-8b-instruct".to_string(),
+        ).unwrap().with_api_base(&base);
+        let config = test_config();
+        config.cancel.cancel();
+
+        let err = provider.generate("Go", &config).await.unwrap_err();
+        assert!(err.to_string().contains("cancelled"));
+        let requests = requests.lock();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].starts_with("POST /v1/predictions/p3/cancel"));
+    }
+
+    #[tokio::test]
+    async fn test_baseten_deployment_retries_cold_start() {
+        let (base, requests) = mock_server(|_| vec![
+            mock(503, "Retry-After: 0\r\n", serde_json::json!({"error": "Model is waking up"})),
+            mock(200, "", serde_json::json!({
+                "choices": [{"message": {"role": "assistant", "content": "Warm now"}}],
+                "usage": {"prompt_tokens": 5, "completion_tokens": 2}
+            })),
+        ]).await;
+
+        let provider = SimpleExternalProvider::new(
+            ApiProvider::Baseten, "bt_key".to_string(), "abcd1234".to_string(),
+        ).unwrap().with_api_base(&base);
+
+        assert_eq!(provider.generate("Hello", &test_config()).await.unwrap(), "Warm now");
+        assert_eq!(provider.last_usage().unwrap().input_tokens, Some(5));
+        let requests = requests.lock();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].starts_with("POST /environments/production/predict"));
+        assert!(requests[1].to_ascii_lowercase().contains("authorization: api-key bt_key"));
+    }
+
+    #[tokio::test]
+    async fn test_baseten_deployment_plain_output() {
+        let (base, _) = mock_server(|_| vec![
+            mock(200, "", serde_json::json!({"output": "Custom truss reply"})),
+        ]).await;
+
+        let provider = SimpleExternalProvider::new(
+            ApiProvider::Baseten, "bt_key".to_string(), "abcd1234".to_string(),
+        ).unwrap().with_api_base(&base);
+
+        assert_eq!(provider.generate("Hello", &test_config()).await.unwrap(), "Custom truss reply");
+    }
+
+    #[test]
+    fn test_hf_loading_delay() {
+        let body = r#"{"error":"Model bigscience/bloom is currently loading","estimated_time":20.5}"#;
+        assert_eq!(hf_loading_delay(body), Some(std::time::Duration::from_secs_f64(20.5)));
+        assert_eq!(hf_loading_delay(r#"{"estimated_time":9999}"#), Some(HF_MAX_LOADING_WAIT));
+        assert_eq!(hf_loading_delay(r#"{"error":"Service Unavailable"}"#), None);
+        assert_eq!(hf_loading_delay("<html>bad gateway</html>"), None);
+    }
+
+    #[test]
+    fn test_parse_hf_stream_line() {
+        let event = parse_hf_stream_line(r#"data:{"token":{"id":1,"text":" Hello","special":false},"generated_text":null,"details":null}"#)
+            .unwrap().unwrap();
+        assert_eq!(event.text, " Hello");
+        assert_eq!(event.generated_tokens, None);
+
+        let last = parse_hf_stream_line(r#"data: {"token":{"id":2,"text":"</s>","special":true},"generated_text":" Hello","details":{"generated_tokens":2}}"#)
+            .unwrap().unwrap();
+        assert!(last.text.is_empty());
+        assert_eq!(last.generated_tokens, Some(2));
+
+        assert!(parse_hf_stream_line("").unwrap().is_none());
+        assert!(parse_hf_stream_line(r#"data:{"error":"Input validation error"}"#).is_err());
+
+        // An event split inside a multi-byte character decodes once its line completes
+        let bytes = "data:{\"token\":{\"id\":3,\"text\":\"caf\u{e9}\",\"special\":false}}\n".as_bytes();
+        let split = bytes.iter().position(|&b| b == 0xc3).unwrap() + 1;
+        let mut lines = LineBuffer::default();
+        assert!(lines.push(&bytes[..split]).is_empty());
+        let line = lines.push(&bytes[split..]).remove(0);
+        assert_eq!(parse_hf_stream_line(&line).unwrap().unwrap().text, "caf\u{e9}");
+    }
+}
@@ -1,23 +1,221 @@
 //! Streaming response handling for LLM generation
 
-use tokio::sync::mpsc;
-use futures::Stream;
+use tokio::sync::{mpsc, Notify};
+use tokio::time::{sleep_until, Instant, Sleep};
+use dashmap::DashMap;
+use futures::{Stream, StreamExt};
+use parking_lot::Mutex;
+use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
+
+use super::{LLMTimeoutError, TokenUsage};
+
+/// Shared slot a producer task writes into when the stream fails mid-way.
+/// The channel closing alone can't distinguish "finished" from "failed".
+pub type StreamErrorSlot = Arc<Mutex<Option<String>>>;
+
+/// Shared slot for provider-reported token usage, filled when the final
+/// chunk arrives (OpenAI `usage`, Anthropic `message_delta`, Ollama `eval_count`).
+pub type StreamUsageSlot = Arc<Mutex<Option<TokenUsage>>>;
+
+/// Stops an in-flight generation. Providers check it between tokens and
+/// drop their HTTP response when it fires, so the upstream request is
+/// aborted rather than left to finish. Clones share the same state; the
+/// default token is never cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once `cancel` has been called
+    pub async fn cancelled(&self) {
+        loop {
+            // Register before checking the flag so a cancel in between isn't missed
+            let notified = self.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Next item of `stream`, or None once cancelled
+    pub async fn next_unless_cancelled<S: Stream + Unpin>(&self, stream: &mut S) -> Option<S::Item> {
+        if self.is_cancelled() {
+            return None;
+        }
+        tokio::select! {
+            item = stream.next() => item,
+            _ = self.cancelled() => None,
+        }
+    }
+}
+
+/// In-flight generations by request id, so a stop from the UI can reach
+/// the provider producing the tokens
+#[derive(Debug, Clone, Default)]
+pub struct GenerationRegistry {
+    tokens: Arc<DashMap<String, CancellationToken>>,
+}
+
+impl GenerationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Token for a new generation; replaces any earlier one under the same id
+    pub fn register(&self, request_id: &str) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.tokens.insert(request_id.to_string(), token.clone());
+        token
+    }
+
+    /// Cancel `request_id`. Returns false if no such generation is running.
+    pub fn cancel(&self, request_id: &str) -> bool {
+        match self.tokens.remove(request_id) {
+            Some((_, token)) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forget a generation that has finished
+    pub fn finish(&self, request_id: &str) {
+        self.tokens.remove(request_id);
+    }
+}
 
 /// Token stream for streaming generation
 pub struct TokenStream {
     receiver: mpsc::Receiver<String>,
+    error: StreamErrorSlot,
+    usage: StreamUsageSlot,
+    deadlines: Option<StreamDeadlines>,
+    /// Set when a deadline ended the stream
+    timed_out: Option<Duration>,
+}
+
+/// Give-up points for a stream: no first token within `first_token` of the
+/// request, then no token for `idle`, or (when set) past `total`
+struct StreamDeadlines {
+    idle: Option<Duration>,
+    /// The current wait: for the first token, then for the next one
+    wait: Option<(Duration, Pin<Box<Sleep>>)>,
+    total: Option<(Duration, Pin<Box<Sleep>>)>,
+}
+
+impl StreamDeadlines {
+    fn token_received(&mut self) {
+        match (self.idle, &mut self.wait) {
+            (Some(idle), Some((timeout, timer))) => {
+                *timeout = idle;
+                timer.as_mut().reset(Instant::now() + idle);
+            }
+            (idle, wait) => {
+                *wait = idle.map(|idle| (idle, Box::pin(sleep_until(Instant::now() + idle))));
+            }
+        }
+    }
+
+    /// The timeout that expired, if any
+    fn poll_expired(&mut self, cx: &mut Context<'_>) -> Option<Duration> {
+        [&mut self.wait, &mut self.total]
+            .into_iter()
+            .flatten()
+            .find_map(|(timeout, timer)| timer.as_mut().poll(cx).is_ready().then_some(*timeout))
+    }
 }
 
 impl TokenStream {
     pub fn new(receiver: mpsc::Receiver<String>) -> Self {
-        Self { receiver }
+        Self::with_error_slot(receiver, Arc::new(Mutex::new(None)))
+    }
+
+    /// Create a stream whose producer can report a terminal error through `error`
+    pub fn with_error_slot(receiver: mpsc::Receiver<String>, error: StreamErrorSlot) -> Self {
+        Self { receiver, error, usage: Arc::new(Mutex::new(None)), deadlines: None, timed_out: None }
+    }
+
+    /// Attach a slot the producer fills with authoritative token counts
+    pub fn with_usage_slot(mut self, usage: StreamUsageSlot) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    /// End the stream with an `LLMTimeoutError` when the first token hasn't
+    /// arrived `first_token` after `started`, when a later token doesn't
+    /// arrive within `idle` of the previous one, or when `total` has passed
+    /// since `started`. The receiver is closed, so the producer stops at its
+    /// next send.
+    pub fn with_deadlines(
+        mut self,
+        first_token: Option<Duration>,
+        idle: Option<Duration>,
+        total: Option<Duration>,
+        started: Instant,
+    ) -> Self {
+        if first_token.is_some() || idle.is_some() || total.is_some() {
+            self.deadlines = Some(StreamDeadlines {
+                idle,
+                wait: first_token.map(|first| (first, Box::pin(sleep_until(started + first)))),
+                total: total.map(|total| (total, Box::pin(sleep_until(started + total)))),
+            });
+        }
+        self
     }
     
     /// Get next token
     pub async fn next(&mut self) -> Option<String> {
-        self.receiver.recv().await
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+
+    /// Error reported by the producer, if the stream ended because of a failure.
+    /// Only meaningful once `next()` has returned `None`.
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().clone()
+    }
+
+    /// Whether a deadline from `with_deadlines` ended the stream
+    pub fn timed_out(&self) -> bool {
+        self.timed_out.is_some()
+    }
+
+    /// Why the stream ended early, if it did: an `LLMTimeoutError` when a
+    /// deadline expired (so `LLMTimeoutError::is_timeout` still matches),
+    /// otherwise the producer's error. Only meaningful once `next()` has
+    /// returned `None`.
+    pub fn failure(&self) -> Option<anyhow::Error> {
+        if let Some(timeout) = self.timed_out {
+            return Some(LLMTimeoutError { timeout }.into());
+        }
+        self.error().map(|err| anyhow::anyhow!(err))
+    }
+
+    /// Token usage reported by the provider, if any. Only meaningful once
+    /// `next()` has returned `None`; None means counts must be estimated.
+    pub fn usage(&self) -> Option<TokenUsage> {
+        self.usage.lock().clone()
     }
     
     /// Collect all tokens into a string
@@ -28,13 +226,48 @@ impl TokenStream {
         }
         result
     }
+
+    /// Collect all tokens, failing if the producer reported a stream error
+    pub async fn try_collect(mut self) -> anyhow::Result<String> {
+        let mut result = String::new();
+        while let Some(token) = self.next().await {
+            result.push_str(&token);
+        }
+        match self.failure() {
+            Some(err) => Err(err),
+            None => Ok(result),
+        }
+    }
 }
 
 impl Stream for TokenStream {
     type Item = String;
     
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.receiver.poll_recv(cx)
+        let this = &mut *self;
+        if this.timed_out.is_some() {
+            return Poll::Ready(None);
+        }
+        match this.receiver.poll_recv(cx) {
+            Poll::Ready(Some(token)) => {
+                if let Some(deadlines) = &mut this.deadlines {
+                    deadlines.token_received();
+                }
+                return Poll::Ready(Some(token));
+            }
+            Poll::Ready(None) => return Poll::Ready(None),
+            Poll::Pending => {}
+        }
+        match this.deadlines.as_mut().and_then(|d| d.poll_expired(cx)) {
+            Some(timeout) => {
+                tracing::warn!(timeout_secs = timeout.as_secs(), "LLM stream timed out");
+                this.receiver.close();
+                *this.error.lock() = Some(LLMTimeoutError { timeout }.to_string());
+                this.timed_out = Some(timeout);
+                Poll::Ready(None)
+            }
+            None => Poll::Pending,
+        }
     }
 }
 
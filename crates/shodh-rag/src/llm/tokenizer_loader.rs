@@ -2,7 +2,7 @@
 //! Handles JSON (Phi-3, Mistral), SentencePiece (Orca-2, Gemma), and BPE (Qwen)
 
 use anyhow::{Result, anyhow};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -33,9 +33,18 @@ pub enum TokenizerFormat {
     BPETokenizer,
 }
 
+/// Where the tokenizer for a model file comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenizerSource {
+    /// GGUF model carrying its own vocabulary; no separate file needed
+    EmbeddedGguf,
+    /// tokenizer.json on disk
+    File(PathBuf),
+}
+
 /// Production tokenizer loader
 pub struct TokenizerLoader {
-    cache_dir: std::path::PathBuf,
+    cache_dir: PathBuf,
 }
 
 impl TokenizerLoader {
@@ -57,6 +66,51 @@ impl TokenizerLoader {
         }
     }
     
+    /// Find the tokenizer for a model file, downloading `tokenizer.json` from the
+    /// model's Hugging Face repo when nothing is on disk. `repo_id` falls back to
+    /// `_name_or_path` in a config.json next to the model.
+    pub async fn resolve_for_model(&self, model_path: &Path, repo_id: Option<&str>) -> Result<TokenizerSource> {
+        if gguf_has_embedded_tokenizer(model_path) {
+            return Ok(TokenizerSource::EmbeddedGguf);
+        }
+
+        let candidates = self.candidate_paths(model_path);
+        if let Some(found) = candidates.iter().find(|p| p.is_file()) {
+            return Ok(TokenizerSource::File(found.clone()));
+        }
+
+        let model_dir = model_path.parent().unwrap_or_else(|| Path::new("."));
+        let repo_id = repo_id.map(str::to_string).or_else(|| infer_repo_id(model_dir));
+        let download_outcome = match &repo_id {
+            Some(repo) => match crate::llm::download_tokenizers::download_repo_tokenizer(repo, model_dir).await {
+                Ok(path) => return Ok(TokenizerSource::File(path)),
+                Err(e) => format!("download from {} failed: {}", repo, e),
+            },
+            None => "no Hugging Face repo id known for this model, download skipped".to_string(),
+        };
+
+        let checked = candidates.iter()
+            .map(|p| format!("  {}", p.display()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Err(anyhow!(
+            "No tokenizer found for {}.\nChecked:\n{}\n{}.\nSelect a tokenizer.json for this model manually.",
+            model_path.display(),
+            checked,
+            download_outcome
+        ))
+    }
+
+    fn candidate_paths(&self, model_path: &Path) -> Vec<PathBuf> {
+        let model_dir = model_path.parent().unwrap_or_else(|| Path::new("."));
+        let stem = model_path.file_stem().and_then(|s| s.to_str()).unwrap_or("model");
+        vec![
+            model_dir.join("tokenizer.json"),
+            model_dir.join(format!("{}.tokenizer.json", stem)),
+            self.cache_dir.join(format!("{}_tokenizer.json", stem)),
+        ]
+    }
+
     /// Load Phi-3 tokenizer (JSON format)
     fn load_phi3_tokenizer(&self) -> Result<Box<dyn UniversalTokenizer>> {
         let tokenizer_path = self.cache_dir.join("phi3_tokenizer.json");
@@ -125,6 +179,24 @@ impl TokenizerLoader {
     }
 }
 
+/// True when `path` is a GGUF file whose metadata includes a tokenizer
+fn gguf_has_embedded_tokenizer(path: &Path) -> bool {
+    crate::llm::gguf_metadata::read_gguf_metadata(path)
+        .map(|meta| meta.has_embedded_tokenizer())
+        .unwrap_or(false)
+}
+
+/// Read the HF repo id a model was exported from, if its config records one
+fn infer_repo_id(model_dir: &Path) -> Option<String> {
+    ["config.json", "genai_config.json"].iter().find_map(|name| {
+        let content = fs::read_to_string(model_dir.join(name)).ok()?;
+        let config: serde_json::Value = serde_json::from_str(&content).ok()?;
+        let repo = config["_name_or_path"].as_str()?;
+        // Local export paths are not repo ids
+        (repo.split('/').count() == 2 && !Path::new(repo).exists()).then(|| repo.to_string())
+    })
+}
+
 /// JSON tokenizer implementation (for Phi-3, Mistral)
 struct JsonTokenizerImpl {
     vocab: HashMap<String, u32>,
@@ -517,4 +589,34 @@ impl UniversalTokenizer for BPETokenizerWrapper {
     fn special_tokens(&self) -> &HashMap<String, u32> {
         &self.special_tokens
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_resolve_for_model_prefers_local_files() {
+        let dir = std::env::temp_dir().join(format!("shodh-tokenizer-resolve-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let loader = TokenizerLoader::new(&dir.join("cache"));
+
+        let gguf = dir.join("model.gguf");
+        crate::llm::gguf_metadata::tests::write_gguf(&gguf, &[("tokenizer.ggml.model", "llama")], &[], 3);
+        assert_eq!(loader.resolve_for_model(&gguf, None).await.unwrap(), TokenizerSource::EmbeddedGguf);
+
+        let onnx = dir.join("model.onnx");
+        fs::write(&onnx, b"onnx").unwrap();
+        let err = loader.resolve_for_model(&onnx, None).await.unwrap_err().to_string();
+        assert!(err.contains(&dir.join("tokenizer.json").display().to_string()));
+        assert!(err.contains("model.tokenizer.json"));
+
+        fs::write(dir.join("tokenizer.json"), b"{}").unwrap();
+        assert_eq!(
+            loader.resolve_for_model(&onnx, None).await.unwrap(),
+            TokenizerSource::File(dir.join("tokenizer.json"))
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
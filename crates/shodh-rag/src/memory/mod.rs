@@ -1,20 +1,31 @@
 //! Simplified Memory System for conversation context
 //!
-//! Provides in-memory conversation history with JSON file persistence.
+//! Provides in-memory conversation history persisted to an append-only JSONL log.
 //! Replaces the heavyweight RocksDB/Vamana-based system.
 
+mod store;
 pub mod types;
 
 use anyhow::Result;
 use serde::{Serialize, Deserialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 use std::path::PathBuf;
 use uuid::Uuid;
 use chrono::Utc;
 
+use crate::embeddings::{l2_normalize, EmbeddingModel};
+
 pub use types::*;
 
+use store::{LogEntry, MemoryLog};
+
+/// Upper bound on the content length of a compressed memory
+const COMPRESSED_SUMMARY_CHARS: usize = 280;
+
+/// Memories embedded per model call when backfilling embeddings
+const EMBED_BACKFILL_BATCH: usize = 32;
+
 /// Configuration for the memory system
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryConfig {
@@ -53,24 +64,103 @@ pub struct MemorySystem {
     config: MemoryConfig,
     memories: Arc<RwLock<Vec<Memory>>>,
     stats: Arc<RwLock<MemoryStats>>,
+    log: MemoryLog,
+    /// Embeds recorded content for `Similarity` retrieval; lexical scoring without it
+    embedder: Option<Arc<dyn EmbeddingModel>>,
 }
 
 impl MemorySystem {
     pub fn new(config: MemoryConfig) -> Result<Self> {
         std::fs::create_dir_all(&config.storage_path)?;
 
-        let system = Self {
-            config: config.clone(),
-            memories: Arc::new(RwLock::new(Vec::new())),
+        let (log, memories) = MemoryLog::open(&config.storage_path)?;
+
+        Ok(Self {
+            config,
+            memories: Arc::new(RwLock::new(memories)),
             stats: Arc::new(RwLock::new(MemoryStats::default())),
+            log,
+            embedder: None,
+        })
+    }
+
+    /// Attach an embedding model for vector `Similarity` retrieval. Memories
+    /// stored without embeddings are embedded by `backfill_embeddings`.
+    pub fn with_embedder(self, embedder: Arc<dyn EmbeddingModel>) -> Self {
+        Self { embedder: Some(embedder), ..self }
+    }
+
+    /// Embed stored memories that were recorded without an embedder, in
+    /// batches on the blocking pool, and persist the result. Returns the
+    /// number of memories embedded.
+    pub async fn backfill_embeddings(&self) -> Result<usize> {
+        let Some(embedder) = self.embedder.clone() else {
+            return Ok(0);
+        };
+        let pending: Vec<(MemoryId, String)> = {
+            let memories = self.memories.read().map_err(|e| anyhow::anyhow!("Lock: {}", e))?;
+            memories.iter()
+                .filter(|m| m.experience.embeddings.is_none())
+                .map(|m| (m.id.clone(), m.experience.content.clone()))
+                .collect()
         };
 
-        system.load_from_disk()?;
-        Ok(system)
+        let mut embedded = HashMap::new();
+        for batch in pending.chunks(EMBED_BACKFILL_BATCH) {
+            let texts: Vec<String> = batch.iter().map(|(_, content)| content.clone()).collect();
+            let model = embedder.clone();
+            let vectors = tokio::task::spawn_blocking(move || {
+                let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+                model.embed_documents(&texts)
+            })
+            .await?;
+            match vectors {
+                Ok(vectors) => embedded.extend(
+                    batch.iter().map(|(id, _)| id.clone()).zip(vectors.into_iter().map(l2_normalize)),
+                ),
+                Err(e) => tracing::warn!("Memory embedding failed for a batch of {}: {}", batch.len(), e),
+            }
+        }
+        if embedded.is_empty() {
+            return Ok(0);
+        }
+
+        let mut memories = self.memories.write().map_err(|e| anyhow::anyhow!("Lock: {}", e))?;
+        let mut backfilled = 0;
+        for m in memories.iter_mut().filter(|m| m.experience.embeddings.is_none()) {
+            if let Some(vector) = embedded.remove(&m.id) {
+                m.experience.embeddings = Some(vector);
+                backfilled += 1;
+            }
+        }
+        if backfilled > 0 {
+            tracing::info!(backfilled, "Embedded stored memories");
+            self.log.compact(&memories)?;
+        }
+        Ok(backfilled)
+    }
+
+    /// Embed `experience` on the blocking pool if it has no embedding yet, so
+    /// model inference doesn't stall an async worker
+    async fn ensure_embedded(&self, experience: &mut Experience) {
+        if experience.embeddings.is_some() {
+            return;
+        }
+        let Some(embedder) = self.embedder.clone() else {
+            return;
+        };
+        let content = experience.content.clone();
+        experience.embeddings = tokio::task::spawn_blocking(move || embed_content(embedder.as_ref(), &content))
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Memory embedding task failed: {}", e);
+                None
+            });
     }
 
     /// Record an experience
-    pub fn record(&self, experience: Experience) -> Result<MemoryId> {
+    pub async fn record(&self, mut experience: Experience) -> Result<MemoryId> {
+        self.ensure_embedded(&mut experience).await;
         let id = MemoryId(Uuid::new_v4());
         let importance = Self::calculate_importance(&experience);
         let memory = Memory {
@@ -83,42 +173,129 @@ impl MemorySystem {
             compressed: false,
         };
 
+        let entry = LogEntry::Put { memory: Box::new(memory.clone()) };
+
         let mut memories = self.memories.write().map_err(|e| anyhow::anyhow!("Lock: {}", e))?;
         memories.push(memory);
 
+        let mut rewritten = false;
         let max = self.config.working_memory_size;
         if memories.len() > max * 2 {
             memories.sort_by(|a, b| b.importance.partial_cmp(&a.importance).unwrap_or(std::cmp::Ordering::Equal));
             memories.truncate(max);
+            rewritten = true;
+        }
+        if self.config.auto_compress {
+            rewritten |= Self::compress_memories(&mut memories, &self.config) > 0;
         }
 
-        drop(memories);
-        if let Err(e) = self.persist_to_disk() {
+        // Bulk changes rewrite the log; a plain record is a single append
+        let persisted = if rewritten {
+            self.log.compact(&memories)
+        } else {
+            self.log_changes(&memories, &[entry])
+        };
+        if let Err(e) = persisted {
             tracing::warn!("Memory persist failed: {}", e);
         }
         Ok(id)
     }
 
+    /// Look up a single memory by id.
+    pub fn get(&self, id: &MemoryId) -> Result<Memory> {
+        let memories = self.memories.read().map_err(|e| anyhow::anyhow!("Lock: {}", e))?;
+        memories.iter()
+            .find(|m| &m.id == id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Memory not found: {}", id.0))
+    }
+
+    /// Replace a memory's experience, recalculating its importance. The id and
+    /// creation time are kept.
+    pub async fn update(&self, id: &MemoryId, mut experience: Experience) -> Result<()> {
+        self.ensure_embedded(&mut experience).await;
+        let importance = Self::calculate_importance(&experience);
+
+        let mut memories = self.memories.write().map_err(|e| anyhow::anyhow!("Lock: {}", e))?;
+        let memory = memories.iter_mut()
+            .find(|m| &m.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Memory not found: {}", id.0))?;
+        memory.experience = experience;
+        memory.importance = importance;
+        memory.compressed = false;
+
+        let entry = LogEntry::Put { memory: Box::new(memory.clone()) };
+        self.log_changes(&memories, &[entry])
+    }
+
+    /// Remove a single memory, e.g. to redact something the user asked to forget.
+    pub fn delete(&self, id: &MemoryId) -> Result<()> {
+        let mut memories = self.memories.write().map_err(|e| anyhow::anyhow!("Lock: {}", e))?;
+        let index = memories.iter()
+            .position(|m| &m.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Memory not found: {}", id.0))?;
+        memories.remove(index);
+
+        self.log_changes(&memories, &[LogEntry::Delete { id: id.clone() }])
+    }
+
+    /// Compress memories older than `compression_age_days` whose importance is
+    /// below `importance_threshold`, replacing their content with an extractive
+    /// summary. Entities are kept as-is. No-op unless `auto_compress` is set;
+    /// already-compressed memories are skipped. Returns the number compressed.
+    pub fn compress_aged(&self) -> Result<usize> {
+        if !self.config.auto_compress {
+            return Ok(0);
+        }
+        let mut memories = self.memories.write().map_err(|e| anyhow::anyhow!("Lock: {}", e))?;
+        let compressed = Self::compress_memories(&mut memories, &self.config);
+
+        if compressed > 0 {
+            tracing::debug!(compressed, "Compressed aged memories");
+            self.log.compact(&memories)?;
+        }
+        Ok(compressed)
+    }
+
+    fn compress_memories(memories: &mut [Memory], config: &MemoryConfig) -> usize {
+        let cutoff = Utc::now() - chrono::Duration::days(config.compression_age_days as i64);
+        let mut compressed = 0;
+        for m in memories.iter_mut()
+            .filter(|m| !m.compressed && m.created_at < cutoff && m.importance < config.importance_threshold)
+        {
+            let summary = extractive_summary(&m.experience.content, &m.experience.entities);
+            if summary.len() < m.experience.content.len() {
+                m.experience.metadata.insert(
+                    "original_length".to_string(),
+                    m.experience.content.len().to_string(),
+                );
+                m.experience.content = summary;
+            }
+            m.compressed = true;
+            compressed += 1;
+        }
+        compressed
+    }
+
     /// Retrieve memories matching a query, respecting the requested retrieval mode.
     pub fn retrieve(&self, query: &Query) -> Result<Vec<Memory>> {
+        // Embed the query before taking the lock; model inference is the slow part
+        let query_vector = match query.retrieval_mode {
+            RetrievalMode::Similarity => query.query_embedding.clone().or_else(|| {
+                let embedder = self.embedder.as_ref()?;
+                let text = query.query_text.as_deref()?;
+                embedder.embed_query_normalized(text)
+                    .map_err(|e| tracing::warn!("Memory query embedding failed: {}", e))
+                    .ok()
+            }),
+            _ => None,
+        };
+
         let memories = self.memories.read().map_err(|e| anyhow::anyhow!("Lock: {}", e))?;
 
         // Phase 1: filter by hard constraints (type, time, importance)
         let mut candidates: Vec<Memory> = memories.iter()
-            .filter(|m| {
-                if let Some(threshold) = query.importance_threshold {
-                    if m.importance < threshold { return false; }
-                }
-                if let Some(types) = &query.experience_types {
-                    if !types.iter().any(|t| {
-                        std::mem::discriminant(&m.experience.experience_type) == std::mem::discriminant(t)
-                    }) { return false; }
-                }
-                if let Some((start, end)) = &query.time_range {
-                    if m.created_at < *start || m.created_at > *end { return false; }
-                }
-                true
-            })
+            .filter(|m| Self::passes_filters(m, query))
             .cloned()
             .collect();
 
@@ -129,13 +306,17 @@ impl MemorySystem {
                 candidates.sort_by(|a, b| b.created_at.cmp(&a.created_at));
             }
             RetrievalMode::Similarity | RetrievalMode::Hybrid => {
-                // Text relevance scoring + recency boost
-                if let Some(ref text) = query.query_text {
-                    let query_lower = text.to_lowercase();
+                // Relevance scoring (cosine when both sides are embedded, else
+                // word overlap) + recency boost
+                if query.query_text.is_some() || query_vector.is_some() {
+                    let query_lower = query.query_text.as_deref().unwrap_or_default().to_lowercase();
                     let query_words: Vec<&str> = query_lower.split_whitespace().collect();
 
                     candidates = candidates.into_iter()
                         .map(|mut m| {
+                            let vector_score = query_vector.as_deref()
+                                .zip(m.experience.embeddings.as_deref())
+                                .map(|(q, e)| cosine_similarity(q, e).max(0.0));
                             let content_lower = m.experience.content.to_lowercase();
                             let entity_lower: Vec<String> = m.experience.entities.iter()
                                 .map(|e| e.to_lowercase())
@@ -145,7 +326,8 @@ impl MemorySystem {
                             let word_hits = query_words.iter()
                                 .filter(|w| content_lower.contains(*w) || entity_lower.iter().any(|e| e.contains(*w)))
                                 .count();
-                            let text_score = if query_words.is_empty() { 0.0 }
+                            let text_score = if let Some(score) = vector_score { score }
+                                else if query_words.is_empty() { 0.0 }
                                 else { word_hits as f32 / query_words.len() as f32 };
 
                             // Recency decay (halve score per 7 days)
@@ -166,7 +348,7 @@ impl MemorySystem {
                     candidates.sort_by(|a, b| b.importance.partial_cmp(&a.importance).unwrap_or(std::cmp::Ordering::Equal));
                 }
             }
-            RetrievalMode::Causal | RetrievalMode::Associative => {
+            RetrievalMode::Associative => {
                 // Entity overlap — find memories that share entities with the query
                 if let Some(ref text) = query.query_text {
                     let query_lower = text.to_lowercase();
@@ -184,12 +366,108 @@ impl MemorySystem {
                     candidates.sort_by(|a, b| b.created_at.cmp(&a.created_at));
                 }
             }
+            RetrievalMode::Causal => {
+                // Traversal passes through filtered-out memories, so walk the full set
+                candidates = Self::causal_walk(&memories, query);
+            }
         }
 
         candidates.truncate(query.max_results);
         Ok(candidates)
     }
 
+    /// Hard constraints (importance, type, time range) shared by all modes.
+    fn passes_filters(m: &Memory, query: &Query) -> bool {
+        if let Some(threshold) = query.importance_threshold {
+            if m.importance < threshold { return false; }
+        }
+        if let Some(types) = &query.experience_types {
+            if !types.iter().any(|t| {
+                std::mem::discriminant(&m.experience.experience_type) == std::mem::discriminant(t)
+            }) { return false; }
+        }
+        if let Some((start, end)) = &query.time_range {
+            if m.created_at < *start || m.created_at > *end { return false; }
+        }
+        true
+    }
+
+    /// Walk `causal_chain` links outward from the seed memory, up to
+    /// `causal_depth` links in each direction. Antecedents are followed through
+    /// the seed's `causal_chain`, consequents through memories that name it in
+    /// theirs. Results are ordered by causal distance, causes before effects at
+    /// equal distance. Filters apply to results, not to traversal.
+    fn causal_walk(memories: &[Memory], query: &Query) -> Vec<Memory> {
+        let seed = query.causal_seed.as_ref()
+            .and_then(|id| memories.iter().position(|m| &m.id == id))
+            .or_else(|| query.query_text.as_deref().and_then(|text| Self::best_text_match(memories, text)));
+        let Some(seed) = seed else { return Vec::new() };
+
+        let index: HashMap<&MemoryId, usize> = memories.iter()
+            .enumerate()
+            .map(|(i, m)| (&m.id, i))
+            .collect();
+        let mut effects: HashMap<&MemoryId, Vec<usize>> = HashMap::new();
+        for (i, m) in memories.iter().enumerate() {
+            for cause in &m.experience.causal_chain {
+                effects.entry(cause).or_default().push(i);
+            }
+        }
+
+        let walk = |next: &dyn Fn(usize) -> Vec<usize>| -> Vec<(usize, usize)> {
+            let mut visited = HashSet::from([seed]);
+            let mut frontier = vec![seed];
+            let mut found = Vec::new();
+            for distance in 1..=query.causal_depth {
+                frontier = frontier.iter()
+                    .flat_map(|&i| next(i))
+                    .filter(|&j| visited.insert(j))
+                    .collect();
+                if frontier.is_empty() { break; }
+                found.extend(frontier.iter().map(|&j| (distance, j)));
+            }
+            found
+        };
+        let causes = walk(&|i| memories[i].experience.causal_chain.iter()
+            .filter_map(|id| index.get(id).copied())
+            .collect());
+        let consequents = walk(&|i| effects.get(&memories[i].id).cloned().unwrap_or_default());
+
+        let mut ranked = vec![(0, seed)];
+        ranked.extend(causes);
+        ranked.extend(consequents);
+        ranked.sort_by_key(|&(distance, _)| distance);
+
+        let mut seen = HashSet::new();
+        ranked.into_iter()
+            .filter(|&(_, i)| seen.insert(i))
+            .map(|(_, i)| &memories[i])
+            .filter(|m| Self::passes_filters(m, query))
+            .take(query.max_results)
+            .cloned()
+            .collect()
+    }
+
+    /// Index of the memory whose content and entities share the most words with
+    /// `text`, most recent first on ties.
+    fn best_text_match(memories: &[Memory], text: &str) -> Option<usize> {
+        let query_lower = text.to_lowercase();
+        let query_words: Vec<&str> = query_lower.split_whitespace().collect();
+        memories.iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let content_lower = m.experience.content.to_lowercase();
+                let hits = query_words.iter()
+                    .filter(|w| content_lower.contains(*w)
+                        || m.experience.entities.iter().any(|e| e.to_lowercase().contains(*w)))
+                    .count();
+                (i, hits)
+            })
+            .filter(|&(_, hits)| hits > 0)
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| memories[a.0].created_at.cmp(&memories[b.0].created_at)))
+            .map(|(i, _)| i)
+    }
+
     /// Calculate importance based on experience content and type.
     fn calculate_importance(experience: &Experience) -> f32 {
         let mut score: f32 = 0.3; // base
@@ -246,9 +524,10 @@ impl MemorySystem {
         }
 
         let removed = before - memories.len();
-        drop(memories);
-        if let Err(e) = self.persist_to_disk() {
-            tracing::warn!("Memory persist after forget failed: {}", e);
+        if removed > 0 {
+            if let Err(e) = self.log.compact(&memories) {
+                tracing::warn!("Memory persist after forget failed: {}", e);
+            }
         }
         Ok(removed)
     }
@@ -257,27 +536,301 @@ impl MemorySystem {
         self.memories.read().map(|m| m.len()).unwrap_or(0)
     }
 
-    fn persist_to_disk(&self) -> Result<()> {
-        let memories = self.memories.read().map_err(|e| anyhow::anyhow!("Lock: {}", e))?;
-        let json = serde_json::to_string(&*memories)?;
-        std::fs::write(self.config.storage_path.join("memories.json"), json)?;
-        Ok(())
+    /// Force pending writes to stable storage; call on shutdown.
+    pub fn flush(&self) -> Result<()> {
+        self.log.flush()
     }
 
-    fn load_from_disk(&self) -> Result<()> {
-        let path = self.config.storage_path.join("memories.json");
-        if path.exists() {
-            let json = std::fs::read_to_string(&path)
-                .map_err(|e| anyhow::anyhow!("Failed to read memories file: {}", e))?;
-            let data: Vec<Memory> = serde_json::from_str(&json)
-                .map_err(|e| {
-                    tracing::warn!("Corrupt memories.json, starting fresh: {}", e);
-                    anyhow::anyhow!("Failed to parse memories: {}", e)
-                })?;
-            let mut memories = self.memories.write()
-                .map_err(|e| anyhow::anyhow!("Memory lock poisoned: {}", e))?;
-            *memories = data;
+    /// Append changes to the log, compacting it once stale entries dominate.
+    /// Called with the memories lock held so log order matches memory order.
+    fn log_changes(&self, memories: &[Memory], entries: &[LogEntry]) -> Result<()> {
+        if self.log.append(entries, memories.len())? {
+            self.log.compact(memories)?;
         }
         Ok(())
     }
 }
+
+/// Normalized document embedding for memory content; failures are logged
+/// and leave the memory unembedded.
+fn embed_content(embedder: &dyn EmbeddingModel, content: &str) -> Option<Vec<f32>> {
+    embedder.embed_document_normalized(content)
+        .map_err(|e| tracing::warn!("Memory embedding failed: {}", e))
+        .ok()
+}
+
+/// Cosine similarity; 0.0 for mismatched dimensions or zero vectors.
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a < 1e-12 || norm_b < 1e-12 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+/// Extractive summary: the first sentence plus the sentences mentioning the
+/// most entities, kept in original order, within `COMPRESSED_SUMMARY_CHARS`.
+/// Without entities the leading sentences are kept instead.
+fn extractive_summary(content: &str, entities: &[String]) -> String {
+    if content.len() <= COMPRESSED_SUMMARY_CHARS {
+        return content.to_string();
+    }
+
+    let sentences: Vec<&str> = content
+        .split_inclusive(['.', '!', '?', '\n'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+    let entities_lower: Vec<String> = entities.iter().map(|e| e.to_lowercase()).collect();
+
+    let mut ranked: Vec<(usize, usize)> = sentences.iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let lower = s.to_lowercase();
+            (i, entities_lower.iter().filter(|e| lower.contains(e.as_str())).count())
+        })
+        .filter(|&(i, hits)| i == 0 || hits > 0 || entities_lower.is_empty())
+        .collect();
+    // First sentence always leads; the rest by entity mentions
+    ranked.sort_by(|a, b| (b.0 == 0).cmp(&(a.0 == 0)).then(b.1.cmp(&a.1)).then(a.0.cmp(&b.0)));
+
+    let mut selected = Vec::new();
+    let mut len = 0;
+    for (i, _) in ranked {
+        let sentence_len = sentences[i].len() + 1;
+        if len + sentence_len > COMPRESSED_SUMMARY_CHARS {
+            continue;
+        }
+        len += sentence_len;
+        selected.push(i);
+    }
+
+    if selected.is_empty() {
+        // First sentence alone is too long: cut it at a char boundary
+        let mut end = COMPRESSED_SUMMARY_CHARS - '…'.len_utf8();
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        return format!("{}…", content[..end].trim_end());
+    }
+
+    selected.sort_unstable();
+    selected.iter().map(|&i| sentences[i]).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> MemoryConfig {
+        MemoryConfig {
+            storage_path: std::env::temp_dir().join(format!("shodh_memory_test_{}", Uuid::new_v4())),
+            ..Default::default()
+        }
+    }
+
+    fn test_system() -> MemorySystem {
+        MemorySystem::new(test_config()).unwrap()
+    }
+
+    fn experience(content: &str, causal_chain: Vec<MemoryId>) -> Experience {
+        Experience {
+            experience_type: ExperienceType::Decision,
+            content: content.to_string(),
+            context: None,
+            entities: Vec::new(),
+            metadata: HashMap::new(),
+            embeddings: None,
+            related_memories: Vec::new(),
+            causal_chain,
+            outcomes: Vec::new(),
+        }
+    }
+
+    fn causal_query(seed: Option<MemoryId>, text: Option<&str>, depth: usize) -> Query {
+        Query {
+            query_text: text.map(str::to_string),
+            query_embedding: None,
+            time_range: None,
+            experience_types: None,
+            importance_threshold: None,
+            max_results: 10,
+            retrieval_mode: RetrievalMode::Causal,
+            causal_seed: seed,
+            causal_depth: depth,
+        }
+    }
+
+    /// Maps synonyms onto shared axes so paraphrases embed close together
+    struct SynonymEmbedder;
+
+    impl EmbeddingModel for SynonymEmbedder {
+        fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+            self.embed_document(text)
+        }
+        fn embed_document(&self, text: &str) -> Result<Vec<f32>> {
+            let mut v = vec![0.0; 3];
+            for word in text.to_lowercase().split_whitespace() {
+                match word {
+                    "car" | "automobile" | "vehicle" => v[0] += 1.0,
+                    "meal" | "dinner" | "food" => v[1] += 1.0,
+                    _ => v[2] += 0.1,
+                }
+            }
+            Ok(v)
+        }
+        fn dimension(&self) -> usize {
+            3
+        }
+    }
+
+    fn contents(memories: &[Memory]) -> Vec<&str> {
+        memories.iter().map(|m| m.experience.content.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_causal_retrieval_walks_chain_by_distance() {
+        let system = test_system();
+        // a -> b -> c -> d, plus an unrelated memory
+        let a = system.record(experience("disk filled up", vec![])).await.unwrap();
+        let b = system.record(experience("database writes failed", vec![a])).await.unwrap();
+        let c = system.record(experience("api returned errors", vec![b.clone()])).await.unwrap();
+        system.record(experience("users reported outage", vec![c])).await.unwrap();
+        system.record(experience("unrelated lunch order", vec![])).await.unwrap();
+
+        let from_b = system.retrieve(&causal_query(Some(b), None, 3)).unwrap();
+        assert_eq!(contents(&from_b), vec![
+            "database writes failed",
+            "disk filled up",
+            "api returned errors",
+            "users reported outage",
+        ]);
+
+        let from_text = system.retrieve(&causal_query(None, Some("outage reported"), 3)).unwrap();
+        assert_eq!(contents(&from_text), vec![
+            "users reported outage",
+            "api returned errors",
+            "database writes failed",
+            "disk filled up",
+        ]);
+
+        let bounded = system.retrieve(&causal_query(None, Some("outage reported"), 1)).unwrap();
+        assert_eq!(contents(&bounded), vec!["users reported outage", "api returned errors"]);
+
+        let _ = std::fs::remove_dir_all(&system.config.storage_path);
+    }
+
+    #[tokio::test]
+    async fn test_compress_aged_is_idempotent_and_persists() {
+        let config = MemoryConfig { auto_compress: true, importance_threshold: 1.1, ..test_config() };
+        let system = MemorySystem::new(config.clone()).unwrap();
+
+        let filler = "Nothing of note happened during this part of the meeting. ".repeat(8);
+        let mut exp = experience(&format!("Alice approved the Falcon budget. {}Bob owns the rollout.", filler), vec![]);
+        exp.entities = vec!["Alice".to_string(), "Falcon".to_string(), "Bob".to_string()];
+        let id = system.record(exp).await.unwrap();
+        system.record(experience("fresh memory stays verbose", vec![])).await.unwrap();
+
+        system.memories.write().unwrap().iter_mut()
+            .filter(|m| m.id == id)
+            .for_each(|m| m.created_at = Utc::now() - chrono::Duration::days(30));
+
+        assert_eq!(system.compress_aged().unwrap(), 1);
+        let compressed = system.memories.read().unwrap().iter().find(|m| m.id == id).cloned().unwrap();
+        assert!(compressed.compressed);
+        assert_eq!(compressed.experience.content, "Alice approved the Falcon budget. Bob owns the rollout.");
+        assert_eq!(compressed.experience.entities.len(), 3);
+
+        assert_eq!(system.compress_aged().unwrap(), 0);
+
+        let reloaded = MemorySystem::new(config.clone()).unwrap();
+        let restored = reloaded.memories.read().unwrap().iter().find(|m| m.id == id).cloned().unwrap();
+        assert!(restored.compressed);
+        assert_eq!(restored.experience.content, compressed.experience.content);
+
+        let _ = std::fs::remove_dir_all(&config.storage_path);
+    }
+
+    #[tokio::test]
+    async fn test_similarity_retrieval_uses_embeddings() {
+        let config = test_config();
+        let system = MemorySystem::new(config.clone()).unwrap().with_embedder(Arc::new(SynonymEmbedder));
+        system.record(experience("cooked dinner for the team", vec![])).await.unwrap();
+        system.record(experience("bought a new automobile", vec![])).await.unwrap();
+
+        let query = Query {
+            retrieval_mode: RetrievalMode::Similarity,
+            ..causal_query(None, Some("vehicle purchase"), 0)
+        };
+        let results = system.retrieve(&query).unwrap();
+        assert_eq!(results[0].experience.content, "bought a new automobile");
+
+        // Embeddings are persisted; without an embedder scoring falls back to word overlap
+        let reloaded = MemorySystem::new(config.clone()).unwrap();
+        assert!(reloaded.memories.read().unwrap().iter().all(|m| m.experience.embeddings.is_some()));
+        let lexical = reloaded.retrieve(&Query {
+            query_text: Some("cooked".to_string()),
+            ..query
+        }).unwrap();
+        assert_eq!(lexical[0].experience.content, "cooked dinner for the team");
+
+        let _ = std::fs::remove_dir_all(&config.storage_path);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_embeds_memories_in_batches() {
+        let config = test_config();
+        let plain = MemorySystem::new(config.clone()).unwrap();
+        for i in 0..EMBED_BACKFILL_BATCH + 2 {
+            plain.record(experience(&format!("meeting note {}", i), vec![])).await.unwrap();
+        }
+        plain.record(experience("bought a new automobile", vec![])).await.unwrap();
+        drop(plain);
+
+        let system = MemorySystem::new(config.clone()).unwrap().with_embedder(Arc::new(SynonymEmbedder));
+        assert_eq!(system.backfill_embeddings().await.unwrap(), EMBED_BACKFILL_BATCH + 3);
+        assert_eq!(system.backfill_embeddings().await.unwrap(), 0);
+
+        let query = Query {
+            retrieval_mode: RetrievalMode::Similarity,
+            ..causal_query(None, Some("vehicle purchase"), 0)
+        };
+        assert_eq!(system.retrieve(&query).unwrap()[0].experience.content, "bought a new automobile");
+
+        let reloaded = MemorySystem::new(config.clone()).unwrap();
+        assert!(reloaded.memories.read().unwrap().iter().all(|m| m.experience.embeddings.is_some()));
+
+        let _ = std::fs::remove_dir_all(&config.storage_path);
+    }
+
+    #[tokio::test]
+    async fn test_get_update_delete_by_id() {
+        let config = test_config();
+        let system = MemorySystem::new(config.clone()).unwrap();
+        let id = system.record(experience("my password hint is blue", vec![])).await.unwrap();
+        let before = system.get(&id).unwrap();
+
+        let mut corrected = experience("my password hint is green", vec![]);
+        corrected.experience_type = ExperienceType::Task;
+        system.update(&id, corrected).await.unwrap();
+        let after = system.get(&id).unwrap();
+        assert_eq!(after.experience.content, "my password hint is green");
+        assert!(after.importance > before.importance);
+        assert_eq!(after.created_at, before.created_at);
+
+        system.delete(&id).unwrap();
+        assert!(system.get(&id).is_err());
+        assert!(system.delete(&id).is_err());
+        assert!(system.update(&id, experience("gone", vec![])).await.is_err());
+
+        let reloaded = MemorySystem::new(config.clone()).unwrap();
+        assert_eq!(reloaded.count(), 0);
+
+        let _ = std::fs::remove_dir_all(&config.storage_path);
+    }
+}
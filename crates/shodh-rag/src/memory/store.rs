@@ -0,0 +1,254 @@
+//! Append-only JSONL persistence for the memory system
+//!
+//! Every change is appended to `memories.jsonl` as a single `put` or `delete`
+//! line, so a write costs O(1) instead of rewriting the whole store. The log
+//! is compacted into one `put` per live memory once stale entries dominate.
+//! A legacy `memories.json` snapshot is migrated on first open.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::types::{Memory, MemoryId};
+
+const LOG_FILE: &str = "memories.jsonl";
+const LEGACY_FILE: &str = "memories.json";
+
+/// Logs shorter than this are never compacted
+const COMPACT_MIN_ENTRIES: usize = 256;
+
+/// One line of the memory log
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum LogEntry {
+    Put { memory: Box<Memory> },
+    Delete { id: MemoryId },
+}
+
+/// Append handle plus the number of entries currently in the log
+struct LogFile {
+    file: File,
+    entries: usize,
+}
+
+pub struct MemoryLog {
+    path: PathBuf,
+    inner: Mutex<LogFile>,
+}
+
+impl MemoryLog {
+    /// Open the log in `dir`, replaying it into the current set of memories.
+    /// Migrates a legacy `memories.json` and repairs a torn final line.
+    pub fn open(dir: &Path) -> Result<(Self, Vec<Memory>)> {
+        let path = dir.join(LOG_FILE);
+        let legacy = dir.join(LEGACY_FILE);
+
+        let (memories, entries, needs_compaction) = if path.exists() {
+            replay(&path)?
+        } else if legacy.exists() {
+            let json = std::fs::read_to_string(&legacy)
+                .map_err(|e| anyhow::anyhow!("Failed to read memories file: {}", e))?;
+            let memories: Vec<Memory> = serde_json::from_str(&json)
+                .map_err(|e| {
+                    tracing::warn!("Corrupt memories.json, starting fresh: {}", e);
+                    anyhow::anyhow!("Failed to parse memories: {}", e)
+                })?;
+            tracing::info!(count = memories.len(), "Migrating memories.json to append-only log");
+            (memories, 0, true)
+        } else {
+            (Vec::new(), 0, false)
+        };
+
+        let log = Self {
+            inner: Mutex::new(LogFile { file: open_append(&path)?, entries }),
+            path,
+        };
+        if needs_compaction {
+            log.compact(&memories)?;
+            if legacy.exists() {
+                std::fs::rename(&legacy, dir.join("memories.json.migrated"))?;
+            }
+        }
+        Ok((log, memories))
+    }
+
+    /// Append entries as one write. Returns true when the log has grown large
+    /// enough relative to `live` memories that the caller should compact.
+    pub fn append(&self, entries: &[LogEntry], live: usize) -> Result<bool> {
+        let mut buf = Vec::new();
+        for entry in entries {
+            serde_json::to_writer(&mut buf, entry)?;
+            buf.push(b'\n');
+        }
+
+        let mut inner = self.inner.lock().map_err(|e| anyhow::anyhow!("Lock: {}", e))?;
+        inner.file.write_all(&buf)?;
+        inner.entries += entries.len();
+        Ok(inner.entries > COMPACT_MIN_ENTRIES && inner.entries > live * 2)
+    }
+
+    /// Rewrite the log as one `put` per memory. Written to a temp file and
+    /// renamed over the log so a crash leaves either the old or new log intact.
+    pub fn compact(&self, memories: &[Memory]) -> Result<()> {
+        let mut inner = self.inner.lock().map_err(|e| anyhow::anyhow!("Lock: {}", e))?;
+
+        let tmp = self.path.with_extension("jsonl.tmp");
+        let mut writer = std::io::BufWriter::new(File::create(&tmp)?);
+        for memory in memories {
+            serde_json::to_writer(&mut writer, &PutRef { op: "put", memory })?;
+            writer.write_all(b"\n")?;
+        }
+        writer.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        std::fs::rename(&tmp, &self.path)?;
+
+        *inner = LogFile { file: open_append(&self.path)?, entries: memories.len() };
+        Ok(())
+    }
+
+    /// Force appended entries to stable storage
+    pub fn flush(&self) -> Result<()> {
+        let inner = self.inner.lock().map_err(|e| anyhow::anyhow!("Lock: {}", e))?;
+        inner.file.sync_data()?;
+        Ok(())
+    }
+}
+
+/// Borrowing twin of `LogEntry::Put` so compaction doesn't clone every memory
+#[derive(Serialize)]
+struct PutRef<'a> {
+    op: &'static str,
+    memory: &'a Memory,
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open memory log {}", path.display()))
+}
+
+/// Replay the log in order. Unparseable lines (a write torn by a crash) are
+/// skipped and flag the log for compaction.
+fn replay(path: &Path) -> Result<(Vec<Memory>, usize, bool)> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut slots: Vec<Option<Memory>> = Vec::new();
+    let mut index: HashMap<MemoryId, usize> = HashMap::new();
+    let mut entries = 0;
+    let mut corrupt = false;
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        entries += 1;
+        match serde_json::from_str::<LogEntry>(&line) {
+            Ok(LogEntry::Put { memory }) => match index.get(&memory.id) {
+                Some(&slot) => slots[slot] = Some(*memory),
+                None => {
+                    index.insert(memory.id.clone(), slots.len());
+                    slots.push(Some(*memory));
+                }
+            },
+            Ok(LogEntry::Delete { id }) => {
+                if let Some(slot) = index.remove(&id) {
+                    slots[slot] = None;
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Skipping unreadable memory log entry: {}", e);
+                corrupt = true;
+            }
+        }
+    }
+
+    let memories: Vec<Memory> = slots.into_iter().flatten().collect();
+    let needs_compaction = corrupt || (entries > COMPACT_MIN_ENTRIES && entries > memories.len() * 2);
+    Ok((memories, entries, needs_compaction))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::types::{Experience, ExperienceType};
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("shodh_memory_log_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn memory(content: &str) -> Memory {
+        Memory {
+            id: MemoryId(Uuid::new_v4()),
+            experience: Experience {
+                experience_type: ExperienceType::Conversation,
+                content: content.to_string(),
+                context: None,
+                entities: Vec::new(),
+                metadata: HashMap::new(),
+                embeddings: None,
+                related_memories: Vec::new(),
+                causal_chain: Vec::new(),
+                outcomes: Vec::new(),
+            },
+            importance: 0.5,
+            access_count: 0,
+            created_at: Utc::now(),
+            last_accessed: Utc::now(),
+            compressed: false,
+        }
+    }
+
+    fn contents(memories: &[Memory]) -> Vec<&str> {
+        memories.iter().map(|m| m.experience.content.as_str()).collect()
+    }
+
+    #[test]
+    fn test_replay_applies_puts_and_deletes_in_order() {
+        let dir = temp_dir();
+        let (log, _) = MemoryLog::open(&dir).unwrap();
+        let a = memory("a");
+        let b = memory("b");
+        let mut a2 = a.clone();
+        a2.experience.content = "a edited".to_string();
+        log.append(&[LogEntry::Put { memory: Box::new(a) }, LogEntry::Put { memory: Box::new(b.clone()) }], 2).unwrap();
+        log.append(&[LogEntry::Put { memory: Box::new(a2) }, LogEntry::Delete { id: b.id }], 1).unwrap();
+        log.flush().unwrap();
+        drop(log);
+
+        let (_, memories) = MemoryLog::open(&dir).unwrap();
+        assert_eq!(contents(&memories), vec!["a edited"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_migrates_legacy_json_and_skips_torn_line() {
+        let dir = temp_dir();
+        let legacy = vec![memory("old one"), memory("old two")];
+        std::fs::write(dir.join(LEGACY_FILE), serde_json::to_string(&legacy).unwrap()).unwrap();
+
+        let (log, memories) = MemoryLog::open(&dir).unwrap();
+        assert_eq!(contents(&memories), vec!["old one", "old two"]);
+        assert!(!dir.join(LEGACY_FILE).exists());
+        drop(log);
+
+        // Simulate a crash mid-append
+        let mut file = open_append(&dir.join(LOG_FILE)).unwrap();
+        file.write_all(b"{\"op\":\"put\",\"memory\":{\"id\"").unwrap();
+        drop(file);
+
+        let (_, memories) = MemoryLog::open(&dir).unwrap();
+        assert_eq!(contents(&memories), vec!["old one", "old two"]);
+        let log_text = std::fs::read_to_string(dir.join(LOG_FILE)).unwrap();
+        assert_eq!(log_text.lines().count(), 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
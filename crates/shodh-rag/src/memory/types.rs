@@ -347,6 +347,11 @@ pub struct Query {
     pub importance_threshold: Option<f32>,
     pub max_results: usize,
     pub retrieval_mode: RetrievalMode,
+    /// Starting memory for `Causal` retrieval; when `None` the best
+    /// `query_text` match is used
+    pub causal_seed: Option<MemoryId>,
+    /// Maximum number of causal links followed from the seed
+    pub causal_depth: usize,
 }
 
 /// Retrieval modes
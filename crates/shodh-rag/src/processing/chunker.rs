@@ -1,6 +1,24 @@
 use crate::types::DocumentSection;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::LazyLock;
 use uuid::Uuid;
 
+/// How documents are split into chunks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkStrategy {
+    /// Sliding window of `chunk_size` bytes with overlap, snapped to sentence breaks
+    #[default]
+    FixedSize,
+    /// Structure-aware: markdown sections and fenced code blocks, or top-level
+    /// definitions in source files. Other text falls back to `Recursive`.
+    Semantic,
+    /// Split on paragraphs, then lines, then sentences, then words until pieces fit
+    Recursive,
+}
+
 #[derive(Debug, Clone)]
 pub struct ChunkResult {
     pub id: Uuid,
@@ -9,12 +27,58 @@ pub struct ChunkResult {
     pub heading: Option<String>,
     pub start_offset: usize,
     pub end_offset: usize,
+    pub strategy: ChunkStrategy,
 }
 
+#[derive(Debug, Clone)]
 pub struct TextChunker {
     chunk_size: usize,
     chunk_overlap: usize,
     min_chunk_size: usize,
+    strategy: ChunkStrategy,
+}
+
+/// Start of a top-level definition in common languages (column 0 only)
+static RE_CODE_BOUNDARY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"^(?:(?:pub(?:\([^)]*\))?|export|default|async|static|public|private|protected|internal|abstract|final|unsafe|extern(?:\s+"C")?)\s+)*(?:fn|struct|enum|impl|trait|mod|type|def|class|function|func|interface|object|module)\b"#,
+    )
+    .unwrap()
+});
+
+const CODE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "js", "jsx", "ts", "tsx", "go", "java", "kt", "c", "cc", "cpp", "h", "hpp",
+    "cs", "rb", "php", "swift", "scala",
+];
+/// DOCX and EPUB are converted to markdown by the parser, so they chunk like markdown
+const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown", "mdx", "docx", "epub"];
+
+/// Separators tried in order by the recursive splitter
+const RECURSIVE_SEPARATORS: &[&str] = &["\n\n", "\n", ". ", " "];
+/// Chinese/Japanese/Korean: sentence and clause punctuation instead of spaces
+const CJK_SEPARATORS: &[&str] = &[
+    "\n\n", "\n", "。", "！", "？", "! ", "? ", ". ", "；", "，", "、", " ",
+];
+/// A CJK character is three UTF-8 bytes and roughly one token, while English
+/// averages about four bytes per token. CJK chunks are capped at this share of
+/// `chunk_size` bytes so both hold a similar number of tokens.
+const CJK_SIZE_NUMERATOR: usize = 3;
+const CJK_SIZE_DENOMINATOR: usize = 4;
+
+/// Rows shown in a sheet summary when the whole sheet does not fit
+const SHEET_PREVIEW_ROWS: usize = 5;
+
+/// A contiguous byte range of the source text that should not be split further
+/// unless it exceeds the chunk size.
+#[derive(Debug, Clone)]
+struct Segment {
+    start: usize,
+    end: usize,
+    heading: Option<String>,
+    /// Never split (fenced code block), even if oversized
+    atomic: bool,
+    /// Piece of an oversized block; packed only with its own siblings
+    split: bool,
 }
 
 impl TextChunker {
@@ -23,7 +87,151 @@ impl TextChunker {
             chunk_size,
             chunk_overlap,
             min_chunk_size,
+            strategy: ChunkStrategy::FixedSize,
+        }
+    }
+
+    pub fn with_strategy(mut self, strategy: ChunkStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn strategy(&self) -> ChunkStrategy {
+        self.strategy
+    }
+
+    /// Chunk using the configured strategy. `source` (a file path or name) picks
+    /// markdown vs code handling for `Semantic`. CJK prose is split on sentence
+    /// punctuation whatever the strategy, since it has no spaces to break on.
+    pub fn chunk_for_source(&self, text: &str, source: &str) -> Vec<ChunkResult> {
+        let ext = std::path::Path::new(source)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .unwrap_or_default();
+        if !CODE_EXTENSIONS.contains(&ext.as_str()) && super::language::is_cjk(text) {
+            let markdown = self.strategy == ChunkStrategy::Semantic && MARKDOWN_EXTENSIONS.contains(&ext.as_str());
+            return self.chunk_cjk(text, markdown);
+        }
+
+        match self.strategy {
+            ChunkStrategy::FixedSize => self.chunk(text),
+            ChunkStrategy::Recursive => self.chunk_recursive(text),
+            ChunkStrategy::Semantic => {
+                if MARKDOWN_EXTENSIONS.contains(&ext.as_str()) {
+                    self.chunk_markdown(text)
+                } else if CODE_EXTENSIONS.contains(&ext.as_str()) {
+                    self.chunk_code(text)
+                } else {
+                    self.chunk_recursive(text)
+                }
+            }
+        }
+    }
+
+    /// Markdown: headings stay with their section body and fenced code blocks
+    /// are never split. Sections are packed together up to `chunk_size`.
+    pub fn chunk_markdown(&self, text: &str) -> Vec<ChunkResult> {
+        let segments = self.split_oversized(text, markdown_segments(text), RECURSIVE_SEPARATORS);
+        self.pack_segments(text, segments, ChunkStrategy::Semantic)
+    }
+
+    /// Chinese/Japanese/Korean text: recursive splitting on sentence
+    /// punctuation, with `chunk_size` scaled to a comparable token count.
+    /// Markdown keeps its heading/fence structure. No overlap.
+    pub fn chunk_cjk(&self, text: &str, markdown: bool) -> Vec<ChunkResult> {
+        let scaled = Self {
+            chunk_size: (self.chunk_size * CJK_SIZE_NUMERATOR / CJK_SIZE_DENOMINATOR).max(1),
+            ..self.clone()
+        };
+        let (segments, strategy) = if markdown {
+            (markdown_segments(text), ChunkStrategy::Semantic)
+        } else {
+            let whole = Segment { start: 0, end: text.len(), heading: None, atomic: false, split: false };
+            (vec![whole], ChunkStrategy::Recursive)
+        };
+        let segments = scaled.split_oversized(text, segments, CJK_SEPARATORS);
+        scaled.pack_segments(text, segments, strategy)
+    }
+
+    /// Source code: split at top-level function/class/impl boundaries, keeping
+    /// doc comments and attributes with the definition they annotate.
+    pub fn chunk_code(&self, text: &str) -> Vec<ChunkResult> {
+        let segments = self.split_oversized(text, code_segments(text), RECURSIVE_SEPARATORS);
+        self.pack_segments(text, segments, ChunkStrategy::Semantic)
+    }
+
+    /// Recursive splitting on progressively finer separators. No overlap.
+    pub fn chunk_recursive(&self, text: &str) -> Vec<ChunkResult> {
+        let segments = self.split_oversized(
+            text,
+            vec![Segment { start: 0, end: text.len(), heading: None, atomic: false, split: false }],
+            RECURSIVE_SEPARATORS,
+        );
+        self.pack_segments(text, segments, ChunkStrategy::Recursive)
+    }
+
+    /// Break non-atomic segments larger than `chunk_size` into pieces that fit,
+    /// trying `separators` coarsest first
+    fn split_oversized(&self, text: &str, segments: Vec<Segment>, separators: &[&str]) -> Vec<Segment> {
+        let mut out = Vec::with_capacity(segments.len());
+        for seg in segments {
+            if seg.atomic || seg.end - seg.start <= self.chunk_size {
+                out.push(seg);
+                continue;
+            }
+            // recursive_ranges already packs the pieces as tightly as they fit
+            for (start, end) in recursive_ranges(text, seg.start, seg.end, self.chunk_size, separators) {
+                out.push(Segment { start, end, heading: seg.heading.clone(), atomic: false, split: true });
+            }
+        }
+        out
+    }
+
+    /// Greedily merge adjacent segments into chunks of at most `chunk_size`
+    /// (a single oversized atomic segment becomes its own chunk). Pieces of a
+    /// split block are never merged with neighbouring blocks, so a definition's
+    /// tail doesn't share a chunk with the next definition.
+    fn pack_segments(&self, text: &str, segments: Vec<Segment>, strategy: ChunkStrategy) -> Vec<ChunkResult> {
+        let mut chunks = Vec::new();
+        let mut current: Option<(usize, usize, Option<String>)> = None;
+
+        let flush = |range: (usize, usize, Option<String>), chunks: &mut Vec<ChunkResult>| {
+            let (start, end, heading) = range;
+            let chunk_text = &text[start..end];
+            if chunk_text.trim().len() < self.min_chunk_size {
+                return;
+            }
+            chunks.push(ChunkResult {
+                id: Uuid::new_v4(),
+                text: chunk_text.to_string(),
+                index: chunks.len(),
+                heading,
+                start_offset: start,
+                end_offset: end,
+                strategy,
+            });
+        };
+
+        let mut current_split = false;
+        for seg in segments {
+            let mergeable = !current_split && !seg.split;
+            current_split = seg.split;
+            current = match current.take() {
+                Some((start, _, heading)) if mergeable && seg.end - start <= self.chunk_size => {
+                    Some((start, seg.end, heading))
+                }
+                Some(done) => {
+                    flush(done, &mut chunks);
+                    Some((seg.start, seg.end, seg.heading))
+                }
+                None => Some((seg.start, seg.end, seg.heading)),
+            };
+        }
+        if let Some(done) = current {
+            flush(done, &mut chunks);
         }
+        chunks
     }
 
     pub fn chunk(&self, text: &str) -> Vec<ChunkResult> {
@@ -38,6 +246,7 @@ impl TextChunker {
                 heading: None,
                 start_offset: 0,
                 end_offset: text.len(),
+                strategy: ChunkStrategy::FixedSize,
             }];
         }
 
@@ -68,6 +277,7 @@ impl TextChunker {
                     heading,
                     start_offset: start,
                     end_offset: actual_end,
+                    strategy: ChunkStrategy::FixedSize,
                 });
                 index += 1;
             }
@@ -134,6 +344,165 @@ impl TextChunker {
     }
 }
 
+/// Split a markdown document into blocks: paragraphs, whole fenced code blocks,
+/// and headings merged into the block that follows them.
+fn markdown_segments(text: &str) -> Vec<Segment> {
+    let mut blocks: Vec<(Segment, bool)> = Vec::new(); // (segment, is_heading)
+    let mut heading: Option<String> = None;
+    let mut fence: Option<&str> = None;
+    let mut para_start: Option<usize> = None;
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let trimmed = line.trim();
+
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+                let start = para_start.take().unwrap_or(line_start);
+                blocks.push((Segment { start, end: offset, heading: heading.clone(), atomic: true, split: false }, false));
+            }
+            continue;
+        }
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            if let Some(start) = para_start.take() {
+                blocks.push((Segment { start, end: line_start, heading: heading.clone(), atomic: false, split: false }, false));
+            }
+            fence = Some(&trimmed[..3]);
+            para_start = Some(line_start);
+        } else if line.starts_with('#') {
+            if let Some(start) = para_start.take() {
+                blocks.push((Segment { start, end: line_start, heading: heading.clone(), atomic: false, split: false }, false));
+            }
+            heading = Some(trimmed.trim_start_matches('#').trim().to_string());
+            blocks.push((Segment { start: line_start, end: offset, heading: heading.clone(), atomic: false, split: false }, true));
+        } else if trimmed.is_empty() {
+            if let Some(start) = para_start.take() {
+                blocks.push((Segment { start, end: line_start, heading: heading.clone(), atomic: false, split: false }, false));
+            }
+        } else if para_start.is_none() {
+            para_start = Some(line_start);
+        }
+    }
+    if let Some(start) = para_start {
+        // Unterminated fence runs to end of document and stays atomic
+        blocks.push((Segment { start, end: text.len(), heading: heading.clone(), atomic: fence.is_some(), split: false }, false));
+    }
+
+    // Attach each heading to the block after it so they land in the same chunk
+    let mut segments: Vec<Segment> = Vec::with_capacity(blocks.len());
+    let mut pending_heading: Option<usize> = None;
+    for (seg, is_heading) in blocks {
+        if is_heading {
+            pending_heading.get_or_insert(seg.start);
+            continue;
+        }
+        let start = pending_heading.take().unwrap_or(seg.start);
+        segments.push(Segment { start, ..seg });
+    }
+    if let Some(start) = pending_heading {
+        segments.push(Segment { start, end: text.len(), heading: None, atomic: false, split: false });
+    }
+    segments
+}
+
+/// Split source code at top-level definitions. Comment/attribute lines directly
+/// above a definition belong to it; anything before the first one (imports,
+/// module docs) is its own segment.
+fn code_segments(text: &str) -> Vec<Segment> {
+    let mut lines: Vec<(usize, &str)> = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        lines.push((offset, line));
+        offset += line.len();
+    }
+
+    let is_annotation = |line: &str| {
+        let t = line.trim_start();
+        !t.is_empty() && ["//", "#", "@", "/*", "*", "--"].iter().any(|p| t.starts_with(p))
+    };
+
+    let mut starts: Vec<(usize, String)> = Vec::new();
+    for (i, (_, line)) in lines.iter().enumerate() {
+        if !RE_CODE_BOUNDARY.is_match(line) {
+            continue;
+        }
+        let mut first = i;
+        while first > 0 && is_annotation(lines[first - 1].1) {
+            first -= 1;
+        }
+        let signature: String = line.trim().chars().take(80).collect();
+        starts.push((lines[first].0, signature));
+    }
+
+    let mut segments = Vec::with_capacity(starts.len() + 1);
+    let first_start = starts.first().map(|(s, _)| *s).unwrap_or(text.len());
+    if first_start > 0 {
+        segments.push(Segment { start: 0, end: first_start, heading: None, atomic: false, split: false });
+    }
+    for (i, (start, signature)) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).map(|(s, _)| *s).unwrap_or(text.len());
+        if end > *start {
+            segments.push(Segment { start: *start, end, heading: Some(signature.clone()), atomic: false, split: false });
+        }
+    }
+    segments
+}
+
+/// Byte ranges covering `text[start..end]`, each at most `max_len`, split on the
+/// coarsest separator that works. Separators stay with the preceding piece.
+fn recursive_ranges(text: &str, start: usize, end: usize, max_len: usize, separators: &[&str]) -> Vec<(usize, usize)> {
+    if end - start <= max_len {
+        return vec![(start, end)];
+    }
+    let Some((sep, rest)) = separators.split_first() else {
+        // No separators left: hard split on char boundaries
+        let mut ranges = Vec::new();
+        let mut pos = start;
+        while pos < end {
+            let mut next = snap_to_char_boundary(text, (pos + max_len).min(end));
+            if next <= pos {
+                next = (pos + 1..=end).find(|&p| text.is_char_boundary(p)).unwrap_or(end);
+            }
+            ranges.push((pos, next));
+            pos = next;
+        }
+        return ranges;
+    };
+
+    let slice = &text[start..end];
+    if !slice.contains(sep) {
+        return recursive_ranges(text, start, end, max_len, rest);
+    }
+
+    // Split into pieces, then pack adjacent pieces back up to max_len
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let mut piece_start = start;
+    for (pos, _) in slice.match_indices(sep) {
+        let piece_end = start + pos + sep.len();
+        for piece in recursive_ranges(text, piece_start, piece_end, max_len, rest) {
+            push_packed(&mut ranges, piece, max_len);
+        }
+        piece_start = piece_end;
+    }
+    if piece_start < end {
+        for piece in recursive_ranges(text, piece_start, end, max_len, rest) {
+            push_packed(&mut ranges, piece, max_len);
+        }
+    }
+    ranges
+}
+
+fn push_packed(ranges: &mut Vec<(usize, usize)>, piece: (usize, usize), max_len: usize) {
+    match ranges.last_mut() {
+        Some(last) if last.1 == piece.0 && piece.1 - last.0 <= max_len => last.1 = piece.1,
+        _ => ranges.push(piece),
+    }
+}
+
 /// Snap a byte offset to the nearest valid UTF-8 char boundary (rounding down).
 /// If `pos` is already on a boundary, returns `pos` unchanged.
 /// If `pos` is beyond text length, returns `text.len()`.
@@ -163,6 +532,10 @@ pub struct ContextualChunkResult {
     pub heading: Option<String>,
     pub start_offset: usize,
     pub end_offset: usize,
+    /// Strategy that produced this chunk (`Semantic` for structured PDF sections)
+    pub strategy: ChunkStrategy,
+    /// Section-level metadata (e.g. EPUB chapter) merged into the stored chunk's metadata
+    pub metadata: HashMap<String, String>,
 }
 
 impl TextChunker {
@@ -175,7 +548,7 @@ impl TextChunker {
         doc_title: &str,
         doc_source: &str,
     ) -> Vec<ContextualChunkResult> {
-        let base_chunks = self.chunk(text);
+        let base_chunks = self.chunk_for_source(text, doc_source);
 
         // Extract first paragraph as document summary (for chunks without headings)
         let doc_summary: String = text
@@ -208,6 +581,8 @@ impl TextChunker {
                     heading: chunk.heading,
                     start_offset: chunk.start_offset,
                     end_offset: chunk.end_offset,
+                    strategy: chunk.strategy,
+                    metadata: HashMap::new(),
                 }
             })
             .collect()
@@ -225,8 +600,59 @@ impl TextChunker {
         let mut results = Vec::new();
         let mut global_index = 0usize;
 
+        // Multi-sheet workbooks get an overview chunk; each sheet is then chunked as a sub-document
+        let sheets: Vec<String> = sections
+            .iter()
+            .filter_map(|section| match section {
+                DocumentSection::Sheet { name, index, headers, rows, .. } => Some(format!(
+                    "- {}: {} rows; columns: {}",
+                    sheet_label(name.as_deref(), *index),
+                    rows.len(),
+                    (0..headers.len()).map(|i| column_name(headers, i)).collect::<Vec<_>>().join(", ")
+                )),
+                _ => None,
+            })
+            .collect();
+        if sheets.len() > 1 {
+            let body = format!("Workbook with {} sheets:\n{}", sheets.len(), sheets.join("\n"));
+            let mut metadata = HashMap::new();
+            metadata.insert("table_part".to_string(), "workbook".to_string());
+            results.push(ContextualChunkResult {
+                id: Uuid::new_v4(),
+                contextualized_text: format!("Document: \"{}\". Source: {}. Workbook overview. {}", doc_title, doc_source, body),
+                text: body.clone(),
+                index: global_index,
+                heading: Some("Workbook".to_string()),
+                start_offset: 0,
+                end_offset: body.len(),
+                strategy: ChunkStrategy::Semantic,
+                metadata,
+            });
+            global_index += 1;
+        }
+
         for section in sections {
             match section {
+                DocumentSection::Sheet { name, index, headers, rows, numeric_columns } => {
+                    let label = sheet_label(name.as_deref(), *index);
+                    let context_prefix = format!("Document: \"{}\". Source: {}. {}. ", doc_title, doc_source, label);
+                    let mut metadata = HashMap::new();
+                    metadata.insert("sheet".to_string(), label.clone());
+                    metadata.insert("sheet_index".to_string(), index.to_string());
+                    metadata.insert(
+                        "table_columns".to_string(),
+                        (0..headers.len()).map(|i| column_name(headers, i)).collect::<Vec<_>>().join(","),
+                    );
+                    metadata.insert("numeric_columns".to_string(), numeric_columns.join(","));
+                    metadata.insert("row_count".to_string(), rows.len().to_string());
+
+                    for mut sc in self.sheet_chunks(headers, rows, numeric_columns, &label, &context_prefix, metadata) {
+                        sc.index = global_index;
+                        results.push(sc);
+                        global_index += 1;
+                    }
+                }
+
                 DocumentSection::FormFields { fields, page } => {
                     let mut body = String::new();
                     for (key, value) in fields {
@@ -262,6 +688,8 @@ impl TextChunker {
                             heading: Some("Form Fields".to_string()),
                             start_offset: 0,
                             end_offset: body.len(),
+                            strategy: ChunkStrategy::Semantic,
+                            metadata: HashMap::new(),
                         });
                         global_index += 1;
                     } else {
@@ -291,6 +719,8 @@ impl TextChunker {
                                 heading: Some("Form Fields".to_string()),
                                 start_offset: 0,
                                 end_offset: chunk_text.len(),
+                                strategy: ChunkStrategy::Semantic,
+                                metadata: HashMap::new(),
                             });
                             global_index += 1;
                             chunk_start = chunk_end;
@@ -329,6 +759,8 @@ impl TextChunker {
                             heading: Some(format!("Table (Page {})", page)),
                             start_offset: 0,
                             end_offset: table_body.len(),
+                            strategy: ChunkStrategy::Semantic,
+                            metadata: HashMap::new(),
                         });
                         global_index += 1;
                     } else {
@@ -364,6 +796,8 @@ impl TextChunker {
                                 heading: Some(format!("Table (Page {})", page)),
                                 start_offset: 0,
                                 end_offset: chunk_text.len(),
+                                strategy: ChunkStrategy::Semantic,
+                                metadata: HashMap::new(),
                             });
                             global_index += 1;
                             row_start = row_end;
@@ -392,6 +826,8 @@ impl TextChunker {
                             heading: Some("Relationships".to_string()),
                             start_offset: 0,
                             end_offset: content.len(),
+                            strategy: ChunkStrategy::Semantic,
+                            metadata: HashMap::new(),
                         });
                         global_index += 1;
                     } else {
@@ -426,6 +862,26 @@ impl TextChunker {
                         global_index += 1;
                     }
                 }
+
+                DocumentSection::Chapter { title, spine_index, content } => {
+                    let content = content.trim();
+                    if content.len() < self.min_chunk_size {
+                        continue;
+                    }
+
+                    // Chunked on its own so no chunk straddles two chapters
+                    let sub_chunks = self.chunk_with_context(content, doc_title, doc_source);
+                    for mut sc in sub_chunks {
+                        sc.index = global_index;
+                        if sc.heading.is_none() {
+                            sc.heading = Some(title.clone());
+                        }
+                        sc.metadata.insert("chapter".to_string(), title.clone());
+                        sc.metadata.insert("spine_index".to_string(), spine_index.to_string());
+                        results.push(sc);
+                        global_index += 1;
+                    }
+                }
             }
         }
 
@@ -433,8 +889,282 @@ impl TextChunker {
     }
 }
 
+impl TextChunker {
+    /// A summary chunk (row count, columns, the whole table when it fits or
+    /// else its first rows) followed by row groups of up to `chunk_size`, each
+    /// row written as `header: value` pairs. Every chunk carries `metadata`.
+    fn sheet_chunks(
+        &self,
+        headers: &[String],
+        rows: &[Vec<String>],
+        numeric_columns: &[String],
+        label: &str,
+        context_prefix: &str,
+        metadata: HashMap<String, String>,
+    ) -> Vec<ContextualChunkResult> {
+        let chunk = |text: String, heading: String, part: &str, row_range: Option<String>| {
+            let mut metadata = metadata.clone();
+            metadata.insert("table_part".to_string(), part.to_string());
+            if let Some(row_range) = row_range {
+                metadata.insert("row_range".to_string(), row_range);
+            }
+            ContextualChunkResult {
+                id: Uuid::new_v4(),
+                contextualized_text: format!("{}{}", context_prefix, text),
+                end_offset: text.len(),
+                text,
+                index: 0,
+                heading: Some(heading),
+                start_offset: 0,
+                strategy: ChunkStrategy::Semantic,
+                metadata,
+            }
+        };
+
+        let columns = (0..headers.len())
+            .map(|i| {
+                let name = column_name(headers, i);
+                if numeric_columns.contains(&name) { format!("{} (numeric)", name) } else { name }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut summary = format!("{}: {} rows, {} columns.\nColumns: {}", label, rows.len(), headers.len(), columns);
+        let table = markdown_table(headers, rows);
+        if table.len() <= self.chunk_size * 2 {
+            summary.push_str(&format!("\n\n{}", table));
+        } else {
+            let preview = &rows[..rows.len().min(SHEET_PREVIEW_ROWS)];
+            summary.push_str(&format!(
+                "\n\nFirst {} of {} rows:\n{}",
+                preview.len(), rows.len(), markdown_table(headers, preview)
+            ));
+        }
+        let mut chunks = vec![chunk(summary, label.to_string(), "summary", None)];
+
+        let row_lines: Vec<String> = rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let pairs: Vec<String> = row
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, value)| !value.trim().is_empty())
+                    .map(|(col, value)| format!("{}: {}", column_name(headers, col), value.trim()))
+                    .collect();
+                format!("Row {}: {}", i + 1, pairs.join("; "))
+            })
+            .collect();
+
+        let mut start = 0;
+        while start < row_lines.len() {
+            let mut end = start + 1;
+            let mut size = row_lines[start].len();
+            while end < row_lines.len() && size + row_lines[end].len() < self.chunk_size {
+                size += row_lines[end].len() + 1;
+                end += 1;
+            }
+            chunks.push(chunk(
+                row_lines[start..end].join("\n"),
+                format!("{} rows {}-{}", label, start + 1, end),
+                "rows",
+                Some(format!("{}-{}", start + 1, end)),
+            ));
+            start = end;
+        }
+        chunks
+    }
+}
+
+/// "Sheet: <name>", or "Table" for a CSV file
+fn sheet_label(name: Option<&str>, index: usize) -> String {
+    match name {
+        Some(name) if !name.trim().is_empty() => format!("Sheet: {}", name.trim()),
+        Some(_) => format!("Sheet {}", index + 1),
+        None => "Table".to_string(),
+    }
+}
+
+/// Header of column `i`, or "Column N" when it is blank or missing
+pub(crate) fn column_name(headers: &[String], i: usize) -> String {
+    headers
+        .get(i)
+        .map(|h| h.trim())
+        .filter(|h| !h.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("Column {}", i + 1))
+}
+
+fn markdown_table(headers: &[String], rows: &[Vec<String>]) -> String {
+    let cell = |value: &str| value.replace('|', "/").replace(['\n', '\r'], " ").trim().to_string();
+    let width = headers.len().max(rows.iter().map(Vec::len).max().unwrap_or(0));
+    let line = |values: Vec<String>| format!("| {} |", values.join(" | "));
+
+    let mut lines = vec![
+        line((0..width).map(|i| cell(&column_name(headers, i))).collect()),
+        line(vec!["---".to_string(); width]),
+    ];
+    for row in rows {
+        lines.push(line((0..width).map(|i| cell(row.get(i).map_or("", String::as_str))).collect()));
+    }
+    lines.join("\n")
+}
+
 impl Default for TextChunker {
     fn default() -> Self {
         Self::new(1750, 200, 100)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_keeps_heading_with_body_and_fences_intact() {
+        let code = (0..30).map(|i| format!("let x{} = {};\n", i, i)).collect::<String>();
+        let doc = format!(
+            "# Intro\n\nSome introduction text that explains the project.\n\n## Usage\n\nRun it like this:\n\n```rust\n{}```\n\n## License\n\nMIT licensed, see LICENSE for details.\n",
+            code
+        );
+        let chunker = TextChunker::new(200, 0, 10).with_strategy(ChunkStrategy::Semantic);
+        let chunks = chunker.chunk_for_source(&doc, "README.md");
+
+        assert!(chunks.iter().all(|c| c.strategy == ChunkStrategy::Semantic));
+        // The fenced block is never split, even though it exceeds chunk_size
+        let fence_chunks: Vec<_> = chunks.iter().filter(|c| c.text.contains("```")).collect();
+        assert_eq!(fence_chunks.len(), 1);
+        assert_eq!(fence_chunks[0].text.matches("```").count(), 2);
+        // No chunk ends with a dangling heading
+        for c in &chunks {
+            let last_line = c.text.trim_end().lines().last().unwrap();
+            assert!(!last_line.starts_with('#'), "heading split from body: {:?}", c.text);
+        }
+        let license = chunks.iter().find(|c| c.text.contains("MIT licensed")).unwrap();
+        assert!(license.text.contains("## License"));
+        assert_eq!(license.heading.as_deref(), Some("License"));
+    }
+
+    #[test]
+    fn test_code_splits_on_top_level_definitions() {
+        let body = "    let value = compute();\n    println!(\"{}\", value);\n".repeat(3);
+        let src = format!(
+            "use std::fmt;\n\n/// First function\nfn first() {{\n{}}}\n\n#[derive(Debug)]\npub struct Second {{\n    a: u32,\n}}\n\npub async fn third() {{\n{}}}\n",
+            body, body
+        );
+        let chunker = TextChunker::new(180, 0, 5).with_strategy(ChunkStrategy::Semantic);
+        let chunks = chunker.chunk_for_source(&src, "src/lib.rs");
+
+        let first = chunks.iter().find(|c| c.text.contains("fn first")).unwrap();
+        assert!(first.text.starts_with("/// First function"));
+        let second = chunks.iter().find(|c| c.text.contains("struct Second")).unwrap();
+        assert!(second.text.starts_with("#[derive(Debug)]"));
+        assert!(!second.text.contains("fn first"));
+        for c in &chunks {
+            assert_eq!(&src[c.start_offset..c.end_offset], c.text);
+        }
+    }
+
+    #[test]
+    fn test_recursive_respects_chunk_size_and_covers_text() {
+        let text = "Sentence number one is here. ".repeat(40) + "\n\n" + &"Another paragraph line.\n".repeat(20);
+        let chunker = TextChunker::new(150, 0, 1).with_strategy(ChunkStrategy::Recursive);
+        let chunks = chunker.chunk_for_source(&text, "notes.txt");
+
+        assert!(chunks.iter().all(|c| c.text.len() <= 150));
+        assert!(chunks.iter().all(|c| c.strategy == ChunkStrategy::Recursive));
+        let rebuilt: String = chunks.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(rebuilt, text);
+    }
+
+    #[test]
+    fn test_default_strategy_is_fixed_size() {
+        let chunker = TextChunker::default();
+        assert_eq!(chunker.strategy(), ChunkStrategy::FixedSize);
+        let chunks = chunker.chunk_with_context(&"word ".repeat(100), "Doc", "doc.md");
+        assert!(chunks.iter().all(|c| c.strategy == ChunkStrategy::FixedSize));
+    }
+
+    #[test]
+    fn test_chapters_chunk_separately_and_carry_chapter_metadata() {
+        let sections = vec![
+            DocumentSection::Chapter {
+                title: "Getting Started".to_string(),
+                spine_index: 2,
+                content: "# Getting Started\n\nInstall the toolchain with rustup.".to_string(),
+            },
+            DocumentSection::Chapter {
+                title: "Ownership".to_string(),
+                spine_index: 3,
+                content: "Every value has exactly one owner at a time.".to_string(),
+            },
+        ];
+        let chunker = TextChunker::new(1000, 0, 10).with_strategy(ChunkStrategy::Semantic);
+        let chunks = chunker.chunk_structured(&sections, "Rust Notes", "rust.epub");
+
+        // Both chapters would fit in one chunk, but never share one
+        assert_eq!(chunks.len(), 2);
+        assert!(!chunks[0].text.contains("owner"));
+        assert_eq!(chunks[1].heading.as_deref(), Some("Ownership"));
+        assert_eq!(chunks[1].metadata["chapter"], "Ownership");
+        assert_eq!(chunks[1].metadata["spine_index"], "3");
+        assert_eq!(chunks[0].metadata["chapter"], "Getting Started");
+        assert_eq!((chunks[0].index, chunks[1].index), (0, 1));
+    }
+
+    #[test]
+    fn test_sheets_chunk_as_summary_plus_key_value_rows() {
+        let rows: Vec<Vec<String>> = (1..=4)
+            .map(|q| vec![format!("Q{}", q), (q * 1000).to_string(), "EU".to_string()])
+            .collect();
+        let sheet = |name: &str, index| DocumentSection::Sheet {
+            name: Some(name.to_string()),
+            index,
+            headers: vec!["Quarter".to_string(), "Revenue".to_string(), String::new()],
+            rows: rows.clone(),
+            numeric_columns: vec!["Revenue".to_string()],
+        };
+        let chunker = TextChunker::new(100, 0, 10);
+        let chunks = chunker.chunk_structured(&[sheet("Sales", 0), sheet("Plan", 1)], "Finance", "finance.xlsx");
+
+        assert_eq!(chunks[0].metadata["table_part"], "workbook");
+        assert!(chunks[0].text.contains("- Sheet: Plan: 4 rows; columns: Quarter, Revenue, Column 3"));
+
+        let summary = &chunks[1];
+        assert_eq!(summary.metadata["table_part"], "summary");
+        assert_eq!(summary.metadata["numeric_columns"], "Revenue");
+        assert_eq!(summary.metadata["table_columns"], "Quarter,Revenue,Column 3");
+        assert!(summary.text.starts_with("Sheet: Sales: 4 rows, 3 columns.\nColumns: Quarter, Revenue (numeric), Column 3"));
+        // Small enough to carry the whole table for charting
+        assert!(summary.text.contains("| Quarter | Revenue | Column 3 |\n| --- | --- | --- |\n| Q1 | 1000 | EU |"));
+        assert!(summary.text.ends_with("| Q4 | 4000 | EU |"));
+
+        let rows: Vec<_> = chunks.iter().filter(|c| c.metadata.get("sheet").is_some_and(|s| s == "Sheet: Sales")).skip(1).collect();
+        assert!(rows.iter().all(|c| c.text.len() <= 100 && c.metadata["table_part"] == "rows"));
+        assert_eq!(rows[0].text.lines().next(), Some("Row 1: Quarter: Q1; Revenue: 1000; Column 3: EU"));
+        assert_eq!(rows[0].metadata["row_range"], "1-2");
+        assert_eq!(rows.last().unwrap().heading.as_deref(), Some("Sheet: Sales rows 3-4"));
+        assert!(chunks.windows(2).all(|w| w[1].index == w[0].index + 1));
+    }
+
+    #[test]
+    fn test_cjk_splits_on_sentence_punctuation() {
+        let sentence = "检索增强生成先从知识库中找到相关文档，再让模型根据这些文档回答问题。";
+        let text = sentence.repeat(30);
+        let chunker = TextChunker::new(400, 50, 10);
+        let chunks = chunker.chunk_for_source(&text, "notes.txt");
+
+        assert!(chunks.len() > 1);
+        for c in &chunks {
+            // Scaled budget: 400 * 3/4 bytes
+            assert!(c.text.len() <= 300);
+            assert!(c.text.ends_with('。'), "chunk ends mid-sentence: {}", c.text);
+            assert_eq!(c.strategy, ChunkStrategy::Recursive);
+        }
+        let rebuilt: String = chunks.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(rebuilt, text);
+
+        // Latin-script text keeps the configured chunker
+        let english = chunker.chunk_for_source(&"Plain English sentence here. ".repeat(40), "notes.txt");
+        assert!(english.iter().all(|c| c.strategy == ChunkStrategy::FixedSize));
+    }
+}
@@ -0,0 +1,233 @@
+//! DOCX → markdown conversion of `word/document.xml`
+//!
+//! Paragraphs become blocks, Word heading styles (resolved through
+//! `word/styles.xml`, so localized style ids work) become `#` headings, list
+//! paragraphs become bullets and tables become markdown tables. Images,
+//! deleted revisions and field codes are skipped.
+
+use anyhow::{Context, Result};
+use roxmltree::{Document, Node};
+use std::collections::HashMap;
+
+/// Deepest heading level emitted
+const MAX_HEADING_LEVEL: usize = 6;
+
+/// Markdown for a DOCX body. `styles_xml` is `word/styles.xml`, when present.
+pub fn docx_to_markdown(document_xml: &str, styles_xml: Option<&str>) -> Result<String> {
+    let doc = Document::parse(document_xml).context("Invalid word/document.xml")?;
+    let headings = styles_xml
+        .and_then(|xml| Document::parse(xml).ok())
+        .map(|styles| heading_styles(&styles))
+        .unwrap_or_default();
+
+    let Some(body) = doc.descendants().find(|n| is_named(n, "body")) else {
+        return Ok(String::new());
+    };
+    let mut blocks = Vec::new();
+    push_blocks(body, &headings, &mut blocks);
+    Ok(blocks.join("\n\n"))
+}
+
+/// Heading level for each paragraph style id that is a heading or title
+fn heading_styles(styles: &Document) -> HashMap<String, usize> {
+    styles
+        .descendants()
+        .filter(|n| is_named(n, "style"))
+        .filter_map(|style| {
+            let id = attr(style, "styleId")?;
+            let name = child(style, "name").and_then(|n| attr(n, "val")).unwrap_or_default();
+            let outline = child(style, "pPr")
+                .and_then(|p| child(p, "outlineLvl"))
+                .and_then(|o| attr(o, "val"))
+                .and_then(|v| v.parse::<usize>().ok());
+            let level = heading_level_from_name(name)
+                .or_else(|| heading_level_from_name(id))
+                .or(outline.map(|l| l + 1))?;
+            Some((id.to_string(), level))
+        })
+        .collect()
+}
+
+/// "heading 2" / "Heading2" → 2, "Title" → 1
+fn heading_level_from_name(name: &str) -> Option<usize> {
+    let lower = name.to_ascii_lowercase().replace(' ', "");
+    if lower == "title" {
+        return Some(1);
+    }
+    lower.strip_prefix("heading")?.parse().ok().filter(|l| (1..=9).contains(l))
+}
+
+fn push_blocks(parent: Node, headings: &HashMap<String, usize>, blocks: &mut Vec<String>) {
+    for node in parent.children().filter(|n| n.is_element()) {
+        match node.tag_name().name() {
+            "p" => {
+                if let Some(block) = paragraph_block(node, headings) {
+                    blocks.push(block);
+                }
+            }
+            "tbl" => {
+                if let Some(table) = table_block(node) {
+                    blocks.push(table);
+                }
+            }
+            // Content controls and tracked insertions wrap ordinary paragraphs
+            "sdt" | "sdtContent" | "ins" | "customXml" => push_blocks(node, headings, blocks),
+            _ => {}
+        }
+    }
+}
+
+fn paragraph_block(p: Node, headings: &HashMap<String, usize>) -> Option<String> {
+    let text = paragraph_text(p);
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    let props = child(p, "pPr");
+    let style = props.and_then(|pr| child(pr, "pStyle")).and_then(|s| attr(s, "val"));
+    let level = style
+        .and_then(|s| headings.get(s).copied().or_else(|| heading_level_from_name(s)))
+        .or_else(|| {
+            props
+                .and_then(|pr| child(pr, "outlineLvl"))
+                .and_then(|o| attr(o, "val"))
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|l| *l < 9)
+                .map(|l| l + 1)
+        });
+    if let Some(level) = level {
+        // Headings are a single line
+        let heading = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        return Some(format!("{} {}", "#".repeat(level.min(MAX_HEADING_LEVEL)), heading));
+    }
+
+    let is_list = props.and_then(|pr| child(pr, "numPr")).is_some();
+    Some(if is_list { format!("- {}", text) } else { text.to_string() })
+}
+
+/// Visible text of a paragraph or cell: runs, tabs and breaks, without
+/// images, deleted text or field instructions
+fn paragraph_text(node: Node) -> String {
+    let mut text = String::new();
+    collect_text(node, &mut text);
+    text
+}
+
+fn collect_text(node: Node, out: &mut String) {
+    for n in node.children().filter(|n| n.is_element()) {
+        match n.tag_name().name() {
+            "t" => out.push_str(n.text().unwrap_or_default()),
+            "tab" => out.push('\t'),
+            "br" | "cr" => out.push('\n'),
+            "p" => {
+                if !out.is_empty() && !out.ends_with('\n') {
+                    out.push('\n');
+                }
+                collect_text(n, out);
+            }
+            "drawing" | "pict" | "object" | "del" | "delText" | "instrText" | "Fallback" | "pPr" | "rPr" => {}
+            _ => collect_text(n, out),
+        }
+    }
+}
+
+fn table_block(tbl: Node) -> Option<String> {
+    let rows: Vec<Vec<String>> = children(tbl, "tr")
+        .map(|tr| {
+            let mut cells = Vec::new();
+            for tc in children(tr, "tc") {
+                let text = paragraph_text(tc).split_whitespace().collect::<Vec<_>>().join(" ");
+                cells.push(text.replace('|', "\\|"));
+                // Merged cells still occupy their columns
+                let span = child(tc, "tcPr")
+                    .and_then(|pr| child(pr, "gridSpan"))
+                    .and_then(|s| attr(s, "val"))
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .unwrap_or(1);
+                cells.resize(cells.len() + span.saturating_sub(1), String::new());
+            }
+            cells
+        })
+        .filter(|cells| cells.iter().any(|c| !c.is_empty()))
+        .collect();
+    let columns = rows.iter().map(Vec::len).max()?;
+    if columns == 0 {
+        return None;
+    }
+
+    let line = |cells: &[String]| {
+        let padded: Vec<&str> = (0..columns).map(|i| cells.get(i).map_or("", String::as_str)).collect();
+        format!("| {} |", padded.join(" | "))
+    };
+    let mut table = vec![line(&rows[0]), format!("| {} |", vec!["---"; columns].join(" | "))];
+    table.extend(rows[1..].iter().map(|r| line(r)));
+    Some(table.join("\n"))
+}
+
+/// Element with local name `name`, ignoring the namespace prefix
+fn is_named(node: &Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| is_named(n, name))
+}
+
+fn children<'a, 'input: 'a>(node: Node<'a, 'input>, name: &'a str) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children().filter(move |n| is_named(n, name))
+}
+
+/// Attribute by local name, whatever its namespace prefix
+fn attr<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes().find(|a| a.name() == name).map(|a| a.value())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: &str = r#"xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main""#;
+
+    #[test]
+    fn test_headings_lists_and_tables() {
+        let styles = format!(
+            r#"<w:styles {W}>
+                <w:style w:type="paragraph" w:styleId="berschrift1"><w:name w:val="heading 1"/></w:style>
+                <w:style w:type="paragraph" w:styleId="Custom"><w:name w:val="Custom"/><w:pPr><w:outlineLvl w:val="1"/></w:pPr></w:style>
+            </w:styles>"#
+        );
+        let document = format!(
+            r#"<w:document {W}><w:body>
+                <w:p><w:pPr><w:pStyle w:val="berschrift1"/></w:pPr><w:r><w:t>Overview</w:t></w:r></w:p>
+                <w:p><w:r><w:t xml:space="preserve">Intro </w:t></w:r><w:r><w:drawing/></w:r><w:r><w:t>text.</w:t></w:r></w:p>
+                <w:p><w:pPr><w:pStyle w:val="Custom"/></w:pPr><w:r><w:t>Details</w:t></w:r></w:p>
+                <w:p><w:pPr><w:numPr><w:ilvl w:val="0"/></w:numPr></w:pPr><w:r><w:t>First point</w:t></w:r></w:p>
+                <w:p><w:r><w:delText>removed</w:delText></w:r></w:p>
+                <w:tbl>
+                    <w:tr><w:tc><w:p><w:r><w:t>Item</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Cost</w:t></w:r></w:p></w:tc></w:tr>
+                    <w:tr><w:tc><w:p><w:r><w:t>Licence</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>1,200</w:t></w:r></w:p></w:tc></w:tr>
+                    <w:tr><w:tc><w:tcPr><w:gridSpan w:val="2"/></w:tcPr><w:p><w:r><w:t>Total | net</w:t></w:r></w:p></w:tc></w:tr>
+                </w:tbl>
+            </w:body></w:document>"#
+        );
+
+        let md = docx_to_markdown(&document, Some(&styles)).unwrap();
+        assert_eq!(
+            md,
+            "# Overview\n\nIntro text.\n\n## Details\n\n- First point\n\n\
+             | Item | Cost |\n| --- | --- |\n| Licence | 1,200 |\n| Total \\| net |  |"
+        );
+    }
+
+    #[test]
+    fn test_heading_style_ids_without_styles_part() {
+        let document = format!(
+            r#"<w:document {W}><w:body>
+                <w:p><w:pPr><w:pStyle w:val="Heading2"/></w:pPr><w:r><w:t>Scope</w:t></w:r></w:p>
+                <w:p><w:pPr><w:pStyle w:val="Title"/></w:pPr><w:r><w:t>Annual</w:t></w:r><w:r><w:br/><w:t>Report</w:t></w:r></w:p>
+            </w:body></w:document>"#
+        );
+        assert_eq!(docx_to_markdown(&document, None).unwrap(), "## Scope\n\n# Annual Report");
+    }
+}
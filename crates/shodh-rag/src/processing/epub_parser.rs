@@ -0,0 +1,430 @@
+//! EPUB → per-chapter markdown
+//!
+//! `META-INF/container.xml` points at the package document (`content.opf`),
+//! whose spine gives the reading order. Each XHTML spine item becomes one
+//! chapter: headings become `#` headings, lists become bullets, `<pre>` blocks
+//! become fenced code and tables become markdown tables. Cover pages, nav
+//! documents, images and non-linear items are skipped.
+
+use anyhow::{anyhow, Context, Result};
+use roxmltree::{Document, Node, ParsingOptions};
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+
+/// One XHTML document from the spine
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpubChapter {
+    pub title: String,
+    /// Position in the spine (0-based), counting skipped items
+    pub spine_index: usize,
+    pub content: String,
+}
+
+struct ManifestItem {
+    href: String,
+    media_type: String,
+    properties: String,
+}
+
+/// Chapters of an EPUB in reading order; chapters without text are dropped
+pub fn read_epub<R: Read + Seek>(reader: R) -> Result<Vec<EpubChapter>> {
+    let mut archive = zip::ZipArchive::new(reader).context("EPUB is not a valid ZIP archive")?;
+
+    let container = read_entry(&mut archive, "META-INF/container.xml")?;
+    let container = Document::parse(&container).context("Invalid META-INF/container.xml")?;
+    let opf_path = container
+        .descendants()
+        .find(|n| is_named(n, "rootfile"))
+        .and_then(|n| attr(n, "full-path"))
+        .ok_or_else(|| anyhow!("container.xml names no package document"))?
+        .to_string();
+    let base_dir = opf_path.rsplit_once('/').map_or("", |(dir, _)| dir).to_string();
+
+    let opf = read_entry(&mut archive, &opf_path)?;
+    let opf = Document::parse_with_options(&opf, xml_options())
+        .with_context(|| format!("Invalid package document {}", opf_path))?;
+
+    let manifest: HashMap<&str, ManifestItem> = opf
+        .descendants()
+        .filter(|n| is_named(n, "item"))
+        .filter_map(|item| {
+            let id = attr(item, "id")?;
+            Some((id, ManifestItem {
+                href: attr(item, "href")?.to_string(),
+                media_type: attr(item, "media-type").unwrap_or_default().to_string(),
+                properties: attr(item, "properties").unwrap_or_default().to_string(),
+            }))
+        })
+        .collect();
+    // EPUB 2 names the cover through <meta name="cover"> and <guide>
+    let cover_id = opf
+        .descendants()
+        .find(|n| is_named(n, "meta") && attr(*n, "name") == Some("cover"))
+        .and_then(|n| attr(n, "content"));
+    let cover_href = opf
+        .descendants()
+        .find(|n| is_named(n, "reference") && attr(*n, "type") == Some("cover"))
+        .and_then(|n| attr(n, "href"))
+        .map(|href| href.split('#').next().unwrap_or_default());
+
+    let mut chapters = Vec::new();
+    let spine = opf.descendants().filter(|n| is_named(n, "itemref"));
+    for (spine_index, itemref) in spine.enumerate() {
+        let Some(idref) = attr(itemref, "idref") else { continue };
+        let Some(item) = manifest.get(idref) else { continue };
+        let is_xhtml = item.media_type.contains("html") || item.media_type.is_empty();
+        let is_cover = Some(idref) == cover_id
+            || Some(item.href.as_str()) == cover_href
+            || is_cover_name(idref)
+            || is_cover_name(item.href.rsplit('/').next().unwrap_or_default());
+        let is_nav = item.properties.split_whitespace().any(|p| p == "nav");
+        if !is_xhtml || is_cover || is_nav || attr(itemref, "linear") == Some("no") {
+            continue;
+        }
+
+        let path = resolve_href(&base_dir, &item.href);
+        let xhtml = match read_entry(&mut archive, &path) {
+            Ok(xhtml) => xhtml,
+            Err(e) => {
+                tracing::warn!("Skipping EPUB spine item {}: {}", path, e);
+                continue;
+            }
+        };
+        let (heading, content) = xhtml_to_markdown(&xhtml);
+        if content.trim().is_empty() {
+            continue;
+        }
+        let title = heading.unwrap_or_else(|| format!("Chapter {}", chapters.len() + 1));
+        chapters.push(EpubChapter { title, spine_index, content });
+    }
+
+    Ok(chapters)
+}
+
+/// "cover", "Cover.xhtml", "cover_page.html" — but not "discovery.xhtml"
+fn is_cover_name(name: &str) -> bool {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .next()
+        .is_some_and(|first| first.eq_ignore_ascii_case("cover"))
+}
+
+fn read_entry<R: Read + Seek>(archive: &mut zip::ZipArchive<R>, name: &str) -> Result<String> {
+    let mut entry = archive
+        .by_name(name)
+        .with_context(|| format!("EPUB is missing {}", name))?;
+    let mut text = String::new();
+    entry
+        .read_to_string(&mut text)
+        .with_context(|| format!("Failed to read {} from EPUB", name))?;
+    Ok(text)
+}
+
+fn xml_options() -> ParsingOptions {
+    ParsingOptions { allow_dtd: true, ..ParsingOptions::default() }
+}
+
+/// Zip entry path of an href relative to the package document
+fn resolve_href(base_dir: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let mut parts: Vec<&str> = base_dir.split('/').filter(|p| !p.is_empty()).collect();
+    for part in href.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            p => parts.push(p),
+        }
+    }
+    percent_decode(&parts.join("/"))
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Ok(b) = u8::from_str_radix(std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default(), 16) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Chapter title (first heading, else `<title>`) and markdown body.
+/// XHTML using entities XML doesn't know (`&nbsp;` from an HTML DTD) falls
+/// back to plain tag stripping.
+fn xhtml_to_markdown(xhtml: &str) -> (Option<String>, String) {
+    let doc = match Document::parse_with_options(xhtml, xml_options()) {
+        Ok(doc) => doc,
+        Err(e) => {
+            tracing::debug!("EPUB chapter is not well-formed XML ({}), stripping tags", e);
+            return (None, super::parser::strip_html_tags(xhtml));
+        }
+    };
+
+    let Some(body) = doc.descendants().find(|n| is_named(n, "body")) else {
+        return (None, String::new());
+    };
+    let mut blocks = Vec::new();
+    push_blocks(body, &mut blocks);
+
+    let heading = body
+        .descendants()
+        .filter(|n| n.is_element() && matches!(n.tag_name().name(), "h1" | "h2" | "h3"))
+        .map(|h| collapse_whitespace(&inline_text(h)))
+        .find(|h| !h.is_empty());
+    let title = heading.or_else(|| {
+        doc.descendants()
+            .find(|n| is_named(n, "title"))
+            .map(|t| collapse_whitespace(&inline_text(t)))
+            .filter(|t| !t.is_empty())
+    });
+    (title, blocks.join("\n\n"))
+}
+
+/// Elements whose content is never reading text
+fn is_skipped(name: &str) -> bool {
+    matches!(name, "script" | "style" | "nav" | "img" | "svg" | "image" | "audio" | "video" | "object" | "head")
+}
+
+fn push_blocks(parent: Node, blocks: &mut Vec<String>) {
+    let mut inline = String::new();
+    for node in parent.children() {
+        if node.is_text() {
+            inline.push_str(node.text().unwrap_or_default());
+            continue;
+        }
+        if !node.is_element() {
+            continue;
+        }
+        let name = node.tag_name().name();
+        if is_skipped(name) {
+            continue;
+        }
+        let block = match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                let text = collapse_whitespace(&inline_text(node));
+                (!text.is_empty()).then(|| format!("{} {}", "#".repeat(level), text))
+            }
+            "p" | "dt" | "dd" | "figcaption" | "caption" => {
+                let text = collapse_lines(&inline_text(node));
+                (!text.is_empty()).then_some(text)
+            }
+            "li" => {
+                let text = collapse_whitespace(&inline_text(node));
+                (!text.is_empty()).then(|| format!("- {}", text))
+            }
+            "pre" => {
+                let code = inline_text(node);
+                let code = code.trim_matches('\n');
+                (!code.trim().is_empty()).then(|| format!("```\n{}\n```", code))
+            }
+            "table" => table_block(node),
+            "br" => {
+                inline.push('\n');
+                None
+            }
+            _ if has_block_children(node) => {
+                flush_inline(&mut inline, blocks);
+                push_blocks(node, blocks);
+                None
+            }
+            // Inline markup (em, a, span, code) or a text-only div
+            _ => {
+                inline.push_str(&inline_text(node));
+                None
+            }
+        };
+        if let Some(block) = block {
+            flush_inline(&mut inline, blocks);
+            blocks.push(block);
+        }
+    }
+    flush_inline(&mut inline, blocks);
+}
+
+fn flush_inline(inline: &mut String, blocks: &mut Vec<String>) {
+    let text = collapse_lines(inline);
+    if !text.is_empty() {
+        blocks.push(text);
+    }
+    inline.clear();
+}
+
+fn has_block_children(node: Node) -> bool {
+    node.children().any(|c| {
+        c.is_element()
+            && matches!(
+                c.tag_name().name(),
+                "p" | "div" | "section" | "article" | "aside" | "header" | "footer" | "main"
+                    | "blockquote" | "ul" | "ol" | "li" | "dl" | "pre" | "table" | "figure"
+                    | "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+            )
+    })
+}
+
+/// Text of an element, keeping `<br>` as a newline
+fn inline_text(node: Node) -> String {
+    let mut out = String::new();
+    for n in node.children() {
+        if n.is_text() {
+            out.push_str(n.text().unwrap_or_default());
+        } else if n.is_element() {
+            match n.tag_name().name() {
+                "br" => out.push('\n'),
+                name if is_skipped(name) => {}
+                _ => out.push_str(&inline_text(n)),
+            }
+        }
+    }
+    out
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Collapse whitespace within lines, keeping explicit line breaks
+fn collapse_lines(text: &str) -> String {
+    text.lines()
+        .map(collapse_whitespace)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn table_block(table: Node) -> Option<String> {
+    let rows: Vec<Vec<String>> = table
+        .descendants()
+        .filter(|n| is_named(n, "tr"))
+        .map(|tr| {
+            tr.children()
+                .filter(|c| is_named(c, "td") || is_named(c, "th"))
+                .map(|cell| collapse_whitespace(&inline_text(cell)).replace('|', "\\|"))
+                .collect::<Vec<_>>()
+        })
+        .filter(|cells| cells.iter().any(|c| !c.is_empty()))
+        .collect();
+    let columns = rows.iter().map(Vec::len).max()?;
+    if columns == 0 {
+        return None;
+    }
+
+    let line = |cells: &[String]| {
+        let padded: Vec<&str> = (0..columns).map(|i| cells.get(i).map_or("", String::as_str)).collect();
+        format!("| {} |", padded.join(" | "))
+    };
+    let mut lines = vec![line(&rows[0]), format!("| {} |", vec!["---"; columns].join(" | "))];
+    lines.extend(rows[1..].iter().map(|r| line(r)));
+    Some(lines.join("\n"))
+}
+
+/// Element with local name `name`, ignoring the namespace prefix
+fn is_named(node: &Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+/// Attribute by local name, whatever its namespace prefix
+fn attr<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes().find(|a| a.name() == name).map(|a| a.value())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    fn chapter(title: &str, body: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml"><head><title>{title}</title></head>
+<body>{body}</body></html>"#
+        )
+    }
+
+    /// A minimal EPUB 3: mimetype, container, package, nav, cover and two chapters
+    fn minimal_epub() -> Vec<u8> {
+        let opf = r#"<?xml version="1.0" encoding="utf-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="id">urn:uuid:1</dc:identifier><dc:title>Rust Notes</dc:title><dc:language>en</dc:language>
+  </metadata>
+  <manifest>
+    <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+    <item id="cover-image" href="images/cover.jpg" media-type="image/jpeg" properties="cover-image"/>
+    <item id="cover" href="text/cover.xhtml" media-type="application/xhtml+xml"/>
+    <item id="ch1" href="text/ch%201.xhtml" media-type="application/xhtml+xml"/>
+    <item id="ch2" href="text/ch2.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine><itemref idref="cover"/><itemref idref="nav"/><itemref idref="ch2"/><itemref idref="ch1"/></spine>
+</package>"#;
+        let files = [
+            ("META-INF/container.xml", r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles>
+</container>"#.to_string()),
+            ("OEBPS/content.opf", opf.to_string()),
+            ("OEBPS/nav.xhtml", chapter("Contents", "<nav><ol><li>Ownership</li></ol></nav>")),
+            ("OEBPS/text/cover.xhtml", chapter("Cover", r#"<div><img src="../images/cover.jpg" alt="cover"/></div>"#)),
+            ("OEBPS/text/ch2.xhtml", chapter("Ch 1", "<section><h1>Getting   Started</h1><p>Install the <em>toolchain</em>.</p>\
+                <pre><code>cargo new hello\ncargo run</code></pre></section>")),
+            ("OEBPS/text/ch 1.xhtml", chapter("Ownership", "<p>Every value has an owner.</p>\
+                <ul><li>Moves</li><li>Borrows</li></ul>\
+                <table><tr><th>Kind</th><th>Copy</th></tr><tr><td>i32</td><td>yes</td></tr></table>")),
+        ];
+
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let stored = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("mimetype", stored).unwrap();
+        zip.write_all(b"application/epub+zip").unwrap();
+        for (name, content) in files {
+            zip.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_chapters_follow_spine_and_skip_cover_and_nav() {
+        let chapters = read_epub(Cursor::new(minimal_epub())).unwrap();
+        assert_eq!(chapters.len(), 2);
+
+        assert_eq!(chapters[0].title, "Getting Started");
+        assert_eq!(chapters[0].spine_index, 2);
+        assert_eq!(
+            chapters[0].content,
+            "# Getting Started\n\nInstall the toolchain.\n\n```\ncargo new hello\ncargo run\n```"
+        );
+
+        // No heading in the body: the <title> names the chapter
+        assert_eq!(chapters[1].title, "Ownership");
+        assert_eq!(chapters[1].spine_index, 3);
+        assert_eq!(
+            chapters[1].content,
+            "Every value has an owner.\n\n- Moves\n\n- Borrows\n\n| Kind | Copy |\n| --- | --- |\n| i32 | yes |"
+        );
+    }
+
+    #[test]
+    fn test_non_xml_chapter_falls_back_to_tag_stripping() {
+        let (title, text) = xhtml_to_markdown("<html><body><p>A&nbsp;B</p><p>C</body></html>");
+        assert_eq!(title, None);
+        assert!(text.contains('C'));
+    }
+
+    #[test]
+    fn test_resolve_href() {
+        assert_eq!(resolve_href("OEBPS", "text/ch%201.xhtml#s1"), "OEBPS/text/ch 1.xhtml");
+        assert_eq!(resolve_href("OEBPS/text", "../ch2.xhtml"), "OEBPS/ch2.xhtml");
+        assert_eq!(resolve_href("", "ch3.xhtml"), "ch3.xhtml");
+    }
+}
@@ -0,0 +1,64 @@
+//! Language and script detection (via `whatlang`)
+//!
+//! Documents get an ISO 639-3 `language` code in their metadata so search can
+//! filter by it, and the chunker switches to sentence-based splitting for
+//! scripts written without spaces between words.
+
+use whatlang::Script;
+
+/// Detection looks at the start of the text only; a few thousand characters
+/// are plenty and keep this cheap for large documents
+const SAMPLE_CHARS: usize = 4000;
+
+/// Below this, detection is a guess (short or mixed-language text).
+/// `Info::is_reliable` (0.9) rejects many ordinary one-line documents.
+const MIN_CONFIDENCE: f64 = 0.5;
+
+/// ISO 639-3 code of the text's language ("eng", "cmn", "jpn"), unless
+/// detection is unsure
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let info = whatlang::detect(sample(text))?;
+    (info.confidence() >= MIN_CONFIDENCE).then(|| info.lang().code())
+}
+
+/// Whether the text is mostly Chinese, Japanese or Korean
+pub fn is_cjk(text: &str) -> bool {
+    matches!(
+        whatlang::detect_script(sample(text)),
+        Some(Script::Mandarin | Script::Hiragana | Script::Katakana | Script::Hangul)
+    )
+}
+
+/// Whether a stored language code satisfies a filter value, ignoring case
+pub fn language_matches(stored: &str, wanted: &str) -> bool {
+    stored.trim().eq_ignore_ascii_case(wanted.trim())
+}
+
+fn sample(text: &str) -> &str {
+    match text.char_indices().nth(SAMPLE_CHARS) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_language_and_cjk_script() {
+        let english = "The quick brown fox jumps over the lazy dog while the farmer watches from the porch.";
+        let chinese = "检索增强生成是一种将信息检索与文本生成结合起来的方法。它先从知识库中找到相关文档，再让模型根据这些文档回答问题。";
+        let japanese = "これは日本語の文章です。検索拡張生成は、文書を検索してから回答を生成する仕組みです。";
+
+        assert_eq!(detect_language(english), Some("eng"));
+        assert_eq!(detect_language(chinese), Some("cmn"));
+        assert_eq!(detect_language(japanese), Some("jpn"));
+
+        assert!(!is_cjk(english));
+        assert!(is_cjk(chinese));
+        assert!(is_cjk(japanese));
+        assert!(is_cjk("한국어 문장은 띄어쓰기를 사용하지만 한글로 작성됩니다."));
+        assert!(language_matches("cmn", " CMN"));
+    }
+}
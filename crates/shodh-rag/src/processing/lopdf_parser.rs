@@ -6,6 +6,8 @@ use anyhow::{anyhow, Context, Result};
 use lopdf::{Document, Object};
 use std::path::Path;
 
+use super::pdf_tables::{self, PdfTable};
+
 /// Parsed PDF with structured content: pages, form fields, metadata.
 #[derive(Debug, Clone)]
 pub struct ParsedPdfDocument {
@@ -15,11 +17,13 @@ pub struct ParsedPdfDocument {
     pub form_fields: Vec<FormField>,
 }
 
-/// Single page with text and annotations.
+/// Single page with text, tables and annotations.
 #[derive(Debug, Clone)]
 pub struct ParsedPage {
     pub page_number: usize,
+    /// Page text; when tables were detected, only the text outside them
     pub text: String,
+    pub tables: Vec<PdfTable>,
     pub annotations: Vec<AnnotationEntry>,
 }
 
@@ -41,6 +45,9 @@ pub struct FormField {
 
 pub struct LoPdfParser;
 
+/// Images smaller than this (e.g. 500×500) are decoration, not scanned text
+const MIN_OCR_IMAGE_PIXELS: usize = 250_000;
+
 impl LoPdfParser {
     pub fn parse(path: &Path) -> Result<ParsedPdfDocument> {
         let doc = Document::load(path)
@@ -61,12 +68,13 @@ impl LoPdfParser {
         let mut pages = Vec::with_capacity(page_ids.len());
 
         for (i, &page_id) in page_ids.iter().enumerate() {
-            let text = Self::extract_page_text(doc, page_id).unwrap_or_default();
+            let (text, tables) = Self::extract_page_text_and_tables(doc, page_id);
             let annotations = Self::extract_page_annotations(doc, page_id).unwrap_or_default();
 
             pages.push(ParsedPage {
                 page_number: i + 1,
                 text,
+                tables,
                 annotations,
             });
         }
@@ -119,6 +127,32 @@ impl LoPdfParser {
 
     // ── Page text ─────────────────────────────────────────────────────
 
+    /// Page text plus any column-aligned tables. Table lines are left out of
+    /// the text so their numbers aren't indexed twice as flattened runs.
+    /// Positioned runs skip text they can't decode (CID-keyed fonts), so when
+    /// they hold less than the plain extraction the plain text is kept whole.
+    fn extract_page_text_and_tables(doc: &Document, page_id: (u32, u16)) -> (String, Vec<PdfTable>) {
+        let full_text = Self::extract_page_text(doc, page_id).unwrap_or_default();
+        let lines = pdf_tables::page_lines(doc, page_id);
+        let tables = pdf_tables::detect_tables(&lines);
+        if tables.is_empty() {
+            return (full_text, Vec::new());
+        }
+
+        let run_chars: usize = lines.iter().map(|line| visible_chars(&line.text())).sum();
+        if run_chars < visible_chars(&full_text) {
+            return (full_text, tables.into_iter().map(|(_, table)| table).collect());
+        }
+
+        let text = lines.iter()
+            .enumerate()
+            .filter(|(i, _)| !tables.iter().any(|(range, _)| range.contains(i)))
+            .map(|(_, line)| line.text())
+            .collect::<Vec<_>>()
+            .join("\n");
+        (text, tables.into_iter().map(|(_, table)| table).collect())
+    }
+
     fn extract_page_text(doc: &Document, page_id: (u32, u16)) -> Result<String> {
         let page = doc.get_object(page_id)?;
         let page_dict = page.as_dict().map_err(|_| anyhow!("Page is not a dict"))?;
@@ -202,6 +236,116 @@ impl LoPdfParser {
         result
     }
 
+    // ── Scanned pages ─────────────────────────────────────────────────
+
+    /// Embedded images of pages that have no text layer, for OCR. Images
+    /// come back as JPEG (DCT streams as stored) or BMP (8-bit gray/RGB
+    /// pixel data); other encodings and small images (logos, icons) are
+    /// skipped.
+    pub fn image_only_pages(path: &Path) -> Result<Vec<(usize, Vec<Vec<u8>>)>> {
+        let doc = Document::load(path)
+            .with_context(|| format!("lopdf: failed to load {}", path.display()))?;
+
+        let mut pages = Vec::new();
+        for (i, &page_id) in doc.get_pages().values().enumerate() {
+            let has_text = Self::extract_page_text(&doc, page_id)
+                .map(|t| !t.trim().is_empty())
+                .unwrap_or(false);
+            if has_text {
+                continue;
+            }
+            let images = Self::page_images(&doc, page_id);
+            if !images.is_empty() {
+                pages.push((i + 1, images));
+            }
+        }
+        Ok(pages)
+    }
+
+    fn page_images(doc: &Document, page_id: (u32, u16)) -> Vec<Vec<u8>> {
+        let (own, inherited) = doc.get_page_resources(page_id);
+        let resources = own
+            .into_iter()
+            .chain(inherited.iter().filter_map(|id| doc.get_dictionary(*id).ok()));
+
+        let mut seen = std::collections::HashSet::new();
+        let mut images = Vec::new();
+        for res in resources {
+            let xobjects = match res.get(b"XObject") {
+                Ok(Object::Reference(id)) => doc.get_dictionary(*id).ok(),
+                Ok(Object::Dictionary(dict)) => Some(dict),
+                _ => None,
+            };
+            for (_, obj) in xobjects.into_iter().flat_map(|x| x.iter()) {
+                let Ok(id) = obj.as_reference() else { continue };
+                if !seen.insert(id) {
+                    continue;
+                }
+                let Ok(Object::Stream(stream)) = doc.get_object(id) else { continue };
+                if stream.dict.get(b"Subtype").and_then(Object::as_name_str).ok() != Some("Image") {
+                    continue;
+                }
+                if let Some(encoded) = Self::encode_image(doc, stream) {
+                    images.push(encoded);
+                }
+            }
+        }
+        images
+    }
+
+    fn encode_image(doc: &Document, stream: &lopdf::Stream) -> Option<Vec<u8>> {
+        let dict = &stream.dict;
+        let width = dict.get(b"Width").and_then(Object::as_i64).ok().filter(|w| *w > 0)? as usize;
+        let height = dict.get(b"Height").and_then(Object::as_i64).ok().filter(|h| *h > 0)? as usize;
+        if width.saturating_mul(height) < MIN_OCR_IMAGE_PIXELS {
+            return None;
+        }
+
+        let filters = stream.filters().unwrap_or_default();
+        if filters.iter().any(|f| f == "DCTDecode") {
+            return (filters.len() == 1).then(|| stream.content.clone());
+        }
+        if filters.iter().any(|f| f != "FlateDecode") {
+            return None;
+        }
+        if dict.get(b"BitsPerComponent").and_then(Object::as_i64).unwrap_or(8) != 8 {
+            return None;
+        }
+        let components = Self::color_components(doc, dict.get(b"ColorSpace").ok()?)?;
+
+        let pixels = if filters.is_empty() {
+            stream.content.clone()
+        } else {
+            // `decompressed_content` refuses image streams; decode a copy without the subtype
+            let mut copy = stream.clone();
+            copy.dict.remove(b"Subtype");
+            copy.decompressed_content().ok()?
+        };
+        if pixels.len() < width.checked_mul(height)?.checked_mul(components)? {
+            return None;
+        }
+        Some(encode_bmp(width, height, components, &pixels))
+    }
+
+    /// Components per pixel for gray and RGB color spaces
+    fn color_components(doc: &Document, color_space: &Object) -> Option<usize> {
+        match color_space {
+            Object::Reference(id) => Self::color_components(doc, doc.get_object(*id).ok()?),
+            Object::Name(name) => match name.as_slice() {
+                b"DeviceGray" | b"CalGray" => Some(1),
+                b"DeviceRGB" | b"CalRGB" => Some(3),
+                _ => None,
+            },
+            Object::Array(arr) if arr.first().and_then(|o| o.as_name_str().ok()) == Some("ICCBased") => {
+                let profile = doc.get_object(arr.get(1)?.as_reference().ok()?).ok()?.as_stream().ok()?;
+                profile.dict.get(b"N").and_then(Object::as_i64).ok()
+                    .filter(|n| *n == 1 || *n == 3)
+                    .map(|n| n as usize)
+            }
+            _ => None,
+        }
+    }
+
     // ── Annotations ───────────────────────────────────────────────────
 
     fn extract_page_annotations(
@@ -382,6 +526,40 @@ impl LoPdfParser {
     }
 }
 
+/// Uncompressed 24-bit BMP from rows of 8-bit gray or RGB pixels
+fn encode_bmp(width: usize, height: usize, components: usize, pixels: &[u8]) -> Vec<u8> {
+    const HEADER_LEN: usize = 54;
+    let row_len = (width * 3 + 3) & !3;
+    let data_len = row_len * height;
+
+    let mut bmp = Vec::with_capacity(HEADER_LEN + data_len);
+    bmp.extend_from_slice(b"BM");
+    bmp.extend_from_slice(&((HEADER_LEN + data_len) as u32).to_le_bytes());
+    bmp.extend_from_slice(&0u32.to_le_bytes());
+    bmp.extend_from_slice(&(HEADER_LEN as u32).to_le_bytes());
+    bmp.extend_from_slice(&40u32.to_le_bytes());
+    bmp.extend_from_slice(&(width as i32).to_le_bytes());
+    bmp.extend_from_slice(&(height as i32).to_le_bytes());
+    bmp.extend_from_slice(&1u16.to_le_bytes());
+    bmp.extend_from_slice(&24u16.to_le_bytes());
+    // No compression; image size, resolution and palette left at defaults
+    bmp.extend_from_slice(&[0u8; 24]);
+
+    // BMP rows run bottom-up, BGR, padded to 4 bytes
+    for row in pixels.chunks_exact(width * components).take(height).rev() {
+        let start = bmp.len();
+        for px in row.chunks_exact(components) {
+            match px {
+                [gray] => bmp.extend_from_slice(&[*gray; 3]),
+                [r, g, b] => bmp.extend_from_slice(&[*b, *g, *r]),
+                _ => bmp.extend_from_slice(&[0; 3]),
+            }
+        }
+        bmp.resize(start + row_len, 0);
+    }
+    bmp
+}
+
 // ── PDF string decoding ──────────────────────────────────────────────
 
 /// Robust PDF string decoder: handles UTF-8, UTF-16BE, UTF-16LE, PDFDocEncoding.
@@ -441,6 +619,11 @@ fn clean_decoded(s: &str) -> String {
 }
 
 /// Unescape PDF string escapes (\n, \r, \t, \\, \(, \)).
+/// Characters other than whitespace
+fn visible_chars(s: &str) -> usize {
+    s.chars().filter(|c| !c.is_whitespace()).count()
+}
+
 fn unescape_pdf_string(s: &str) -> String {
     s.replace("\\n", "\n")
         .replace("\\r", "\r")
@@ -547,12 +730,158 @@ impl ParsedPdfDocument {
         self.pages.len()
     }
 
-    /// Combined text from all pages.
+    /// Combined text from all pages, tables as markdown after their page's text.
     pub fn full_text(&self) -> String {
         self.pages
             .iter()
-            .map(|p| p.text.as_str())
+            .map(|p| {
+                std::iter::once(p.text.clone())
+                    .chain(p.tables.iter().map(PdfTable::to_markdown))
+                    .filter(|t| !t.trim().is_empty())
+                    .collect::<Vec<_>>()
+                    .join("\n\n")
+            })
             .collect::<Vec<_>>()
             .join("\n\n")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::{dictionary, Stream};
+
+    /// One page per entry: (text shown on the page, whether it holds a scan-sized image)
+    fn write_pdf(pages: &[(Option<&str>, bool)]) -> std::path::PathBuf {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Helvetica",
+        });
+        let image_id = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject", "Subtype" => "Image", "Width" => 600, "Height" => 500,
+                "ColorSpace" => "DeviceGray", "BitsPerComponent" => 8,
+            },
+            vec![200u8; 600 * 500],
+        ));
+
+        let mut kids = Vec::new();
+        for (text, scanned) in pages {
+            let mut content = String::new();
+            if *scanned {
+                content.push_str("q\n600 0 0 500 0 0 cm\n/Im1 Do\nQ\n");
+            }
+            if let Some(text) = text {
+                content.push_str(&format!("BT\n/F1 12 Tf\n72 720 Td\n({}) Tj\nET\n", text));
+            }
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content.into_bytes()));
+            let page_id = doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+                "Resources" => dictionary! {
+                    "Font" => dictionary! { "F1" => font_id },
+                    "XObject" => dictionary! { "Im1" => image_id },
+                },
+            });
+            kids.push(page_id.into());
+        }
+        doc.objects.insert(pages_id, Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        }));
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+
+        let path = std::env::temp_dir().join(format!("shodh-lopdf-{}.pdf", uuid::Uuid::new_v4()));
+        doc.save(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_image_only_pages_skip_pages_with_text() {
+        let path = write_pdf(&[(Some("Cover letter"), true), (None, true), (Some("Appendix"), false)]);
+        let pages = LoPdfParser::image_only_pages(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(pages.len(), 1);
+        let (page, images) = &pages[0];
+        assert_eq!(*page, 2);
+        let bmp = &images[0];
+        assert_eq!(&bmp[..2], b"BM");
+        assert_eq!(bmp.len(), 54 + 600 * 3 * 500);
+        assert_eq!(&bmp[54..57], &[200, 200, 200]);
+    }
+
+    #[test]
+    fn test_column_aligned_table_is_extracted() {
+        let doc = LoPdfParser::parse_bytes(include_bytes!("testdata/simple_table.pdf")).unwrap();
+        let page = &doc.pages[0];
+        assert_eq!(page.tables.len(), 1);
+        let table = &page.tables[0];
+        assert_eq!(table.headers, vec!["Region", "Q1", "Q2", "Total"]);
+        assert_eq!(table.rows.len(), 3);
+        assert_eq!(table.rows[1], vec!["South", "980", "1,010", "1,990"]);
+
+        // Table cells aren't repeated as flattened text
+        assert!(page.text.contains("Regional Sales Report 2024"));
+        assert!(page.text.contains("Figures are unaudited."));
+        assert!(!page.text.contains("1,350"));
+        assert!(doc.full_text().contains("| North | 1,200 | 1,350 | 2,550 |"));
+    }
+
+    #[test]
+    fn test_table_page_keeps_text_runs_cannot_decode() {
+        let mut content = String::new();
+        for (row, cells) in [["Region", "Q1", "Q2"], ["North", "120", "135"], ["South", "98", "101"]].iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                let (x, y) = (72 + col * 120, 700 - row * 20);
+                content.push_str(&format!("BT\n/F1 12 Tf\n{} {} Td\n({}) Tj\nET\n", x, y, cell));
+            }
+        }
+        content.push_str("BT\n/F2 12 Tf\n72 600 Td\n(Kontakt) Tj\nET\n");
+
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let helvetica = doc.add_object(dictionary! {
+            "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Helvetica",
+        });
+        let cid_font = doc.add_object(dictionary! {
+            "Type" => "Font", "Subtype" => "Type0", "BaseFont" => "NotoSans", "Encoding" => "Identity-H",
+        });
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.into_bytes()));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+            "Resources" => dictionary! { "Font" => dictionary! { "F1" => helvetica, "F2" => cid_font } },
+        });
+        doc.objects.insert(pages_id, Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => 1,
+            "Kids" => vec![page_id.into()],
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        }));
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+
+        let (text, tables) = LoPdfParser::extract_page_text_and_tables(&doc, page_id);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].rows[0], vec!["North", "120", "135"]);
+        assert!(text.contains("Kontakt"));
+    }
+
+    #[test]
+    fn test_encode_bmp_pads_rows_and_flips() {
+        // 2×2 RGB: top row red, bottom row blue
+        let pixels = [255, 0, 0, 255, 0, 0, 0, 0, 255, 0, 0, 255];
+        let bmp = encode_bmp(2, 2, 3, &pixels);
+        assert_eq!(bmp.len(), 54 + 8 * 2);
+        // First stored row is the bottom one, in BGR order
+        assert_eq!(&bmp[54..60], &[255, 0, 0, 255, 0, 0]);
+        assert_eq!(&bmp[62..65], &[0, 0, 255]);
+    }
+}
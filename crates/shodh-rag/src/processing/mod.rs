@@ -1,10 +1,15 @@
 pub mod chunker;
+pub mod docx_parser;
+pub mod epub_parser;
+pub mod language;
 pub mod lopdf_parser;
+pub mod ocr;
 pub mod parser;
+pub mod pdf_tables;
 
 #[cfg(windows)]
 pub mod windows_ocr;
 
-pub use chunker::{ChunkResult, ContextualChunkResult, TextChunker};
+pub use chunker::{ChunkResult, ChunkStrategy, ContextualChunkResult, TextChunker};
 pub use lopdf_parser::LoPdfParser;
 pub use parser::{DocumentParser, ParsedDocument};
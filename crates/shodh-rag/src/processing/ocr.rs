@@ -0,0 +1,166 @@
+//! Cross-platform OCR
+//!
+//! Windows uses Windows.Media.Ocr; everywhere else shells out to the
+//! `tesseract` CLI (found on PATH, in the usual Homebrew/system locations, or
+//! via `TESSERACT_PATH`). With no backend installed, OCR logs a warning and
+//! yields empty text so documents still index whatever else they contain.
+
+use anyhow::Result;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Recognize text in an encoded image (PNG, JPEG, BMP, TIFF)
+pub fn extract_text(image_bytes: &[u8]) -> Result<String> {
+    if !is_available() {
+        static WARNED: AtomicBool = AtomicBool::new(false);
+        if !WARNED.swap(true, Ordering::Relaxed) {
+            tracing::warn!("{}; images and scanned pages will have no text", UNAVAILABLE_HINT);
+        }
+        return Ok(String::new());
+    }
+
+    #[cfg(windows)]
+    {
+        super::windows_ocr::ocr_image_bytes(image_bytes)
+    }
+
+    #[cfg(not(windows))]
+    {
+        tesseract::recognize(image_bytes)
+    }
+}
+
+/// Whether an OCR backend is installed
+pub fn is_available() -> bool {
+    #[cfg(windows)]
+    {
+        super::windows_ocr::is_ocr_available()
+    }
+
+    #[cfg(not(windows))]
+    {
+        tesseract::binary().is_some()
+    }
+}
+
+#[cfg(windows)]
+const UNAVAILABLE_HINT: &str = "Windows OCR is not available — install an OCR language pack";
+#[cfg(not(windows))]
+const UNAVAILABLE_HINT: &str = "Tesseract not found — install it or set TESSERACT_PATH";
+
+/// OCR a PDF without a usable text layer. Windows renders every page;
+/// elsewhere the embedded images of pages without text are recognized.
+pub fn ocr_pdf(path: &Path) -> Result<String> {
+    #[cfg(windows)]
+    {
+        super::windows_ocr::ocr_pdf(path)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let pages = ocr_image_only_pages(path);
+        Ok(pages.into_iter().map(|(_, text)| text).collect::<Vec<_>>().join("\n\n"))
+    }
+}
+
+/// OCR text of the pages that are only a scanned image, by page number.
+/// Pages with a text layer are skipped, so this is cheap for born-digital PDFs.
+pub fn ocr_image_only_pages(path: &Path) -> Vec<(usize, String)> {
+    let pages = match super::lopdf_parser::LoPdfParser::image_only_pages(path) {
+        Ok(pages) => pages,
+        Err(e) => {
+            tracing::debug!("Could not scan {} for image-only pages: {}", path.display(), e);
+            return Vec::new();
+        }
+    };
+
+    let mut results = Vec::new();
+    for (page, images) in pages {
+        let mut text = String::new();
+        for image in images {
+            match extract_text(&image) {
+                Ok(t) if !t.trim().is_empty() => {
+                    if !text.is_empty() {
+                        text.push('\n');
+                    }
+                    text.push_str(t.trim());
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(page, "OCR failed on page image in {}: {}", path.display(), e),
+            }
+        }
+        if !text.is_empty() {
+            results.push((page, text));
+        }
+    }
+    if !results.is_empty() {
+        tracing::info!(pages = results.len(), "OCR recovered text from scanned pages: {}", path.display());
+    }
+    results
+}
+
+#[cfg(not(windows))]
+mod tesseract {
+    use anyhow::{anyhow, Context, Result};
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    use std::sync::OnceLock;
+
+    /// Checked after `TESSERACT_PATH`. GUI apps on macOS don't inherit the
+    /// shell PATH, hence the explicit Homebrew locations.
+    const CANDIDATES: &[&str] = &[
+        "tesseract",
+        "/opt/homebrew/bin/tesseract",
+        "/usr/local/bin/tesseract",
+        "/usr/bin/tesseract",
+    ];
+
+    pub fn binary() -> Option<&'static str> {
+        static BINARY: OnceLock<Option<String>> = OnceLock::new();
+        BINARY
+            .get_or_init(|| {
+                std::env::var("TESSERACT_PATH")
+                    .ok()
+                    .into_iter()
+                    .chain(CANDIDATES.iter().map(|c| c.to_string()))
+                    .find(|bin| {
+                        Command::new(bin)
+                            .arg("--version")
+                            .stdout(Stdio::null())
+                            .stderr(Stdio::null())
+                            .status()
+                            .is_ok_and(|s| s.success())
+                    })
+            })
+            .as_deref()
+    }
+
+    pub fn recognize(image_bytes: &[u8]) -> Result<String> {
+        let binary = binary().ok_or_else(|| anyhow!("Tesseract is not installed"))?;
+        let mut child = Command::new(binary)
+            .args(["stdin", "stdout"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to start tesseract")?;
+
+        // Written from another thread so a full stdout pipe can't deadlock us
+        let mut stdin = child.stdin.take().context("tesseract stdin unavailable")?;
+        let input = image_bytes.to_vec();
+        let writer = std::thread::spawn(move || stdin.write_all(&input));
+
+        let output = child.wait_with_output().context("Failed to run tesseract")?;
+        let written = writer.join().map_err(|_| anyhow!("tesseract input thread panicked"))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "tesseract exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        written.context("Failed to send image to tesseract")?;
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
@@ -3,6 +3,7 @@ use calamine::{open_workbook_auto, Data, Reader};
 use std::collections::HashMap;
 use std::path::Path;
 
+use crate::processing::chunker::column_name;
 use crate::types::{DocumentFormat, DocumentSection};
 
 #[derive(Debug, Clone)]
@@ -15,6 +16,7 @@ pub struct ParsedDocument {
     pub structured_sections: Vec<DocumentSection>,
 }
 
+#[derive(Debug, Clone, Default)]
 pub struct DocumentParser;
 
 impl DocumentParser {
@@ -42,6 +44,7 @@ impl DocumentParser {
             "docx" => self.parse_docx(path)?,
             "xlsx" | "xls" | "ods" | "xlsm" | "xlsb" => self.parse_spreadsheet(path)?,
             "pptx" => self.parse_pptx(path)?,
+            "epub" => self.parse_epub(path)?,
             "html" | "htm" => self.parse_html(path)?,
             "png" | "jpg" | "jpeg" | "bmp" | "tiff" | "tif" => self.parse_image(path)?,
             _ => std::fs::read_to_string(path)
@@ -60,12 +63,15 @@ impl DocumentParser {
         let structured_sections = match format {
             DocumentFormat::PDF => self.extract_pdf_structure(path, &content),
             DocumentFormat::Spreadsheet => self.extract_spreadsheet_structure(path, &mut metadata),
+            DocumentFormat::CSV => self.extract_csv_structure(&content, &mut metadata),
+            DocumentFormat::Epub => self.extract_epub_structure(path, &mut metadata),
             _ => Vec::new(),
         };
 
         if !structured_sections.is_empty() {
             let field_count = structured_sections.iter().filter(|s| matches!(s, DocumentSection::FormFields { .. })).count();
-            tracing::info!(sections = structured_sections.len(), form_field_groups = field_count, "PDF structured extraction complete");
+            let table_count = structured_sections.iter().filter(|s| matches!(s, DocumentSection::Table { .. } | DocumentSection::Sheet { .. })).count();
+            tracing::info!(sections = structured_sections.len(), form_field_groups = field_count, tables = table_count, "Structured extraction complete");
         }
 
         Ok(ParsedDocument {
@@ -114,7 +120,7 @@ impl DocumentParser {
                 let garble_score = Self::column_garble_score(&cleaned);
                 if garble_score < 0.25 {
                     // Good quality — use pdf_extract output
-                    return Ok(cleaned);
+                    return Ok(Self::with_scanned_pages(path, cleaned));
                 }
 
                 // Likely garbled columns — try OCR for better spatial layout
@@ -141,7 +147,7 @@ impl DocumentParser {
                 }
 
                 // OCR unavailable or failed — return pdf_extract output as-is
-                return Ok(cleaned);
+                return Ok(Self::with_scanned_pages(path, cleaned));
             }
         }
 
@@ -154,14 +160,12 @@ impl DocumentParser {
         }
 
         // Both failed — try OCR as last resort
-        #[cfg(windows)]
-        {
-            tracing::info!("No text in PDF, attempting Windows OCR: {}", path.display());
-            match super::windows_ocr::ocr_pdf(path) {
-                Ok(ocr_text) => return Ok(ocr_text),
-                Err(e) => {
-                    tracing::warn!("Windows OCR failed for {}: {}", path.display(), e);
-                }
+        tracing::info!("No text in PDF, attempting OCR: {}", path.display());
+        match super::ocr::ocr_pdf(path) {
+            Ok(ocr_text) if !ocr_text.trim().is_empty() => return Ok(ocr_text),
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!("OCR failed for {}: {}", path.display(), e);
             }
         }
 
@@ -171,6 +175,16 @@ impl DocumentParser {
         ))
     }
 
+    /// Append OCR text for pages that are only a scanned image, so mixed
+    /// PDFs (typed pages plus scanned attachments) lose nothing.
+    fn with_scanned_pages(path: &Path, mut text: String) -> String {
+        for (_, ocr_text) in super::ocr::ocr_image_only_pages(path) {
+            text.push_str("\n\n");
+            text.push_str(&ocr_text);
+        }
+        text
+    }
+
     /// Score how likely the extracted text is garbled from column merging.
     /// Returns 0.0 (clean) to 1.0 (heavily garbled).
     ///
@@ -257,22 +271,29 @@ impl DocumentParser {
             });
         }
 
-        // 3. Per-page text sections
+        // 3. Per-page text sections, then the tables detected on that page
         for page in &lopdf_doc.pages {
             let text = page.text.trim();
-            if text.is_empty() {
-                continue;
-            }
-            sections.push(DocumentSection::Text {
-                content: text.to_string(),
-                page: page.page_number,
-                heading: None,
-            });
-        }
-
-        // If lopdf produced no page text but we have fallback content,
-        // add it as a single text section
-        let has_text_sections = sections.iter().any(|s| matches!(s, DocumentSection::Text { .. }));
+            if !text.is_empty() {
+                sections.push(DocumentSection::Text {
+                    content: text.to_string(),
+                    page: page.page_number,
+                    heading: None,
+                });
+            }
+            for table in &page.tables {
+                sections.push(DocumentSection::Table {
+                    headers: table.headers.clone(),
+                    rows: table.rows.clone(),
+                    page: page.page_number,
+                    caption: None,
+                });
+            }
+        }
+
+        // If lopdf produced no page text or tables but we have fallback
+        // content, add it as a single text section
+        let has_text_sections = sections.iter().any(|s| matches!(s, DocumentSection::Text { .. } | DocumentSection::Table { .. }));
         if !has_text_sections && !fallback_content.trim().is_empty() {
             sections.push(DocumentSection::Text {
                 content: fallback_content.to_string(),
@@ -291,18 +312,24 @@ impl DocumentParser {
         let mut archive = zip::ZipArchive::new(file)
             .with_context(|| format!("Failed to read DOCX as ZIP: {}", path.display()))?;
 
+        use std::io::Read;
         let mut xml_content = String::new();
         {
             let mut document_xml = archive
                 .by_name("word/document.xml")
                 .with_context(|| format!("DOCX missing word/document.xml: {}", path.display()))?;
-            use std::io::Read;
             document_xml
                 .read_to_string(&mut xml_content)
                 .with_context(|| "Failed to read document.xml from DOCX")?;
         }
+        // Optional: maps localized heading style ids to heading levels
+        let styles_xml = archive.by_name("word/styles.xml").ok().and_then(|mut styles| {
+            let mut xml = String::new();
+            styles.read_to_string(&mut xml).ok().map(|_| xml)
+        });
 
-        let text = extract_docx_text(&xml_content);
+        let text = super::docx_parser::docx_to_markdown(&xml_content, styles_xml.as_deref())
+            .with_context(|| format!("Failed to parse DOCX: {}", path.display()))?;
 
         if text.is_empty() {
             return Err(anyhow::anyhow!(
@@ -314,19 +341,62 @@ impl DocumentParser {
         Ok(text)
     }
 
-    fn parse_image(&self, path: &Path) -> Result<String> {
-        #[cfg(windows)]
-        {
-            super::windows_ocr::ocr_image(path)
-        }
+    fn read_epub_chapters(&self, path: &Path) -> Result<Vec<super::epub_parser::EpubChapter>> {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open EPUB: {}", path.display()))?;
+        super::epub_parser::read_epub(std::io::BufReader::new(file))
+            .with_context(|| format!("Failed to parse EPUB: {}", path.display()))
+    }
 
-        #[cfg(not(windows))]
-        {
-            Err(anyhow::anyhow!(
-                "Image OCR not available on this platform: {}",
+    fn parse_epub(&self, path: &Path) -> Result<String> {
+        let chapters = self.read_epub_chapters(path)?;
+        if chapters.is_empty() {
+            return Err(anyhow::anyhow!(
+                "EPUB contains no extractable text: {}",
                 path.display()
-            ))
+            ));
         }
+        Ok(chapters
+            .into_iter()
+            .map(|c| c.content)
+            .collect::<Vec<_>>()
+            .join("\n\n"))
+    }
+
+    /// One `DocumentSection::Chapter` per spine chapter, so chunks keep chapter
+    /// boundaries and carry the chapter title. Records `chapter_count` in metadata.
+    fn extract_epub_structure(
+        &self,
+        path: &Path,
+        metadata: &mut HashMap<String, String>,
+    ) -> Vec<DocumentSection> {
+        let chapters = match self.read_epub_chapters(path) {
+            Ok(chapters) => chapters,
+            Err(e) => {
+                tracing::warn!("EPUB chapter extraction failed: {:#}", e);
+                return Vec::new();
+            }
+        };
+        metadata.insert("chapter_count".to_string(), chapters.len().to_string());
+        chapters
+            .into_iter()
+            .map(|c| DocumentSection::Chapter {
+                title: c.title,
+                spine_index: c.spine_index,
+                content: c.content,
+            })
+            .collect()
+    }
+
+    fn parse_image(&self, path: &Path) -> Result<String> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Failed to read image: {}", path.display()))?;
+        let text = super::ocr::extract_text(&bytes)
+            .with_context(|| format!("OCR failed for image: {}", path.display()))?;
+        if text.trim().is_empty() {
+            tracing::warn!("OCR found no text in image: {}", path.display());
+        }
+        Ok(text)
     }
 
     /// Parse Excel/ODS spreadsheet into flat text (one row per line, pipe-separated).
@@ -376,7 +446,7 @@ impl DocumentParser {
         Ok(all_text)
     }
 
-    /// Extract per-sheet `DocumentSection::Table` sections from a spreadsheet.
+    /// Extract one `DocumentSection::Sheet` per non-empty sheet of a workbook.
     /// First non-empty row of each sheet is treated as headers; remaining rows are data.
     /// Also populates metadata with sheet count and total row count.
     fn extract_spreadsheet_structure(
@@ -404,64 +474,14 @@ impl DocumentParser {
                 Err(_) => continue,
             };
 
-            if range.is_empty() {
-                continue;
-            }
-
             let all_rows: Vec<Vec<String>> = range
                 .rows()
                 .map(|row| row.iter().map(cell_to_string).collect())
-                .filter(|row: &Vec<String>| !row.iter().all(|c| c.is_empty()))
                 .collect();
-
-            if all_rows.is_empty() {
-                continue;
+            if let Some(sheet) = sheet_section(Some(sheet_name.clone()), sheet_idx, all_rows, metadata) {
+                total_rows += sheet_row_count(&sheet);
+                sections.push(sheet);
             }
-
-            // First row = headers, rest = data
-            let headers = all_rows[0].clone();
-            let data_rows: Vec<Vec<String>> = all_rows.into_iter().skip(1).collect();
-            total_rows += data_rows.len();
-
-            // Detect numeric columns for downstream chart generation
-            let numeric_cols: Vec<usize> = (0..headers.len())
-                .filter(|&col_idx| {
-                    let numeric_count = data_rows
-                        .iter()
-                        .filter(|row| {
-                            row.get(col_idx)
-                                .map(|v| !v.is_empty() && v.parse::<f64>().is_ok())
-                                .unwrap_or(false)
-                        })
-                        .count();
-                    // Column is numeric if >50% of non-empty values parse as numbers
-                    numeric_count > 0 && numeric_count * 2 >= data_rows.len()
-                })
-                .collect();
-
-            if !numeric_cols.is_empty() {
-                let numeric_headers: Vec<&str> = numeric_cols
-                    .iter()
-                    .filter_map(|&i| headers.get(i).map(|h| h.as_str()))
-                    .collect();
-                metadata.insert(
-                    format!("sheet_{}_numeric_columns", sheet_idx),
-                    numeric_headers.join(","),
-                );
-            }
-
-            let caption = if sheet_names.len() > 1 {
-                Some(sheet_name.clone())
-            } else {
-                None
-            };
-
-            sections.push(DocumentSection::Table {
-                headers,
-                rows: data_rows,
-                page: sheet_idx + 1,
-                caption,
-            });
         }
 
         metadata.insert("total_data_rows".to_string(), total_rows.to_string());
@@ -476,6 +496,21 @@ impl DocumentParser {
         sections
     }
 
+    /// A CSV file as a single `DocumentSection::Sheet`
+    fn extract_csv_structure(
+        &self,
+        content: &str,
+        metadata: &mut HashMap<String, String>,
+    ) -> Vec<DocumentSection> {
+        match sheet_section(None, 0, parse_csv(content), metadata) {
+            Some(sheet) => {
+                metadata.insert("total_data_rows".to_string(), sheet_row_count(&sheet).to_string());
+                vec![sheet]
+            }
+            None => Vec::new(),
+        }
+    }
+
     /// Parse PPTX by extracting text from each slide's XML.
     fn parse_pptx(&self, path: &Path) -> Result<String> {
         let file = std::fs::File::open(path)
@@ -557,6 +592,96 @@ impl DocumentParser {
     }
 }
 
+/// Build a `DocumentSection::Sheet` from raw rows: empty rows are dropped,
+/// the first remaining row is the header. Records the sheet's numeric
+/// columns in `metadata` as `sheet_<index>_numeric_columns`.
+fn sheet_section(
+    name: Option<String>,
+    index: usize,
+    rows: Vec<Vec<String>>,
+    metadata: &mut HashMap<String, String>,
+) -> Option<DocumentSection> {
+    let mut rows = rows.into_iter().filter(|row| !row.iter().all(|c| c.trim().is_empty()));
+    let headers = rows.next()?;
+    let rows: Vec<Vec<String>> = rows.collect();
+
+    // Column is numeric if >50% of data rows hold a number in it. Named the
+    // way the chunker names columns, so blank headers still match.
+    let width = headers.len().max(rows.iter().map(Vec::len).max().unwrap_or(0));
+    let numeric_columns: Vec<String> = (0..width)
+        .filter(|col_idx| {
+            let numeric_count = rows
+                .iter()
+                .filter(|row| row.get(*col_idx).is_some_and(|v| !v.is_empty() && v.parse::<f64>().is_ok()))
+                .count();
+            numeric_count > 0 && numeric_count * 2 >= rows.len()
+        })
+        .map(|col_idx| column_name(&headers, col_idx))
+        .collect();
+
+    if !numeric_columns.is_empty() {
+        metadata.insert(format!("sheet_{}_numeric_columns", index), numeric_columns.join(","));
+    }
+
+    Some(DocumentSection::Sheet { name, index, headers, rows, numeric_columns })
+}
+
+fn sheet_row_count(section: &DocumentSection) -> usize {
+    match section {
+        DocumentSection::Sheet { rows, .. } => rows.len(),
+        _ => 0,
+    }
+}
+
+/// Parse CSV text with RFC 4180 quoting (quoted delimiters, `""` escapes,
+/// line breaks inside quotes). The delimiter is whichever of `,`, `;` and
+/// tab occurs most in the first line.
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let text = text.trim_start_matches('\u{feff}');
+    let first_line = text.lines().next().unwrap_or("");
+    let delimiter = [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|d| first_line.matches(*d).count())
+        .unwrap_or(',');
+
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+        } else if c == '"' && field.trim().is_empty() {
+            field.clear();
+            in_quotes = true;
+        } else if c == delimiter {
+            row.push(std::mem::take(&mut field).trim().to_string());
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            row.push(std::mem::take(&mut field).trim().to_string());
+            rows.push(std::mem::take(&mut row));
+        } else {
+            field.push(c);
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field.trim().to_string());
+        rows.push(row);
+    }
+    rows
+}
+
 /// Convert a calamine cell to a clean string representation.
 fn cell_to_string(cell: &Data) -> String {
     match cell {
@@ -632,7 +757,7 @@ fn extract_pptx_slide_text(xml: &str) -> String {
 }
 
 /// Strip HTML tags and decode common entities, returning visible text content.
-fn strip_html_tags(html: &str) -> String {
+pub(super) fn strip_html_tags(html: &str) -> String {
     let mut result = String::with_capacity(html.len() / 2);
     let mut in_tag = false;
     let mut in_script = false;
@@ -787,56 +912,38 @@ fn strip_html_tags(html: &str) -> String {
     cleaned
 }
 
-/// Extract text from DOCX XML by parsing <w:t> elements within <w:p> paragraphs
-fn extract_docx_text(xml: &str) -> String {
-    let mut result = String::new();
-    let mut pos = 0;
 
-    while pos < xml.len() {
-        if let Some(p_start) = xml[pos..].find("<w:p") {
-            let abs_p_start = pos + p_start;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            let p_end = if let Some(end) = xml[abs_p_start..].find("</w:p>") {
-                abs_p_start + end + 6
-            } else {
-                xml.len()
-            };
+    #[test]
+    fn test_parse_csv_handles_quotes_and_sniffs_delimiter() {
+        let rows = parse_csv("\u{feff}Name,Note,Amount\r\n\"Smith, J\",\"said \"\"hi\"\"\nthen left\",12.5\n\nLee,,3\n");
+        assert_eq!(rows[0], vec!["Name", "Note", "Amount"]);
+        assert_eq!(rows[1], vec!["Smith, J", "said \"hi\"\nthen left", "12.5"]);
+        assert_eq!(rows[3], vec!["Lee", "", "3"]);
 
-            let paragraph = &xml[abs_p_start..p_end];
-            let mut para_text = String::new();
-            let mut t_pos = 0;
+        let rows = parse_csv("Quarter;Revenue\nQ1;1,5");
+        assert_eq!(rows, vec![vec!["Quarter", "Revenue"], vec!["Q1", "1,5"]]);
 
-            while t_pos < paragraph.len() {
-                if let Some(t_start) = paragraph[t_pos..].find("<w:t") {
-                    let abs_t_start = t_pos + t_start;
-                    if let Some(tag_end) = paragraph[abs_t_start..].find('>') {
-                        let content_start = abs_t_start + tag_end + 1;
-                        if let Some(t_end) = paragraph[content_start..].find("</w:t>") {
-                            para_text.push_str(&paragraph[content_start..content_start + t_end]);
-                            t_pos = content_start + t_end + 6;
-                        } else {
-                            t_pos = content_start;
-                        }
-                    } else {
-                        t_pos = abs_t_start + 4;
-                    }
-                } else {
-                    break;
-                }
-            }
-
-            if !para_text.is_empty() {
-                if !result.is_empty() {
-                    result.push('\n');
-                }
-                result.push_str(&para_text);
-            }
-
-            pos = p_end;
-        } else {
-            break;
-        }
+        let mut metadata = HashMap::new();
+        let Some(DocumentSection::Sheet { headers, rows, numeric_columns, .. }) =
+            sheet_section(None, 0, parse_csv("Quarter,Revenue\nQ1,100\n,\nQ2,250\n"), &mut metadata)
+        else {
+            panic!("expected a sheet");
+        };
+        assert_eq!(headers, vec!["Quarter", "Revenue"]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(numeric_columns, vec!["Revenue"]);
+        assert_eq!(metadata["sheet_0_numeric_columns"], "Revenue");
+
+        // Blank headers get the chunker's "Column N" name
+        let Some(DocumentSection::Sheet { numeric_columns, .. }) =
+            sheet_section(None, 1, parse_csv("Quarter,\nQ1,100\nQ2,250\n"), &mut metadata)
+        else {
+            panic!("expected a sheet");
+        };
+        assert_eq!(numeric_columns, vec!["Column 2"]);
     }
-
-    result
 }
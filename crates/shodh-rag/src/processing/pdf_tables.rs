@@ -0,0 +1,400 @@
+//! Table detection for PDF pages
+//!
+//! Text runs are positioned by replaying the page's content stream (CTM,
+//! text matrix, `Td`/`TD`/`Tm`/`T*`), grouped into lines by baseline and split
+//! into cells at wide horizontal gaps. Consecutive lines whose cells fall into
+//! the same column bands form a table, with the first line as its header.
+//! Run widths are estimated from the font size since glyph metrics aren't
+//! read; that separates columns reliably but can't measure text exactly.
+
+use lopdf::content::Content;
+use lopdf::{Document, Object};
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// A table needs its header plus at least this many rows
+const MIN_TABLE_ROWS: usize = 2;
+
+/// Aligned lines with longer cells than this on average are multi-column
+/// prose, not a table
+const MAX_MEAN_CELL_CHARS: usize = 30;
+
+/// Estimated glyph advance, in font sizes
+const GLYPH_WIDTH_EM: f32 = 0.5;
+
+/// Gap between runs, in font sizes, that starts a new cell
+const CELL_GAP_EM: f32 = 1.0;
+
+/// Vertical distance, in font sizes, beyond which a line can't continue a table
+const MAX_ROW_GAP_EM: f32 = 3.0;
+
+/// A table reconstructed from column-aligned text
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl PdfTable {
+    /// GitHub-flavored markdown table
+    pub fn to_markdown(&self) -> String {
+        let escape = |cell: &String| cell.replace('|', "\\|");
+        let mut md = format!("| {} |\n", self.headers.iter().map(escape).collect::<Vec<_>>().join(" | "));
+        md.push_str(&format!("| {} |", vec!["---"; self.headers.len()].join(" | ")));
+        for row in &self.rows {
+            md.push_str(&format!("\n| {} |", row.iter().map(escape).collect::<Vec<_>>().join(" | ")));
+        }
+        md
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Cell {
+    x0: f32,
+    x1: f32,
+    text: String,
+}
+
+/// One baseline of text, cells left to right
+#[derive(Debug, Clone)]
+pub(crate) struct LayoutLine {
+    y: f32,
+    size: f32,
+    cells: Vec<Cell>,
+}
+
+impl LayoutLine {
+    pub fn text(&self) -> String {
+        self.cells.iter().map(|c| c.text.as_str()).collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// Positioned lines of a page, top to bottom
+pub(crate) fn page_lines(doc: &Document, page_id: (u32, u16)) -> Vec<LayoutLine> {
+    group_lines(text_runs(doc, page_id))
+}
+
+/// Tables in `lines`, each with the range of lines it covers
+pub(crate) fn detect_tables(lines: &[LayoutLine]) -> Vec<(Range<usize>, PdfTable)> {
+    let mut tables = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        match table_at(lines, i) {
+            Some((end, table)) => {
+                tables.push((i..end, table));
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+    tables
+}
+
+fn table_at(lines: &[LayoutLine], start: usize) -> Option<(usize, PdfTable)> {
+    let header = &lines[start];
+    if header.cells.len() < 2 {
+        return None;
+    }
+    let mut bands: Vec<(f32, f32)> = header.cells.iter().map(|c| (c.x0, c.x1)).collect();
+    let mut rows = Vec::new();
+    let mut prev_y = header.y;
+
+    for line in &lines[start + 1..] {
+        if prev_y - line.y > line.size.max(header.size) * MAX_ROW_GAP_EM {
+            break;
+        }
+        let Some(columns) = assign_columns(line, &bands, header.size * 0.5) else {
+            break;
+        };
+        let mut widened = bands.clone();
+        for (cell, &col) in line.cells.iter().zip(&columns) {
+            widened[col] = (widened[col].0.min(cell.x0), widened[col].1.max(cell.x1));
+        }
+        if widened.windows(2).any(|w| w[0].1 >= w[1].0) {
+            break;
+        }
+        bands = widened;
+
+        let mut row = vec![String::new(); bands.len()];
+        for (cell, col) in line.cells.iter().zip(columns) {
+            row[col] = cell.text.clone();
+        }
+        rows.push(row);
+        prev_y = line.y;
+    }
+
+    if rows.len() < MIN_TABLE_ROWS {
+        return None;
+    }
+    let cells: Vec<usize> = header.cells.iter()
+        .map(|c| c.text.chars().count())
+        .chain(rows.iter().flatten().filter(|c| !c.is_empty()).map(|c| c.chars().count()))
+        .collect();
+    if cells.iter().sum::<usize>() / cells.len() > MAX_MEAN_CELL_CHARS {
+        return None;
+    }
+
+    let end = start + 1 + rows.len();
+    let headers = header.cells.iter().map(|c| c.text.clone()).collect();
+    Some((end, PdfTable { headers, rows }))
+}
+
+/// Column of each cell in `line`, or None if a cell falls outside the bands
+/// or straddles two of them
+fn assign_columns(line: &LayoutLine, bands: &[(f32, f32)], tolerance: f32) -> Option<Vec<usize>> {
+    if line.cells.len() < 2 || line.cells.len() > bands.len() {
+        return None;
+    }
+    let overlaps = |cell: &Cell, band: &(f32, f32)| cell.x0 <= band.1 + tolerance && cell.x1 >= band.0 - tolerance;
+
+    let mut columns = Vec::with_capacity(line.cells.len());
+    let mut next = 0;
+    for cell in &line.cells {
+        let col = (next..bands.len()).find(|&c| overlaps(cell, &bands[c]))?;
+        if bands.get(col + 1).is_some_and(|band| overlaps(cell, band)) {
+            return None;
+        }
+        columns.push(col);
+        next = col + 1;
+    }
+    Some(columns)
+}
+
+// ── Content stream replay ─────────────────────────────────────────
+
+/// Affine transform `[a b c d e f]`, applied to row vectors as in the PDF spec
+#[derive(Debug, Clone, Copy)]
+struct Matrix([f32; 6]);
+
+impl Matrix {
+    const IDENTITY: Self = Self([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    fn translate(tx: f32, ty: f32) -> Self {
+        Self([1.0, 0.0, 0.0, 1.0, tx, ty])
+    }
+
+    /// `self` followed by `other`
+    fn then(self, other: Self) -> Self {
+        let [a, b, c, d, e, f] = self.0;
+        let [oa, ob, oc, od, oe, of] = other.0;
+        Self([
+            a * oa + b * oc,
+            a * ob + b * od,
+            c * oa + d * oc,
+            c * ob + d * od,
+            e * oa + f * oc + oe,
+            e * ob + f * od + of,
+        ])
+    }
+
+    fn from_operands(nums: &[f32]) -> Option<Self> {
+        Some(Self(nums.get(..6)?.try_into().ok()?))
+    }
+}
+
+#[derive(Debug)]
+struct TextRun {
+    x0: f32,
+    x1: f32,
+    y: f32,
+    size: f32,
+    text: String,
+}
+
+fn text_runs(doc: &Document, page_id: (u32, u16)) -> Vec<TextRun> {
+    let Some(content) = doc.get_page_content(page_id).ok().and_then(|data| Content::decode(&data).ok()) else {
+        return Vec::new();
+    };
+    let encodings: BTreeMap<Vec<u8>, String> = doc.get_page_fonts(page_id)
+        .into_iter()
+        .map(|(name, font)| (name, font.get_font_encoding().to_string()))
+        .collect();
+
+    let mut runs = Vec::new();
+    let mut ctm = Matrix::IDENTITY;
+    let mut saved = Vec::new();
+    let (mut tm, mut tlm) = (Matrix::IDENTITY, Matrix::IDENTITY);
+    let (mut size, mut leading) = (0.0f32, 0.0f32);
+    let mut encoding: Option<String> = None;
+
+    for op in &content.operations {
+        let nums: Vec<f32> = op.operands.iter().filter_map(|o| o.as_float().ok()).collect();
+        match op.operator.as_str() {
+            "q" => saved.push(ctm),
+            "Q" => ctm = saved.pop().unwrap_or(ctm),
+            "cm" => {
+                if let Some(m) = Matrix::from_operands(&nums) {
+                    ctm = m.then(ctm);
+                }
+            }
+            "BT" => {
+                tm = Matrix::IDENTITY;
+                tlm = Matrix::IDENTITY;
+            }
+            "Tf" => {
+                size = nums.first().copied().unwrap_or(size);
+                encoding = op.operands.first()
+                    .and_then(|o| o.as_name().ok())
+                    .and_then(|name| encodings.get(name))
+                    .cloned();
+            }
+            "TL" => leading = nums.first().copied().unwrap_or(leading),
+            "Td" | "TD" if nums.len() == 2 => {
+                if op.operator == "TD" {
+                    leading = -nums[1];
+                }
+                tlm = Matrix::translate(nums[0], nums[1]).then(tlm);
+                tm = tlm;
+            }
+            "Tm" => {
+                if let Some(m) = Matrix::from_operands(&nums) {
+                    tlm = m;
+                    tm = m;
+                }
+            }
+            "T*" => {
+                tlm = Matrix::translate(0.0, -leading).then(tlm);
+                tm = tlm;
+            }
+            "Tj" | "'" | "\"" | "TJ" => {
+                if op.operator != "Tj" && op.operator != "TJ" {
+                    tlm = Matrix::translate(0.0, -leading).then(tlm);
+                    tm = tlm;
+                }
+                let shown: Vec<&Object> = match op.operands.last() {
+                    Some(Object::Array(items)) if op.operator == "TJ" => items.iter().collect(),
+                    Some(last) => vec![last],
+                    None => continue,
+                };
+                if let Some(run) = show_text(&shown, encoding.as_deref(), size, &mut tm, ctm) {
+                    runs.push(run);
+                }
+            }
+            _ => {}
+        }
+    }
+    runs
+}
+
+/// Advance the text matrix over `shown` and return the run it drew
+fn show_text(shown: &[&Object], encoding: Option<&str>, size: f32, tm: &mut Matrix, ctm: Matrix) -> Option<TextRun> {
+    // Two-byte CID encodings need the font's CMap, which isn't read here
+    if encoding == Some("Identity-H") || encoding == Some("Identity-V") {
+        return None;
+    }
+    let start = tm.then(ctm);
+    let mut text = String::new();
+    for item in shown {
+        let advance = match item {
+            Object::String(bytes, _) => {
+                let piece = Document::decode_text(encoding, bytes);
+                let advance = piece.chars().count() as f32 * size * GLYPH_WIDTH_EM;
+                text.push_str(&piece);
+                advance
+            }
+            // TJ adjustments are in thousandths of an em; large negative ones stand in for spaces
+            other => {
+                let adjust = other.as_float().unwrap_or(0.0) / 1000.0 * size;
+                if -adjust > size * 0.2 && !text.ends_with(' ') {
+                    text.push(' ');
+                }
+                -adjust
+            }
+        };
+        *tm = Matrix::translate(advance, 0.0).then(*tm);
+    }
+
+    let text = text.trim().to_string();
+    if text.is_empty() || text.chars().all(|c| c.is_control()) {
+        return None;
+    }
+    let end = tm.then(ctm);
+    let [_, _, c, d, x, y] = start.0;
+    let x_end = end.0[4];
+    Some(TextRun {
+        x0: x.min(x_end),
+        x1: x.max(x_end),
+        y,
+        size: size * (c * c + d * d).sqrt(),
+        text,
+    })
+}
+
+/// Group runs into lines by baseline and split each line into cells
+fn group_lines(mut runs: Vec<TextRun>) -> Vec<LayoutLine> {
+    runs.sort_by(|a, b| b.y.total_cmp(&a.y).then(a.x0.total_cmp(&b.x0)));
+
+    let mut grouped: Vec<Vec<TextRun>> = Vec::new();
+    for run in runs {
+        match grouped.last_mut() {
+            Some(line) if (line[0].y - run.y).abs() <= line[0].size.max(run.size) * 0.5 => line.push(run),
+            _ => grouped.push(vec![run]),
+        }
+    }
+
+    grouped.into_iter()
+        .map(|mut line| {
+            line.sort_by(|a, b| a.x0.total_cmp(&b.x0));
+            let size = line.iter().map(|r| r.size).fold(0.0, f32::max);
+            let y = line[0].y;
+            let mut cells: Vec<Cell> = Vec::new();
+            for run in line {
+                match cells.last_mut() {
+                    Some(cell) if run.x0 - cell.x1 < size * CELL_GAP_EM => {
+                        cell.text.push(' ');
+                        cell.text.push_str(&run.text);
+                        cell.x1 = cell.x1.max(run.x1);
+                    }
+                    _ => cells.push(Cell { x0: run.x0, x1: run.x1, text: run.text }),
+                }
+            }
+            LayoutLine { y, size, cells }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(y: f32, cells: &[(f32, &str)]) -> LayoutLine {
+        LayoutLine {
+            y,
+            size: 10.0,
+            cells: cells.iter()
+                .map(|(x, text)| Cell { x0: *x, x1: x + text.len() as f32 * 5.0, text: text.to_string() })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_aligned_lines_form_a_table() {
+        let lines = vec![
+            line(700.0, &[(72.0, "Quarterly results")]),
+            line(680.0, &[(72.0, "Region"), (200.0, "Revenue"), (300.0, "Growth")]),
+            line(666.0, &[(72.0, "North"), (210.0, "1,200"), (305.0, "4%")]),
+            line(652.0, &[(72.0, "South"), (215.0, "980"), (305.0, "-2%")]),
+            line(638.0, &[(72.0, "West"), (305.0, "n/a")]),
+            line(600.0, &[(72.0, "Figures are unaudited.")]),
+        ];
+        let tables = detect_tables(&lines);
+        assert_eq!(tables.len(), 1);
+        let (range, table) = &tables[0];
+        assert_eq!(*range, 1..5);
+        assert_eq!(table.headers, vec!["Region", "Revenue", "Growth"]);
+        assert_eq!(table.rows[1], vec!["South", "980", "-2%"]);
+        // Missing cells stay in their column
+        assert_eq!(table.rows[2], vec!["West", "", "n/a"]);
+        assert!(table.to_markdown().starts_with("| Region | Revenue | Growth |\n| --- | --- | --- |\n| North |"));
+    }
+
+    #[test]
+    fn test_two_column_prose_is_not_a_table() {
+        let left = "The committee reviewed the proposal in detail";
+        let right = "and recommended approval subject to conditions";
+        let lines: Vec<LayoutLine> = (0..4)
+            .map(|i| line(700.0 - i as f32 * 12.0, &[(72.0, left), (320.0, right)]))
+            .collect();
+        assert!(detect_tables(&lines).is_empty());
+    }
+}
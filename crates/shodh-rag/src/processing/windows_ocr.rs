@@ -5,7 +5,7 @@ use windows::core::HSTRING;
 use windows::Data::Pdf::PdfDocument;
 use windows::Graphics::Imaging::{BitmapDecoder, BitmapPixelFormat, SoftwareBitmap};
 use windows::Media::Ocr::OcrEngine;
-use windows::Storage::Streams::InMemoryRandomAccessStream;
+use windows::Storage::Streams::{DataWriter, InMemoryRandomAccessStream};
 use windows::Storage::StorageFile;
 
 /// OCR a scanned PDF by rendering each page and running Windows OCR.
@@ -89,25 +89,20 @@ pub fn ocr_pdf(path: &Path) -> Result<String> {
     Ok(all_text)
 }
 
-/// OCR an image file (PNG, JPG, BMP, TIFF) using Windows OCR API.
-pub fn ocr_image(path: &Path) -> Result<String> {
-    let abs_path = if path.is_absolute() {
-        path.to_path_buf()
-    } else {
-        std::env::current_dir()?.join(path)
-    };
-
-    let path_str = HSTRING::from(abs_path.to_string_lossy().as_ref());
-    let file = StorageFile::GetFileFromPathAsync(&path_str)
-        .context("Failed to create StorageFile async op")?
+/// OCR an encoded image (PNG, JPG, BMP, TIFF) using Windows OCR API.
+pub fn ocr_image_bytes(image_bytes: &[u8]) -> Result<String> {
+    let stream = InMemoryRandomAccessStream::new()
+        .context("Failed to create in-memory stream")?;
+    let writer = DataWriter::CreateDataWriter(&stream)
+        .context("Failed to create stream writer")?;
+    writer.WriteBytes(image_bytes).context("Failed to buffer image")?;
+    writer
+        .StoreAsync()
+        .context("Failed to create store async op")?
         .get()
-        .context("Failed to open image file for OCR")?;
-
-    let stream = file
-        .OpenReadAsync()
-        .context("Failed to create open async op")?
-        .get()
-        .context("Failed to open image stream")?;
+        .context("Failed to write image to stream")?;
+    writer.DetachStream().context("Failed to detach stream writer")?;
+    stream.Seek(0).context("Failed to seek stream")?;
 
     let decoder = BitmapDecoder::CreateAsync(&stream)
         .context("Failed to create bitmap decoder async op")?
@@ -135,16 +130,7 @@ pub fn ocr_image(path: &Path) -> Result<String> {
         .get()
         .context("OCR recognition failed")?;
 
-    let text = result.Text().context("Failed to get OCR text")?.to_string();
-
-    if text.trim().is_empty() {
-        return Err(anyhow::anyhow!(
-            "OCR produced no text for image: {}",
-            path.display()
-        ));
-    }
-
-    Ok(text)
+    Ok(result.Text().context("Failed to get OCR text")?.to_string())
 }
 
 /// Check if Windows OCR is available on this system.
@@ -4,6 +4,21 @@
 //! Industry-standard approach: keep the last N messages verbatim for immediate
 //! context, and summarize everything before into a compact representation
 //! preserving key facts, entities, and decisions.
+//!
+//! Long conversations also get persistent checkpoints: once enough turns pile
+//! up past the last checkpoint, everything but the newest turns is folded
+//! into a stored summary, which is extended at each later checkpoint.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::llm::LLMManager;
+
+/// Turns left out of a checkpoint so the latest exchange stays verbatim
+pub const CHECKPOINT_KEEP_RECENT: usize = 6;
+const CHECKPOINT_MAX_TOKENS: usize = 400;
+/// Characters of each turn shown to the LLM when summarizing
+const CHECKPOINT_TURN_CHARS: usize = 1500;
 
 /// A compressed representation of conversation history.
 pub struct CompressedHistory {
@@ -141,3 +156,95 @@ pub fn format_compressed_history(history: &CompressedHistory) -> String {
 
     result
 }
+
+/// Stored summary of a conversation's first `covered_messages` messages
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryCheckpoint {
+    pub summary: String,
+    pub covered_messages: usize,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Number of messages the next checkpoint should cover, when more than
+/// `threshold` messages have accumulated since the last one. A threshold of
+/// 0 disables checkpoints.
+pub fn checkpoint_due(
+    total_messages: usize,
+    existing: Option<&SummaryCheckpoint>,
+    threshold: usize,
+) -> Option<usize> {
+    let covered = existing.map(|c| c.covered_messages).unwrap_or(0);
+    if threshold == 0 || total_messages <= covered + threshold {
+        return None;
+    }
+    let new_covered = total_messages.saturating_sub(CHECKPOINT_KEEP_RECENT);
+    (new_covered > covered).then_some(new_covered)
+}
+
+/// Prompt asking the LLM to extend `previous` with `turns`
+pub fn checkpoint_prompt(previous: Option<&str>, turns: &[(String, String)]) -> String {
+    let transcript = turns
+        .iter()
+        .map(|(role, content)| {
+            let content: String = content.chars().take(CHECKPOINT_TURN_CHARS).collect();
+            format!("{}: {}", role, content)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let previous = previous
+        .map(|p| format!("Summary so far:\n{}\n\n", p))
+        .unwrap_or_default();
+    format!(
+        "Summarize this conversation for later reference in at most 200 words. \
+         Keep the user's goals, decisions, named entities, files and open questions; \
+         drop pleasantries. Merge with the summary so far rather than repeating it. \
+         Reply with the summary only.\n\n{}New turns:\n{}\n\nSummary:",
+        previous, transcript
+    )
+}
+
+/// Summary covering `previous` plus `turns`. Uses the LLM when available and
+/// falls back to the rule-based topic/entity summary.
+pub async fn summarize_checkpoint(
+    llm: Option<&LLMManager>,
+    previous: Option<&str>,
+    turns: &[(String, String)],
+) -> String {
+    if let Some(llm) = llm {
+        match llm.generate_custom(&checkpoint_prompt(previous, turns), CHECKPOINT_MAX_TOKENS).await {
+            Ok(summary) if !summary.trim().is_empty() => return summary.trim().to_string(),
+            Ok(_) => tracing::warn!("LLM returned an empty conversation summary, using rule-based summary"),
+            Err(e) => tracing::warn!("Conversation summary failed, using rule-based summary: {}", e),
+        }
+    }
+    let rule_based = compress_history(turns, 0).summary.unwrap_or_default();
+    match previous {
+        Some(previous) if !rule_based.is_empty() => format!("{} {}", previous, rule_based),
+        Some(previous) => previous.to_string(),
+        None => rule_based,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoints_fall_due_after_threshold_and_keep_recent_turns() {
+        assert_eq!(checkpoint_due(20, None, 20), None);
+        assert_eq!(checkpoint_due(21, None, 20), Some(21 - CHECKPOINT_KEEP_RECENT));
+        assert_eq!(checkpoint_due(100, None, 0), None);
+
+        let checkpoint = SummaryCheckpoint {
+            summary: "Discussed Q3 budget".to_string(),
+            covered_messages: 15,
+            updated_at: Utc::now(),
+        };
+        assert_eq!(checkpoint_due(35, Some(&checkpoint), 20), None);
+        assert_eq!(checkpoint_due(36, Some(&checkpoint), 20), Some(36 - CHECKPOINT_KEEP_RECENT));
+
+        let prompt = checkpoint_prompt(Some("Discussed Q3 budget"), &[("user".into(), "And Q4?".into())]);
+        assert!(prompt.contains("Summary so far:\nDiscussed Q3 budget") && prompt.contains("user: And Q4?"));
+    }
+}
@@ -17,15 +17,52 @@ use std::collections::{HashMap, HashSet};
 pub struct EvalQuery {
     /// The query text
     pub query: String,
-    /// IDs of documents that are relevant to this query.
+    /// Labels of documents that are relevant to this query: source paths,
+    /// content hashes or doc ids (see `rank_documents`).
     /// For graded relevance, use `graded_relevance` instead.
     pub relevant_ids: HashSet<String>,
-    /// Optional graded relevance: doc_id → relevance score (0.0 to 1.0).
+    /// Optional graded relevance: document label → relevance score (0.0 to 1.0).
     /// If empty, binary relevance from `relevant_ids` is used.
     #[serde(default)]
     pub graded_relevance: HashMap<String, f32>,
 }
 
+/// Metadata keys that can label a document, most stable first. Doc ids
+/// change whenever a file is re-indexed, so labels are usually the source
+/// path or the content hash.
+const DOCUMENT_LABEL_KEYS: [&str; 4] = ["source_file", "file_path", crate::indexing::CONTENT_HASH_KEY, "doc_id"];
+
+/// Collapse chunk hits (metadata and score, in rank order) to at most
+/// `depth` documents. Each document is named by whichever of its labels
+/// `query` uses, so relevance can be given as a source path, content hash
+/// or doc id; documents the query doesn't label keep their doc id.
+pub fn rank_documents<'a>(
+    query: &EvalQuery,
+    hits: impl IntoIterator<Item = (&'a HashMap<String, String>, f32)>,
+    depth: usize,
+) -> Vec<EvalResult> {
+    let labelled = |value: &String| {
+        query.relevant_ids.contains(value) || query.graded_relevance.contains_key(value)
+    };
+    let mut seen = HashSet::new();
+    let mut ranked = Vec::new();
+    for (metadata, score) in hits {
+        if ranked.len() >= depth {
+            break;
+        }
+        let labels: Vec<&String> = DOCUMENT_LABEL_KEYS.iter().filter_map(|k| metadata.get(*k)).collect();
+        let Some(document) = metadata.get("doc_id").or(labels.first().copied()) else {
+            continue;
+        };
+        if !seen.insert(document.clone()) {
+            continue;
+        }
+        let id = labels.into_iter().find(|l| labelled(l)).unwrap_or(document);
+        ranked.push(EvalResult { id: id.clone(), score });
+    }
+    ranked
+}
+
 /// A single retrieved result for evaluation.
 #[derive(Debug, Clone)]
 pub struct EvalResult {
@@ -320,6 +357,36 @@ mod tests {
             .collect()
     }
 
+    #[test]
+    fn test_rank_documents_matches_path_and_hash_labels() {
+        let chunk = |doc: &str, path: &str, hash: &str| -> HashMap<String, String> {
+            HashMap::from([
+                ("doc_id".to_string(), doc.to_string()),
+                ("source_file".to_string(), path.to_string()),
+                ("content_hash".to_string(), hash.to_string()),
+            ])
+        };
+        let (a1, a2) = (chunk("uuid-a", "/docs/a.md", "ha"), chunk("uuid-a", "/docs/a.md", "ha"));
+        let (b, c) = (chunk("uuid-b", "/docs/b.md", "hb"), chunk("uuid-c", "/docs/c.md", "hc"));
+        let query = EvalQuery {
+            query: "q".to_string(),
+            // One label by path, one by content hash
+            relevant_ids: HashSet::from(["/docs/c.md".to_string(), "ha".to_string()]),
+            graded_relevance: HashMap::new(),
+        };
+
+        let hits = [(&b, 0.9), (&a1, 0.8), (&a2, 0.7), (&c, 0.6)];
+        let ranked = rank_documents(&query, hits, 10);
+        let ids: Vec<&str> = ranked.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["uuid-b", "ha", "/docs/c.md"]);
+
+        let metrics = evaluate(&[query], &[1, 3], |_| ranked.clone());
+        assert_eq!(metrics.mrr, 0.5);
+        assert_eq!(*metrics.recall_at.get(&1).unwrap(), 0.0);
+        assert_eq!(*metrics.recall_at.get(&3).unwrap(), 1.0);
+        assert!((*metrics.precision_at.get(&3).unwrap() - 2.0 / 3.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_perfect_retrieval() {
         let eval_set = vec![EvalQuery {
@@ -24,11 +24,14 @@ pub use retrieval_decision::{
 };
 pub use context_optimizer::{build_context_for_query, ContextQueryIntent, ContextTier};
 pub use system_context::{build_system_context, build_prompt_prefix, QueryType};
-pub use structured_output::{parse_llm_response, FormField, FieldType, StructuredOutput, ChartType, ChartData, Dataset, DiagramType, SystemActionType, STRUCTURED_OUTPUT_INSTRUCTIONS};
+pub use structured_output::{parse_llm_response, extract_form, form_schema, FormField, FieldType, StructuredOutput, ChartType, ChartData, Dataset, DiagramType, SystemActionType, STRUCTURED_OUTPUT_INSTRUCTIONS};
 pub use citation_validator::{CitationValidator, SourceDocument};
 pub use form_exporter::{export_form_as_html, export_form_as_json_schema};
-pub use conversation_summarizer::{compress_history, format_compressed_history, CompressedHistory};
+pub use conversation_summarizer::{
+    checkpoint_due, compress_history, format_compressed_history, summarize_checkpoint, CompressedHistory,
+    SummaryCheckpoint,
+};
 pub use query_decomposer::{decompose_query, merge_results, DecomposedQuery, DecompositionStrategy, HasIdAndScore};
 pub use context_compressor::{compress_chunk, compress_context};
-pub use eval::{evaluate, format_report, EvalQuery, EvalResult, EvalMetrics, QueryMetrics};
+pub use eval::{evaluate, format_report, rank_documents, EvalQuery, EvalResult, EvalMetrics, QueryMetrics};
 pub use llm_router::{RouterOutput, RouterIntent, RouterTokenUsage};
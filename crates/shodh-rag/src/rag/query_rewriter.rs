@@ -5,6 +5,7 @@
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RewrittenQuery {
@@ -37,15 +38,47 @@ impl Default for ConversationContext {
     }
 }
 
+/// Words marking an entity as an organization or thing rather than a person
+const ORG_MARKERS: &[&str] = &[
+    "inc", "ltd", "llc", "llp", "corp", "corporation", "company", "co", "bank",
+    "group", "limited", "university", "institute", "foundation", "agency",
+    "department", "ministry", "trust", "fund", "services", "technologies",
+];
+
+/// Words after "it is" that make "it" a dummy subject ("is it possible to...")
+const EXPLETIVE_COMPLEMENTS: &[&str] = &[
+    "possible", "true", "ok", "okay", "necessary", "likely", "worth", "safe",
+    "fine", "better", "best", "enough", "important", "allowed", "legal",
+    "required", "mandatory", "time", "raining",
+];
+
+/// Words that can follow "her" when it is an object, not a possessive
+const NON_NOUN_FOLLOWERS: &[&str] = &[
+    "and", "or", "to", "with", "about", "for", "in", "on", "at", "from",
+    "again", "too", "please", "by", "as", "if", "when",
+];
+
+/// What a pronoun can refer to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Referent {
+    Person,
+    Group,
+    Thing,
+}
+
 /// Query rewriter that uses conversation context to expand queries
 pub struct QueryRewriter {
     /// Enable debug logging
     pub debug: bool,
+    /// Resolve pronouns and follow-ups ("the same but for X", "compare it to Y")
+    /// against the conversation, only when one referent clearly stands out.
+    /// When off, pronouns are naively replaced with the first entity.
+    pub resolve_coreferences: bool,
 }
 
 impl QueryRewriter {
     pub fn new() -> Self {
-        Self { debug: false }
+        Self { debug: false, resolve_coreferences: true }
     }
 
     pub fn with_debug(mut self, debug: bool) -> Self {
@@ -53,6 +86,11 @@ impl QueryRewriter {
         self
     }
 
+    pub fn with_coreference_resolution(mut self, enabled: bool) -> Self {
+        self.resolve_coreferences = enabled;
+        self
+    }
+
     /// Decide if query needs document retrieval (go/no-go decision)
     ///
     /// Returns true if the query is asking about documents/information that needs lookup.
@@ -277,6 +315,8 @@ Rewritten Query:"#,
     /// - "what is her salary" (after discussing anushree) → "what is anushree salary"
     /// - "tell me more" (after salary question) → "tell me more about anushree salary"
     /// - "and the PAN?" (after discussing anushree) → "what is anushree PAN"
+    /// - "the same but for Varun" (after "what is Anushree's salary") → "what is Varun's salary"
+    /// - "compare it to Q2.pdf" (after discussing Q1.pdf) → "compare Q1.pdf to Q2.pdf"
     pub fn rewrite_rule_based(
         &self,
         query: &str,
@@ -311,43 +351,53 @@ Rewritten Query:"#,
         let primary_entity = Self::find_primary_entity(context);
         let last_topic = Self::find_last_topic(context);
 
-        // 1. Resolve gendered pronouns: her/his/their → entity name
-        let gendered_pronouns = [
-            (" her ", " {entity} "), (" his ", " {entity} "),
-            (" their ", " {entity} "), (" she ", " {entity} "),
-            (" he ", " {entity} "), (" they ", " {entity} "),
-            ("her ", "{entity} "), ("his ", "{entity} "),
-        ];
+        if self.resolve_coreferences {
+            if let Some((resolved, change)) = Self::resolve_follow_up(&rewritten, context) {
+                rewritten = resolved;
+                changes.push(change);
+            } else if let Some((resolved, resolutions)) = Self::resolve_pronouns(&rewritten, context, &[]) {
+                rewritten = resolved;
+                changes.extend(resolutions);
+            }
+        } else {
+            // 1. Resolve gendered pronouns: her/his/their → entity name
+            let gendered_pronouns = [
+                (" her ", " {entity} "), (" his ", " {entity} "),
+                (" their ", " {entity} "), (" she ", " {entity} "),
+                (" he ", " {entity} "), (" they ", " {entity} "),
+                ("her ", "{entity} "), ("his ", "{entity} "),
+            ];
 
-        if let Some(ref entity) = primary_entity {
-            for (pronoun, replacement) in &gendered_pronouns {
-                let replacement = replacement.replace("{entity}", entity);
-                if let Some(new) = Self::case_insensitive_replace(&rewritten, pronoun, &replacement) {
-                    rewritten = new;
-                    changes.push(format!("Resolved pronoun to '{}'", entity));
-                    break; // Only replace once per query
+            if let Some(ref entity) = primary_entity {
+                for (pronoun, replacement) in &gendered_pronouns {
+                    let replacement = replacement.replace("{entity}", entity);
+                    if let Some(new) = Self::case_insensitive_replace(&rewritten, pronoun, &replacement) {
+                        rewritten = new;
+                        changes.push(format!("Resolved pronoun to '{}'", entity));
+                        break; // Only replace once per query
+                    }
                 }
             }
-        }
 
-        // 2. Resolve "it"/"this"/"that" → file name if files discussed, else entity
-        let demonstratives = [" it ", " this ", " that ", " it?", " this?", " that?"];
-        let replacement_target = if !context.files_discussed.is_empty() {
-            Some(context.files_discussed[0].clone())
-        } else {
-            primary_entity.clone()
-        };
-
-        if let Some(ref target) = replacement_target {
-            for pronoun in &demonstratives {
-                let replacement = pronoun.replace(
-                    pronoun.trim_matches(|c: char| c == ' ' || c == '?'),
-                    target,
-                );
-                if let Some(new) = Self::case_insensitive_replace(&rewritten, pronoun, &replacement) {
-                    rewritten = new;
-                    changes.push(format!("Resolved demonstrative to '{}'", target));
-                    break;
+            // 2. Resolve "it"/"this"/"that" → file name if files discussed, else entity
+            let demonstratives = [" it ", " this ", " that ", " it?", " this?", " that?"];
+            let replacement_target = if !context.files_discussed.is_empty() {
+                Some(context.files_discussed[0].clone())
+            } else {
+                primary_entity.clone()
+            };
+
+            if let Some(ref target) = replacement_target {
+                for pronoun in &demonstratives {
+                    let replacement = pronoun.replace(
+                        pronoun.trim_matches(|c: char| c == ' ' || c == '?'),
+                        target,
+                    );
+                    if let Some(new) = Self::case_insensitive_replace(&rewritten, pronoun, &replacement) {
+                        rewritten = new;
+                        changes.push(format!("Resolved demonstrative to '{}'", target));
+                        break;
+                    }
                 }
             }
         }
@@ -515,6 +565,272 @@ Rewritten Query:"#,
         None
     }
 
+    /// Resolve "the same but for X" and "compare it to Y" follow-ups
+    fn resolve_follow_up(query: &str, context: &ConversationContext) -> Option<(String, String)> {
+        Self::resolve_same_for(query, context).or_else(|| Self::resolve_comparison(query, context))
+    }
+
+    /// "the same but for X" → the previous question with its subject swapped for X.
+    /// Only when the previous question names exactly one known entity or file.
+    fn resolve_same_for(query: &str, context: &ConversationContext) -> Option<(String, String)> {
+        const PREFIXES: &[&str] = &[
+            "the same but for ", "same but for ", "the same thing for ", "same thing for ",
+            "the same for ", "same for ", "do the same for ", "same question for ",
+        ];
+        let trimmed = query.trim().trim_end_matches(['?', '.', '!']);
+        let lower = trimmed.to_lowercase();
+        let prefix = PREFIXES.iter().find(|p| lower.starts_with(*p))?;
+        let target = trimmed.get(prefix.len()..)?.trim();
+        if target.is_empty() {
+            return None;
+        }
+
+        let previous = Self::find_last_user_query(&context.recent_messages)?;
+        // The previous question may itself lean on a pronoun
+        let previous = Self::resolve_pronouns(&previous, context, &[target])
+            .map(|(resolved, _)| resolved)
+            .unwrap_or(previous);
+        let previous_lower = previous.to_lowercase();
+        if previous_lower.len() != previous.len() {
+            return None;
+        }
+
+        let target_lower = target.to_lowercase();
+        let subjects: Vec<String> = Self::dedup_candidates(
+            context.entities.iter()
+                .chain(&context.files_discussed)
+                .filter(|c| {
+                    let c = c.to_lowercase();
+                    c != target_lower && Self::find_phrase(&previous_lower, &c).is_some()
+                })
+                .cloned()
+                .collect(),
+        );
+        let [subject] = subjects.as_slice() else {
+            return None;
+        };
+
+        let start = Self::find_phrase(&previous_lower, &subject.to_lowercase())?;
+        let rewritten = format!("{}{}{}", &previous[..start], target, &previous[start + subject.len()..]);
+        Some((rewritten, format!("Resolved 'same for' follow-up by replacing '{}' with '{}'", subject, target)))
+    }
+
+    /// "compare it to Y" / "how does that compare with Y" / "it vs Y": the
+    /// pronoun becomes the most recently discussed entity or file other than Y
+    fn resolve_comparison(query: &str, context: &ConversationContext) -> Option<(String, String)> {
+        let spans = Self::word_spans(query);
+        let words: Vec<String> = spans.iter().map(|&(s, e)| query[s..e].to_lowercase()).collect();
+
+        let compare_at = words.iter()
+            .position(|w| matches!(w.as_str(), "compare" | "compared" | "comparing" | "vs" | "versus"))?;
+        let pronoun_at = std::iter::once(compare_at + 1)
+            .chain(compare_at.checked_sub(1))
+            .find(|&i| words.get(i).is_some_and(|w| matches!(w.as_str(), "it" | "that" | "this" | "them")))?;
+        let connector_at = if matches!(words[compare_at].as_str(), "vs" | "versus") && pronoun_at < compare_at {
+            compare_at
+        } else {
+            (compare_at.max(pronoun_at) + 1..words.len())
+                .find(|&i| matches!(words[i].as_str(), "to" | "with" | "against" | "and"))?
+        };
+
+        let other = query[spans[connector_at].1..].trim().trim_end_matches(['?', '.', '!']).trim().to_lowercase();
+        if other.is_empty() {
+            return None;
+        }
+
+        let candidates: Vec<String> = Self::dedup_candidates(
+            context.files_discussed.iter()
+                .chain(&context.entities)
+                .filter(|c| {
+                    let c = c.to_lowercase();
+                    Self::find_phrase(&other, &c).is_none() && Self::find_phrase(&c, &other).is_none()
+                })
+                .cloned()
+                .collect(),
+        );
+        let referent = Self::select_referent(candidates, &context.recent_messages)?;
+
+        let (start, end) = spans[pronoun_at];
+        let rewritten = format!("{}{}{}", &query[..start], referent, &query[end..]);
+        Some((rewritten, format!("Resolved comparison reference to '{}'", referent)))
+    }
+
+    /// Replace pronouns with the entity, file or topic they most likely refer
+    /// to. Each kind of pronoun is resolved only when one referent clearly
+    /// stands out and the query doesn't already name a candidate of that kind.
+    fn resolve_pronouns(
+        query: &str,
+        context: &ConversationContext,
+        exclude: &[&str],
+    ) -> Option<(String, Vec<String>)> {
+        let spans = Self::word_spans(query);
+        let words: Vec<String> = spans.iter().map(|&(s, e)| query[s..e].to_lowercase()).collect();
+        let query_lower = query.to_lowercase();
+        let mut referents: HashMap<Referent, Option<String>> = HashMap::new();
+        let mut replacements = Vec::new();
+
+        for (i, word) in words.iter().enumerate() {
+            let next = words.get(i + 1).map(String::as_str);
+            let (referent, possessive) = match word.as_str() {
+                "he" | "she" | "him" => (Referent::Person, false),
+                "his" => (Referent::Person, true),
+                "her" => (Referent::Person, next.is_some_and(|n| !NON_NOUN_FOLLOWERS.contains(&n))),
+                "they" | "them" => (Referent::Group, false),
+                "their" => (Referent::Group, true),
+                "it" if !Self::is_expletive_it(&words, i) => (Referent::Thing, false),
+                "its" => (Referent::Thing, true),
+                // Usually a determiner or conjunction; only a trailing "that" is a pronoun
+                "that" if i + 1 == words.len() => (Referent::Thing, false),
+                _ => continue,
+            };
+
+            let resolved = referents.entry(referent).or_insert_with(|| {
+                let mut candidates = Self::candidates(context, referent);
+                candidates.retain(|c| !exclude.iter().any(|x| x.eq_ignore_ascii_case(c)));
+                // The query already names one, so the pronoun most likely points at it
+                if candidates.iter().any(|c| Self::find_phrase(&query_lower, &c.to_lowercase()).is_some()) {
+                    return None;
+                }
+                let topic = context.topic.trim();
+                if candidates.is_empty() && referent == Referent::Thing && (1..=4).contains(&topic.split_whitespace().count()) {
+                    candidates.push(topic.to_string());
+                }
+                Self::select_referent(candidates, &context.recent_messages)
+            });
+
+            if let Some(name) = resolved {
+                let text = if possessive { Self::possessive(name) } else { name.clone() };
+                replacements.push((spans[i], text, name.clone()));
+            }
+        }
+
+        if replacements.is_empty() {
+            return None;
+        }
+
+        let mut rewritten = query.to_string();
+        let mut changes = Vec::new();
+        for ((start, end), text, name) in replacements.into_iter().rev() {
+            changes.push(format!("Resolved '{}' to '{}'", &query[start..end], name));
+            rewritten.replace_range(start..end, &text);
+        }
+        changes.reverse();
+        Some((rewritten, changes))
+    }
+
+    /// Entities and files a pronoun of this kind could refer to
+    fn candidates(context: &ConversationContext, referent: Referent) -> Vec<String> {
+        let candidates = match referent {
+            Referent::Person => context.entities.iter()
+                .filter(|e| Self::looks_like_person(e))
+                .cloned()
+                .collect(),
+            Referent::Group => context.entities.clone(),
+            Referent::Thing => context.files_discussed.iter()
+                .chain(context.entities.iter().filter(|e| !Self::looks_like_person(e)))
+                .cloned()
+                .collect(),
+        };
+        Self::dedup_candidates(candidates)
+    }
+
+    /// Drop repeats and names contained in a longer candidate, so "Anushree"
+    /// and "Anushree Sharma" count as one
+    fn dedup_candidates(candidates: Vec<String>) -> Vec<String> {
+        let lower: Vec<String> = candidates.iter().map(|c| c.to_lowercase()).collect();
+        let keep: Vec<bool> = lower.iter().enumerate()
+            .map(|(i, c)| {
+                !lower.iter().enumerate().any(|(j, other)| {
+                    (other.len() > c.len() && Self::find_phrase(other, c).is_some()) || (other == c && j < i)
+                })
+            })
+            .collect();
+        candidates.into_iter().zip(keep).filter_map(|(c, keep)| keep.then_some(c)).collect()
+    }
+
+    /// The candidate mentioned most recently, if it clearly stands out: either
+    /// the only candidate, or mentioned in a later message than every other
+    fn select_referent(candidates: Vec<String>, recent_messages: &[String]) -> Option<String> {
+        let mut ranked: Vec<(Option<usize>, String)> = candidates.into_iter()
+            .map(|c| (Self::last_mention(&c, recent_messages), c))
+            .collect();
+        ranked.sort_by_key(|(mention, _)| std::cmp::Reverse(*mention));
+        match ranked.as_slice() {
+            [(_, only)] => Some(only.clone()),
+            [(Some(first), top), (second, _), ..] if Some(*first) > *second => Some(top.clone()),
+            _ => None,
+        }
+    }
+
+    /// Index of the latest message mentioning `candidate`
+    fn last_mention(candidate: &str, recent_messages: &[String]) -> Option<usize> {
+        let needle = candidate.to_lowercase();
+        recent_messages.iter().rposition(|m| Self::find_phrase(&m.to_lowercase(), &needle).is_some())
+    }
+
+    /// Byte offset of `needle` in `haystack` as a whole word or phrase
+    fn find_phrase(haystack: &str, needle: &str) -> Option<usize> {
+        if needle.is_empty() {
+            return None;
+        }
+        haystack.match_indices(needle).map(|(i, _)| i).find(|&i| {
+            let before = haystack[..i].chars().next_back();
+            let after = haystack[i + needle.len()..].chars().next();
+            !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+        })
+    }
+
+    /// Byte ranges of the words in `text`; apostrophes stay inside words so
+    /// "it's" is never mistaken for "it"
+    fn word_spans(text: &str) -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+        let mut start = None;
+        for (i, c) in text.char_indices() {
+            let in_word = c.is_alphanumeric() || c == '\'' || c == '’';
+            match (in_word, start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    spans.push((s, i));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            spans.push((s, text.len()));
+        }
+        spans
+    }
+
+    /// "it" as a dummy subject: "is it possible", "it is worth", "it would be better"
+    fn is_expletive_it(words: &[String], i: usize) -> bool {
+        let complement = |j: usize| words.get(j).is_some_and(|w| EXPLETIVE_COMPLEMENTS.contains(&w.as_str()));
+        let copula = |j: usize| words.get(j).is_some_and(|w| matches!(w.as_str(), "is" | "was" | "would" | "will" | "be"));
+        (copula(i + 1) && (complement(i + 2) || (copula(i + 2) && complement(i + 3))))
+            || (i > 0 && copula(i - 1) && complement(i + 1))
+    }
+
+    /// Capitalized one-to-three word names without organization markers.
+    /// All-caps words ("PAN", "GST") are treated as things.
+    fn looks_like_person(entity: &str) -> bool {
+        let words: Vec<&str> = entity.split_whitespace().collect();
+        (1..=3).contains(&words.len())
+            && words.iter().all(|w| {
+                w.chars().next().is_some_and(char::is_uppercase)
+                    && w.chars().skip(1).any(char::is_lowercase)
+                    && w.chars().all(|c| c.is_alphabetic() || c == '-' || c == '\'')
+                    && !ORG_MARKERS.contains(&w.to_lowercase().as_str())
+            })
+    }
+
+    fn possessive(name: &str) -> String {
+        if name.ends_with('s') {
+            format!("{}'", name)
+        } else {
+            format!("{}'s", name)
+        }
+    }
+
     /// Find the last substantive user query in conversation (for bare command expansion)
     fn find_last_user_query(messages: &[String]) -> Option<String> {
         let bare_commands = [
@@ -700,6 +1016,98 @@ mod tests {
         assert!(result.rewritten_query.to_lowercase().contains("varun"));
     }
 
+    fn conversation(entities: &[&str], files: &[&str], messages: &[&str]) -> ConversationContext {
+        ConversationContext {
+            entities: entities.iter().map(|s| s.to_string()).collect(),
+            files_discussed: files.iter().map(|s| s.to_string()).collect(),
+            recent_messages: messages.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_coreference_resolves_most_recent_matching_entity() {
+        let rewriter = QueryRewriter::new();
+        let context = conversation(
+            &["Varun", "Anushree Sharma", "Acme Corp"],
+            &[],
+            &[
+                "user: who is Anushree Sharma",
+                "assistant: Anushree Sharma is a senior engineer at Acme Corp.",
+                "user: what does Varun do",
+                "assistant: Varun leads the finance team.",
+            ],
+        );
+
+        let result = rewriter.rewrite_rule_based("what about his salary?", &context);
+        assert_eq!(result.rewritten_query, "what about Varun's salary?");
+
+        let result = rewriter.rewrite_rule_based("when was it founded", &context);
+        assert_eq!(result.rewritten_query, "when was Acme Corp founded");
+
+        // Both people appear in the latest message: too ambiguous to resolve
+        let tied = conversation(&["Varun", "Anushree"], &[], &["assistant: Varun and Anushree joined in 2020."]);
+        let result = rewriter.rewrite_rule_based("what is her salary", &tied);
+        assert!(!result.used_context);
+    }
+
+    #[test]
+    fn test_coreference_leaves_unambiguous_queries_alone() {
+        let rewriter = QueryRewriter::new();
+        let context = conversation(&["Varun"], &["q1_report.pdf"], &["user: summarize q1_report.pdf for Varun"]);
+
+        for query in [
+            "is it possible to export the report as csv",
+            "which documents say that the contract was renewed",
+            "what is Varun's salary and his joining date",
+            "it's raining outside today",
+        ] {
+            let result = rewriter.rewrite_rule_based(query, &context);
+            assert_eq!(result.rewritten_query, query);
+        }
+    }
+
+    #[test]
+    fn test_same_but_for_pattern() {
+        let rewriter = QueryRewriter::new();
+        let context = conversation(
+            &["Anushree"],
+            &[],
+            &["user: what is Anushree's salary", "assistant: Anushree's salary is 12 LPA."],
+        );
+
+        let result = rewriter.rewrite_rule_based("the same but for Varun", &context);
+        assert_eq!(result.rewritten_query, "what is Varun's salary");
+
+        // The previous question used a pronoun; it is resolved before swapping
+        let context = conversation(
+            &["Anushree"],
+            &[],
+            &["user: who is Anushree", "assistant: Anushree is an engineer.", "user: what is her PAN number"],
+        );
+        let result = rewriter.rewrite_rule_based("same for Varun?", &context);
+        assert_eq!(result.rewritten_query, "what is Varun's PAN number");
+    }
+
+    #[test]
+    fn test_compare_it_to_pattern() {
+        let rewriter = QueryRewriter::new();
+        let context = conversation(
+            &[],
+            &["q1_report.pdf", "q2_report.pdf"],
+            &["user: summarize q1_report.pdf", "assistant: q1_report.pdf shows revenue growth of 12%."],
+        );
+
+        let result = rewriter.rewrite_rule_based("compare it to q2_report.pdf", &context);
+        assert_eq!(result.rewritten_query, "compare q1_report.pdf to q2_report.pdf");
+
+        let result = rewriter.rewrite_rule_based("how does that compare with q2_report.pdf?", &context);
+        assert_eq!(result.rewritten_query, "how does q1_report.pdf compare with q2_report.pdf?");
+
+        let result = rewriter.rewrite_rule_based("it vs q2_report.pdf", &context);
+        assert_eq!(result.rewritten_query, "q1_report.pdf vs q2_report.pdf");
+    }
+
     #[test]
     fn test_query_expansion_synonyms() {
         let rewriter = QueryRewriter::new();
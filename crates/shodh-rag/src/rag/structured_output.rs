@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use anyhow::{Result, Context};
 use crate::system::file_ops::{FileSystemAction, FileSystemResult};
 use crate::system::command_executor::{CommandAction, CommandResult};
+use crate::llm::LLMManager;
 
 /// Structured output types that LLM can generate
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -495,6 +496,68 @@ EXAMPLES OF INCORRECT RESPONSES (DO NOT DO THIS):
 REMEMBER: Code blocks with data > Describing that you'll provide data
 "##;
 
+/// JSON schema for a form definition, as accepted in ```form blocks
+pub fn form_schema() -> serde_json::Value {
+    let nullable_string = serde_json::json!({"type": ["string", "null"]});
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "title": {"type": "string"},
+            "description": nullable_string,
+            "fields": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"},
+                        "type": {"enum": ["text", "number", "email", "date", "select", "checkbox", "radio", "textarea", "tel", "url"]},
+                        "label": {"type": "string"},
+                        "required": {"type": "boolean"},
+                        "placeholder": nullable_string,
+                        "options": {"type": ["array", "null"], "items": {"type": "string"}},
+                        "default_value": nullable_string
+                    },
+                    "required": ["id", "type", "label", "required"]
+                }
+            }
+        },
+        "required": ["title", "fields"]
+    })
+}
+
+/// Have the LLM produce a form for `request`. Generation is constrained to
+/// `form_schema()` where the provider supports it, so fields always parse;
+/// other providers answer with a ```form block that goes through
+/// `parse_llm_response`.
+pub async fn extract_form(llm: &LLMManager, request: &str) -> Result<StructuredOutput> {
+    let prompt = format!(
+        "Design a form for the following request. Use snake_case field ids and \
+         list choices in `options` for select and radio fields.\n\nRequest: {}",
+        request
+    );
+
+    if llm.supports_json_schema() {
+        let spec: FormSpec = llm.generate_structured(&prompt, &form_schema()).await
+            .context("Form extraction failed")?;
+        return Ok(StructuredOutput::Form {
+            title: spec.title,
+            description: spec.description,
+            fields: spec.fields,
+        });
+    }
+
+    let response = llm.generate(&format!(
+        "{}\n\nAnswer with a single ```form code block containing JSON with `title`, \
+         optional `description`, and `fields` (each with id, type, label, required, \
+         and optional placeholder, options, default_value).",
+        prompt
+    )).await.context("Form extraction failed")?;
+    parse_llm_response(&response)
+        .into_iter()
+        .find(|output| matches!(output, StructuredOutput::Form { .. }))
+        .context("Model response did not contain a valid form")
+}
+
 /// Parse LLM response and extract structured outputs
 pub fn parse_llm_response(response: &str) -> Vec<StructuredOutput> {
     let mut outputs = Vec::new();
@@ -906,4 +969,20 @@ That's all."#;
             _ => panic!("Expected chart output"),
         }
     }
+
+    #[test]
+    fn test_form_schema_output_parses_as_form_spec() {
+        // Optional members come back as null from schema-constrained generation
+        let constrained = r#"{"title": "Intake", "description": null, "fields": [
+            {"id": "dob", "type": "date", "label": "Date of birth", "required": true,
+             "placeholder": null, "options": null, "default_value": null}
+        ]}"#;
+        let spec: FormSpec = serde_json::from_str(constrained).unwrap();
+        assert_eq!(spec.fields[0].id, "dob");
+        assert!(matches!(spec.fields[0].field_type, FieldType::Date));
+        assert!(spec.fields[0].options.is_none());
+
+        let grammar = crate::llm::json_grammar::json_schema_to_gbnf(&form_schema());
+        assert!(grammar.contains(r#""\"date\"" ws"#));
+    }
 }
@@ -1,28 +1,32 @@
 use anyhow::{Context, Result};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
-use std::sync::LazyLock;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
 use uuid::Uuid;
 
-use crate::config::RAGConfig;
+use crate::config::{RAGConfig, SearchConfig};
 use crate::embeddings::e5::{E5Config, E5Embeddings};
-use crate::embeddings::EmbeddingModel;
-use crate::processing::chunker::TextChunker;
+use crate::embeddings::{EmbeddingModel, MultiVectorEmbedding};
+use crate::indexing::CONTENT_HASH_KEY;
+use crate::processing::chunker::{ChunkStrategy, ContextualChunkResult, TextChunker};
 use crate::processing::parser::DocumentParser;
 use crate::reranking::CrossEncoderReranker;
 use crate::search::hybrid::{score_aware_rrf, HybridSource};
-use crate::search::TextSearch;
+use crate::search::{maxsim_score_normalized, Bm25Params, QueryCache, QueryCacheStats, TextSearch};
+use crate::space_crypto::{is_sealed, SpaceKey, SpaceKeyring, SEALED_PREFIX};
 use crate::storage::LanceStore;
 use crate::types::{
-    ChunkRecord, Citation, ComprehensiveResult, DocumentFormat, MetadataFilter, SimpleSearchResult,
+    ChunkRecord, Citation, ComprehensiveResult, DocumentFormat, DocumentSort, MetadataFilter,
+    RerankOptions, SimpleSearchResult,
 };
 
 /// Normalize a file path for consistent storage and lookup across Windows/Unix.
 /// Converts backslashes to forward slashes and lowercases on Windows so that
 /// `delete_by_source` predicates always match regardless of how the path was
 /// originally formatted.
-fn normalize_source_path(path: &Path) -> String {
+pub fn normalize_source_path(path: &Path) -> String {
     let s = path.display().to_string().replace('\\', "/");
     if cfg!(windows) { s.to_lowercase() } else { s }
 }
@@ -52,6 +56,15 @@ static RE_INVOICE_NO: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"(?i)(?:invoice|inv|bill)[\s.#:_\-]*(?:no\.?|number)?[\s.#:_\-]*([A-Z0-9/\-]+)").unwrap()
 });
 
+/// Stable name for a chunk strategy, stored in chunk metadata
+fn chunk_strategy_label(strategy: ChunkStrategy) -> &'static str {
+    match strategy {
+        ChunkStrategy::FixedSize => "fixed_size",
+        ChunkStrategy::Semantic => "semantic",
+        ChunkStrategy::Recursive => "recursive",
+    }
+}
+
 /// Extract structured fields from chunk text using regex.
 /// Returns key-value pairs to merge into chunk metadata.
 /// This runs once at ingest time — zero cost at query time.
@@ -104,17 +117,244 @@ fn extract_structured_fields(text: &str) -> HashMap<String, String> {
         }
     }
 
+    // Tables (spreadsheet sheets, PDF tables) — lets chat offer charts
+    if has_markdown_table(text) {
+        fields.insert("has_table".to_string(), "true".to_string());
+    }
+
     fields
 }
 
+/// Whether `text` holds a markdown table: a pipe row followed by a `---` row
+fn has_markdown_table(text: &str) -> bool {
+    let is_separator = |line: &str| {
+        line.starts_with('|') && line.contains("---") && line.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '))
+    };
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    lines.windows(2).any(|w| w[0].starts_with('|') && is_separator(w[1]))
+}
+
+/// A file that has been parsed and chunked but not yet embedded or stored.
+pub struct PreparedDocument {
+    pub source: String,
+    pub title: String,
+    pub space_id: String,
+    metadata: HashMap<String, String>,
+    citation: Citation,
+    chunks: Vec<ContextualChunkResult>,
+    /// Chunks came from typed sections (forms, tables); headings become `chunk_type`
+    structured: bool,
+}
+
+impl PreparedDocument {
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+}
+
+/// Parses and chunks files without touching engine state, so many files can
+/// be prepared concurrently and then handed to `RAGEngine::ingest_prepared`.
+#[derive(Clone)]
+pub struct DocumentPreparer {
+    parser: DocumentParser,
+    chunker: TextChunker,
+}
+
+impl DocumentPreparer {
+    pub fn prepare(&self, path: &Path, metadata: HashMap<String, String>) -> Result<PreparedDocument> {
+        let source = normalize_source_path(path);
+        let parsed = self.parser.parse_file(path)?;
+
+        let mut merged_metadata = parsed.metadata;
+        for (k, v) in metadata {
+            merged_metadata.insert(k, v);
+        }
+        // Ensure file_path in metadata matches the canonical source used for
+        // deletion on re-index. This prevents mismatches if the caller passes a
+        // differently-formatted path string.
+        merged_metadata.insert("file_path".to_string(), source.clone());
+        // Folder indexing hashes up front to skip unchanged files; every other
+        // path hashes here so duplicate detection sees all documents
+        if !merged_metadata.contains_key(CONTENT_HASH_KEY) {
+            let hash = crate::indexing::file_content_hash(path)
+                .with_context(|| format!("Failed to hash {}", path.display()))?;
+            merged_metadata.insert(CONTENT_HASH_KEY.to_string(), hash);
+        }
+        if let Some(language) = crate::processing::language::detect_language(&parsed.content) {
+            merged_metadata.insert("language".to_string(), language.to_string());
+        }
+
+        let title = merged_metadata
+            .get("title")
+            .cloned()
+            .unwrap_or_else(|| parsed.title.clone());
+        let space_id = merged_metadata
+            .get("space_id")
+            .cloned()
+            .unwrap_or_default();
+        let citation = Citation {
+            title: parsed.title.clone(),
+            source: source.clone(),
+            ..Citation::default()
+        };
+
+        // Use structure-aware chunking for documents with structured data (PDF forms,
+        // spreadsheet tables, relationships). Keeps related data together as atomic units
+        // instead of scattering them across naive sliding-window chunks.
+        let structured = !parsed.structured_sections.is_empty();
+        let chunks = if structured {
+            self.chunker.chunk_structured(&parsed.structured_sections, &title, &source)
+        } else {
+            // Contextual chunking: prepend document-level context to each chunk
+            self.chunker.chunk_with_context(&parsed.content, &title, &source)
+        };
+
+        Ok(PreparedDocument {
+            source,
+            title,
+            space_id,
+            metadata: merged_metadata,
+            citation,
+            chunks,
+            structured,
+        })
+    }
+}
+
 pub struct RAGEngine {
     store: LanceStore,
     text_search: TextSearch,
-    embeddings: Box<dyn EmbeddingModel>,
+    embeddings: Arc<dyn EmbeddingModel>,
     chunker: TextChunker,
     parser: DocumentParser,
     config: RAGConfig,
     reranker: Option<CrossEncoderReranker>,
+    multi_vector: Option<Box<dyn MultiVectorEmbedding>>,
+    query_cache: QueryCache,
+    /// Encrypted spaces and the keys of the unlocked ones
+    keyring: SpaceKeyring,
+}
+
+/// Candidates rescored by the MaxSim stage; each needs a document-side inference
+const MAXSIM_RERANK_DEPTH: usize = 30;
+
+/// Documents scanned by `filter_documents` before post-filtering and sorting
+const FILTER_SCAN_LIMIT: usize = 100_000;
+
+/// Candidate pool multiplier when author/year/language filters will discard results
+const POST_FILTER_POOL_FACTOR: usize = 4;
+
+/// LanceDB table that re-embedded chunks are staged in before replacing the
+/// originals
+const REEMBED_TABLE: &str = "documents_reembed";
+
+/// Progress of an interrupted re-embedding run, under the data dir
+const REEMBED_STATE_FILE: &str = "reembed_state.json";
+
+/// Spaces the app indexes into itself rather than through `SpaceManager`;
+/// never treated as orphaned
+const RESERVED_SPACE_IDS: &[&str] = &[crate::agent::calendar_indexer::CALENDAR_SPACE_ID];
+
+/// The stored vectors were produced by a model with a different dimension
+/// than the current one, so similarity scores would be meaningless
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "Indexed embeddings are {index}-dimensional but the embedding model produces {model} dimensions. \
+     Re-embed your documents to search with this model."
+)]
+pub struct EmbeddingDimensionMismatch {
+    pub index: usize,
+    pub model: usize,
+}
+
+impl EmbeddingDimensionMismatch {
+    /// Whether `err` (or anything it wraps) is a dimension mismatch
+    pub fn is_mismatch(err: &anyhow::Error) -> bool {
+        err.downcast_ref::<EmbeddingDimensionMismatch>().is_some()
+    }
+}
+
+/// Chunks covered by a re-embedding run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ReembedScope {
+    All,
+    #[serde(rename_all = "camelCase")]
+    Space { space_id: String },
+}
+
+impl ReembedScope {
+    fn predicate(&self) -> Option<String> {
+        match self {
+            Self::All => None,
+            Self::Space { space_id } => Some(format!("space_id = '{}'", space_id.replace('\'', "''"))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReembedPhase {
+    /// Embedding documents into the staging table
+    Embedding,
+    /// Replacing the original chunks with the staged ones
+    Swapping,
+    Done,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReembedProgress {
+    pub scope: ReembedScope,
+    pub phase: ReembedPhase,
+    pub documents_done: usize,
+    pub documents_total: usize,
+    pub chunks_embedded: usize,
+    /// Continued a run that was interrupted earlier
+    pub resumed: bool,
+}
+
+/// Text to embed for a stored chunk. Approximates the chunker's context
+/// prefix; the document summary it falls back to isn't stored.
+fn reembed_text(chunk: &ChunkRecord) -> String {
+    if chunk.heading.is_empty() {
+        format!("Document: \"{}\". Source: {}. {}", chunk.title, chunk.source, chunk.text)
+    } else {
+        format!(
+            "Document: \"{}\". Source: {}. Section: {}. {}",
+            chunk.title, chunk.source, chunk.heading, chunk.text
+        )
+    }
+}
+
+/// Persisted between batches so an interrupted run picks up where it stopped
+#[derive(Debug, Serialize, Deserialize)]
+struct ReembedState {
+    scope: ReembedScope,
+    dimension: usize,
+    phase: ReembedPhase,
+}
+
+/// A re-embedding run in progress; see `RAGEngine::reembed`
+pub struct ReembedRun {
+    scope: ReembedScope,
+    state: ReembedState,
+    state_path: PathBuf,
+    staging: LanceStore,
+    /// Documents still to embed, next one last
+    pending: Vec<String>,
+    progress: ReembedProgress,
+}
+
+impl ReembedRun {
+    pub fn progress(&self) -> &ReembedProgress {
+        &self.progress
+    }
+
+    fn save_state(&self) -> Result<()> {
+        std::fs::write(&self.state_path, serde_json::to_string(&self.state)?)
+            .context("Failed to save re-embedding progress")
+    }
 }
 
 impl RAGEngine {
@@ -134,11 +374,19 @@ impl RAGEngine {
         )
         .context("Failed to initialize Tantivy search")?;
 
-        let embeddings: Box<dyn EmbeddingModel> =
+        let mut multi_vector: Option<Box<dyn MultiVectorEmbedding>> = None;
+        let embeddings: Arc<dyn EmbeddingModel> =
             if config.embedding.use_e5 {
-                let e5_config = E5Config::auto_detect(&config.embedding.model_dir)
+                let mut e5_config = E5Config::auto_detect(&config.embedding.model_dir)
                     .ok_or_else(|| anyhow::anyhow!("E5 model not found at configured path"))?;
-                Box::new(E5Embeddings::new(e5_config).context("Failed to load E5 embeddings")?)
+                e5_config.batch_size = config.embedding.batch_size;
+                e5_config.normalize = config.embedding.normalize;
+                let e5 = E5Embeddings::new(e5_config).context("Failed to load E5 embeddings")?;
+                if config.features.enable_maxsim_rerank {
+                    // Shares the ONNX session with the single-vector model
+                    multi_vector = Some(Box::new(e5.clone()));
+                }
+                Arc::new(e5)
             } else {
                 return Err(anyhow::anyhow!(
                     "No embedding model available. Place E5 model in: {}",
@@ -150,7 +398,8 @@ impl RAGEngine {
             config.chunking.chunk_size,
             config.chunking.chunk_overlap,
             config.chunking.min_chunk_size,
-        );
+        )
+        .with_strategy(config.chunking.strategy);
 
         // Try to load cross-encoder reranker if enabled and model exists
         let reranker = if config.features.enable_reranking || config.features.enable_cross_encoder {
@@ -169,6 +418,8 @@ impl RAGEngine {
             None
         };
 
+        let query_cache = QueryCache::new(&config.cache);
+
         let mut engine = Self {
             store,
             text_search,
@@ -177,6 +428,9 @@ impl RAGEngine {
             parser: DocumentParser::new(),
             config,
             reranker,
+            multi_vector,
+            query_cache,
+            keyring: SpaceKeyring::default(),
         };
 
         // After schema migration the Tantivy index is empty but LanceDB still
@@ -202,9 +456,12 @@ impl RAGEngine {
         &mut self,
         content: &str,
         format: DocumentFormat,
-        metadata: HashMap<String, String>,
+        mut metadata: HashMap<String, String>,
         citation: Citation,
     ) -> Result<Vec<Uuid>> {
+        metadata
+            .entry(CONTENT_HASH_KEY.to_string())
+            .or_insert_with(|| blake3::hash(content.as_bytes()).to_hex().to_string());
         let title = metadata
             .get("title")
             .cloned()
@@ -220,6 +477,8 @@ impl RAGEngine {
             .unwrap_or_default();
 
         let doc_id = Uuid::new_v4();
+        // Fails early when the space is encrypted and locked
+        let encrypted = self.keyring.key(&space_id)?.is_some();
 
         // Contextual chunking: prepend document-level context to each chunk
         // before embedding for better retrieval (Anthropic's contextual retrieval approach)
@@ -229,6 +488,7 @@ impl RAGEngine {
             return Ok(Vec::new());
         }
 
+        self.check_index_dimension()?;
         // Embed the contextualized text (with document context prefix) for better vector representation
         let chunk_texts: Vec<&str> = chunks.iter().map(|c| c.contextualized_text.as_str()).collect();
         let embeddings = self.embeddings.embed_documents(&chunk_texts)?;
@@ -249,6 +509,7 @@ impl RAGEngine {
 
             // Extract structured fields (emails, phones, etc.) from chunk text
             let mut per_chunk_meta: HashMap<String, String> = metadata.clone();
+            per_chunk_meta.insert("chunk_strategy".to_string(), chunk_strategy_label(chunk.strategy).to_string());
             let extracted = extract_structured_fields(&chunk.text);
             for (k, v) in &extracted {
                 per_chunk_meta.insert(k.clone(), v.clone());
@@ -281,6 +542,14 @@ impl RAGEngine {
             ));
         }
 
+        // Encrypted spaces stay out of the plaintext FTS index
+        if encrypted {
+            for record in &mut chunk_records {
+                self.keyring.seal_record(record)?;
+            }
+            fts_batch.clear();
+        }
+
         // Insert into LanceDB
         self.store
             .upsert_chunks(chunk_records)
@@ -290,6 +559,7 @@ impl RAGEngine {
         // Index in Tantivy
         self.text_search.index_chunks_batch(&fts_batch)?;
         self.text_search.commit()?;
+        self.query_cache.invalidate_space(&space_id);
 
         tracing::info!(
             "Ingested document '{}' ({} chunks) into space '{}'",
@@ -309,122 +579,176 @@ impl RAGEngine {
         path: &Path,
         metadata: HashMap<String, String>,
     ) -> Result<Vec<Uuid>> {
-        let source = normalize_source_path(path);
-
-        // Delete any existing chunks for this source path to prevent duplicates.
-        // This makes re-indexing idempotent: the same file always produces a clean
-        // replacement rather than accumulating stale copies.
-        self.store.delete_by_source(&source).await.ok();
-        self.text_search.delete_by_source(&source)?;
-        self.text_search.commit()?;
-
-        let parsed = self.parser.parse_file(path)?;
+        let doc = self.document_preparer().prepare(path, metadata)?;
+        self.ingest_prepared(vec![doc])
+            .await
+            .pop()
+            .map(|(_, result)| result)
+            .unwrap_or_else(|| Ok(Vec::new()))
+    }
 
-        let mut merged_metadata = parsed.metadata;
-        for (k, v) in metadata {
-            merged_metadata.insert(k, v);
+    /// Owned parser + chunker with this engine's settings, for preparing
+    /// files off the engine (e.g. in parallel `spawn_blocking` workers).
+    pub fn document_preparer(&self) -> DocumentPreparer {
+        DocumentPreparer {
+            parser: self.parser.clone(),
+            chunker: self.chunker.clone(),
         }
-        // Ensure file_path in metadata matches the canonical source used for
-        // deletion above. This prevents mismatches if the caller passes a
-        // differently-formatted path string.
-        merged_metadata.insert("file_path".to_string(), source.clone());
+    }
 
-        let citation = Citation {
-            title: parsed.title.clone(),
-            source: source.clone(),
-            ..Citation::default()
+    /// Embed and store prepared documents. Chunks from all documents are
+    /// embedded together so small files still fill embedding batches.
+    /// Each document replaces any previously indexed chunks for its source.
+    /// Returns one result per document, keyed by source.
+    pub async fn ingest_prepared(
+        &mut self,
+        docs: Vec<PreparedDocument>,
+    ) -> Vec<(String, Result<Vec<Uuid>>)> {
+        let texts: Vec<&str> = docs
+            .iter()
+            .flat_map(|d| d.chunks.iter().map(|c| c.contextualized_text.as_str()))
+            .collect();
+        let embeddings = if texts.is_empty() {
+            Ok(Vec::new())
+        } else {
+            self.embeddings.embed_documents(&texts)
         };
 
-        // Use structure-aware chunking for documents with structured data (PDF forms,
-        // spreadsheet tables, relationships). Keeps related data together as atomic units
-        // instead of scattering them across naive sliding-window chunks.
-        if !parsed.structured_sections.is_empty() {
-            let title = merged_metadata
-                .get("title")
-                .cloned()
-                .unwrap_or_else(|| parsed.title.clone());
-            let space_id = merged_metadata
-                .get("space_id")
-                .cloned()
-                .unwrap_or_default();
-
-            let chunks = self.chunker.chunk_structured(
-                &parsed.structured_sections,
-                &title,
-                &source,
-            );
-
-            if chunks.is_empty() {
-                return Ok(Vec::new());
+        let mut embeddings = match embeddings {
+            Ok(e) => e.into_iter(),
+            Err(e) => {
+                let msg = e.to_string();
+                return docs
+                    .into_iter()
+                    .map(|d| (d.source, Err(anyhow::anyhow!("Embedding failed: {}", msg))))
+                    .collect();
             }
+        };
 
-            let doc_id = Uuid::new_v4();
-            let chunk_texts: Vec<&str> = chunks.iter().map(|c| c.contextualized_text.as_str()).collect();
-            let embeddings = self.embeddings.embed_documents(&chunk_texts)?;
+        let mut results = Vec::with_capacity(docs.len());
+        for doc in docs {
+            let vectors: Vec<Vec<f32>> = embeddings.by_ref().take(doc.chunks.len()).collect();
+            let source = doc.source.clone();
+            let result = self.store_prepared(doc, vectors).await;
+            results.push((source, result));
+        }
+        results
+    }
+
+    /// Replace a source's chunks with a prepared document's chunks.
+    ///
+    /// New chunks are written before old ones are removed, so a failure part
+    /// way through leaves the previous version of the file searchable rather
+    /// than a half-indexed one. Re-indexing stays idempotent: the same file
+    /// always ends up with exactly one set of chunks.
+    async fn store_prepared(
+        &mut self,
+        doc: PreparedDocument,
+        vectors: Vec<Vec<f32>>,
+    ) -> Result<Vec<Uuid>> {
+        self.check_index_dimension()?;
+        let encrypted = self.keyring.key(&doc.space_id)?.is_some();
+        // Whatever happens below, the source's previous chunks may be gone
+        self.query_cache.invalidate_space(&doc.space_id);
+
+        if doc.chunks.is_empty() {
+            self.store.delete_by_source(&doc.source).await.ok();
+            self.text_search.delete_by_source(&doc.source)?;
+            self.text_search.commit()?;
+            return Ok(Vec::new());
+        }
+        if vectors.len() != doc.chunks.len() {
+            return Err(anyhow::anyhow!(
+                "Got {} embeddings for {} chunks of {}",
+                vectors.len(),
+                doc.chunks.len(),
+                doc.source
+            ));
+        }
 
-            let citation_json = serde_json::to_string(&citation).unwrap_or_else(|_| "{}".to_string());
-            let metadata_json = serde_json::to_string(&merged_metadata).unwrap_or_else(|_| "{}".to_string());
-            let now = chrono::Utc::now().timestamp();
+        let doc_id = Uuid::new_v4();
+        let citation_json = serde_json::to_string(&doc.citation).unwrap_or_else(|_| "{}".to_string());
+        let metadata_json = serde_json::to_string(&doc.metadata).unwrap_or_else(|_| "{}".to_string());
+        let now = chrono::Utc::now().timestamp();
 
-            let mut chunk_records = Vec::with_capacity(chunks.len());
-            let mut fts_batch = Vec::with_capacity(chunks.len());
-            let mut chunk_ids = Vec::with_capacity(chunks.len());
+        let mut chunk_records = Vec::with_capacity(doc.chunks.len());
+        let mut fts_batch = Vec::with_capacity(doc.chunks.len());
+        let mut chunk_ids = Vec::with_capacity(doc.chunks.len());
 
-            for (i, (chunk, embedding)) in chunks.iter().zip(embeddings.into_iter()).enumerate() {
-                let chunk_id = chunk.id;
-                chunk_ids.push(chunk_id);
+        for (i, (chunk, embedding)) in doc.chunks.iter().zip(vectors).enumerate() {
+            let chunk_id = chunk.id;
+            chunk_ids.push(chunk_id);
 
-                let mut per_chunk_meta = merged_metadata.clone();
+            let mut per_chunk_meta = doc.metadata.clone();
+            per_chunk_meta.insert("chunk_strategy".to_string(), chunk_strategy_label(chunk.strategy).to_string());
+            if doc.structured {
                 if let Some(heading) = &chunk.heading {
                     per_chunk_meta.insert("chunk_type".to_string(), heading.clone());
                 }
-                // Extract structured fields (emails, phones, etc.) at ingest time
-                let extracted = extract_structured_fields(&chunk.text);
-                for (k, v) in &extracted {
-                    per_chunk_meta.insert(k.clone(), v.clone());
-                }
-
-                let per_chunk_meta_json = serde_json::to_string(&per_chunk_meta)
-                    .unwrap_or_else(|_| metadata_json.clone());
-
-                chunk_records.push(ChunkRecord {
-                    id: chunk_id.to_string(),
-                    doc_id: doc_id.to_string(),
-                    chunk_index: i as u32,
-                    text: chunk.text.clone(),
-                    title: title.clone(),
-                    source: source.clone(),
-                    heading: chunk.heading.clone().unwrap_or_default(),
-                    vector: embedding,
-                    space_id: space_id.clone(),
-                    metadata_json: per_chunk_meta_json,
-                    citation_json: citation_json.clone(),
-                    created_at: now,
-                });
-
-                fts_batch.push((
-                    chunk_id.to_string(),
-                    chunk.contextualized_text.clone(),
-                    title.clone(),
-                    source.clone(),
-                ));
+            }
+            per_chunk_meta.extend(chunk.metadata.clone());
+            // Extract structured fields (emails, phones, etc.) at ingest time
+            let extracted = extract_structured_fields(&chunk.text);
+            for (k, v) in &extracted {
+                per_chunk_meta.insert(k.clone(), v.clone());
             }
 
-            self.store.upsert_chunks(chunk_records).await
-                .context("Failed to store structured chunks in LanceDB")?;
-            self.text_search.index_chunks_batch(&fts_batch)?;
-            self.text_search.commit()?;
+            let per_chunk_meta_json = serde_json::to_string(&per_chunk_meta)
+                .unwrap_or_else(|_| metadata_json.clone());
 
-            tracing::info!(
-                "Ingested structured document '{}' ({} chunks, {} sections) into space '{}'",
-                title, chunk_ids.len(), parsed.structured_sections.len(), space_id,
-            );
+            // Store the original text (without context prefix) for display
+            chunk_records.push(ChunkRecord {
+                id: chunk_id.to_string(),
+                doc_id: doc_id.to_string(),
+                chunk_index: i as u32,
+                text: chunk.text.clone(),
+                title: doc.title.clone(),
+                source: doc.source.clone(),
+                heading: chunk.heading.clone().unwrap_or_default(),
+                vector: embedding,
+                space_id: doc.space_id.clone(),
+                metadata_json: per_chunk_meta_json,
+                citation_json: citation_json.clone(),
+                created_at: now,
+            });
+
+            // Index contextualized text in FTS for richer BM25 matching
+            fts_batch.push((
+                chunk_id.to_string(),
+                chunk.contextualized_text.clone(),
+                doc.title.clone(),
+                doc.source.clone(),
+            ));
+        }
 
-            return Ok(chunk_ids);
+        if encrypted {
+            for record in &mut chunk_records {
+                self.keyring.seal_record(record)?;
+            }
+            fts_batch.clear();
         }
 
-        self.add_document(&parsed.content, parsed.format, merged_metadata, citation)
+        self.store.upsert_chunks(chunk_records).await
+            .context("Failed to store chunks in LanceDB")?;
+
+        // BM25 swaps old entries for new ones in one commit, so keyword search
+        // never sees the source with neither version indexed
+        self.text_search.replace_source(&doc.source, &fts_batch)?;
+
+        self.store
+            .delete_by_source_except_doc(&doc.source, &doc_id.to_string())
             .await
+            .context("Failed to remove previous chunks from LanceDB")?;
+
+        tracing::info!(
+            "Ingested {}document '{}' ({} chunks) into space '{}'",
+            if doc.structured { "structured " } else { "" },
+            doc.title,
+            chunk_ids.len(),
+            doc.space_id,
+        );
+
+        Ok(chunk_ids)
     }
 
     /// Search with hybrid vector + FTS fusion
@@ -433,7 +757,17 @@ impl RAGEngine {
         query: &str,
         k: usize,
     ) -> Result<Vec<SimpleSearchResult>> {
-        let results = self.search_comprehensive(query, k, None).await?;
+        self.search_with(query, k, RerankOptions::default()).await
+    }
+
+    /// `search` with per-query reranking controls
+    pub async fn search_with(
+        &self,
+        query: &str,
+        k: usize,
+        rerank: RerankOptions,
+    ) -> Result<Vec<SimpleSearchResult>> {
+        let results = self.search_comprehensive_with(query, k, None, rerank).await?;
 
         Ok(results
             .into_iter()
@@ -474,6 +808,41 @@ impl RAGEngine {
         query: &str,
         k: usize,
         filter: Option<MetadataFilter>,
+    ) -> Result<Vec<ComprehensiveResult>> {
+        self.search_comprehensive_with(query, k, filter, RerankOptions::default()).await
+    }
+
+    /// `search_comprehensive` with per-query reranking controls.
+    /// Served from the query cache when an identical search is still fresh.
+    pub async fn search_comprehensive_with(
+        &self,
+        query: &str,
+        k: usize,
+        filter: Option<MetadataFilter>,
+        rerank: RerankOptions,
+    ) -> Result<Vec<ComprehensiveResult>> {
+        self.check_index_dimension()?;
+        if let Some(space_id) = filter.as_ref().and_then(|f| f.space_id.as_deref()) {
+            self.keyring.key(space_id)?;
+        }
+        let cache_key = QueryCache::key(query, k, filter.as_ref(), &rerank);
+        if let Some(cached) = self.query_cache.get(cache_key) {
+            tracing::debug!(query = query, "Query cache hit");
+            return Ok(cached);
+        }
+
+        let space_id = filter.as_ref().and_then(|f| f.space_id.clone());
+        let results = self.search_uncached(query, k, filter, rerank).await?;
+        self.query_cache.insert(cache_key, space_id, results.clone());
+        Ok(results)
+    }
+
+    async fn search_uncached(
+        &self,
+        query: &str,
+        k: usize,
+        filter: Option<MetadataFilter>,
+        rerank: RerankOptions,
     ) -> Result<Vec<ComprehensiveResult>> {
         // Decompose complex queries into independent sub-queries
         let decomposed = crate::rag::query_decomposer::decompose_query(query);
@@ -489,7 +858,7 @@ impl RAGEngine {
             // Search each sub-query independently
             let mut result_sets = Vec::new();
             for sub_query in &decomposed.sub_queries {
-                match self.search_single_query(sub_query, k, filter.clone()).await {
+                match self.search_single_query(sub_query, k, filter.clone(), rerank).await {
                     Ok(results) => result_sets.push(results),
                     Err(e) => {
                         tracing::warn!(sub_query = sub_query, error = %e, "Sub-query search failed");
@@ -510,7 +879,7 @@ impl RAGEngine {
             return Ok(merged);
         }
 
-        let mut results = self.search_single_query(query, k, filter).await?;
+        let mut results = self.search_single_query(query, k, filter, rerank).await?;
         self.expand_with_neighbors(&mut results, 1).await;
         Ok(results)
     }
@@ -521,9 +890,16 @@ impl RAGEngine {
         query: &str,
         k: usize,
         filter: Option<MetadataFilter>,
+        rerank: RerankOptions,
     ) -> Result<Vec<ComprehensiveResult>> {
         // Use same candidate count for both vector and FTS for balanced fusion
-        let candidate_count = k * self.config.search.candidate_multiplier;
+        let mut candidate_count = k * self.config.search.candidate_multiplier;
+        // Author/year/language filters run after retrieval, so widen the pool they narrow
+        let citation_filter = filter.as_ref().filter(|f| f.has_citation_filter());
+        let language_filter = filter.as_ref().filter(|f| f.language.is_some());
+        if citation_filter.is_some() || language_filter.is_some() {
+            candidate_count *= POST_FILTER_POOL_FACTOR;
+        }
 
         // Generate query embedding
         let query_embedding = self.embeddings.embed_query(query)?;
@@ -543,6 +919,8 @@ impl RAGEngine {
                 lance_filter.as_deref(),
             )
             .await?;
+        // Decrypts unlocked spaces; hits from locked ones are dropped
+        let vector_hits = self.keyring.open_hits(vector_hits);
 
         let vector_results: Vec<(String, f32)> = vector_hits
             .iter()
@@ -550,11 +928,20 @@ impl RAGEngine {
             .collect();
 
         // Full-text search via Tantivy — use SAME candidate count for balanced fusion
-        let fts_results = self.text_search.search_filtered(
-            query,
-            candidate_count,
-            source_filter,
-        )?;
+        let fts_results = if self.config.search.bm25_enabled {
+            self.text_search.search_bm25(
+                query,
+                candidate_count,
+                source_filter,
+                Bm25Params { k1: self.config.search.bm25_k1, b: self.config.search.bm25_b },
+            )?
+        } else {
+            self.text_search.search_filtered(
+                query,
+                candidate_count,
+                source_filter,
+            )?
+        };
 
         // Log source diversity at each stage for diagnostics
         let vector_sources: std::collections::HashSet<&str> = vector_hits
@@ -578,6 +965,8 @@ impl RAGEngine {
             self.config.search.rrf_k,
             candidate_count, // Get more candidates for reranking
             self.config.search.score_weight,
+            self.config.search.vector_weight,
+            self.config.search.text_weight,
         );
 
         tracing::info!(
@@ -599,7 +988,7 @@ impl RAGEngine {
 
         // Fetch full data for FTS-only results from LanceDB
         let fts_only_hits = if !fts_only_ids.is_empty() {
-            self.store.get_by_ids(&fts_only_ids).await?
+            self.keyring.open_hits(self.store.get_by_ids(&fts_only_ids).await?)
         } else {
             Vec::new()
         };
@@ -654,6 +1043,17 @@ impl RAGEngine {
             // Skip results where we can't find full data (shouldn't happen now)
         }
 
+        if let Some(f) = citation_filter {
+            let before = results.len();
+            results.retain(|r| f.matches_citation(&r.citation));
+            tracing::info!(before = before, after = results.len(), "Citation filter");
+        }
+        if let Some(f) = language_filter {
+            let before = results.len();
+            results.retain(|r| f.matches_language(&r.metadata));
+            tracing::info!(before = before, after = results.len(), "Language filter");
+        }
+
         // Log source diversity of built results
         {
             let built_sources: std::collections::HashSet<&str> = results
@@ -683,34 +1083,31 @@ impl RAGEngine {
         // Deduplicate near-identical chunks (from overlapping windows)
         Self::deduplicate_results(&mut results, 0.75);
 
-        // Apply cross-encoder reranking if available (before MMR so diversity uses final scores)
-        if let Some(reranker) = &self.reranker {
-            if results.len() > 1 {
-                let candidates: Vec<(String, String)> = results
-                    .iter()
-                    .map(|r| (r.id.to_string(), r.snippet.clone()))
-                    .collect();
+        // Optional late-interaction rescoring of the top candidates
+        if let Some(multi_vector) = &self.multi_vector {
+            Self::maxsim_rerank(multi_vector.as_ref(), query, &mut results);
+        }
 
-                match reranker.rerank(query, &candidates, candidates.len()) {
-                    Ok(reranked) => {
-                        let rerank_scores: HashMap<String, f32> =
-                            reranked.into_iter().collect();
+        // Apply cross-encoder reranking if available (before MMR so diversity uses final scores)
+        if rerank.cross_encoder && results.len() > 1 {
+            let head = rerank.top_k.unwrap_or(results.len()).min(results.len());
+            let candidates: Vec<(String, String)> = results[..head]
+                .iter()
+                .map(|r| (r.id.to_string(), r.snippet.clone()))
+                .collect();
 
-                        // Update scores where reranking succeeded; keep original score
-                        // for any candidates the cross-encoder couldn't tokenize.
-                        for result in &mut results {
-                            if let Some(&new_score) = rerank_scores.get(&result.id.to_string()) {
-                                result.score = new_score;
-                            }
-                        }
-                        results.sort_by(|a, b| {
-                            b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal)
-                        });
-                    }
-                    Err(e) => {
-                        tracing::warn!("Reranking failed, using fusion scores: {}", e);
+            if let Some(rerank_scores) = self.cross_encoder_scores(query, &candidates) {
+                // Update scores where reranking succeeded; keep original score
+                // for any candidates the cross-encoder couldn't tokenize.
+                for result in &mut results[..head] {
+                    if let Some(&new_score) = rerank_scores.get(&result.id.to_string()) {
+                        result.score = new_score;
                     }
                 }
+                results[..head].sort_by(|a, b| {
+                    b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal)
+                });
+                Self::fit_tail_under_head(&mut results, head);
             }
         }
 
@@ -757,6 +1154,7 @@ impl RAGEngine {
 
             match self.store.get_neighbors(&doc_id, chunk_index, window).await {
                 Ok(neighbors) if !neighbors.is_empty() => {
+                    let neighbors = self.keyring.open_hits(neighbors);
                     let mut before = String::new();
                     let mut after = String::new();
 
@@ -810,10 +1208,18 @@ impl RAGEngine {
         }
 
         let deleted = self.store.delete_by_doc_id(doc_id).await?;
+        self.query_cache.invalidate_all();
         tracing::info!(doc_id = %doc_id, deleted = deleted, "Deleted document by doc_id");
         Ok(deleted)
     }
 
+    /// Content hashes recorded for files indexed under `folder` (or for the
+    /// file itself), keyed by normalized source path.
+    pub async fn indexed_content_hashes(&self, folder: &str) -> Result<HashMap<String, String>> {
+        let normalized = normalize_source_path(Path::new(folder));
+        self.store.content_hashes(&normalized).await
+    }
+
     /// Delete all documents from a specific source/folder.
     /// The source path is normalized the same way as during indexing so that
     /// Windows backslash / mixed-case paths always match.
@@ -822,6 +1228,7 @@ impl RAGEngine {
         let deleted = self.store.delete_by_source(&normalized).await?;
         self.text_search.delete_by_source(&normalized)?;
         self.text_search.commit()?;
+        self.query_cache.invalidate_all();
         Ok(deleted)
     }
 
@@ -833,6 +1240,7 @@ impl RAGEngine {
         let deleted = self.store.delete_by_source_prefix(&normalized).await?;
         self.text_search.delete_by_source_prefix(&normalized)?;
         self.text_search.commit()?;
+        self.query_cache.invalidate_all();
         Ok(deleted)
     }
 
@@ -851,6 +1259,7 @@ impl RAGEngine {
 
         // Delete from LanceDB by space_id
         let deleted = self.store.delete_by_space_id(space_id).await?;
+        self.query_cache.invalidate_space(space_id);
 
         tracing::info!(
             space_id = %space_id,
@@ -860,10 +1269,88 @@ impl RAGEngine {
         Ok(deleted)
     }
 
+    /// Every chunk in a space with its embedding, in document/chunk order
+    pub async fn export_space_chunks(&self, space_id: &str) -> Result<Vec<ChunkRecord>> {
+        let predicate = format!("space_id = '{}'", space_id.replace('\'', "''"));
+        self.store.export_chunks(Some(&predicate)).await
+    }
+
+    /// Replace a space's chunks with previously exported ones. With `reembed`
+    /// the vectors are recomputed with the current embedding model; otherwise
+    /// they must already have its dimension.
+    ///
+    /// All-or-nothing: everything is validated and sealed before the index is
+    /// touched, and a failed write removes whatever part of the space was
+    /// stored so a retry starts clean.
+    pub async fn import_space_chunks(
+        &mut self,
+        space_id: &str,
+        mut chunks: Vec<ChunkRecord>,
+        reembed: bool,
+    ) -> Result<usize> {
+        self.check_index_dimension()?;
+        if reembed {
+            let texts: Vec<String> = chunks.iter().map(reembed_text).collect();
+            let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+            let vectors = self.embeddings.embed_documents(&texts)
+                .context("Failed to re-embed imported chunks")?;
+            for (chunk, vector) in chunks.iter_mut().zip(vectors) {
+                chunk.vector = vector;
+            }
+        }
+        let dimension = self.embeddings.dimension();
+        if let Some(bad) = chunks.iter().find(|c| c.vector.len() != dimension) {
+            return Err(anyhow::anyhow!(
+                "Chunk {} has a {}-dimensional embedding, expected {}",
+                bad.id,
+                bad.vector.len(),
+                dimension
+            ));
+        }
+
+        let encrypted = self.keyring.key(space_id)?.is_some();
+        for chunk in &mut chunks {
+            chunk.space_id = space_id.to_string();
+        }
+        // Only the display text is archived, so it stands in for the
+        // contextualized text in the FTS index
+        let fts_batch: Vec<(String, String, String, String)> = chunks
+            .iter()
+            .filter(|_| !encrypted)
+            .map(|c| (c.id.clone(), c.text.clone(), c.title.clone(), c.source.clone()))
+            .collect();
+        if encrypted {
+            for chunk in &mut chunks {
+                self.keyring.seal_record(chunk)?;
+            }
+        }
+        let count = chunks.len();
+
+        self.delete_by_space_id(space_id).await?;
+        let written = async {
+            self.store.upsert_chunks(chunks).await
+                .context("Failed to store imported chunks in LanceDB")?;
+            self.text_search.index_chunks_batch(&fts_batch)?;
+            self.text_search.commit()
+        }
+        .await;
+        if let Err(e) = written {
+            if let Err(cleanup) = self.delete_by_space_id(space_id).await {
+                tracing::error!(space_id = %space_id, error = %cleanup, "Failed to roll back partial space import");
+            }
+            return Err(e);
+        }
+        self.query_cache.invalidate_space(space_id);
+
+        tracing::info!(space_id = %space_id, chunks = count, reembedded = reembed, "Imported space chunks");
+        Ok(count)
+    }
+
     /// Clear all data
     pub async fn clear_all_data(&mut self) -> Result<()> {
         self.store.clear().await?;
         self.text_search.clear()?;
+        self.query_cache.invalidate_all();
         Ok(())
     }
 
@@ -913,6 +1400,7 @@ impl RAGEngine {
             .store
             .list_chunks(predicate.as_deref(), limit)
             .await?;
+        let hits = self.keyring.open_hits(hits);
 
         let mut results = Vec::with_capacity(hits.len());
         for hit in hits {
@@ -940,6 +1428,66 @@ impl RAGEngine {
         Ok(results)
     }
 
+    /// Documents matching `filter`, one result per document (its first
+    /// chunk), sorted without running the embedding model. Scores are 0.
+    pub async fn filter_documents(
+        &self,
+        filter: &MetadataFilter,
+        sort_by: DocumentSort,
+        limit: usize,
+    ) -> Result<Vec<ComprehensiveResult>> {
+        // Every document has a chunk 0, so it stands in for the document
+        let predicate = match filter.to_lance_predicate() {
+            Some(p) => format!("({}) AND chunk_index = 0", p),
+            None => "chunk_index = 0".to_string(),
+        };
+        let hits = self
+            .store
+            .list_chunks(Some(&predicate), FILTER_SCAN_LIMIT)
+            .await?;
+        let mut hits = self.keyring.open_hits(hits);
+
+        match sort_by {
+            DocumentSort::Date => hits.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
+            DocumentSort::Title => hits.sort_by_cached_key(|h| h.title.to_lowercase()),
+        }
+
+        let mut results = Vec::new();
+        for hit in hits {
+            let metadata: HashMap<String, String> =
+                serde_json::from_str(&hit.metadata_json).unwrap_or_default();
+            if !filter.matches_metadata(&metadata) {
+                continue;
+            }
+            let citation: Citation =
+                serde_json::from_str(&hit.citation_json).unwrap_or_default();
+            if !filter.matches_citation(&citation) {
+                continue;
+            }
+
+            let mut full_metadata = metadata;
+            full_metadata.insert("doc_id".to_string(), hit.doc_id.clone());
+            full_metadata.insert("chunk_index".to_string(), hit.chunk_index.to_string());
+            full_metadata.insert("source_file".to_string(), hit.source.clone());
+            full_metadata.insert("space_id".to_string(), hit.space_id.clone());
+            full_metadata.insert("created_at".to_string(), hit.created_at.to_string());
+
+            results.push(ComprehensiveResult {
+                id: Uuid::parse_str(&hit.id).unwrap_or_default(),
+                score: 0.0,
+                metadata: full_metadata,
+                citation,
+                snippet: hit.text,
+                source_index: "filter".to_string(),
+            });
+            if results.len() >= limit {
+                break;
+            }
+        }
+
+        Ok(results)
+    }
+
     /// Raw LanceDB query — returns SearchHit objects without wrapping in ComprehensiveResult.
     /// Useful for ID lookups during deletion.
     pub async fn list_documents_raw(
@@ -950,16 +1498,352 @@ impl RAGEngine {
         self.store.list_chunks(predicate, limit).await
     }
 
+    /// Fail with `EmbeddingDimensionMismatch` when stored vectors can't be
+    /// compared with the current model's
+    fn check_index_dimension(&self) -> Result<()> {
+        let (index, model) = (self.store.dimension(), self.embeddings.dimension());
+        if index != model {
+            return Err(EmbeddingDimensionMismatch { index, model }.into());
+        }
+        Ok(())
+    }
+
+    /// Recompute the vectors of every chunk in `scope` with the current
+    /// embedding model. Documents are embedded into a staging table one at a
+    /// time and swapped in at the end, so an interrupted run resumes from the
+    /// last finished document and search keeps the old vectors until then.
+    /// A dimension change has to cover all spaces.
+    ///
+    /// Runs all three steps of `reembed_start`, `reembed_batch` and
+    /// `reembed_swap`; callers sharing the engine behind a lock can call them
+    /// separately to only hold the write lock for the swap.
+    pub async fn reembed(
+        &mut self,
+        scope: ReembedScope,
+        on_progress: &(dyn Fn(&ReembedProgress) + Send + Sync),
+    ) -> Result<ReembedProgress> {
+        let mut run = self.reembed_start(scope, on_progress).await?;
+        while !self.reembed_batch(&mut run, usize::MAX, on_progress).await? {}
+        self.reembed_swap(run, on_progress).await
+    }
+
+    /// Set up the staging table for a re-embedding run, resuming an
+    /// interrupted run with the same scope and dimension
+    pub async fn reembed_start(
+        &self,
+        scope: ReembedScope,
+        on_progress: &(dyn Fn(&ReembedProgress) + Send + Sync),
+    ) -> Result<ReembedRun> {
+        let dimension = self.embeddings.dimension();
+        if scope != ReembedScope::All && self.store.dimension() != dimension {
+            return Err(anyhow::anyhow!(
+                "The embedding dimension changed ({} -> {}); re-embed all spaces instead of one",
+                self.store.dimension(),
+                dimension
+            ));
+        }
+
+        let state_path = self.config.data_dir.join(REEMBED_STATE_FILE);
+        let previous: Option<ReembedState> = std::fs::read_to_string(&state_path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok());
+        let resumed = previous.as_ref()
+            .is_some_and(|p| p.scope == scope && p.dimension == dimension);
+        let state = match previous {
+            Some(p) if resumed => p,
+            _ => ReembedState { scope: scope.clone(), dimension, phase: ReembedPhase::Embedding },
+        };
+
+        let mut staging = self.store.sibling_table(REEMBED_TABLE, dimension).await?;
+        if !resumed || staging.dimension() != dimension {
+            // Leftovers from a run with another scope or model
+            staging.recreate(dimension).await?;
+        }
+        let mut run = ReembedRun {
+            progress: ReembedProgress {
+                scope: scope.clone(),
+                phase: state.phase,
+                documents_done: 0,
+                documents_total: 0,
+                chunks_embedded: 0,
+                resumed,
+            },
+            scope,
+            state,
+            state_path,
+            staging,
+            pending: Vec::new(),
+        };
+        run.save_state()?;
+
+        let documents = self.store.doc_ids(run.scope.predicate().as_deref()).await?;
+        run.progress.documents_total = documents.len();
+        if run.state.phase == ReembedPhase::Embedding {
+            let done = run.staging.doc_ids(None).await?;
+            run.progress.documents_done = documents.iter().filter(|d| done.contains(*d)).count();
+            run.pending = documents.into_iter().filter(|d| !done.contains(d)).rev().collect();
+        }
+        on_progress(&run.progress);
+        Ok(run)
+    }
+
+    /// Embed up to `max_documents` more documents of `run` into its staging
+    /// table. Returns true once none are left.
+    pub async fn reembed_batch(
+        &self,
+        run: &mut ReembedRun,
+        max_documents: usize,
+        on_progress: &(dyn Fn(&ReembedProgress) + Send + Sync),
+    ) -> Result<bool> {
+        for _ in 0..max_documents {
+            let Some(doc_id) = run.pending.pop() else {
+                break;
+            };
+            run.progress.chunks_embedded += self.reembed_document(&run.staging, &doc_id).await?;
+            run.progress.documents_done += 1;
+            on_progress(&run.progress);
+        }
+        Ok(run.pending.is_empty())
+    }
+
+    /// Replace the chunks in scope with the staged ones. Documents indexed
+    /// or deleted since the run started are caught up first, so nothing
+    /// changed while the batches ran is lost or brought back.
+    pub async fn reembed_swap(
+        &mut self,
+        mut run: ReembedRun,
+        on_progress: &(dyn Fn(&ReembedProgress) + Send + Sync),
+    ) -> Result<ReembedProgress> {
+        if run.state.phase == ReembedPhase::Embedding {
+            let documents = self.store.doc_ids(run.scope.predicate().as_deref()).await?;
+            let done = run.staging.doc_ids(None).await?;
+            for doc_id in documents.difference(&done) {
+                run.progress.chunks_embedded += self.reembed_document(&run.staging, doc_id).await?;
+            }
+            for stale in done.difference(&documents) {
+                run.staging.delete_by_doc_id(stale).await?;
+            }
+            run.progress.documents_total = documents.len();
+
+            run.state.phase = ReembedPhase::Swapping;
+            run.save_state()?;
+        }
+
+        // Swapping is idempotent: the staged chunks stay until it completes
+        let dimension = run.state.dimension;
+        run.progress.phase = ReembedPhase::Swapping;
+        on_progress(&run.progress);
+        match &run.scope {
+            ReembedScope::All => self.store.recreate(dimension).await?,
+            ReembedScope::Space { space_id } => {
+                self.store.delete_by_space_id(space_id).await?;
+            }
+        }
+        for doc_id in run.staging.doc_ids(None).await? {
+            let doc_predicate = format!("doc_id = '{}'", doc_id.replace('\'', "''"));
+            let chunks = run.staging.export_chunks(Some(&doc_predicate)).await?;
+            self.store.upsert_chunks(chunks).await?;
+        }
+        run.staging.drop_table().await?;
+        std::fs::remove_file(&run.state_path).ok();
+        // Chunk ids and text are unchanged, so the FTS index stays valid
+        self.query_cache.invalidate_all();
+
+        let mut progress = run.progress;
+        progress.phase = ReembedPhase::Done;
+        progress.documents_done = progress.documents_total;
+        on_progress(&progress);
+        tracing::info!(
+            scope = ?progress.scope,
+            documents = progress.documents_total,
+            chunks = progress.chunks_embedded,
+            dimension,
+            resumed = progress.resumed,
+            "Re-embedding complete"
+        );
+        Ok(progress)
+    }
+
+    /// Embed one document's chunks into `staging`; returns the chunk count
+    async fn reembed_document(&self, staging: &LanceStore, doc_id: &str) -> Result<usize> {
+        let doc_predicate = format!("doc_id = '{}'", doc_id.replace('\'', "''"));
+        let mut chunks = self.store.export_chunks(Some(&doc_predicate)).await?;
+        // Embed the plaintext of encrypted chunks; the stored fields stay sealed
+        let mut plain = chunks.clone();
+        for chunk in &mut plain {
+            self.keyring.open_record(chunk)?;
+        }
+        let texts: Vec<String> = plain.iter().map(reembed_text).collect();
+        let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+        let vectors = self.embeddings.embed_documents(&texts)
+            .with_context(|| format!("Failed to embed document {}", doc_id))?;
+        for (chunk, vector) in chunks.iter_mut().zip(vectors) {
+            chunk.vector = vector;
+        }
+        let count = chunks.len();
+        staging.upsert_chunks(chunks).await?;
+        Ok(count)
+    }
+
+    /// Identifier of the embedding model, taken from its model directory name
+    pub fn embedding_model_id(&self) -> String {
+        self.config.embedding.model_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.config.embedding.model_dir.display().to_string())
+    }
+
     /// Access to the embedding model for external use
     pub fn embeddings(&self) -> &dyn EmbeddingModel {
         self.embeddings.as_ref()
     }
 
+    /// Shared handle to the embedding model, e.g. for `MemorySystem::with_embedder`
+    pub fn shared_embeddings(&self) -> Arc<dyn EmbeddingModel> {
+        self.embeddings.clone()
+    }
+
+    /// Whether a cross-encoder model is loaded
+    pub fn has_cross_encoder(&self) -> bool {
+        self.reranker.is_some()
+    }
+
+    /// Cross-encoder relevance of each `(id, text)` candidate to `query`.
+    /// None when no model is loaded or inference fails; candidates that
+    /// fail to tokenize are missing from the map.
+    pub fn cross_encoder_scores(
+        &self,
+        query: &str,
+        candidates: &[(String, String)],
+    ) -> Option<HashMap<String, f32>> {
+        let reranker = self.reranker.as_ref()?;
+        match reranker.rerank(query, candidates, candidates.len()) {
+            // Raw logits; squash into (0, 1) so they compare with fused scores
+            Ok(reranked) => Some(
+                reranked.into_iter()
+                    .map(|(id, logit)| (id, 1.0 / (1.0 + (-logit).exp())))
+                    .collect(),
+            ),
+            Err(e) => {
+                tracing::warn!("Reranking failed, using fusion scores: {}", e);
+                None
+            }
+        }
+    }
+
     /// Access to config
     pub fn config(&self) -> &RAGConfig {
         &self.config
     }
 
+    /// Replace the retrieval tuning (RRF k, fusion weights, thresholds).
+    /// Takes effect on the next search; nothing is reindexed.
+    pub fn set_search_config(&mut self, search: SearchConfig) -> Result<()> {
+        search.validate().map_err(|e| anyhow::anyhow!(e))?;
+        self.config.search = search;
+        self.query_cache.invalidate_all();
+        Ok(())
+    }
+
+    /// Query cache hit/miss counters
+    pub fn query_cache_stats(&self) -> QueryCacheStats {
+        self.query_cache.stats()
+    }
+
+    /// Mark a space as encrypted and locked (at startup, from space metadata)
+    pub fn register_encrypted_space(&mut self, space_id: &str) {
+        self.keyring.register_locked(space_id);
+    }
+
+    pub fn is_space_encrypted(&self, space_id: &str) -> bool {
+        self.keyring.is_encrypted(space_id)
+    }
+
+    pub fn is_space_locked(&self, space_id: &str) -> bool {
+        self.keyring.is_locked(space_id)
+    }
+
+    /// Hold the space's key in memory so its chunks are readable and writable
+    pub fn unlock_space(&mut self, space_id: &str, key: SpaceKey) {
+        self.keyring.unlock(space_id, key);
+        self.query_cache.invalidate_space(space_id);
+    }
+
+    /// Drop the space's key. Cached results may hold decrypted text, so the
+    /// whole query cache is cleared.
+    pub fn lock_space(&mut self, space_id: &str) -> bool {
+        let was_encrypted = self.keyring.lock(space_id);
+        self.query_cache.invalidate_all();
+        was_encrypted
+    }
+
+    /// Encrypt an existing space in place with `key` and leave it unlocked.
+    /// Sealed copies are written before the plaintext rows are deleted; then
+    /// old table versions are pruned and the full-text index is merged and
+    /// garbage collected, since both keep deleted text in their files. If
+    /// either cleanup fails it is logged and that plaintext remains on disk
+    /// until the next successful vacuum or purge. If sealing fails the
+    /// plaintext chunks are put back and the space stays unencrypted.
+    /// Returns the number of chunks encrypted.
+    pub async fn encrypt_space(&mut self, space_id: &str, key: SpaceKey) -> Result<usize> {
+        if self.keyring.is_encrypted(space_id) {
+            return Err(anyhow::anyhow!("Space '{}' is already encrypted", space_id));
+        }
+
+        let plaintext_chunks = self.export_space_chunks(space_id).await?;
+        let mut chunks = plaintext_chunks.clone();
+        self.keyring.unlock(space_id, key);
+        let count = chunks.len();
+
+        let sealed: Result<()> = async {
+            for chunk in &mut chunks {
+                self.keyring.seal_record(chunk)?;
+            }
+            if count > 0 {
+                self.store.upsert_chunks(chunks).await
+                    .context("Failed to store encrypted chunks")?;
+                let plaintext = format!(
+                    "space_id = '{}' AND text NOT LIKE '{}%'",
+                    space_id.replace('\'', "''"),
+                    SEALED_PREFIX
+                );
+                self.store.delete_where(&plaintext).await?;
+            }
+            Ok(())
+        }.await;
+
+        if let Err(e) = sealed {
+            self.keyring.remove(space_id);
+            if let Err(restore) = self.store.upsert_chunks(plaintext_chunks).await {
+                tracing::error!(space_id = %space_id, error = %restore, "Failed to restore plaintext chunks");
+            }
+            return Err(e);
+        }
+
+        for chunk in &plaintext_chunks {
+            let _ = self.text_search.delete_by_id(&chunk.id);
+        }
+        self.text_search.commit()?;
+        if count > 0 {
+            if let Err(e) = self.text_search.purge_deleted() {
+                tracing::warn!(space_id = %space_id, error = %e, "Deleted plaintext not purged from the full-text index");
+            }
+            if let Err(e) = self.store.vacuum().await {
+                tracing::warn!(space_id = %space_id, error = %e, "Old plaintext table versions not pruned; run vacuum");
+            }
+        }
+        self.query_cache.invalidate_space(space_id);
+
+        tracing::info!(space_id = %space_id, chunks = count, "Encrypted space at rest");
+        Ok(count)
+    }
+
+    /// Forget a deleted space's key and encryption state
+    pub fn forget_space(&mut self, space_id: &str) {
+        self.keyring.remove(space_id);
+        self.query_cache.invalidate_space(space_id);
+    }
+
     /// Trigger index creation if needed (after large ingestion)
     pub async fn optimize(&self) -> Result<()> {
         // Compact LanceDB to remove tombstoned rows from previous deletions
@@ -968,6 +1852,124 @@ impl RAGEngine {
         self.store.create_index_if_needed().await
     }
 
+    /// Compact the store, drop all old table versions, purge deleted documents
+    /// from the full-text index and rebuild the vector index if needed. Slower
+    /// than `optimize`; meant for explicit maintenance.
+    pub async fn vacuum(&self) -> Result<crate::storage::CompactionReport> {
+        let report = self.store.vacuum().await?;
+        self.text_search.purge_deleted()?;
+        self.store.create_index_if_needed().await?;
+        Ok(report)
+    }
+
+    /// Delete chunks whose non-empty `space_id` is not in `valid_space_ids`.
+    /// Chunks without a space are global and kept, as are internal spaces such
+    /// as the calendar's. Returns (spaces, chunks) removed.
+    pub async fn delete_orphaned_spaces(
+        &mut self,
+        valid_space_ids: &std::collections::HashSet<String>,
+    ) -> Result<(usize, usize)> {
+        let orphaned: std::collections::BTreeSet<String> = self
+            .store
+            .list_chunks(None, 1_000_000)
+            .await?
+            .into_iter()
+            .map(|hit| hit.space_id)
+            .filter(|id| !id.is_empty() && !valid_space_ids.contains(id))
+            .filter(|id| !RESERVED_SPACE_IDS.contains(&id.as_str()))
+            .collect();
+
+        let mut chunks = 0;
+        for space_id in &orphaned {
+            chunks += self.delete_by_space_id(space_id).await?;
+        }
+        Ok((orphaned.len(), chunks))
+    }
+
+    /// One fingerprint per stored document, built from its chunks. Documents
+    /// in locked spaces are left out since their metadata can't be read.
+    async fn document_fingerprints(
+        &self,
+        predicate: Option<&str>,
+    ) -> Result<Vec<crate::dedup::DocumentFingerprint>> {
+        let hits = self.keyring.open_hits(self.store.list_chunks(predicate, 1_000_000).await?);
+
+        let mut by_doc: HashMap<String, crate::dedup::DocumentFingerprint> = HashMap::new();
+        for hit in hits {
+            let entry = by_doc.entry(hit.doc_id.clone()).or_insert_with(|| {
+                let content_hash = serde_json::from_str::<HashMap<String, String>>(&hit.metadata_json)
+                    .ok()
+                    .and_then(|m| m.get(CONTENT_HASH_KEY).cloned())
+                    .unwrap_or_default();
+                crate::dedup::DocumentFingerprint {
+                    doc_id: hit.doc_id.clone(),
+                    title: hit.title.clone(),
+                    source: hit.source.clone(),
+                    space_id: hit.space_id.clone(),
+                    content_hash,
+                    chunk_count: 0,
+                    text_bytes: 0,
+                    created_at: hit.created_at,
+                }
+            });
+            entry.chunk_count += 1;
+            entry.text_bytes += hit.text.len() as u64;
+            entry.created_at = entry.created_at.min(hit.created_at);
+        }
+        Ok(by_doc.into_values().collect())
+    }
+
+    /// Group documents with identical content across all spaces
+    pub async fn find_duplicate_documents(&self) -> Result<crate::dedup::DuplicateReport> {
+        Ok(crate::dedup::group_duplicates(self.document_fingerprints(None).await?))
+    }
+
+    /// Keep `keep_id` and delete the documents in `remove_ids`, which must
+    /// all have the same content hash as the kept one.
+    pub async fn dedupe_documents(
+        &mut self,
+        keep_id: &str,
+        remove_ids: &[String],
+    ) -> Result<crate::dedup::DedupeOutcome> {
+        let predicate = |id: &str| format!("doc_id = '{}'", id.replace('\'', "''"));
+        let kept = self
+            .document_fingerprints(Some(&predicate(keep_id)))
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Document '{}' not found", keep_id))?;
+        if kept.content_hash.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Document '{}' has no content hash; re-index it before deduplicating",
+                keep_id
+            ));
+        }
+
+        let mut victims = Vec::with_capacity(remove_ids.len());
+        for id in remove_ids.iter().filter(|id| id.as_str() != keep_id) {
+            let doc = self
+                .document_fingerprints(Some(&predicate(id)))
+                .await?
+                .pop()
+                .ok_or_else(|| anyhow::anyhow!("Document '{}' not found", id))?;
+            if doc.content_hash != kept.content_hash {
+                return Err(anyhow::anyhow!(
+                    "Document '{}' is not a duplicate of '{}'",
+                    id,
+                    keep_id
+                ));
+            }
+            victims.push(doc);
+        }
+
+        let mut outcome = crate::dedup::DedupeOutcome::default();
+        for doc in victims {
+            outcome.removed_chunks += self.delete_by_doc_id(&doc.doc_id).await?;
+            outcome.removed_documents += 1;
+            outcome.reclaimed_bytes += doc.text_bytes;
+        }
+        Ok(outcome)
+    }
+
     /// Rebuild the Tantivy full-text index from LanceDB.
     /// Used after schema migration wipes the old index, or to repair inconsistencies.
     pub async fn rebuild_text_index(&mut self) -> Result<()> {
@@ -981,13 +1983,16 @@ impl RAGEngine {
             return Ok(());
         }
 
+        // Encrypted chunks never go into the plaintext index
         let batch: Vec<(String, String, String, String)> = all_chunks
             .into_iter()
+            .filter(|hit| !is_sealed(&hit.text))
             .map(|hit| (hit.id, hit.text, hit.title, hit.source))
             .collect();
 
         self.text_search.index_chunks_batch(&batch)?;
         self.text_search.commit()?;
+        self.query_cache.invalidate_all();
 
         let indexed = self.text_search.count().unwrap_or(0);
         tracing::info!(
@@ -998,6 +2003,54 @@ impl RAGEngine {
         Ok(())
     }
 
+    /// Rescore the top `MAXSIM_RERANK_DEPTH` results by token-level MaxSim
+    /// against the query, blended with their fused scores (see
+    /// `blend_maxsim`). Candidates below the cut keep their fused scores and
+    /// stay behind the rescored ones. On any embedding error the fused order
+    /// is left untouched.
+    fn maxsim_rerank(
+        model: &dyn MultiVectorEmbedding,
+        query: &str,
+        results: &mut [ComprehensiveResult],
+    ) {
+        if results.len() < 2 {
+            return;
+        }
+        let started = std::time::Instant::now();
+        let query_tokens = match model.embed_query_tokens(query) {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                tracing::warn!("MaxSim query embedding failed, skipping stage: {}", e);
+                return;
+            }
+        };
+
+        let depth = results.len().min(MAXSIM_RERANK_DEPTH);
+        let mut scores = Vec::with_capacity(depth);
+        for result in &results[..depth] {
+            match model.embed_document_tokens(&result.snippet) {
+                Ok(doc_tokens) => scores.push(maxsim_score_normalized(&query_tokens, &doc_tokens)),
+                Err(e) => {
+                    tracing::warn!("MaxSim document embedding failed, skipping stage: {}", e);
+                    return;
+                }
+            }
+        }
+
+        let fused: Vec<f32> = results[..depth].iter().map(|r| r.score).collect();
+        for (result, score) in results[..depth].iter_mut().zip(Self::blend_maxsim(&fused, &scores)) {
+            result.score = score;
+        }
+        results[..depth].sort_by(|a, b| {
+            b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        tracing::info!(
+            rescored = depth,
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "MaxSim rerank complete"
+        );
+    }
+
     /// Remove near-duplicate results using Jaccard similarity on word sets.
     /// Only deduplicates chunks from the SAME source file (overlapping windows).
     /// Chunks from different files are never merged — even if they have similar
@@ -1052,6 +2105,47 @@ impl RAGEngine {
         results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
     }
 
+    /// Combine fused and MaxSim scores for the same candidates. The two live
+    /// on different scales (fusion output vs. cosine in [-1, 1]), so each is
+    /// min-max normalized over the candidates and averaged, and the result is
+    /// mapped back onto the fused range: the score threshold, the unreranked
+    /// tail and later stages keep seeing fusion-scale scores.
+    fn blend_maxsim(fused: &[f32], maxsim: &[f32]) -> Vec<f32> {
+        fn unit(scores: &[f32]) -> Vec<f32> {
+            let lo = scores.iter().copied().fold(f32::INFINITY, f32::min);
+            let hi = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            scores
+                .iter()
+                .map(|s| if hi > lo { (s - lo) / (hi - lo) } else { 0.5 })
+                .collect()
+        }
+        let lo = fused.iter().copied().fold(f32::INFINITY, f32::min);
+        let hi = fused.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        // Tied fused scores still need room for MaxSim to order them
+        let span = if hi > lo { hi - lo } else { 1e-3 };
+        unit(fused)
+            .into_iter()
+            .zip(unit(maxsim))
+            .map(|(f, m)| lo + span * (f + m) / 2.0)
+            .collect()
+    }
+
+    /// After reranking only the first `head` results, scale the unreranked
+    /// tail down so it stays below the head and keeps its own order
+    fn fit_tail_under_head(results: &mut [ComprehensiveResult], head: usize) {
+        if head == 0 || head >= results.len() {
+            return;
+        }
+        let floor = results[..head].iter().map(|r| r.score).fold(f32::INFINITY, f32::min);
+        let tail_max = results[head..].iter().map(|r| r.score).fold(f32::NEG_INFINITY, f32::max);
+        if tail_max > floor && tail_max > 0.0 {
+            let scale = floor.max(0.0) / tail_max;
+            for result in &mut results[head..] {
+                result.score *= scale;
+            }
+        }
+    }
+
     /// Hard cap on results per source file to guarantee diversity across documents.
     /// After scoring and MMR, retain at most `max_per_source` chunks from any single file.
     /// Maximal Marginal Relevance — diminishing returns per source file.
@@ -1080,3 +2174,63 @@ impl RAGEngine {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reembed_scope_serde_and_predicate() {
+        let scope: ReembedScope = serde_json::from_str(r#"{"kind":"space","spaceId":"o'brien"}"#).unwrap();
+        assert_eq!(scope, ReembedScope::Space { space_id: "o'brien".to_string() });
+        assert_eq!(scope.predicate().as_deref(), Some("space_id = 'o''brien'"));
+        assert_eq!(serde_json::to_string(&ReembedScope::All).unwrap(), r#"{"kind":"all"}"#);
+        assert!(ReembedScope::All.predicate().is_none());
+    }
+
+    #[test]
+    fn test_table_chunks_are_flagged() {
+        let table = "| Region | Q1 |\n| --- | --- |\n| North | 1,200 |";
+        assert_eq!(extract_structured_fields(table).get("has_table").map(String::as_str), Some("true"));
+        assert!(!extract_structured_fields("Totals | see appendix\n---").contains_key("has_table"));
+    }
+
+    #[test]
+    fn test_unreranked_tail_stays_below_reranked_head() {
+        let result = |score| ComprehensiveResult {
+            id: Uuid::new_v4(),
+            score,
+            metadata: HashMap::new(),
+            citation: Citation::default(),
+            snippet: String::new(),
+            source_index: "hybrid".to_string(),
+        };
+        // Head rescored by the cross-encoder, tail still on the fusion scale
+        let mut results = vec![result(0.9), result(0.2), result(0.6), result(0.3)];
+        RAGEngine::fit_tail_under_head(&mut results, 2);
+        let scores: Vec<f32> = results.iter().map(|r| r.score).collect();
+        assert_eq!(&scores[..2], &[0.9, 0.2]);
+        assert!(scores[2] <= 0.2 + f32::EPSILON && scores[3] < scores[2]);
+    }
+
+    #[test]
+    fn test_maxsim_blend_stays_on_the_fused_scale() {
+        let fused = [0.05, 0.04, 0.03];
+        let blended = RAGEngine::blend_maxsim(&fused, &[-0.2, 0.1, 0.9]);
+        assert!(blended.iter().all(|s| (0.03..=0.05).contains(s)));
+        // MaxSim lifts the third candidate past the second
+        assert!(blended[2] > blended[1]);
+
+        // Tied fused scores are ordered by MaxSim alone
+        let tied = RAGEngine::blend_maxsim(&[0.2, 0.2], &[0.3, 0.7]);
+        assert!(tied[1] > tied[0] && tied[0] >= 0.2);
+    }
+
+    #[test]
+    fn test_mismatch_is_detected_through_context() {
+        let err = anyhow::Error::from(EmbeddingDimensionMismatch { index: 384, model: 768 })
+            .context("search failed");
+        assert!(EmbeddingDimensionMismatch::is_mismatch(&err));
+        assert!(!EmbeddingDimensionMismatch::is_mismatch(&anyhow::anyhow!("other")));
+    }
+}
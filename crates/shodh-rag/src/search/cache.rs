@@ -0,0 +1,221 @@
+//! LRU cache of ranked search results
+//!
+//! Keyed by a hash of the normalized query, result count, metadata filter and
+//! rerank options. Entries expire after a short TTL and are dropped whenever
+//! documents change in the space they were searched in.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use lru::LruCache;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::config::QueryCacheConfig;
+use crate::types::{ComprehensiveResult, MetadataFilter, RerankOptions};
+
+struct CachedResults {
+    /// Space the search was limited to; None searched every space
+    space_id: Option<String>,
+    stored_at: Instant,
+    results: Vec<ComprehensiveResult>,
+}
+
+/// Hit/miss counters and occupancy, for diagnostics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryCacheStats {
+    pub enabled: bool,
+    pub entries: usize,
+    pub capacity: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+pub struct QueryCache {
+    /// None when caching is disabled
+    entries: Option<Mutex<LruCache<u64, CachedResults>>>,
+    ttl: Duration,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl QueryCache {
+    pub fn new(config: &QueryCacheConfig) -> Self {
+        let entries = NonZeroUsize::new(config.capacity)
+            .filter(|_| config.enabled)
+            .map(|cap| Mutex::new(LruCache::new(cap)));
+        Self {
+            entries,
+            ttl: Duration::from_secs(config.ttl_secs),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Cache key for a search. Whitespace and case in the query are ignored.
+    pub fn key(
+        query: &str,
+        k: usize,
+        filter: Option<&MetadataFilter>,
+        rerank: &RerankOptions,
+    ) -> u64 {
+        let normalized = query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        let mut hasher = DefaultHasher::new();
+        normalized.hash(&mut hasher);
+        k.hash(&mut hasher);
+        // Via Value so `custom` map keys serialize in sorted order
+        filter
+            .and_then(|f| serde_json::to_value(f).ok())
+            .map(|v| v.to_string())
+            .hash(&mut hasher);
+        rerank.cross_encoder.hash(&mut hasher);
+        rerank.top_k.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Fresh results for `key`, counting a hit or miss
+    pub fn get(&self, key: u64) -> Option<Vec<ComprehensiveResult>> {
+        let entries = self.entries.as_ref()?;
+        let mut entries = entries.lock();
+        let fresh = match entries.get(&key) {
+            Some(cached) if cached.stored_at.elapsed() <= self.ttl => Some(cached.results.clone()),
+            Some(_) => {
+                entries.pop(&key);
+                None
+            }
+            None => None,
+        };
+        let counter = if fresh.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        fresh
+    }
+
+    pub fn insert(&self, key: u64, space_id: Option<String>, results: Vec<ComprehensiveResult>) {
+        if let Some(entries) = &self.entries {
+            entries.lock().put(key, CachedResults { space_id, stored_at: Instant::now(), results });
+        }
+    }
+
+    /// Drop entries that could include documents from `space_id`: searches
+    /// in that space and unscoped searches
+    pub fn invalidate_space(&self, space_id: &str) {
+        if let Some(entries) = &self.entries {
+            let mut entries = entries.lock();
+            let stale: Vec<u64> = entries
+                .iter()
+                .filter(|(_, cached)| !matches!(cached.space_id.as_deref(), Some(s) if s != space_id))
+                .map(|(key, _)| *key)
+                .collect();
+            for key in stale {
+                entries.pop(&key);
+            }
+        }
+    }
+
+    pub fn invalidate_all(&self) {
+        if let Some(entries) = &self.entries {
+            entries.lock().clear();
+        }
+    }
+
+    pub fn stats(&self) -> QueryCacheStats {
+        let (entries, capacity) = self
+            .entries
+            .as_ref()
+            .map(|e| {
+                let e = e.lock();
+                (e.len(), e.cap().get())
+            })
+            .unwrap_or((0, 0));
+        QueryCacheStats {
+            enabled: self.entries.is_some(),
+            entries,
+            capacity,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Citation;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn result(snippet: &str) -> ComprehensiveResult {
+        ComprehensiveResult {
+            id: Uuid::new_v4(),
+            score: 1.0,
+            metadata: HashMap::new(),
+            citation: Citation::default(),
+            snippet: snippet.to_string(),
+            source_index: "test".to_string(),
+        }
+    }
+
+    fn space_filter(space: &str) -> MetadataFilter {
+        MetadataFilter { space_id: Some(space.to_string()), ..Default::default() }
+    }
+
+    fn config(ttl_secs: u64) -> QueryCacheConfig {
+        QueryCacheConfig { enabled: true, capacity: 8, ttl_secs }
+    }
+
+    #[test]
+    fn test_hits_ignore_query_whitespace_and_case() {
+        let cache = QueryCache::new(&config(60));
+        let rerank = RerankOptions::default();
+        let key = QueryCache::key("Quarterly  revenue", 10, None, &rerank);
+        assert!(cache.get(key).is_none());
+
+        cache.insert(key, None, vec![result("q3")]);
+        let again = QueryCache::key(" quarterly revenue ", 10, None, &rerank);
+        assert_eq!(cache.get(again).unwrap()[0].snippet, "q3");
+        assert_ne!(key, QueryCache::key("quarterly revenue", 5, None, &rerank));
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+    }
+
+    #[test]
+    fn test_expired_entries_miss() {
+        let cache = QueryCache::new(&config(0));
+        let key = QueryCache::key("q", 10, None, &RerankOptions::default());
+        cache.insert(key, None, vec![result("a")]);
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(cache.get(key).is_none());
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[test]
+    fn test_invalidate_space_keeps_other_spaces() {
+        let cache = QueryCache::new(&config(60));
+        let rerank = RerankOptions::default();
+        let a = QueryCache::key("q", 10, Some(&space_filter("a")), &rerank);
+        let b = QueryCache::key("q", 10, Some(&space_filter("b")), &rerank);
+        let all = QueryCache::key("q", 10, None, &rerank);
+        cache.insert(a, Some("a".to_string()), vec![result("a")]);
+        cache.insert(b, Some("b".to_string()), vec![result("b")]);
+        cache.insert(all, None, vec![result("all")]);
+
+        cache.invalidate_space("a");
+        assert!(cache.get(a).is_none());
+        assert!(cache.get(all).is_none());
+        assert!(cache.get(b).is_some());
+    }
+
+    #[test]
+    fn test_disabled_cache_stores_nothing() {
+        let cache = QueryCache::new(&QueryCacheConfig { enabled: false, ..config(60) });
+        let key = QueryCache::key("q", 10, None, &RerankOptions::default());
+        cache.insert(key, None, vec![result("a")]);
+        assert!(cache.get(key).is_none());
+        assert!(!cache.stats().enabled);
+    }
+}
@@ -64,12 +64,16 @@ pub fn reciprocal_rank_fusion(
 /// Unlike plain RRF which discards quality signals, this modulates rank-based scores
 /// by the original similarity/BM25 scores so high-confidence matches get a boost.
 /// `score_weight` controls the blend: 0.0 = pure RRF, higher = more score influence.
+/// `vector_weight` and `text_weight` scale each list's contribution; 1.0 each is
+/// unweighted RRF.
 pub fn score_aware_rrf(
     vector_results: Vec<(String, f32)>,
     fts_results: Vec<(String, f32)>,
     k: usize,
     top_k: usize,
     score_weight: f32,
+    vector_weight: f32,
+    text_weight: f32,
 ) -> Vec<(String, f32, HybridSource)> {
     let normalize = |results: &[(String, f32)]| -> HashMap<String, f32> {
         if results.is_empty() {
@@ -96,7 +100,7 @@ pub fn score_aware_rrf(
     for (rank, (id, _)) in vector_results.iter().enumerate() {
         let rrf = 1.0 / (k as f32 + rank as f32 + 1.0);
         let orig_score = vec_norm.get(id).copied().unwrap_or(0.0);
-        let combined = rrf * (1.0 + score_weight * orig_score);
+        let combined = vector_weight * rrf * (1.0 + score_weight * orig_score);
         scores
             .entry(id.clone())
             .and_modify(|(s, src)| {
@@ -109,7 +113,7 @@ pub fn score_aware_rrf(
     for (rank, (id, _)) in fts_results.iter().enumerate() {
         let rrf = 1.0 / (k as f32 + rank as f32 + 1.0);
         let orig_score = fts_norm.get(id).copied().unwrap_or(0.0);
-        let combined = rrf * (1.0 + score_weight * orig_score);
+        let combined = text_weight * rrf * (1.0 + score_weight * orig_score);
         scores
             .entry(id.clone())
             .and_modify(|(s, src)| {
@@ -188,3 +192,24 @@ pub fn weighted_fusion(
     merged.truncate(top_k);
     merged
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranked(ids: &[&str]) -> Vec<(String, f32)> {
+        ids.iter().enumerate().map(|(i, id)| (id.to_string(), 1.0 - i as f32 * 0.1)).collect()
+    }
+
+    #[test]
+    fn test_fusion_weights_shift_ranking() {
+        let vector = ranked(&["v1", "v2"]);
+        let text = ranked(&["t1", "t2"]);
+
+        let vector_heavy = score_aware_rrf(vector.clone(), text.clone(), 60, 4, 0.3, 2.0, 0.5);
+        assert_eq!(vector_heavy[0].0, "v1");
+
+        let text_heavy = score_aware_rrf(vector, text, 60, 4, 0.3, 0.5, 2.0);
+        assert_eq!(text_heavy[0].0, "t1");
+    }
+}
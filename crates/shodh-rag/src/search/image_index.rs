@@ -0,0 +1,164 @@
+//! Image vectors keyed by image id, kept apart from the text index
+//!
+//! Image collections are small next to document chunks, so search is a
+//! brute-force cosine scan over a JSON file rather than another LanceDB table.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageEntry {
+    /// Empty when no image embedding model was loaded at upload time
+    pub vector: Vec<f32>,
+    pub file_path: String,
+    /// OCR text, shown alongside visual matches
+    pub ocr_text: String,
+    /// blake3 of the image bytes, for skipping re-uploads; empty when unknown
+    #[serde(default)]
+    pub content_hash: String,
+    /// Space the image was uploaded into; None for global uploads
+    #[serde(default)]
+    pub space_id: Option<String>,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+pub struct ImageIndex {
+    entries: HashMap<String, ImageEntry>,
+    path: Option<PathBuf>,
+}
+
+impl ImageIndex {
+    /// Load from `path`, starting empty when the file does not exist yet
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .with_context(|| format!("Corrupt image index at {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        Ok(Self { entries, path: Some(path) })
+    }
+
+    /// Write back to the file it was loaded from; a no-op for in-memory indexes
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(&self.entries)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn insert(&mut self, image_id: String, entry: ImageEntry) {
+        self.entries.insert(image_id, entry);
+    }
+
+    pub fn remove(&mut self, image_id: &str) -> Option<ImageEntry> {
+        self.entries.remove(image_id)
+    }
+
+    pub fn get(&self, image_id: &str) -> Option<&ImageEntry> {
+        self.entries.get(image_id)
+    }
+
+    /// Id of an image already indexed in `space_id` with these exact bytes
+    pub fn find_by_hash(&self, content_hash: &str, space_id: Option<&str>) -> Option<&str> {
+        if content_hash.is_empty() {
+            return None;
+        }
+        self.entries
+            .iter()
+            .find(|(_, entry)| entry.content_hash == content_hash && entry.space_id.as_deref() == space_id)
+            .map(|(id, _)| id.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Top `limit` images by cosine similarity to `query`, best first.
+    /// Entries without a vector or from a model with a different dimension
+    /// are skipped.
+    pub fn search(&self, query: &[f32], limit: usize) -> Vec<(String, f32)> {
+        let mut scored: Vec<(String, f32)> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.vector.len() == query.len())
+            .map(|(id, entry)| (id.clone(), cosine(query, &entry.vector)))
+            .collect();
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(limit);
+        scored
+    }
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denom = norm(a) * norm(b);
+    if denom > 1e-12 { dot / denom } else { 0.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(vector: Vec<f32>) -> ImageEntry {
+        ImageEntry {
+            vector,
+            file_path: String::new(),
+            ocr_text: String::new(),
+            content_hash: String::new(),
+            space_id: None,
+            added_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_search_ranks_by_cosine_and_skips_other_dimensions() {
+        let mut index = ImageIndex::default();
+        index.insert("diagram".into(), entry(vec![0.9, 0.1, 0.0]));
+        index.insert("photo".into(), entry(vec![0.0, 1.0, 0.0]));
+        index.insert("old-model".into(), entry(vec![1.0, 0.0]));
+
+        let hits = index.search(&[2.0, 0.0, 0.0], 10);
+        assert_eq!(hits.iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>(), vec!["diagram", "photo"]);
+        assert!(hits[0].1 > 0.99 && hits[1].1.abs() < 1e-6);
+
+        index.remove("diagram");
+        assert_eq!(index.search(&[2.0, 0.0, 0.0], 1)[0].0, "photo");
+    }
+
+    #[test]
+    fn test_find_by_hash_is_per_space() {
+        let mut index = ImageIndex::default();
+        index.insert("scan".into(), ImageEntry {
+            content_hash: "abc".into(),
+            space_id: Some("legal".into()),
+            ..entry(Vec::new())
+        });
+        index.insert("unhashed".into(), entry(Vec::new()));
+
+        assert_eq!(index.find_by_hash("abc", Some("legal")), Some("scan"));
+        assert_eq!(index.find_by_hash("abc", Some("finance")), None);
+        assert_eq!(index.find_by_hash("abc", None), None);
+        assert_eq!(index.find_by_hash("", None), None);
+    }
+}
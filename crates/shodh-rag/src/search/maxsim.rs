@@ -0,0 +1,47 @@
+//! Late-interaction (ColBERT-style) scoring over per-token embeddings.
+
+/// MaxSim: for each query token, take its best dot product against any
+/// document token, then sum. With unit-length token vectors this is a sum of
+/// cosine similarities, so the maximum is `query.len()`.
+pub fn maxsim_score(query: &[Vec<f32>], doc: &[Vec<f32>]) -> f32 {
+    if doc.is_empty() {
+        return 0.0;
+    }
+    query
+        .iter()
+        .map(|q| {
+            doc.iter()
+                .map(|d| q.iter().zip(d).map(|(a, b)| a * b).sum::<f32>())
+                .fold(f32::NEG_INFINITY, f32::max)
+        })
+        .sum()
+}
+
+/// `maxsim_score` divided by the number of query tokens, giving a score in
+/// [-1, 1] that is comparable across queries of different lengths.
+pub fn maxsim_score_normalized(query: &[Vec<f32>], doc: &[Vec<f32>]) -> f32 {
+    if query.is_empty() {
+        return 0.0;
+    }
+    maxsim_score(query, doc) / query.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maxsim_takes_best_match_per_query_token() {
+        let query = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        let doc = vec![vec![1.0, 0.0], vec![0.6, 0.8]];
+        // token 0 best = 1.0 (doc[0]); token 1 best = 0.8 (doc[1])
+        assert!((maxsim_score(&query, &doc) - 1.8).abs() < 1e-6);
+        assert!((maxsim_score_normalized(&query, &doc) - 0.9).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_maxsim_empty_inputs() {
+        assert_eq!(maxsim_score(&[vec![1.0]], &[]), 0.0);
+        assert_eq!(maxsim_score_normalized(&[], &[vec![1.0]]), 0.0);
+    }
+}
@@ -1,5 +1,11 @@
+pub mod cache;
 pub mod hybrid;
+pub mod image_index;
+pub mod maxsim;
 pub mod text_search;
 
+pub use cache::{QueryCache, QueryCacheStats};
 pub use hybrid::{reciprocal_rank_fusion, weighted_fusion, HybridResult, HybridSource};
-pub use text_search::TextSearch;
+pub use image_index::{ImageEntry, ImageIndex};
+pub use maxsim::{maxsim_score, maxsim_score_normalized};
+pub use text_search::{Bm25Params, TextSearch};
@@ -1,9 +1,31 @@
 use anyhow::{Context, Result};
+use std::collections::HashMap;
 use std::path::Path;
 use tantivy::collector::TopDocs;
-use tantivy::query::QueryParser;
-use tantivy::schema::{self, Schema, STORED, STRING, TEXT, Value as TantivyValue};
-use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument};
+use tantivy::postings::Postings;
+use tantivy::query::{Query, QueryParser};
+use tantivy::schema::{self, IndexRecordOption, Schema, STORED, STRING, TEXT, Value as TantivyValue};
+use tantivy::{doc, DocAddress, DocId, DocSet, Index, IndexReader, IndexWriter, ReloadPolicy, Searcher, TantivyDocument, Term};
+
+/// Candidates fetched per requested result before BM25 rescoring
+const BM25_CANDIDATE_FACTOR: usize = 3;
+
+/// BM25 tuning. Tantivy's built-in scorer fixes k1 = 1.2 and b = 0.75;
+/// these let keyword-heavy corpora raise term saturation or length
+/// normalization.
+#[derive(Debug, Clone, Copy)]
+pub struct Bm25Params {
+    /// Term frequency saturation
+    pub k1: f32,
+    /// Document length normalization (0.0 = none, 1.0 = full)
+    pub b: f32,
+}
+
+impl Default for Bm25Params {
+    fn default() -> Self {
+        Self { k1: 1.2, b: 0.75 }
+    }
+}
 
 pub struct TextSearch {
     index: Index,
@@ -142,23 +164,88 @@ impl TextSearch {
         source_filter: Option<&str>,
     ) -> Result<Vec<(String, f32)>> {
         let searcher = self.reader.searcher();
+        let parsed_query = self.parse_query(query)?;
+        let matches = self.collect_matches(&searcher, parsed_query.as_ref(), k, source_filter)?;
+        Ok(matches.into_iter().map(|(id, _, score)| (id, score)).collect())
+    }
+
+    /// Search scored with BM25 using `params`. Tantivy's matches form the
+    /// candidate pool, which is rescored from the index's document
+    /// frequencies and field lengths. Scores are divided by the top score,
+    /// so they fall in (0, 1] like vector similarities.
+    pub fn search_bm25(
+        &self,
+        query: &str,
+        k: usize,
+        source_filter: Option<&str>,
+        params: Bm25Params,
+    ) -> Result<Vec<(String, f32)>> {
+        let searcher = self.reader.searcher();
+        let parsed_query = self.parse_query(query)?;
+        let candidates = self.collect_matches(
+            &searcher,
+            parsed_query.as_ref(),
+            k * BM25_CANDIDATE_FACTOR,
+            source_filter,
+        )?;
+        if candidates.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut terms: Vec<Term> = Vec::new();
+        parsed_query.query_terms(&mut |term, _| {
+            if !terms.contains(term) {
+                terms.push(term.clone());
+            }
+        });
+
+        let addresses: Vec<DocAddress> = candidates.iter().map(|(_, addr, _)| *addr).collect();
+        let scores = bm25_scores(&searcher, &terms, &addresses, params)?;
+
+        let mut results: Vec<(String, f32)> = candidates
+            .into_iter()
+            .map(|(id, addr, _)| (id, scores.get(&addr).copied().unwrap_or(0.0)))
+            .collect();
+        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        results.truncate(k);
+
+        if let Some(max_score) = results.first().map(|(_, s)| *s) {
+            if max_score > 0.0 {
+                for item in &mut results {
+                    item.1 /= max_score;
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    fn parse_query(&self, query: &str) -> Result<Box<dyn Query>> {
         let query_parser =
             QueryParser::for_index(&self.index, vec![self.text_field, self.title_field]);
 
-        let parsed_query = match query_parser.parse_query(query) {
-            Ok(q) => q,
+        match query_parser.parse_query(query) {
+            Ok(q) => Ok(q),
             Err(_) => {
                 let escaped_query = query.replace('"', "");
                 let fallback_parser = QueryParser::for_index(&self.index, vec![self.text_field]);
-                fallback_parser.parse_query(&format!("\"{}\"", escaped_query))?
+                Ok(fallback_parser.parse_query(&format!("\"{}\"", escaped_query))?)
             }
-        };
+        }
+    }
 
+    /// Top `k` matches with Tantivy's score, after the optional source filter
+    fn collect_matches(
+        &self,
+        searcher: &Searcher,
+        parsed_query: &dyn Query,
+        k: usize,
+        source_filter: Option<&str>,
+    ) -> Result<Vec<(String, DocAddress, f32)>> {
         // Fetch extra candidates when filtering to compensate for post-filter reduction.
         // Without this, source-filtered queries return fewer results than vector search,
         // causing asymmetric fusion.
         let fetch_limit = if source_filter.is_some() { k * 3 } else { k };
-        let top_docs = searcher.search(&parsed_query, &TopDocs::with_limit(fetch_limit))?;
+        let top_docs = searcher.search(parsed_query, &TopDocs::with_limit(fetch_limit))?;
 
         let mut results = Vec::with_capacity(k);
         for (score, doc_address) in top_docs {
@@ -176,7 +263,7 @@ impl TextSearch {
 
                 if let Some(id_val) = doc.get_first(self.id_field) {
                     if let Some(id_text) = id_val.as_str() {
-                        results.push((id_text.to_string(), score));
+                        results.push((id_text.to_string(), doc_address, score));
                         if results.len() >= k {
                             break;
                         }
@@ -215,6 +302,16 @@ impl TextSearch {
         self.delete_matching_source(source, false)
     }
 
+    /// Replace a source's documents with `chunks` in a single commit. The new
+    /// documents sit uncommitted in the writer while the delete scans the
+    /// committed index, so only the old ones are matched, and the delete's
+    /// commit publishes additions and removals together.
+    pub fn replace_source(&self, source: &str, chunks: &[(String, String, String, String)]) -> Result<()> {
+        self.index_chunks_batch(chunks)?;
+        self.delete_by_source(source)?;
+        self.commit()
+    }
+
     /// Delete all documents whose source starts with the given prefix.
     pub fn delete_by_source_prefix(&self, prefix: &str) -> Result<()> {
         self.delete_matching_source(prefix, true)
@@ -273,6 +370,32 @@ impl TextSearch {
         Ok(())
     }
 
+    /// Rewrite the index without its deleted documents. A delete only marks
+    /// documents as gone; their stored text stays in the segment files until
+    /// the segments are merged and the replaced files are garbage collected.
+    pub fn purge_deleted(&self) -> Result<()> {
+        let mut writer = self.writer.lock();
+        writer.commit().context("Tantivy commit failed")?;
+        let segment_ids = self.index.searchable_segment_ids()?;
+        if !segment_ids.is_empty() {
+            writer
+                .merge(&segment_ids)
+                .wait()
+                .context("Tantivy segment merge failed")?;
+        }
+        self.reader.reload()?;
+        let gc = writer
+            .garbage_collect_files()
+            .wait()
+            .context("Tantivy garbage collection failed")?;
+        tracing::info!(
+            segments = segment_ids.len(),
+            deleted_files = gc.deleted_files.len(),
+            "Tantivy: purged deleted documents"
+        );
+        Ok(())
+    }
+
     pub fn clear(&self) -> Result<()> {
         let mut writer = self.writer.lock();
         writer.delete_all_documents()?;
@@ -292,3 +415,159 @@ impl TextSearch {
         self.count().unwrap_or(0) == 0
     }
 }
+
+/// BM25 score of each document for `terms`, summed across the fields the
+/// terms belong to. Each field uses its own average length.
+fn bm25_scores(
+    searcher: &Searcher,
+    terms: &[Term],
+    docs: &[DocAddress],
+    params: Bm25Params,
+) -> Result<HashMap<DocAddress, f32>> {
+    let total_docs = searcher.num_docs().max(1) as f32;
+
+    // Documents grouped by segment and sorted, so each posting list is
+    // walked forward once per segment
+    let mut by_segment: HashMap<u32, Vec<DocId>> = HashMap::new();
+    for addr in docs {
+        by_segment.entry(addr.segment_ord).or_default().push(addr.doc_id);
+    }
+    for ids in by_segment.values_mut() {
+        ids.sort_unstable();
+        ids.dedup();
+    }
+
+    let mut avg_len_by_field = HashMap::new();
+    let mut scores: HashMap<DocAddress, f32> = HashMap::new();
+
+    for term in terms {
+        let field = term.field();
+        let doc_freq = searcher.doc_freq(term)? as f32;
+        if doc_freq == 0.0 {
+            continue;
+        }
+        let idf = (1.0 + (total_docs - doc_freq + 0.5) / (doc_freq + 0.5)).ln();
+
+        let avg_len = match avg_len_by_field.get(&field) {
+            Some(len) => *len,
+            None => {
+                let mut tokens = 0u64;
+                for segment in searcher.segment_readers() {
+                    tokens += segment.inverted_index(field)?.total_num_tokens();
+                }
+                let len = (tokens as f32 / total_docs).max(1.0);
+                avg_len_by_field.insert(field, len);
+                len
+            }
+        };
+
+        for (&segment_ord, doc_ids) in &by_segment {
+            let segment = searcher.segment_reader(segment_ord);
+            let Some(mut postings) = segment
+                .inverted_index(field)?
+                .read_postings(term, IndexRecordOption::WithFreqs)?
+            else {
+                continue;
+            };
+            let fieldnorms = segment.get_fieldnorms_reader(field)?;
+
+            for &doc_id in doc_ids {
+                if postings.doc() > doc_id || postings.seek(doc_id) != doc_id {
+                    continue;
+                }
+                let tf = postings.term_freq() as f32;
+                let len = fieldnorms.fieldnorm(doc_id) as f32;
+                let norm = params.k1 * (1.0 - params.b + params.b * len / avg_len);
+                *scores.entry(DocAddress::new(segment_ord, doc_id)).or_insert(0.0) +=
+                    idf * tf * (params.k1 + 1.0) / (tf + norm);
+            }
+        }
+    }
+
+    Ok(scores)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_with(chunks: &[(&str, &str)]) -> (TextSearch, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("shodh_bm25_{}", uuid::Uuid::new_v4()));
+        let search = TextSearch::new(dir.to_str().unwrap()).unwrap();
+        let batch: Vec<(String, String, String, String)> = chunks
+            .iter()
+            .map(|(id, text)| (id.to_string(), text.to_string(), String::new(), "test".to_string()))
+            .collect();
+        search.index_chunks_batch(&batch).unwrap();
+        search.commit().unwrap();
+        (search, dir)
+    }
+
+    #[test]
+    fn test_bm25_scores_are_normalized_and_respect_length() {
+        let long_tail = "filler ".repeat(200);
+        let (search, dir) = index_with(&[
+            ("short", "indemnification clause"),
+            ("long", &format!("indemnification clause {}", long_tail)),
+            ("other", "unrelated text"),
+        ]);
+
+        let results = search.search_bm25("indemnification", 10, None, Bm25Params::default()).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, "short");
+        assert!((results[0].1 - 1.0).abs() < 1e-6);
+        assert!(results[1].1 < 1.0);
+
+        // Without length normalization both matches score the same
+        let flat = search
+            .search_bm25("indemnification", 10, None, Bm25Params { k1: 1.2, b: 0.0 })
+            .unwrap();
+        assert!((flat[0].1 - flat[1].1).abs() < 1e-6);
+
+        drop(search);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_replace_source_keeps_only_new_chunks() {
+        let (search, dir) = index_with(&[("old-1", "retainer agreement"), ("old-2", "retainer invoice")]);
+        let chunk = |id: &str, text: &str| (id.to_string(), text.to_string(), String::new(), "test".to_string());
+
+        search.replace_source("test", &[chunk("new-1", "retainer agreement amended")]).unwrap();
+        let ids: Vec<String> = search.search("retainer", 10).unwrap().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec!["new-1".to_string()]);
+
+        // An empty replacement just removes the source
+        search.replace_source("test", &[]).unwrap();
+        assert!(search.search("retainer", 10).unwrap().is_empty());
+
+        drop(search);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_purge_deleted_removes_stored_text_from_disk() {
+        let secret = "quarterly zqxjvault figures";
+        let (search, dir) = index_with(&[("secret", secret), ("kept", "public notice")]);
+        let on_disk = || {
+            std::fs::read_dir(dir.join("tantivy_index")).unwrap().any(|entry| {
+                let bytes = std::fs::read(entry.unwrap().path()).unwrap_or_default();
+                bytes.windows(secret.len()).any(|w| w == secret.as_bytes())
+            })
+        };
+        assert!(on_disk());
+
+        // Deleting alone leaves the stored text in the segment files
+        search.delete_by_id("secret").unwrap();
+        search.commit().unwrap();
+        assert!(on_disk());
+
+        search.purge_deleted().unwrap();
+        assert!(!on_disk());
+        assert_eq!(search.search("notice", 10).unwrap().len(), 1);
+        assert!(search.search("zqxjvault", 10).unwrap().is_empty());
+
+        drop(search);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
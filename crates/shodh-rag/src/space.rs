@@ -316,6 +316,26 @@ impl SpaceManager {
         Ok(space_docs.get(space_id).cloned().unwrap_or_default())
     }
 
+    /// Add a space created elsewhere (e.g. imported from an archive), keeping
+    /// its id and document list. Fails if a space with that id exists.
+    pub fn insert_space(&self, space: Space) -> Result<(), String> {
+        let mut spaces = self.spaces.lock().map_err(|e| e.to_string())?;
+        if spaces.iter().any(|s| s.id == space.id) {
+            return Err(format!("Space with ID {} already exists", space.id));
+        }
+        {
+            let mut space_docs = self.space_documents.lock().map_err(|e| e.to_string())?;
+            let mut doc_spaces = self.document_spaces.lock().map_err(|e| e.to_string())?;
+            space_docs.insert(space.id.clone(), space.documents.clone());
+            for doc_id in &space.documents {
+                doc_spaces.insert(doc_id.clone(), space.id.clone());
+            }
+        }
+        spaces.push(space);
+        drop(spaces);
+        self.save_spaces()
+    }
+
     pub fn set_space_metadata(&self, space_id: &str, key: &str, value: &str) -> Result<(), String> {
         let mut spaces = self.spaces.lock().map_err(|e| e.to_string())?;
         let space = spaces.iter_mut().find(|s| s.id == space_id)
@@ -0,0 +1,297 @@
+//! Portable space archives
+//!
+//! A `.tar.zst` holding everything needed to recreate a space on another
+//! install, independent of either machine's data directory:
+//!
+//! - `manifest.json` — `ArchiveManifest`: schema version and the embedding
+//!   model the vectors came from
+//! - `space.json` — the `Space` record, including its document list and
+//!   metadata (system prompt, LLM settings)
+//! - `chunks.jsonl` — one chunk per line, without its vector
+//! - `embeddings.f32` — little-endian f32 vectors, `embedding_dimension`
+//!   values per chunk, in `chunks.jsonl` order
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+
+use crate::space::Space;
+use crate::types::ChunkRecord;
+
+/// Bumped when the archive layout changes incompatibly
+pub const ARCHIVE_SCHEMA_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const SPACE_FILE: &str = "space.json";
+const CHUNKS_FILE: &str = "chunks.jsonl";
+const EMBEDDINGS_FILE: &str = "embeddings.f32";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveManifest {
+    pub schema_version: u32,
+    pub created_at: String,
+    pub space_id: String,
+    pub space_name: String,
+    pub embedding_model: String,
+    pub embedding_dimension: usize,
+    pub document_count: usize,
+    pub chunk_count: usize,
+}
+
+/// `ChunkRecord` as stored in `chunks.jsonl`; the vector lives in `embeddings.f32`
+#[derive(Serialize, Deserialize)]
+struct ArchivedChunk {
+    id: String,
+    doc_id: String,
+    chunk_index: u32,
+    text: String,
+    title: String,
+    source: String,
+    heading: String,
+    metadata_json: String,
+    citation_json: String,
+    created_at: i64,
+}
+
+pub struct SpaceArchive {
+    pub manifest: ArchiveManifest,
+    pub space: Space,
+    pub chunks: Vec<ChunkRecord>,
+}
+
+impl SpaceArchive {
+    pub fn new(space: Space, chunks: Vec<ChunkRecord>, embedding_model: String, embedding_dimension: usize) -> Self {
+        let document_count = chunks.iter()
+            .map(|c| c.doc_id.as_str())
+            .collect::<std::collections::HashSet<_>>()
+            .len();
+        let manifest = ArchiveManifest {
+            schema_version: ARCHIVE_SCHEMA_VERSION,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            space_id: space.id.clone(),
+            space_name: space.name.clone(),
+            embedding_model,
+            embedding_dimension,
+            document_count,
+            chunk_count: chunks.len(),
+        };
+        Self { manifest, space, chunks }
+    }
+
+    /// Why the archived vectors can't be searched with the given embedding
+    /// model, if they can't
+    pub fn embedding_mismatch(&self, model: &str, dimension: usize) -> Option<String> {
+        let m = &self.manifest;
+        (m.embedding_model != model || m.embedding_dimension != dimension).then(|| format!(
+            "archive was embedded with '{}' ({} dimensions) but this install uses '{}' ({} dimensions)",
+            m.embedding_model, m.embedding_dimension, model, dimension
+        ))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let dimension = self.manifest.embedding_dimension;
+        let mut chunks_jsonl = Vec::new();
+        let mut embeddings = Vec::with_capacity(self.chunks.len() * dimension * 4);
+        for chunk in &self.chunks {
+            if chunk.vector.len() != dimension {
+                return Err(anyhow!(
+                    "Chunk {} has {} embedding values, manifest says {}",
+                    chunk.id, chunk.vector.len(), dimension
+                ));
+            }
+            serde_json::to_writer(&mut chunks_jsonl, &ArchivedChunk::from(chunk))?;
+            chunks_jsonl.push(b'\n');
+            for value in &chunk.vector {
+                embeddings.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        let encoder = zstd::Encoder::new(file, 0)?;
+        let mut tar = tar::Builder::new(encoder);
+        append_file(&mut tar, MANIFEST_FILE, &serde_json::to_vec_pretty(&self.manifest)?)?;
+        append_file(&mut tar, SPACE_FILE, &serde_json::to_vec_pretty(&self.space)?)?;
+        append_file(&mut tar, CHUNKS_FILE, &chunks_jsonl)?;
+        append_file(&mut tar, EMBEDDINGS_FILE, &embeddings)?;
+        tar.into_inner()?.finish()?.flush()?;
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut tar = tar::Archive::new(zstd::Decoder::new(file)?);
+
+        let (mut manifest, mut space, mut chunks_jsonl, mut embeddings) = (None, None, None, None);
+        for entry in tar.entries()? {
+            let mut entry = entry?;
+            let name = entry.path()?.to_string_lossy().to_string();
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data)?;
+            match name.as_str() {
+                MANIFEST_FILE => manifest = Some(serde_json::from_slice::<ArchiveManifest>(&data).context("Invalid manifest")?),
+                SPACE_FILE => space = Some(serde_json::from_slice::<Space>(&data).context("Invalid space record")?),
+                CHUNKS_FILE => chunks_jsonl = Some(data),
+                EMBEDDINGS_FILE => embeddings = Some(data),
+                other => tracing::debug!(entry = %other, "Skipping unknown archive entry"),
+            }
+        }
+
+        let manifest = manifest.context("Archive has no manifest.json — not a space archive")?;
+        if manifest.schema_version > ARCHIVE_SCHEMA_VERSION {
+            return Err(anyhow!(
+                "Archive schema version {} is newer than supported version {}",
+                manifest.schema_version, ARCHIVE_SCHEMA_VERSION
+            ));
+        }
+        let space = space.context("Archive has no space.json")?;
+        let chunks_jsonl = chunks_jsonl.context("Archive has no chunks.jsonl")?;
+        let embeddings = embeddings.context("Archive has no embeddings.f32")?;
+
+        let dimension = manifest.embedding_dimension;
+        let mut vectors = embeddings
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect::<Vec<_>>()
+            .into_iter();
+        let mut chunks = Vec::with_capacity(manifest.chunk_count);
+        for line in BufReader::new(chunks_jsonl.as_slice()).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let archived: ArchivedChunk = serde_json::from_str(&line).context("Invalid chunk record")?;
+            let vector: Vec<f32> = vectors.by_ref().take(dimension).collect();
+            if vector.len() != dimension {
+                return Err(anyhow!("embeddings.f32 is shorter than chunks.jsonl"));
+            }
+            chunks.push(archived.into_record(vector, &space.id));
+        }
+        if chunks.len() != manifest.chunk_count {
+            return Err(anyhow!(
+                "Archive holds {} chunks but the manifest lists {}",
+                chunks.len(), manifest.chunk_count
+            ));
+        }
+
+        Ok(Self { manifest, space, chunks })
+    }
+}
+
+fn append_file<W: Write>(tar: &mut tar::Builder<W>, name: &str, data: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    tar.append_data(&mut header, name, data)
+        .with_context(|| format!("Failed to add {} to archive", name))
+}
+
+impl From<&ChunkRecord> for ArchivedChunk {
+    fn from(c: &ChunkRecord) -> Self {
+        Self {
+            id: c.id.clone(),
+            doc_id: c.doc_id.clone(),
+            chunk_index: c.chunk_index,
+            text: c.text.clone(),
+            title: c.title.clone(),
+            source: c.source.clone(),
+            heading: c.heading.clone(),
+            metadata_json: c.metadata_json.clone(),
+            citation_json: c.citation_json.clone(),
+            created_at: c.created_at,
+        }
+    }
+}
+
+impl ArchivedChunk {
+    fn into_record(self, vector: Vec<f32>, space_id: &str) -> ChunkRecord {
+        ChunkRecord {
+            id: self.id,
+            doc_id: self.doc_id,
+            chunk_index: self.chunk_index,
+            text: self.text,
+            title: self.title,
+            source: self.source,
+            heading: self.heading,
+            vector,
+            space_id: space_id.to_string(),
+            metadata_json: self.metadata_json,
+            citation_json: self.citation_json,
+            created_at: self.created_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn space() -> Space {
+        Space {
+            id: "space-1".to_string(),
+            name: "Legal".to_string(),
+            emoji: "⚖️".to_string(),
+            document_count: 1,
+            last_active: "2026-01-01T00:00:00Z".to_string(),
+            is_shared: false,
+            new_insights: 0,
+            folder_path: Some("/home/a/contracts".to_string()),
+            watching_changes: false,
+            documents: vec!["doc-1".to_string()],
+            metadata: HashMap::from([("system_prompt".to_string(), "Cite clauses".to_string())]),
+        }
+    }
+
+    fn chunk(index: u32, vector: Vec<f32>) -> ChunkRecord {
+        ChunkRecord {
+            id: format!("chunk-{}", index),
+            doc_id: "doc-1".to_string(),
+            chunk_index: index,
+            text: format!("clause {}", index),
+            title: "Lease".to_string(),
+            source: "/home/a/contracts/lease.pdf".to_string(),
+            heading: String::new(),
+            vector,
+            space_id: "space-1".to_string(),
+            metadata_json: "{}".to_string(),
+            citation_json: "{}".to_string(),
+            created_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let path = std::env::temp_dir().join(format!("shodh-archive-{}.tar.zst", uuid::Uuid::new_v4()));
+        let chunks = vec![chunk(0, vec![0.25, -1.0, 3.5]), chunk(1, vec![1.0, 0.0, -0.5])];
+        SpaceArchive::new(space(), chunks, "multilingual-e5-base".to_string(), 3)
+            .write(&path)
+            .unwrap();
+
+        let archive = SpaceArchive::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(archive.manifest.schema_version, ARCHIVE_SCHEMA_VERSION);
+        assert_eq!((archive.manifest.document_count, archive.manifest.chunk_count), (1, 2));
+        assert_eq!(archive.space.metadata["system_prompt"], "Cite clauses");
+        assert_eq!(archive.chunks[1].text, "clause 1");
+        assert_eq!(archive.chunks[1].vector, vec![1.0, 0.0, -0.5]);
+        assert_eq!(archive.chunks[0].vector, vec![0.25, -1.0, 3.5]);
+
+        assert!(archive.embedding_mismatch("multilingual-e5-base", 3).is_none());
+        assert!(archive.embedding_mismatch("multilingual-e5-base", 768).is_some());
+        assert!(archive.embedding_mismatch("bge-small", 3).is_some());
+    }
+
+    #[test]
+    fn test_write_rejects_wrong_dimension() {
+        let path = std::env::temp_dir().join(format!("shodh-archive-{}.tar.zst", uuid::Uuid::new_v4()));
+        let archive = SpaceArchive::new(space(), vec![chunk(0, vec![1.0])], "m".to_string(), 3);
+        assert!(archive.write(&path).is_err());
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,277 @@
+//! Opt-in encryption at rest for individual spaces.
+//!
+//! A space key is derived from a user passphrase with Argon2id and a random
+//! per-space salt; chunk text, title, heading, metadata and citation are
+//! sealed with AES-256-GCM before they reach LanceDB. Only the salt and an
+//! encrypted check value are persisted (in the space's metadata) — the
+//! passphrase and derived key live in memory while the space is unlocked and
+//! are dropped on `lock`.
+//!
+//! Threat model: protects the on-disk store against someone who copies the
+//! data directory or a backup. It does not protect against malware running
+//! as the user while a space is unlocked. Left in plaintext:
+//! - embeddings, which leak approximate topic similarity
+//! - source paths, doc/space ids, chunk positions and timestamps
+//! - nothing is written to the Tantivy index, so encrypted spaces are
+//!   searched by vector similarity only
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use std::collections::HashMap;
+
+use crate::storage::SearchHit;
+use crate::types::ChunkRecord;
+
+/// Prefix marking a sealed field
+pub const SEALED_PREFIX: &str = "enc:v1:";
+/// Space metadata key holding the base64 Argon2 salt
+pub const SALT_METADATA_KEY: &str = "encryption_salt";
+/// Space metadata key holding `CHECK_PLAINTEXT` sealed with the space key
+pub const CHECK_METADATA_KEY: &str = "encryption_check";
+
+const CHECK_PLAINTEXT: &str = "shodh-space-key-check";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Whether a stored field was sealed by a space key
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(SEALED_PREFIX)
+}
+
+/// AES-256 key for one space. Never serialized.
+#[derive(Clone)]
+pub struct SpaceKey([u8; 32]);
+
+impl std::fmt::Debug for SpaceKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SpaceKey(..)")
+    }
+}
+
+impl SpaceKey {
+    /// Argon2id with the crate's default cost parameters
+    pub fn derive(passphrase: &str, salt: &[u8]) -> Result<Self> {
+        let mut key = [0u8; 32];
+        argon2::Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| anyhow!("Key derivation failed: {}", e))?;
+        Ok(Self(key))
+    }
+
+    /// New key for a space with a fresh salt. Returns the key and the
+    /// (salt, check) values to persist in the space metadata.
+    pub fn create(passphrase: &str) -> Result<(Self, String, String)> {
+        if passphrase.chars().count() < 8 {
+            return Err(anyhow!("Passphrase must be at least 8 characters"));
+        }
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let key = Self::derive(passphrase, &salt)?;
+        let check = key.seal(CHECK_PLAINTEXT)?;
+        Ok((key, STANDARD.encode(salt), check))
+    }
+
+    /// Re-derive a space key and confirm it against the stored check value
+    pub fn unlock(passphrase: &str, salt_b64: &str, check: &str) -> Result<Self> {
+        let salt = STANDARD.decode(salt_b64).context("Corrupt encryption salt")?;
+        let key = Self::derive(passphrase, &salt)?;
+        match key.open(check) {
+            Ok(plain) if plain == CHECK_PLAINTEXT => Ok(key),
+            _ => Err(anyhow!("Incorrect passphrase")),
+        }
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.0))
+    }
+
+    /// Encrypt with a random nonce: `enc:v1:` + base64(nonce || ciphertext)
+    pub fn seal(&self, plaintext: &str) -> Result<String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher()
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| anyhow!("Encryption failed"))?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(format!("{}{}", SEALED_PREFIX, STANDARD.encode(sealed)))
+    }
+
+    /// Decrypt a sealed field. Unsealed values pass through unchanged.
+    pub fn open(&self, value: &str) -> Result<String> {
+        let Some(encoded) = value.strip_prefix(SEALED_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let bytes = STANDARD.decode(encoded).context("Corrupt sealed value")?;
+        if bytes.len() < NONCE_LEN {
+            return Err(anyhow!("Corrupt sealed value"));
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher()
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("Decryption failed (wrong key or tampered data)"))?;
+        String::from_utf8(plaintext).context("Decrypted value is not UTF-8")
+    }
+
+    fn seal_record(&self, chunk: &mut ChunkRecord) -> Result<()> {
+        for field in [
+            &mut chunk.text,
+            &mut chunk.title,
+            &mut chunk.heading,
+            &mut chunk.metadata_json,
+            &mut chunk.citation_json,
+        ] {
+            if !is_sealed(field) {
+                *field = self.seal(field)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn open_fields(&self, fields: [&mut String; 5]) -> Result<()> {
+        for field in fields {
+            *field = self.open(field)?;
+        }
+        Ok(())
+    }
+}
+
+/// Which spaces are encrypted, and the keys of those currently unlocked
+#[derive(Debug, Default)]
+pub struct SpaceKeyring {
+    spaces: HashMap<String, Option<SpaceKey>>,
+}
+
+impl SpaceKeyring {
+    /// Mark a space as encrypted without unlocking it
+    pub fn register_locked(&mut self, space_id: &str) {
+        self.spaces.entry(space_id.to_string()).or_insert(None);
+    }
+
+    pub fn unlock(&mut self, space_id: &str, key: SpaceKey) {
+        self.spaces.insert(space_id.to_string(), Some(key));
+    }
+
+    /// Drop the space's key from memory. Returns false if it wasn't encrypted.
+    pub fn lock(&mut self, space_id: &str) -> bool {
+        match self.spaces.get_mut(space_id) {
+            Some(key) => {
+                *key = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forget a space entirely (e.g. after it was deleted)
+    pub fn remove(&mut self, space_id: &str) {
+        self.spaces.remove(space_id);
+    }
+
+    pub fn is_encrypted(&self, space_id: &str) -> bool {
+        self.spaces.contains_key(space_id)
+    }
+
+    pub fn is_locked(&self, space_id: &str) -> bool {
+        matches!(self.spaces.get(space_id), Some(None))
+    }
+
+    /// Key for a space: `Ok(None)` if it isn't encrypted, an error if it is locked
+    pub fn key(&self, space_id: &str) -> Result<Option<&SpaceKey>> {
+        match self.spaces.get(space_id) {
+            None => Ok(None),
+            Some(Some(key)) => Ok(Some(key)),
+            Some(None) => Err(anyhow!(
+                "Space '{}' is encrypted and locked. Unlock it with its passphrase first.",
+                space_id
+            )),
+        }
+    }
+
+    /// Seal a chunk about to be stored. Returns whether it was encrypted.
+    pub fn seal_record(&self, chunk: &mut ChunkRecord) -> Result<bool> {
+        match self.key(&chunk.space_id)? {
+            Some(key) => key.seal_record(chunk).map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    /// Decrypt a stored chunk in place (e.g. before re-embedding it)
+    pub fn open_record(&self, chunk: &mut ChunkRecord) -> Result<()> {
+        if let Some(key) = self.key(&chunk.space_id)? {
+            key.open_fields([
+                &mut chunk.text,
+                &mut chunk.title,
+                &mut chunk.heading,
+                &mut chunk.metadata_json,
+                &mut chunk.citation_json,
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Decrypt hits from unlocked spaces and drop those from locked ones
+    /// or that fail to decrypt
+    pub fn open_hits(&self, hits: Vec<SearchHit>) -> Vec<SearchHit> {
+        if self.spaces.is_empty() {
+            return hits;
+        }
+        hits.into_iter()
+            .filter_map(|mut hit| match self.key(&hit.space_id) {
+                Ok(None) => Some(hit),
+                Ok(Some(key)) => {
+                    let opened = key.open_fields([
+                        &mut hit.text,
+                        &mut hit.title,
+                        &mut hit.heading,
+                        &mut hit.metadata_json,
+                        &mut hit.citation_json,
+                    ]);
+                    match opened {
+                        Ok(()) => Some(hit),
+                        Err(e) => {
+                            tracing::warn!(id = %hit.id, error = %e, "Dropping undecryptable chunk");
+                            None
+                        }
+                    }
+                }
+                Err(_) => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_roundtrip_and_wrong_passphrase() {
+        let (key, salt, check) = SpaceKey::create("correct horse battery").unwrap();
+        let sealed = key.seal("patient notes").unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("patient"));
+        assert_eq!(key.open(&sealed).unwrap(), "patient notes");
+        // Fresh nonce per seal
+        assert_ne!(sealed, key.seal("patient notes").unwrap());
+
+        let reopened = SpaceKey::unlock("correct horse battery", &salt, &check).unwrap();
+        assert_eq!(reopened.open(&sealed).unwrap(), "patient notes");
+        assert!(SpaceKey::unlock("wrong passphrase", &salt, &check).is_err());
+
+        let mut keyring = SpaceKeyring::default();
+        keyring.register_locked("legal");
+        assert!(keyring.key("legal").is_err());
+        assert!(keyring.key("other").unwrap().is_none());
+        keyring.unlock("legal", reopened);
+        assert!(keyring.key("legal").unwrap().is_some());
+        assert!(keyring.lock("legal"));
+        assert!(keyring.is_locked("legal"));
+        keyring.remove("legal");
+        assert!(!keyring.is_encrypted("legal"));
+    }
+}
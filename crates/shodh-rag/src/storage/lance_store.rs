@@ -6,6 +6,7 @@ use arrow_array::{
 use arrow_schema::{DataType, Field, Schema};
 use lancedb::query::{ExecutableQuery, QueryBase};
 use lancedb::table::OptimizeAction;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 
 use crate::types::ChunkRecord;
@@ -23,17 +24,73 @@ impl LanceStore {
             .execute()
             .await
             .context("Failed to connect to LanceDB")?;
+        Self::open(db, "documents", dimension).await
+    }
 
-        let store = Self {
+    /// Open (or create) `table_name`. An existing table keeps the dimension
+    /// it was built with, so a model change shows up as a mismatch rather
+    /// than silently mixing vector sizes; empty tables are rebuilt at the
+    /// requested dimension.
+    async fn open(db: lancedb::Connection, table_name: &str, dimension: usize) -> Result<Self> {
+        let mut store = Self {
             db,
             dimension,
-            table_name: "documents".to_string(),
+            table_name: table_name.to_string(),
         };
 
         store.ensure_table().await?;
+        if let Some(stored) = store.table_dimension().await? {
+            if stored != dimension {
+                if store.count().await? == 0 {
+                    store.recreate(dimension).await?;
+                } else {
+                    tracing::warn!(
+                        table = %store.table_name,
+                        stored_dimension = stored,
+                        configured_dimension = dimension,
+                        "Stored embeddings have a different dimension than the embedding model"
+                    );
+                    store.dimension = stored;
+                }
+            }
+        }
         Ok(store)
     }
 
+    /// Another table in the same database, e.g. a staging table for re-embedding
+    pub async fn sibling_table(&self, table_name: &str, dimension: usize) -> Result<Self> {
+        Self::open(self.db.clone(), table_name, dimension).await
+    }
+
+    /// Dimension of the vectors stored in this table
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    async fn table_dimension(&self) -> Result<Option<usize>> {
+        let table = self.db.open_table(&self.table_name).execute().await?;
+        let schema = table.schema().await?;
+        Ok(schema.field_with_name("vector").ok().and_then(|f| match f.data_type() {
+            DataType::FixedSizeList(_, n) => Some(*n as usize),
+            _ => None,
+        }))
+    }
+
+    /// Drop every row and rebuild the table for vectors of `dimension`
+    pub async fn recreate(&mut self, dimension: usize) -> Result<()> {
+        self.dimension = dimension;
+        self.clear().await
+    }
+
+    /// Remove the table entirely
+    pub async fn drop_table(self) -> Result<()> {
+        let names = self.db.table_names().execute().await?;
+        if names.contains(&self.table_name) {
+            self.db.drop_table(&self.table_name, &[]).await?;
+        }
+        Ok(())
+    }
+
     fn schema(&self) -> Arc<Schema> {
         Arc::new(Schema::new(vec![
             Field::new("id", DataType::Utf8, false),
@@ -107,25 +164,58 @@ impl LanceStore {
     }
 
     /// Public compaction entry point — opens the table and runs compact+prune.
-    pub async fn compact(&self) -> Result<()> {
+    pub async fn compact(&self) -> Result<CompactionReport> {
         let table = self.db.open_table(&self.table_name).execute().await
             .context("Failed to open table for compaction")?;
-        self.compact_table(&table).await;
-        Ok(())
+        Ok(self.compact_table(&table).await)
+    }
+
+    /// Compact, then prune every old table version rather than only those
+    /// older than the default retention window.
+    pub async fn vacuum(&self) -> Result<CompactionReport> {
+        let table = self.db.open_table(&self.table_name).execute().await
+            .context("Failed to open table for vacuum")?;
+        let mut report = self.compact_table(&table).await;
+
+        let stats = table
+            .optimize(OptimizeAction::Prune {
+                older_than: Some(chrono::Duration::zero()),
+                delete_unverified: Some(false),
+                error_if_tagged_old_versions: Some(false),
+            })
+            .await
+            .context("Failed to prune old versions")?;
+        if let Some(prune) = stats.prune {
+            report.bytes_pruned += prune.bytes_removed;
+            report.old_versions_pruned += prune.old_versions;
+        }
+        Ok(report)
     }
 
     /// Compact and prune tombstoned rows so deleted data is physically removed.
     /// Best-effort: failures are logged but don't propagate to the caller.
-    async fn compact_table(&self, table: &lancedb::Table) {
+    async fn compact_table(&self, table: &lancedb::Table) -> CompactionReport {
         match table.optimize(OptimizeAction::All).await {
             Ok(stats) => {
                 tracing::debug!(
                     compaction = ?stats.compaction,
                     "LanceDB compaction completed"
                 );
+                let mut report = CompactionReport::default();
+                if let Some(compaction) = stats.compaction {
+                    report.fragments_removed = compaction.fragments_removed;
+                    report.fragments_added = compaction.fragments_added;
+                    report.files_removed = compaction.files_removed;
+                }
+                if let Some(prune) = stats.prune {
+                    report.bytes_pruned = prune.bytes_removed;
+                    report.old_versions_pruned = prune.old_versions;
+                }
+                report
             }
             Err(e) => {
                 tracing::warn!("LanceDB compaction failed (non-fatal): {}", e);
+                CompactionReport::default()
             }
         }
     }
@@ -234,6 +324,16 @@ impl LanceStore {
         Ok(count_before - count_after)
     }
 
+    /// Delete rows matching a raw SQL predicate
+    pub async fn delete_where(&self, predicate: &str) -> Result<usize> {
+        let table = self.db.open_table(&self.table_name).execute().await?;
+        let count_before = table.count_rows(None).await.unwrap_or(0);
+        table.delete(predicate).await?;
+        self.compact_table(&table).await;
+        let count_after = table.count_rows(None).await.unwrap_or(0);
+        Ok(count_before - count_after)
+    }
+
     pub async fn delete_by_source(&self, source: &str) -> Result<usize> {
         let table = self.db.open_table(&self.table_name).execute().await?;
         let count_before = table.count_rows(None).await.unwrap_or(0);
@@ -244,6 +344,62 @@ impl LanceStore {
         Ok(count_before - count_after)
     }
 
+    /// Delete a source's chunks except those belonging to `keep_doc_id`.
+    /// Used after inserting a re-indexed file so old chunks are only removed
+    /// once their replacements are stored.
+    pub async fn delete_by_source_except_doc(&self, source: &str, keep_doc_id: &str) -> Result<usize> {
+        let table = self.db.open_table(&self.table_name).execute().await?;
+        let count_before = table.count_rows(None).await.unwrap_or(0);
+        let predicate = format!(
+            "source = '{}' AND doc_id != '{}'",
+            source.replace('\'', "''"),
+            keep_doc_id.replace('\'', "''"),
+        );
+        table.delete(&predicate).await?;
+        self.compact_table(&table).await;
+        let count_after = table.count_rows(None).await.unwrap_or(0);
+        Ok(count_before - count_after)
+    }
+
+    /// Stored `content_hash` metadata per source for sources under `prefix`.
+    /// Sources indexed without a hash are omitted.
+    pub async fn content_hashes(&self, prefix: &str) -> Result<HashMap<String, String>> {
+        let table = self.db.open_table(&self.table_name).execute().await?;
+        let escaped = prefix.replace('\'', "''").replace('%', "\\%").replace('_', "\\_");
+        let results = table
+            .query()
+            .only_if(format!("source LIKE '{}%'", escaped))
+            .select(lancedb::query::Select::columns(&["source", "metadata_json"]))
+            .execute()
+            .await
+            .context("Failed to query content hashes")?;
+
+        let batches: Vec<RecordBatch> = futures::TryStreamExt::try_collect(results).await?;
+        let mut hashes = HashMap::new();
+
+        for batch in &batches {
+            let sources = batch.column_by_name("source").and_then(|c| c.as_any().downcast_ref::<StringArray>());
+            let metas = batch.column_by_name("metadata_json").and_then(|c| c.as_any().downcast_ref::<StringArray>());
+
+            if let (Some(sources), Some(metas)) = (sources, metas) {
+                for i in 0..batch.num_rows() {
+                    let source = sources.value(i);
+                    if hashes.contains_key(source) {
+                        continue;
+                    }
+                    let hash = serde_json::from_str::<HashMap<String, String>>(metas.value(i))
+                        .ok()
+                        .and_then(|mut m| m.remove("content_hash"));
+                    if let Some(hash) = hash {
+                        hashes.insert(source.to_string(), hash);
+                    }
+                }
+            }
+        }
+
+        Ok(hashes)
+    }
+
     /// Delete all chunks whose source starts with the given prefix.
     /// Used for folder-level deletion where individual files are stored with
     /// their full path as the source.
@@ -290,6 +446,28 @@ impl LanceStore {
         Ok(count)
     }
 
+    /// Distinct doc_ids of chunks matching `predicate`
+    pub async fn doc_ids(&self, predicate: Option<&str>) -> Result<BTreeSet<String>> {
+        let table = self.db.open_table(&self.table_name).execute().await?;
+        let mut query = table.query().select(lancedb::query::Select::columns(&["doc_id"]));
+        if let Some(pred) = predicate {
+            query = query.only_if(pred);
+        }
+        let results = query.execute().await.context("Failed to query doc_ids")?;
+
+        let batches: Vec<RecordBatch> = futures::TryStreamExt::try_collect(results).await?;
+        let mut ids = BTreeSet::new();
+        for batch in &batches {
+            if let Some(col) = batch.column_by_name("doc_id").and_then(|c| c.as_any().downcast_ref::<StringArray>()) {
+                ids.extend((0..col.len())
+                    .map(|i| col.value(i))
+                    .filter(|id| !id.is_empty() && *id != "__seed__")
+                    .map(str::to_string));
+            }
+        }
+        Ok(ids)
+    }
+
     /// Count distinct documents (unique doc_ids) in the store.
     pub async fn count_documents(&self) -> Result<usize> {
         let table = self.db.open_table(&self.table_name).execute().await?;
@@ -377,6 +555,51 @@ impl LanceStore {
         Ok(extract_hits_from_batches(&batches, 0.0))
     }
 
+    /// Full chunk records, vectors included, matching `predicate`. Used for
+    /// space export; searches should use `list_chunks`.
+    pub async fn export_chunks(&self, predicate: Option<&str>) -> Result<Vec<ChunkRecord>> {
+        let table = self.db.open_table(&self.table_name).execute().await?;
+        let mut query = table.query();
+        if let Some(pred) = predicate {
+            query = query.only_if(pred);
+        }
+        let results = query
+            .execute()
+            .await
+            .context("LanceDB export query failed")?;
+
+        let batches: Vec<RecordBatch> = futures::TryStreamExt::try_collect(results).await?;
+        let mut records = Vec::new();
+        for batch in &batches {
+            let vectors = batch.column_by_name("vector")
+                .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>())
+                .context("Chunk table has no vector column")?;
+            let hits = extract_hits_from_batches(std::slice::from_ref(batch), 0.0);
+            for (i, hit) in hits.into_iter().enumerate() {
+                let values = vectors.value(i);
+                let vector = values.as_any().downcast_ref::<Float32Array>()
+                    .map(|v| v.values().to_vec())
+                    .unwrap_or_default();
+                records.push(ChunkRecord {
+                    id: hit.id,
+                    doc_id: hit.doc_id,
+                    chunk_index: hit.chunk_index,
+                    text: hit.text,
+                    title: hit.title,
+                    source: hit.source,
+                    heading: hit.heading,
+                    vector,
+                    space_id: hit.space_id,
+                    metadata_json: hit.metadata_json,
+                    citation_json: hit.citation_json,
+                    created_at: hit.created_at,
+                });
+            }
+        }
+        records.sort_by(|a, b| a.doc_id.cmp(&b.doc_id).then(a.chunk_index.cmp(&b.chunk_index)));
+        Ok(records)
+    }
+
     pub async fn create_index_if_needed(&self) -> Result<()> {
         let count = self.count().await?;
         if count >= 1_000 {
@@ -472,9 +695,22 @@ pub struct SearchHit {
     pub space_id: String,
     pub metadata_json: String,
     pub citation_json: String,
+    /// Unix timestamp (seconds) the chunk was indexed
+    pub created_at: i64,
     pub score: f32,
 }
 
+/// What a compaction/vacuum pass did to the table's files
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactionReport {
+    pub fragments_removed: usize,
+    pub fragments_added: usize,
+    pub files_removed: usize,
+    pub bytes_pruned: u64,
+    pub old_versions_pruned: u64,
+}
+
 /// Extract SearchHit records from Arrow RecordBatches.
 /// Centralizes the column extraction logic used by vector_search, list_chunks,
 /// get_neighbors, and get_by_ids to avoid code duplication.
@@ -491,6 +727,7 @@ fn extract_hits_from_batches(batches: &[RecordBatch], default_score: f32) -> Vec
         let metadata_jsons = batch.column_by_name("metadata_json").and_then(|c| c.as_any().downcast_ref::<StringArray>());
         let citation_jsons = batch.column_by_name("citation_json").and_then(|c| c.as_any().downcast_ref::<StringArray>());
         let space_ids = batch.column_by_name("space_id").and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        let created_ats = batch.column_by_name("created_at").and_then(|c| c.as_any().downcast_ref::<Int64Array>());
         let distances = batch.column_by_name("_distance").and_then(|c| c.as_any().downcast_ref::<Float32Array>());
 
         let (Some(ids), Some(texts), Some(titles), Some(sources)) = (ids, texts, titles, sources) else {
@@ -515,6 +752,7 @@ fn extract_hits_from_batches(batches: &[RecordBatch], default_score: f32) -> Vec
                 space_id: space_ids.map(|s| s.value(i).to_string()).unwrap_or_default(),
                 metadata_json: metadata_jsons.map(|m| m.value(i).to_string()).unwrap_or_else(|| "{}".to_string()),
                 citation_json: citation_jsons.map(|c| c.value(i).to_string()).unwrap_or_else(|| "{}".to_string()),
+                created_at: created_ats.map(|c| c.value(i)).unwrap_or(0),
                 score,
             });
         }
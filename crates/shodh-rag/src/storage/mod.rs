@@ -1,3 +1,3 @@
 pub mod lance_store;
 
-pub use lance_store::{LanceStore, SearchHit};
+pub use lance_store::{CompactionReport, LanceStore, SearchHit};
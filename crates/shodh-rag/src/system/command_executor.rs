@@ -44,8 +44,8 @@ pub struct CommandResult {
     pub exit_code: Option<i32>,
 }
 
-/// Classify command risk level
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Classify command risk level (ordered from least to most risky)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum CommandRiskLevel {
     Safe,      // Read-only operations (Get-, ls, echo, etc.)
     Moderate,  // Write operations (New-, mkdir, touch)
@@ -0,0 +1,219 @@
+//! Confirmation gate for command execution
+//!
+//! Commands at or above the configured risk threshold need a single-use
+//! confirmation token obtained from `preview`. Every execution attempt is
+//! recorded in an audit trail with its risk level and confirmation status.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::command_executor::{analyze_command_risk, CommandAction, CommandRiskLevel};
+
+/// How long a confirmation token stays valid
+const TOKEN_TTL_SECS: i64 = 300;
+/// Audit entries kept in memory
+const MAX_AUDIT_ENTRIES: usize = 1000;
+
+/// Result of previewing a command before execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandPreview {
+    pub command: String,
+    pub risk: CommandRiskLevel,
+    pub requires_confirmation: bool,
+    /// Pass back to `authorize` to run a gated command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmation_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Command audit log entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandAuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub command: String,
+    pub risk: CommandRiskLevel,
+    /// A valid confirmation token was presented
+    pub confirmed: bool,
+    pub allowed: bool,
+    pub result: String,
+}
+
+struct PendingConfirmation {
+    command: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// Enforces confirmation for risky commands and keeps the audit trail
+pub struct CommandGate {
+    threshold: Mutex<CommandRiskLevel>,
+    pending: Mutex<HashMap<String, PendingConfirmation>>,
+    audit_log: Mutex<Vec<CommandAuditEntry>>,
+}
+
+impl Default for CommandGate {
+    fn default() -> Self {
+        Self::new(CommandRiskLevel::High)
+    }
+}
+
+/// Text used for risk analysis and token binding
+pub fn command_text(action: &CommandAction) -> String {
+    match action {
+        CommandAction::PowerShell { command, .. } | CommandAction::Bash { command, .. } => {
+            command.clone()
+        }
+        CommandAction::System { program, args, .. } => {
+            std::iter::once(program.as_str())
+                .chain(args.iter().map(|a| a.as_str()))
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+    }
+}
+
+impl CommandGate {
+    pub fn new(threshold: CommandRiskLevel) -> Self {
+        Self {
+            threshold: Mutex::new(threshold),
+            pending: Mutex::new(HashMap::new()),
+            audit_log: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn threshold(&self) -> CommandRiskLevel {
+        *self.threshold.lock()
+    }
+
+    pub fn set_threshold(&self, threshold: CommandRiskLevel) {
+        *self.threshold.lock() = threshold;
+    }
+
+    /// High-risk commands always need confirmation, whatever the threshold
+    pub fn requires_confirmation(&self, risk: CommandRiskLevel) -> bool {
+        risk >= self.threshold() || risk == CommandRiskLevel::High
+    }
+
+    /// Classify a command and issue a confirmation token if it is gated
+    pub fn preview(&self, action: &CommandAction) -> CommandPreview {
+        let command = command_text(action);
+        let risk = analyze_command_risk(&command);
+        let requires_confirmation = self.requires_confirmation(risk);
+
+        let (confirmation_token, expires_at) = if requires_confirmation {
+            let now = Utc::now();
+            let token = uuid::Uuid::new_v4().to_string();
+            let expires_at = now + Duration::seconds(TOKEN_TTL_SECS);
+            let mut pending = self.pending.lock();
+            pending.retain(|_, p| p.expires_at > now);
+            pending.insert(
+                token.clone(),
+                PendingConfirmation { command: command.clone(), expires_at },
+            );
+            (Some(token), Some(expires_at))
+        } else {
+            (None, None)
+        };
+
+        CommandPreview { command, risk, requires_confirmation, confirmation_token, expires_at }
+    }
+
+    /// Check whether `action` may run. Consumes the token; refusals are audited.
+    /// Returns the risk level and whether the command was confirmed.
+    pub fn authorize(
+        &self,
+        action: &CommandAction,
+        token: Option<&str>,
+    ) -> Result<(CommandRiskLevel, bool)> {
+        let command = command_text(action);
+        let risk = analyze_command_risk(&command);
+
+        // Tokens are single-use even when the command turns out not to need one
+        let confirmed = token
+            .and_then(|t| self.pending.lock().remove(t))
+            .map(|p| p.command == command && p.expires_at > Utc::now())
+            .unwrap_or(false);
+
+        if self.requires_confirmation(risk) && !confirmed {
+            let reason = if token.is_some() {
+                "confirmation token is invalid, expired or for a different command"
+            } else {
+                "confirmation required; call preview_command_action first"
+            };
+            self.record(command, risk, false, false, format!("Refused: {}", reason));
+            return Err(anyhow!("{:?}-risk command refused: {}", risk, reason));
+        }
+
+        Ok((risk, confirmed))
+    }
+
+    /// Append an entry to the audit trail
+    pub fn record(
+        &self,
+        command: String,
+        risk: CommandRiskLevel,
+        confirmed: bool,
+        allowed: bool,
+        result: String,
+    ) {
+        tracing::info!(
+            "🛡️ Command audit [{:?}, confirmed={}, allowed={}]: {}",
+            risk, confirmed, allowed, command
+        );
+        let mut log = self.audit_log.lock();
+        log.push(CommandAuditEntry {
+            timestamp: Utc::now(),
+            command,
+            risk,
+            confirmed,
+            allowed,
+            result,
+        });
+        if log.len() > MAX_AUDIT_ENTRIES {
+            let excess = log.len() - MAX_AUDIT_ENTRIES;
+            log.drain(..excess);
+        }
+    }
+
+    /// Get audit log
+    pub fn get_audit_log(&self) -> Vec<CommandAuditEntry> {
+        self.audit_log.lock().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bash(command: &str) -> CommandAction {
+        CommandAction::Bash { command: command.to_string(), description: None }
+    }
+
+    #[test]
+    fn test_high_risk_requires_matching_token() {
+        let gate = CommandGate::new(CommandRiskLevel::High);
+
+        assert!(gate.authorize(&bash("echo hi"), None).is_ok());
+        assert!(gate.authorize(&bash("rm -rf build"), None).is_err());
+
+        let preview = gate.preview(&bash("rm -rf build"));
+        assert!(preview.requires_confirmation);
+        let token = preview.confirmation_token.unwrap();
+
+        // Token is bound to the previewed command
+        assert!(gate.authorize(&bash("rm -rf /"), Some(&token)).is_err());
+
+        let preview = gate.preview(&bash("rm -rf build"));
+        let token = preview.confirmation_token.unwrap();
+        assert!(gate.authorize(&bash("rm -rf build"), Some(&token)).unwrap().1);
+        // ...and single-use
+        assert!(gate.authorize(&bash("rm -rf build"), Some(&token)).is_err());
+
+        let audit = gate.get_audit_log();
+        assert_eq!(audit.len(), 3);
+        assert!(audit.iter().all(|e| !e.allowed));
+    }
+}
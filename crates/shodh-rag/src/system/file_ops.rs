@@ -14,6 +14,9 @@ pub enum FileSystemAction {
     CreateFolders {
         base_path: PathBuf,
         structure: FolderStructure,
+        /// Return the tree that would be created without creating it
+        #[serde(default)]
+        dry_run: bool,
     },
     CreateFile {
         path: PathBuf,
@@ -24,15 +27,23 @@ pub enum FileSystemAction {
     Copy {
         source: PathBuf,
         destination: PathBuf,
+        /// Report what would happen without touching the filesystem
+        #[serde(default)]
+        dry_run: bool,
     },
     Move {
         source: PathBuf,
         destination: PathBuf,
+        /// Report what would happen without touching the filesystem
+        #[serde(default)]
+        dry_run: bool,
     },
     Delete {
         path: PathBuf,
         #[serde(default)]
         recursive: bool,
+        #[serde(default)]
+        dry_run: bool,
     },
     ListDirectory {
         path: PathBuf,
@@ -60,44 +71,93 @@ pub struct FileSystemResult {
     pub output: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub affected_paths: Option<Vec<String>>,
+    /// Nothing was changed; the result describes what would happen
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<FileActionPreview>,
+}
+
+/// What a copy/move/delete would do, returned by the dry-run previews
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileActionPreview {
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+    /// The destination exists and would be replaced or merged into
+    pub overwrites_existing: bool,
+    pub total_bytes: u64,
+    pub file_count: usize,
+    /// Delete only: whether files are removed permanently instead of going to the trash
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permanent: Option<bool>,
 }
 
 /// Create folder structure recursively
 pub fn create_folder_structure(base: &Path, structure: &FolderStructure) -> Result<FileSystemResult> {
     let mut created_paths = Vec::new();
+    for path in planned_folders(base, structure) {
+        fs::create_dir_all(&path)
+            .context(format!("Failed to create folder: {:?}", path))?;
+        created_paths.push(path.to_string_lossy().to_string());
+    }
+
+    Ok(FileSystemResult {
+        success: true,
+        message: format!("Created {} folders", created_paths.len()),
+        output: None,
+        affected_paths: Some(created_paths),
+        dry_run: false,
+        preview: None,
+    })
+}
+
+/// Dry run of `create_folder_structure`: the folders that don't exist yet,
+/// rendered as an indented tree in `output`. Nothing is created.
+pub fn preview_folder_structure(base: &Path, structure: &FolderStructure) -> Result<FileSystemResult> {
+    let new_folders: Vec<PathBuf> = planned_folders(base, structure)
+        .into_iter()
+        .filter(|path| !path.exists())
+        .collect();
+
+    let mut tree = format!("{}/\n", base.display());
+    for path in &new_folders {
+        let relative = path.strip_prefix(base).unwrap_or(path);
+        let depth = relative.components().count();
+        let name = relative.file_name().unwrap_or(relative.as_os_str()).to_string_lossy();
+        tree.push_str(&format!("{}{}/\n", "  ".repeat(depth), name));
+    }
 
+    Ok(FileSystemResult {
+        success: true,
+        message: format!("Would create {} folders", new_folders.len()),
+        output: Some(tree),
+        affected_paths: Some(new_folders.iter().map(|p| p.to_string_lossy().to_string()).collect()),
+        dry_run: true,
+        preview: None,
+    })
+}
+
+/// Every folder a structure describes, parents before children. Nested
+/// entries are sorted by name so previews are stable.
+fn planned_folders(base: &Path, structure: &FolderStructure) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
     match structure {
         FolderStructure::Simple(folders) => {
-            for folder in folders {
-                let path = base.join(folder);
-                fs::create_dir_all(&path)
-                    .context(format!("Failed to create folder: {:?}", path))?;
-                created_paths.push(path.to_string_lossy().to_string());
-            }
+            paths.extend(folders.iter().map(|folder| base.join(folder)));
         }
-
         FolderStructure::Nested(map) => {
-            for (folder, sub_structure) in map {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            for (folder, sub_structure) in entries {
                 let path = base.join(folder);
-                fs::create_dir_all(&path)
-                    .context(format!("Failed to create folder: {:?}", path))?;
-                created_paths.push(path.to_string_lossy().to_string());
-
-                // Recursively create subfolders
-                let sub_result = create_folder_structure(&path, sub_structure)?;
-                if let Some(mut sub_paths) = sub_result.affected_paths {
-                    created_paths.append(&mut sub_paths);
-                }
+                let children = planned_folders(&path, sub_structure);
+                paths.push(path);
+                paths.extend(children);
             }
         }
     }
-
-    Ok(FileSystemResult {
-        success: true,
-        message: format!("Created {} folders", created_paths.len()),
-        output: None,
-        affected_paths: Some(created_paths),
-    })
+    paths
 }
 
 /// Create a file with content
@@ -122,6 +182,8 @@ pub fn create_file(path: &Path, content: &str, overwrite: bool) -> Result<FileSy
         message: format!("Created file: {:?}", path),
         output: None,
         affected_paths: Some(vec![path.to_string_lossy().to_string()]),
+        dry_run: false,
+        preview: None,
     })
 }
 
@@ -148,6 +210,8 @@ pub fn copy_path(source: &Path, destination: &Path) -> Result<FileSystemResult>
         message: format!("Copied {:?} to {:?}", source, destination),
         output: None,
         affected_paths: Some(vec![destination.to_string_lossy().to_string()]),
+        dry_run: false,
+        preview: None,
     })
 }
 
@@ -189,6 +253,8 @@ pub fn move_path(source: &Path, destination: &Path) -> Result<FileSystemResult>
         message: format!("Moved {:?} to {:?}", source, destination),
         output: None,
         affected_paths: Some(vec![destination.to_string_lossy().to_string()]),
+        dry_run: false,
+        preview: None,
     })
 }
 
@@ -216,9 +282,99 @@ pub fn delete_path(path: &Path, recursive: bool) -> Result<FileSystemResult> {
         message: format!("Deleted: {:?}", path),
         output: None,
         affected_paths: Some(vec![path.to_string_lossy().to_string()]),
+        dry_run: false,
+        preview: None,
+    })
+}
+
+/// Dry run of `copy_path`
+pub fn preview_copy(source: &Path, destination: &Path) -> Result<FileSystemResult> {
+    preview_transfer("copy", source, destination)
+}
+
+/// Dry run of `move_path`
+pub fn preview_move(source: &Path, destination: &Path) -> Result<FileSystemResult> {
+    preview_transfer("move", source, destination)
+}
+
+fn preview_transfer(verb: &str, source: &Path, destination: &Path) -> Result<FileSystemResult> {
+    if !source.exists() {
+        return Err(anyhow!("Source does not exist: {:?}", source));
+    }
+
+    let (total_bytes, file_count) = path_size(source)?;
+    let overwrites_existing = destination.exists();
+
+    Ok(FileSystemResult {
+        success: true,
+        message: format!(
+            "Would {} {:?} to {:?} ({} files, {} bytes){}",
+            verb, source, destination, file_count, total_bytes,
+            if overwrites_existing { ", overwriting existing target" } else { "" }
+        ),
+        output: None,
+        affected_paths: Some(vec![destination.to_string_lossy().to_string()]),
+        dry_run: true,
+        preview: Some(FileActionPreview {
+            source: source.to_string_lossy().to_string(),
+            destination: Some(destination.to_string_lossy().to_string()),
+            overwrites_existing,
+            total_bytes,
+            file_count,
+            permanent: None,
+        }),
+    })
+}
+
+/// Dry run of `delete_path`. Deletion bypasses the trash, so it is always
+/// reported as permanent.
+pub fn preview_delete(path: &Path, recursive: bool) -> Result<FileSystemResult> {
+    if !path.exists() {
+        return Err(anyhow!("Path does not exist: {:?}", path));
+    }
+    if path.is_dir() && !recursive && fs::read_dir(path)?.next().is_some() {
+        return Err(anyhow!("Directory is not empty, use recursive=true: {:?}", path));
+    }
+
+    let (total_bytes, file_count) = path_size(path)?;
+
+    Ok(FileSystemResult {
+        success: true,
+        message: format!(
+            "Would permanently delete {:?} ({} files, {} bytes)",
+            path, file_count, total_bytes
+        ),
+        output: None,
+        affected_paths: Some(vec![path.to_string_lossy().to_string()]),
+        dry_run: true,
+        preview: Some(FileActionPreview {
+            source: path.to_string_lossy().to_string(),
+            destination: None,
+            overwrites_existing: false,
+            total_bytes,
+            file_count,
+            permanent: Some(true),
+        }),
     })
 }
 
+/// Total bytes and file count under a path. Symlinks are counted, not followed.
+fn path_size(path: &Path) -> Result<(u64, usize)> {
+    let meta = fs::symlink_metadata(path)
+        .context(format!("Failed to read metadata: {:?}", path))?;
+    if !meta.is_dir() {
+        return Ok((meta.len(), 1));
+    }
+
+    let mut total = (0, 0);
+    for entry in fs::read_dir(path)? {
+        let (bytes, files) = path_size(&entry?.path())?;
+        total.0 += bytes;
+        total.1 += files;
+    }
+    Ok(total)
+}
+
 /// List directory contents
 pub fn list_directory(path: &Path, recursive: bool) -> Result<FileSystemResult> {
     if !path.exists() {
@@ -256,6 +412,8 @@ pub fn list_directory(path: &Path, recursive: bool) -> Result<FileSystemResult>
         message: format!("Listed {} items", files.len()),
         output: Some(output),
         affected_paths: None,
+        dry_run: false,
+        preview: None,
     })
 }
 
@@ -304,6 +462,47 @@ mod tests {
         let _ = fs::remove_dir_all(temp_dir);
     }
 
+    #[test]
+    fn test_preview_folder_structure_creates_nothing() {
+        let temp_dir = env::temp_dir().join(format!("shodh_test_preview_{}", std::process::id()));
+        let structure = FolderStructure::Nested(HashMap::from([
+            ("src".to_string(), FolderStructure::Simple(vec!["bin".to_string()])),
+            ("docs".to_string(), FolderStructure::Simple(vec![])),
+        ]));
+
+        let result = preview_folder_structure(&temp_dir, &structure).unwrap();
+        assert_eq!(result.message, "Would create 3 folders");
+        assert_eq!(
+            result.output.unwrap(),
+            format!("{}/\n  docs/\n  src/\n    bin/\n", temp_dir.display())
+        );
+        assert!(!temp_dir.exists());
+    }
+
+    #[test]
+    fn test_preview_delete_and_copy_touch_nothing() {
+        let temp_dir = env::temp_dir().join(format!("shodh_test_fs_preview_{}", std::process::id()));
+        fs::create_dir_all(temp_dir.join("sub")).unwrap();
+        fs::write(temp_dir.join("a.txt"), "hello").unwrap();
+        fs::write(temp_dir.join("sub/b.txt"), "abc").unwrap();
+        let target = env::temp_dir().join(format!("shodh_test_fs_target_{}", std::process::id()));
+
+        assert!(preview_delete(&temp_dir, false).is_err());
+        let result = preview_delete(&temp_dir, true).unwrap();
+        let preview = result.preview.unwrap();
+        assert!(result.dry_run);
+        assert_eq!((preview.total_bytes, preview.file_count), (8, 2));
+        assert_eq!(preview.permanent, Some(true));
+        assert!(temp_dir.exists());
+
+        let preview = preview_copy(&temp_dir.join("a.txt"), &temp_dir.join("sub/b.txt")).unwrap().preview.unwrap();
+        assert!(preview.overwrites_existing);
+        assert!(!preview_move(&temp_dir, &target).unwrap().preview.unwrap().overwrites_existing);
+        assert!(!target.exists());
+
+        let _ = fs::remove_dir_all(temp_dir);
+    }
+
     #[test]
     fn test_create_file() {
         let temp_dir = env::temp_dir();
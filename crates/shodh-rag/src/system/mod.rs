@@ -9,10 +9,12 @@
 pub mod file_ops;
 pub mod command_executor;
 pub mod os_integration;
+pub mod command_policy;
 
 pub use file_ops::{
-    FileSystemAction, FileSystemResult, FolderStructure,
-    create_folder_structure, create_file, copy_path, move_path, delete_path, list_directory
+    FileSystemAction, FileSystemResult, FileActionPreview, FolderStructure,
+    create_folder_structure, preview_folder_structure, create_file, copy_path, move_path, delete_path,
+    preview_copy, preview_move, preview_delete, list_directory
 };
 
 pub use command_executor::{
@@ -20,6 +22,10 @@ pub use command_executor::{
     execute_command, execute_powershell, execute_bash, analyze_command_risk
 };
 
+pub use command_policy::{
+    CommandGate, CommandPreview, CommandAuditEntry, command_text
+};
+
 pub use os_integration::{
-    open_in_file_manager, get_system_info, list_running_processes
+    open_in_file_manager, get_system_info, list_running_processes, ProcessQuery, ProcessSort
 };
@@ -1,7 +1,7 @@
 //! OS-specific integrations with cross-platform abstraction
 //! Provides unified interface for Windows, macOS, and Linux features
 
-use anyhow::{Result, anyhow, Context};
+use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -204,62 +204,79 @@ fn get_total_memory_mb() -> u64 {
     }
 }
 
-/// List running processes (simplified, platform-specific)
-pub fn list_running_processes() -> Result<Vec<ProcessInfo>> {
-    #[cfg(target_os = "windows")]
-    {
-        use std::process::Command;
-        let output = Command::new("powershell")
-            .args(["-Command", "Get-Process | Select-Object Id,Name,CPU,WorkingSet | ConvertTo-Json"])
-            .output()
-            .context("Failed to list processes")?;
-
-        let json_str = String::from_utf8_lossy(&output.stdout);
-        let processes: Vec<serde_json::Value> = serde_json::from_str(&json_str)
-            .unwrap_or_default();
-
-        Ok(processes
-            .into_iter()
-            .filter_map(|p| {
-                Some(ProcessInfo {
-                    pid: p["Id"].as_u64()? as u32,
-                    name: p["Name"].as_str()?.to_string(),
-                    cpu_percent: p["CPU"].as_f64().map(|c| c as f32),
-                    memory_mb: p["WorkingSet"].as_u64().map(|ws| ws / 1024 / 1024),
-                })
-            })
-            .collect())
+/// Sort order for process listings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessSort {
+    #[default]
+    Cpu,
+    Memory,
+    Name,
+    Pid,
+}
+
+/// Filter, sort and limit options for `list_running_processes`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProcessQuery {
+    /// Case-insensitive name substring, or an exact pid
+    #[serde(default)]
+    pub filter: Option<String>,
+    #[serde(default)]
+    pub sort_by: ProcessSort,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// List running processes with CPU% (of one core) and resident memory.
+///
+/// CPU usage needs two samples, so this blocks for
+/// `sysinfo::MINIMUM_CPU_UPDATE_INTERVAL`; call it from a blocking task.
+pub fn list_running_processes(query: &ProcessQuery) -> Result<Vec<ProcessInfo>> {
+    use sysinfo::System;
+
+    let mut sys = System::new();
+    sys.refresh_processes();
+    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    sys.refresh_processes();
+
+    let processes = sys
+        .processes()
+        .iter()
+        .map(|(pid, process)| ProcessInfo {
+            pid: pid.as_u32(),
+            name: process.name().to_string(),
+            cpu_percent: Some(process.cpu_usage()),
+            memory_mb: Some(process.memory() / 1024 / 1024),
+        })
+        .collect();
+
+    Ok(apply_process_query(processes, query))
+}
+
+fn apply_process_query(mut processes: Vec<ProcessInfo>, query: &ProcessQuery) -> Vec<ProcessInfo> {
+    if let Some(filter) = query.filter.as_deref().map(str::trim).filter(|f| !f.is_empty()) {
+        match filter.parse::<u32>() {
+            Ok(pid) => processes.retain(|p| p.pid == pid),
+            Err(_) => {
+                let needle = filter.to_lowercase();
+                processes.retain(|p| p.name.to_lowercase().contains(&needle));
+            }
+        }
     }
 
-    #[cfg(not(target_os = "windows"))]
-    {
-        use std::process::Command;
-        let output = Command::new("ps")
-            .args(["aux"])
-            .output()
-            .context("Failed to list processes")?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let processes: Vec<ProcessInfo> = stdout
-            .lines()
-            .skip(1) // Skip header
-            .filter_map(|line| {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 11 {
-                    Some(ProcessInfo {
-                        pid: parts[1].parse().ok()?,
-                        name: parts[10].to_string(),
-                        cpu_percent: parts[2].parse().ok(),
-                        memory_mb: None, // ps doesn't give direct MB
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect();
+    match query.sort_by {
+        ProcessSort::Cpu => processes.sort_by(|a, b| {
+            b.cpu_percent.unwrap_or(0.0).total_cmp(&a.cpu_percent.unwrap_or(0.0))
+        }),
+        ProcessSort::Memory => processes.sort_by_key(|p| std::cmp::Reverse(p.memory_mb.unwrap_or(0))),
+        ProcessSort::Name => processes.sort_by_key(|p| p.name.to_lowercase()),
+        ProcessSort::Pid => processes.sort_by_key(|p| p.pid),
+    }
 
-        Ok(processes)
+    if let Some(limit) = query.limit {
+        processes.truncate(limit);
     }
+    processes
 }
 
 #[cfg(test)]
@@ -278,7 +295,7 @@ mod tests {
 
     #[test]
     fn test_list_processes() {
-        let processes = list_running_processes().unwrap();
+        let processes = list_running_processes(&ProcessQuery::default()).unwrap();
         println!("Found {} processes", processes.len());
         assert!(!processes.is_empty());
 
@@ -287,4 +304,31 @@ mod tests {
             println!("Process: {:?}", process);
         }
     }
+
+    #[test]
+    fn test_process_query_filter_sort_limit() {
+        let proc = |pid, name: &str, cpu, mem| ProcessInfo {
+            pid,
+            name: name.to_string(),
+            cpu_percent: Some(cpu),
+            memory_mb: Some(mem),
+        };
+        let processes = vec![
+            proc(1, "init", 0.1, 5),
+            proc(20, "Chrome", 12.0, 800),
+            proc(21, "chrome-helper", 30.0, 200),
+            proc(300, "bash", 1.0, 10),
+        ];
+
+        let query = ProcessQuery { filter: Some("chrome".into()), sort_by: ProcessSort::Memory, limit: None };
+        let pids: Vec<u32> = apply_process_query(processes.clone(), &query).iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![20, 21]);
+
+        let query = ProcessQuery { filter: None, sort_by: ProcessSort::Cpu, limit: Some(2) };
+        let pids: Vec<u32> = apply_process_query(processes.clone(), &query).iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![21, 20]);
+
+        let query = ProcessQuery { filter: Some("300".into()), ..Default::default() };
+        assert_eq!(apply_process_query(processes, &query)[0].name, "bash");
+    }
 }
@@ -8,6 +8,7 @@ use std::collections::HashMap;
 use std::sync::LazyLock;
 use crate::types::ComprehensiveResult;
 use crate::rag_engine::RAGEngine;
+use crate::llm::LLMManager;
 
 static NUMBERED_HEADING_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
     regex::Regex::new(r"^\d+\.\s+(.+)$").expect("numbered heading regex is valid")
@@ -22,7 +23,7 @@ static NAME_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
     regex::Regex::new(r"\b[A-Z][a-z]+ [A-Z][a-z]+\b").expect("name regex is valid")
 });
 static MUSTACHE_VAR_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
-    regex::Regex::new(r"\{\{(\w+)\}\}").expect("mustache var regex is valid")
+    regex::Regex::new(r"\{\{(\w+)(?::(\w+))?\}\}").expect("mustache var regex is valid")
 });
 static BRACKET_VAR_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
     regex::Regex::new(r"\[([A-Z_]+)\]").expect("bracket var regex is valid")
@@ -75,6 +76,44 @@ pub struct TemplateVariable {
     pub description: String,
     pub default_value: Option<String>,
     pub validation_pattern: Option<String>,
+    #[serde(default)]
+    pub placeholder_type: PlaceholderType,
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// Kind of value a placeholder takes, written `{{name:kind}}`; a bare
+/// `{{name}}` is plain text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaceholderType {
+    #[default]
+    Text,
+    /// Free-form markdown; left empty, it can be written by the LLM
+    RichText,
+    Date,
+    Number,
+    Boolean,
+}
+
+impl PlaceholderType {
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind.to_lowercase().as_str() {
+            "text" | "string" => Some(Self::Text),
+            "richtext" | "markdown" => Some(Self::RichText),
+            "date" => Some(Self::Date),
+            "number" => Some(Self::Number),
+            "bool" | "boolean" => Some(Self::Boolean),
+            _ => None,
+        }
+    }
+}
+
+/// A value that does not satisfy its placeholder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateInputError {
+    pub variable: String,
+    pub message: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -151,6 +190,203 @@ impl TemplateExtractor {
         Ok(template)
     }
 
+    /// Build a template from user-written markdown. `## ` headings split it
+    /// into sections and `{{name:kind}}` placeholders become its variables.
+    pub fn template_from_markdown(
+        &self,
+        name: String,
+        description: String,
+        body: &str,
+    ) -> Result<DocumentTemplate, String> {
+        let mut sections: Vec<TemplateSection> = Vec::new();
+        let mut heading = String::new();
+        let mut content = String::new();
+        for line in body.lines() {
+            if let Some(title) = line.strip_prefix("## ") {
+                push_section(&mut sections, std::mem::take(&mut heading), &content);
+                heading = title.trim().to_string();
+                content.clear();
+            } else {
+                content.push_str(line);
+                content.push('\n');
+            }
+        }
+        push_section(&mut sections, heading, &content);
+        if sections.is_empty() {
+            return Err("Template body is empty".to_string());
+        }
+
+        let mut variables: Vec<TemplateVariable> = Vec::new();
+        for cap in MUSTACHE_VAR_RE.captures_iter(body) {
+            let var_name = &cap[1];
+            let placeholder_type = match cap.get(2) {
+                Some(kind) => PlaceholderType::parse(kind.as_str()).ok_or_else(|| {
+                    format!("Unknown placeholder type '{}' for {{{{{}}}}}", kind.as_str(), var_name)
+                })?,
+                None => PlaceholderType::Text,
+            };
+            match variables.iter().find(|v| v.name == var_name) {
+                Some(existing) if existing.placeholder_type != placeholder_type && cap.get(2).is_some() => {
+                    return Err(format!("Placeholder {{{{{}}}}} is declared with two different types", var_name));
+                }
+                Some(_) => {}
+                None => variables.push(TemplateVariable {
+                    name: var_name.to_string(),
+                    description: humanize(var_name),
+                    default_value: None,
+                    validation_pattern: None,
+                    placeholder_type,
+                    required: true,
+                }),
+            }
+        }
+
+        for section in &mut sections {
+            section.content_type = self.infer_content_type(std::slice::from_ref(&section.placeholder));
+        }
+        let example_content = self.render(&sections, &self.sample_values(&variables), &variables, &OutputFormat::Markdown);
+
+        Ok(DocumentTemplate {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            description,
+            sections,
+            metadata: TemplateMetadata {
+                document_type: "Custom Template".to_string(),
+                industry: None,
+                language: "en".to_string(),
+                created_from: vec![],
+                confidence_score: 1.0,
+                usage_count: 0,
+            },
+            variables,
+            example_content,
+        })
+    }
+
+    /// Check `values` against the template's placeholders. With `llm_fill`,
+    /// empty rich-text placeholders are left for the LLM instead of reported.
+    pub fn validate_inputs(
+        &self,
+        template: &DocumentTemplate,
+        values: &HashMap<String, String>,
+        llm_fill: bool,
+    ) -> Vec<TemplateInputError> {
+        let mut errors = Vec::new();
+        for var in &template.variables {
+            let error = |message: &str| TemplateInputError {
+                variable: var.name.clone(),
+                message: format!("{} {}", humanize(&var.name), message),
+            };
+            let Some(value) = values.get(&var.name).map(|v| v.trim()).filter(|v| !v.is_empty()) else {
+                let left_for_llm = llm_fill && var.placeholder_type == PlaceholderType::RichText;
+                if var.required && var.default_value.is_none() && !left_for_llm {
+                    errors.push(error("is required"));
+                }
+                continue;
+            };
+
+            let type_error = match var.placeholder_type {
+                PlaceholderType::Date if chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_err() => {
+                    Some("must be a date (YYYY-MM-DD)")
+                }
+                PlaceholderType::Number if value.replace(',', "").parse::<f64>().is_err() => Some("must be a number"),
+                PlaceholderType::Boolean
+                    if !matches!(value.to_lowercase().as_str(), "true" | "false" | "yes" | "no") =>
+                {
+                    Some("must be yes or no")
+                }
+                _ => None,
+            };
+            if let Some(message) = type_error {
+                errors.push(error(message));
+                continue;
+            }
+
+            if let Some(pattern) = &var.validation_pattern {
+                match regex::Regex::new(pattern) {
+                    Ok(re) if !re.is_match(value) => errors.push(error("does not match the expected format")),
+                    Ok(_) => {}
+                    Err(_) => errors.push(error("has an invalid validation pattern")),
+                }
+            }
+        }
+        errors
+    }
+
+    /// Placeholder values for previews: defaults where set, otherwise a
+    /// sample of the right type
+    pub fn sample_values(&self, variables: &[TemplateVariable]) -> HashMap<String, String> {
+        variables
+            .iter()
+            .map(|var| {
+                let value = var.default_value.clone().unwrap_or_else(|| match var.placeholder_type {
+                    PlaceholderType::Text => format!("Sample {}", humanize(&var.name).to_lowercase()),
+                    PlaceholderType::RichText => format!(
+                        "*{} will be written here.*",
+                        humanize(&var.name)
+                    ),
+                    PlaceholderType::Date => chrono::Utc::now().format("%Y-%m-%d").to_string(),
+                    PlaceholderType::Number => "42".to_string(),
+                    PlaceholderType::Boolean => "yes".to_string(),
+                });
+                (var.name.clone(), value)
+            })
+            .collect()
+    }
+
+    /// Ask the LLM to write every rich-text placeholder without a value,
+    /// using the section it sits in and the other values as context.
+    /// Returns the names that were filled.
+    pub async fn fill_rich_text(
+        &self,
+        template: &DocumentTemplate,
+        values: &mut HashMap<String, String>,
+        llm: &LLMManager,
+    ) -> Result<Vec<String>, String> {
+        let mut filled = Vec::new();
+        for var in &template.variables {
+            let has_value = values.get(&var.name).is_some_and(|v| !v.trim().is_empty());
+            if var.placeholder_type != PlaceholderType::RichText || has_value {
+                continue;
+            }
+
+            let marker = format!("{{{{{}", var.name);
+            let section = template
+                .sections
+                .iter()
+                .find(|s| s.placeholder.contains(&marker))
+                .map(|s| s.name.as_str())
+                .filter(|name| !name.is_empty())
+                .unwrap_or(&template.name);
+            let known: Vec<String> = template
+                .variables
+                .iter()
+                .filter_map(|v| values.get(&v.name).filter(|val| !val.trim().is_empty()).map(|val| format!("- {}: {}", humanize(&v.name), val.trim())))
+                .collect();
+
+            let prompt = format!(
+                "You are filling in a {document_type} based on the template \"{template}\".\n\n\
+                Write the \"{field}\" content for the \"{section}\" section.\n\n\
+                Known details:\n{known}\n\n\
+                Write only the content in Markdown, without a heading. Do not invent names, \
+                figures or dates that are not among the known details.",
+                document_type = template.metadata.document_type.to_lowercase(),
+                template = template.name,
+                field = humanize(&var.name),
+                section = section,
+                known = if known.is_empty() { "- (none)".to_string() } else { known.join("\n") },
+            );
+            let text = llm
+                .generate(&prompt)
+                .await
+                .map_err(|e| format!("Failed to write {}: {}", humanize(&var.name), e))?;
+            values.insert(var.name.clone(), text.trim().to_string());
+            filled.push(var.name.clone());
+        }
+        Ok(filled)
+    }
+
     /// Generate new document from template
     pub fn generate_from_template(
         &self,
@@ -161,25 +397,46 @@ impl TemplateExtractor {
             .get(&request.template_id)
             .ok_or_else(|| "Template not found".to_string())?;
 
+        Ok(self.render(&template.sections, &request.variables, &template.variables, &request.output_format))
+    }
+
+    fn render(
+        &self,
+        sections: &[TemplateSection],
+        values: &HashMap<String, String>,
+        variables: &[TemplateVariable],
+        output_format: &OutputFormat,
+    ) -> String {
         let mut output = String::new();
 
-        for section in &template.sections {
-            match request.output_format {
-                OutputFormat::Markdown => {
-                    output.push_str(&format!("## {}\n\n", section.name));
-                }
-                OutputFormat::Html => {
-                    output.push_str(&format!("<h2>{}</h2>\n", section.name));
-                }
-                _ => {
-                    output.push_str(&format!("{}\n\n", section.name));
+        for section in sections {
+            if !section.name.is_empty() {
+                match output_format {
+                    OutputFormat::Markdown => {
+                        output.push_str(&format!("## {}\n\n", section.name));
+                    }
+                    OutputFormat::Html => {
+                        output.push_str(&format!("<h2>{}</h2>\n", section.name));
+                    }
+                    _ => {
+                        output.push_str(&format!("{}\n\n", section.name));
+                    }
                 }
             }
 
-            let mut content = section.placeholder.clone();
-            for (var_name, var_value) in &request.variables {
+            // Typed placeholders; unfilled ones without a default stay visible
+            let mut content = MUSTACHE_VAR_RE
+                .replace_all(&section.placeholder, |cap: &regex::Captures| {
+                    values
+                        .get(&cap[1])
+                        .filter(|v| !v.trim().is_empty())
+                        .cloned()
+                        .or_else(|| variables.iter().find(|v| v.name == cap[1]).and_then(|v| v.default_value.clone()))
+                        .unwrap_or_else(|| cap[0].to_string())
+                })
+                .to_string();
+            for (var_name, var_value) in values {
                 let patterns = vec![
-                    format!("{{{{{}}}}}", var_name),
                     format!("[{}]", var_name.to_uppercase()),
                     format!("${}", var_name),
                 ];
@@ -192,7 +449,7 @@ impl TemplateExtractor {
             output.push_str("\n\n");
         }
 
-        Ok(output)
+        output
     }
 
     async fn get_document_chunks(
@@ -394,16 +651,21 @@ impl TemplateExtractor {
     }
 
     fn extract_variables(&self, chunks: &[ComprehensiveResult]) -> Vec<TemplateVariable> {
-        let mut variables = Vec::new();
+        let mut variables: Vec<TemplateVariable> = Vec::new();
         let combined = chunks.iter().map(|c| c.snippet.as_str()).collect::<Vec<_>>().join("\n");
 
         for cap in MUSTACHE_VAR_RE.captures_iter(&combined) {
             if let Some(var_name) = cap.get(1) {
+                if variables.iter().any(|v| v.name == var_name.as_str()) {
+                    continue;
+                }
                 variables.push(TemplateVariable {
                     name: var_name.as_str().to_string(),
                     description: format!("Variable: {}", var_name.as_str()),
                     default_value: None,
                     validation_pattern: None,
+                    placeholder_type: cap.get(2).and_then(|kind| PlaceholderType::parse(kind.as_str())).unwrap_or_default(),
+                    required: false,
                 });
             }
         }
@@ -417,6 +679,8 @@ impl TemplateExtractor {
                         description: format!("Placeholder: {}", var_name.as_str()),
                         default_value: None,
                         validation_pattern: None,
+                        placeholder_type: PlaceholderType::Text,
+                        required: false,
                     });
                 }
             }
@@ -473,8 +737,78 @@ impl TemplateExtractor {
     }
 }
 
+fn push_section(sections: &mut Vec<TemplateSection>, name: String, content: &str) {
+    let placeholder = content.trim().to_string();
+    if name.is_empty() && placeholder.is_empty() {
+        return;
+    }
+    sections.push(TemplateSection {
+        name,
+        order: sections.len(),
+        content_type: ContentType::Text,
+        placeholder,
+        is_required: true,
+        formatting_rules: vec![],
+    });
+}
+
+/// `client_name` -> `Client name`
+fn humanize(name: &str) -> String {
+    let spaced = name.replace('_', " ");
+    let mut chars = spaced.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
 impl Default for TemplateExtractor {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_template_validates_and_renders_typed_placeholders() {
+        let extractor = TemplateExtractor::new();
+        let template = extractor
+            .template_from_markdown(
+                "Audit".into(),
+                String::new(),
+                "Prepared for {{client_name}} on {{date:date}}\n\n## Findings\n{{findings:richtext}}\n\n## Fee\n{{fee:number}} for {{client_name}}",
+            )
+            .unwrap();
+        assert_eq!(
+            template.sections.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(),
+            vec!["", "Findings", "Fee"]
+        );
+        assert_eq!(template.variables.len(), 4);
+        assert!(extractor.template_from_markdown("Bad".into(), String::new(), "{{x:colour}}").is_err());
+
+        let mut values = HashMap::from([
+            ("client_name".to_string(), "Acme".to_string()),
+            ("date".to_string(), "03/04/2025".to_string()),
+            ("fee".to_string(), "1,200".to_string()),
+        ]);
+        let invalid: Vec<_> = extractor.validate_inputs(&template, &values, false).into_iter().map(|e| e.variable).collect();
+        assert_eq!(invalid, vec!["date", "findings"]);
+
+        values.insert("date".into(), "2025-04-03".into());
+        assert!(extractor.validate_inputs(&template, &values, true).is_empty());
+
+        let mut templates = HashMap::new();
+        templates.insert(template.id.clone(), template.clone());
+        let output = extractor
+            .generate_from_template(
+                TemplateGenerationRequest { template_id: template.id.clone(), variables: values, output_format: OutputFormat::Markdown },
+                &templates,
+            )
+            .unwrap();
+        assert!(output.starts_with("Prepared for Acme on 2025-04-03\n\n## Findings\n\n{{findings:richtext}}"));
+        assert!(output.contains("1,200 for Acme"));
+    }
+}
@@ -70,6 +70,7 @@ pub enum DocumentFormat {
     CSV,
     Spreadsheet,
     Presentation,
+    Epub,
     Code,
 }
 
@@ -84,6 +85,7 @@ impl DocumentFormat {
             "csv" => Self::CSV,
             "xlsx" | "xls" | "ods" | "xlsm" | "xlsb" => Self::Spreadsheet,
             "pptx" | "ppt" | "odp" => Self::Presentation,
+            "epub" => Self::Epub,
             "rs" | "py" | "js" | "ts" | "jsx" | "tsx" | "go" | "java" | "c" | "cpp" | "h"
             | "hpp" | "cs" | "rb" | "php" | "swift" | "kt" | "scala" | "r" | "sql" | "sh"
             | "bash" | "zsh" | "fish" | "ps1" | "bat" | "cmd" | "yaml" | "yml" | "toml"
@@ -119,6 +121,23 @@ pub enum DocumentSection {
     Relationships {
         content: String,
     },
+    /// A spreadsheet sheet or CSV file, chunked as its own sub-document: a
+    /// summary of its columns plus row groups written as `header: value` pairs.
+    Sheet {
+        /// Sheet name; None for CSV files
+        name: Option<String>,
+        index: usize,
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+        /// Headers of columns whose values are mostly numbers
+        numeric_columns: Vec<String>,
+    },
+    /// An ebook chapter (EPUB spine item) as markdown. Chunks never span chapters.
+    Chapter {
+        title: String,
+        spine_index: usize,
+        content: String,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -129,9 +148,108 @@ pub struct MetadataFilter {
     pub date_from: Option<i64>,
     pub date_to: Option<i64>,
     pub custom: Option<HashMap<String, String>>,
+    /// Case-insensitive substring of any citation author
+    #[serde(default)]
+    pub author: Option<String>,
+    /// Inclusive publication year bounds, checked against `Citation::year`
+    #[serde(default)]
+    pub year_from: Option<i32>,
+    #[serde(default)]
+    pub year_to: Option<i32>,
+    /// ISO 639-3 code detected at ingest ("eng", "cmn"), matched against the
+    /// chunk's `language` metadata
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+/// Per-query reranking controls for `search_comprehensive_with`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RerankOptions {
+    /// Rescore fused candidates with the cross-encoder, when its model is loaded
+    pub cross_encoder: bool,
+    /// Rerank only this many top candidates; None reranks all of them
+    pub top_k: Option<usize>,
+}
+
+impl Default for RerankOptions {
+    fn default() -> Self {
+        Self { cross_encoder: true, top_k: None }
+    }
+}
+
+/// Ordering for metadata-only document listings
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentSort {
+    /// Most recently indexed first
+    #[default]
+    Date,
+    /// Title, case-insensitive A-Z
+    Title,
 }
 
 impl MetadataFilter {
+    /// Check the fields LanceDB can't filter on: `source_type` (matched
+    /// against the file type/extension), `custom` key/value pairs and `language`.
+    pub fn matches_metadata(&self, metadata: &HashMap<String, String>) -> bool {
+        if let Some(ref wanted) = self.source_type {
+            let wanted = wanted.trim_start_matches('.').to_lowercase();
+            let found = ["source_type", "file_type", "file_extension"].iter().any(|key| {
+                metadata
+                    .get(*key)
+                    .map(|v| v.trim_start_matches('.').to_lowercase() == wanted)
+                    .unwrap_or(false)
+            });
+            if !found {
+                return false;
+            }
+        }
+        if let Some(ref custom) = self.custom {
+            if custom.iter().any(|(k, v)| metadata.get(k) != Some(v)) {
+                return false;
+            }
+        }
+        self.matches_language(metadata)
+    }
+
+    /// Check the language filter; chunks indexed before language detection
+    /// have no `language` and are excluded while it is set
+    pub fn matches_language(&self, metadata: &HashMap<String, String>) -> bool {
+        match self.language.as_deref() {
+            Some(wanted) => metadata
+                .get("language")
+                .is_some_and(|l| crate::processing::language::language_matches(l, wanted)),
+            None => true,
+        }
+    }
+
+    /// Whether any citation-level filter (author, year range) is set
+    pub fn has_citation_filter(&self) -> bool {
+        self.author.is_some() || self.year_from.is_some() || self.year_to.is_some()
+    }
+
+    /// Check the author and year-range filters against a citation. A year
+    /// bound excludes citations without a recognizable year.
+    pub fn matches_citation(&self, citation: &Citation) -> bool {
+        if let Some(ref wanted) = self.author {
+            let wanted = wanted.trim().to_lowercase();
+            if !citation.authors.iter().any(|a| a.to_lowercase().contains(&wanted)) {
+                return false;
+            }
+        }
+        if self.year_from.is_some() || self.year_to.is_some() {
+            let Some(year) = citation_year(&citation.year) else {
+                return false;
+            };
+            if self.year_from.is_some_and(|from| year < from)
+                || self.year_to.is_some_and(|to| year > to)
+            {
+                return false;
+            }
+        }
+        true
+    }
+
     pub fn to_lance_predicate(&self) -> Option<String> {
         let mut predicates = Vec::new();
 
@@ -172,3 +290,77 @@ pub struct ChunkRecord {
     pub citation_json: String,
     pub created_at: i64,
 }
+
+/// First four-digit run in a citation year ("2021", "2021-05-01", "c. 1999")
+fn citation_year(year: &str) -> Option<i32> {
+    year.as_bytes()
+        .windows(4)
+        .find(|w| w.iter().all(u8::is_ascii_digit))
+        .and_then(|w| std::str::from_utf8(w).ok()?.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paper(title: &str, authors: &[&str], year: &str) -> Citation {
+        Citation {
+            title: title.to_string(),
+            authors: authors.iter().map(|a| a.to_string()).collect(),
+            year: year.to_string(),
+            ..Citation::default()
+        }
+    }
+
+    fn titles(filter: &MetadataFilter, corpus: &[Citation]) -> Vec<String> {
+        corpus
+            .iter()
+            .filter(|c| filter.matches_citation(c))
+            .map(|c| c.title.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_citation_filters_narrow_mixed_corpus() {
+        let corpus = vec![
+            paper("attention", &["Ashish Vaswani", "Noam Shazeer"], "2017"),
+            paper("switch", &["William Fedus", "Noam Shazeer"], "2021-01"),
+            paper("palm", &["Aakanksha Chowdhery", "Noam Shazeer"], "2022"),
+            paper("bert", &["Jacob Devlin"], "2018"),
+            paper("undated", &["Noam Shazeer"], "n.d."),
+        ];
+
+        let by_author = MetadataFilter { author: Some("shazeer".to_string()), ..Default::default() };
+        assert_eq!(titles(&by_author, &corpus), ["attention", "switch", "palm", "undated"]);
+
+        let after_2020 = MetadataFilter {
+            author: Some("Noam Shazeer".to_string()),
+            year_from: Some(2020),
+            ..Default::default()
+        };
+        assert_eq!(titles(&after_2020, &corpus), ["switch", "palm"]);
+
+        let window = MetadataFilter { year_from: Some(2017), year_to: Some(2018), ..Default::default() };
+        assert_eq!(titles(&window, &corpus), ["attention", "bert"]);
+
+        assert_eq!(titles(&MetadataFilter::default(), &corpus).len(), corpus.len());
+    }
+
+    #[test]
+    fn test_filter_without_citation_fields_still_deserializes() {
+        let filter: MetadataFilter = serde_json::from_str(r#"{"space_id": "s1"}"#).unwrap();
+        assert!(!filter.has_citation_filter());
+        assert_eq!(filter.space_id.as_deref(), Some("s1"));
+    }
+
+    #[test]
+    fn test_language_filter_matches_detected_language() {
+        let filter = MetadataFilter { language: Some("cmn".to_string()), ..Default::default() };
+        let chinese = HashMap::from([("language".to_string(), "cmn".to_string())]);
+        let english = HashMap::from([("language".to_string(), "eng".to_string())]);
+        assert!(filter.matches_metadata(&chinese));
+        assert!(!filter.matches_language(&english));
+        assert!(!filter.matches_language(&HashMap::new()));
+        assert!(MetadataFilter::default().matches_language(&HashMap::new()));
+    }
+}